@@ -3,6 +3,7 @@ use songbird::{
     constants::*,
     driver::{
         bench_internals::mixer::{mix_logic, state::DecodeState},
+        DownmixMode,
         MixMode,
     },
     input::{codecs::*, Input, LiveInput, Parsed},
@@ -44,7 +45,8 @@ pub fn mix_one_frame(c: &mut Criterion) {
                             &mut resample_scratch,
                             input,
                             local_input,
-                            black_box(1.0),
+                            black_box([1.0; 2]),
+                            DownmixMode::default(),
                             None,
                         ));
                     },
@@ -69,7 +71,8 @@ pub fn mix_one_frame(c: &mut Criterion) {
                             &mut resample_scratch,
                             input,
                             local_input,
-                            black_box(1.0),
+                            black_box([1.0; 2]),
+                            DownmixMode::default(),
                             None,
                         ));
                     },
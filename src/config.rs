@@ -1,13 +1,33 @@
 #[cfg(all(feature = "driver", feature = "receive"))]
-use crate::driver::{Channels, DecodeMode, SampleRate};
+use crate::driver::{
+    Channels,
+    DecodeMode,
+    DecryptFailurePolicy,
+    Latency,
+    ReceiveConsent,
+    Recorder,
+    SampleFormat,
+    SampleLayout,
+    SampleRate,
+    Transcriber,
+};
+#[cfg(all(feature = "driver", feature = "receive"))]
+use crate::model::id::UserId;
 #[cfg(feature = "driver")]
 use crate::{
     driver::{
         retry::Retry,
         tasks::disposal::DisposalThread,
+        BandwidthCap,
         CryptoMode,
+        DownmixMode,
+        Dynamics,
         MixMode,
+        OverloadPolicy,
         Scheduler,
+        SilenceDetection,
+        Spawner,
+        VirtualClock,
         DEFAULT_SCHEDULER,
     },
     input::codecs::*,
@@ -20,11 +40,15 @@ use crate::driver::SchedulerConfig;
 
 #[cfg(feature = "driver")]
 use symphonia::core::{codecs::CodecRegistry, probe::Probe};
+#[cfg(feature = "driver")]
+use tracing::{info_span, Span};
 
 use derivative::Derivative;
-#[cfg(feature = "receive")]
-use std::num::NonZeroUsize;
+#[cfg(feature = "driver")]
+use std::net::IpAddr;
 use std::time::Duration;
+#[cfg(feature = "receive")]
+use std::{collections::HashMap, num::NonZeroUsize, sync::Arc};
 
 /// Configuration for drivers and calls.
 #[derive(Clone, Derivative)]
@@ -43,6 +67,23 @@ pub struct Config {
     /// [`CryptoMode::Aes256Gcm`]: CryptoMode::Aes256Gcm
     pub crypto_mode: CryptoMode,
 
+    #[cfg(feature = "driver")]
+    /// Additional encryption schemes to select, in order of preference, if the voice
+    /// server does not offer [`crypto_mode`].
+    ///
+    /// If none of these are offered either, the best scheme supported by both songbird
+    /// and the server is chosen. This allows newly enabled schemes to be adopted (or
+    /// avoided) without failing connections to servers which do not yet offer them.
+    ///
+    /// Defaults to an empty list.
+    ///
+    /// Changes to this field will not immediately apply if the
+    /// driver is actively connected, but will apply to subsequent
+    /// sessions.
+    ///
+    /// [`crypto_mode`]: Self::crypto_mode
+    pub crypto_preference: Vec<CryptoMode>,
+
     #[cfg(all(feature = "driver", feature = "receive"))]
     /// Configures whether decoding and decryption occur for all received packets.
     ///
@@ -73,6 +114,26 @@ pub struct Config {
     /// Defaults to [`SampleRate::Hz48000`].
     pub decode_sample_rate: SampleRate,
 
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    /// Configures the sample format for output audio when using [`DecodeMode::Decode`].
+    ///
+    /// Any choice other than [`SampleFormat::I16`] with [`SampleLayout::Interleaved`]
+    /// delivers audio via [`VoiceData::samples`], rather than [`VoiceData::decoded_voice`].
+    ///
+    /// Defaults to [`SampleFormat::I16`].
+    ///
+    /// [`VoiceData::samples`]: crate::events::context_data::VoiceData::samples
+    /// [`VoiceData::decoded_voice`]: crate::events::context_data::VoiceData::decoded_voice
+    pub decode_sample_format: SampleFormat,
+
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    /// Configures how channels are arranged in output audio when using [`DecodeMode::Decode`].
+    ///
+    /// See [`Self::decode_sample_format`] for how non-default choices are delivered.
+    ///
+    /// Defaults to [`SampleLayout::Interleaved`].
+    pub decode_sample_layout: SampleLayout,
+
     #[cfg(all(feature = "driver", feature = "receive"))]
     /// Configures the amount of time after a user/SSRC is inactive before their decoder state
     /// should be removed.
@@ -86,20 +147,145 @@ pub struct Config {
     /// A playout buffer allows Songbird to smooth out jitter in audio packet arrivals,
     /// as well as to correct for reordering of packets by the network.
     ///
-    /// This does not affect the arrival of raw packet events.
+    /// This does not affect the arrival of raw packet events. Changes made via
+    /// [`Driver::set_config`] apply to existing users' buffers, which will rebuffer
+    /// (when growing) or skip their oldest held audio (when shrinking).
+    ///
+    /// Defaults to 5 packets (100ms). See [`Latency`] for presets.
     ///
-    /// Defaults to 5 packets (100ms).
+    /// [`Driver::set_config`]: crate::driver::Driver::set_config
     pub playout_buffer_length: NonZeroUsize,
 
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    /// Per-SSRC overrides of [`Self::playout_buffer_length`], for users known to
+    /// suffer from high network jitter.
+    ///
+    /// Defaults to no overrides.
+    pub playout_buffer_overrides: HashMap<u32, NonZeroUsize>,
+
     #[cfg(all(feature = "driver", feature = "receive"))]
     /// Configures the initial amount of extra space allocated to handle packet bursts.
     ///
     /// Each SSRC's receive buffer will start at capacity `playout_buffer_length +
     /// playout_spike_length`, up to a maximum 64 packets.
     ///
-    /// Defaults to 3 packets (thus capacity defaults to 8).
+    /// Defaults to 3 packets (thus capacity defaults to 8). See [`Latency`] for presets.
     pub playout_spike_length: usize,
 
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    /// Configures how many packets after their playout slot a missing packet may
+    /// still arrive, and be decoded as a correction.
+    ///
+    /// Lost packets are replaced by concealment audio when their slot is played out.
+    /// With [`DecodeMode::Decode`], any missing packet which arrives within this window
+    /// is decoded and reported in [`VoiceTick::corrections`], so that consumers which
+    /// can revise recent audio (e.g., transcription) may replace the concealment.
+    /// Corrections are decoded separately from each user's main audio stream, and
+    /// do not alter any audio already played out.
+    ///
+    /// Defaults to `0`, discarding all late packets.
+    ///
+    /// [`VoiceTick::corrections`]: crate::events::context_data::VoiceTick::corrections
+    pub late_packet_grace: usize,
+
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    /// Configures the maximum number of UDP packets read from the socket on each
+    /// wakeup of the receive task, reducing wakeups and syscalls in calls with
+    /// many speakers.
+    ///
+    /// On Linux, each batch is read using a single `recvmmsg` call. Values are
+    /// capped at 64 packets.
+    ///
+    /// Defaults to 8 packets.
+    pub receive_batch_size: NonZeroUsize,
+
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    /// Configures how the driver reacts to received packets which fail decryption.
+    ///
+    /// Defaults to [`DecryptFailurePolicy::Drop`].
+    pub decrypt_failure_policy: DecryptFailurePolicy,
+
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    /// Configures the number of consecutive packets from one SSRC which may fail
+    /// to decode before that SSRC's Opus decoder is reset.
+    ///
+    /// Every failure fires a [`CoreEvent::DecodeError`], whether or not this is set.
+    ///
+    /// Defaults to `None`, never resetting decoders.
+    ///
+    /// [`CoreEvent::DecodeError`]: crate::events::CoreEvent::DecodeError
+    pub decode_error_reset: Option<NonZeroUsize>,
+
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    #[derivative(Debug = "ignore")]
+    /// Speech-to-text backend fed with segments of each user's received audio.
+    ///
+    /// This requires [`DecodeMode::Decode`] to be set.
+    ///
+    /// Defaults to `None`.
+    pub transcriber: Option<Arc<dyn Transcriber>>,
+
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    #[derivative(Debug = "ignore")]
+    /// Destination for recordings of each user's received Opus packets, written
+    /// without decoding.
+    ///
+    /// This requires [`DecodeMode::Decrypt`] or [`DecodeMode::Decode`] to be set.
+    ///
+    /// Defaults to `None`.
+    pub recorder: Option<Arc<dyn Recorder>>,
+
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    #[derivative(Debug = "ignore")]
+    /// Decides whether each user's received audio may be decrypted and decoded.
+    ///
+    /// Entries in [`receive_consent_overrides`] take precedence over this hook.
+    ///
+    /// Defaults to `None`, allowing all received audio.
+    ///
+    /// [`receive_consent_overrides`]: Self::receive_consent_overrides
+    pub receive_consent: Option<Arc<dyn ReceiveConsent>>,
+
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    /// Per-user decisions on whether received audio may be decrypted and decoded,
+    /// overriding [`receive_consent`].
+    ///
+    /// These are typically changed at runtime using [`Driver::set_receive_consent`].
+    ///
+    /// Defaults to no overrides.
+    ///
+    /// [`receive_consent`]: Self::receive_consent
+    /// [`Driver::set_receive_consent`]: crate::driver::Driver::set_receive_consent
+    pub receive_consent_overrides: HashMap<UserId, bool>,
+
+    #[cfg(all(feature = "driver", feature = "receive"))]
+    /// Configures how much recently received audio to keep for each user, for
+    /// later retrieval with [`Driver::dump_last`].
+    ///
+    /// Each user's buffer holds decoded audio (with silence filling any gaps in
+    /// their speech), so this requires [`DecodeMode::Decode`] to be set.
+    ///
+    /// Defaults to `None`, which keeps no audio.
+    ///
+    /// [`Driver::dump_last`]: crate::driver::Driver::dump_last
+    pub listen_back: Option<Duration>,
+
+    #[cfg(feature = "denoise")]
+    /// Configures whether RNNoise-based noise suppression is applied to each user's
+    /// decoded audio.
+    ///
+    /// Denoised audio is delivered in [`VoiceTick`] events, and passed to any
+    /// [`transcriber`] or [listen-back buffer]. This requires [`DecodeMode::Decode`]
+    /// and a [`decode_sample_rate`] of [`SampleRate::Hz48000`]; otherwise it has no effect.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`VoiceTick`]: crate::events::CoreEvent::VoiceTick
+    /// [`transcriber`]: Self::transcriber
+    /// [listen-back buffer]: Self::listen_back
+    /// [`decode_sample_rate`]: Self::decode_sample_rate
+    pub denoise: bool,
+
     #[cfg(feature = "gateway")]
     /// Configures the amount of time to wait for Discord to reply with connection information
     /// if [`Call::join`]/[`join_gateway`] are used.
@@ -114,15 +300,46 @@ pub struct Config {
     /// [`join_gateway`]: crate::Call::join_gateway
     pub gateway_timeout: Option<Duration>,
 
+    #[cfg(feature = "gateway")]
+    /// Configures whether a [`Call`] re-requests its target channel from Discord's gateway
+    /// when a join is interrupted by a conflicting voice state.
+    ///
+    /// Conflicts occur when, before a join completes, Discord reports that the bot
+    /// was moved to another channel (e.g., by an admin), disconnected, or claimed by
+    /// another session using the same bot account. By default these fail the join with
+    /// a matching [`JoinError`]. If enabled, the join is instead re-requested once before
+    /// failing, reclaiming the bot's voice state from any other session.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`Call`]: crate::Call
+    /// [`JoinError`]: crate::error::JoinError
+    pub rejoin_on_conflict: bool,
+
     #[cfg(feature = "driver")]
     /// Configures whether the driver will mix and output stereo or mono Opus data
     /// over a voice channel.
     ///
+    /// [`Mono`] output roughly halves bandwidth for bots whose audio gains nothing
+    /// from stereo, such as TTS or announcement bots. This may be changed on a live
+    /// driver using [`Driver::set_config`].
+    ///
     /// Defaults to [`Stereo`].
     ///
+    /// [`Mono`]: MixMode::Mono
     /// [`Stereo`]: MixMode::Stereo
+    /// [`Driver::set_config`]: crate::driver::Driver::set_config
     pub mix_mode: MixMode,
 
+    #[cfg(feature = "driver")]
+    /// Configures how sources with more than two channels (e.g., 5.1 audio) are
+    /// downmixed into the driver's [`mix_mode`].
+    ///
+    /// Defaults to [`DownmixMode::Itu`].
+    ///
+    /// [`mix_mode`]: Self::mix_mode
+    pub downmix: DownmixMode,
+
     #[cfg(feature = "driver")]
     /// Number of concurrently active tracks to allocate memory for.
     ///
@@ -146,6 +363,20 @@ pub struct Config {
     /// [`Driver`]: crate::driver::Driver
     pub driver_retry: Retry,
 
+    #[cfg(feature = "driver")]
+    /// Retry logic used when a lazy [`Compose`] input fails to create its audio stream.
+    ///
+    /// Failures caused by [`AudioStreamError::Unsupported`] are never retried, and
+    /// the wait before each retry is at least the time requested by
+    /// [`AudioStreamError::RetryIn`].
+    ///
+    /// Defaults to `None`, where lazy inputs are created only once.
+    ///
+    /// [`Compose`]: crate::input::Compose
+    /// [`AudioStreamError::Unsupported`]: crate::input::AudioStreamError::Unsupported
+    /// [`AudioStreamError::RetryIn`]: crate::input::AudioStreamError::RetryIn
+    pub input_retry: Option<Retry>,
+
     #[cfg(feature = "driver")]
     /// Configures whether or not each mixed audio packet is [soft-clipped] into the
     /// [-1, 1] audio range.
@@ -161,6 +392,13 @@ pub struct Config {
     /// [soft-clipped]: https://opus-codec.org/docs/opus_api-1.3.1/group__opus__decoder.html#gaff99598b352e8939dded08d96e125e0b
     pub use_softclip: bool,
 
+    #[cfg(feature = "driver")]
+    /// Configures an optional compressor and limiter applied to mixed audio,
+    /// before soft-clipping and encoding.
+    ///
+    /// Defaults to `None`.
+    pub dynamics: Option<Dynamics>,
+
     #[cfg(feature = "driver")]
     /// Configures the maximum amount of time to wait for an attempted voice
     /// connection to Discord.
@@ -168,6 +406,142 @@ pub struct Config {
     /// Defaults to 10 seconds. If set to `None`, connections will never time out.
     pub driver_timeout: Option<Duration>,
 
+    #[cfg(feature = "driver")]
+    /// Configures how long a connected driver may go without any playing tracks
+    /// before automatically leaving its call.
+    ///
+    /// Paused and preparing tracks do not count as playing. When this expires, a
+    /// [`DriverDisconnect`] event is fired with [`DisconnectReason::Idle`], and any
+    /// owning [`Call`] leaves its voice channel.
+    ///
+    /// Defaults to `None`, which never leaves.
+    ///
+    /// [`DriverDisconnect`]: crate::events::CoreEvent::DriverDisconnect
+    /// [`DisconnectReason::Idle`]: crate::events::context_data::DisconnectReason::Idle
+    /// [`Call`]: crate::Call
+    pub idle_timeout: Option<Duration>,
+
+    #[cfg(feature = "driver")]
+    /// Configures how long a connected driver may be the only user in its channel
+    /// before automatically leaving its call.
+    ///
+    /// Other users are tracked from voice gateway messages, and from voice state
+    /// updates passed to [`Driver::set_member_present`] (or automatically, when
+    /// using [`Songbird`]). When this expires, a [`DriverDisconnect`] event is fired
+    /// with [`DisconnectReason::Alone`], and any owning [`Call`] leaves its voice
+    /// channel.
+    ///
+    /// Defaults to `None`, which never leaves.
+    ///
+    /// [`Driver::set_member_present`]: crate::driver::Driver::set_member_present
+    /// [`Songbird`]: crate::Songbird
+    /// [`DriverDisconnect`]: crate::events::CoreEvent::DriverDisconnect
+    /// [`DisconnectReason::Alone`]: crate::events::context_data::DisconnectReason::Alone
+    /// [`Call`]: crate::Call
+    pub alone_timeout: Option<Duration>,
+
+    #[cfg(feature = "driver")]
+    /// Configures how much audio must be sent between each [`Transmit`] event.
+    ///
+    /// Only time spent actually transmitting (both audio and trailing silence frames)
+    /// counts towards this interval, which is rounded up to a whole number of 20ms frames.
+    ///
+    /// Defaults to `None`, which disables these events.
+    ///
+    /// [`Transmit`]: crate::events::CoreEvent::Transmit
+    pub transmit_event_interval: Option<Duration>,
+
+    #[cfg(feature = "driver")]
+    /// Stops transmitting during sustained silence in mixed audio, resuming when
+    /// audible output returns.
+    ///
+    /// See [`SilenceDetection`] for details.
+    ///
+    /// Defaults to `None`, which transmits all mixed audio.
+    pub silence_detection: Option<SilenceDetection>,
+
+    #[cfg(feature = "driver")]
+    /// Configures how the mixer sheds work when it cannot mix every track within
+    /// its per-frame time budget.
+    ///
+    /// See [`OverloadPolicy`] for details.
+    ///
+    /// Defaults to `None`, which always mixes every track.
+    pub overload_policy: Option<OverloadPolicy>,
+
+    #[cfg(feature = "driver")]
+    /// Limits the bitrate of audio sent by this driver, alone or as part of a
+    /// budget shared with other calls.
+    ///
+    /// See [`BandwidthCap`] for details.
+    ///
+    /// Defaults to `None`, which sends audio at the requested bitrate.
+    pub bandwidth_cap: Option<BandwidthCap>,
+
+    #[cfg(feature = "driver")]
+    /// Configures the `delay` field sent in this driver's speaking state updates.
+    ///
+    /// Discord does not document this value, and official clients send `0`, but
+    /// some interop tooling reads it as a synchronisation hint. The values sent
+    /// by other users are included in [`CoreEvent::SpeakingStateUpdate`] events.
+    ///
+    /// Defaults to `0`.
+    ///
+    /// [`CoreEvent::SpeakingStateUpdate`]: crate::events::CoreEvent::SpeakingStateUpdate
+    pub speaking_delay: u32,
+
+    #[cfg(feature = "driver")]
+    /// Configures the local addresses which the voice UDP socket may bind to, in
+    /// order of preference.
+    ///
+    /// New connections bind to the first address able to complete IP discovery with
+    /// the voice server. If [`Self::udp_failover_threshold`] sends in a row then fail
+    /// on an established connection, the driver rebinds to the next address (wrapping
+    /// around) and re-runs IP discovery, without leaving the call. This allows, e.g.,
+    /// falling back to a backup uplink if the primary interface goes down.
+    ///
+    /// Defaults to empty, which binds to the unspecified IPv4 address and never fails over.
+    pub bind_addresses: Vec<IpAddr>,
+
+    #[cfg(feature = "driver")]
+    /// Configures how many consecutive voice or keepalive packets must fail to send
+    /// before the driver fails over to its next [bind address].
+    ///
+    /// This has no effect unless at least two bind addresses are given.
+    ///
+    /// Defaults to `50` (one second of audio).
+    ///
+    /// [bind address]: Self::bind_addresses
+    pub udp_failover_threshold: u32,
+
+    #[cfg(feature = "driver")]
+    /// Configures how many times a UDP send is repeated within the same tick if it
+    /// fails with a transient error, such as `EPERM` from a full conntrack table or
+    /// `ENETUNREACH` during a route change.
+    ///
+    /// Each retry is made after a short wait (250µs), which delays the sends of
+    /// any other calls handled by the same thread, so this should be kept small.
+    /// Other errors are never retried. All failures are counted in
+    /// [`Driver::udp_send_stats`].
+    ///
+    /// Defaults to `1`.
+    ///
+    /// [`Driver::udp_send_stats`]: crate::driver::Driver::udp_send_stats
+    pub udp_send_retries: u8,
+
+    #[cfg(feature = "driver")]
+    /// Configures how many consecutive voice or keepalive packets must fail to send,
+    /// after any retries, before the driver declares its connection dead and
+    /// reconnects.
+    ///
+    /// If [bind addresses] are configured for failover, this should be set higher
+    /// than [`Self::udp_failover_threshold`] so that failover is tried first.
+    ///
+    /// Defaults to `None`, where failed sends never end the connection.
+    ///
+    /// [bind addresses]: Self::bind_addresses
+    pub udp_dead_threshold: Option<u32>,
+
     #[cfg(feature = "driver")]
     #[derivative(Debug = "ignore")]
     /// Registry of the inner codecs supported by the driver, adding audiopus-based
@@ -208,6 +582,40 @@ pub struct Config {
     /// [`Driver`]: crate::Driver
     pub scheduler: Option<Scheduler>,
 
+    #[cfg(feature = "driver")]
+    /// Where a driver's async tasks (its core, event, websocket, and UDP receive
+    /// tasks) are spawned.
+    ///
+    /// Drivers may be given a handle to a dedicated runtime, isolating voice
+    /// from any heavy workloads on the runtime which created them. Changes only
+    /// apply to tasks spawned afterwards: running tasks are not moved.
+    ///
+    /// Defaults to [`Spawner::current`].
+    pub spawner: Spawner,
+
+    #[cfg(feature = "driver")]
+    /// Replaces the driver's real-time 20ms mixing clock with a manually advanced
+    /// [`VirtualClock`], for deterministic testing.
+    ///
+    /// This should not be used for live voice connections.
+    ///
+    /// Defaults to `None`.
+    pub virtual_clock: Option<VirtualClock>,
+
+    #[cfg(feature = "driver")]
+    /// Identifier attached (as the `id` field of a `call` span) to all task spans
+    /// and log messages emitted by a driver, so that the output of many concurrent
+    /// calls can be filtered per call.
+    ///
+    /// Calls created by [`Songbird`] have this set to their guild and shard IDs,
+    /// unless another identifier is given. This field is read when a driver is
+    /// created, and changes will not apply to existing drivers.
+    ///
+    /// Defaults to `None`.
+    ///
+    /// [`Songbird`]: crate::Songbird
+    pub call_identifier: Option<String>,
+
     // Test only attributes
     #[cfg(feature = "driver")]
     #[cfg(test)]
@@ -224,6 +632,8 @@ impl Default for Config {
         Self {
             #[cfg(feature = "driver")]
             crypto_mode: CryptoMode::Aes256Gcm,
+            #[cfg(feature = "driver")]
+            crypto_preference: Vec::new(),
             #[cfg(all(feature = "driver", feature = "receive"))]
             decode_mode: DecodeMode::Decrypt,
             #[cfg(all(feature = "driver", feature = "receive"))]
@@ -231,24 +641,80 @@ impl Default for Config {
             #[cfg(all(feature = "driver", feature = "receive"))]
             decode_sample_rate: SampleRate::Hz48000,
             #[cfg(all(feature = "driver", feature = "receive"))]
+            decode_sample_format: SampleFormat::I16,
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            decode_sample_layout: SampleLayout::Interleaved,
+            #[cfg(all(feature = "driver", feature = "receive"))]
             decode_state_timeout: Duration::from_secs(60),
             #[cfg(all(feature = "driver", feature = "receive"))]
-            playout_buffer_length: NonZeroUsize::new(5).unwrap(),
+            playout_buffer_length: Latency::Balanced.playout_buffer_length(),
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            playout_buffer_overrides: HashMap::new(),
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            playout_spike_length: Latency::Balanced.playout_spike_length(),
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            late_packet_grace: 0,
             #[cfg(all(feature = "driver", feature = "receive"))]
-            playout_spike_length: 3,
+            receive_batch_size: NonZeroUsize::new(8).unwrap(),
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            decrypt_failure_policy: DecryptFailurePolicy::Drop,
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            decode_error_reset: None,
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            transcriber: None,
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            recorder: None,
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            receive_consent: None,
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            receive_consent_overrides: HashMap::new(),
+            #[cfg(all(feature = "driver", feature = "receive"))]
+            listen_back: None,
+            #[cfg(feature = "denoise")]
+            denoise: false,
             #[cfg(feature = "gateway")]
             gateway_timeout: Some(Duration::from_secs(10)),
+            #[cfg(feature = "gateway")]
+            rejoin_on_conflict: false,
             #[cfg(feature = "driver")]
             mix_mode: MixMode::Stereo,
             #[cfg(feature = "driver")]
+            downmix: DownmixMode::Itu,
+            #[cfg(feature = "driver")]
             preallocated_tracks: 1,
             #[cfg(feature = "driver")]
             use_softclip: true,
             #[cfg(feature = "driver")]
+            dynamics: None,
+            #[cfg(feature = "driver")]
             driver_retry: Retry::default(),
             #[cfg(feature = "driver")]
+            input_retry: None,
+            #[cfg(feature = "driver")]
             driver_timeout: Some(Duration::from_secs(10)),
             #[cfg(feature = "driver")]
+            idle_timeout: None,
+            #[cfg(feature = "driver")]
+            alone_timeout: None,
+            #[cfg(feature = "driver")]
+            transmit_event_interval: None,
+            #[cfg(feature = "driver")]
+            silence_detection: None,
+            #[cfg(feature = "driver")]
+            overload_policy: None,
+            #[cfg(feature = "driver")]
+            bandwidth_cap: None,
+            #[cfg(feature = "driver")]
+            speaking_delay: 0,
+            #[cfg(feature = "driver")]
+            bind_addresses: Vec::new(),
+            #[cfg(feature = "driver")]
+            udp_failover_threshold: 50,
+            #[cfg(feature = "driver")]
+            udp_send_retries: 1,
+            #[cfg(feature = "driver")]
+            udp_dead_threshold: None,
+            #[cfg(feature = "driver")]
             codec_registry: &CODEC_REGISTRY,
             #[cfg(feature = "driver")]
             format_registry: &PROBE,
@@ -257,6 +723,12 @@ impl Default for Config {
             #[cfg(feature = "driver")]
             scheduler: None,
             #[cfg(feature = "driver")]
+            spawner: Spawner::default(),
+            #[cfg(feature = "driver")]
+            virtual_clock: None,
+            #[cfg(feature = "driver")]
+            call_identifier: None,
+            #[cfg(feature = "driver")]
             #[cfg(test)]
             tick_style: TickStyle::Timed,
             #[cfg(feature = "driver")]
@@ -275,6 +747,20 @@ impl Config {
         self
     }
 
+    /// Sets this `Config`'s fallback cryptographic tagging schemes, in order of preference.
+    #[must_use]
+    pub fn crypto_preference(mut self, crypto_preference: Vec<CryptoMode>) -> Self {
+        self.crypto_preference = crypto_preference;
+        self
+    }
+
+    /// Returns all preferred cryptographic tagging schemes, most preferred first.
+    pub(crate) fn preferred_crypto_modes(&self) -> Vec<CryptoMode> {
+        std::iter::once(self.crypto_mode)
+            .chain(self.crypto_preference.iter().copied())
+            .collect()
+    }
+
     #[cfg(feature = "receive")]
     /// Sets this `Config`'s received packet decryption/decoding behaviour.
     #[must_use]
@@ -299,6 +785,22 @@ impl Config {
         self
     }
 
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s sample format for output audio when using [`DecodeMode::Decode`]
+    #[must_use]
+    pub fn decode_sample_format(mut self, decode_sample_format: SampleFormat) -> Self {
+        self.decode_sample_format = decode_sample_format;
+        self
+    }
+
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s channel arrangement for output audio when using [`DecodeMode::Decode`]
+    #[must_use]
+    pub fn decode_sample_layout(mut self, decode_sample_layout: SampleLayout) -> Self {
+        self.decode_sample_layout = decode_sample_layout;
+        self
+    }
+
     #[cfg(feature = "receive")]
     /// Sets this `Config`'s received packet decoder cleanup timer.
     #[must_use]
@@ -307,6 +809,18 @@ impl Config {
         self
     }
 
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s receive buffering to match a [`Latency`] preset.
+    ///
+    /// This overwrites [`Self::playout_buffer_length`] and [`Self::playout_spike_length`],
+    /// so any individual adjustments should be made after calling this.
+    #[must_use]
+    pub fn latency(mut self, latency: Latency) -> Self {
+        self.playout_buffer_length = latency.playout_buffer_length();
+        self.playout_spike_length = latency.playout_spike_length();
+        self
+    }
+
     #[cfg(feature = "receive")]
     /// Sets this `Config`'s playout buffer length, in packets.
     #[must_use]
@@ -315,6 +829,28 @@ impl Config {
         self
     }
 
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s per-SSRC playout buffer lengths, in packets.
+    #[must_use]
+    pub fn playout_buffer_overrides(
+        mut self,
+        playout_buffer_overrides: HashMap<u32, NonZeroUsize>,
+    ) -> Self {
+        self.playout_buffer_overrides = playout_buffer_overrides;
+        self
+    }
+
+    #[cfg(feature = "receive")]
+    /// Returns the playout buffer length used for a given SSRC, accounting for
+    /// any entry in [`Self::playout_buffer_overrides`].
+    #[must_use]
+    pub fn playout_buffer_length_for(&self, ssrc: u32) -> NonZeroUsize {
+        self.playout_buffer_overrides
+            .get(&ssrc)
+            .copied()
+            .unwrap_or(self.playout_buffer_length)
+    }
+
     #[cfg(feature = "receive")]
     /// Sets this `Config`'s additional pre-allocated space to handle bursty audio packets.
     #[must_use]
@@ -323,6 +859,105 @@ impl Config {
         self
     }
 
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s window for decoding late packets as corrections.
+    #[must_use]
+    pub fn late_packet_grace(mut self, late_packet_grace: usize) -> Self {
+        self.late_packet_grace = late_packet_grace;
+        self
+    }
+
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s maximum number of UDP packets to read per wakeup.
+    #[must_use]
+    pub fn receive_batch_size(mut self, receive_batch_size: NonZeroUsize) -> Self {
+        self.receive_batch_size = receive_batch_size;
+        self
+    }
+
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s behaviour on receiving packets which fail decryption.
+    #[must_use]
+    pub fn decrypt_failure_policy(mut self, decrypt_failure_policy: DecryptFailurePolicy) -> Self {
+        self.decrypt_failure_policy = decrypt_failure_policy;
+        self
+    }
+
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s number of consecutive decode failures after which
+    /// an SSRC's decoder is reset.
+    #[must_use]
+    pub fn decode_error_reset(mut self, decode_error_reset: Option<NonZeroUsize>) -> Self {
+        self.decode_error_reset = decode_error_reset;
+        self
+    }
+
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s speech-to-text backend.
+    #[must_use]
+    pub fn transcriber(mut self, transcriber: Option<Arc<dyn Transcriber>>) -> Self {
+        self.transcriber = transcriber;
+        self
+    }
+
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s destination for recordings of each user's received audio.
+    #[must_use]
+    pub fn recorder(mut self, recorder: Option<Arc<dyn Recorder>>) -> Self {
+        self.recorder = recorder;
+        self
+    }
+
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s hook deciding whether each user's audio may be received.
+    #[must_use]
+    pub fn receive_consent(mut self, receive_consent: Option<Arc<dyn ReceiveConsent>>) -> Self {
+        self.receive_consent = receive_consent;
+        self
+    }
+
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s per-user overrides of the receive consent hook.
+    #[must_use]
+    pub fn receive_consent_overrides(
+        mut self,
+        receive_consent_overrides: HashMap<UserId, bool>,
+    ) -> Self {
+        self.receive_consent_overrides = receive_consent_overrides;
+        self
+    }
+
+    #[cfg(feature = "receive")]
+    /// Returns whether audio from `ssrc` may be decrypted and decoded, accounting
+    /// for [`Self::receive_consent_overrides`] and [`Self::receive_consent`].
+    #[must_use]
+    pub fn receive_consent_for(&self, ssrc: u32, user_id: Option<UserId>) -> bool {
+        user_id
+            .and_then(|id| self.receive_consent_overrides.get(&id).copied())
+            .or_else(|| {
+                self.receive_consent
+                    .as_ref()
+                    .map(|hook| hook.allow(ssrc, user_id))
+            })
+            .unwrap_or(true)
+    }
+
+    #[cfg(feature = "receive")]
+    /// Sets this `Config`'s length of retained audio for each user.
+    #[must_use]
+    pub fn listen_back(mut self, listen_back: Option<Duration>) -> Self {
+        self.listen_back = listen_back;
+        self
+    }
+
+    #[cfg(feature = "denoise")]
+    /// Sets whether this `Config` suppresses noise in decoded audio.
+    #[must_use]
+    pub fn denoise(mut self, denoise: bool) -> Self {
+        self.denoise = denoise;
+        self
+    }
+
     /// Sets this `Config`'s audio mixing channel count.
     #[must_use]
     pub fn mix_mode(mut self, mix_mode: MixMode) -> Self {
@@ -330,6 +965,13 @@ impl Config {
         self
     }
 
+    /// Sets this `Config`'s downmixing behaviour for multichannel sources.
+    #[must_use]
+    pub fn downmix(mut self, downmix: DownmixMode) -> Self {
+        self.downmix = downmix;
+        self
+    }
+
     /// Sets this `Config`'s number of tracks to preallocate.
     #[must_use]
     pub fn preallocated_tracks(mut self, preallocated_tracks: usize) -> Self {
@@ -344,6 +986,13 @@ impl Config {
         self
     }
 
+    /// Sets this `Config`'s master-bus compressor and limiter.
+    #[must_use]
+    pub fn dynamics(mut self, dynamics: Option<Dynamics>) -> Self {
+        self.dynamics = dynamics;
+        self
+    }
+
     /// Sets this `Config`'s timeout for establishing a voice connection.
     #[must_use]
     pub fn driver_timeout(mut self, driver_timeout: Option<Duration>) -> Self {
@@ -351,6 +1000,85 @@ impl Config {
         self
     }
 
+    /// Sets this `Config`'s time without playing tracks before leaving a call.
+    #[must_use]
+    pub fn idle_timeout(mut self, idle_timeout: Option<Duration>) -> Self {
+        self.idle_timeout = idle_timeout;
+        self
+    }
+
+    /// Sets this `Config`'s time spent alone in a channel before leaving a call.
+    #[must_use]
+    pub fn alone_timeout(mut self, alone_timeout: Option<Duration>) -> Self {
+        self.alone_timeout = alone_timeout;
+        self
+    }
+
+    /// Sets this `Config`'s amount of sent audio between [`Transmit`] events.
+    ///
+    /// [`Transmit`]: crate::events::CoreEvent::Transmit
+    #[must_use]
+    pub fn transmit_event_interval(mut self, transmit_event_interval: Option<Duration>) -> Self {
+        self.transmit_event_interval = transmit_event_interval;
+        self
+    }
+
+    /// Sets this `Config`'s detection of sustained silence in mixed audio.
+    #[must_use]
+    pub fn silence_detection(mut self, silence_detection: Option<SilenceDetection>) -> Self {
+        self.silence_detection = silence_detection;
+        self
+    }
+
+    /// Sets this `Config`'s handling of mixer overload.
+    #[must_use]
+    pub fn overload_policy(mut self, overload_policy: Option<OverloadPolicy>) -> Self {
+        self.overload_policy = overload_policy;
+        self
+    }
+
+    /// Sets this `Config`'s limits on outgoing bitrate.
+    #[must_use]
+    pub fn bandwidth_cap(mut self, bandwidth_cap: Option<BandwidthCap>) -> Self {
+        self.bandwidth_cap = bandwidth_cap;
+        self
+    }
+
+    /// Sets this `Config`'s `delay` value for outgoing speaking state updates.
+    #[must_use]
+    pub fn speaking_delay(mut self, speaking_delay: u32) -> Self {
+        self.speaking_delay = speaking_delay;
+        self
+    }
+
+    /// Sets this `Config`'s preferred local addresses for voice UDP traffic.
+    #[must_use]
+    pub fn bind_addresses(mut self, bind_addresses: Vec<IpAddr>) -> Self {
+        self.bind_addresses = bind_addresses;
+        self
+    }
+
+    /// Sets this `Config`'s number of consecutive failed UDP sends before failing over.
+    #[must_use]
+    pub fn udp_failover_threshold(mut self, udp_failover_threshold: u32) -> Self {
+        self.udp_failover_threshold = udp_failover_threshold;
+        self
+    }
+
+    /// Sets this `Config`'s number of retries for UDP sends failing with transient errors.
+    #[must_use]
+    pub fn udp_send_retries(mut self, udp_send_retries: u8) -> Self {
+        self.udp_send_retries = udp_send_retries;
+        self
+    }
+
+    /// Sets this `Config`'s number of consecutive failed UDP sends before reconnecting.
+    #[must_use]
+    pub fn udp_dead_threshold(mut self, udp_dead_threshold: Option<u32>) -> Self {
+        self.udp_dead_threshold = udp_dead_threshold;
+        self
+    }
+
     /// Sets this `Config`'s voice connection retry configuration.
     #[must_use]
     pub fn driver_retry(mut self, driver_retry: Retry) -> Self {
@@ -358,6 +1086,13 @@ impl Config {
         self
     }
 
+    /// Sets this `Config`'s retry configuration for creating lazy inputs.
+    #[must_use]
+    pub fn input_retry(mut self, input_retry: Option<Retry>) -> Self {
+        self.input_retry = input_retry;
+        self
+    }
+
     /// Sets this `Config`'s symphonia codec registry.
     #[must_use]
     pub fn codec_registry(mut self, codec_registry: &'static CodecRegistry) -> Self {
@@ -386,6 +1121,27 @@ impl Config {
         self
     }
 
+    /// Sets where this `Config`'s drivers spawn their async tasks.
+    #[must_use]
+    pub fn spawner(mut self, spawner: Spawner) -> Self {
+        self.spawner = spawner;
+        self
+    }
+
+    /// Sets this `Config`'s virtual mixing clock, used in place of real time.
+    #[must_use]
+    pub fn virtual_clock(mut self, virtual_clock: Option<VirtualClock>) -> Self {
+        self.virtual_clock = virtual_clock;
+        self
+    }
+
+    /// Sets this `Config`'s identifier used in driver spans and log messages.
+    #[must_use]
+    pub fn call_identifier(mut self, call_identifier: Option<String>) -> Self {
+        self.call_identifier = call_identifier;
+        self
+    }
+
     /// Returns a lightweight reference to the audio scheduler this `Config` will use.
     #[must_use]
     pub fn get_scheduler(&self) -> Scheduler {
@@ -395,6 +1151,18 @@ impl Config {
             .clone()
     }
 
+    /// Creates the span enclosing all tasks of a driver using this `Config`.
+    ///
+    /// This is disabled if no [`call_identifier`] is set.
+    ///
+    /// [`call_identifier`]: Config::call_identifier
+    pub(crate) fn call_span(&self) -> Span {
+        match &self.call_identifier {
+            Some(id) => info_span!("call", id = %id),
+            None => Span::none(),
+        }
+    }
+
     /// Ensures a global disposer has been set, initializing one if not.
     #[must_use]
     pub(crate) fn initialise_disposer(self) -> Self {
@@ -409,6 +1177,8 @@ impl Config {
     pub(crate) fn make_safe(&mut self, previous: &Config, connected: bool) {
         if connected {
             self.crypto_mode = previous.crypto_mode;
+            self.crypto_preference
+                .clone_from(&previous.crypto_preference);
         }
     }
 }
@@ -454,6 +1224,9 @@ impl Config {
         let sc_config = SchedulerConfig {
             strategy: crate::driver::SchedulerMode::MaxPerThread(1.try_into().unwrap()),
             move_expensive_tasks: true,
+            batch_sends: true,
+            offload_encryption: false,
+            pacing: None,
         };
 
         let config = Config::default()
@@ -476,4 +1249,11 @@ impl Config {
         self.gateway_timeout = gateway_timeout;
         self
     }
+
+    /// Sets whether conflicting voice states re-request a join, rather than failing it.
+    #[must_use]
+    pub fn rejoin_on_conflict(mut self, rejoin_on_conflict: bool) -> Self {
+        self.rejoin_on_conflict = rejoin_on_conflict;
+        self
+    }
 }
@@ -33,6 +33,12 @@ pub(crate) const RESAMPLE_OUTPUT_FRAME_SIZE: usize = MONO_FRAME_SIZE / 2;
 /// The maximum number of bad frames to allow in an Opus source before blocking passthrough.
 pub(crate) const OPUS_PASSTHROUGH_STRIKE_LIMIT: u8 = 3;
 
+/// Audio decoded and discarded ahead of the target of an Opus seek, so that the
+/// decoder has converged by the time playback resumes ([RFC 7845, section 4.6]).
+///
+/// [RFC 7845, section 4.6]: https://www.rfc-editor.org/rfc/rfc7845#section-4.6
+pub(crate) const OPUS_SEEK_PREROLL: Duration = Duration::from_millis(80);
+
 /// Number of samples in one complete frame of audio per channel.
 ///
 /// This is equally the number of stereo (joint) samples in an audio frame.
@@ -0,0 +1,359 @@
+use crate::constants::MONO_FRAME_SIZE;
+use flume::{Sender, TrySendError};
+use std::f32::consts::PI;
+use symphonia_core::audio::AudioBuffer;
+use uuid::Uuid;
+
+/// Size of the FFT used for spectrum analysis, as a power of two at least as
+/// large as one audio frame.
+const FFT_SIZE: usize = MONO_FRAME_SIZE.next_power_of_two();
+
+/// The kind of data produced by an [`Analysis`] feed for each 20ms tick.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum AnalysisKind {
+    /// Peak absolute sample values, dividing each tick into this many equal buckets.
+    ///
+    /// This is limited to one bucket per sample (960 per tick).
+    Waveform(usize),
+    /// Peak FFT magnitudes, dividing the spectrum from 0Hz to 24kHz into this many
+    /// equal-width bands.
+    ///
+    /// This is limited to 512 bands. Magnitudes are scaled so that a full-scale
+    /// sine wave measures approximately `1.0`.
+    Spectrum(usize),
+}
+
+impl AnalysisKind {
+    fn len(self) -> usize {
+        match self {
+            Self::Waveform(points) => points.clamp(1, MONO_FRAME_SIZE),
+            Self::Spectrum(bins) => bins.clamp(1, FFT_SIZE / 2),
+        }
+    }
+}
+
+/// Settings for a live feed of waveform or spectrum data, for visualisers.
+///
+/// Feeds are started using [`Driver::analysis_feed`]. Audio is measured after all
+/// playing tracks have been mixed, as a mono downmix, and before any
+/// [`Dynamics`] or soft-clipping are applied.
+///
+/// While a feed is active, Opus passthrough is disabled so that audio is always
+/// available to measure.
+///
+/// [`Driver::analysis_feed`]: crate::driver::Driver::analysis_feed
+/// [`Dynamics`]: crate::driver::Dynamics
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct Analysis {
+    /// The data computed for each tick.
+    pub kind: AnalysisKind,
+    /// Whether to also measure the audio of each playing track.
+    ///
+    /// Defaults to `false`.
+    pub per_track: bool,
+    /// Number of frames which may be queued for a slow receiver before new
+    /// frames are discarded.
+    ///
+    /// Defaults to `50` (one second of audio).
+    pub capacity: usize,
+}
+
+impl Analysis {
+    /// Creates settings for a feed of waveform peaks, with `points` values per tick.
+    #[must_use]
+    pub fn waveform(points: usize) -> Self {
+        Self::new(AnalysisKind::Waveform(points))
+    }
+
+    /// Creates settings for a feed of spectrum magnitudes, with `bins` values per tick.
+    #[must_use]
+    pub fn spectrum(bins: usize) -> Self {
+        Self::new(AnalysisKind::Spectrum(bins))
+    }
+
+    fn new(kind: AnalysisKind) -> Self {
+        Self {
+            kind,
+            per_track: false,
+            capacity: 50,
+        }
+    }
+
+    /// Sets whether each playing track should also be measured.
+    #[must_use]
+    pub fn per_track(mut self, per_track: bool) -> Self {
+        self.per_track = per_track;
+        self
+    }
+
+    /// Sets the number of frames which may be queued for a slow receiver.
+    #[must_use]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+}
+
+/// Waveform or spectrum data measured over one 20ms tick of audio.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct AnalysisFrame {
+    /// Data measured from the mix of all playing tracks.
+    pub mix: Vec<f32>,
+    /// Data measured from each playing track, if [`Analysis::per_track`] is set.
+    pub tracks: Vec<(Uuid, Vec<f32>)>,
+}
+
+/// Mixer-side state for an active analysis feed.
+pub struct AnalysisTap {
+    settings: Analysis,
+    tx: Sender<AnalysisFrame>,
+    /// Mono downmix of the mix before the current track was added.
+    before: Vec<f32>,
+    mono: Vec<f32>,
+    tracks: Vec<(Uuid, Vec<f32>)>,
+    window: Vec<f32>,
+    re: Vec<f32>,
+    im: Vec<f32>,
+}
+
+impl AnalysisTap {
+    pub(crate) fn new(settings: Analysis, tx: Sender<AnalysisFrame>) -> Self {
+        let window = match settings.kind {
+            AnalysisKind::Spectrum(_) => (0..MONO_FRAME_SIZE)
+                .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / MONO_FRAME_SIZE as f32).cos())
+                .collect(),
+            AnalysisKind::Waveform(_) => vec![],
+        };
+
+        Self {
+            settings,
+            tx,
+            before: vec![],
+            mono: vec![],
+            tracks: vec![],
+            window,
+            re: vec![],
+            im: vec![],
+        }
+    }
+
+    pub(crate) fn per_track(&self) -> bool {
+        self.settings.per_track
+    }
+
+    /// Records the mix before a track is added to it.
+    pub(crate) fn before_track(&mut self, mix: &AudioBuffer<f32>) {
+        downmix(mix, &mut self.before);
+    }
+
+    /// Measures the audio added to the mix by one track since [`Self::before_track`].
+    pub(crate) fn after_track(&mut self, track: Uuid, mix: &AudioBuffer<f32>) {
+        downmix(mix, &mut self.mono);
+        for (sample, before) in self.mono.iter_mut().zip(&self.before) {
+            *sample -= before;
+        }
+
+        let data = self.measure();
+        self.tracks.push((track, data));
+    }
+
+    /// Measures the complete mix, and sends this tick's frame.
+    ///
+    /// Returns `false` once the receiver has been dropped.
+    pub(crate) fn finish(&mut self, mix: &AudioBuffer<f32>) -> bool {
+        downmix(mix, &mut self.mono);
+
+        let frame = AnalysisFrame {
+            mix: self.measure(),
+            tracks: std::mem::take(&mut self.tracks),
+        };
+
+        !matches!(self.tx.try_send(frame), Err(TrySendError::Disconnected(_)))
+    }
+
+    fn measure(&mut self) -> Vec<f32> {
+        let len = self.settings.kind.len();
+
+        match self.settings.kind {
+            AnalysisKind::Waveform(_) => (0..len)
+                .map(|i| {
+                    let start = i * MONO_FRAME_SIZE / len;
+                    let end = (i + 1) * MONO_FRAME_SIZE / len;
+                    self.mono[start..end]
+                        .iter()
+                        .fold(0.0f32, |acc, s| acc.max(s.abs()))
+                })
+                .collect(),
+            AnalysisKind::Spectrum(_) => {
+                self.re.clear();
+                self.re
+                    .extend(self.mono.iter().zip(&self.window).map(|(s, w)| s * w));
+                self.re.resize(FFT_SIZE, 0.0);
+                self.im.clear();
+                self.im.resize(FFT_SIZE, 0.0);
+
+                fft(&mut self.re, &mut self.im);
+
+                // A windowed sine of amplitude `a` peaks at `a * sum(window) / 2`.
+                let scale = 2.0 / self.window.iter().sum::<f32>();
+                let half = FFT_SIZE / 2;
+
+                (0..len)
+                    .map(|i| {
+                        let start = i * half / len;
+                        let end = ((i + 1) * half / len).max(start + 1);
+                        (start..end)
+                            .map(|k| self.re[k].hypot(self.im[k]) * scale)
+                            .fold(0.0f32, f32::max)
+                    })
+                    .collect()
+            },
+        }
+    }
+}
+
+/// Averages all channels of one mixed frame into `out`.
+fn downmix(mix: &AudioBuffer<f32>, out: &mut Vec<f32>) {
+    out.clear();
+    out.resize(MONO_FRAME_SIZE, 0.0);
+
+    let planes = mix.planes();
+    let planes = planes.planes();
+    let scale = 1.0 / planes.len().max(1) as f32;
+
+    for plane in planes {
+        for (o, s) in out.iter_mut().zip(plane.iter()) {
+            *o += s * scale;
+        }
+    }
+}
+
+/// In-place iterative radix-2 FFT. Both slices must have the same power-of-two length.
+fn fft(re: &mut [f32], im: &mut [f32]) {
+    let n = re.len();
+
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+
+        if i < j {
+            re.swap(i, j);
+            im.swap(i, j);
+        }
+    }
+
+    let mut len = 2;
+    while len <= n {
+        let (w_im, w_re) = (-2.0 * PI / len as f32).sin_cos();
+
+        for start in (0..n).step_by(len) {
+            let (mut cur_re, mut cur_im) = (1.0f32, 0.0f32);
+
+            for k in 0..len / 2 {
+                let (a, b) = (start + k, start + k + len / 2);
+                let t_re = re[b] * cur_re - im[b] * cur_im;
+                let t_im = re[b] * cur_im + im[b] * cur_re;
+
+                re[b] = re[a] - t_re;
+                im[b] = im[a] - t_im;
+                re[a] += t_re;
+                im[a] += t_im;
+
+                let next_re = cur_re * w_re - cur_im * w_im;
+                cur_im = cur_re * w_im + cur_im * w_re;
+                cur_re = next_re;
+            }
+        }
+
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::SAMPLE_RATE_RAW;
+    use symphonia_core::audio::{Layout, Signal, SignalSpec};
+
+    /// Returns the centre frequency of a spectrum band, in Hz.
+    fn band_centre(band: usize, bands: usize) -> f32 {
+        (band as f32 + 0.5) * (SAMPLE_RATE_RAW as f32 / 2.0) / bands as f32
+    }
+
+    fn sine_mix(freq: f32, amplitude: f32) -> AudioBuffer<f32> {
+        let mut mix = AudioBuffer::<f32>::new(
+            MONO_FRAME_SIZE as u64,
+            SignalSpec::new_with_layout(SAMPLE_RATE_RAW as u32, Layout::Stereo),
+        );
+        mix.render_reserved(Some(MONO_FRAME_SIZE));
+
+        for plane in mix.planes_mut().planes() {
+            for (i, s) in plane.iter_mut().enumerate() {
+                *s = amplitude * (2.0 * PI * freq * i as f32 / SAMPLE_RATE_RAW as f32).sin();
+            }
+        }
+
+        mix
+    }
+
+    #[test]
+    fn spectrum_peaks_at_tone_frequency() {
+        let (tx, rx) = flume::unbounded();
+        let mut tap = AnalysisTap::new(Analysis::spectrum(64), tx);
+
+        assert!(tap.finish(&sine_mix(1_000.0, 0.5)));
+        let frame = rx.try_recv().unwrap();
+        assert_eq!(frame.mix.len(), 64);
+
+        let (peak, value) = frame
+            .mix
+            .iter()
+            .enumerate()
+            .max_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        assert!((band_centre(peak, 64) - 1_000.0).abs() < 375.0);
+        assert!(*value > 0.35 && *value < 0.55);
+    }
+
+    #[test]
+    fn per_track_waveform_isolates_each_track() {
+        let (tx, rx) = flume::unbounded();
+        let mut tap = AnalysisTap::new(Analysis::waveform(32).per_track(true), tx);
+        let track = Uuid::new_v4();
+
+        let mut mix = sine_mix(440.0, 0.0);
+        tap.before_track(&mix);
+        mix = sine_mix(440.0, 0.5);
+        tap.after_track(track, &mix);
+
+        assert!(tap.finish(&mix));
+        let frame = rx.try_recv().unwrap();
+
+        assert_eq!(frame.tracks.len(), 1);
+        assert_eq!(frame.tracks[0].0, track);
+        assert_eq!(frame.tracks[0].1, frame.mix);
+        assert!(frame.mix.iter().all(|peak| *peak > 0.1 && *peak <= 0.5));
+    }
+
+    #[test]
+    fn tap_closes_with_receiver() {
+        let (tx, rx) = flume::bounded(1);
+        let mut tap = AnalysisTap::new(Analysis::waveform(8), tx);
+        let mix = sine_mix(440.0, 0.5);
+
+        assert!(tap.finish(&mix));
+        // A full channel only discards frames.
+        assert!(tap.finish(&mix));
+
+        drop(rx);
+        assert!(!tap.finish(&mix));
+    }
+}
@@ -0,0 +1,227 @@
+use crate::constants::AUDIO_FRAME_RATE;
+use audiopus::Bitrate;
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// A bandwidth budget shared between every driver configured to use it.
+///
+/// The budget is split evenly between all drivers holding a live voice connection,
+/// so that the combined bitrate of all calls stays under `max_kbps`. Clones refer to
+/// the same budget, so a single value should be created for each process (or group
+/// of calls) and passed to each driver's [`BandwidthCap`].
+#[derive(Clone, Debug)]
+pub struct SharedBandwidth {
+    inner: Arc<SharedInner>,
+}
+
+#[derive(Debug)]
+struct SharedInner {
+    max_kbps: u32,
+    calls: AtomicUsize,
+}
+
+impl SharedBandwidth {
+    /// Creates a new budget of `max_kbps` to be split between calls.
+    #[must_use]
+    pub fn new(max_kbps: u32) -> Self {
+        Self {
+            inner: Arc::new(SharedInner {
+                max_kbps,
+                calls: AtomicUsize::new(0),
+            }),
+        }
+    }
+
+    /// Returns the total bitrate available to all calls, in kbps.
+    #[must_use]
+    pub fn max_kbps(&self) -> u32 {
+        self.inner.max_kbps
+    }
+
+    /// Returns the number of connected calls currently drawing from this budget.
+    #[must_use]
+    pub fn active_calls(&self) -> usize {
+        self.inner.calls.load(Ordering::Relaxed)
+    }
+
+    /// Returns the bitrate available to each connected call, in kbps.
+    #[must_use]
+    pub fn share_kbps(&self) -> u32 {
+        let calls = u32::try_from(self.active_calls()).unwrap_or(u32::MAX);
+        self.inner.max_kbps / calls.max(1)
+    }
+
+    pub(crate) fn join(&self) -> BandwidthShare {
+        self.inner.calls.fetch_add(1, Ordering::Relaxed);
+
+        BandwidthShare {
+            budget: self.clone(),
+        }
+    }
+}
+
+/// One call's claim on a [`SharedBandwidth`], released on drop.
+#[derive(Debug)]
+pub(crate) struct BandwidthShare {
+    budget: SharedBandwidth,
+}
+
+impl BandwidthShare {
+    pub(crate) fn kbps(&self) -> u32 {
+        self.budget.share_kbps()
+    }
+
+    pub(crate) fn is_of(&self, budget: &SharedBandwidth) -> bool {
+        Arc::ptr_eq(&self.budget.inner, &budget.inner)
+    }
+}
+
+impl Drop for BandwidthShare {
+    fn drop(&mut self) {
+        self.budget.inner.calls.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// Limits on the bitrate of audio sent by a driver.
+///
+/// While a limit applies, the driver lowers its Opus encoder's bitrate to stay under
+/// it whenever the requested bitrate (set via [`Driver::set_bitrate`] or a track's
+/// bitrate automation) would exceed it. [`Bitrate::Auto`] and [`Bitrate::Max`] are
+/// treated as exceeding any limit. A [`CoreEvent::Throttle`] event fires whenever
+/// throttling engages, changes, or releases.
+///
+/// Limits apply to whole packets as sent: the encoder is given whatever remains once
+/// each packet's IP, UDP, and RTP headers and any encryption nonce or tag are
+/// accounted for.
+///
+/// Opus passthrough is disabled while a limit applies, as the bitrate of a source's
+/// packets cannot be controlled.
+///
+/// [`Driver::set_bitrate`]: super::Driver::set_bitrate
+/// [`CoreEvent::Throttle`]: crate::events::CoreEvent::Throttle
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct BandwidthCap {
+    /// The maximum bitrate of this call, in kbps.
+    ///
+    /// Defaults to `None`.
+    pub max_kbps: Option<u32>,
+    /// A budget shared with other calls, of which this call uses an even share
+    /// while connected.
+    ///
+    /// Defaults to `None`.
+    pub shared: Option<SharedBandwidth>,
+    /// The lowest usable bitrate, in kbps, below which newly added tracks are
+    /// refused with [`PlayError::Bandwidth`].
+    ///
+    /// Defaults to `None`, which accepts all tracks however low the limit.
+    ///
+    /// [`PlayError::Bandwidth`]: crate::tracks::PlayError::Bandwidth
+    pub refuse_below_kbps: Option<u32>,
+}
+
+impl BandwidthCap {
+    /// Sets the maximum bitrate of this call, in kbps.
+    #[must_use]
+    pub fn max_kbps(mut self, max_kbps: Option<u32>) -> Self {
+        self.max_kbps = max_kbps;
+        self
+    }
+
+    /// Sets a budget shared with other calls.
+    #[must_use]
+    pub fn shared(mut self, shared: Option<SharedBandwidth>) -> Self {
+        self.shared = shared;
+        self
+    }
+
+    /// Sets the lowest usable bitrate, below which new tracks are refused.
+    #[must_use]
+    pub fn refuse_below_kbps(mut self, refuse_below_kbps: Option<u32>) -> Self {
+        self.refuse_below_kbps = refuse_below_kbps;
+        self
+    }
+
+    /// Returns the limit applying to a call, given its share of any shared budget.
+    pub(crate) fn limit_kbps(&self, share: Option<&BandwidthShare>) -> Option<u32> {
+        let shared = share.map(BandwidthShare::kbps);
+
+        match (self.max_kbps, shared) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+/// Length of the IPv4 and UDP headers placed before each voice packet.
+const IP_UDP_HEADER_LEN: usize = 20 + 8;
+
+/// Returns the bitrate spent on headers when sending `packet_overhead` bytes of RTP
+/// header and encryption data alongside each frame of audio, in bps.
+pub(crate) fn packet_overhead_bps(packet_overhead: usize) -> i64 {
+    ((IP_UDP_HEADER_LEN + packet_overhead) * 8 * AUDIO_FRAME_RATE) as i64
+}
+
+/// Returns whether `bitrate`, plus `overhead_bps` of packet headers, lies within
+/// `limit_kbps`.
+pub(crate) fn bitrate_fits(bitrate: Bitrate, limit_kbps: u32, overhead_bps: i64) -> bool {
+    match bitrate {
+        Bitrate::BitsPerSecond(bps) =>
+            i64::from(bps) + overhead_bps <= i64::from(limit_kbps) * 1000,
+        Bitrate::Auto | Bitrate::Max => false,
+    }
+}
+
+/// Converts a limit in kbps into a bitrate usable by the Opus encoder, leaving
+/// `overhead_bps` for packet headers.
+pub(crate) fn limit_bitrate(limit_kbps: u32, overhead_bps: i64) -> Bitrate {
+    // libopus accepts bitrates from 500bps.
+    let bps = i64::from(limit_kbps) * 1000 - overhead_bps;
+    Bitrate::BitsPerSecond(bps.clamp(500, i64::from(i32::MAX)) as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_budget_splits_between_calls() {
+        let budget = SharedBandwidth::new(192);
+        let cap = BandwidthCap::default()
+            .max_kbps(Some(128))
+            .shared(Some(budget.clone()));
+
+        let a = budget.join();
+        assert_eq!(cap.limit_kbps(Some(&a)), Some(128));
+
+        let b = budget.join();
+        assert_eq!(budget.active_calls(), 2);
+        assert_eq!(cap.limit_kbps(Some(&a)), Some(96));
+
+        drop(b);
+        assert_eq!(cap.limit_kbps(Some(&a)), Some(128));
+        assert_eq!(cap.limit_kbps(None), Some(128));
+    }
+
+    #[test]
+    fn unbounded_bitrates_never_fit() {
+        assert!(bitrate_fits(Bitrate::BitsPerSecond(64_000), 64, 0));
+        assert!(!bitrate_fits(Bitrate::BitsPerSecond(64_001), 64, 0));
+        assert!(!bitrate_fits(Bitrate::Auto, u32::MAX, 0));
+        assert!(!bitrate_fits(Bitrate::Max, u32::MAX, 0));
+    }
+
+    #[test]
+    fn packet_headers_count_against_limit() {
+        // IPv4, UDP, and a bare 12B RTP header at 50 packets per second.
+        let overhead = packet_overhead_bps(12);
+        assert_eq!(overhead, 16_000);
+
+        assert!(bitrate_fits(Bitrate::BitsPerSecond(48_000), 64, overhead));
+        assert!(!bitrate_fits(Bitrate::BitsPerSecond(48_001), 64, overhead));
+        assert_eq!(limit_bitrate(64, overhead), Bitrate::BitsPerSecond(48_000));
+        assert_eq!(limit_bitrate(8, overhead), Bitrate::BitsPerSecond(500));
+    }
+}
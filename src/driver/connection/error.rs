@@ -1,7 +1,10 @@
 //! Connection errors and convenience types.
 
 use crate::{
-    driver::tasks::{error::Recipient, message::*},
+    driver::{
+        tasks::{error::Recipient, message::*},
+        GatewayError,
+    },
     ws::Error as WsError,
 };
 use crypto_secretbox::Error as CryptoError;
@@ -27,6 +30,10 @@ pub enum Error {
     CryptoModeUnavailable,
     /// An indicator that an endpoint URL was invalid.
     EndpointUrl,
+    /// An external [`VoiceGateway`] failed to establish a session.
+    ///
+    /// [`VoiceGateway`]: crate::driver::VoiceGateway
+    Gateway(GatewayError),
     /// Discord failed to correctly respond to IP discovery.
     IllegalDiscoveryResponse,
     /// Could not parse Discord's view of our IP.
@@ -79,6 +86,13 @@ impl From<SendError<MixerMessage>> for Error {
     }
 }
 
+#[cfg(feature = "receive")]
+impl From<SendError<UdpRxMessage>> for Error {
+    fn from(_e: SendError<UdpRxMessage>) -> Error {
+        Error::InterconnectFailure(Recipient::UdpRx)
+    }
+}
+
 impl From<WsError> for Error {
     fn from(e: WsError) -> Error {
         Error::Ws(e)
@@ -101,6 +115,7 @@ impl fmt::Display for Error {
             Self::CryptoModeInvalid => write!(f, "server changed negotiated encryption mode"),
             Self::CryptoModeUnavailable => write!(f, "server did not offer chosen encryption mode"),
             Self::EndpointUrl => write!(f, "endpoint URL received from gateway was invalid"),
+            Self::Gateway(e) => write!(f, "external gateway failed ({e})"),
             Self::IllegalDiscoveryResponse => {
                 write!(f, "IP discovery/NAT punching response was invalid")
             },
@@ -128,6 +143,7 @@ impl StdError for Error {
             | Error::Ws(_)
             | Error::TimedOut => None,
             Error::Crypto(e) => e.source(),
+            Error::Gateway(e) => Some(e.as_ref()),
             Error::Io(e) => e.source(),
             Error::Json(e) => e.source(),
         }
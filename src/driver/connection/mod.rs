@@ -6,19 +6,25 @@ use super::{
     crypto::Cipher,
     tasks::{
         message::*,
+        supervise::spawn_supervised,
         ws::{self as ws_task, AuxNetwork},
     },
     Config,
     CryptoMode,
+    CryptoState,
+    RtpCounters,
+    TaskHandle,
+    VoiceSession,
 };
 use crate::{
     constants::*,
+    events::context_data::DriverTask,
     model::{
         payload::{Identify, Resume, SelectProtocol},
         Event as GatewayEvent,
         ProtocolData,
     },
-    ws::WsStream,
+    ws::{WsEvent, WsStream},
     ConnectionInfo,
 };
 use discortp::discord::{IpDiscoveryPacket, IpDiscoveryType, MutableIpDiscoveryPacket};
@@ -27,33 +33,84 @@ use flume::Sender;
 use socket2::Socket;
 #[cfg(feature = "receive")]
 use std::sync::Arc;
-use std::{net::IpAddr, str::FromStr};
-use tokio::{net::UdpSocket, spawn, time::timeout};
+use std::{
+    net::{IpAddr, Ipv4Addr},
+    str::FromStr,
+};
+use tokio::{net::UdpSocket, time::timeout};
 use tracing::{debug, info, instrument};
 use url::Url;
 
 pub(crate) struct Connection {
     pub(crate) info: ConnectionInfo,
+    pub(crate) crypto_mode: CryptoMode,
+    /// Encryption modes requested for this call in place of the driver's `Config`.
+    pub(crate) crypto_preference: Option<Vec<CryptoMode>>,
     pub(crate) ssrc: u32,
     pub(crate) ws: Sender<WsMessage>,
+    pub(crate) ws_task: TaskHandle,
+    #[cfg(feature = "receive")]
+    pub(crate) udp_rx: Sender<UdpRxMessage>,
+    #[cfg(feature = "receive")]
+    pub(crate) udp_rx_task: TaskHandle,
+    #[cfg(feature = "receive")]
+    udp_rx_state: UdpRxState,
+    bind_idx: usize,
+    /// Attempt index of this connection, used to tag a respawned receive task.
+    #[cfg(feature = "receive")]
+    idx: usize,
+    remote: (IpAddr, u16),
+    secret_key: Vec<u8>,
+}
+
+/// Session state needed to respawn the UDP receive task if it dies.
+#[cfg(feature = "receive")]
+struct UdpRxState {
+    cipher: Cipher,
+    crypto_mode: CryptoMode,
+    rx: flume::Receiver<UdpRxMessage>,
+    socket: std::net::UdpSocket,
+    ssrc_tracker: Arc<SsrcTracker>,
+}
+
+/// A voice session whose websocket and UDP handshakes have completed, but whose
+/// tasks have not yet been started.
+struct Established {
+    client: WsStream,
+    ws_msg_tx: Sender<WsMessage>,
+    ws_msg_rx: flume::Receiver<WsMessage>,
+    heartbeat_interval: f64,
+    udp: UdpSocket,
+    bind_idx: usize,
+    ssrc: u32,
+    remote: (IpAddr, u16),
+    cipher: Cipher,
+    crypto_mode: CryptoMode,
+    secret_key: Vec<u8>,
+    /// Nonce and RTP counters to continue from, if adopting an exported session.
+    resumed: Option<(Option<u32>, RtpCounters)>,
 }
 
 impl Connection {
     pub(crate) async fn new(
         info: ConnectionInfo,
+        crypto_preference: Option<Vec<CryptoMode>>,
         interconnect: &Interconnect,
         config: &Config,
         idx: usize,
     ) -> Result<Connection> {
+        let inner = Connection::new_inner(info, crypto_preference, interconnect, config, idx);
+
         if let Some(t) = config.driver_timeout {
-            timeout(t, Connection::new_inner(info, interconnect, config, idx)).await?
+            timeout(t, inner).await?
         } else {
-            Connection::new_inner(info, interconnect, config, idx).await
+            inner.await
         }
     }
 
     pub(crate) async fn new_inner(
         mut info: ConnectionInfo,
+        crypto_preference: Option<Vec<CryptoMode>>,
         interconnect: &Interconnect,
         config: &Config,
         idx: usize,
@@ -81,13 +138,13 @@ impl Connection {
             };
 
             match value {
-                GatewayEvent::Ready(r) => {
+                WsEvent::Gateway(GatewayEvent::Ready(r)) => {
                     ready = Some(r);
                     if hello.is_some() {
                         break;
                     }
                 },
-                GatewayEvent::Hello(h) => {
+                WsEvent::Gateway(GatewayEvent::Hello(h)) => {
                     hello = Some(h);
                     if ready.is_some() {
                         break;
@@ -108,84 +165,243 @@ impl Connection {
         let ready =
             ready.expect("Ready packet expected in connection initialisation, but not found.");
 
-        let chosen_crypto = CryptoMode::negotiate(&ready.modes, Some(config.crypto_mode))?;
+        let preferred_crypto = crypto_preference
+            .clone()
+            .unwrap_or_else(|| config.preferred_crypto_modes());
+        let chosen_crypto = CryptoMode::negotiate(&ready.modes, &preferred_crypto)?;
 
         info!(
             "Crypto scheme negotiation -- wanted {:?}. Chose {:?} from modes {:?}.",
-            config.crypto_mode, chosen_crypto, ready.modes
+            preferred_crypto, chosen_crypto, ready.modes
         );
 
-        let udp = UdpSocket::bind("0.0.0.0:0").await?;
+        let remote = (ready.ip, ready.port);
+        let (bind_idx, udp, address, port) = bind_udp(config, 0, remote, ready.ssrc).await?;
+
+        client
+            .send_json(&GatewayEvent::from(SelectProtocol {
+                protocol: "udp".into(),
+                data: ProtocolData {
+                    address,
+                    mode: chosen_crypto.to_request_str().into(),
+                    port,
+                },
+            }))
+            .await?;
+
+        let (cipher, secret_key) = init_cipher(&mut client, chosen_crypto, &ws_msg_tx).await?;
+
+        info!("Connected to: {}", info.endpoint);
+
+        Established {
+            client,
+            ws_msg_tx,
+            ws_msg_rx,
+            heartbeat_interval: hello.heartbeat_interval,
+            udp,
+            bind_idx,
+            ssrc: ready.ssrc,
+            remote,
+            cipher,
+            crypto_mode: chosen_crypto,
+            secret_key,
+            resumed: None,
+        }
+        .start(info, crypto_preference, interconnect, config, idx)
+    }
 
-        // Optimisation for non-receive case: set rx buffer size to zero.
-        let udp = if cfg!(feature = "receive") {
-            udp
+    /// Adopts a session exported from another driver, resuming its websocket
+    /// and re-binding UDP without a fresh handshake.
+    pub(crate) async fn resume(
+        session: VoiceSession,
+        interconnect: &Interconnect,
+        config: &Config,
+        idx: usize,
+    ) -> Result<Connection> {
+        let inner = Connection::resume_inner(session, interconnect, config, idx);
+
+        if let Some(t) = config.driver_timeout {
+            timeout(t, inner).await?
         } else {
-            let socket = Socket::from(udp.into_std()?);
+            inner.await
+        }
+    }
 
-            // Some operating systems do not allow setting the recv buffer to 0.
-            #[cfg(any(target_os = "linux", target_os = "windows"))]
-            socket.set_recv_buffer_size(0)?;
+    async fn resume_inner(
+        session: VoiceSession,
+        interconnect: &Interconnect,
+        config: &Config,
+        idx: usize,
+    ) -> Result<Connection> {
+        let mut info = session.info();
+        let (ws_msg_tx, ws_msg_rx) = flume::unbounded();
 
-            UdpSocket::from_std(socket.into())?
-        };
+        let (client, heartbeat_interval) = resume_ws(&mut info, &ws_msg_tx).await?;
 
-        udp.connect((ready.ip, ready.port)).await?;
+        let remote = (session.remote_ip, session.remote_port);
+        let (bind_idx, udp, address, port) = bind_udp(config, 0, remote, session.ssrc).await?;
 
-        // Follow Discord's IP Discovery procedures, in case NAT tunnelling is needed.
-        let mut bytes = [0; IpDiscoveryPacket::const_packet_size()];
-        {
-            let mut view = MutableIpDiscoveryPacket::new(&mut bytes[..]).expect(
-                "Too few bytes in 'bytes' for IPDiscovery packet.\
-                    (Blame: IpDiscoveryPacket::const_packet_size()?)",
-            );
-            view.set_pkt_type(IpDiscoveryType::Request);
-            view.set_length(70);
-            view.set_ssrc(ready.ssrc);
+        debug!("Resumed session seen externally as {}:{}.", address, port);
+
+        let cipher = session
+            .crypto_mode
+            .cipher_from_key(&session.secret_key)
+            .map_err(|_| Error::CryptoInvalidLength)?;
+
+        info!("Resumed exported session on: {}", info.endpoint);
+
+        Established {
+            client,
+            ws_msg_tx,
+            ws_msg_rx,
+            heartbeat_interval,
+            udp,
+            bind_idx,
+            ssrc: session.ssrc,
+            remote,
+            cipher,
+            crypto_mode: session.crypto_mode,
+            resumed: Some((session.nonce, session.rtp_counters())),
+            secret_key: session.secret_key,
         }
+        .start(info, None, interconnect, config, idx)
+    }
 
-        udp.send(&bytes).await?;
+    /// Returns the state needed for another driver to adopt this session.
+    pub(crate) fn session(&self) -> VoiceSession {
+        VoiceSession::new(
+            &self.info,
+            self.ssrc,
+            self.crypto_mode,
+            self.secret_key.clone(),
+            self.remote,
+        )
+    }
 
-        let (len, _addr) = udp.recv_from(&mut bytes).await?;
-        {
-            let view =
-                IpDiscoveryPacket::new(&bytes[..len]).ok_or(Error::IllegalDiscoveryResponse)?;
+    /// Respawns this connection's UDP receive task after it has died, keeping
+    /// the session's encryption state and known SSRCs.
+    ///
+    /// Decoder state and any unplayed audio from other users are lost.
+    #[cfg(feature = "receive")]
+    pub(crate) fn restart_udp_rx(
+        &mut self,
+        interconnect: &Interconnect,
+        config: &Config,
+    ) -> Result<()> {
+        self.udp_rx_task = self.udp_rx_state.spawn(interconnect, config, self.idx)?;
 
-            if view.get_pkt_type() != IpDiscoveryType::Response {
-                return Err(Error::IllegalDiscoveryResponse);
-            }
+        Ok(())
+    }
 
-            // We could do something clever like binary search,
-            // but possibility of UDP spoofing precludes us from
-            // making the assumption we can find a "left edge" of '\0's.
-            let nul_byte_index = view
-                .get_address_raw()
-                .iter()
-                .position(|&b| b == 0)
-                .ok_or(Error::IllegalIp)?;
-
-            let address_str = std::str::from_utf8(&view.get_address_raw()[..nul_byte_index])
-                .map_err(|_| Error::IllegalIp)?;
-
-            let address = IpAddr::from_str(address_str).map_err(|_| Error::IllegalIp)?;
-
-            client
-                .send_json(&GatewayEvent::from(SelectProtocol {
-                    protocol: "udp".into(),
-                    data: ProtocolData {
-                        address,
-                        mode: chosen_crypto.to_request_str().into(),
-                        port: view.get_port(),
-                    },
-                }))
-                .await?;
+    #[instrument(skip(self))]
+    pub async fn reconnect(&mut self, config: &Config) -> Result<()> {
+        if let Some(t) = config.driver_timeout {
+            timeout(t, self.reconnect_inner()).await?
+        } else {
+            self.reconnect_inner().await
         }
+    }
 
-        let cipher = init_cipher(&mut client, chosen_crypto, &ws_msg_tx).await?;
+    #[instrument(skip(self))]
+    pub async fn reconnect_inner(&mut self) -> Result<()> {
+        // Thread may have died, we want to send to prompt a clean exit
+        // (if at all possible) and then proceed as normal.
+        let (client, heartbeat_interval) = resume_ws(&mut self.info, &self.ws).await?;
 
-        info!("Connected to: {}", info.endpoint);
+        self.ws.send(WsMessage::SetKeepalive(heartbeat_interval))?;
+        self.ws.send(WsMessage::Ws(Box::new(client)))?;
+
+        info!("Reconnected to: {}", &self.info.endpoint);
+        Ok(())
+    }
+}
+
+impl Connection {
+    /// Moves voice traffic onto the next usable local address in [`Config::bind_addresses`],
+    /// keeping the current session.
+    ///
+    /// [`Config::bind_addresses`]: crate::Config::bind_addresses
+    #[instrument(skip(self, interconnect, config))]
+    pub async fn rebind(&mut self, interconnect: &Interconnect, config: &Config) -> Result<()> {
+        let (bind_idx, udp, address, port) =
+            bind_udp(config, self.bind_idx + 1, self.remote, self.ssrc).await?;
+
+        // The session's cipher and crypto mode are unchanged: discovery only needs to
+        // succeed to show that the voice server is reachable through the new interface.
+        info!(
+            "Rebound voice UDP to {:?}, seen externally as {}:{}.",
+            config.bind_addresses.get(bind_idx),
+            address,
+            port
+        );
+
+        #[cfg(feature = "receive")]
+        let (udp_rx, udp_tx) = {
+            let udp_tx = udp.into_std()?;
+            self.udp_rx_state.socket = udp_tx.try_clone()?;
+            let udp_rx = UdpSocket::from_std(udp_tx.try_clone()?)?;
+            (udp_rx, udp_tx)
+        };
+        #[cfg(not(feature = "receive"))]
+        let udp_tx = udp.into_std()?;
+
+        interconnect.mixer.send(MixerMessage::ReplaceUdp(udp_tx))?;
+        #[cfg(feature = "receive")]
+        self.udp_rx.send(UdpRxMessage::ReplaceSocket(udp_rx))?;
 
-        info!("WS heartbeat duration {}ms.", hello.heartbeat_interval);
+        self.bind_idx = bind_idx;
+
+        Ok(())
+    }
+}
+
+impl Connection {
+    /// Closes the websocket, and waits for this connection's UDP receive and
+    /// WS tasks to exit (in that order).
+    ///
+    /// The mixer must have released its own handles to both tasks beforehand.
+    pub(crate) async fn shutdown(&mut self) {
+        // The UDP receive task only exits once every sender to it is gone,
+        // including the one held here for rebinding.
+        #[cfg(feature = "receive")]
+        {
+            drop(std::mem::replace(&mut self.udp_rx, flume::unbounded().0));
+            self.udp_rx_task.join().await;
+        }
+
+        if self.ws.send(WsMessage::Close).is_ok() {
+            self.ws_task.join().await;
+        }
+    }
+}
+
+impl Established {
+    /// Hands this session's UDP socket to the mixer, and spawns its websocket
+    /// and UDP receive tasks.
+    fn start(
+        self,
+        info: ConnectionInfo,
+        crypto_preference: Option<Vec<CryptoMode>>,
+        interconnect: &Interconnect,
+        config: &Config,
+        idx: usize,
+    ) -> Result<Connection> {
+        let Established {
+            client,
+            ws_msg_tx,
+            ws_msg_rx,
+            heartbeat_interval,
+            udp,
+            bind_idx,
+            ssrc,
+            remote,
+            cipher,
+            crypto_mode,
+            secret_key,
+            resumed,
+        } = self;
+
+        info!("WS heartbeat duration {}ms.", heartbeat_interval);
 
         #[cfg(feature = "receive")]
         let (udp_receiver_msg_tx, udp_receiver_msg_rx) = flume::unbounded();
@@ -200,22 +416,27 @@ impl Connection {
         #[cfg(feature = "receive")]
         let (udp_rx, udp_tx) = {
             let udp_tx = udp.into_std()?;
-            let udp_rx = UdpSocket::from_std(udp_tx.try_clone()?)?;
+            let udp_rx = udp_tx.try_clone()?;
             (udp_rx, udp_tx)
         };
         #[cfg(not(feature = "receive"))]
         let udp_tx = udp.into_std()?;
 
-        let ssrc = ready.ssrc;
+        // An adopted session must continue its nonce from the exporting driver,
+        // or packets could reuse a nonce under the same key.
+        let (nonce, counters) = resumed.unzip();
+        let crypto_state = match nonce.flatten() {
+            Some(nonce) => CryptoState::from(crypto_mode).with_nonce(nonce),
+            None => crypto_mode.into(),
+        };
 
         let mix_conn = MixerConnection {
             #[cfg(feature = "receive")]
-            cipher: cipher.clone(),
+            crypto: PacketCrypto::Discord(cipher.clone(), crypto_state),
             #[cfg(not(feature = "receive"))]
-            cipher,
-            crypto_state: chosen_crypto.into(),
+            crypto: PacketCrypto::Discord(cipher, crypto_state),
             #[cfg(feature = "receive")]
-            udp_rx: udp_receiver_msg_tx,
+            udp_rx: udp_receiver_msg_tx.clone(),
             udp_tx,
         };
 
@@ -225,7 +446,7 @@ impl Connection {
 
         interconnect
             .mixer
-            .send(MixerMessage::SetConn(mix_conn, ready.ssrc))?;
+            .send(MixerMessage::SetConn(mix_conn, ssrc, counters))?;
 
         #[cfg(feature = "receive")]
         let ssrc_tracker = Arc::new(SsrcTracker::default());
@@ -234,111 +455,257 @@ impl Connection {
             ws_msg_rx,
             client,
             ssrc,
-            hello.heartbeat_interval,
+            heartbeat_interval,
             idx,
             info.clone(),
             #[cfg(feature = "receive")]
             ssrc_tracker.clone(),
         );
 
-        spawn(ws_task::runner(interconnect.clone(), ws_state));
+        let ws_task = spawn_supervised(
+            DriverTask::Ws,
+            idx,
+            interconnect.core.clone(),
+            &config.spawner,
+            ws_task::runner(interconnect.clone(), ws_state),
+        );
 
         #[cfg(feature = "receive")]
-        spawn(udp_rx::runner(
-            interconnect.clone(),
-            udp_receiver_msg_rx,
+        let udp_rx_state = UdpRxState {
             cipher,
-            chosen_crypto,
-            config.clone(),
-            udp_rx,
+            crypto_mode,
+            rx: udp_receiver_msg_rx,
+            socket: udp_rx,
             ssrc_tracker,
-        ));
+        };
+
+        #[cfg(feature = "receive")]
+        let udp_rx_task = udp_rx_state.spawn(interconnect, config, idx)?;
 
         Ok(Connection {
             info,
+            crypto_mode,
+            crypto_preference,
             ssrc,
             ws: ws_msg_tx,
+            ws_task,
+            #[cfg(feature = "receive")]
+            udp_rx: udp_receiver_msg_tx,
+            #[cfg(feature = "receive")]
+            udp_rx_task,
+            #[cfg(feature = "receive")]
+            udp_rx_state,
+            bind_idx,
+            #[cfg(feature = "receive")]
+            idx,
+            remote,
+            secret_key,
         })
     }
+}
 
-    #[instrument(skip(self))]
-    pub async fn reconnect(&mut self, config: &Config) -> Result<()> {
-        if let Some(t) = config.driver_timeout {
-            timeout(t, self.reconnect_inner()).await?
-        } else {
-            self.reconnect_inner().await
-        }
+#[cfg(feature = "receive")]
+impl UdpRxState {
+    fn spawn(
+        &self,
+        interconnect: &Interconnect,
+        config: &Config,
+        idx: usize,
+    ) -> Result<TaskHandle> {
+        let socket = UdpSocket::from_std(self.socket.try_clone()?)?;
+
+        Ok(spawn_supervised(
+            DriverTask::UdpRx,
+            idx,
+            interconnect.core.clone(),
+            &config.spawner,
+            udp_rx::runner(
+                interconnect.clone(),
+                self.rx.clone(),
+                self.cipher.clone(),
+                self.crypto_mode,
+                config.clone(),
+                socket,
+                self.ssrc_tracker.clone(),
+            ),
+        ))
     }
+}
 
-    #[instrument(skip(self))]
-    pub async fn reconnect_inner(&mut self) -> Result<()> {
-        let url = generate_url(&mut self.info.endpoint)?;
+impl Drop for Connection {
+    fn drop(&mut self) {
+        info!("Disconnected");
+    }
+}
 
-        // Thread may have died, we want to send to prompt a clean exit
-        // (if at all possible) and then proceed as normal.
-        let mut client = WsStream::connect(url).await?;
+fn generate_url(endpoint: &mut String) -> Result<Url> {
+    if endpoint.ends_with(":80") {
+        let len = endpoint.len();
 
-        client
-            .send_json(&GatewayEvent::from(Resume {
-                server_id: self.info.guild_id.into(),
-                session_id: self.info.session_id.clone(),
-                token: self.info.token.clone(),
-            }))
-            .await?;
+        endpoint.truncate(len - 3);
+    }
 
-        let mut hello = None;
-        let mut resumed = None;
+    // Plaintext endpoints are only expected from local mock servers.
+    let url = if endpoint.starts_with("ws://") {
+        format!("{endpoint}/?v={VOICE_GATEWAY_VERSION}")
+    } else {
+        format!("wss://{endpoint}/?v={VOICE_GATEWAY_VERSION}")
+    };
 
-        loop {
-            let Some(value) = client.recv_json().await? else {
-                continue;
-            };
+    Url::parse(&url).or(Err(Error::EndpointUrl))
+}
 
-            match value {
-                GatewayEvent::Resumed => {
-                    resumed = Some(());
-                    if hello.is_some() {
-                        break;
-                    }
-                },
-                GatewayEvent::Hello(h) => {
-                    hello = Some(h);
-                    if resumed.is_some() {
-                        break;
-                    }
-                },
-                other => {
-                    self.ws.send(WsMessage::Deliver(other))?;
-                },
-            }
-        }
+/// Opens a new websocket to this session's voice gateway and resumes it.
+///
+/// Any other events received during the handshake are forwarded to `tx`.
+/// Returns the new websocket and its heartbeat interval.
+async fn resume_ws(info: &mut ConnectionInfo, tx: &Sender<WsMessage>) -> Result<(WsStream, f64)> {
+    let url = generate_url(&mut info.endpoint)?;
 
-        let hello =
-            hello.expect("Hello packet expected in connection initialisation, but not found.");
+    let mut client = WsStream::connect(url).await?;
 
-        self.ws
-            .send(WsMessage::SetKeepalive(hello.heartbeat_interval))?;
-        self.ws.send(WsMessage::Ws(Box::new(client)))?;
+    client
+        .send_json(&GatewayEvent::from(Resume {
+            server_id: info.guild_id.into(),
+            session_id: info.session_id.clone(),
+            token: info.token.clone(),
+        }))
+        .await?;
 
-        info!("Reconnected to: {}", &self.info.endpoint);
-        Ok(())
+    let mut hello = None;
+    let mut resumed = None;
+
+    loop {
+        let Some(value) = client.recv_json().await? else {
+            continue;
+        };
+
+        match value {
+            WsEvent::Gateway(GatewayEvent::Resumed) => {
+                resumed = Some(());
+                if hello.is_some() {
+                    break;
+                }
+            },
+            WsEvent::Gateway(GatewayEvent::Hello(h)) => {
+                hello = Some(h);
+                if resumed.is_some() {
+                    break;
+                }
+            },
+            other => {
+                tx.send(WsMessage::Deliver(other))?;
+            },
+        }
     }
+
+    let hello = hello.expect("Hello packet expected in connection initialisation, but not found.");
+
+    Ok((client, hello.heartbeat_interval))
 }
 
-impl Drop for Connection {
-    fn drop(&mut self) {
-        info!("Disconnected");
+/// Binds a voice UDP socket to the first local address, trying each of
+/// [`Config::bind_addresses`] in turn from `start`, which completes IP discovery.
+///
+/// Returns the index of the address used, the connected socket, and the external
+/// address and port reported by the voice server.
+///
+/// [`Config::bind_addresses`]: crate::Config::bind_addresses
+async fn bind_udp(
+    config: &Config,
+    start: usize,
+    remote: (IpAddr, u16),
+    ssrc: u32,
+) -> Result<(usize, UdpSocket, IpAddr, u16)> {
+    let n_addrs = config.bind_addresses.len().max(1);
+    let mut last_err = Error::IllegalDiscoveryResponse;
+
+    for i in 0..n_addrs {
+        let idx = (start + i) % n_addrs;
+        let local = config
+            .bind_addresses
+            .get(idx)
+            .copied()
+            .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+        // A dead interface may swallow the discovery request entirely.
+        let attempt = discover_ip(local, remote, ssrc);
+        let res = if let Some(t) = config.driver_timeout {
+            timeout(t, attempt).await.unwrap_or(Err(Error::TimedOut))
+        } else {
+            attempt.await
+        };
+
+        match res {
+            Ok((udp, address, port)) => return Ok((idx, udp, address, port)),
+            Err(why) => {
+                debug!("Failed to bind voice UDP to {}: {}", local, why);
+                last_err = why;
+            },
+        }
     }
+
+    Err(last_err)
 }
 
-fn generate_url(endpoint: &mut String) -> Result<Url> {
-    if endpoint.ends_with(":80") {
-        let len = endpoint.len();
+async fn discover_ip(
+    local: IpAddr,
+    remote: (IpAddr, u16),
+    ssrc: u32,
+) -> Result<(UdpSocket, IpAddr, u16)> {
+    let udp = UdpSocket::bind((local, 0)).await?;
+
+    // Optimisation for non-receive case: set rx buffer size to zero.
+    let udp = if cfg!(feature = "receive") {
+        udp
+    } else {
+        let socket = Socket::from(udp.into_std()?);
+
+        // Some operating systems do not allow setting the recv buffer to 0.
+        #[cfg(any(target_os = "linux", target_os = "windows"))]
+        socket.set_recv_buffer_size(0)?;
+
+        UdpSocket::from_std(socket.into())?
+    };
+
+    udp.connect(remote).await?;
+
+    // Follow Discord's IP Discovery procedures, in case NAT tunnelling is needed.
+    let mut bytes = [0; IpDiscoveryPacket::const_packet_size()];
+    {
+        let mut view = MutableIpDiscoveryPacket::new(&mut bytes[..]).expect(
+            "Too few bytes in 'bytes' for IPDiscovery packet.\
+                (Blame: IpDiscoveryPacket::const_packet_size()?)",
+        );
+        view.set_pkt_type(IpDiscoveryType::Request);
+        view.set_length(70);
+        view.set_ssrc(ssrc);
+    }
 
-        endpoint.truncate(len - 3);
+    udp.send(&bytes).await?;
+
+    let (len, _addr) = udp.recv_from(&mut bytes).await?;
+    let view = IpDiscoveryPacket::new(&bytes[..len]).ok_or(Error::IllegalDiscoveryResponse)?;
+
+    if view.get_pkt_type() != IpDiscoveryType::Response {
+        return Err(Error::IllegalDiscoveryResponse);
     }
 
-    Url::parse(&format!("wss://{endpoint}/?v={VOICE_GATEWAY_VERSION}")).or(Err(Error::EndpointUrl))
+    // We could do something clever like binary search,
+    // but possibility of UDP spoofing precludes us from
+    // making the assumption we can find a "left edge" of '\0's.
+    let nul_byte_index = view
+        .get_address_raw()
+        .iter()
+        .position(|&b| b == 0)
+        .ok_or(Error::IllegalIp)?;
+
+    let address_str = std::str::from_utf8(&view.get_address_raw()[..nul_byte_index])
+        .map_err(|_| Error::IllegalIp)?;
+
+    let address = IpAddr::from_str(address_str).map_err(|_| Error::IllegalIp)?;
+
+    Ok((udp, address, view.get_port()))
 }
 
 #[inline]
@@ -346,21 +713,23 @@ async fn init_cipher(
     client: &mut WsStream,
     mode: CryptoMode,
     tx: &Sender<WsMessage>,
-) -> Result<Cipher> {
+) -> Result<(Cipher, Vec<u8>)> {
     loop {
         let Some(value) = client.recv_json().await? else {
             continue;
         };
 
         match value {
-            GatewayEvent::SessionDescription(desc) => {
+            WsEvent::Gateway(GatewayEvent::SessionDescription(desc)) => {
                 if desc.mode != mode.to_request_str() {
                     return Err(Error::CryptoModeInvalid);
                 }
 
-                return mode
+                let cipher = mode
                     .cipher_from_key(&desc.secret_key)
-                    .map_err(|_| Error::CryptoInvalidLength);
+                    .map_err(|_| Error::CryptoInvalidLength)?;
+
+                return Ok((cipher, desc.secret_key));
             },
             other => {
                 // Discord can and will send user-specific payload packets during this time
@@ -0,0 +1,26 @@
+use crate::model::id::UserId;
+
+/// Decides whether audio received from each user in a call may be processed.
+///
+/// When set via [`Config::receive_consent`], this is consulted for each SSRC when
+/// its first RTP packet arrives, *before* that packet is decrypted. Packets from
+/// denied SSRCs are discarded without being decrypted, decoded, buffered, or
+/// passed to any event handler, allowing per-user recording consent to be enforced
+/// at the lowest possible layer.
+///
+/// Decisions are cached per SSRC, and are revisited once Discord announces the
+/// user who owns an SSRC, or whenever the driver's [`Config`] changes. Per-user
+/// decisions can be flipped at runtime using [`Driver::set_receive_consent`], which
+/// take precedence over this hook.
+///
+/// [`Config`]: crate::Config
+/// [`Config::receive_consent`]: crate::Config::receive_consent
+/// [`Driver::set_receive_consent`]: crate::driver::Driver::set_receive_consent
+pub trait ReceiveConsent: Send + Sync {
+    /// Returns whether audio from `ssrc` may be decrypted and decoded.
+    ///
+    /// `user_id` is `None` if the owner of `ssrc` has not yet been announced.
+    ///
+    /// This is called from the driver's UDP receive task, and must not block.
+    fn allow(&self, ssrc: u32, user_id: Option<UserId>) -> bool;
+}
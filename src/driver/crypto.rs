@@ -7,13 +7,14 @@ use chacha20poly1305::XChaCha20Poly1305;
 use crypto_secretbox::{cipher::InvalidLength, Error as CryptoError, XSalsa20Poly1305};
 #[cfg(feature = "receive")]
 use discortp::rtcp::MutableRtcpPacket;
-use discortp::{rtp::RtpPacket, MutablePacket};
-#[cfg(any(feature = "receive", test))]
+#[cfg(any(feature = "receive", feature = "mock-server", test))]
+use discortp::Packet;
 use discortp::{
-    rtp::{MutableRtpPacket, RtpExtensionPacket},
-    Packet,
+    rtp::{MutableRtpPacket, RtpExtensionPacket, RtpPacket},
+    MutablePacket,
 };
 use rand::Rng;
+use serde::{de::Error as _, Deserialize, Deserializer, Serialize, Serializer};
 use std::{num::Wrapping, str::FromStr};
 use typenum::Unsigned;
 
@@ -111,6 +112,21 @@ impl FromStr for CryptoMode {
     }
 }
 
+/// Serialises a [`CryptoMode`] as its name in Discord's voice protocol.
+impl Serialize for CryptoMode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.to_request_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for CryptoMode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse()
+            .map_err(|_| D::Error::custom(format!("unrecognised crypto mode: {name}")))
+    }
+}
+
 #[allow(deprecated)]
 impl CryptoMode {
     /// Returns the underlying crypto algorithm used by a given [`CryptoMode`].
@@ -130,7 +146,6 @@ impl CryptoMode {
     /// Compliant SRTP would leave all extensions in cleartext, hence 'more' SRTP
     /// compliant.
     #[must_use]
-    #[cfg(any(feature = "receive", test))]
     pub(crate) const fn is_more_srtp_compliant(self) -> bool {
         match self {
             CryptoMode::Aes256Gcm | CryptoMode::XChaCha20Poly1305 => true,
@@ -169,12 +184,10 @@ impl CryptoMode {
 
     /// Returns the best available crypto mode, given the `modes` offered by the Discord voice server.
     ///
-    /// If `preferred` is set and the mode exists in the server's supported algorithms, then that
-    /// mode will be chosen. Otherwise we select the highest-scoring option which is mutually understood.
-    pub(crate) fn negotiate<It, T>(
-        modes: It,
-        preferred: Option<Self>,
-    ) -> Result<Self, ConnectionError>
+    /// Modes listed in `preferred` which exist in the server's supported algorithms are chosen
+    /// first, in the order given. Otherwise we select the highest-scoring option which is mutually
+    /// understood.
+    pub(crate) fn negotiate<It, T>(modes: It, preferred: &[Self]) -> Result<Self, ConnectionError>
     where
         T: AsRef<str>,
         It: IntoIterator<Item = T>,
@@ -186,12 +199,10 @@ impl CryptoMode {
                 continue;
             };
 
-            let mut el_priority = el.priority();
-            if let Some(preferred) = preferred {
-                if el == preferred {
-                    el_priority = u64::MAX;
-                }
-            }
+            let el_priority = preferred
+                .iter()
+                .position(|p| *p == el)
+                .map_or_else(|| el.priority(), |rank| u64::MAX - rank as u64);
 
             let accept = match best {
                 None => true,
@@ -446,6 +457,24 @@ impl CryptoState {
     pub fn kind(self) -> CryptoMode {
         CryptoMode::from(self)
     }
+
+    /// Returns the nonce counter of the next packet, for modes which increment one.
+    pub(crate) fn nonce(self) -> Option<u32> {
+        match self {
+            Self::Lite(i) | Self::Aes256Gcm(i) | Self::XChaCha20Poly1305(i) => Some(i.0),
+            Self::Normal | Self::Suffix => None,
+        }
+    }
+
+    /// Continues from the given nonce counter, for modes which increment one.
+    pub(crate) fn with_nonce(mut self, nonce: u32) -> Self {
+        match &mut self {
+            Self::Lite(i) | Self::Aes256Gcm(i) | Self::XChaCha20Poly1305(i) => *i = Wrapping(nonce),
+            Self::Normal | Self::Suffix => {},
+        }
+
+        self
+    }
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
@@ -507,12 +536,47 @@ impl Cipher {
         &self,
         packet: &mut impl MutablePacket,
         payload_len: usize,
+    ) -> Result<(), CryptoError> {
+        self.encrypt_pkt_in_place_inner(packet, payload_len, 0)
+    }
+
+    /// Encrypts a Discord RTP packet using the given key, accounting for any
+    /// RTP header extensions.
+    ///
+    /// Use of this requires that the input packet has had a nonce generated in the correct location,
+    /// and `payload_len` specifies the number of bytes after the header including this nonce.
+    #[inline]
+    pub(crate) fn encrypt_rtp_in_place(
+        &self,
+        packet: &mut MutableRtpPacket<'_>,
+        payload_len: usize,
+    ) -> Result<(), CryptoError> {
+        // As in decryption, the extension preamble is left in the clear
+        // (and authenticated) by the SRTP-like modes.
+        let plain_bytes = if self.mode().is_more_srtp_compliant() && packet.get_extension() != 0 {
+            RtpExtensionPacket::minimum_packet_size()
+        } else {
+            0
+        };
+
+        self.encrypt_pkt_in_place_inner(packet, payload_len, plain_bytes)
+    }
+
+    #[inline]
+    fn encrypt_pkt_in_place_inner(
+        &self,
+        packet: &mut impl MutablePacket,
+        payload_len: usize,
+        n_plaintext_body_bytes: usize,
     ) -> Result<(), CryptoError> {
         let mode = self.mode();
-        let header_len = packet.packet().len() - packet.payload().len();
+        let header_len = packet.packet().len() - packet.payload().len() + n_plaintext_body_bytes;
+        let body_len = payload_len
+            .checked_sub(n_plaintext_body_bytes)
+            .ok_or(CryptoError)?;
 
         let (header, body) = packet.packet_mut().split_at_mut(header_len);
-        let (slice_to_use, body_remaining) = mode.nonce_slice(header, &mut body[..payload_len])?;
+        let (slice_to_use, body_remaining) = mode.nonce_slice(header, &mut body[..body_len])?;
 
         let tag_size = self.encryption_tag_len();
 
@@ -557,7 +621,7 @@ impl Cipher {
         Ok(())
     }
 
-    #[cfg(any(feature = "receive", test))]
+    #[cfg(any(feature = "receive", feature = "mock-server", test))]
     pub(crate) fn decrypt_rtp_in_place(
         &self,
         packet: &mut MutableRtpPacket<'_>,
@@ -608,7 +672,7 @@ impl Cipher {
     /// If successful, this returns the number of bytes to be ignored from the
     /// start and end of the packet payload.
     #[inline]
-    #[cfg(any(feature = "receive", test))]
+    #[cfg(any(feature = "receive", feature = "mock-server", test))]
     pub(crate) fn decrypt_pkt_in_place(
         &self,
         packet: &mut impl MutablePacket,
@@ -671,7 +735,7 @@ impl Cipher {
 
 // Temporary functions -- MSRV is ostensibly 1.74, slice::split_at(_mut)_checked is 1.80+.
 // TODO: Remove in v0.5+ with MSRV bump to 1.81+.
-#[cfg(any(feature = "receive", test))]
+#[cfg(any(feature = "receive", feature = "mock-server", test))]
 #[inline]
 #[must_use]
 const fn split_at_checked(els: &[u8], mid: usize) -> Option<(&[u8], &[u8])> {
@@ -682,7 +746,7 @@ const fn split_at_checked(els: &[u8], mid: usize) -> Option<(&[u8], &[u8])> {
     }
 }
 
-#[cfg(any(feature = "receive", test))]
+#[cfg(any(feature = "receive", feature = "mock-server", test))]
 #[inline]
 #[must_use]
 fn split_at_mut_checked(els: &mut [u8], mid: usize) -> Option<(&mut [u8], &mut [u8])> {
@@ -786,6 +850,51 @@ mod test {
         }
     }
 
+    #[test]
+    fn symmetric_encrypt_decrypt_with_extensions() {
+        use crate::driver::rtp_extension::{self, RtpExtension};
+
+        const TRUE_PAYLOAD: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+        let exts = [RtpExtension::new(2, [9, 9, 9]).unwrap()];
+        let ext_len = rtp_extension::block_len(&exts);
+
+        for mode in [CryptoMode::Aes256Gcm, CryptoMode::XChaCha20Poly1305] {
+            let mut buf = vec![
+                0u8;
+                MutableRtpPacket::minimum_packet_size()
+                    + ext_len
+                    + TRUE_PAYLOAD.len()
+                    + mode.nonce_size()
+                    + mode.encryption_tag_len()
+            ];
+
+            let cipher = mode.cipher_from_key(&[7u8; 32]).unwrap();
+            let mut pkt = MutableRtpPacket::new(&mut buf[..]).unwrap();
+            let mut crypto_state = CryptoState::from(mode);
+            pkt.set_extension(1);
+            let payload = pkt.payload_mut();
+            rtp_extension::write_extensions(&exts, payload).unwrap();
+            payload[ext_len..][..TRUE_PAYLOAD.len()].copy_from_slice(&TRUE_PAYLOAD);
+
+            let final_payload_size =
+                crypto_state.write_packet_nonce(&mut pkt, ext_len + TRUE_PAYLOAD.len());
+
+            assert!(cipher
+                .encrypt_rtp_in_place(&mut pkt, final_payload_size)
+                .is_ok());
+
+            let final_pkt_len = MutableRtpPacket::minimum_packet_size() + final_payload_size;
+            let mut pkt = MutableRtpPacket::new(&mut buf[..final_pkt_len]).unwrap();
+
+            let (start, end) = cipher.decrypt_rtp_in_place(&mut pkt).unwrap();
+            assert_eq!(&pkt.packet()[start..final_pkt_len - end], &TRUE_PAYLOAD);
+            assert_eq!(
+                rtp_extension::read_extensions(pkt.payload()).unwrap().0,
+                exts
+            );
+        }
+    }
+
     #[test]
     #[allow(deprecated)]
     fn negotiate_cryptomode() {
@@ -797,23 +906,37 @@ mod test {
         ]
         .map(CryptoMode::to_request_str);
         assert_eq!(
-            CryptoMode::negotiate(test_set, None).unwrap(),
+            CryptoMode::negotiate(test_set, &[]).unwrap(),
             CryptoMode::XChaCha20Poly1305
         );
         assert_eq!(
-            CryptoMode::negotiate(test_set, Some(CryptoMode::Aes256Gcm)).unwrap(),
+            CryptoMode::negotiate(test_set, &[CryptoMode::Aes256Gcm]).unwrap(),
             CryptoMode::XChaCha20Poly1305
         );
 
         // Preference wins in spite of the defined `priority` value.
         assert_eq!(
-            CryptoMode::negotiate(test_set, Some(CryptoMode::Suffix)).unwrap(),
+            CryptoMode::negotiate(test_set, &[CryptoMode::Suffix]).unwrap(),
             CryptoMode::Suffix
         );
 
+        // Preferences are tried in order, skipping those the server does not offer.
+        assert_eq!(
+            CryptoMode::negotiate(
+                test_set,
+                &[
+                    CryptoMode::Aes256Gcm,
+                    CryptoMode::Lite,
+                    CryptoMode::XChaCha20Poly1305
+                ]
+            )
+            .unwrap(),
+            CryptoMode::Lite
+        );
+
         // If there is no mutual intelligibility, return an error.
         let bad_modes = ["not_real", "des", "rc5"];
-        assert!(CryptoMode::negotiate(&bad_modes, None).is_err());
-        assert!(CryptoMode::negotiate(&bad_modes, Some(CryptoMode::Aes256Gcm)).is_err());
+        assert!(CryptoMode::negotiate(&bad_modes, &[]).is_err());
+        assert!(CryptoMode::negotiate(&bad_modes, &[CryptoMode::Aes256Gcm]).is_err());
     }
 }
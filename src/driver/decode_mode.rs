@@ -1,4 +1,5 @@
 use audiopus::{Channels as OpusChannels, SampleRate as OpusRate};
+use std::num::NonZeroUsize;
 
 /// Decode behaviour for received RTP packets within the driver.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
@@ -27,6 +28,42 @@ impl DecodeMode {
     }
 }
 
+/// Behaviour of the driver when a received packet fails to decrypt.
+///
+/// Occasional failures are expected due to corrupted or spoofed packets, but
+/// a long run of failures from a user usually indicates that the session key
+/// has fallen out of sync with Discord.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum DecryptFailurePolicy {
+    /// Failed packets are logged and dropped.
+    ///
+    /// The default choice.
+    #[default]
+    Drop,
+    /// Failed packets are dropped, and a [`CoreEvent::DecryptFail`] is fired
+    /// for each failure.
+    ///
+    /// [`CoreEvent::DecryptFail`]: crate::events::CoreEvent::DecryptFail
+    Notify,
+    /// As [`Self::Notify`], but the driver will perform a full reconnect (negotiating
+    /// a new session key) after this many consecutive failures from any one SSRC.
+    Reconnect(NonZeroUsize),
+}
+
+impl DecryptFailurePolicy {
+    pub(crate) fn should_notify(self) -> bool {
+        self != Self::Drop
+    }
+
+    pub(crate) fn reconnect_after(self) -> Option<usize> {
+        match self {
+            Self::Reconnect(n) => Some(n.get()),
+            _ => None,
+        }
+    }
+}
+
 /// The channel layout of output audio when using [`DecodeMode::Decode`].
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
 #[non_exhaustive]
@@ -80,6 +117,18 @@ pub enum SampleRate {
     Hz48000,
 }
 
+impl SampleRate {
+    pub(crate) fn hz(self) -> u32 {
+        match self {
+            SampleRate::Hz8000 => 8_000,
+            SampleRate::Hz12000 => 12_000,
+            SampleRate::Hz16000 => 16_000,
+            SampleRate::Hz24000 => 24_000,
+            SampleRate::Hz48000 => 48_000,
+        }
+    }
+}
+
 impl From<SampleRate> for OpusRate {
     fn from(value: SampleRate) -> Self {
         match value {
@@ -91,3 +140,30 @@ impl From<SampleRate> for OpusRate {
         }
     }
 }
+
+/// The sample format of output audio when using [`DecodeMode::Decode`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum SampleFormat {
+    /// Signed 16-bit integer samples.
+    ///
+    /// The default choice.
+    #[default]
+    I16,
+    /// 32-bit float samples, in the range `-1.0..=1.0`.
+    F32,
+}
+
+/// The arrangement of channels in output audio when using [`DecodeMode::Decode`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum SampleLayout {
+    /// Samples from each channel alternate (i.e., `L, R, L, R, ...`).
+    ///
+    /// The default choice.
+    #[default]
+    Interleaved,
+    /// All samples from each channel are stored contiguously, one channel after
+    /// another (i.e., `L, L, ..., R, R, ...`).
+    Planar,
+}
@@ -0,0 +1,250 @@
+use crate::constants::SAMPLE_RATE_RAW;
+use std::time::Duration;
+
+/// Settings for a master-bus compressor and brickwall limiter, applied to mixed
+/// audio before it is encoded.
+///
+/// Summing several loud tracks can easily exceed full scale, which is otherwise
+/// only tamed by [soft-clipping]. The compressor first smoothly reduces the level
+/// of loud passages, using a soft knee around `threshold`. The limiter then
+/// guarantees that no sample exceeds `ceiling`.
+///
+/// Opus passthrough frames are not processed.
+///
+/// [soft-clipping]: crate::Config::use_softclip
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct Dynamics {
+    /// Level above which the compressor reduces gain, in dBFS.
+    ///
+    /// Defaults to `-12.0`.
+    pub threshold: f32,
+    /// Ratio of input level change to output level change above `threshold`.
+    ///
+    /// Values below `1.0` are treated as `1.0` (no compression). Defaults to `4.0`.
+    pub ratio: f32,
+    /// Width of the soft knee centred on `threshold`, in dB.
+    ///
+    /// Defaults to `6.0`.
+    pub knee: f32,
+    /// Time taken for the compressor to react to louder audio.
+    ///
+    /// Defaults to 5ms.
+    pub attack: Duration,
+    /// Time taken for the compressor and limiter to recover once audio is quieter.
+    ///
+    /// Defaults to 100ms.
+    pub release: Duration,
+    /// Gain applied after compression, in dB.
+    ///
+    /// Defaults to `0.0`.
+    pub makeup: f32,
+    /// Peak level which the limiter never allows output to exceed, in dBFS.
+    ///
+    /// Defaults to `-1.0`.
+    pub ceiling: f32,
+}
+
+impl Default for Dynamics {
+    fn default() -> Self {
+        Self {
+            threshold: -12.0,
+            ratio: 4.0,
+            knee: 6.0,
+            attack: Duration::from_millis(5),
+            release: Duration::from_millis(100),
+            makeup: 0.0,
+            ceiling: -1.0,
+        }
+    }
+}
+
+impl Dynamics {
+    /// Sets the level above which the compressor reduces gain, in dBFS.
+    #[must_use]
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets the compression ratio above the threshold.
+    #[must_use]
+    pub fn ratio(mut self, ratio: f32) -> Self {
+        self.ratio = ratio;
+        self
+    }
+
+    /// Sets the width of the compressor's soft knee, in dB.
+    #[must_use]
+    pub fn knee(mut self, knee: f32) -> Self {
+        self.knee = knee;
+        self
+    }
+
+    /// Sets how quickly the compressor reacts to louder audio.
+    #[must_use]
+    pub fn attack(mut self, attack: Duration) -> Self {
+        self.attack = attack;
+        self
+    }
+
+    /// Sets how quickly gain recovers once audio is quieter.
+    #[must_use]
+    pub fn release(mut self, release: Duration) -> Self {
+        self.release = release;
+        self
+    }
+
+    /// Sets the gain applied after compression, in dB.
+    #[must_use]
+    pub fn makeup(mut self, makeup: f32) -> Self {
+        self.makeup = makeup;
+        self
+    }
+
+    /// Sets the peak level which output may never exceed, in dBFS.
+    #[must_use]
+    pub fn ceiling(mut self, ceiling: f32) -> Self {
+        self.ceiling = ceiling;
+        self
+    }
+
+    /// Returns the compressor's gain change for a given input level, in dB.
+    fn gain_reduction(&self, level: f32) -> f32 {
+        let ratio = self.ratio.max(1.0);
+        let knee = self.knee.max(0.0);
+        let over = level - self.threshold;
+
+        let out = if 2.0 * over < -knee {
+            level
+        } else if 2.0 * over.abs() <= knee && knee > 0.0 {
+            level + (1.0 / ratio - 1.0) * (over + knee / 2.0).powi(2) / (2.0 * knee)
+        } else {
+            self.threshold + over / ratio
+        };
+
+        out - level
+    }
+}
+
+/// Running envelope state for a [`Dynamics`] stage.
+#[derive(Debug)]
+pub(crate) struct DynamicsState {
+    settings: Dynamics,
+    attack_coeff: f32,
+    release_coeff: f32,
+    ceiling: f32,
+    /// Smoothed compressor gain change, in dB.
+    envelope: f32,
+    /// Current limiter gain, as a linear factor.
+    limit_gain: f32,
+}
+
+impl DynamicsState {
+    pub(crate) fn new(settings: Dynamics) -> Self {
+        Self {
+            settings,
+            attack_coeff: time_coeff(settings.attack),
+            release_coeff: time_coeff(settings.release),
+            ceiling: db_to_gain(settings.ceiling),
+            envelope: 0.0,
+            limit_gain: 1.0,
+        }
+    }
+
+    pub(crate) fn settings(&self) -> Dynamics {
+        self.settings
+    }
+
+    /// Compresses and limits interleaved audio in place.
+    ///
+    /// All channels share one gain, preserving the stereo image.
+    pub(crate) fn process(&mut self, samples: &mut [f32], channels: usize) {
+        for frame in samples.chunks_exact_mut(channels.max(1)) {
+            let peak = frame.iter().fold(0.0f32, |acc, s| acc.max(s.abs()));
+            let level = 20.0 * peak.max(1e-9).log10();
+
+            let target = self.settings.gain_reduction(level);
+            let coeff = if target < self.envelope {
+                self.attack_coeff
+            } else {
+                self.release_coeff
+            };
+            self.envelope = coeff * self.envelope + (1.0 - coeff) * target;
+
+            let gain = db_to_gain(self.envelope + self.settings.makeup);
+            let peak = peak * gain;
+
+            // The limiter reacts instantly, so no sample can pass the ceiling.
+            self.limit_gain += (1.0 - self.limit_gain) * (1.0 - self.release_coeff);
+            if peak * self.limit_gain > self.ceiling {
+                self.limit_gain = self.ceiling / peak;
+            }
+
+            let gain = gain * self.limit_gain;
+            for sample in frame {
+                *sample *= gain;
+            }
+        }
+    }
+}
+
+fn db_to_gain(db: f32) -> f32 {
+    10.0f32.powf(db / 20.0)
+}
+
+/// Returns the one-pole smoothing coefficient for a given time constant.
+fn time_coeff(time: Duration) -> f32 {
+    let samples = time.as_secs_f32() * SAMPLE_RATE_RAW as f32;
+
+    if samples > 0.0 {
+        (-1.0 / samples).exp()
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{MONO_FRAME_SIZE, STEREO_FRAME_SIZE};
+
+    fn sine(amplitude: f32, len: usize) -> Vec<f32> {
+        (0..len)
+            .map(|i| {
+                let t = (i / 2) as f32 / SAMPLE_RATE_RAW as f32;
+                amplitude * (2.0 * std::f32::consts::PI * 440.0 * t).sin()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn output_never_exceeds_ceiling() {
+        let settings = Dynamics::default();
+        let mut state = DynamicsState::new(settings);
+        let ceiling = db_to_gain(settings.ceiling);
+
+        // Several hot tracks summed together, far beyond full scale.
+        let mut audio = sine(6.0, 50 * STEREO_FRAME_SIZE);
+        for frame in audio.chunks_mut(STEREO_FRAME_SIZE) {
+            state.process(frame, 2);
+        }
+
+        assert!(audio.iter().all(|s| s.abs() <= ceiling + 1e-6));
+    }
+
+    #[test]
+    fn quiet_audio_is_untouched() {
+        let mut state = DynamicsState::new(Dynamics::default());
+
+        let original = sine(0.05, 10 * MONO_FRAME_SIZE);
+        let mut audio = original.clone();
+        for frame in audio.chunks_mut(MONO_FRAME_SIZE) {
+            state.process(frame, 1);
+        }
+
+        for (a, b) in audio.iter().zip(&original) {
+            assert!((a - b).abs() < 1e-4);
+        }
+    }
+}
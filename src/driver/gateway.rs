@@ -0,0 +1,109 @@
+use crate::Config;
+use async_trait::async_trait;
+use std::{
+    error::Error as StdError,
+    fmt::{Debug, Formatter, Result as FmtResult},
+    net::UdpSocket,
+    sync::Arc,
+};
+
+/// Boxed error returned by external gateways and packet protectors.
+pub type GatewayError = Box<dyn StdError + Send + Sync + 'static>;
+
+/// A non-Discord voice endpoint which the driver can send RTP audio to,
+/// such as an SFU's RTP ingress (e.g., Janus or LiveKit).
+///
+/// Implementors perform any signalling needed to set up a media session
+/// (e.g., SDP offer/answer, or a REST call to create an ingress), and then
+/// hand the driver a connected UDP socket to send to. The driver then mixes,
+/// queues, and sends audio exactly as it would in a Discord call.
+///
+/// Packets are standard RTP carrying 20ms stereo Opus frames with dynamic
+/// payload type `120`, which the remote end should be told to expect. Any
+/// [RTP header extensions] set on the driver are also sent. Discord-specific
+/// behaviour (UDP keepalives, speaking updates, voice receive, and gateway
+/// events such as [`CoreEvent::DriverConnect`]) is disabled for these sessions.
+///
+/// Gateways are used via [`Driver::connect_gateway`]. Discord calls made with
+/// [`Driver::connect`] are unaffected.
+///
+/// [RTP header extensions]: crate::driver::Driver::set_rtp_extensions
+/// [`CoreEvent::DriverConnect`]: crate::events::CoreEvent::DriverConnect
+/// [`Driver::connect_gateway`]: crate::driver::Driver::connect_gateway
+/// [`Driver::connect`]: crate::driver::Driver::connect
+#[async_trait]
+pub trait VoiceGateway: Send + Sync {
+    /// Establishes a media session with this gateway.
+    ///
+    /// This is bounded by [`Config::driver_timeout`].
+    async fn connect(&self, config: &Config) -> Result<GatewaySession, GatewayError>;
+
+    /// Called when the driver leaves a session created by this gateway, to allow
+    /// any teardown signalling.
+    ///
+    /// This must not block.
+    fn disconnect(&self) {}
+}
+
+/// Protects outbound RTP packets for an external gateway, e.g., with SRTP.
+///
+/// Songbird does not include an SRTP implementation: gateways requiring one should
+/// wrap an SRTP context keyed during their handshake (e.g., via DTLS-SRTP).
+pub trait RtpProtector: Send + Sync {
+    /// Number of bytes [`Self::protect`] adds to the end of each packet,
+    /// such as an SRTP authentication tag.
+    fn overhead(&self) -> usize;
+
+    /// Protects the RTP packet held in `packet[..len]` in place, returning its
+    /// new length.
+    ///
+    /// `packet` holds at least [`Self::overhead`] spare bytes after `len`.
+    ///
+    /// # Errors
+    ///
+    /// Errors cause the current packet to be dropped.
+    fn protect(&self, packet: &mut [u8], len: usize) -> Result<usize, GatewayError>;
+}
+
+/// A media session established by a [`VoiceGateway`].
+#[non_exhaustive]
+pub struct GatewaySession {
+    /// UDP socket used to send RTP packets, connected to the remote endpoint.
+    pub socket: UdpSocket,
+    /// SSRC to place on all outbound packets.
+    pub ssrc: u32,
+    /// Protection applied to each packet before it is sent.
+    ///
+    /// If `None`, packets are sent as plain RTP.
+    pub protector: Option<Arc<dyn RtpProtector>>,
+}
+
+impl GatewaySession {
+    /// Creates a session sending plain RTP with the given SSRC over a
+    /// connected UDP socket.
+    #[must_use]
+    pub fn new(socket: UdpSocket, ssrc: u32) -> Self {
+        Self {
+            socket,
+            ssrc,
+            protector: None,
+        }
+    }
+
+    /// Sets [`Self::protector`] in a manner that allows method chaining.
+    #[must_use]
+    pub fn protector(mut self, protector: Arc<dyn RtpProtector>) -> Self {
+        self.protector = Some(protector);
+        self
+    }
+}
+
+impl Debug for GatewaySession {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("GatewaySession")
+            .field("socket", &self.socket)
+            .field("ssrc", &self.ssrc)
+            .field("protector", &self.protector.as_ref().map(|_| "<protector>"))
+            .finish()
+    }
+}
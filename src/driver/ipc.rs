@@ -0,0 +1,408 @@
+//! Experimental control of a [`Driver`] from another process, over a Unix socket.
+//!
+//! A worker process which owns a voice connection exposes its driver with
+//! [`Driver::serve_ipc`]. A supervisor process can then connect with an [`IpcClient`]
+//! to play, stop, seek, and change the volume of tracks, allowing (for instance) a
+//! bot to restart without tearing down its voice connections.
+//!
+//! Each [`IpcCommand`] and [`IpcReply`] is sent as one line of JSON. Tracks are
+//! identified by the UUIDs of their [`TrackHandle`]s, and remain controllable by any
+//! client until stopped, so a restarted supervisor can resume control of existing
+//! tracks.
+//!
+//! *This API is experimental, and its wire format may change between minor releases.*
+//!
+//! [`Driver`]: super::Driver
+//! [`Driver::serve_ipc`]: super::Driver::serve_ipc
+
+use super::tasks::message::CoreMessage;
+use crate::{
+    input::{File, HttpRequest, Input},
+    tracks::{Track, TrackHandle},
+};
+use flume::Sender;
+use parking_lot::Mutex;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    error::Error as StdError,
+    fmt::{Display, Formatter, Result as FmtResult},
+    io::Error as IoError,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    net::{
+        unix::{OwnedReadHalf, OwnedWriteHalf},
+        UnixListener,
+        UnixStream,
+    },
+    task::{JoinHandle, JoinSet},
+};
+use tracing::{debug, instrument};
+use uuid::Uuid;
+
+/// An audio source for a worker process to open and play.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum IpcSource {
+    /// A file, local to the worker process.
+    File {
+        /// Path to the file.
+        path: PathBuf,
+    },
+    /// A remote file, fetched over HTTP(S) by the worker process.
+    Http {
+        /// URL of the file.
+        url: String,
+    },
+}
+
+/// A control message sent from an [`IpcClient`] to a worker's driver.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum IpcCommand {
+    /// Plays a new track alongside any others, replying with its ID.
+    Play {
+        /// Audio to play.
+        source: IpcSource,
+    },
+    /// Stops a track, after which it can no longer be controlled.
+    Stop {
+        /// ID of the target track.
+        track: Uuid,
+    },
+    /// Seeks a track to a new position.
+    Seek {
+        /// ID of the target track.
+        track: Uuid,
+        /// Position to seek to.
+        position: Duration,
+    },
+    /// Changes the volume of a track.
+    Volume {
+        /// ID of the target track.
+        track: Uuid,
+        /// New volume, where `1.0` is unchanged.
+        volume: f32,
+    },
+}
+
+/// A worker's reply to an [`IpcCommand`].
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum IpcReply {
+    /// The command succeeded.
+    Ok {
+        /// ID of a newly created track.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        track: Option<Uuid>,
+    },
+    /// The command could not be carried out.
+    Err {
+        /// Description of the failure.
+        message: String,
+    },
+}
+
+/// Errors encountered while controlling a driver over IPC.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum IpcError {
+    /// The socket could not be used.
+    Io(IoError),
+    /// A message could not be encoded or decoded.
+    Json(serde_json::Error),
+    /// The worker closed the connection.
+    Closed,
+    /// The worker failed to carry out a command.
+    Remote(String),
+}
+
+impl Display for IpcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(f, "failed to control driver over IPC: ")?;
+        match self {
+            Self::Io(e) => write!(f, "i/o error ({e})"),
+            Self::Json(e) => write!(f, "malformed message ({e})"),
+            Self::Closed => write!(f, "connection closed by worker"),
+            Self::Remote(e) => write!(f, "command failed ({e})"),
+        }
+    }
+}
+
+impl StdError for IpcError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Json(e) => Some(e),
+            Self::Closed | Self::Remote(_) => None,
+        }
+    }
+}
+
+impl From<IoError> for IpcError {
+    fn from(e: IoError) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for IpcError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Json(e)
+    }
+}
+
+/// A running IPC endpoint for a [`Driver`], created by [`Driver::serve_ipc`].
+///
+/// The endpoint, and all connections to it, are closed when this is dropped.
+///
+/// [`Driver`]: super::Driver
+/// [`Driver::serve_ipc`]: super::Driver::serve_ipc
+#[derive(Debug)]
+pub struct IpcServer {
+    path: PathBuf,
+    task: JoinHandle<()>,
+}
+
+impl IpcServer {
+    pub(crate) fn bind(path: &Path, core: Sender<CoreMessage>) -> Result<Self, IoError> {
+        let listener = UnixListener::bind(path)?;
+        let state = Arc::new(ServerState {
+            core,
+            client: Client::new(),
+            tracks: Mutex::default(),
+        });
+
+        Ok(Self {
+            path: path.into(),
+            task: tokio::spawn(accept_loop(listener, state)),
+        })
+    }
+
+    /// Returns the path of the socket which clients connect to.
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        self.task.abort();
+        drop(std::fs::remove_file(&self.path));
+    }
+}
+
+struct ServerState {
+    core: Sender<CoreMessage>,
+    client: Client,
+    tracks: Mutex<HashMap<Uuid, TrackHandle>>,
+}
+
+impl ServerState {
+    async fn handle(&self, cmd: IpcCommand) -> Result<Option<Uuid>, String> {
+        let (track, out) = match cmd {
+            IpcCommand::Play { source } => return self.play(source).map(Some),
+            IpcCommand::Stop { track } => {
+                let handle = self.tracks.lock().remove(&track);
+                (track, known(track, handle)?.stop())
+            },
+            IpcCommand::Seek { track, position } => {
+                let handle = self.tracks.lock().get(&track).cloned();
+                let out = known(track, handle)?.seek_async(position).await;
+                (track, out.map(|_| ()))
+            },
+            IpcCommand::Volume { track, volume } => {
+                let handle = self.tracks.lock().get(&track).cloned();
+                (track, known(track, handle)?.set_volume(volume))
+            },
+        };
+
+        out.map(|()| None).map_err(|e| {
+            // Ended tracks cannot be revived, so stop tracking them.
+            self.tracks.lock().remove(&track);
+            e.to_string()
+        })
+    }
+
+    fn play(&self, source: IpcSource) -> Result<Uuid, String> {
+        let input: Input = match source {
+            IpcSource::File { path } => File::new(path).into(),
+            IpcSource::Http { url } => HttpRequest::new(self.client.clone(), url).into(),
+        };
+
+        let (handle, ctx) = Track::from(input).into_context();
+        self.core
+            .send(CoreMessage::AddTrack(ctx))
+            .map_err(|_| "driver has shut down".to_string())?;
+
+        let uuid = handle.uuid();
+        self.tracks.lock().insert(uuid, handle);
+
+        Ok(uuid)
+    }
+}
+
+fn known(track: Uuid, handle: Option<TrackHandle>) -> Result<TrackHandle, String> {
+    handle.ok_or_else(|| format!("no track with ID {track}"))
+}
+
+async fn accept_loop(listener: UnixListener, state: Arc<ServerState>) {
+    let mut conns = JoinSet::new();
+
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    conns.spawn(serve_conn(stream, state.clone()));
+                },
+                Err(e) => {
+                    debug!("Failed to accept IPC connection: {e:?}");
+                },
+            },
+            Some(_) = conns.join_next() => {},
+        }
+    }
+}
+
+async fn serve_conn(stream: UnixStream, state: Arc<ServerState>) {
+    let (rx, mut tx) = stream.into_split();
+    let mut lines = BufReader::new(rx).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let reply = match serde_json::from_str(&line) {
+            Ok(cmd) => match state.handle(cmd).await {
+                Ok(track) => IpcReply::Ok { track },
+                Err(message) => IpcReply::Err { message },
+            },
+            Err(e) => IpcReply::Err {
+                message: e.to_string(),
+            },
+        };
+
+        if write_line(&mut tx, &reply).await.is_err() {
+            break;
+        }
+    }
+}
+
+async fn write_line<T: Serialize>(tx: &mut OwnedWriteHalf, msg: &T) -> Result<(), IpcError> {
+    let mut bytes = serde_json::to_vec(msg)?;
+    bytes.push(b'\n');
+    tx.write_all(&bytes).await?;
+
+    Ok(())
+}
+
+/// A connection to a [`Driver`] in another process, exposed by [`Driver::serve_ipc`].
+///
+/// [`Driver`]: super::Driver
+/// [`Driver::serve_ipc`]: super::Driver::serve_ipc
+#[derive(Debug)]
+pub struct IpcClient {
+    rx: Lines<BufReader<OwnedReadHalf>>,
+    tx: OwnedWriteHalf,
+}
+
+impl IpcClient {
+    /// Connects to a driver's IPC socket.
+    #[instrument]
+    pub async fn connect(path: impl AsRef<Path> + std::fmt::Debug) -> Result<Self, IpcError> {
+        let (rx, tx) = UnixStream::connect(path).await?.into_split();
+
+        Ok(Self {
+            rx: BufReader::new(rx).lines(),
+            tx,
+        })
+    }
+
+    /// Sends a command to the driver, returning the ID of any new track.
+    pub async fn send(&mut self, cmd: &IpcCommand) -> Result<Option<Uuid>, IpcError> {
+        write_line(&mut self.tx, cmd).await?;
+
+        let line = self.rx.next_line().await?.ok_or(IpcError::Closed)?;
+        match serde_json::from_str(&line)? {
+            IpcReply::Ok { track } => Ok(track),
+            IpcReply::Err { message } => Err(IpcError::Remote(message)),
+        }
+    }
+
+    /// Plays a new track in the driver, returning its ID.
+    pub async fn play(&mut self, source: IpcSource) -> Result<Uuid, IpcError> {
+        self.send(&IpcCommand::Play { source })
+            .await?
+            .ok_or_else(|| IpcError::Remote("no track ID returned".into()))
+    }
+
+    /// Stops a track.
+    pub async fn stop(&mut self, track: Uuid) -> Result<(), IpcError> {
+        self.send(&IpcCommand::Stop { track }).await.map(|_| ())
+    }
+
+    /// Seeks a track to a new position, once the driver has completed the seek.
+    pub async fn seek(&mut self, track: Uuid, position: Duration) -> Result<(), IpcError> {
+        self.send(&IpcCommand::Seek { track, position })
+            .await
+            .map(|_| ())
+    }
+
+    /// Changes the volume of a track.
+    pub async fn set_volume(&mut self, track: Uuid, volume: f32) -> Result<(), IpcError> {
+        self.send(&IpcCommand::Volume { track, volume })
+            .await
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::test_data::FILE_WAV_TARGET, driver::Driver, tracks::PlayMode, Config};
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn client_controls_tracks_over_socket() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config);
+        t_handle.spawn_ticker();
+
+        let path = std::env::temp_dir().join(format!("songbird-ipc-{}.sock", std::process::id()));
+        drop(std::fs::remove_file(&path));
+        let server = driver.serve_ipc(&path).unwrap();
+
+        let mut client = IpcClient::connect(server.path()).await.unwrap();
+        let track = client
+            .play(IpcSource::File {
+                path: FILE_WAV_TARGET.into(),
+            })
+            .await
+            .unwrap();
+        client.set_volume(track, 0.5).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let tracks = driver.tracks().await;
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].0.uuid(), track);
+        assert!((tracks[0].1.volume - 0.5).abs() < f32::EPSILON);
+
+        // A restarted supervisor may take over control of existing tracks.
+        drop(client);
+        let mut client = IpcClient::connect(server.path()).await.unwrap();
+        client.stop(track).await.unwrap();
+        assert!(matches!(client.stop(track).await, Err(IpcError::Remote(_))));
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(driver
+            .tracks()
+            .await
+            .iter()
+            .all(|(_, state)| state.playing == PlayMode::Stop));
+    }
+}
@@ -0,0 +1,95 @@
+use std::num::NonZeroUsize;
+
+/// Presets trading receive latency against robustness to network jitter.
+///
+/// Selecting a preset via [`Config::latency`] sets each of the receive buffering
+/// options it covers, which may then be overridden individually:
+///
+/// | Preset       | [`playout_buffer_length`] | [`playout_spike_length`] |
+/// |--------------|---------------------------|--------------------------|
+/// | [`Lowest`]   | 2 packets (40ms)          | 1 packet                 |
+/// | [`Balanced`] | 5 packets (100ms)         | 3 packets                |
+/// | [`Robust`]   | 10 packets (200ms)        | 8 packets                |
+///
+/// [`DecodeMode`] is not affected, as it controls which received audio is
+/// made available rather than how long it is held.
+///
+/// [`Config::latency`]: crate::Config::latency
+/// [`playout_buffer_length`]: crate::Config::playout_buffer_length
+/// [`playout_spike_length`]: crate::Config::playout_spike_length
+/// [`Lowest`]: Self::Lowest
+/// [`Balanced`]: Self::Balanced
+/// [`Robust`]: Self::Robust
+/// [`DecodeMode`]: super::DecodeMode
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Latency {
+    /// Holds as little audio as possible before playout.
+    ///
+    /// Suited to local or wired networks: packets arriving more than 40ms late
+    /// are concealed rather than played.
+    Lowest,
+    /// Matches the default buffering of [`Config`].
+    ///
+    /// This is the default choice.
+    ///
+    /// [`Config`]: crate::Config
+    #[default]
+    Balanced,
+    /// Holds extra audio to ride out bursty or congested networks, at the cost
+    /// of 200ms of delay.
+    Robust,
+}
+
+impl Latency {
+    /// Returns the number of packets this preset buffers for each user before playout.
+    #[must_use]
+    pub fn playout_buffer_length(self) -> NonZeroUsize {
+        let len = match self {
+            Self::Lowest => 2,
+            Self::Balanced => 5,
+            Self::Robust => 10,
+        };
+
+        NonZeroUsize::new(len).expect("Preset playout lengths are non-zero.")
+    }
+
+    /// Returns the extra packet capacity this preset allocates to absorb bursts.
+    #[must_use]
+    pub fn playout_spike_length(self) -> usize {
+        match self {
+            Self::Lowest => 1,
+            Self::Balanced => 3,
+            Self::Robust => 8,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    #[test]
+    fn presets_apply_and_allow_overrides() {
+        let config = Config::default();
+        assert_eq!(
+            config.playout_buffer_length,
+            Latency::default().playout_buffer_length()
+        );
+        assert_eq!(
+            config.playout_spike_length,
+            Latency::default().playout_spike_length()
+        );
+
+        let config = Config::default().latency(Latency::Robust);
+        assert_eq!(config.playout_buffer_length.get(), 10);
+        assert_eq!(config.playout_spike_length, 8);
+
+        let config = Config::default()
+            .latency(Latency::Lowest)
+            .playout_spike_length(4);
+        assert_eq!(config.playout_buffer_length.get(), 2);
+        assert_eq!(config.playout_spike_length, 4);
+    }
+}
@@ -1,15 +1,17 @@
 use audiopus::Channels;
-use symphonia_core::audio::Layout;
+use symphonia_core::audio::{Channels as SymphChannels, Layout};
 
 use crate::constants::{MONO_FRAME_SIZE, STEREO_FRAME_SIZE};
 
-/// Mixing behaviour for sent audio sources processed within the driver.
-///
-/// This has no impact on Opus packet passthrough, which will pass packets
-/// irrespective of their channel count.
+/// Mixing and encoding behaviour for sent audio, which sets the channel count of
+/// all audio sent by the driver.
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum MixMode {
-    /// Audio sources will be downmixed into a mono buffer.
+    /// Audio sources will be downmixed into a mono buffer, and sent as mono Opus.
+    ///
+    /// This roughly halves the bandwidth needed for audio where stereo is of no
+    /// benefit, such as TTS or announcements. Stereo Opus sources are decoded and
+    /// downmixed rather than passed through.
     Mono,
     /// Audio sources will be mixed into into a stereo buffer, where mono sources
     /// will be duplicated into both channels.
@@ -57,3 +59,108 @@ impl From<MixMode> for Channels {
         val.to_opus()
     }
 }
+
+/// Downmixing behaviour for sources with more than two channels (e.g., 5.1 or 7.1
+/// audio), used when mixing them into the driver's [`MixMode`].
+///
+/// Mono and stereo sources are unaffected by this setting.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum DownmixMode {
+    /// Channels are weighted by their position using ITU-R BS.775 coefficients.
+    ///
+    /// Front channels map directly to their side, centre channels are split
+    /// across both sides at -3dB, surround and rear channels are added to their
+    /// side at -3dB, and LFE channels are discarded. The result is normalised
+    /// to prevent clipping.
+    #[default]
+    Itu,
+    /// All channels are averaged with equal weight into every output channel.
+    Average,
+}
+
+impl DownmixMode {
+    /// Returns the `[left, right]` gain applied to each channel of a source
+    /// with the given layout, in channel order.
+    pub(crate) fn gains(self, channels: SymphChannels) -> impl Iterator<Item = [f32; 2]> {
+        let chan_count = channels.count() as f32;
+        let (l_total, r_total) = channels
+            .iter()
+            .map(|c| self.raw_gain(c, chan_count))
+            .fold((0.0, 0.0), |(l, r), [gl, gr]| (l + gl, r + gr));
+        let norm = 1.0 / l_total.max(r_total).max(1.0);
+
+        channels.iter().map(move |c| {
+            let [l, r] = self.raw_gain(c, chan_count);
+            [l * norm, r * norm]
+        })
+    }
+
+    fn raw_gain(self, channel: SymphChannels, chan_count: f32) -> [f32; 2] {
+        const MINUS_3DB: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+        let centre = SymphChannels::FRONT_CENTRE
+            | SymphChannels::REAR_CENTRE
+            | SymphChannels::TOP_CENTRE
+            | SymphChannels::TOP_FRONT_CENTRE
+            | SymphChannels::TOP_REAR_CENTRE
+            | SymphChannels::FRONT_CENTRE_HIGH;
+        let left = SymphChannels::FRONT_LEFT_CENTRE
+            | SymphChannels::REAR_LEFT
+            | SymphChannels::REAR_LEFT_CENTRE
+            | SymphChannels::SIDE_LEFT
+            | SymphChannels::TOP_FRONT_LEFT
+            | SymphChannels::TOP_REAR_LEFT
+            | SymphChannels::FRONT_LEFT_WIDE
+            | SymphChannels::FRONT_LEFT_HIGH;
+        let right = SymphChannels::FRONT_RIGHT_CENTRE
+            | SymphChannels::REAR_RIGHT
+            | SymphChannels::REAR_RIGHT_CENTRE
+            | SymphChannels::SIDE_RIGHT
+            | SymphChannels::TOP_FRONT_RIGHT
+            | SymphChannels::TOP_REAR_RIGHT
+            | SymphChannels::FRONT_RIGHT_WIDE
+            | SymphChannels::FRONT_RIGHT_HIGH;
+
+        match self {
+            Self::Average => [1.0 / chan_count; 2],
+            Self::Itu if channel == SymphChannels::FRONT_LEFT => [1.0, 0.0],
+            Self::Itu if channel == SymphChannels::FRONT_RIGHT => [0.0, 1.0],
+            Self::Itu if centre.contains(channel) => [MINUS_3DB; 2],
+            Self::Itu if left.contains(channel) => [MINUS_3DB, 0.0],
+            Self::Itu if right.contains(channel) => [0.0, MINUS_3DB],
+            Self::Itu => [0.0; 2],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn itu_downmix_weights_surround_channels() {
+        let five_one = SymphChannels::FRONT_LEFT
+            | SymphChannels::FRONT_RIGHT
+            | SymphChannels::FRONT_CENTRE
+            | SymphChannels::LFE1
+            | SymphChannels::REAR_LEFT
+            | SymphChannels::REAR_RIGHT;
+
+        let gains: Vec<_> = DownmixMode::Itu.gains(five_one).collect();
+        let norm = 1.0 / (1.0 + 2.0 * std::f32::consts::FRAC_1_SQRT_2);
+
+        assert_eq!(gains.len(), 6);
+        assert!((gains[0][0] - norm).abs() < 1e-6);
+        assert!(gains[0][1].abs() < 1e-6);
+        assert!((gains[2][0] - gains[2][1]).abs() < 1e-6);
+        assert!(gains[3].iter().all(|g| g.abs() < 1e-6));
+        assert!(gains[4][1].abs() < 1e-6);
+        assert!((gains.iter().map(|g| g[0]).sum::<f32>() - 1.0).abs() < 1e-6);
+
+        let avg: Vec<_> = DownmixMode::Average.gains(five_one).collect();
+        assert!(avg
+            .iter()
+            .all(|g| (g[0] - 1.0 / 6.0).abs() < 1e-6 && (g[0] - g[1]).abs() < 1e-6));
+    }
+}
@@ -11,25 +11,67 @@
 #[cfg(feature = "internals")]
 pub mod bench_internals;
 
+mod analysis;
+mod bandwidth;
 pub(crate) mod connection;
+#[cfg(feature = "receive")]
+mod consent;
 mod crypto;
 #[cfg(feature = "receive")]
 mod decode_mode;
+mod dynamics;
+mod gateway;
+#[cfg(all(feature = "ipc", unix))]
+pub mod ipc;
+#[cfg(feature = "receive")]
+mod latency;
 mod mix_mode;
+mod overload;
+#[cfg(feature = "receive")]
+mod recorder;
 pub mod retry;
+#[cfg(feature = "rtp-control")]
+mod rtp_control;
+pub mod rtp_extension;
 mod scheduler;
+mod session;
+mod silence;
+mod spawner;
 pub(crate) mod tasks;
 #[cfg(test)]
 pub(crate) mod test_config;
 #[cfg(any(test, feature = "internals"))]
 mod test_impls;
+#[cfg(feature = "receive")]
+mod transcriber;
+mod udp_send;
+mod virtual_clock;
 
+pub(crate) use analysis::AnalysisTap;
+pub use analysis::{Analysis, AnalysisFrame, AnalysisKind};
+pub(crate) use bandwidth::{bitrate_fits, limit_bitrate, packet_overhead_bps, BandwidthShare};
+pub use bandwidth::{BandwidthCap, SharedBandwidth};
 use connection::error::{Error, Result};
+#[cfg(feature = "receive")]
+pub use consent::ReceiveConsent;
+#[cfg(any(test, feature = "mock-server"))]
+pub(crate) use crypto::Cipher;
 pub use crypto::CryptoMode;
 pub(crate) use crypto::CryptoState;
 #[cfg(feature = "receive")]
 pub use decode_mode::*;
-pub use mix_mode::MixMode;
+pub use dynamics::Dynamics;
+pub(crate) use dynamics::DynamicsState;
+pub use gateway::{GatewayError, GatewaySession, RtpProtector, VoiceGateway};
+#[cfg(feature = "receive")]
+pub use latency::Latency;
+pub use mix_mode::{DownmixMode, MixMode};
+pub use overload::{OverloadPolicy, OverloadStrategy};
+#[cfg(feature = "receive")]
+pub use recorder::{DirectoryRecorder, OggOpusWriter, Recorder};
+#[cfg(feature = "rtp-control")]
+pub use rtp_control::{RtpOverride, RtpState};
+use rtp_extension::RtpExtension;
 pub use scheduler::{
     Config as SchedulerConfig,
     Error as SchedulerError,
@@ -38,17 +80,28 @@ pub use scheduler::{
     Scheduler,
     DEFAULT_SCHEDULER,
 };
+pub(crate) use session::RtpCounters;
+pub use session::VoiceSession;
+pub use silence::SilenceDetection;
+pub(crate) use spawner::TaskHandle;
+pub use spawner::{Executor, Spawner};
 #[cfg(test)]
 pub use test_config::*;
 #[cfg(any(test, feature = "internals"))]
 pub use test_impls::*;
+#[cfg(feature = "receive")]
+pub use transcriber::{SpeechSegment, Transcriber, TranscriptSink};
+pub(crate) use udp_send::send_with_retry;
+pub use udp_send::UdpSendStats;
+pub use virtual_clock::VirtualClock;
 
 #[cfg(feature = "builtin-queue")]
 use crate::tracks::TrackQueue;
 use crate::{
-    events::EventData,
-    input::Input,
-    tracks::{Track, TrackHandle},
+    events::{context_data::ChannelMoveData, EventData},
+    id::UserId,
+    input::{Input, MakePlayableError},
+    tracks::{Track, TrackHandle, TrackState},
     Config,
     ConnectionInfo,
     Event,
@@ -62,8 +115,12 @@ use core::{
     task::{Context, Poll},
 };
 use flume::{r#async::RecvFut, SendError, Sender};
-#[cfg(feature = "builtin-queue")]
-use std::time::Duration;
+use std::{
+    fmt::Debug,
+    result::Result as StdResult,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 #[allow(unused_imports)]
 pub use tasks::disposal::DisposalThread;
 use tasks::message::CoreMessage;
@@ -77,6 +134,7 @@ use tracing::instrument;
 #[derive(Clone, Debug)]
 pub struct Driver {
     config: Config,
+    crypto_preference: Option<Vec<CryptoMode>>,
     self_mute: bool,
     sender: Sender<CoreMessage>,
     // Making this an Option is an abhorrent hack to coerce the borrow checker
@@ -99,6 +157,7 @@ impl Driver {
 
         Driver {
             config,
+            crypto_preference: None,
             self_mute: false,
             sender,
             #[cfg(feature = "builtin-queue")]
@@ -138,7 +197,97 @@ impl Driver {
     /// Connects to a voice channel using the specified server.
     #[instrument(skip(self))]
     pub(crate) fn raw_connect(&mut self, info: ConnectionInfo, tx: Sender<Result<()>>) {
-        self.send(CoreMessage::ConnectWithResult(info, tx));
+        self.send(CoreMessage::ConnectWithResult(
+            info,
+            self.crypto_preference.clone(),
+            tx,
+        ));
+    }
+
+    /// Connects to an external (non-Discord) voice endpoint, such as an SFU, using
+    /// the given gateway's handshake.
+    ///
+    /// This leaves any current Discord call. See [`VoiceGateway`] for details on the
+    /// RTP stream sent. Leaving via [`Self::leave`] ends the gateway's session.
+    #[instrument(skip(self, gateway))]
+    pub fn connect_gateway(&mut self, gateway: Arc<dyn VoiceGateway>) -> Connect {
+        let (tx, rx) = flume::bounded(1);
+
+        self.send(CoreMessage::ConnectGateway(gateway, tx));
+
+        Connect {
+            inner: rx.into_recv_async(),
+        }
+    }
+
+    /// Sets the encryption modes offered when this driver (or parent `Call`) next
+    /// connects, most preferred first, in place of [`Config::crypto_mode`] and
+    /// [`Config::crypto_preference`].
+    ///
+    /// This is useful when a particular voice server or proxy only supports some
+    /// modes. `None` restores the modes chosen by this driver's [`Config`]. Changes
+    /// do not affect an established connection.
+    ///
+    /// [`Config::crypto_mode`]: crate::Config::crypto_mode
+    /// [`Config::crypto_preference`]: crate::Config::crypto_preference
+    #[instrument(skip(self))]
+    pub fn set_crypto_preference(&mut self, modes: Option<Vec<CryptoMode>>) {
+        self.crypto_preference = modes;
+    }
+
+    /// Returns the encryption mode negotiated for the current voice connection.
+    ///
+    /// Returns `None` if the driver is not connected.
+    #[instrument(skip(self))]
+    pub async fn crypto_mode(&mut self) -> Option<CryptoMode> {
+        let (tx, rx) = flume::bounded(1);
+        self.send(CoreMessage::GetCryptoMode(tx));
+
+        rx.recv_async().await.ok().flatten()
+    }
+
+    /// Returns counts of packets sent and failed on the current voice connection.
+    ///
+    /// Returns `None` if the driver is not connected.
+    #[instrument(skip(self))]
+    pub async fn udp_send_stats(&mut self) -> Option<UdpSendStats> {
+        let (tx, rx) = flume::bounded(1);
+        self.send(CoreMessage::GetUdpSendStats(tx));
+
+        rx.recv_async().await.ok().flatten()
+    }
+
+    /// Exports the state of the current voice session, so that another driver
+    /// (possibly in another process) can adopt it via [`Self::resume_session`].
+    ///
+    /// This driver stops sending audio once the session is exported, so that the
+    /// adopting driver can continue its nonce and RTP counters without reusing any
+    /// values. It should then be dropped or otherwise stopped without calling
+    /// [`Self::leave`], since leaving ends the session on Discord's side.
+    /// Returns `None` if the driver is not connected.
+    #[instrument(skip(self))]
+    pub async fn export_session(&mut self) -> Option<VoiceSession> {
+        let (tx, rx) = flume::bounded(1);
+        self.send(CoreMessage::ExportSession(tx));
+
+        rx.recv_async().await.ok().flatten()
+    }
+
+    /// Adopts a voice session exported from another driver by [`Self::export_session`],
+    /// resuming its voice websocket rather than rejoining the channel.
+    ///
+    /// Discord only permits a session to be resumed while it remains valid, i.e.,
+    /// shortly after its previous websocket closes and while the bot is still in
+    /// the channel. If this fails, callers must join the channel as normal.
+    #[instrument(skip(self))]
+    pub fn resume_session(&mut self, session: VoiceSession) -> Connect {
+        let (tx, rx) = flume::bounded(1);
+
+        self.send(CoreMessage::ResumeSession(session, tx));
+
+        Connect {
+            inner: rx.into_recv_async(),
+        }
     }
 
     /// Leaves the current voice channel, disconnecting from it.
@@ -150,8 +299,37 @@ impl Driver {
         self.send(CoreMessage::Disconnect);
     }
 
+    /// Reports that the bot has been moved between voice channels, firing a
+    /// [`CoreEvent::ChannelMoved`] event.
+    ///
+    /// [`CoreEvent::ChannelMoved`]: crate::events::CoreEvent::ChannelMoved
+    pub(crate) fn channel_moved(&mut self, data: ChannelMoveData) {
+        self.send(CoreMessage::ChannelMoved(data));
+    }
+
+    /// Informs the driver whether another user is present in its voice channel,
+    /// e.g., from a gateway voice state update.
+    ///
+    /// This supplements the users announced by Discord's voice gateway, and is
+    /// used by [`Config::alone_timeout`]. Presence is forgotten whenever the driver
+    /// leaves a call.
+    ///
+    /// Callers which receive voice state updates themselves should call this as
+    /// each user joins (`true`) or leaves (`false`) the driver's channel.
+    ///
+    /// [`Config::alone_timeout`]: crate::Config::alone_timeout
+    #[instrument(skip(self))]
+    pub fn set_member_present(&mut self, user_id: impl Into<UserId> + Debug, present: bool) {
+        self.send(CoreMessage::SetMemberPresent(
+            user_id.into().into(),
+            present,
+        ));
+    }
+
     /// Sets whether the current connection is to be muted.
     ///
+    /// While muted, all tracks keep advancing as normal, but only silence is sent.
+    ///
     /// If there is no live voice connection, then this only acts as a settings
     /// update for future connections.
     #[instrument(skip(self))]
@@ -160,6 +338,83 @@ impl Driver {
         self.send(CoreMessage::Mute(mute));
     }
 
+    /// Pauses every track currently playing on this driver.
+    ///
+    /// This is applied by the mixer in a single step, so tracks cannot start or
+    /// advance (e.g., via a [`TrackQueue`]) part-way through. Tracks added afterwards
+    /// play as normal, allowing an announcement to play over a paused queue.
+    ///
+    /// To keep all tracks advancing while sending silence, use [`mute`] instead.
+    ///
+    /// [`TrackQueue`]: crate::tracks::TrackQueue
+    /// [`mute`]: Self::mute
+    #[instrument(skip(self))]
+    pub fn pause_all(&mut self) {
+        self.send(CoreMessage::PauseAll(true));
+    }
+
+    /// Resumes every track paused by [`pause_all`].
+    ///
+    /// Tracks which were already paused, or which have been played or paused via their
+    /// own handles since, are left as they are.
+    ///
+    /// [`pause_all`]: Self::pause_all
+    #[instrument(skip(self))]
+    pub fn resume_all(&mut self) {
+        self.send(CoreMessage::PauseAll(false));
+    }
+
+    /// Sets the RTP header extensions attached to every outbound voice packet.
+    ///
+    /// An empty list (the default) sends packets without any extensions. This is
+    /// intended for experimentation: see [`rtp_extension`] for details.
+    ///
+    /// [`rtp_extension`]: crate::driver::rtp_extension
+    #[instrument(skip(self))]
+    pub fn set_rtp_extensions(&mut self, extensions: Vec<RtpExtension>) {
+        self.send(CoreMessage::SetRtpExtensions(extensions));
+    }
+
+    /// Overrides the SSRC, sequence number, and/or timestamp of outbound RTP packets.
+    ///
+    /// This is intended for advanced senders only, and incorrect values will
+    /// prevent Discord from accepting audio: see [`RtpOverride`] for details.
+    #[cfg(feature = "rtp-control")]
+    #[instrument(skip(self))]
+    pub fn set_rtp_override(&mut self, rtp: RtpOverride) {
+        self.send(CoreMessage::SetRtpOverride(rtp));
+    }
+
+    /// Returns the RTP header fields which will be used by the next outbound packet.
+    ///
+    /// Returns `None` if the driver is not connected.
+    #[cfg(feature = "rtp-control")]
+    #[instrument(skip(self))]
+    pub async fn rtp_state(&mut self) -> Option<RtpState> {
+        let (tx, rx) = flume::bounded(1);
+        self.send(CoreMessage::GetRtpState(tx));
+
+        rx.recv_async().await.ok().flatten()
+    }
+
+    /// Sends an arbitrary JSON payload over the voice gateway.
+    ///
+    /// **This is experimental**, and exists so that new opcodes (e.g., soundboard
+    /// or DAVE protocol transitions) can be tried out before songbird supports them.
+    /// Payloads are sent as-is, so must include their own `op` and `d` fields: songbird
+    /// does not track any state they change, and malformed payloads may cause Discord
+    /// to close the connection.
+    ///
+    /// Payloads are dropped if the driver is not connected. Any replies which songbird
+    /// does not understand fire [`CoreEvent::RawGatewayPayload`].
+    ///
+    /// [`CoreEvent::RawGatewayPayload`]: crate::events::CoreEvent::RawGatewayPayload
+    #[cfg(feature = "raw-gateway")]
+    #[instrument(skip(self))]
+    pub fn send_gateway_payload(&mut self, payload: serde_json::Value) {
+        self.send(CoreMessage::SendGatewayPayload(payload));
+    }
+
     /// Returns whether the driver is muted (i.e., processes audio internally
     /// but submits none).
     #[instrument(skip(self))]
@@ -182,6 +437,25 @@ impl Driver {
         self.play_only(input.into())
     }
 
+    /// Plays audio from an input once it is fully initialised and parsed, returning
+    /// a handle for further control.
+    ///
+    /// Unlike [`Self::play_input`], the input is made playable (off-thread, via
+    /// [`Input::make_playable_async`]) *before* it is added to the driver. Inputs which
+    /// cannot be played are returned as an error here, rather than as a track which
+    /// fires error events moments after being added.
+    #[instrument(skip(self, input))]
+    pub async fn play_input_ready(
+        &mut self,
+        input: Input,
+    ) -> StdResult<TrackHandle, MakePlayableError> {
+        let input = input
+            .make_playable_async(self.config.codec_registry, self.config.format_registry)
+            .await?;
+
+        Ok(self.play_input(input))
+    }
+
     /// Plays audio from a [`Track`] object.
     ///
     /// The main difference between this function and [`Self::play_input`] is
@@ -208,6 +482,35 @@ impl Driver {
         handle
     }
 
+    /// Plays audio from an input, starting on the frame sent closest to `start_at`.
+    ///
+    /// See [`Self::schedule`] for details.
+    #[instrument(skip(self, input))]
+    pub fn schedule_input(&mut self, input: Input, start_at: Instant) -> TrackHandle {
+        self.schedule(input.into(), start_at)
+    }
+
+    /// Plays audio from a [`Track`] object, starting on the frame sent closest to `start_at`.
+    ///
+    /// The track is added paused and made playable immediately, so that it is ready
+    /// well ahead of its start time. Its start is then tied to the mixer's own clock
+    /// rather than to a timer, as described in [`TrackHandle::play_at`].
+    ///
+    /// Calling [`TrackHandle::pause`] or [`TrackHandle::play`] before this instant cancels
+    /// the scheduled start, leaving the track attached to the driver, while
+    /// [`TrackHandle::stop`] removes it entirely.
+    #[instrument(skip(self, track))]
+    pub fn schedule(&mut self, track: Track, start_at: Instant) -> TrackHandle {
+        let handle = self.play(track.pause());
+
+        // Commands are buffered until the mixer receives the track, and the
+        // outcome of readying it is reported via the track's own events.
+        drop(handle.make_playable());
+        drop(handle.play_at(start_at));
+
+        handle
+    }
+
     /// Sets the bitrate for encoding Opus packets sent along
     /// the channel being managed.
     ///
@@ -263,6 +566,130 @@ impl Driver {
         self.send(CoreMessage::RemoveGlobalEvents);
     }
 
+    /// Returns handles to, and the current state of, every track attached to this driver.
+    ///
+    /// This includes tracks whose handles have since been dropped, so that
+    /// orphaned tracks can still be found and stopped. Tracks which have ended
+    /// are not included.
+    #[instrument(skip(self))]
+    pub async fn tracks(&mut self) -> Vec<(TrackHandle, TrackState)> {
+        let (tx, rx) = flume::bounded(1);
+        self.send(CoreMessage::GetTracks(tx));
+
+        rx.recv_async().await.unwrap_or_default()
+    }
+
+    /// Starts a live feed of waveform or spectrum data measured from mixed audio,
+    /// replacing any existing feed.
+    ///
+    /// One [`AnalysisFrame`] is sent for each 20ms of mixed audio. Frames are discarded
+    /// while [`Analysis::capacity`] frames are waiting to be received, and the feed
+    /// stops once the returned receiver is dropped.
+    #[instrument(skip(self))]
+    pub fn analysis_feed(&mut self, analysis: Analysis) -> flume::Receiver<AnalysisFrame> {
+        let (tx, rx) = flume::bounded(analysis.capacity.max(1));
+        self.send(CoreMessage::SetAnalysis(Some(AnalysisTap::new(
+            analysis, tx,
+        ))));
+
+        rx
+    }
+
+    /// Stops any live feed started by [`Self::analysis_feed`].
+    #[instrument(skip(self))]
+    pub fn stop_analysis_feed(&mut self) {
+        self.send(CoreMessage::SetAnalysis(None));
+    }
+
+    /// Returns up to the last `duration` of decoded audio received from a user,
+    /// for "instant replay" or clip features.
+    ///
+    /// Audio is returned as interleaved samples using [`Config::decode_channels`]
+    /// and [`Config::decode_sample_rate`], with silence filling any gaps in speech.
+    /// At most [`Config::listen_back`] of audio is retained, and this returns `None`
+    /// if that is disabled, or if no audio has been received from this user.
+    #[cfg(feature = "receive")]
+    #[instrument(skip(self))]
+    pub async fn dump_last(
+        &mut self,
+        user_id: impl Into<UserId> + Debug,
+        duration: Duration,
+    ) -> Option<Vec<i16>> {
+        let (tx, rx) = flume::bounded(1);
+        self.send(CoreMessage::DumpLast(user_id.into().into(), duration, tx));
+
+        rx.recv_async().await.ok().flatten()
+    }
+
+    /// Overrides whether audio received from a user may be decrypted and decoded.
+    ///
+    /// `Some(false)` discards all of this user's audio before it is decrypted, while
+    /// `Some(true)` permits it. `None` removes any override, deferring to
+    /// [`Config::receive_consent`] (or allowing the user's audio, if that is unset).
+    ///
+    /// Overrides are stored in [`Config::receive_consent_overrides`], and so persist
+    /// across reconnections.
+    ///
+    /// [`Config::receive_consent`]: crate::Config::receive_consent
+    /// [`Config::receive_consent_overrides`]: crate::Config::receive_consent_overrides
+    #[cfg(feature = "receive")]
+    #[instrument(skip(self))]
+    pub fn set_receive_consent(
+        &mut self,
+        user_id: impl Into<UserId> + Debug,
+        allowed: Option<bool>,
+    ) {
+        let user_id = user_id.into().into();
+        let overrides = &mut self.config.receive_consent_overrides;
+
+        if let Some(allowed) = allowed {
+            overrides.insert(user_id, allowed);
+        } else {
+            overrides.remove(&user_id);
+        }
+
+        self.set_config(self.config.clone());
+    }
+
+    /// Gracefully stops all of this driver's background tasks, resolving once
+    /// they have exited.
+    ///
+    /// If `flush` is set, the driver first waits (for at most this long) for all
+    /// playing tracks to finish. The mixer is then stopped between frames, followed
+    /// by the UDP receive and websocket tasks, and finally the event thread, so that
+    /// any events fired during shutdown are still delivered to handlers.
+    ///
+    /// This does not tell the main gateway that the bot has left its voice channel.
+    /// Any later command sent to this driver will restart its background tasks.
+    #[instrument(skip(self))]
+    pub async fn shutdown(&mut self, flush: Option<Duration>) {
+        let (tx, rx) = flume::bounded(1);
+
+        if self.sender.send(CoreMessage::Shutdown(flush, tx)).is_ok() {
+            _ = rx.recv_async().await;
+        }
+    }
+
+    /// Exposes this driver to other processes over a Unix socket bound at `path`,
+    /// for control with an [`IpcClient`].
+    ///
+    /// Requires the `"ipc"` feature. *This API is experimental.*
+    ///
+    /// [`IpcClient`]: ipc::IpcClient
+    #[cfg(all(feature = "ipc", unix))]
+    #[instrument(skip(self))]
+    pub fn serve_ipc(
+        &self,
+        path: impl AsRef<std::path::Path> + Debug,
+    ) -> std::io::Result<ipc::IpcServer> {
+        ipc::IpcServer::bind(path.as_ref(), self.sender.clone())
+    }
+
+    /// Returns a sender to this driver's current background tasks.
+    pub(crate) fn core_sender(&self) -> Sender<CoreMessage> {
+        self.sender.clone()
+    }
+
     /// Sends a message to the inner tasks, restarting it if necessary.
     fn send(&mut self, status: CoreMessage) {
         // Restart thread if it errored.
@@ -299,8 +726,46 @@ impl Driver {
     ///
     /// Requires the `"builtin-queue"` feature.
     pub async fn enqueue(&mut self, mut track: Track) -> TrackHandle {
-        let preload_time = TrackQueue::get_preload_time(&mut track).await;
-        self.enqueue_with_preload(track, preload_time)
+        let duration = TrackQueue::get_duration(&mut track).await;
+        let queue = self.queue.take().expect(
+            "Enqueue: The only case this can fail is if a previous queue operation panicked.",
+        );
+        let handle = queue.add_inner(
+            track,
+            self,
+            TrackQueue::preload_time(duration),
+            None,
+            duration,
+        );
+        self.queue = Some(queue);
+
+        handle
+    }
+
+    /// Adds an existing [`Track`] to this driver's built-in queue on behalf of a requester.
+    ///
+    /// See [`TrackQueue::add_with_requester`] for how `requester` is used.
+    ///
+    /// Requires the `"builtin-queue"` feature.
+    pub async fn enqueue_with_requester(
+        &mut self,
+        mut track: Track,
+        requester: u64,
+    ) -> TrackHandle {
+        let duration = TrackQueue::get_duration(&mut track).await;
+        let queue = self.queue.take().expect(
+            "Enqueue: The only case this can fail is if a previous queue operation panicked.",
+        );
+        let handle = queue.add_inner(
+            track,
+            self,
+            TrackQueue::preload_time(duration),
+            Some(requester),
+            duration,
+        );
+        self.queue = Some(queue);
+
+        handle
     }
 
     /// Add an existing [`Track`] to the queue, using a known time to preload the next track.
@@ -355,3 +820,331 @@ impl Future for Connect {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::test_data::{FILE_WAV_TARGET, FILE_WEBM_TARGET},
+        events::{
+            context_data::{OverloadData, TransmitData},
+            CallContext,
+        },
+        id::{ChannelId, GuildId},
+        input::{tone::Tone, File},
+        tracks::PlayMode,
+        CoreEvent,
+        EventContext,
+        TrackEvent,
+    };
+    use flume::Sender;
+    use std::num::NonZeroU64;
+
+    struct EndSignal {
+        tx: Sender<()>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventHandler for EndSignal {
+        async fn act(&self, _ctx: &EventContext<'_>) -> Option<Event> {
+            _ = self.tx.send(());
+            None
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(15_000)]
+    async fn shutdown_flushes_tracks_and_delivers_events() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config);
+
+        let handle = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+        let (tx, rx) = flume::unbounded();
+        handle
+            .add_event(Event::Track(TrackEvent::End), EndSignal { tx })
+            .unwrap();
+
+        t_handle.spawn_ticker();
+        driver.shutdown(Some(Duration::from_secs(10))).await;
+
+        assert!(rx.try_recv().is_ok());
+        assert!(handle.get_info().await.is_err());
+    }
+
+    struct TransmitSignal {
+        tx: Sender<TransmitData>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventHandler for TransmitSignal {
+        async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+            if let EventContext::Transmit(data) = ctx {
+                _ = self.tx.send(*data);
+            }
+            None
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn transmit_events_fire_per_interval() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let config = config.transmit_event_interval(Some(Duration::from_millis(100)));
+        let mut driver = Driver::new(config);
+
+        let (tx, rx) = flume::unbounded();
+        driver.add_global_event(Event::Core(CoreEvent::Transmit), TransmitSignal { tx });
+
+        let handle = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+        t_handle.ready_track(&handle, None).await;
+        t_handle.skip(10).await;
+
+        let data = rx.recv_async().await.unwrap();
+        assert_eq!(data.total_frames(), 5);
+        assert!(data.frames > 0);
+    }
+
+    struct CallSignal {
+        tx: Sender<CallContext>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventHandler for CallSignal {
+        async fn act(&self, _ctx: &EventContext<'_>) -> Option<Event> {
+            unreachable!("Driver events are dispatched with their call context.")
+        }
+
+        async fn act_in_call(&self, _ctx: &EventContext<'_>, call: &CallContext) -> Option<Event> {
+            _ = self.tx.send(call.clone());
+            Some(Event::Cancel)
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn events_carry_call_context() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let config = config
+            .call_identifier(Some("radio".into()))
+            .transmit_event_interval(Some(Duration::from_millis(100)));
+        let mut driver = Driver::new(config);
+
+        let (tx, rx) = flume::unbounded();
+        driver.add_global_event(Event::Core(CoreEvent::Transmit), CallSignal { tx });
+
+        let handle = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+        t_handle.ready_track(&handle, None).await;
+        t_handle.skip(10).await;
+
+        let call = rx.recv_async().await.unwrap();
+        assert_eq!(call.tag.as_deref(), Some("radio"));
+        assert!(call.guild_id.is_none());
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn scheduled_tracks_start_on_time_or_cancel() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config);
+
+        let start_at = Instant::now() + Duration::from_millis(300);
+        let started = driver.schedule_input(File::new(FILE_WAV_TARGET).into(), start_at);
+        let cancelled = driver.schedule_input(File::new(FILE_WAV_TARGET).into(), start_at);
+        t_handle.spawn_ticker();
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(started.get_info().await.unwrap().playing, PlayMode::Pause);
+        assert!(cancelled.pause().is_ok());
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(started.get_info().await.unwrap().playing, PlayMode::Play);
+        assert_eq!(cancelled.get_info().await.unwrap().playing, PlayMode::Pause);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn tracks_lists_unretained_tracks() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config);
+
+        let kept = driver.play(Track::from(File::new(FILE_WAV_TARGET)).pause());
+        drop(driver.play(Track::from(File::new(FILE_WAV_TARGET)).pause()));
+        t_handle.spawn_ticker();
+
+        let tracks = driver.tracks().await;
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(tracks[0].0.uuid(), kept.uuid());
+        assert!(tracks.iter().all(|(_, s)| s.playing == PlayMode::Pause));
+
+        assert!(tracks[1].0.stop().is_ok());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(driver.tracks().await.len(), 1);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn silence_detection_stops_sending_quiet_audio() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let config = config.silence_detection(Some(
+            SilenceDetection::default().hold(Duration::from_millis(60)),
+        ));
+        let mut driver = Driver::new(config);
+
+        let handle = driver.play(Track::from(Tone::silence(Duration::from_secs(5))));
+        t_handle.ready_track(&handle, None).await;
+
+        let mut saw_silence = false;
+        for _ in 0..20 {
+            t_handle.tick(1);
+            if let Some(pkt) = t_handle.recv_async().await.raw() {
+                saw_silence |= pkt.is_explicit_silence();
+            }
+        }
+
+        assert!(saw_silence);
+        assert!(handle.get_info().await.unwrap().playing.is_playing());
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn play_input_ready_rejects_unplayable_inputs() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config);
+        t_handle.spawn_ticker();
+
+        let missing = driver
+            .play_input_ready(File::new("resources/does-not-exist.wav").into())
+            .await;
+        assert!(missing.is_err());
+        assert!(driver.tracks().await.is_empty());
+
+        let handle = driver
+            .play_input_ready(Tone::sine(440.0, Duration::from_secs(1)).into())
+            .await
+            .unwrap();
+        let tracks = driver.tracks().await;
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].0.uuid(), handle.uuid());
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn mono_mix_mode_downmixes_stereo_opus() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.mix_mode(MixMode::Mono));
+
+        let handle = driver.play(Track::from(File::new(FILE_WEBM_TARGET)));
+        t_handle.ready_track(&handle, None).await;
+        t_handle.tick(1);
+
+        let pkt = t_handle.recv_async().await;
+        assert!(pkt.raw().unwrap().is_mixed_with_nonzero_signal());
+    }
+
+    struct OverloadSignal {
+        tx: Sender<OverloadData>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventHandler for OverloadSignal {
+        async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+            if let EventContext::Overload(data) = ctx {
+                _ = self.tx.send(*data);
+            }
+            None
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn overload_holds_lowest_priority_tracks() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let config = config.overload_policy(Some(OverloadPolicy::default().budget(Duration::ZERO)));
+        let mut driver = Driver::new(config);
+
+        let (tx, rx) = flume::unbounded();
+        driver.add_global_event(Event::Core(CoreEvent::Overload), OverloadSignal { tx });
+
+        let low = driver.play(Track::from(Tone::sine(440.0, Duration::from_secs(5))));
+        let high = driver.play(Track::from(Tone::sine(440.0, Duration::from_secs(5))).priority(1));
+        t_handle.ready_track(&low, None).await;
+        t_handle.ready_track(&high, None).await;
+        let low_pos = low.get_info().await.unwrap().position;
+        let high_pos = high.get_info().await.unwrap().position;
+        t_handle.skip(5).await;
+
+        let data = rx.recv_async().await.unwrap();
+        assert!(data.engaged);
+        assert_eq!(data.strategy, OverloadStrategy::DropLowestPriority);
+        assert_eq!(data.held_tracks, 1);
+
+        assert_eq!(low.get_info().await.unwrap().position, low_pos);
+        assert!(high.get_info().await.unwrap().position > high_pos);
+    }
+
+    struct MoveSignal {
+        tx: Sender<ChannelMoveData>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventHandler for MoveSignal {
+        async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+            if let EventContext::ChannelMoved(data) = ctx {
+                _ = self.tx.send(*data);
+            }
+            None
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn channel_move_fires_event_and_keeps_tracks() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config);
+
+        let (tx, rx) = flume::unbounded();
+        driver.add_global_event(Event::Core(CoreEvent::ChannelMoved), MoveSignal { tx });
+
+        let track = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+        t_handle.spawn_ticker();
+
+        let data = ChannelMoveData {
+            from: ChannelId(NonZeroU64::new(1).unwrap()),
+            to: ChannelId(NonZeroU64::new(2).unwrap()),
+            guild_id: GuildId(NonZeroU64::new(3).unwrap()),
+        };
+        driver.channel_moved(data);
+
+        assert_eq!(rx.recv_async().await.unwrap(), data);
+        assert_eq!(track.get_info().await.unwrap().playing, PlayMode::Play);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn resume_all_only_resumes_tracks_paused_by_driver() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config);
+
+        let playing = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+        let paused = driver.play(Track::from(File::new(FILE_WAV_TARGET)).pause());
+        t_handle.spawn_ticker();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        driver.pause_all();
+        let announcement = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(playing.get_info().await.unwrap().playing, PlayMode::Pause);
+        assert_eq!(paused.get_info().await.unwrap().playing, PlayMode::Pause);
+        assert_eq!(
+            announcement.get_info().await.unwrap().playing,
+            PlayMode::Play
+        );
+
+        driver.resume_all();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(playing.get_info().await.unwrap().playing, PlayMode::Play);
+        assert_eq!(paused.get_info().await.unwrap().playing, PlayMode::Pause);
+    }
+}
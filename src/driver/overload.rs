@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+/// Ways for the mixer to shed work when mixing every track would overrun its
+/// time budget.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum OverloadStrategy {
+    /// Tracks are mixed in order of descending [`priority`] until the budget is
+    /// spent, and any remaining tracks are held in place for that frame.
+    ///
+    /// This is the default choice.
+    ///
+    /// [`priority`]: crate::tracks::Track::priority
+    #[default]
+    DropLowestPriority,
+    /// Tracks are mixed in turn until the budget is spent, and any remaining
+    /// tracks are held in place to be mixed first on the next frame.
+    ///
+    /// Held tracks fall behind rather than skip audio, but no track is starved.
+    Interleave,
+    /// All tracks are mixed and encoded in mono until mixing has recovered to
+    /// within half of the budget.
+    ///
+    /// This has no effect if the driver already uses [`MixMode::Mono`].
+    ///
+    /// [`MixMode::Mono`]: super::MixMode::Mono
+    DownmixMono,
+}
+
+/// Settings for how the mixer behaves when it cannot mix all tracks within its
+/// per-frame time budget.
+///
+/// By default, every track is mixed on every frame regardless of cost, so an
+/// overloaded mixer misses its packet deadlines in unpredictable ways. A policy
+/// instead degrades playback in a chosen, predictable way. A [`CoreEvent::Overload`]
+/// event fires whenever the policy engages or releases.
+///
+/// At least one track is always mixed per frame, however long it takes.
+///
+/// [`CoreEvent::Overload`]: crate::events::CoreEvent::Overload
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct OverloadPolicy {
+    /// How work is shed once the budget is exceeded.
+    ///
+    /// Defaults to [`OverloadStrategy::DropLowestPriority`].
+    pub strategy: OverloadStrategy,
+    /// The time each frame's mixing may take.
+    ///
+    /// Defaults to 15ms, leaving headroom within each 20ms frame for encoding
+    /// and other calls sharing the same thread.
+    pub budget: Duration,
+}
+
+impl Default for OverloadPolicy {
+    fn default() -> Self {
+        Self {
+            strategy: OverloadStrategy::default(),
+            budget: Duration::from_millis(15),
+        }
+    }
+}
+
+impl OverloadPolicy {
+    /// Sets how work is shed once the budget is exceeded.
+    #[must_use]
+    pub fn strategy(mut self, strategy: OverloadStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Sets the time each frame's mixing may take.
+    #[must_use]
+    pub fn budget(mut self, budget: Duration) -> Self {
+        self.budget = budget;
+        self
+    }
+}
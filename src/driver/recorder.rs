@@ -0,0 +1,278 @@
+use crate::{model::id::UserId, opus_util::PacketInfo};
+use std::{
+    fs::File,
+    io::{BufWriter, Result as IoResult, Write},
+    path::PathBuf,
+};
+
+/// Opens an output for each user's recording of received audio.
+///
+/// When set via [`Config::recorder`], the driver writes each user's Opus frames
+/// into an [`OggOpusWriter`] exactly as they were received, without decoding any
+/// audio. Each user's recording begins with their first packet, and lost packets
+/// or periods of silence are filled with silent frames so that recordings of every
+/// user in a call stay aligned with one another. Recordings are finished once their
+/// user's state is dropped by the driver (see [`Config::decode_state_timeout`]),
+/// or the connection ends.
+///
+/// Packets must be decrypted, so this requires either [`DecodeMode::Decrypt`] (the
+/// cheapest option) or [`DecodeMode::Decode`]. Writes are made from the driver's
+/// receive task, so outputs should be buffered.
+///
+/// [`Config::recorder`]: crate::Config::recorder
+/// [`Config::decode_state_timeout`]: crate::Config::decode_state_timeout
+/// [`DecodeMode::Decrypt`]: crate::driver::DecodeMode::Decrypt
+/// [`DecodeMode::Decode`]: crate::driver::DecodeMode::Decode
+pub trait Recorder: Send + Sync {
+    /// Opens the output for a new recording of the SSRC `ssrc`, sent by `user_id`
+    /// if this has been announced by Discord.
+    ///
+    /// If this fails, audio from `ssrc` will not be recorded.
+    fn open(&self, ssrc: u32, user_id: Option<UserId>) -> IoResult<Box<dyn Write + Send>>;
+}
+
+/// A [`Recorder`] storing each user's audio as a file in one directory.
+///
+/// Files are named `<user_id>-<ssrc>.opus`, or `unknown-<ssrc>.opus` if the
+/// sender of an SSRC is unknown. Existing files are overwritten.
+#[derive(Clone, Debug)]
+pub struct DirectoryRecorder {
+    dir: PathBuf,
+}
+
+impl DirectoryRecorder {
+    /// Records into files within `dir`, which must already exist.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+}
+
+impl Recorder for DirectoryRecorder {
+    fn open(&self, ssrc: u32, user_id: Option<UserId>) -> IoResult<Box<dyn Write + Send>> {
+        let name = match user_id {
+            Some(id) => format!("{id}-{ssrc}.opus"),
+            None => format!("unknown-{ssrc}.opus"),
+        };
+
+        let file = File::create(self.dir.join(name))?;
+
+        Ok(Box::new(BufWriter::new(file)))
+    }
+}
+
+/// Number of samples per channel assumed for packets whose header can't be read.
+const FALLBACK_PACKET_SAMPLES: usize = 960;
+
+/// Maximum number of packets held on one page, bounding how much audio is
+/// lost if a recording is not cleanly finished (1s).
+const MAX_PAGE_PACKETS: usize = 50;
+
+/// Maximum number of segments (lacing values) in one Ogg page.
+const MAX_PAGE_SEGMENTS: usize = 255;
+
+const HEADER_BOS: u8 = 0x02;
+const HEADER_EOS: u8 = 0x04;
+
+/// Writes a single stream of Opus packets into an Ogg Opus ([RFC 7845]) file.
+///
+/// Packets are expected to hold 48kHz stereo audio, as sent by Discord. Each
+/// packet's duration is read from its header to keep the stream's timing.
+///
+/// [RFC 7845]: https://www.rfc-editor.org/rfc/rfc7845
+#[derive(Debug)]
+pub struct OggOpusWriter<W: Write> {
+    inner: W,
+    serial: u32,
+    page_seq: u32,
+    granule: u64,
+    packets: usize,
+    segments: Vec<u8>,
+    body: Vec<u8>,
+}
+
+impl<W: Write> OggOpusWriter<W> {
+    /// Begins a new Ogg Opus stream in `inner`, writing its headers.
+    ///
+    /// `serial` identifies this stream within the file.
+    pub fn new(inner: W, serial: u32) -> IoResult<Self> {
+        let mut out = Self {
+            inner,
+            serial,
+            page_seq: 0,
+            granule: 0,
+            packets: 0,
+            segments: Vec::with_capacity(MAX_PAGE_SEGMENTS),
+            body: Vec::new(),
+        };
+
+        let mut head = Vec::with_capacity(19);
+        head.extend_from_slice(b"OpusHead");
+        head.push(1); // Version.
+        head.push(2); // Channel count.
+                      // Pre-skip: frames are taken mid-stream, so no encoder delay is trimmed.
+        head.extend_from_slice(&0u16.to_le_bytes());
+        head.extend_from_slice(&48_000u32.to_le_bytes());
+        head.extend_from_slice(&0i16.to_le_bytes()); // Output gain.
+        head.push(0); // Channel mapping family.
+        out.push_segments(&head);
+        out.flush_page(HEADER_BOS)?;
+
+        let vendor = concat!("songbird ", env!("CARGO_PKG_VERSION"));
+        let mut tags = Vec::with_capacity(16 + vendor.len());
+        tags.extend_from_slice(b"OpusTags");
+        tags.extend_from_slice(&(vendor.len() as u32).to_le_bytes());
+        tags.extend_from_slice(vendor.as_bytes());
+        tags.extend_from_slice(&0u32.to_le_bytes()); // No user comments.
+        out.push_segments(&tags);
+        out.flush_page(0)?;
+
+        Ok(out)
+    }
+
+    /// Appends one Opus packet to the stream.
+    pub fn write_packet(&mut self, packet: &[u8]) -> IoResult<()> {
+        let new_segments = packet.len() / 255 + 1;
+        if self.segments.len() + new_segments > MAX_PAGE_SEGMENTS {
+            self.flush_page(0)?;
+        }
+
+        let samples = PacketInfo::parse(packet).map_or(FALLBACK_PACKET_SAMPLES, |p| p.samples());
+        self.granule += samples as u64;
+        self.push_segments(packet);
+        self.packets += 1;
+
+        if self.packets >= MAX_PAGE_PACKETS {
+            self.flush_page(0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the total duration of audio written, in samples per channel at 48kHz.
+    #[must_use]
+    pub fn samples(&self) -> u64 {
+        self.granule
+    }
+
+    /// Ends the stream, writing any buffered packets, and returns the inner writer.
+    pub fn finish(mut self) -> IoResult<W> {
+        self.flush_page(HEADER_EOS)?;
+        self.inner.flush()?;
+
+        Ok(self.inner)
+    }
+
+    fn push_segments(&mut self, packet: &[u8]) {
+        let full = packet.len() / 255;
+        self.segments.resize(self.segments.len() + full, 255);
+        self.segments.push((packet.len() % 255) as u8);
+        self.body.extend_from_slice(packet);
+    }
+
+    fn flush_page(&mut self, header_type: u8) -> IoResult<()> {
+        let mut page = Vec::with_capacity(27 + self.segments.len() + self.body.len());
+        page.extend_from_slice(b"OggS");
+        page.push(0); // Version.
+        page.push(header_type);
+        page.extend_from_slice(&self.granule.to_le_bytes());
+        page.extend_from_slice(&self.serial.to_le_bytes());
+        page.extend_from_slice(&self.page_seq.to_le_bytes());
+        page.extend_from_slice(&[0; 4]); // CRC, filled below.
+        page.push(self.segments.len() as u8);
+        page.extend_from_slice(&self.segments);
+        page.extend_from_slice(&self.body);
+
+        let crc = ogg_crc(&page);
+        page[22..26].copy_from_slice(&crc.to_le_bytes());
+
+        self.inner.write_all(&page)?;
+
+        self.page_seq += 1;
+        self.packets = 0;
+        self.segments.clear();
+        self.body.clear();
+
+        Ok(())
+    }
+}
+
+/// CRC-32 lookup table for Ogg's polynomial (`0x04c11db7`, unreflected).
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut r = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            r = if r & 0x8000_0000 == 0 {
+                r << 1
+            } else {
+                (r << 1) ^ 0x04c1_1db7
+            };
+            bit += 1;
+        }
+        table[i] = r;
+        i += 1;
+    }
+    table
+};
+
+fn ogg_crc(data: &[u8]) -> u32 {
+    data.iter().fold(0, |crc, &byte| {
+        (crc << 8) ^ CRC_TABLE[(((crc >> 24) as u8) ^ byte) as usize]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::SILENT_FRAME;
+
+    #[test]
+    fn pages_are_framed_and_timed() {
+        let mut writer = OggOpusWriter::new(vec![], 7).unwrap();
+        for _ in 0..(MAX_PAGE_PACKETS + 1) {
+            writer.write_packet(&SILENT_FRAME).unwrap();
+        }
+        assert_eq!(writer.samples(), 960 * (MAX_PAGE_PACKETS as u64 + 1));
+        let out = writer.finish().unwrap();
+
+        // OpusHead, OpusTags, one full audio page, then the EOS page.
+        let mut pages = vec![];
+        let mut rest = &out[..];
+        while !rest.is_empty() {
+            assert_eq!(&rest[..4], b"OggS");
+            let n_segs = rest[26] as usize;
+            let body_len: usize = rest[27..][..n_segs].iter().map(|&s| s as usize).sum();
+            let len = 27 + n_segs + body_len;
+
+            let mut page = rest[..len].to_vec();
+            let crc = u32::from_le_bytes(page[22..26].try_into().unwrap());
+            page[22..26].fill(0);
+            assert_eq!(ogg_crc(&page), crc);
+
+            pages.push(rest[..len].to_vec());
+            rest = &rest[len..];
+        }
+
+        assert_eq!(pages.len(), 4);
+        assert_eq!(pages[0][5], HEADER_BOS);
+        assert_eq!(&pages[0][28..36], b"OpusHead");
+        assert_eq!(&pages[1][28..36], b"OpusTags");
+        assert_eq!(pages[2][26] as usize, MAX_PAGE_PACKETS);
+        assert_eq!(pages[3][5], HEADER_EOS);
+
+        let granule = |page: &[u8]| u64::from_le_bytes(page[6..14].try_into().unwrap());
+        assert_eq!(granule(&pages[2]), 960 * MAX_PAGE_PACKETS as u64);
+        assert_eq!(granule(&pages[3]), 960 * (MAX_PAGE_PACKETS as u64 + 1));
+        // Page sequence numbers count up.
+        assert_eq!(u32::from_le_bytes(pages[3][18..22].try_into().unwrap()), 3);
+    }
+
+    #[test]
+    fn crc_matches_reference() {
+        // CRC-32/CKSUM without its final XOR, as used by Ogg.
+        assert_eq!(ogg_crc(b""), 0);
+        assert_eq!(ogg_crc(b"123456789"), 0x89a1_897f);
+    }
+}
@@ -0,0 +1,70 @@
+use discortp::rtp::MutableRtpPacket;
+
+/// Overrides for the RTP header fields of outbound voice packets.
+///
+/// These are intended for advanced senders, such as users cross-fading
+/// between two drivers or relaying audio to and from other RTP peers (e.g., SFUs).
+/// Misuse will stop Discord from accepting audio: in particular, `ssrc` must
+/// match the SSRC Discord assigned to the session unless the receiving end
+/// has been told to expect another.
+///
+/// Each field left as `None` keeps the driver's default behaviour. Overrides
+/// are applied immediately if the driver is connected, and again on every
+/// (re)connection. Sequence numbers and timestamps continue to advance from
+/// their overridden values as packets are sent.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct RtpOverride {
+    /// SSRC to place on outbound packets, in place of the one assigned by Discord.
+    pub ssrc: Option<u32>,
+    /// Sequence number of the next packet, rather than a random start point.
+    pub sequence: Option<u16>,
+    /// RTP timestamp of the next packet, rather than a random start point.
+    pub timestamp: Option<u32>,
+}
+
+impl RtpOverride {
+    /// Applies all set overrides to the given values.
+    pub(crate) fn apply(&self, ssrc: &mut u32, sequence: &mut u16, timestamp: &mut u32) {
+        if let Some(val) = self.ssrc {
+            *ssrc = val;
+        }
+        if let Some(val) = self.sequence {
+            *sequence = val;
+        }
+        if let Some(val) = self.timestamp {
+            *timestamp = val;
+        }
+    }
+
+    /// Applies all set overrides to an RTP packet header.
+    pub(crate) fn apply_to_packet(&self, rtp: &mut MutableRtpPacket<'_>) {
+        let mut state = RtpState::from_packet(rtp);
+        self.apply(&mut state.ssrc, &mut state.sequence, &mut state.timestamp);
+
+        rtp.set_ssrc(state.ssrc);
+        rtp.set_sequence(state.sequence.into());
+        rtp.set_timestamp(state.timestamp.into());
+    }
+}
+
+/// RTP header fields which will be used by the next outbound voice packet.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct RtpState {
+    /// SSRC placed on outbound packets.
+    pub ssrc: u32,
+    /// Sequence number of the next packet.
+    pub sequence: u16,
+    /// RTP timestamp of the next packet, in 48kHz samples.
+    pub timestamp: u32,
+}
+
+impl RtpState {
+    pub(crate) fn from_packet(rtp: &MutableRtpPacket<'_>) -> Self {
+        Self {
+            ssrc: rtp.get_ssrc(),
+            sequence: rtp.get_sequence().into(),
+            timestamp: rtp.get_timestamp().into(),
+        }
+    }
+}
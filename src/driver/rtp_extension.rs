@@ -0,0 +1,246 @@
+//! Writing and reading RTP header extensions, for experimentation with outbound metadata.
+//!
+//! Extensions use the one-byte header form of [RFC 8285]: each element carries an ID
+//! in `1..=14` and between 1 and 16 bytes of data. Elements set via
+//! [`Driver::set_rtp_extensions`] are attached to every outbound voice packet.
+//!
+//! Discord does not currently define any extensions for packets sent by bots (such
+//! as targeting audio at a subset of listeners). This machinery exists so that such
+//! features can be adopted, or tested, without changes to the packet path.
+//!
+//! [RFC 8285]: https://www.rfc-editor.org/rfc/rfc8285
+//! [`Driver::set_rtp_extensions`]: super::Driver::set_rtp_extensions
+use std::{
+    error::Error,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+/// Profile identifier of the one-byte RTP header extension form.
+pub const ONE_BYTE_PROFILE: u16 = 0xBEDE;
+
+/// Length of the extension preamble (profile and length) preceding all elements.
+pub const PREAMBLE_LEN: usize = 4;
+
+/// A single RTP header extension element.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct RtpExtension {
+    id: u8,
+    data: Vec<u8>,
+}
+
+impl RtpExtension {
+    /// Creates a new extension element.
+    ///
+    /// `id` must lie in `1..=14`, and `data` must hold between 1 and 16 bytes.
+    pub fn new(id: u8, data: impl Into<Vec<u8>>) -> Result<Self, RtpExtensionError> {
+        let data = data.into();
+
+        if !(1..=14).contains(&id) {
+            return Err(RtpExtensionError::InvalidId(id));
+        }
+
+        if !(1..=16).contains(&data.len()) {
+            return Err(RtpExtensionError::InvalidLength(data.len()));
+        }
+
+        Ok(Self { id, data })
+    }
+
+    /// The ID of this element.
+    #[must_use]
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    /// The data carried by this element.
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+/// Errors encountered while writing or reading RTP header extensions.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum RtpExtensionError {
+    /// Element IDs must lie in `1..=14`.
+    InvalidId(u8),
+    /// Element data must hold between 1 and 16 bytes.
+    InvalidLength(usize),
+    /// The given buffer cannot hold the extension block.
+    BufferTooSmall,
+    /// The extension block does not use the one-byte header form.
+    UnsupportedProfile(u16),
+    /// The extension block ended partway through an element.
+    Malformed,
+}
+
+impl Display for RtpExtensionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("invalid RTP header extension: ")?;
+        match self {
+            Self::InvalidId(id) => write!(f, "element ID {id} is not in 1..=14"),
+            Self::InvalidLength(len) => write!(f, "element length {len} is not in 1..=16"),
+            Self::BufferTooSmall => f.write_str("buffer too small"),
+            Self::UnsupportedProfile(p) => write!(f, "unsupported profile {p:#06x}"),
+            Self::Malformed => f.write_str("truncated element"),
+        }
+    }
+}
+
+impl Error for RtpExtensionError {}
+
+/// Returns the number of bytes needed to write `extensions`, including the
+/// preamble and any padding.
+///
+/// This is zero if `extensions` is empty.
+#[must_use]
+pub fn block_len(extensions: &[RtpExtension]) -> usize {
+    if extensions.is_empty() {
+        return 0;
+    }
+
+    let elements: usize = extensions.iter().map(|e| 1 + e.data.len()).sum();
+
+    PREAMBLE_LEN + elements.next_multiple_of(4)
+}
+
+/// Writes `extensions` into the start of `buf` as an extension block, returning the
+/// number of bytes written.
+///
+/// This writes nothing if `extensions` is empty. The caller is responsible for
+/// setting the RTP header's extension bit if any bytes were written.
+pub fn write_extensions(
+    extensions: &[RtpExtension],
+    buf: &mut [u8],
+) -> Result<usize, RtpExtensionError> {
+    let len = block_len(extensions);
+
+    if len == 0 {
+        return Ok(0);
+    }
+
+    let buf = buf
+        .get_mut(..len)
+        .ok_or(RtpExtensionError::BufferTooSmall)?;
+    let words = u16::try_from((len - PREAMBLE_LEN) / 4)
+        .expect("Element count and length limits keep blocks well below u16::MAX words.");
+
+    buf[..2].copy_from_slice(&ONE_BYTE_PROFILE.to_be_bytes());
+    buf[2..PREAMBLE_LEN].copy_from_slice(&words.to_be_bytes());
+
+    let mut cursor = PREAMBLE_LEN;
+    for ext in extensions {
+        let n = ext.data.len();
+        buf[cursor] = (ext.id << 4) | (n - 1) as u8;
+        buf[cursor + 1..][..n].copy_from_slice(&ext.data);
+        cursor += 1 + n;
+    }
+
+    buf[cursor..].fill(0);
+
+    Ok(len)
+}
+
+/// Reads an extension block from the start of `buf`, returning its elements and the
+/// total length of the block.
+pub fn read_extensions(buf: &[u8]) -> Result<(Vec<RtpExtension>, usize), RtpExtensionError> {
+    let preamble = buf
+        .get(..PREAMBLE_LEN)
+        .ok_or(RtpExtensionError::Malformed)?;
+    let profile = u16::from_be_bytes([preamble[0], preamble[1]]);
+    let words = u16::from_be_bytes([preamble[2], preamble[3]]);
+
+    if profile != ONE_BYTE_PROFILE {
+        return Err(RtpExtensionError::UnsupportedProfile(profile));
+    }
+
+    let len = PREAMBLE_LEN + 4 * usize::from(words);
+    let body = buf
+        .get(PREAMBLE_LEN..len)
+        .ok_or(RtpExtensionError::Malformed)?;
+
+    let mut out = vec![];
+    let mut cursor = 0;
+    while let Some(&head) = body.get(cursor) {
+        let id = head >> 4;
+
+        match id {
+            // Padding.
+            0 => {
+                cursor += 1;
+                continue;
+            },
+            // Reserved: processing must stop.
+            15 => break,
+            _ => {},
+        }
+
+        let n = usize::from(head & 0x0F) + 1;
+        let data = body
+            .get(cursor + 1..cursor + 1 + n)
+            .ok_or(RtpExtensionError::Malformed)?;
+        out.push(RtpExtension {
+            id,
+            data: data.to_vec(),
+        });
+        cursor += 1 + n;
+    }
+
+    Ok((out, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_with_padding() {
+        let exts = vec![
+            RtpExtension::new(1, [0xAA]).unwrap(),
+            RtpExtension::new(14, vec![1, 2, 3, 4]).unwrap(),
+        ];
+
+        let mut buf = [0xFF; 32];
+        let len = write_extensions(&exts, &mut buf).unwrap();
+
+        // 2 + 5 element bytes, padded to 8.
+        assert_eq!(len, PREAMBLE_LEN + 8);
+        assert_eq!(len, block_len(&exts));
+        assert_eq!(&buf[..4], &[0xBE, 0xDE, 0, 2]);
+        assert_eq!(buf[len - 1], 0);
+
+        assert_eq!(read_extensions(&buf).unwrap(), (exts, len));
+    }
+
+    #[test]
+    fn invalid_elements_refused() {
+        assert_eq!(
+            RtpExtension::new(0, [1]),
+            Err(RtpExtensionError::InvalidId(0))
+        );
+        assert_eq!(
+            RtpExtension::new(15, [1]),
+            Err(RtpExtensionError::InvalidId(15))
+        );
+        assert_eq!(
+            RtpExtension::new(1, Vec::<u8>::new()),
+            Err(RtpExtensionError::InvalidLength(0))
+        );
+        assert_eq!(
+            RtpExtension::new(1, [0; 17]),
+            Err(RtpExtensionError::InvalidLength(17))
+        );
+    }
+
+    #[test]
+    fn small_buffer_and_empty_list() {
+        let exts = [RtpExtension::new(3, [0; 16]).unwrap()];
+
+        assert_eq!(
+            write_extensions(&exts, &mut [0; 8]),
+            Err(RtpExtensionError::BufferTooSmall)
+        );
+        assert_eq!(write_extensions(&[], &mut []), Ok(0));
+    }
+}
@@ -1,4 +1,5 @@
 use super::*;
+use std::time::Duration;
 
 /// Configuration for how a [`Scheduler`] handles tasks.
 ///
@@ -15,6 +16,47 @@ pub struct Config {
     ///
     /// Defaults to `true`.
     pub move_expensive_tasks: bool,
+    /// Send any due UDP keepalive alongside a call's voice packet in a single
+    /// syscall (via `sendmmsg` on Linux), rather than separately.
+    ///
+    /// This only saves one syscall per call each time a keepalive falls due (every
+    /// 5 seconds). Each call sends over its own connected socket, so packets of
+    /// different calls on the same worker are never combined into one syscall.
+    /// Platforms without batched sends fall back to one syscall per packet.
+    /// [`LiveStatBlock::send_syscalls`] and [`LiveStatBlock::packets_sent`] report
+    /// the effect of this setting.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`LiveStatBlock::send_syscalls`]: super::LiveStatBlock::send_syscalls
+    /// [`LiveStatBlock::packets_sent`]: super::LiveStatBlock::packets_sent
+    pub batch_sends: bool,
+    /// Encrypt each worker's voice packets on a dedicated helper thread, rather
+    /// than on the worker itself.
+    ///
+    /// At very high call counts, AEAD encryption is a measurable share of each
+    /// worker's per-tick compute cost (as reported by [`LiveStatBlock`]). Offloading
+    /// it lets each worker mix more calls before tasks must be moved elsewhere.
+    /// Encryption is pipelined: packets mixed on one tick are sent on the next,
+    /// which adds one frame (20ms) of latency to all outbound audio.
+    ///
+    /// Defaults to `false`.
+    ///
+    /// [`LiveStatBlock`]: super::LiveStatBlock
+    pub offload_encryption: bool,
+    /// Spread each tick's outbound voice packets evenly across this window,
+    /// rather than sending them back-to-back.
+    ///
+    /// Each call sends one packet per 20ms tick however many tracks it mixes, but a
+    /// worker sends the packets of all its calls at the start of each tick. Pacing
+    /// waits between these sends so that the last leaves at most `window` after the
+    /// first, smoothing out bursts of traffic from the host. This waiting eats into
+    /// the time available to mix the next tick, so windows longer than half a tick
+    /// (10ms) are shortened to 10ms. Time spent waiting is not counted towards a
+    /// worker's compute cost.
+    ///
+    /// Defaults to `None`.
+    pub pacing: Option<Duration>,
 }
 
 impl Default for Config {
@@ -22,6 +64,9 @@ impl Default for Config {
         Self {
             strategy: Mode::default(),
             move_expensive_tasks: true,
+            batch_sends: false,
+            offload_encryption: false,
+            pacing: None,
         }
     }
 }
@@ -0,0 +1,113 @@
+use std::thread;
+
+use discortp::rtp::{MutableRtpPacket, RtpPacket};
+use flume::{Receiver, Sender};
+use tracing::debug;
+
+use crate::driver::crypto::Cipher;
+
+use super::TaskId;
+
+/// A built voice packet awaiting encryption, and the task it must be sent by.
+pub struct EncryptJob {
+    pub id: TaskId,
+    cipher: Cipher,
+    buf: Vec<u8>,
+    len: usize,
+}
+
+impl EncryptJob {
+    /// The finished packet, or an empty slice if encryption failed.
+    pub fn packet(&self) -> &[u8] {
+        &self.buf[..self.len]
+    }
+}
+
+/// Helper thread encrypting each tick's voice packets on behalf of a [`Live`] worker.
+///
+/// Packets are pipelined: a batch submitted on one tick is collected and sent
+/// on the next, so that encryption runs while the worker waits for its deadline
+/// and mixes the following frame.
+///
+/// [`Live`]: super::Live
+pub struct Encryptor {
+    tx: Sender<Vec<EncryptJob>>,
+    rx: Receiver<Vec<EncryptJob>>,
+    in_flight: bool,
+    spare: Vec<Vec<u8>>,
+}
+
+impl Encryptor {
+    pub fn spawn() -> Self {
+        let (job_tx, job_rx) = flume::bounded(1);
+        let (done_tx, done_rx) = flume::bounded(1);
+
+        thread::spawn(move || run(&job_rx, &done_tx));
+
+        Self {
+            tx: job_tx,
+            rx: done_rx,
+            in_flight: false,
+            spare: vec![],
+        }
+    }
+
+    /// Copies an unencrypted packet into a new job.
+    pub fn job(&mut self, id: TaskId, cipher: Cipher, packet: &[u8]) -> EncryptJob {
+        let mut buf = self.spare.pop().unwrap_or_default();
+        buf.clear();
+        buf.extend_from_slice(packet);
+
+        EncryptJob {
+            id,
+            cipher,
+            len: buf.len(),
+            buf,
+        }
+    }
+
+    /// Returns the jobs submitted on the previous tick, blocking until they
+    /// are encrypted.
+    pub fn collect(&mut self) -> Vec<EncryptJob> {
+        if !std::mem::take(&mut self.in_flight) {
+            return vec![];
+        }
+
+        self.rx.recv().unwrap_or_default()
+    }
+
+    /// Starts encrypting a batch of jobs, to be returned by the next [`Self::collect`].
+    pub fn submit(&mut self, jobs: Vec<EncryptJob>) {
+        if jobs.is_empty() {
+            return;
+        }
+
+        self.in_flight = self.tx.send(jobs).is_ok();
+    }
+
+    /// Keeps the packet buffers of sent jobs for reuse.
+    pub fn recycle(&mut self, jobs: Vec<EncryptJob>) {
+        self.spare.extend(jobs.into_iter().map(|job| job.buf));
+    }
+}
+
+fn run(rx: &Receiver<Vec<EncryptJob>>, tx: &Sender<Vec<EncryptJob>>) {
+    while let Ok(mut jobs) = rx.recv() {
+        for job in &mut jobs {
+            let payload_len = job.len - RtpPacket::minimum_packet_size();
+            let mut rtp = MutableRtpPacket::new(&mut job.buf[..]).expect(
+                "FATAL: Too few bytes in self.packet for RTP header.\
+                    (Blame: VOICE_PACKET_MAX?)",
+            );
+
+            if let Err(e) = job.cipher.encrypt_rtp_in_place(&mut rtp, payload_len) {
+                debug!("Failed to encrypt offloaded packet: {e:?}");
+                job.len = 0;
+            }
+        }
+
+        if tx.send(jobs).is_err() {
+            break;
+        }
+    }
+}
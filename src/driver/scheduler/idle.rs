@@ -1,5 +1,6 @@
 use std::{collections::HashMap, time::Duration};
 
+use flume::SendError;
 use nohash_hasher::{BuildNoHashHasher, IntMap};
 use tokio::time::{Instant as TokInstant, Interval};
 use tracing::info;
@@ -23,6 +24,8 @@ pub(crate) struct Idle {
     next_id: TaskId,
     next_worker_id: WorkerId,
     workers: Vec<Worker>,
+    // Set via `Scheduler::set_workers`; zero sizes the pool purely on demand.
+    target_workers: usize,
     to_cull: Vec<TaskId>,
 }
 
@@ -44,6 +47,7 @@ impl Idle {
             next_id: TaskId::new(),
             next_worker_id: WorkerId::new(),
             workers: Vec::with_capacity(16),
+            target_workers: 0,
             to_cull: vec![],
         };
 
@@ -108,6 +112,9 @@ impl Idle {
                 Ok(SchedulerMessage::Overspill(worker_id, id, task)) => {
                     self.schedule_mixer(task, id, Some(worker_id));
                 },
+                Ok(SchedulerMessage::SetWorkers(n)) => {
+                    self.set_workers(n.get());
+                },
                 Ok(SchedulerMessage::GetStats(tx)) => {
                     _ = tx.send(self.workers.iter().map(Worker::stats).collect());
                 },
@@ -132,7 +139,9 @@ impl Idle {
                 let mut i = 0;
                 while i < self.workers.len() {
                     if let Some(then) = self.workers[i].try_mark_empty(now) {
-                        if now.duration_since(then) >= self.cull_timer {
+                        if now.duration_since(then) >= self.cull_timer
+                            && self.workers.len() > self.target_workers
+                        {
                             self.workers.swap_remove(i);
                             continue;
                         }
@@ -170,8 +179,11 @@ impl Idle {
                         self.stats.move_mixer_to_live();
                         break;
                     },
-                    Err(e) => {
-                        loop_task = Some(e.0 .1);
+                    Err(SendError(msg)) => {
+                        let WorkerMessage::Schedule(_, task) = msg else {
+                            unreachable!("Only tasks are returned by a failed schedule.")
+                        };
+                        loop_task = Some(task);
                         let worker = self.workers.swap_remove(idx);
 
                         // NOTE: we have incremented worker's live counter for this mixer in
@@ -187,7 +199,10 @@ impl Idle {
         }
     }
 
-    /// Fetch the first `Worker` that has room for a new task, creating one if needed.
+    /// Fetch a `Worker` that has room for a new task, creating one if needed.
+    ///
+    /// Without a target pool size, this is the first worker with room. Otherwise, tasks
+    /// are spread over the least loaded workers.
     ///
     /// If an inbound task has spilled from another thread, then do not reschedule it there.
     fn fetch_worker(
@@ -195,22 +210,66 @@ impl Idle {
         task: &ParkedMixer,
         avoid: Option<WorkerId>,
     ) -> (&mut Worker, usize) {
-        let idx = self
+        let idx = if self.target_workers == 0 {
+            self.workers
+                .iter()
+                .position(|w| w.can_schedule(task, avoid))
+        } else if self.workers.len() < self.target_workers {
+            None
+        } else {
+            self.workers
+                .iter()
+                .enumerate()
+                .filter(|(_, w)| w.can_schedule(task, avoid))
+                .min_by_key(|(_, w)| w.stats().live_mixers())
+                .map(|(i, _)| i)
+        };
+
+        let idx = idx.unwrap_or_else(|| {
+            self.add_worker();
+            self.workers.len() - 1
+        });
+
+        (&mut self.workers[idx], idx)
+    }
+
+    fn add_worker(&mut self) {
+        self.workers.push(Worker::new(
+            self.next_worker_id.incr(),
+            self.config.clone(),
+            self.tx.clone(),
+            self.stats.clone(),
+        ));
+        self.stats.add_worker();
+    }
+
+    /// Resize the worker pool to `n` threads, and rebalance live tasks across it.
+    ///
+    /// Removed workers hand their tasks back as overspill, which are then placed on the
+    /// least loaded remaining workers.
+    fn set_workers(&mut self, n: usize) {
+        self.target_workers = n;
+
+        while self.workers.len() > n {
+            if let Some(worker) = self.workers.pop() {
+                worker.retire();
+            }
+        }
+
+        while self.workers.len() < n {
+            self.add_worker();
+        }
+
+        let loads: Vec<usize> = self
             .workers
             .iter()
-            .position(|w| w.can_schedule(task, avoid))
-            .unwrap_or_else(|| {
-                self.workers.push(Worker::new(
-                    self.next_worker_id.incr(),
-                    self.config.clone(),
-                    self.tx.clone(),
-                    self.stats.clone(),
-                ));
-                self.stats.add_worker();
-                self.workers.len() - 1
-            });
+            .map(|w| w.stats().live_mixers() as usize)
+            .collect();
+        let fair_share = loads.iter().sum::<usize>().div_ceil(n);
 
-        (&mut self.workers[idx], idx)
+        for (worker, load) in self.workers.iter().zip(loads) {
+            worker.shed(load.saturating_sub(fair_share));
+        }
     }
 
     pub fn spawn(mut self) {
@@ -247,6 +306,9 @@ mod test {
         let config = Config {
             strategy: Mode::default(),
             move_expensive_tasks: false,
+            batch_sends: true,
+            offload_encryption: false,
+            pacing: None,
         };
 
         let sched = Scheduler::new(config);
@@ -275,6 +337,52 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn worker_pool_resizes_and_rebalances() {
+        let (mut core, _tx) = Idle::new(Config::default());
+        let mut timer = tokio::time::interval(TIMESTEP_LENGTH);
+
+        let mut next_id = TaskId::new();
+        let mut handles = vec![];
+        for i in 0..8 {
+            let ((mixer, listeners), track_handle) =
+                Mixer::test_with_float_unending(Handle::current(), false);
+
+            let send_mixer = ParkedMixer {
+                mixer: Box::new(mixer),
+                ssrc: i,
+                rtp_sequence: i as u16,
+                rtp_timestamp: i,
+                park_time: TokInstant::now().into(),
+                last_cost: None,
+                cull_handle: None,
+            };
+            core.stats.add_idle_mixer();
+            core.schedule_mixer(send_mixer, next_id.incr(), None);
+            handles.push((track_handle, listeners));
+        }
+        assert_eq!(core.workers.len(), 1);
+
+        let loads = |core: &Idle| -> Vec<u64> {
+            core.workers
+                .iter()
+                .map(|w| w.stats().live_mixers())
+                .collect()
+        };
+
+        core.set_workers(4);
+        while loads(&core) != [2, 2, 2, 2] {
+            assert!(core.run_once(&mut timer).await);
+        }
+
+        core.set_workers(1);
+        while loads(&core) != [8] {
+            assert!(core.run_once(&mut timer).await);
+        }
+        assert_eq!(core.stats.live_mixers(), 8);
+    }
+
     #[tokio::test]
     async fn excess_threads_are_cleaned_up() {
         const TEST_TIMER: Duration = Duration::from_millis(500);
@@ -282,6 +390,9 @@ mod test {
         let config = Config {
             strategy: Mode::MaxPerThread(1.try_into().unwrap()),
             move_expensive_tasks: true,
+            batch_sends: true,
+            offload_encryption: false,
+            pacing: None,
         };
 
         let (mut core, tx) = Idle::new(config.clone());
@@ -6,7 +6,10 @@ use tokio::time::Instant as TokInstant;
 
 use crate::{
     constants::*,
-    driver::tasks::{error::Error as DriverError, mixer::Mixer},
+    driver::{
+        tasks::{error::Error as DriverError, mixer::Mixer},
+        VirtualClock,
+    },
 };
 
 #[cfg(test)]
@@ -14,13 +17,23 @@ use crate::driver::test_config::TickStyle;
 
 use super::*;
 
+/// Control messages sent from the idle task to a worker thread.
+pub enum WorkerMessage {
+    /// Take ownership of a `Mixer` and begin processing its audio.
+    Schedule(TaskId, ParkedMixer),
+    /// Return up to `n` `Mixer`s to the idle task, to be rescheduled on other workers.
+    Shed(usize),
+    /// Return all `Mixer`s to the idle task, and exit.
+    Retire,
+}
+
 /// The send-half of a worker thread, with bookkeeping mechanisms to help
 /// the idle task schedule incoming tasks.
 pub struct Worker {
     id: WorkerId,
     stats: Arc<LiveStatBlock>,
     config: Config,
-    tx: Sender<(TaskId, ParkedMixer)>,
+    tx: Sender<WorkerMessage>,
     known_empty_since: Option<TokInstant>,
 }
 
@@ -101,10 +114,22 @@ impl Worker {
         &mut self,
         id: TaskId,
         task: ParkedMixer,
-    ) -> Result<(), SendError<(TaskId, ParkedMixer)>> {
+    ) -> Result<(), SendError<WorkerMessage>> {
         self.mark_busy();
         self.stats.add_mixer();
-        self.tx.send((id, task))
+        self.tx.send(WorkerMessage::Schedule(id, task))
+    }
+
+    /// Ask this worker to return up to `n` tasks, so that they can be moved elsewhere.
+    pub fn shed(&self, n: usize) {
+        if n > 0 {
+            _ = self.tx.send(WorkerMessage::Shed(n));
+        }
+    }
+
+    /// Ask this worker to return all of its tasks and exit.
+    pub fn retire(self) {
+        _ = self.tx.send(WorkerMessage::Retire);
     }
 
     #[must_use]
@@ -115,6 +140,9 @@ impl Worker {
 
 const PACKETS_PER_BLOCK: usize = 16;
 const MEMORY_CULL_TIMER: Duration = Duration::from_secs(10);
+/// Longest window which packet sends may be paced across, leaving the rest of
+/// each tick for mixing.
+const MAX_PACING_WINDOW: Duration = Duration::from_nanos(TIMESTEP_LENGTH.as_nanos() as u64 / 2);
 
 /// A synchronous thread responsible for mixing, encoding, encrypting, and
 /// sending the audio output of many `Mixer`s.
@@ -136,10 +164,12 @@ pub struct Live {
     config: Config,
     stats: Arc<LiveStatBlock>,
     global_stats: Arc<StatBlock>,
-    rx: Receiver<(TaskId, ParkedMixer)>,
+    rx: Receiver<WorkerMessage>,
     tx: Sender<SchedulerMessage>,
 
     excess_buffer_cull_time: Option<Instant>,
+
+    encryptor: Option<Encryptor>,
 }
 
 #[allow(missing_docs)]
@@ -149,7 +179,7 @@ impl Live {
         config: Config,
         global_stats: Arc<StatBlock>,
         stats: Arc<LiveStatBlock>,
-        rx: Receiver<(TaskId, ParkedMixer)>,
+        rx: Receiver<WorkerMessage>,
         tx: Sender<SchedulerMessage>,
     ) -> Self {
         let to_prealloc = config.strategy.prealloc_size();
@@ -161,6 +191,7 @@ impl Live {
             .min(PACKETS_PER_BLOCK);
 
         let packets = vec![packet_block(block_size)];
+        let encryptor = config.offload_encryption.then(Encryptor::spawn);
 
         Self {
             packets,
@@ -180,6 +211,8 @@ impl Live {
             tx,
 
             excess_buffer_cull_time: None,
+
+            encryptor,
         }
     }
 
@@ -245,12 +278,23 @@ impl Live {
 
         self.timed_remove_excess_blocks(end_of_work);
 
+        // Hand this tick's packets to be encrypted, and take back those of
+        // the previous tick to send in their place.
+        let encrypted = self.pipeline_encryption();
+
         // Wait till the right time to send this packet:
         // usually a 20ms tick, in test modes this is either a finite number of runs or user input.
         self.march_deadline();
 
         // Send all.
-        self.start_of_work = Some(Instant::now());
+        let start_of_sends = Instant::now();
+        self.start_of_work = Some(start_of_sends);
+        let to_send = self.packet_lens.iter().filter(|len| **len > 0).count()
+            + encrypted
+                .iter()
+                .filter(|job| !job.packet().is_empty())
+                .count();
+        let mut pacer = Pacer::new(self.config.pacing, start_of_sends, to_send);
         for (i, (packet_len, mixer)) in self
             .packet_lens
             .iter_mut()
@@ -260,7 +304,12 @@ impl Live {
             let (block, inner) = get_memory_indices(i);
             let packet = &mut self.packets[block][inner..];
             if *packet_len > 0 {
-                let res = mixer.send_packet(&packet[..*packet_len]);
+                if let Some(pacer) = &mut pacer {
+                    pacer.wait();
+                }
+                let res = mixer
+                    .send_packet(&packet[..*packet_len], self.config.batch_sends)
+                    .map(|sent| self.stats.record_send(sent));
                 rebuild_if_err(mixer, res, &mut self.to_cull, i);
             }
             #[cfg(test)]
@@ -270,10 +319,22 @@ impl Live {
             advance_rtp_counters(packet);
         }
 
+        self.send_encrypted(encrypted, pacer.as_mut());
+
+        // Time spent waiting on the pacer is idle, not work.
+        if let Some(pacer) = &pacer {
+            self.start_of_work = Some(start_of_sends + pacer.waited);
+        }
+
         for (i, mixer) in self.tasks.iter_mut().enumerate() {
             let res = mixer
                 .audio_commands_events()
-                .and_then(|()| mixer.check_and_send_keepalive(self.start_of_work));
+                .and_then(|()| mixer.check_and_send_keepalive(self.start_of_work))
+                .map(|sent| {
+                    if sent {
+                        self.stats.record_send(1);
+                    }
+                });
             rebuild_if_err(mixer, res, &mut self.to_cull, i);
         }
 
@@ -322,6 +383,15 @@ impl Live {
 
     #[inline]
     fn march_deadline(&mut self) {
+        if let Some(clock) = self
+            .tasks
+            .first()
+            .and_then(|m| m.config.virtual_clock.clone())
+        {
+            self.march_virtual_clock(&clock);
+            return;
+        }
+
         #[cfg(feature = "internals")]
         {
             return;
@@ -330,12 +400,34 @@ impl Live {
         self._march_deadline();
     }
 
+    /// Block until the next tick of a [`VirtualClock`], keeping every mixer
+    /// on this thread which shares the clock in step.
+    ///
+    /// [`VirtualClock`]: crate::driver::VirtualClock
+    #[inline]
+    fn march_virtual_clock(&mut self, clock: &VirtualClock) {
+        let tick = clock.wait_tick(self.tasks[0].virtual_tick);
+
+        for mixer in &mut self.tasks {
+            if mixer
+                .config
+                .virtual_clock
+                .as_ref()
+                .is_some_and(|c| c.ptr_eq(clock))
+            {
+                mixer.virtual_tick = tick;
+            }
+        }
+
+        self.deadline = Instant::now() + TIMESTEP_LENGTH;
+    }
+
     #[inline]
     fn handle_scheduler_msgs(&mut self) -> Result<(), ()> {
         let mut activation_time = None;
         loop {
             match self.rx.try_recv() {
-                Ok((id, task)) => {
+                Ok(WorkerMessage::Schedule(id, task)) => {
                     self.add_task(
                         task,
                         id,
@@ -346,6 +438,16 @@ impl Live {
                         }),
                     );
                 },
+                Ok(WorkerMessage::Shed(n)) =>
+                    for _ in 0..n.min(self.tasks.len()) {
+                        self.return_mixer(self.tasks.len() - 1, None);
+                    },
+                Ok(WorkerMessage::Retire) => {
+                    while !self.tasks.is_empty() {
+                        self.return_mixer(self.tasks.len() - 1, None);
+                    }
+                    return Err(());
+                },
                 Err(TryRecvError::Empty) => break,
                 Err(TryRecvError::Disconnected) => return Err(()),
             }
@@ -354,6 +456,68 @@ impl Live {
         Ok(())
     }
 
+    /// Moves all built packets awaiting encryption to the encryption thread,
+    /// returning the packets it finished from the previous tick.
+    #[inline]
+    fn pipeline_encryption(&mut self) -> Vec<EncryptJob> {
+        let Some(encryptor) = self.encryptor.as_mut() else {
+            return vec![];
+        };
+
+        let mut jobs = vec![];
+        for (i, (packet_len, mixer)) in self
+            .packet_lens
+            .iter_mut()
+            .zip(self.tasks.iter())
+            .enumerate()
+        {
+            if *packet_len == 0 {
+                continue;
+            }
+
+            if let Some(cipher) = mixer.deferred_cipher() {
+                let (block, inner) = get_memory_indices(i);
+                let packet = &self.packets[block][inner..][..*packet_len];
+                jobs.push(encryptor.job(self.ids[i], cipher.clone(), packet));
+                *packet_len = 0;
+            }
+        }
+
+        let done = encryptor.collect();
+        encryptor.submit(jobs);
+
+        done
+    }
+
+    /// Sends packets returned by the encryption thread via their tasks, if these
+    /// are still held by this worker and connected.
+    #[inline]
+    fn send_encrypted(&mut self, jobs: Vec<EncryptJob>, mut pacer: Option<&mut Pacer>) {
+        for job in &jobs {
+            let Some(i) = self.ids.iter().position(|id| *id == job.id) else {
+                continue;
+            };
+            let mixer = &mut self.tasks[i];
+
+            if job.packet().is_empty() || mixer.conn_active.is_none() {
+                continue;
+            }
+
+            if let Some(pacer) = pacer.as_deref_mut() {
+                pacer.wait();
+            }
+
+            let res = mixer
+                .send_packet(job.packet(), self.config.batch_sends)
+                .map(|sent| self.stats.record_send(sent));
+            rebuild_if_err(mixer, res, &mut self.to_cull, i);
+        }
+
+        if let Some(encryptor) = self.encryptor.as_mut() {
+            encryptor.recycle(jobs);
+        }
+    }
+
     /// Handle messages from each tasks's `Driver`, marking dead tasks for removal.
     #[inline]
     fn handle_task_msgs(&mut self) {
@@ -440,11 +604,17 @@ impl Live {
     /// Return a given mixer to the main scheduler if this worker is overloaded.
     #[inline]
     pub fn offload_mixer(&mut self, idx: usize, cost: Duration) {
+        self.return_mixer(idx, Some(cost));
+    }
+
+    /// Return a given mixer to the main scheduler, to be placed on another worker.
+    #[inline]
+    fn return_mixer(&mut self, idx: usize, cost: Option<Duration>) {
         self.stats.remove_mixer();
 
         if let Some((id, mut parked)) = self.remove_task(idx) {
             self.global_stats.move_mixer_to_idle();
-            parked.last_cost = Some(cost);
+            parked.last_cost = cost;
             _ = self
                 .tx
                 .send(SchedulerMessage::Overspill(self.id, id, parked));
@@ -501,8 +671,11 @@ impl Live {
         let mod_samples = (samples_f64 as u64) as u32;
         let rtp_timestamp = task.rtp_timestamp.wrapping_add(mod_samples);
 
+        let mut mixer = task.mixer;
+        mixer.defer_encryption = self.encryptor.is_some();
+
         self.ids.push(id);
-        self.tasks.push(task.mixer);
+        self.tasks.push(mixer);
         self.packet_lens.push(0);
         self.to_cull.push(false);
 
@@ -632,6 +805,44 @@ impl Live {
     }
 }
 
+/// Spaces out the sends of one tick's packets evenly across a window.
+struct Pacer {
+    start: Instant,
+    gap: Duration,
+    sent: u32,
+    /// Total time spent blocked in [`Self::wait`].
+    waited: Duration,
+}
+
+impl Pacer {
+    /// Returns a pacer for `packets` sends, or `None` if no pacing is needed.
+    ///
+    /// Windows longer than [`MAX_PACING_WINDOW`] are shortened to it.
+    fn new(window: Option<Duration>, start: Instant, packets: usize) -> Option<Self> {
+        let gaps = u32::try_from(packets.checked_sub(1)?)
+            .ok()
+            .filter(|gaps| *gaps > 0)?;
+
+        Some(Self {
+            start,
+            gap: window?.min(MAX_PACING_WINDOW) / gaps,
+            sent: 0,
+            waited: Duration::ZERO,
+        })
+    }
+
+    /// Blocks until the next packet is due to be sent.
+    fn wait(&mut self) {
+        let due = self.start + self.gap * self.sent;
+        let now = Instant::now();
+        if due > now {
+            std::thread::sleep(due - now);
+            self.waited += now.elapsed();
+        }
+        self.sent += 1;
+    }
+}
+
 /// Initialises a packet block of the required size, prefilling any constant RTP data.
 #[inline]
 fn packet_block(n_packets: usize) -> Box<[u8]> {
@@ -714,6 +925,81 @@ mod test {
         assert_eq!(rtp.get_ssrc(), sentinel_val as u32);
     }
 
+    #[test]
+    fn pacer_spreads_sends_across_window() {
+        let window = Duration::from_millis(10);
+        assert!(Pacer::new(None, Instant::now(), 4).is_none());
+        assert!(Pacer::new(Some(window), Instant::now(), 1).is_none());
+
+        let start = Instant::now();
+        let mut pacer = Pacer::new(Some(window), start, 3).unwrap();
+        for _ in 0..3 {
+            pacer.wait();
+        }
+        assert!(start.elapsed() >= window);
+        assert!(pacer.waited >= window / 2);
+    }
+
+    #[test]
+    fn pacer_window_is_clamped_within_tick() {
+        let pacer = Pacer::new(Some(TIMESTEP_LENGTH * 2), Instant::now(), 3).unwrap();
+        assert_eq!(pacer.gap * 2, MAX_PACING_WINDOW);
+    }
+
+    #[tokio::test]
+    async fn offloaded_packets_are_sent_encrypted_a_tick_later() {
+        let mut sched = MockScheduler::new(None);
+        sched.core.config.offload_encryption = true;
+        sched.core.encryptor = Some(Encryptor::spawn());
+
+        let rx = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        rx.set_nonblocking(true).unwrap();
+
+        let (mut mixer, _listeners) = Mixer::test_with_float(1, Handle::current(), false);
+        let conn = mixer.conn_active.as_mut().unwrap();
+        conn.udp_tx = std::net::UdpSocket::bind("127.0.0.1:0").unwrap();
+        conn.udp_tx.connect(rx.local_addr().unwrap()).unwrap();
+        sched.add_mixer_direct(mixer);
+
+        let recv_voice = || {
+            let mut buf = [0u8; VOICE_PACKET_MAX];
+            let mut out = vec![];
+            while let Ok(len) = rx.recv(&mut buf) {
+                // Skip any UDP keepalives.
+                if len > RtpPacket::minimum_packet_size() {
+                    out.push(buf[..len].to_vec());
+                }
+            }
+            out
+        };
+        let header = |pkt: &[u8]| -> (u32, u16, u32) {
+            let rtp = RtpPacket::new(pkt).unwrap();
+            (
+                rtp.get_ssrc(),
+                rtp.get_sequence().into(),
+                rtp.get_timestamp().into(),
+            )
+        };
+
+        // The first tick's packet is only handed to the encryption thread.
+        let first = header(&sched.core.packets[0]);
+        assert!(sched.core.run_once());
+        assert!(recv_voice().is_empty());
+
+        // It is sent on the next tick, with its header unchanged.
+        assert!(sched.core.run_once());
+        let mut sent = recv_voice();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(header(&sent[0]), first);
+
+        #[allow(deprecated)]
+        let cipher = crate::driver::CryptoMode::Normal
+            .cipher_from_key(&[0u8; 32])
+            .unwrap();
+        let mut rtp = MutableRtpPacket::new(&mut sent[0][..]).unwrap();
+        assert!(cipher.decrypt_rtp_in_place(&mut rtp).is_ok());
+    }
+
     #[tokio::test]
     async fn block_alloc_is_partial_small() {
         let n_mixers = 1;
@@ -8,12 +8,14 @@ use crate::{constants::TIMESTEP_LENGTH, Config as DriverConfig};
 use super::tasks::message::{Interconnect, MixerMessage};
 
 mod config;
+mod encrypt;
 mod idle;
 mod live;
 mod stats;
 mod task;
 
 pub use config::*;
+use encrypt::*;
 use idle::*;
 pub use live::*;
 pub use stats::*;
@@ -98,6 +100,20 @@ impl Scheduler {
         self.inner.stats.worker_threads()
     }
 
+    /// Resizes the pool of threads used to process live audio sessions to `workers`
+    /// threads, moving live calls between threads to spread them evenly.
+    ///
+    /// Growing the pool spawns new threads immediately, which take calls from busier
+    /// threads. Shrinking the pool stops the most recently spawned threads, whose calls
+    /// are moved onto those which remain. Threads within this pool size are not culled
+    /// while idle, and new calls are placed on the least busy thread.
+    ///
+    /// The per-thread limits of this scheduler's [`Mode`] still apply: additional threads
+    /// will be spawned if calls cannot otherwise be placed.
+    pub fn set_workers(&self, workers: NonZeroUsize) {
+        _ = self.inner.tx.send(SchedulerMessage::SetWorkers(workers));
+    }
+
     /// Request a list of handles to statistics for currently live workers.
     pub async fn worker_thread_stats(&self) -> Result<Vec<Arc<LiveStatBlock>>, Error> {
         let (tx, rx) = flume::bounded(1);
@@ -137,6 +153,8 @@ pub enum SchedulerMessage {
     Demote(TaskId, ParkedMixer),
     /// Move an expensive `Mixer` to another thread in the worker pool.
     Overspill(WorkerId, TaskId, ParkedMixer),
+    /// Resize the worker pool and rebalance live tasks across it.
+    SetWorkers(NonZeroUsize),
     /// Request a copy of all per-worker statistics.
     GetStats(Sender<Vec<Arc<LiveStatBlock>>>),
     /// Cleanup once all `Scheduler` handles are dropped.
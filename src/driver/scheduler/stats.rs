@@ -86,6 +86,8 @@ impl StatBlock {
 pub struct LiveStatBlock {
     live: AtomicU64,
     last_ns: AtomicU64,
+    packets_sent: AtomicU64,
+    send_syscalls: AtomicU64,
 }
 
 impl LiveStatBlock {
@@ -119,6 +121,30 @@ impl LiveStatBlock {
         self.last_ns.load(Ordering::Relaxed)
     }
 
+    #[inline]
+    pub(crate) fn record_send(&self, packets: usize) {
+        self.packets_sent
+            .fetch_add(packets as u64, Ordering::Relaxed);
+        self.send_syscalls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns the total number of UDP packets sent by this worker thread.
+    #[inline]
+    pub fn packets_sent(&self) -> u64 {
+        self.packets_sent.load(Ordering::Relaxed)
+    }
+
+    /// Returns the total number of send syscalls made by this worker thread.
+    ///
+    /// This is lower than [`Self::packets_sent`] only by the number of keepalives
+    /// sent alongside voice packets, when [`Config::batch_sends`] is enabled.
+    ///
+    /// [`Config::batch_sends`]: super::Config::batch_sends
+    #[inline]
+    pub fn send_syscalls(&self) -> u64 {
+        self.send_syscalls.load(Ordering::Relaxed)
+    }
+
     #[inline]
     pub(crate) fn has_room(&self, strategy: &Mode, task: &ParkedMixer) -> bool {
         let task_room = strategy
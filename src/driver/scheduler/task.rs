@@ -9,15 +9,20 @@ use rand::random;
 use tokio::runtime::Handle;
 
 use crate::{
-    driver::tasks::{
-        error::Error as DriverError,
-        message::{EventMessage, Interconnect, MixerMessage},
-        mixer::Mixer,
+    driver::{
+        tasks::{
+            error::Error as DriverError,
+            message::{EventMessage, Interconnect, MixerMessage},
+            mixer::Mixer,
+        },
+        RtpCounters,
     },
     Config,
 };
 
 use super::SchedulerMessage;
+#[cfg(feature = "rtp-control")]
+use crate::driver::RtpState;
 
 /// Typesafe counter used to identify individual mixer/worker instances.
 #[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
@@ -137,12 +142,19 @@ impl ParkedMixer {
     /// Returns whether the mixer should exit and be cleaned up.
     pub fn handle_message(&mut self, msg: MixerMessage) -> Result<bool, ()> {
         match msg {
-            MixerMessage::SetConn(conn, ssrc) => {
+            MixerMessage::SetConn(conn, ssrc, counters) => {
                 // Overridden because payload-specific fields are carried
                 // externally on `ParkedMixer`.
+                let counters = counters.unwrap_or_else(RtpCounters::random);
                 self.ssrc = ssrc;
-                self.rtp_sequence = random::<u16>();
-                self.rtp_timestamp = random::<u32>();
+                self.rtp_sequence = counters.sequence;
+                self.rtp_timestamp = counters.timestamp;
+                #[cfg(feature = "rtp-control")]
+                self.mixer.rtp_override.apply(
+                    &mut self.ssrc,
+                    &mut self.rtp_sequence,
+                    &mut self.rtp_timestamp,
+                );
                 self.mixer.conn_active = Some(conn);
                 self.mixer.update_keepalive(ssrc);
 
@@ -155,6 +167,44 @@ impl ParkedMixer {
 
                 Ok(false)
             },
+            #[cfg(feature = "rtp-control")]
+            MixerMessage::SetRtpOverride(rtp_override) => {
+                // Overridden for the same reason as `SetConn`.
+                self.mixer.rtp_override = rtp_override;
+                if self.mixer.conn_active.is_some() {
+                    rtp_override.apply(
+                        &mut self.ssrc,
+                        &mut self.rtp_sequence,
+                        &mut self.rtp_timestamp,
+                    );
+                }
+
+                Ok(false)
+            },
+            #[cfg(feature = "rtp-control")]
+            MixerMessage::GetRtpState(tx) => {
+                let state = self.mixer.conn_active.as_ref().map(|_| RtpState {
+                    ssrc: self.ssrc,
+                    sequence: self.rtp_sequence,
+                    timestamp: self.rtp_timestamp,
+                });
+                drop(tx.send(state));
+
+                Ok(false)
+            },
+            MixerMessage::ExportSession(session, tx) => {
+                // Overridden for the same reason as `SetConn`.
+                let session = self.mixer.export_session(
+                    *session,
+                    RtpCounters {
+                        sequence: self.rtp_sequence,
+                        timestamp: self.rtp_timestamp,
+                    },
+                );
+                drop(tx.send(session));
+
+                Ok(false)
+            },
             msg => {
                 let (events_failure, conn_failure, should_exit) =
                     self.mixer.handle_message(msg, &mut []);
@@ -177,10 +227,12 @@ impl ParkedMixer {
         //  thread *cares*, so we can prevent wakeups?
         //  Can we do the same for live tracks?
         let mut events_failure = self.mixer.fire_event(EventMessage::Tick).is_err();
+        self.mixer.check_auto_leave(now);
 
         let ka_err = self
             .mixer
             .check_and_send_keepalive(Some(now))
+            .map(|_| ())
             .or_else(DriverError::disarm_would_block);
 
         let conn_failure = if let Err(e) = ka_err {
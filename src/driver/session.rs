@@ -0,0 +1,181 @@
+use super::CryptoMode;
+use crate::{
+    id::{ChannelId, GuildId, UserId},
+    ConnectionInfo,
+};
+use rand::random;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    net::IpAddr,
+    num::NonZeroU64,
+};
+
+/// The minimal state of an established voice session, allowing it to be adopted
+/// by another [`Driver`] without rejoining its voice channel.
+///
+/// Sessions are exported by [`Driver::export_session`] and adopted by
+/// [`Driver::resume_session`], e.g., to hand a call over to a new process during a
+/// zero-downtime deploy. This type can be (de)serialised with serde for transfer
+/// between processes.
+///
+/// This holds the session's token and encryption key, and so must be stored and
+/// transferred securely.
+///
+/// [`Driver`]: super::Driver
+/// [`Driver::export_session`]: super::Driver::export_session
+/// [`Driver::resume_session`]: super::Driver::resume_session
+#[derive(Clone, Deserialize, Eq, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct VoiceSession {
+    /// ID of the voice channel joined, if it is known.
+    pub channel_id: Option<NonZeroU64>,
+    /// URL of the voice websocket gateway server assigned to this call.
+    pub endpoint: String,
+    /// ID of the voice channel's parent guild.
+    pub guild_id: NonZeroU64,
+    /// Unique string describing this session.
+    pub session_id: String,
+    /// Ephemeral secret used to validate this session.
+    pub token: String,
+    /// ID of this bot.
+    pub user_id: NonZeroU64,
+    /// RTP SSRC assigned to this bot by the voice server.
+    pub ssrc: u32,
+    /// Encryption mode negotiated with the voice server.
+    pub crypto_mode: CryptoMode,
+    /// Secret key used to encrypt and decrypt voice packets.
+    pub secret_key: Vec<u8>,
+    /// Address of the voice server's UDP socket.
+    pub remote_ip: IpAddr,
+    /// Port of the voice server's UDP socket.
+    pub remote_port: u16,
+    /// Nonce counter of the next voice packet, for encryption modes which
+    /// increment a nonce with each packet.
+    ///
+    /// An adopting driver continues from this value, so that no nonce is ever
+    /// reused under `secret_key`.
+    pub nonce: Option<u32>,
+    /// RTP sequence number of the next voice packet.
+    pub sequence: u16,
+    /// RTP timestamp of the next voice packet, in 48kHz samples.
+    pub timestamp: u32,
+}
+
+impl VoiceSession {
+    /// Returns the connection information used to establish this session.
+    #[must_use]
+    pub fn info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            channel_id: self.channel_id.map(ChannelId),
+            endpoint: self.endpoint.clone(),
+            guild_id: GuildId(self.guild_id),
+            session_id: self.session_id.clone(),
+            token: self.token.clone(),
+            user_id: UserId(self.user_id),
+        }
+    }
+
+    pub(crate) fn new(
+        info: &ConnectionInfo,
+        ssrc: u32,
+        crypto_mode: CryptoMode,
+        secret_key: Vec<u8>,
+        remote: (IpAddr, u16),
+    ) -> Self {
+        Self {
+            channel_id: info.channel_id.map(|id| id.0),
+            endpoint: info.endpoint.clone(),
+            guild_id: info.guild_id.0,
+            session_id: info.session_id.clone(),
+            token: info.token.clone(),
+            user_id: info.user_id.0,
+            ssrc,
+            crypto_mode,
+            secret_key,
+            remote_ip: remote.0,
+            remote_port: remote.1,
+            nonce: None,
+            sequence: 0,
+            timestamp: 0,
+        }
+    }
+
+    pub(crate) fn rtp_counters(&self) -> RtpCounters {
+        RtpCounters {
+            sequence: self.sequence,
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+/// RTP header counters of the next voice packet, carried over when a session
+/// is adopted by another driver.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RtpCounters {
+    pub sequence: u16,
+    pub timestamp: u32,
+}
+
+impl RtpCounters {
+    /// Returns randomised counters, used when starting a new session.
+    pub(crate) fn random() -> Self {
+        Self {
+            sequence: random::<u16>(),
+            timestamp: random::<u32>(),
+        }
+    }
+}
+
+impl Debug for VoiceSession {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("VoiceSession")
+            .field("channel_id", &self.channel_id)
+            .field("endpoint", &self.endpoint)
+            .field("guild_id", &self.guild_id)
+            .field("session_id", &self.session_id)
+            .field("token", &"<secret>")
+            .field("user_id", &self.user_id)
+            .field("ssrc", &self.ssrc)
+            .field("crypto_mode", &self.crypto_mode)
+            .field("secret_key", &"<secret>")
+            .field("remote_ip", &self.remote_ip)
+            .field("remote_port", &self.remote_port)
+            .field("nonce", &self.nonce)
+            .field("sequence", &self.sequence)
+            .field("timestamp", &self.timestamp)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_round_trips_through_json() {
+        let info = ConnectionInfo {
+            channel_id: Some(ChannelId(NonZeroU64::new(3).unwrap())),
+            endpoint: "voice.example.com".into(),
+            guild_id: GuildId(NonZeroU64::new(1).unwrap()),
+            session_id: "session".into(),
+            token: "token".into(),
+            user_id: UserId(NonZeroU64::new(2).unwrap()),
+        };
+        let session = VoiceSession::new(
+            &info,
+            1234,
+            CryptoMode::XChaCha20Poly1305,
+            vec![7; 32],
+            ([127, 0, 0, 1].into(), 5000),
+        );
+
+        let json = serde_json::to_string(&session).unwrap();
+        assert!(json.contains("aead_xchacha20_poly1305_rtpsize"));
+
+        let parsed: VoiceSession = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, session);
+        assert_eq!(parsed.info(), info);
+        assert!(!format!("{parsed:?}").contains("\"token\""));
+    }
+}
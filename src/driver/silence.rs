@@ -0,0 +1,61 @@
+use std::time::Duration;
+
+use crate::constants::TIMESTEP_LENGTH;
+
+/// Settings for detecting sustained silence in mixed audio, during which the
+/// driver stops transmitting.
+///
+/// Once all mixed output stays at or below `threshold` for `hold`, the driver sends
+/// Discord's usual trailing silence frames, clears its speaking flag, and stops
+/// sending packets until audible output returns. This avoids spending bandwidth on
+/// full-bitrate Opus frames of nothing during long quiet passages.
+///
+/// Opus passthrough frames cannot be inspected, and always count as audible.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct SilenceDetection {
+    /// Peak sample amplitude at or below which a frame is considered silent.
+    ///
+    /// Defaults to `1e-4` (-80 dBFS).
+    pub threshold: f32,
+    /// How long output must remain silent before transmission stops.
+    ///
+    /// Defaults to 500ms.
+    pub hold: Duration,
+}
+
+impl Default for SilenceDetection {
+    fn default() -> Self {
+        Self {
+            threshold: 1e-4,
+            hold: Duration::from_millis(500),
+        }
+    }
+}
+
+impl SilenceDetection {
+    /// Sets the peak amplitude at or below which a frame is considered silent.
+    #[must_use]
+    pub fn threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// Sets how long output must remain silent before transmission stops.
+    #[must_use]
+    pub fn hold(mut self, hold: Duration) -> Self {
+        self.hold = hold;
+        self
+    }
+
+    /// Returns the number of consecutive silent frames needed to stop transmission.
+    pub(crate) fn hold_frames(&self) -> u32 {
+        let frames = self.hold.as_nanos().div_ceil(TIMESTEP_LENGTH.as_nanos());
+        u32::try_from(frames).unwrap_or(u32::MAX).max(1)
+    }
+
+    /// Returns whether the given interleaved samples are all considered silent.
+    pub(crate) fn is_silent(&self, samples: &[f32]) -> bool {
+        samples.iter().all(|s| s.abs() <= self.threshold)
+    }
+}
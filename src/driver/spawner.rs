@@ -0,0 +1,162 @@
+use flume::Receiver;
+use futures::future::BoxFuture;
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    future::Future,
+    sync::Arc,
+};
+use tokio::runtime::Handle;
+
+/// An executor able to run a driver's async tasks.
+///
+/// Tasks are polled to completion by the executor. Driver tasks use Tokio's
+/// networking and timers, so each task must be polled from within the context
+/// of a Tokio runtime (e.g., via [`Handle::enter`]).
+pub trait Executor: Send + Sync {
+    /// Runs `task` until completion.
+    fn spawn(&self, task: BoxFuture<'static, ()>);
+}
+
+/// Controls where a driver's async tasks are spawned.
+///
+/// By default, drivers share the Tokio runtime from which they were created
+/// (e.g., the runtime which called [`Songbird::join`]). Bots running heavy HTTP
+/// or database workloads on that runtime may see jitter in voice connections;
+/// handing drivers a handle to a dedicated runtime isolates them from this.
+///
+/// This affects the driver's core, event, websocket, and UDP receive tasks, and
+/// any tasks they spawn in turn. Audio mixing is controlled separately by the
+/// [`Scheduler`].
+///
+/// [`Songbird::join`]: crate::Songbird::join
+/// [`Scheduler`]: super::Scheduler
+#[derive(Clone, Default)]
+pub struct Spawner {
+    kind: SpawnerKind,
+}
+
+#[derive(Clone, Default)]
+enum SpawnerKind {
+    #[default]
+    Current,
+    Runtime(Handle),
+    Custom(Arc<dyn Executor>),
+}
+
+impl Spawner {
+    /// Spawns tasks on whichever Tokio runtime the driver is created from.
+    #[must_use]
+    pub fn current() -> Self {
+        Self::default()
+    }
+
+    /// Spawns tasks on the Tokio runtime referred to by `handle`.
+    #[must_use]
+    pub fn runtime(handle: Handle) -> Self {
+        Self {
+            kind: SpawnerKind::Runtime(handle),
+        }
+    }
+
+    /// Spawns tasks using a custom [`Executor`].
+    #[must_use]
+    pub fn custom(executor: impl Executor + 'static) -> Self {
+        Self {
+            kind: SpawnerKind::Custom(Arc::new(executor)),
+        }
+    }
+
+    /// Spawns `fut`, returning a handle which completes once it has finished
+    /// or been dropped by the executor.
+    pub(crate) fn spawn<F>(&self, fut: F) -> TaskHandle
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let (done_tx, done_rx) = flume::bounded(1);
+        let task = async move {
+            fut.await;
+            drop(done_tx);
+        };
+
+        match &self.kind {
+            SpawnerKind::Current => drop(tokio::spawn(task)),
+            SpawnerKind::Runtime(handle) => drop(handle.spawn(task)),
+            SpawnerKind::Custom(executor) => executor.spawn(Box::pin(task)),
+        }
+
+        TaskHandle(done_rx)
+    }
+}
+
+impl Debug for Spawner {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        let kind = match &self.kind {
+            SpawnerKind::Current => "Current",
+            SpawnerKind::Runtime(_) => "Runtime",
+            SpawnerKind::Custom(_) => "Custom",
+        };
+
+        f.debug_tuple("Spawner").field(&kind).finish()
+    }
+}
+
+/// Signals the end of a task started by a [`Spawner`].
+///
+/// Dropping this handle detaches the task.
+#[derive(Debug)]
+pub(crate) struct TaskHandle(Receiver<()>);
+
+impl TaskHandle {
+    /// Waits for the task to exit.
+    pub(crate) async fn join(&self) {
+        _ = self.0.recv_async().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Counting {
+        spawned: AtomicUsize,
+    }
+
+    impl Executor for Arc<Counting> {
+        fn spawn(&self, task: BoxFuture<'static, ()>) {
+            self.spawned.fetch_add(1, Ordering::Relaxed);
+            drop(tokio::spawn(task));
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn custom_executor_runs_tasks() {
+        let executor = Arc::new(Counting::default());
+        let spawner = Spawner::custom(executor.clone());
+
+        let (tx, rx) = flume::bounded(1);
+        spawner
+            .spawn(async move {
+                drop(tx.send(()));
+            })
+            .join()
+            .await;
+
+        assert_eq!(executor.spawned.load(Ordering::Relaxed), 1);
+        assert!(rx.try_recv().is_ok());
+    }
+
+    #[test]
+    fn runtime_handle_runs_tasks_outside_runtime() {
+        let rt = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(1)
+            .build()
+            .unwrap();
+        let spawner = Spawner::runtime(rt.handle().clone());
+
+        let handle = spawner.spawn(async {});
+        rt.block_on(handle.join());
+    }
+}
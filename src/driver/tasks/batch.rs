@@ -0,0 +1,193 @@
+#[cfg(feature = "receive")]
+use bytes::BytesMut;
+#[cfg(feature = "receive")]
+use std::io::ErrorKind as IoErrorKind;
+use std::{io::Result as IoResult, net::UdpSocket};
+#[cfg(all(feature = "receive", target_os = "linux"))]
+use tokio::io::Interest;
+#[cfg(feature = "receive")]
+use tokio::net::UdpSocket as AsyncUdpSocket;
+
+/// Largest number of datagrams read by a single call to [`recv_batch`].
+#[cfg(feature = "receive")]
+pub const MAX_RECV_BATCH: usize = 64;
+
+/// Sends several datagrams over a connected socket, using as few syscalls as
+/// the platform allows.
+///
+/// Returns the number of leading datagrams which were sent. On Linux, this uses a
+/// single `sendmmsg` call (falling back to individual sends if unsupported by the
+/// kernel); elsewhere, each datagram is sent in turn.
+pub fn send_batch<const N: usize>(socket: &UdpSocket, packets: [&[u8]; N]) -> IoResult<usize> {
+    #[cfg(target_os = "linux")]
+    match sendmmsg(socket, packets) {
+        Err(e) if e.raw_os_error() == Some(libc::ENOSYS) => {},
+        res => return res,
+    }
+
+    send_each(socket, packets)
+}
+
+fn send_each<const N: usize>(socket: &UdpSocket, packets: [&[u8]; N]) -> IoResult<usize> {
+    for (i, packet) in packets.iter().enumerate() {
+        if let Err(e) = socket.send(packet) {
+            return if i == 0 { Err(e) } else { Ok(i) };
+        }
+    }
+
+    Ok(N)
+}
+
+#[cfg(target_os = "linux")]
+fn sendmmsg<const N: usize>(socket: &UdpSocket, packets: [&[u8]; N]) -> IoResult<usize> {
+    use std::{io::Error as IoError, os::fd::AsRawFd};
+
+    let mut iovecs = packets.map(|p| libc::iovec {
+        iov_base: p.as_ptr() as *mut libc::c_void,
+        iov_len: p.len(),
+    });
+
+    // SAFETY: `mmsghdr` is a plain C struct, for which all-zeroes is a valid
+    // (empty) value.
+    let mut msgs: [libc::mmsghdr; N] = unsafe { std::mem::zeroed() };
+    for (msg, iov) in msgs.iter_mut().zip(iovecs.iter_mut()) {
+        msg.msg_hdr.msg_iov = iov;
+        msg.msg_hdr.msg_iovlen = 1;
+    }
+
+    // SAFETY: every header points to exactly one live iovec, which in turn
+    // borrows a packet outliving this call. The socket is connected, so no
+    // destination addresses are needed.
+    #[allow(clippy::cast_possible_truncation)]
+    let sent = unsafe { libc::sendmmsg(socket.as_raw_fd(), msgs.as_mut_ptr(), N as _, 0) };
+
+    #[allow(clippy::cast_sign_loss)]
+    if sent < 0 {
+        Err(IoError::last_os_error())
+    } else {
+        Ok(sent as usize)
+    }
+}
+
+/// Reads as many waiting datagrams as fit into `bufs` without blocking, storing
+/// the length of each in `lens`.
+///
+/// Returns the number of datagrams read, or a `WouldBlock` error if none were
+/// waiting. On Linux, this uses a single `recvmmsg` call; elsewhere, each datagram
+/// is read in turn. At most [`MAX_RECV_BATCH`] datagrams are read per call.
+#[cfg(feature = "receive")]
+pub fn recv_batch(
+    socket: &AsyncUdpSocket,
+    bufs: &mut [BytesMut],
+    lens: &mut [usize],
+) -> IoResult<usize> {
+    let n = bufs.len().min(lens.len()).min(MAX_RECV_BATCH);
+
+    #[cfg(target_os = "linux")]
+    match socket.try_io(Interest::READABLE, || {
+        recvmmsg(socket, &mut bufs[..n], &mut lens[..n])
+    }) {
+        Err(e) if e.raw_os_error() == Some(libc::ENOSYS) => {},
+        res => return res,
+    }
+
+    let mut read = 0;
+    while read < n {
+        match socket.try_recv(&mut bufs[read]) {
+            Ok(len) => {
+                lens[read] = len;
+                read += 1;
+            },
+            Err(e) if read == 0 || e.kind() != IoErrorKind::WouldBlock => return Err(e),
+            Err(_) => break,
+        }
+    }
+
+    Ok(read)
+}
+
+#[cfg(all(feature = "receive", target_os = "linux"))]
+fn recvmmsg(socket: &AsyncUdpSocket, bufs: &mut [BytesMut], lens: &mut [usize]) -> IoResult<usize> {
+    use std::{io::Error as IoError, os::fd::AsRawFd, ptr};
+
+    // SAFETY: `iovec` and `mmsghdr` are plain C structs, for which all-zeroes is a
+    // valid (empty) value.
+    let mut iovecs: [libc::iovec; MAX_RECV_BATCH] = unsafe { std::mem::zeroed() };
+    let mut msgs: [libc::mmsghdr; MAX_RECV_BATCH] = unsafe { std::mem::zeroed() };
+
+    for ((buf, iov), msg) in bufs.iter_mut().zip(iovecs.iter_mut()).zip(msgs.iter_mut()) {
+        iov.iov_base = buf.as_mut_ptr().cast();
+        iov.iov_len = buf.len();
+        msg.msg_hdr.msg_iov = iov;
+        msg.msg_hdr.msg_iovlen = 1;
+    }
+
+    // SAFETY: the first `bufs.len()` headers each point to one live iovec, which in
+    // turn mutably borrows a buffer outliving this call.
+    #[allow(clippy::cast_possible_truncation)]
+    let read = unsafe {
+        libc::recvmmsg(
+            socket.as_raw_fd(),
+            msgs.as_mut_ptr(),
+            bufs.len() as _,
+            libc::MSG_DONTWAIT,
+            ptr::null_mut(),
+        )
+    };
+
+    #[allow(clippy::cast_sign_loss)]
+    if read < 0 {
+        Err(IoError::last_os_error())
+    } else {
+        let read = read as usize;
+        for (len, msg) in lens.iter_mut().zip(&msgs[..read]) {
+            *len = msg.msg_len as usize;
+        }
+
+        Ok(read)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batch_delivers_all_packets_in_order() {
+        let rx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let tx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tx.connect(rx.local_addr().unwrap()).unwrap();
+
+        assert_eq!(send_batch(&tx, [&[1u8, 2][..], &[3u8][..]]).unwrap(), 2);
+
+        let mut buf = [0u8; 8];
+        assert_eq!(rx.recv(&mut buf).unwrap(), 2);
+        assert_eq!(&buf[..2], &[1, 2]);
+        assert_eq!(rx.recv(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], 3);
+    }
+
+    #[cfg(feature = "receive")]
+    #[tokio::test]
+    async fn batch_reads_all_waiting_packets() {
+        let rx = AsyncUdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let tx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        tx.connect(rx.local_addr().unwrap()).unwrap();
+
+        for i in 1..=3u8 {
+            tx.send(&vec![i; usize::from(i)]).unwrap();
+        }
+
+        let mut bufs = vec![BytesMut::zeroed(8); 4];
+        let mut lens = [0; 4];
+        let mut packets = vec![];
+        while packets.len() < 3 {
+            rx.readable().await.unwrap();
+            if let Ok(n) = recv_batch(&rx, &mut bufs, &mut lens) {
+                packets.extend((0..n).map(|i| bufs[i][..lens[i]].to_vec()));
+            }
+        }
+
+        assert_eq!(packets, vec![vec![1], vec![2, 2], vec![3, 3, 3]]);
+    }
+}
@@ -20,11 +20,17 @@ pub type Result<T> = std::result::Result<T, Error>;
 #[non_exhaustive]
 pub enum Error {
     Crypto(CryptoError),
-    #[cfg(any(feature = "receive", test))]
+    #[cfg(any(feature = "receive", feature = "mock-server", test))]
     /// Received an illegal voice packet on the voice UDP socket.
     IllegalVoicePacket,
     InterconnectFailure(Recipient),
     Io(IoError),
+    /// An external gateway's [`RtpProtector`] failed to protect a packet.
+    ///
+    /// The cause is logged when the failure occurs.
+    ///
+    /// [`RtpProtector`]: crate::driver::RtpProtector
+    Protection,
     Other,
 }
 
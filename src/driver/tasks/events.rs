@@ -1,18 +1,22 @@
 use super::message::*;
 use crate::{
-    events::{EventStore, GlobalEvents, TrackEvent},
+    events::{CallContext, CoreContext, EventStore, GlobalEvents, TrackEvent},
     tracks::{ReadyState, TrackHandle, TrackState},
 };
 use flume::Receiver;
 use tracing::{debug, info, instrument, trace};
 
-#[instrument(skip(evt_rx))]
-pub(crate) async fn runner(evt_rx: Receiver<EventMessage>) {
-    let mut global = GlobalEvents::default();
+#[instrument(skip(evt_rx, call))]
+pub(crate) async fn runner(evt_rx: Receiver<EventMessage>, call: CallContext) {
+    let mut global = GlobalEvents {
+        call,
+        ..Default::default()
+    };
 
     let mut events: Vec<EventStore> = vec![];
     let mut states: Vec<TrackState> = vec![];
     let mut handles: Vec<TrackHandle> = vec![];
+    let mut exit_ack = None;
 
     while let Ok(msg) = evt_rx.recv_async().await {
         match msg {
@@ -33,6 +37,13 @@ pub(crate) async fn runner(evt_rx: Receiver<EventMessage>) {
                 event_store.add_event(data, state.position);
             },
             EventMessage::FireCoreEvent(ctx) => {
+                match &ctx {
+                    CoreContext::DriverConnect(data) | CoreContext::DriverReconnect(data) =>
+                        global.call.observe(&data.info),
+                    CoreContext::DriverDisconnect(data) => global.call.observe(&data.info),
+                    _ => {},
+                }
+
                 let ctx = ctx.to_user_context();
                 let evt = ctx
                     .to_core_event()
@@ -66,6 +77,7 @@ pub(crate) async fn runner(evt_rx: Receiver<EventMessage>) {
                 match change {
                     TrackStateChange::Mode(mut mode) => {
                         std::mem::swap(&mut state.playing, &mut mode);
+                        state.recapture();
                         if state.playing != mode {
                             global.fire_track_event(state.playing.as_track_event(), i);
                             if let Some(other_evts) = state.playing.also_fired_track_events() {
@@ -78,22 +90,34 @@ pub(crate) async fn runner(evt_rx: Receiver<EventMessage>) {
                     TrackStateChange::Volume(vol) => {
                         state.volume = vol;
                     },
+                    TrackStateChange::Pan(pan) => {
+                        state.pan = pan;
+                    },
+                    TrackStateChange::Mute(muted) => {
+                        state.muted = muted;
+                    },
                     TrackStateChange::Position(pos) => {
                         // Currently, only Tick should fire time events.
                         state.position = pos;
+                        state.recapture();
                     },
                     TrackStateChange::Loops(loops, user_set) => {
                         state.loops = loops;
                         if !user_set {
+                            state.loop_count += 1;
                             global.fire_track_event(TrackEvent::Loop, i);
                         }
                     },
+                    TrackStateChange::Truncated => {
+                        global.fire_track_event(TrackEvent::Truncated, i);
+                    },
                     TrackStateChange::Total(new) => {
                         // Massive, unprecedented state changes.
                         *state = new;
                     },
                     TrackStateChange::Ready(ready_state) => {
                         state.ready = ready_state;
+                        state.recapture();
 
                         match ready_state {
                             ReadyState::Playable => {
@@ -105,6 +129,12 @@ pub(crate) async fn runner(evt_rx: Receiver<EventMessage>) {
                             ReadyState::Uninitialised => {},
                         }
                     },
+                    TrackStateChange::Prepared(time) => {
+                        state.ready = ReadyState::Playable;
+                        state.preparation_time = Some(time);
+                        state.recapture();
+                        global.fire_track_event(TrackEvent::Playable, i);
+                    },
                 }
             },
             EventMessage::RemoveAllTracks => {
@@ -131,9 +161,17 @@ pub(crate) async fn runner(evt_rx: Receiver<EventMessage>) {
                     }
                 }
             },
+            EventMessage::Shutdown(tx) => {
+                exit_ack = Some(tx);
+                break;
+            },
             EventMessage::Poison => break,
         }
     }
 
     trace!("Event thread exited.");
+
+    if let Some(tx) = exit_ack {
+        _ = tx.send(());
+    }
 }
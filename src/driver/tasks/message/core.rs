@@ -1,18 +1,45 @@
 #![allow(missing_docs)]
 
+#[cfg(feature = "rtp-control")]
+use crate::driver::{RtpOverride, RtpState};
 use crate::{
-    driver::{connection::error::Error, Bitrate, Config},
-    events::{context_data::DisconnectReason, EventData},
-    tracks::{Track, TrackCommand, TrackHandle},
+    driver::{
+        connection::error::Error,
+        rtp_extension::RtpExtension,
+        AnalysisTap,
+        Bitrate,
+        Config,
+        CryptoMode,
+        UdpSendStats,
+        VoiceGateway,
+        VoiceSession,
+    },
+    events::{
+        context_data::{ChannelMoveData, DisconnectReason, DriverTask, TeardownCause},
+        EventData,
+    },
+    model::id::UserId,
+    tracks::{Track, TrackCommand, TrackHandle, TrackState},
     ConnectionInfo,
 };
 use flume::{Receiver, Sender};
+use std::{sync::Arc, time::Duration};
 
 pub enum CoreMessage {
-    ConnectWithResult(ConnectionInfo, Sender<Result<(), Error>>),
+    ConnectWithResult(
+        ConnectionInfo,
+        Option<Vec<CryptoMode>>,
+        Sender<Result<(), Error>>,
+    ),
+    ConnectGateway(Arc<dyn VoiceGateway>, Sender<Result<(), Error>>),
     RetryConnect(usize),
+    ResumeSession(VoiceSession, Sender<Result<(), Error>>),
+    ExportSession(Sender<Option<VoiceSession>>),
     SignalWsClosure(usize, ConnectionInfo, Option<DisconnectReason>),
     Disconnect,
+    AutoLeave(DisconnectReason),
+    SetMemberPresent(UserId, bool),
+    ChannelMoved(ChannelMoveData),
     SetTrack(Option<TrackContext>),
     AddTrack(TrackContext),
     SetBitrate(Bitrate),
@@ -20,9 +47,26 @@ pub enum CoreMessage {
     RemoveGlobalEvents,
     SetConfig(Config),
     Mute(bool),
+    PauseAll(bool),
+    SetRtpExtensions(Vec<RtpExtension>),
+    #[cfg(feature = "rtp-control")]
+    SetRtpOverride(RtpOverride),
+    #[cfg(feature = "rtp-control")]
+    GetRtpState(Sender<Option<RtpState>>),
+    GetTracks(Sender<Vec<(TrackHandle, TrackState)>>),
+    GetCryptoMode(Sender<Option<CryptoMode>>),
+    GetUdpSendStats(Sender<Option<UdpSendStats>>),
+    SetAnalysis(Option<AnalysisTap>),
+    #[cfg(feature = "raw-gateway")]
+    SendGatewayPayload(serde_json::Value),
+    #[cfg(feature = "receive")]
+    DumpLast(UserId, Duration, Sender<Option<Vec<i16>>>),
     Reconnect,
-    FullReconnect,
+    Rebind,
+    FullReconnect(TeardownCause),
     RebuildInterconnect,
+    TaskPanicked(usize, DriverTask, String),
+    Shutdown(Option<Duration>, Sender<()>),
     Poison,
 }
 
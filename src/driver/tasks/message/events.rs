@@ -4,6 +4,7 @@ use crate::{
     events::{CoreContext, EventData, EventStore},
     tracks::{LoopState, PlayMode, ReadyState, TrackHandle, TrackState},
 };
+use flume::Sender;
 use std::time::Duration;
 
 pub enum EventMessage {
@@ -19,6 +20,7 @@ pub enum EventMessage {
     RemoveAllTracks,
     Tick,
 
+    Shutdown(Sender<()>),
     Poison,
 }
 
@@ -26,9 +28,14 @@ pub enum EventMessage {
 pub enum TrackStateChange {
     Mode(PlayMode),
     Volume(f32),
+    Pan(f32),
+    Mute(bool),
     Position(Duration),
     // Bool indicates user-set.
     Loops(LoopState, bool),
     Total(TrackState),
     Ready(ReadyState),
+    /// The track became playable after preparing for the given time.
+    Prepared(Duration),
+    Truncated,
 }
@@ -3,23 +3,73 @@
 #[cfg(feature = "receive")]
 use super::UdpRxMessage;
 use super::{Interconnect, TrackContext, WsMessage};
+#[cfg(feature = "rtp-control")]
+use crate::driver::{RtpOverride, RtpState};
 
 use crate::{
-    driver::{crypto::Cipher, Bitrate, Config, CryptoState},
+    driver::{
+        crypto::Cipher,
+        rtp_extension::RtpExtension,
+        AnalysisTap,
+        Bitrate,
+        Config,
+        CryptoState,
+        RtpCounters,
+        RtpProtector,
+        UdpSendStats,
+        VoiceSession,
+    },
     input::{AudioStreamError, Compose, Parsed},
+    model::id::UserId,
+    tracks::{TrackHandle, TrackState},
 };
 use flume::Sender;
+#[cfg(feature = "receive")]
+use std::time::Duration;
 use std::{net::UdpSocket, sync::Arc};
 use symphonia_core::{errors::Error as SymphoniaError, formats::SeekedTo};
 
 pub struct MixerConnection {
-    pub cipher: Cipher,
-    pub crypto_state: CryptoState,
+    pub crypto: PacketCrypto,
     #[cfg(feature = "receive")]
     pub udp_rx: Sender<UdpRxMessage>,
     pub udp_tx: UdpSocket,
 }
 
+/// How outbound voice packets are framed and encrypted.
+pub enum PacketCrypto {
+    /// Discord's transport encryption, negotiated over the voice gateway.
+    Discord(Cipher, CryptoState),
+    /// Plain RTP to an external gateway, optionally protected by the gateway.
+    External(Option<Arc<dyn RtpProtector>>),
+}
+
+impl PacketCrypto {
+    /// Returns whether this connection is to a Discord voice server.
+    #[must_use]
+    pub fn is_discord(&self) -> bool {
+        matches!(self, Self::Discord(..))
+    }
+
+    /// Returns the number of bytes placed before each packet's payload.
+    #[must_use]
+    pub fn payload_prefix_len(&self) -> usize {
+        match self {
+            Self::Discord(_, state) => state.kind().payload_prefix_len2(),
+            Self::External(_) => 0,
+        }
+    }
+
+    /// Returns the number of bytes placed after each packet's payload.
+    #[must_use]
+    pub fn payload_suffix_len(&self) -> usize {
+        match self {
+            Self::Discord(_, state) => state.kind().payload_suffix_len(),
+            Self::External(protector) => protector.as_ref().map_or(0, |p| p.overhead()),
+        }
+    }
+}
+
 pub enum MixerMessage {
     AddTrack(TrackContext),
     SetTrack(Option<TrackContext>),
@@ -27,14 +77,30 @@ pub enum MixerMessage {
     SetBitrate(Bitrate),
     SetConfig(Config),
     SetMute(bool),
+    SetPauseAll(bool),
+    SetRtpExtensions(Vec<RtpExtension>),
+    #[cfg(feature = "rtp-control")]
+    SetRtpOverride(RtpOverride),
+    #[cfg(feature = "rtp-control")]
+    GetRtpState(Sender<Option<RtpState>>),
+    GetTracks(Sender<Vec<(TrackHandle, TrackState)>>),
+    GetUdpSendStats(Sender<Option<UdpSendStats>>),
+    SetAnalysis(Option<AnalysisTap>),
+    #[cfg(feature = "receive")]
+    DumpLast(UserId, Duration, Sender<Option<Vec<i16>>>),
+    SetMemberPresent(UserId, bool),
 
-    SetConn(MixerConnection, u32),
+    SetConn(MixerConnection, u32, Option<RtpCounters>),
+    ReplaceUdp(UdpSocket),
     Ws(Option<Sender<WsMessage>>),
     DropConn,
+    ExportSession(Box<VoiceSession>, Sender<Option<VoiceSession>>),
 
     ReplaceInterconnect(Interconnect),
     RebuildEncoder,
 
+    Flush(Sender<()>),
+    Shutdown(Sender<()>),
     Poison,
 }
 
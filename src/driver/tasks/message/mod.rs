@@ -12,9 +12,9 @@ mod ws;
 pub use self::udp_rx::*;
 pub use self::{core::*, disposal::*, events::*, mixer::*, ws::*};
 
+use crate::{events::CallContext, Config};
 use flume::Sender;
-use tokio::spawn;
-use tracing::trace;
+use tracing::{trace, Instrument};
 
 #[derive(Clone, Debug)]
 pub struct Interconnect {
@@ -28,23 +28,24 @@ impl Interconnect {
         drop(self.events.send(EventMessage::Poison));
     }
 
-    pub fn poison_all(&self) {
-        drop(self.mixer.send(MixerMessage::Poison));
-        self.poison();
-    }
-
-    pub fn restart_volatile_internals(&mut self) {
+    pub fn restart_volatile_internals(&mut self, config: &Config) {
         self.poison();
 
         let (evt_tx, evt_rx) = flume::unbounded();
 
         self.events = evt_tx;
 
-        spawn(async move {
-            trace!("Event processor restarted.");
-            super::events::runner(evt_rx).await;
-            trace!("Event processor finished.");
-        });
+        let call = CallContext::new(config.call_identifier.as_deref());
+        drop(
+            config.spawner.spawn(
+                async move {
+                    trace!("Event processor restarted.");
+                    super::events::runner(evt_rx, call).await;
+                    trace!("Event processor finished.");
+                }
+                .in_current_span(),
+            ),
+        );
 
         // Make mixer aware of new targets...
         drop(
@@ -1,17 +1,51 @@
 #![allow(missing_docs)]
 
 use super::Interconnect;
-use crate::driver::Config;
+use crate::{driver::Config, events::context_data::ClientVideo};
 use dashmap::{DashMap, DashSet};
+use flume::Sender;
 use serenity_voice_model::id::UserId;
+use std::time::Duration;
+use tokio::net::UdpSocket;
 
+#[allow(clippy::large_enum_variant)]
 pub enum UdpRxMessage {
     SetConfig(Config),
     ReplaceInterconnect(Interconnect),
+    DumpLast(UserId, Duration, Sender<Option<Vec<i16>>>),
+    ReplaceSocket(UdpSocket),
 }
 
 #[derive(Debug, Default)]
 pub struct SsrcTracker {
     pub disconnected_users: DashSet<UserId>,
     pub user_ssrc_map: DashMap<UserId, u32>,
+    /// Video and rtx SSRCs, which must not be decoded as audio.
+    pub video_ssrcs: DashMap<u32, UserId>,
+}
+
+impl SsrcTracker {
+    /// Records the latest audio and video SSRCs announced for a user.
+    pub fn set_client_video(&self, ev: &ClientVideo) {
+        if ev.audio_ssrc != 0 {
+            self.user_ssrc_map.insert(ev.user_id, ev.audio_ssrc);
+        }
+
+        self.remove_video(ev.user_id);
+        for ssrc in [ev.video_ssrc, ev.rtx_ssrc] {
+            if ssrc != 0 {
+                self.video_ssrcs.insert(ssrc, ev.user_id);
+            }
+        }
+    }
+
+    /// Forgets all video SSRCs belonging to a user.
+    pub fn remove_video(&self, user_id: UserId) {
+        self.video_ssrcs.retain(|_, user| *user != user_id);
+    }
+
+    /// Returns whether `ssrc` carries video rather than audio.
+    pub fn is_video(&self, ssrc: u32) -> bool {
+        self.video_ssrcs.contains_key(&ssrc)
+    }
 }
@@ -1,12 +1,15 @@
 #![allow(missing_docs)]
 
 use super::Interconnect;
-use crate::{model::Event as GatewayEvent, ws::WsStream};
+use crate::ws::{WsEvent, WsStream};
 
 pub enum WsMessage {
     Ws(Box<WsStream>),
     ReplaceInterconnect(Interconnect),
     SetKeepalive(f64),
-    Speaking(bool),
-    Deliver(GatewayEvent),
+    Speaking(bool, u32),
+    Deliver(WsEvent),
+    #[cfg(feature = "raw-gateway")]
+    SendRaw(serde_json::Value),
+    Close,
 }
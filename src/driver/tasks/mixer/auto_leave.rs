@@ -0,0 +1,117 @@
+use crate::{events::context_data::DisconnectReason, model::id::UserId, Config};
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+/// Tracks how long a connected call has been idle or alone, for
+/// [`Config::idle_timeout`] and [`Config::alone_timeout`].
+#[derive(Debug, Default)]
+pub struct AutoLeave {
+    members: HashSet<UserId>,
+    idle_since: Option<Instant>,
+    alone_since: Option<Instant>,
+}
+
+impl AutoLeave {
+    pub fn set_member_present(&mut self, user_id: UserId, present: bool) {
+        if present {
+            self.members.insert(user_id);
+        } else {
+            self.members.remove(&user_id);
+        }
+    }
+
+    /// Forgets all known members, as when leaving a call.
+    pub fn clear(&mut self) {
+        self.members.clear();
+        self.reset();
+    }
+
+    pub fn reset(&mut self) {
+        self.idle_since = None;
+        self.alone_since = None;
+    }
+
+    /// Returns the reason to leave the current call, if either timeout has expired.
+    pub fn check(
+        &mut self,
+        now: Instant,
+        playing: bool,
+        config: &Config,
+    ) -> Option<DisconnectReason> {
+        let idle_since = if playing {
+            self.idle_since = None;
+            None
+        } else {
+            Some(*self.idle_since.get_or_insert(now))
+        };
+
+        let alone_since = if self.members.is_empty() {
+            Some(*self.alone_since.get_or_insert(now))
+        } else {
+            self.alone_since = None;
+            None
+        };
+
+        let expired = |since: Option<Instant>, limit: Option<Duration>| {
+            since
+                .zip(limit)
+                .is_some_and(|(since, limit)| now.duration_since(since) >= limit)
+        };
+
+        let reason = if expired(alone_since, config.alone_timeout) {
+            Some(DisconnectReason::Alone)
+        } else if expired(idle_since, config.idle_timeout) {
+            Some(DisconnectReason::Idle)
+        } else {
+            None
+        };
+
+        if reason.is_some() {
+            self.reset();
+        }
+
+        reason
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeouts_track_activity_and_members() {
+        let config = Config::default()
+            .idle_timeout(Some(Duration::from_secs(10)))
+            .alone_timeout(Some(Duration::from_secs(20)));
+        let start = Instant::now();
+        let mut state = AutoLeave::default();
+        state.set_member_present(UserId(1), true);
+
+        assert_eq!(state.check(start, false, &config), None);
+        // Playing resets the idle timer.
+        assert_eq!(
+            state.check(start + Duration::from_secs(9), true, &config),
+            None
+        );
+        assert_eq!(
+            state.check(start + Duration::from_secs(15), false, &config),
+            None
+        );
+        assert_eq!(
+            state.check(start + Duration::from_secs(25), false, &config),
+            Some(DisconnectReason::Idle)
+        );
+
+        state.set_member_present(UserId(1), false);
+        assert_eq!(
+            state.check(start + Duration::from_secs(30), true, &config),
+            None
+        );
+        assert_eq!(
+            state.check(start + Duration::from_secs(50), true, &config),
+            Some(DisconnectReason::Alone)
+        );
+    }
+}
@@ -1,4 +1,21 @@
-use super::*;
+use super::{resample::ResampleState, *};
+use crate::opus_util::PacketInfo;
+
+/// Fetches the next packet from an input, recording why its stream ended if
+/// there are none left.
+#[inline]
+fn next_packet(
+    input: &mut Parsed,
+    track_status: &mut MixStatus,
+) -> Option<symphonia_core::formats::Packet> {
+    match input.format.next_packet() {
+        Ok(pkt) => Some(pkt),
+        Err(e) => {
+            *track_status = MixStatus::from_end(&e);
+            None
+        },
+    }
+}
 
 /// Mix a track's audio stream into either the shared mixing buffer, or directly into the output
 /// packet ("passthrough") when possible.
@@ -24,7 +41,8 @@ use super::*;
 /// path.
 ///
 /// In the mono -> stereo case, we duplicate across all target channels. In stereo -> mono, we average
-/// the samples from each channel.
+/// the samples from each channel. Sources with more than two channels are downmixed according to
+/// their channel layout and the configured [`DownmixMode`] before resampling or mixing.
 ///
 /// To avoid needing to hold onto resampled data longer than one mix cycle, we take enough input samples
 /// to fill a chunk of the mixer (e.g., 10ms == 20ms / 2) so that they will all be used.
@@ -42,8 +60,10 @@ pub fn mix_symph_indiv(
     input: &mut Parsed,
     // resampler state and positions into partially read packets
     local_state: &mut DecodeState,
-    // volume of this source
-    volume: f32,
+    // volume of this source in each output channel
+    volume: [f32; 2],
+    // how to reduce sources with >2 channels to stereo/mono
+    downmix: DownmixMode,
     // window into the output UDP buffer to copy opus frames into.
     // This is set to `Some` IF passthrough is possible (i.e., one live source).
     mut opus_slot: Option<&mut [u8]>,
@@ -59,19 +79,31 @@ pub fn mix_symph_indiv(
         // fetch a packet: either in progress, passthrough (early exit), or
         let source_packet = if local_state.inner_pos != 0 {
             Some(input.decoder.last_decoded())
-        } else if let Ok(pkt) = input.format.next_packet() {
+        } else if let Some(pkt) = next_packet(input, &mut track_status) {
             if pkt.track_id() != input.track_id {
                 continue;
             }
 
+            // Audio from the first packet reaching the seek target which lies
+            // before it, and must not be played.
+            let mut skip_frames = 0;
+            if let Some(target) = local_state.skip_to {
+                if pkt.ts() + pkt.dur() <= target {
+                    // Preroll: prime the decoder, but discard its output.
+                    drop(input.decoder.decode(&pkt));
+                    continue;
+                }
+
+                local_state.skip_to = None;
+                skip_frames = usize::try_from(target.saturating_sub(pkt.ts())).unwrap_or(0);
+            }
+
             let buf = pkt.buf();
 
             // Opus packet passthrough special case.
             if codec_type == CODEC_TYPE_OPUS && local_state.passthrough != Passthrough::Block {
                 if let Some(slot) = opus_slot.as_mut() {
-                    let sample_ct = buf
-                        .try_into()
-                        .and_then(|buf| audiopus::packet::nb_samples(buf, SAMPLE_RATE));
+                    let sample_ct = PacketInfo::parse(buf).map(|info| info.samples());
 
                     // We don't actually block passthrough until a few violations are
                     // seen. The main one is that most Opus tracks end on a sub-20ms
@@ -91,15 +123,20 @@ pub fn mix_symph_indiv(
                 }
             }
 
-            input
+            let decoded = input
                 .decoder
                 .decode(&pkt)
                 .map_err(|e| {
                     track_status = e.into();
                 })
-                .ok()
+                .ok();
+
+            if decoded.as_ref().is_some_and(|d| skip_frames < d.frames()) {
+                local_state.inner_pos = skip_frames;
+            }
+
+            decoded
         } else {
-            track_status = MixStatus::Ended;
             None
         };
 
@@ -107,7 +144,12 @@ pub fn mix_symph_indiv(
         if source_packet.is_none() {
             if resample_in_progress {
                 // fill up remainder of buf with zeroes, resample, mix
-                let (chan_c, resampler, rs_out_buf) = local_state.resampler.as_mut().unwrap();
+                let ResampleState {
+                    chan_c,
+                    resampler,
+                    out_buf: rs_out_buf,
+                    ..
+                } = local_state.resampler.as_mut().unwrap();
                 let in_len = resample_scratch.frames();
                 let to_render = resampler.input_frames_next().saturating_sub(in_len);
 
@@ -133,9 +175,23 @@ pub fn mix_symph_indiv(
                 let ratio = (rs_out_buf[0].len() as f32) / (resample_scratch.frames() as f32);
                 let out_samples = (ratio * (in_len as f32)).round() as usize;
 
+                // Audio held back by the resampler's delay also lies in this chunk.
+                let delay = resampler.output_delay();
+                let shown = (out_samples + delay).min(rs_out_buf[0].len());
+
                 mix_resampled(rs_out_buf, symph_mix, samples_written, volume);
 
-                samples_written += out_samples;
+                samples_written += shown;
+                drain_resampler(
+                    local_state,
+                    symph_mix,
+                    &mut samples_written,
+                    delay - (shown - out_samples),
+                    volume,
+                );
+            } else if let Some(state) = local_state.resampler.as_ref() {
+                let delay = state.resampler.output_delay();
+                drain_resampler(local_state, symph_mix, &mut samples_written, delay, volume);
             }
 
             break;
@@ -158,6 +214,7 @@ pub fn mix_symph_indiv(
                 local_state.inner_pos,
                 samples_written,
                 volume,
+                downmix,
             );
 
             samples_written += samples_marched;
@@ -166,28 +223,24 @@ pub fn mix_symph_indiv(
             local_state.inner_pos %= pkt_frames;
         } else {
             // NOTE: this should NEVER change in one stream.
-            let chan_c = source_packet.spec().channels.count();
-            let (_, resampler, rs_out_buf) = local_state.resampler.get_or_insert_with(|| {
-                // TODO: integ. error handling here.
-                let resampler = FftFixedOut::new(
-                    in_rate as usize,
-                    SAMPLE_RATE_RAW,
-                    RESAMPLE_OUTPUT_FRAME_SIZE,
-                    4,
-                    chan_c,
-                )
-                .expect("Failed to create resampler.");
-                let out_buf = resampler.output_buffer_allocate(true);
-
-                (chan_c, resampler, out_buf)
-            });
+            // Multichannel sources are downmixed to stereo ahead of the resampler.
+            let src_chan_c = source_packet.spec().channels.count();
+            let chan_c = src_chan_c.min(2);
+            let ResampleState {
+                resampler,
+                out_buf: rs_out_buf,
+                ..
+            } = local_state
+                .resampler
+                .get_or_insert_with(|| ResampleState::take(in_rate, chan_c));
 
             let inner_pos = local_state.inner_pos;
 
             let needed_in_frames = resampler.input_frames_next();
             let available_frames = pkt_frames - inner_pos;
 
-            let force_copy = resample_in_progress || needed_in_frames > available_frames;
+            let force_copy =
+                resample_in_progress || needed_in_frames > available_frames || src_chan_c > 2;
             if (!force_copy) && matches!(source_packet, AudioBufferRef::F32(_)) {
                 // This is the only case where we can pull off a straight resample...
                 // I would really like if this could be a slice of slices,
@@ -220,13 +273,27 @@ pub fn mix_symph_indiv(
                 let frames_to_take = available_frames.min(missing_frames);
 
                 resample_scratch.render_reserved(Some(frames_to_take));
-                copy_into_resampler(
-                    &source_packet,
-                    resample_scratch,
-                    inner_pos,
-                    old_scratch_len,
-                    frames_to_take,
-                );
+                if src_chan_c > 2 {
+                    for plane in resample_scratch.planes_mut().planes() {
+                        plane[old_scratch_len..][..frames_to_take].fill(0.0);
+                    }
+                    mix_over_ref(
+                        &source_packet,
+                        resample_scratch,
+                        inner_pos,
+                        old_scratch_len,
+                        [1.0; 2],
+                        downmix,
+                    );
+                } else {
+                    copy_into_resampler(
+                        &source_packet,
+                        resample_scratch,
+                        inner_pos,
+                        old_scratch_len,
+                        frames_to_take,
+                    );
+                }
 
                 local_state.inner_pos += frames_to_take;
                 local_state.inner_pos %= pkt_frames;
@@ -257,25 +324,74 @@ pub fn mix_symph_indiv(
     (MixType::MixedPcm(samples_written), track_status)
 }
 
+/// Pads the end of a stream with silence, mixing any audio still held back by
+/// its resampler's delay into whatever space remains in this frame.
+///
+/// The resampler is then reset, so that no stale audio bleeds into a loop or seek.
+#[inline]
+fn drain_resampler(
+    local_state: &mut DecodeState,
+    symph_mix: &mut AudioBuffer<f32>,
+    samples_written: &mut usize,
+    mut remaining: usize,
+    volume: [f32; 2],
+) {
+    let Some(ResampleState {
+        chan_c,
+        resampler,
+        out_buf: rs_out_buf,
+        ..
+    }) = local_state.resampler.as_mut()
+    else {
+        return;
+    };
+
+    let silence = vec![vec![0.0f32; resampler.input_frames_max()]; *chan_c];
+    while remaining > 0 && *samples_written + rs_out_buf[0].len() <= MONO_FRAME_SIZE {
+        let in_len = resampler.input_frames_next();
+        let refs: Vec<&[f32]> = silence.iter().map(|s| &s[..in_len]).collect();
+
+        resampler
+            .process_into_buffer(&refs, rs_out_buf, None)
+            .unwrap();
+        mix_resampled(rs_out_buf, symph_mix, *samples_written, volume);
+
+        let shown = remaining.min(rs_out_buf[0].len());
+        *samples_written += shown;
+        remaining -= shown;
+    }
+
+    resampler.reset();
+}
+
 #[inline]
 fn mix_over_ref(
     source: &AudioBufferRef<'_>,
     target: &mut AudioBuffer<f32>,
     source_pos: usize,
     dest_pos: usize,
-    volume: f32,
+    volume: [f32; 2],
+    downmix: DownmixMode,
 ) -> usize {
     match source {
-        AudioBufferRef::U8(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume),
-        AudioBufferRef::U16(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume),
-        AudioBufferRef::U24(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume),
-        AudioBufferRef::U32(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume),
-        AudioBufferRef::S8(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume),
-        AudioBufferRef::S16(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume),
-        AudioBufferRef::S24(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume),
-        AudioBufferRef::S32(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume),
-        AudioBufferRef::F32(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume),
-        AudioBufferRef::F64(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume),
+        AudioBufferRef::U8(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume, downmix),
+        AudioBufferRef::U16(v) =>
+            mix_symph_buffer(v, target, source_pos, dest_pos, volume, downmix),
+        AudioBufferRef::U24(v) =>
+            mix_symph_buffer(v, target, source_pos, dest_pos, volume, downmix),
+        AudioBufferRef::U32(v) =>
+            mix_symph_buffer(v, target, source_pos, dest_pos, volume, downmix),
+        AudioBufferRef::S8(v) => mix_symph_buffer(v, target, source_pos, dest_pos, volume, downmix),
+        AudioBufferRef::S16(v) =>
+            mix_symph_buffer(v, target, source_pos, dest_pos, volume, downmix),
+        AudioBufferRef::S24(v) =>
+            mix_symph_buffer(v, target, source_pos, dest_pos, volume, downmix),
+        AudioBufferRef::S32(v) =>
+            mix_symph_buffer(v, target, source_pos, dest_pos, volume, downmix),
+        AudioBufferRef::F32(v) =>
+            mix_symph_buffer(v, target, source_pos, dest_pos, volume, downmix),
+        AudioBufferRef::F64(v) =>
+            mix_symph_buffer(v, target, source_pos, dest_pos, volume, downmix),
     }
 }
 
@@ -285,7 +401,8 @@ fn mix_symph_buffer<S>(
     target: &mut AudioBuffer<f32>,
     source_pos: usize,
     dest_pos: usize,
-    volume: f32,
+    volume: [f32; 2],
+    downmix: DownmixMode,
 ) -> usize
 where
     S: Sample + IntoSample<f32>,
@@ -307,7 +424,7 @@ where
     if source_mono {
         // mix this signal into *all* output channels at req'd volume.
         let source_plane = source_raw_planes[0];
-        for d_plane in &mut (*target.planes_mut().planes()) {
+        for (d_plane, volume) in (*target.planes_mut().planes()).iter_mut().zip(volume) {
             for (d, s) in d_plane[dest_pos..dest_pos + mix_ct]
                 .iter_mut()
                 .zip(source_plane[source_pos..source_pos + mix_ct].iter())
@@ -315,10 +432,36 @@ where
                 *d += volume * (*s).into_sample();
             }
         }
+    } else if source_chans > 2 {
+        // surround -> stereo/mono: weight each channel by its position in the layout.
+        let mut t_planes = target.planes_mut();
+        let d_planes = t_planes.planes();
+        for (gains, s_plane) in downmix
+            .gains(source.spec().channels)
+            .zip(source_raw_planes.iter())
+        {
+            let gains = if target_mono {
+                [(gains[0] + gains[1]) / 2.0, 0.0]
+            } else {
+                gains
+            };
+
+            for ((d_plane, gain), volume) in d_planes.iter_mut().zip(gains).zip(volume) {
+                if gain == 0.0 {
+                    continue;
+                }
+                for (d, s) in d_plane[dest_pos..dest_pos + mix_ct]
+                    .iter_mut()
+                    .zip(s_plane[source_pos..source_pos + mix_ct].iter())
+                {
+                    *d += volume * gain * (*s).into_sample();
+                }
+            }
+        }
     } else if target_mono {
         // mix all signals into the one target channel: reduce aggregate volume
         // by n_channels.
-        let vol_adj = 1.0 / (source_chans as f32);
+        let vol_adj = volume[0] / (source_chans as f32);
         let mut t_planes = target.planes_mut();
         let d_plane = &mut *t_planes.planes()[0];
         for s_plane in source_raw_planes {
@@ -326,14 +469,15 @@ where
                 .iter_mut()
                 .zip(s_plane[source_pos..source_pos + mix_ct].iter())
             {
-                *d += volume * vol_adj * (*s).into_sample();
+                *d += vol_adj * (*s).into_sample();
             }
         }
     } else {
         // stereo -> stereo: don't change volume, map input -> output channels w/ no duplication
-        for (d_plane, s_plane) in (*target.planes_mut().planes())
+        for ((d_plane, s_plane), volume) in (*target.planes_mut().planes())
             .iter_mut()
             .zip(source_raw_planes[..].iter())
+            .zip(volume)
         {
             for (d, s) in d_plane[dest_pos..dest_pos + mix_ct]
                 .iter_mut()
@@ -352,7 +496,7 @@ fn mix_resampled(
     source: &[Vec<f32>],
     target: &mut AudioBuffer<f32>,
     dest_pos: usize,
-    volume: f32,
+    volume: [f32; 2],
 ) -> usize {
     let mix_ct = source[0].len();
 
@@ -364,7 +508,7 @@ fn mix_resampled(
     // see `mix_symph_buffer` for explanations of stereo<->mono logic.
     if source_mono {
         let source_plane = &source[0];
-        for d_plane in &mut (*target.planes_mut().planes()) {
+        for (d_plane, volume) in (*target.planes_mut().planes()).iter_mut().zip(volume) {
             for (d, s) in d_plane[dest_pos..dest_pos + mix_ct]
                 .iter_mut()
                 .zip(source_plane)
@@ -373,18 +517,19 @@ fn mix_resampled(
             }
         }
     } else if target_mono {
-        let vol_adj = 1.0 / (source_chans as f32);
+        let vol_adj = volume[0] / (source_chans as f32);
         let mut t_planes = target.planes_mut();
         let d_plane = &mut *t_planes.planes()[0];
         for s_plane in source {
             for (d, s) in d_plane[dest_pos..dest_pos + mix_ct].iter_mut().zip(s_plane) {
-                *d += volume * vol_adj * s;
+                *d += vol_adj * s;
             }
         }
     } else {
-        for (d_plane, s_plane) in (*target.planes_mut().planes())
+        for ((d_plane, s_plane), volume) in (*target.planes_mut().planes())
             .iter_mut()
             .zip(source[..].iter())
+            .zip(volume)
         {
             for (d, s) in d_plane[dest_pos..dest_pos + mix_ct].iter_mut().zip(s_plane) {
                 *d += volume * (*s);
@@ -395,6 +540,23 @@ fn mix_resampled(
     mix_ct
 }
 
+/// Returns the `[left, right]` volume of a source panned to `pan` using a
+/// constant-power law, normalised so that a centred source is unchanged.
+#[inline]
+pub fn pan_volume(volume: f32, pan: f32) -> [f32; 2] {
+    if pan == 0.0 {
+        return [volume; 2];
+    }
+
+    let angle = (pan.clamp(-1.0, 1.0) + 1.0) * std::f32::consts::FRAC_PI_4;
+    let (right, left) = angle.sin_cos();
+
+    [
+        volume * std::f32::consts::SQRT_2 * left,
+        volume * std::f32::consts::SQRT_2 * right,
+    ]
+}
+
 #[inline]
 pub(crate) fn copy_into_resampler(
     source: &AudioBufferRef<'_>,
@@ -442,3 +604,23 @@ where
 
     len
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pan_is_constant_power() {
+        assert!(pan_volume(0.5, 0.0).iter().all(|v| (v - 0.5).abs() < 1e-6));
+
+        let [l, r] = pan_volume(1.0, -1.0);
+        assert!((l - std::f32::consts::SQRT_2).abs() < 1e-6);
+        assert!(r.abs() < 1e-6);
+
+        for pan in [-0.75, -0.2, 0.3, 1.0] {
+            let [l, r] = pan_volume(1.0, pan);
+            assert!((l * l + r * r - 2.0).abs() < 1e-5);
+            assert_eq!(l > r, pan < 0.0);
+        }
+    }
+}
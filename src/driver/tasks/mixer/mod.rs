@@ -1,26 +1,65 @@
+mod auto_leave;
 pub mod mix_logic;
 mod pool;
+mod preroll;
+pub mod resample;
 mod result;
 pub mod state;
 pub mod track;
 mod util;
 
+use auto_leave::*;
 use pool::*;
 use result::*;
 use state::*;
 pub use track::*;
 
 use super::{
+    batch::send_batch,
     disposal::DisposalThread,
     error::{Error, Result},
     message::*,
 };
 use crate::{
     constants::*,
-    driver::{CryptoMode, MixMode},
-    events::EventStore,
+    driver::{
+        bitrate_fits,
+        crypto::Cipher,
+        limit_bitrate,
+        packet_overhead_bps,
+        rtp_extension,
+        send_with_retry,
+        AnalysisTap,
+        BandwidthShare,
+        DownmixMode,
+        DynamicsState,
+        MixMode,
+        OverloadStrategy,
+        RtpCounters,
+        SharedBandwidth,
+        SilenceDetection,
+        UdpSendStats,
+        VoiceSession,
+    },
+    events::{
+        context_data::{OverloadData, TeardownCause, ThrottleData, TransmitData},
+        CoreContext,
+        EventStore,
+    },
     input::{Input, Parsed},
-    tracks::{Action, LoopState, PlayError, PlayMode, TrackCommand, TrackHandle, TrackState, View},
+    tracks::{
+        Action,
+        DecodeReason,
+        LoopState,
+        MixInfo,
+        MixPath,
+        PlayError,
+        PlayMode,
+        TrackCommand,
+        TrackHandle,
+        TrackState,
+        View,
+    },
     Config,
 };
 use audiopus::{
@@ -35,10 +74,9 @@ use discortp::{
     MutablePacket,
 };
 use flume::{Receiver, SendError, Sender, TryRecvError};
-use rand::random;
-use rubato::{FftFixedOut, Resampler};
+use rubato::Resampler;
 use std::{
-    io::Write,
+    io::{Error as IoError, ErrorKind as IoErrorKind, Write},
     result::Result as StdResult,
     sync::Arc,
     time::{Duration, Instant},
@@ -52,7 +90,7 @@ use symphonia_core::{
     units::Time,
 };
 use tokio::runtime::Handle;
-use tracing::error;
+use tracing::{error, warn, Span};
 
 #[cfg(test)]
 use crate::driver::test_config::{OutputMessage, OutputMode};
@@ -60,13 +98,30 @@ use crate::driver::test_config::{OutputMessage, OutputMode};
 use discortp::Packet as _;
 
 pub struct Mixer {
+    /// Live waveform or spectrum feed, measured from each mixed frame.
+    analysis: Option<AnalysisTap>,
     pub bitrate: Bitrate,
+    /// Bitrate currently applied to the encoder by a track's bitrate automation,
+    /// in place of `bitrate`.
+    automated_bitrate: Option<Bitrate>,
+    /// This call's claim on a shared bandwidth budget, held while connected.
+    bandwidth_share: Option<BandwidthShare>,
+    /// Bitrate limit currently applying to this call, in kbps.
+    bandwidth_limit: Option<u32>,
+    /// Limit to which the encoder's bitrate is lowered, while the requested
+    /// bitrate exceeds it.
+    throttle: Option<u32>,
     pub config: Arc<Config>,
     pub conn_active: Option<MixerConnection>,
     pub deadline: Instant,
     pub disposer: DisposalThread,
+    /// Master-bus compressor and limiter, applied before soft-clipping.
+    dynamics: Option<DynamicsState>,
     pub encoder: OpusEncoder,
     pub interconnect: Interconnect,
+    /// Channel layout used to mix and encode audio, which differs from
+    /// `config.mix_mode` while an [`OverloadStrategy::DownmixMono`] policy is engaged.
+    pub mix_mode: MixMode,
     pub mix_rx: Receiver<MixerMessage>,
     pub muted: bool,
     // pub packet: [u8; VOICE_PACKET_MAX],
@@ -75,9 +130,44 @@ pub struct Mixer {
     pub soft_clip: SoftClip,
     thread_pool: BlockyTaskPool,
     pub ws: Option<Sender<WsMessage>>,
+    flush_ack: Option<Sender<()>>,
+    auto_leave: AutoLeave,
+    transmit: TransmitData,
+    last_frame_silent: bool,
+    /// Whether the encoder may still hold audio in its lookahead, which must be
+    /// flushed before switching to explicit silence frames.
+    encoder_primed: bool,
+    /// Consecutive mixed frames found silent by the configured [`SilenceDetection`].
+    quiet_frames: u32,
+    /// Whether transmission is stopped due to sustained silence.
+    quiet: bool,
+    /// Whether the configured `OverloadPolicy` is shedding work.
+    overloaded: bool,
+    /// Time taken to mix all tracks in the last frame.
+    last_mix_time: Duration,
+    /// Playing tracks held in place during the last frame to stay within budget.
+    held_tracks: usize,
+    /// Position in the track list to start from under [`OverloadStrategy::Interleave`].
+    interleave_next: usize,
+    /// Scratch list of track indices, in the order they are mixed this frame.
+    mix_order: Vec<usize>,
+    /// Serialised RTP header extension block attached to each outbound packet.
+    rtp_extensions: Vec<u8>,
+    #[cfg(feature = "rtp-control")]
+    pub rtp_override: crate::driver::RtpOverride,
+    /// Span of the owning driver, entered whenever a scheduler works on this mixer.
+    span: Span,
+    /// Leave built packets unencrypted, for the scheduler to encrypt elsewhere.
+    pub defer_encryption: bool,
 
     pub keepalive_deadline: Instant,
     pub keepalive_packet: [u8; MutableKeepalivePacket::minimum_packet_size()],
+    /// Consecutive UDP sends which have failed on the current socket.
+    send_failures: u32,
+    /// Whether the core task has been asked to move to another bind address.
+    rebinding: bool,
+    /// Packets sent and failed on the current connection.
+    send_stats: UdpSendStats,
 
     pub tracks: Vec<InternalTrack>,
     track_handles: Vec<TrackHandle>,
@@ -86,6 +176,8 @@ pub struct Mixer {
     symph_mix: AudioBuffer<f32>,
     resample_scratch: AudioBuffer<f32>,
 
+    pub virtual_tick: u64,
+
     #[cfg(test)]
     pub remaining_loops: Option<u64>,
 
@@ -93,6 +185,41 @@ pub struct Mixer {
     raw_msg: Option<OutputMessage>,
 }
 
+/// Returns whether a source's Opus packets can be sent as-is without exceeding the
+/// channel count of the driver's [`MixMode`].
+fn fits_mix_mode(input: &Parsed, mix_mode: MixMode) -> bool {
+    mix_mode == MixMode::Stereo
+        || input
+            .decoder
+            .codec_params()
+            .channels
+            .is_some_and(|c| c.count() == 1)
+}
+
+/// Returns why a track's latest frame was decoded, rather than passed through.
+fn decode_reason(
+    input: &Parsed,
+    mix_state: &DecodeState,
+    solo: bool,
+    capped: bool,
+    mix_mode: MixMode,
+) -> DecodeReason {
+    if input.decoder.codec_params().codec != CODEC_TYPE_OPUS {
+        DecodeReason::Codec
+    } else if mix_state.preroll.is_enabled() {
+        DecodeReason::Preroll
+    } else if !solo {
+        DecodeReason::Mixing
+    } else if capped {
+        DecodeReason::Bandwidth
+    } else if !fits_mix_mode(input, mix_mode) {
+        DecodeReason::Channels
+    } else {
+        // The packet was rejected by passthrough, or it has been blocked.
+        DecodeReason::FrameSize
+    }
+}
+
 fn new_encoder(bitrate: Bitrate, mix_mode: MixMode) -> Result<OpusEncoder> {
     let mut encoder = OpusEncoder::new(SAMPLE_RATE, mix_mode.to_opus(), CodingMode::Audio)?;
     encoder.set_bitrate(bitrate)?;
@@ -112,6 +239,7 @@ impl Mixer {
         let encoder = new_encoder(bitrate, config.mix_mode)
             .expect("Failed to create encoder in mixing thread with known-good values.");
         let soft_clip = SoftClip::new(config.mix_mode.to_opus());
+        let dynamics = config.dynamics.map(DynamicsState::new);
 
         let keepalive_packet = [0u8; MutableKeepalivePacket::minimum_packet_size()];
 
@@ -120,9 +248,11 @@ impl Mixer {
 
         let thread_pool = BlockyTaskPool::new(async_handle);
 
-        let symph_layout = config.mix_mode.symph_layout();
+        let mix_mode = config.mix_mode;
+        let symph_layout = mix_mode.symph_layout();
 
         let disposer = config.disposer.clone().unwrap_or_default();
+        let span = config.call_span();
         let config = config.into();
 
         let sample_buffer = SampleBuffer::<f32>::new(
@@ -147,13 +277,20 @@ impl Mixer {
         let deadline = Instant::now();
 
         Self {
+            analysis: None,
             bitrate,
+            automated_bitrate: None,
+            bandwidth_share: None,
+            bandwidth_limit: None,
+            throttle: None,
             config,
             conn_active: None,
             deadline,
             disposer,
+            dynamics,
             encoder,
             interconnect,
+            mix_mode,
             mix_rx,
             muted: false,
             prevent_events: false,
@@ -161,9 +298,29 @@ impl Mixer {
             soft_clip,
             thread_pool,
             ws: None,
+            flush_ack: None,
+            auto_leave: AutoLeave::default(),
+            transmit: TransmitData::default(),
+            last_frame_silent: false,
+            encoder_primed: false,
+            quiet_frames: 0,
+            quiet: false,
+            overloaded: false,
+            last_mix_time: Duration::ZERO,
+            held_tracks: 0,
+            interleave_next: 0,
+            mix_order: Vec::new(),
+            rtp_extensions: Vec::new(),
+            #[cfg(feature = "rtp-control")]
+            rtp_override: crate::driver::RtpOverride::default(),
+            span,
+            defer_encryption: false,
 
             keepalive_deadline: deadline,
             keepalive_packet,
+            send_failures: 0,
+            rebinding: false,
+            send_stats: UdpSendStats::default(),
 
             tracks,
             track_handles,
@@ -172,6 +329,8 @@ impl Mixer {
             symph_mix,
             resample_scratch,
 
+            virtual_tick: 0,
+
             #[cfg(test)]
             remaining_loops: None,
             #[cfg(test)]
@@ -179,8 +338,131 @@ impl Mixer {
         }
     }
 
+    /// Changes the channel layout used to mix and encode audio, rebuilding any
+    /// state which depends on it.
+    fn set_mix_mode(&mut self, mix_mode: MixMode) {
+        if mix_mode == self.mix_mode {
+            return;
+        }
+
+        self.mix_mode = mix_mode;
+        self.soft_clip = SoftClip::new(mix_mode.to_opus());
+        self.automated_bitrate = None;
+        if let Ok(enc) = new_encoder(self.capped_bitrate(self.bitrate), mix_mode) {
+            self.encoder = enc;
+        } else {
+            self.bitrate = DEFAULT_BITRATE;
+            self.encoder = new_encoder(self.capped_bitrate(self.bitrate), mix_mode)
+                .expect("Failed fallback rebuild of OpusEncoder with safe inputs.");
+        }
+
+        let sl = mix_mode.symph_layout();
+        self.sample_buffer = SampleBuffer::<f32>::new(
+            MONO_FRAME_SIZE as u64,
+            SignalSpec::new_with_layout(SAMPLE_RATE_RAW as u32, sl),
+        );
+        self.symph_mix = AudioBuffer::<f32>::new(
+            MONO_FRAME_SIZE as u64,
+            SignalSpec::new_with_layout(SAMPLE_RATE_RAW as u32, sl),
+        );
+    }
+
     fn set_bitrate(&mut self, bitrate: Bitrate) -> Result<()> {
-        self.encoder.set_bitrate(bitrate).map_err(Into::into)
+        self.encoder
+            .set_bitrate(self.capped_bitrate(bitrate))
+            .map_err(Into::into)
+    }
+
+    /// Returns the bitrate to encode at in place of `bitrate`, while throttled.
+    fn capped_bitrate(&self, bitrate: Bitrate) -> Bitrate {
+        self.throttle.map_or(bitrate, |limit| {
+            limit_bitrate(limit, self.packet_overhead_bps())
+        })
+    }
+
+    /// Returns the bitrate spent on each packet's headers and encryption overhead.
+    fn packet_overhead_bps(&self) -> i64 {
+        let crypto = self.conn_active.as_ref().map_or(0, |conn| {
+            conn.crypto.payload_prefix_len() + conn.crypto.payload_suffix_len()
+        });
+
+        packet_overhead_bps(RtpPacket::minimum_packet_size() + crypto)
+    }
+
+    /// Applies the configured `BandwidthCap`, lowering the encoder's bitrate while
+    /// the requested bitrate exceeds it and announcing any change to event handlers.
+    fn check_bandwidth(&mut self) {
+        let cap = self.config.bandwidth_cap.as_ref();
+
+        // Only connected calls draw from a shared budget.
+        let shared = cap
+            .and_then(|c| c.shared.as_ref())
+            .filter(|_| self.conn_active.is_some());
+        let keep_share = match (shared, &self.bandwidth_share) {
+            (Some(budget), Some(share)) => share.is_of(budget),
+            (None, None) => true,
+            _ => false,
+        };
+        if !keep_share {
+            self.bandwidth_share = None;
+            self.bandwidth_share = shared.map(SharedBandwidth::join);
+        }
+
+        let limit = cap.and_then(|c| c.limit_kbps(self.bandwidth_share.as_ref()));
+        self.bandwidth_limit = limit;
+
+        let requested = self.automated_bitrate.unwrap_or(self.bitrate);
+        let overhead = self.packet_overhead_bps();
+        let throttle = limit.filter(|&l| !bitrate_fits(requested, l, overhead));
+        if throttle == self.throttle {
+            return;
+        }
+
+        self.throttle = throttle;
+        if let Err(e) = self.set_bitrate(requested) {
+            error!("Failed to apply bandwidth cap {:?}", e);
+        }
+
+        if !self.prevent_events {
+            drop(self.interconnect.events.send(EventMessage::FireCoreEvent(
+                CoreContext::Throttle(ThrottleData {
+                    engaged: throttle.is_some(),
+                    limit_kbps: limit,
+                    requested,
+                }),
+            )));
+        }
+    }
+
+    /// Returns whether the configured `BandwidthCap` leaves too little bitrate to
+    /// accept new tracks.
+    fn refuses_tracks(&self) -> bool {
+        self.config
+            .bandwidth_cap
+            .as_ref()
+            .and_then(|c| c.refuse_below_kbps)
+            .zip(self.bandwidth_limit)
+            .is_some_and(|(min, limit)| limit < min)
+    }
+
+    /// Applies the bitrate automation of the first playing track which has any
+    /// for its current position, or otherwise restores the driver's bitrate.
+    fn apply_bitrate_automation(&mut self) {
+        let target = self
+            .tracks
+            .iter()
+            .filter(|track| track.playing.is_playing())
+            .find_map(InternalTrack::automated_bitrate);
+
+        if target == self.automated_bitrate {
+            return;
+        }
+
+        // Record the target even on failure, so that a bad point is reported once.
+        self.automated_bitrate = target;
+        if let Err(e) = self.set_bitrate(target.unwrap_or(self.bitrate)) {
+            error!("Failed to apply automated bitrate {:?}", e);
+        }
     }
 
     pub(crate) fn do_rebuilds(
@@ -214,7 +496,9 @@ impl Mixer {
 
     pub(crate) fn full_reconnect_gateway(&mut self) -> StdResult<(), SendError<CoreMessage>> {
         self.conn_active = None;
-        self.interconnect.core.send(CoreMessage::FullReconnect)
+        self.interconnect
+            .core
+            .send(CoreMessage::FullReconnect(TeardownCause::UdpFailure))
     }
 
     #[inline]
@@ -223,6 +507,7 @@ impl Mixer {
         msg: MixerMessage,
         packet: &mut [u8],
     ) -> (bool, bool, bool) {
+        let _span = self.span.clone().entered();
         let mut events_failure = false;
         let mut conn_failure = false;
         let mut should_exit = false;
@@ -230,6 +515,9 @@ impl Mixer {
         let error = match msg {
             MixerMessage::AddTrack(t) => self.add_track(t),
             MixerMessage::SetTrack(t) => {
+                for track in &mut self.tracks {
+                    track.mix_state.reset();
+                }
                 self.tracks.clear();
                 self.track_handles.clear();
 
@@ -247,8 +535,10 @@ impl Mixer {
             },
             MixerMessage::SetBitrate(b) => {
                 self.bitrate = b;
-                if let Err(e) = self.set_bitrate(b) {
-                    error!("Failed to update bitrate {:?}", e);
+                if self.automated_bitrate.is_none() {
+                    if let Err(e) = self.set_bitrate(b) {
+                        error!("Failed to update bitrate {:?}", e);
+                    }
                 }
                 Ok(())
             },
@@ -256,22 +546,110 @@ impl Mixer {
                 self.muted = m;
                 Ok(())
             },
-            MixerMessage::SetConn(conn, ssrc) => {
+            MixerMessage::SetPauseAll(p) => self.set_pause_all(p),
+            MixerMessage::SetRtpExtensions(exts) => {
+                let mut block = vec![0u8; rtp_extension::block_len(&exts)];
+                rtp_extension::write_extensions(&exts, &mut block)
+                    .expect("Extension block is sized to fit all elements.");
+                self.rtp_extensions = block;
+                Ok(())
+            },
+            #[cfg(feature = "rtp-control")]
+            MixerMessage::SetRtpOverride(rtp_override) => {
+                self.rtp_override = rtp_override;
+                if self.conn_active.is_some() {
+                    if let Some(mut rtp) = MutableRtpPacket::new(packet) {
+                        rtp_override.apply_to_packet(&mut rtp);
+                    }
+                }
+                Ok(())
+            },
+            MixerMessage::GetUdpSendStats(tx) => {
+                let stats = self.conn_active.as_ref().map(|_| UdpSendStats {
+                    consecutive_failures: self.send_failures,
+                    ..self.send_stats
+                });
+                _ = tx.send(stats);
+                Ok(())
+            },
+            #[cfg(feature = "rtp-control")]
+            MixerMessage::GetRtpState(tx) => {
+                let state = self
+                    .conn_active
+                    .as_ref()
+                    .and(MutableRtpPacket::new(packet))
+                    .map(|rtp| crate::driver::RtpState::from_packet(&rtp));
+                _ = tx.send(state);
+                Ok(())
+            },
+            MixerMessage::GetTracks(tx) => {
+                let tracks = self
+                    .track_handles
+                    .iter()
+                    .cloned()
+                    .zip(self.tracks.iter().map(InternalTrack::state))
+                    .collect();
+                drop(tx.send(tracks));
+                Ok(())
+            },
+            MixerMessage::SetAnalysis(tap) => {
+                self.analysis = tap;
+                Ok(())
+            },
+            #[cfg(feature = "receive")]
+            MixerMessage::DumpLast(user_id, duration, tx) => {
+                if let Some(conn) = &self.conn_active {
+                    conn_failure |= conn
+                        .udp_rx
+                        .send(UdpRxMessage::DumpLast(user_id, duration, tx))
+                        .is_err();
+                }
+                Ok(())
+            },
+            MixerMessage::SetMemberPresent(user_id, present) => {
+                self.auto_leave.set_member_present(user_id, present);
+                Ok(())
+            },
+            MixerMessage::SetConn(conn, ssrc, counters) => {
                 self.conn_active = Some(conn);
                 let mut rtp = MutableRtpPacket::new(packet).expect(
                     "Too few bytes in self.packet for RTP header.\
                         (Blame: VOICE_PACKET_MAX?)",
                 );
+                let counters = counters.unwrap_or_else(RtpCounters::random);
                 rtp.set_ssrc(ssrc);
-                rtp.set_sequence(random::<u16>().into());
-                rtp.set_timestamp(random::<u32>().into());
+                rtp.set_sequence(counters.sequence.into());
+                rtp.set_timestamp(counters.timestamp.into());
+                #[cfg(feature = "rtp-control")]
+                self.rtp_override.apply_to_packet(&mut rtp);
                 self.deadline = Instant::now();
 
                 self.update_keepalive(ssrc);
+                self.send_failures = 0;
+                self.rebinding = false;
+                self.send_stats = UdpSendStats::default();
+                Ok(())
+            },
+            MixerMessage::ReplaceUdp(udp_tx) => {
+                if let Some(conn) = &mut self.conn_active {
+                    conn.udp_tx = udp_tx;
+                }
+                self.send_failures = 0;
+                self.rebinding = false;
                 Ok(())
             },
             MixerMessage::DropConn => {
                 self.conn_active = None;
+                self.auto_leave.clear();
+                Ok(())
+            },
+            MixerMessage::ExportSession(session, tx) => {
+                let counters = RtpPacket::new(packet).map(|rtp| RtpCounters {
+                    sequence: rtp.get_sequence().into(),
+                    timestamp: rtp.get_timestamp().into(),
+                });
+                let session = counters.and_then(|c| self.export_session(*session, c));
+                drop(tx.send(session));
                 Ok(())
             },
             MixerMessage::ReplaceInterconnect(i) => {
@@ -294,25 +672,27 @@ impl Mixer {
                 self.rebuild_tracks()
             },
             MixerMessage::SetConfig(new_config) => {
-                if new_config.mix_mode != self.config.mix_mode {
-                    self.soft_clip = SoftClip::new(new_config.mix_mode.to_opus());
-                    if let Ok(enc) = new_encoder(self.bitrate, new_config.mix_mode) {
-                        self.encoder = enc;
-                    } else {
-                        self.bitrate = DEFAULT_BITRATE;
-                        self.encoder = new_encoder(self.bitrate, new_config.mix_mode)
-                            .expect("Failed fallback rebuild of OpusEncoder with safe inputs.");
-                    }
+                let forced_mono = self.overloaded
+                    && new_config
+                        .overload_policy
+                        .is_some_and(|p| p.strategy == OverloadStrategy::DownmixMono);
+                self.set_mix_mode(if forced_mono {
+                    MixMode::Mono
+                } else {
+                    new_config.mix_mode
+                });
+
+                let same_clock = match (&new_config.virtual_clock, &self.config.virtual_clock) {
+                    (Some(new), Some(old)) => new.ptr_eq(old),
+                    (None, None) => true,
+                    _ => false,
+                };
+                if !same_clock {
+                    self.virtual_tick = 0;
+                }
 
-                    let sl = new_config.mix_mode.symph_layout();
-                    self.sample_buffer = SampleBuffer::<f32>::new(
-                        MONO_FRAME_SIZE as u64,
-                        SignalSpec::new_with_layout(SAMPLE_RATE_RAW as u32, sl),
-                    );
-                    self.symph_mix = AudioBuffer::<f32>::new(
-                        MONO_FRAME_SIZE as u64,
-                        SignalSpec::new_with_layout(SAMPLE_RATE_RAW as u32, sl),
-                    );
+                if self.dynamics.as_ref().map(DynamicsState::settings) != new_config.dynamics {
+                    self.dynamics = new_config.dynamics.map(DynamicsState::new);
                 }
 
                 self.config = Arc::new(
@@ -337,19 +717,23 @@ impl Mixer {
 
                 Ok(())
             },
-            MixerMessage::RebuildEncoder => match new_encoder(self.bitrate, self.config.mix_mode) {
-                Ok(encoder) => {
-                    self.encoder = encoder;
-                    Ok(())
-                },
-                Err(e) => {
-                    error!("Failed to rebuild encoder. Resetting bitrate. {:?}", e);
-                    self.bitrate = DEFAULT_BITRATE;
-                    self.encoder = new_encoder(self.bitrate, self.config.mix_mode)
-                        .expect("Failed fallback rebuild of OpusEncoder with safe inputs.");
-                    Ok(())
+            MixerMessage::RebuildEncoder =>
+                match new_encoder(self.capped_bitrate(self.bitrate), self.mix_mode) {
+                    Ok(encoder) => {
+                        self.encoder = encoder;
+                        self.automated_bitrate = None;
+                        Ok(())
+                    },
+                    Err(e) => {
+                        error!("Failed to rebuild encoder. Resetting bitrate. {:?}", e);
+                        self.automated_bitrate = None;
+                        self.bitrate = DEFAULT_BITRATE;
+                        self.encoder =
+                            new_encoder(self.capped_bitrate(self.bitrate), self.mix_mode)
+                                .expect("Failed fallback rebuild of OpusEncoder with safe inputs.");
+                        Ok(())
+                    },
                 },
-            },
             MixerMessage::Ws(new_ws_handle) => {
                 self.ws = new_ws_handle;
                 if let Err(e) = self.send_gateway_speaking() {
@@ -357,6 +741,20 @@ impl Mixer {
                 }
                 Ok(())
             },
+            MixerMessage::Flush(tx) => {
+                self.flush_ack = Some(tx);
+                self.check_flushed();
+                Ok(())
+            },
+            MixerMessage::Shutdown(tx) => {
+                // Hang up on the UDP receive and WS tasks before acknowledging,
+                // so that the core task can await their exit in turn.
+                self.conn_active = None;
+                self.ws = None;
+                should_exit = true;
+                _ = tx.send(());
+                Ok(())
+            },
             MixerMessage::Poison => {
                 should_exit = true;
                 Ok(())
@@ -371,6 +769,63 @@ impl Mixer {
         (events_failure, conn_failure, should_exit)
     }
 
+    /// Pauses every playing track, or resumes every track paused in this way.
+    ///
+    /// Tracks which were already paused, or have since been played/paused
+    /// by their handles, are left untouched on resume.
+    fn set_pause_all(&mut self, pause: bool) -> Result<()> {
+        for (i, track) in self.tracks.iter_mut().enumerate() {
+            let target = if pause && track.playing.is_playing() {
+                track.held = true;
+                PlayMode::Pause
+            } else if !pause && track.held {
+                track.held = false;
+                PlayMode::Play
+            } else {
+                continue;
+            };
+
+            track.play_at = None;
+            track.playing.change_to(target);
+
+            if !self.prevent_events {
+                self.interconnect.events.send(EventMessage::ChangeState(
+                    i,
+                    TrackStateChange::Mode(track.playing.clone()),
+                ))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Notifies any pending [`MixerMessage::Flush`] request once all tracks have ended.
+    fn check_flushed(&mut self) {
+        if self.tracks.is_empty() {
+            if let Some(tx) = self.flush_ack.take() {
+                _ = tx.send(());
+            }
+        }
+    }
+
+    /// Asks the core task to leave the current call if it has been idle or alone
+    /// for longer than configured.
+    pub(crate) fn check_auto_leave(&mut self, now: Instant) {
+        if self.config.idle_timeout.is_none() && self.config.alone_timeout.is_none() {
+            return;
+        }
+
+        if self.conn_active.is_none() {
+            self.auto_leave.reset();
+            return;
+        }
+
+        let playing = self.tracks.iter().any(|t| t.playing.is_playing());
+        if let Some(reason) = self.auto_leave.check(now, playing, &self.config) {
+            drop(self.interconnect.core.send(CoreMessage::AutoLeave(reason)));
+        }
+    }
+
     pub(crate) fn update_keepalive(&mut self, ssrc: u32) {
         let mut ka = MutableKeepalivePacket::new(&mut self.keepalive_packet[..])
             .expect("FATAL: Insufficient bytes given to keepalive packet.");
@@ -391,7 +846,11 @@ impl Mixer {
 
     #[inline]
     pub fn add_track(&mut self, track: TrackContext) -> Result<()> {
-        let (track, evts, state, handle) = InternalTrack::decompose_track(track);
+        let (mut track, evts, state, handle) = InternalTrack::decompose_track(track);
+        if self.refuses_tracks() {
+            // The track is removed, and its error reported, on the next tick.
+            track.playing = PlayMode::Errored(PlayError::Bandwidth);
+        }
         self.tracks.push(track);
         self.track_handles.push(handle.clone());
         self.interconnect
@@ -420,12 +879,14 @@ impl Mixer {
     #[inline]
     pub(crate) fn audio_commands_events(&mut self) -> Result<()> {
         // Apply user commands.
+        let now = Instant::now();
         for (i, track) in self.tracks.iter_mut().enumerate() {
             // This causes fallible event system changes,
             // but if the event thread has died then we'll certainly
             // detect that on the tick later.
             // Changes to play state etc. MUST all be handled.
             let action = track.process_commands(i, &self.interconnect);
+            track.check_play_at(i, now, &self.interconnect);
 
             if let Some(req) = action.seek_point {
                 track.seek(
@@ -476,7 +937,8 @@ impl Mixer {
 
             if track.playing.is_done() {
                 let p_state = track.playing.clone();
-                let to_drop = self.tracks.swap_remove(i);
+                let mut to_drop = self.tracks.swap_remove(i);
+                to_drop.mix_state.reset();
                 self.disposer
                     .dispose(DisposalMessage::Track(Box::new(to_drop)));
 
@@ -492,6 +954,9 @@ impl Mixer {
             }
         }
 
+        self.check_flushed();
+        self.check_auto_leave(now);
+
         // Tick -- receive side also handles removals in same manner after it increments
         // times etc.
         self.fire_event(EventMessage::Tick)?;
@@ -511,17 +976,27 @@ impl Mixer {
         }
     }
 
-    pub fn crypto_mode(&self) -> CryptoMode {
-        let mode = self.conn_active.as_ref().map(|v| v.crypto_state.kind());
+    /// Returns the number of bytes placed before each packet's Opus payload.
+    pub fn payload_prefix_len(&self) -> usize {
+        let len = self
+            .conn_active
+            .as_ref()
+            .map(|v| v.crypto.payload_prefix_len());
         if cfg!(not(test)) {
-            mode.expect("Shouldn't be mixing packets without access to a cipher + UDP dest.")
+            len.expect("Shouldn't be mixing packets without access to a cipher + UDP dest.")
         } else {
-            mode.unwrap_or_else(|| self.config.crypto_mode)
+            len.unwrap_or_else(|| self.config.crypto_mode.payload_prefix_len2())
         }
     }
 
-    #[inline]
     pub fn mix_and_build_packet(&mut self, packet: &mut [u8]) -> Result<usize> {
+        let _span = self.span.clone().entered();
+
+        // Any change in mix mode due to overload must happen before buffers are prepared.
+        self.check_overload();
+        self.apply_bitrate_automation();
+        self.check_bandwidth();
+
         // symph_mix is an `AudioBuffer` (planar format), we need to convert this
         // later into an interleaved `SampleBuffer` for libopus.
         self.symph_mix.clear();
@@ -538,14 +1013,41 @@ impl Mixer {
             out
         };
 
+        if self
+            .analysis
+            .as_mut()
+            .is_some_and(|tap| !tap.finish(&self.symph_mix))
+        {
+            self.analysis = None;
+        }
+
+        // Zero out all planes of the mix buffer if any audio was written, before
+        // muting or silence detection can discard the frame.
+        if matches!(mix_len, MixType::MixedPcm(a) if a > 0) {
+            for plane in self.symph_mix.planes_mut().planes() {
+                plane.fill(0.0);
+            }
+        }
+
         if self.muted {
             mix_len = MixType::MixedPcm(0);
         }
 
+        if let Some(detect) = self.config.silence_detection {
+            mix_len = self.detect_silence(detect, mix_len)?;
+        }
+
         // Explicit "Silence" frame handling: if there is no mixed data, we must send
         // ~5 frames of silence (unless another good audio frame appears) before we
         // stop sending RTP frames.
-        if mix_len == MixType::MixedPcm(0) {
+        self.last_frame_silent = mix_len == MixType::MixedPcm(0);
+        if self.last_frame_silent && self.encoder_primed {
+            // The encoder holds back the end of the last mixed frame (its lookahead):
+            // encode one frame of true silence so that tracks' tails are not clipped.
+            self.encoder_primed = false;
+            self.sample_buffer.samples_mut().fill(0.0);
+            mix_len = MixType::MixedPcm(MONO_FRAME_SIZE);
+        } else if self.last_frame_silent {
             if self.silence_frames > 0 {
                 self.silence_frames -= 1;
                 let mut rtp = MutableRtpPacket::new(packet).expect(
@@ -554,7 +1056,7 @@ impl Mixer {
                 );
 
                 let payload = rtp.payload_mut();
-                let pre_len = self.crypto_mode().payload_prefix_len2();
+                let pre_len = self.payload_prefix_len();
 
                 payload[pre_len..pre_len + SILENT_FRAME.len()].copy_from_slice(&SILENT_FRAME[..]);
 
@@ -565,12 +1067,20 @@ impl Mixer {
             }
         } else {
             self.silence_frames = 5;
+            self.encoder_primed = matches!(mix_len, MixType::MixedPcm(_));
 
             if let MixType::MixedPcm(n) = mix_len {
+                if let Some(dynamics) = &mut self.dynamics {
+                    let channels = self.mix_mode.channels();
+                    dynamics.process(
+                        &mut self.sample_buffer.samples_mut()[..n * channels],
+                        channels,
+                    );
+                }
+
                 if self.config.use_softclip {
                     self.soft_clip.apply(
-                        (&mut self.sample_buffer.samples_mut()
-                            [..n * self.config.mix_mode.channels()])
+                        (&mut self.sample_buffer.samples_mut()[..n * self.mix_mode.channels()])
                             .try_into()
                             .expect("Mix buffer is known to have a valid sample count (softclip)."),
                     )?;
@@ -580,8 +1090,7 @@ impl Mixer {
 
         // For the benefit of test cases, send the raw un-RTP'd data.
         #[cfg(test)]
-        let out = if let Some(OutputMode::Raw(_)) = &self.config.override_connection {
-            // This case has been handled before buffer clearing above.
+        if let Some(OutputMode::Raw(_)) = &self.config.override_connection {
             let msg = match mix_len {
                 MixType::Passthrough(len) if len == SILENT_FRAME.len() => OutputMessage::Silent,
                 MixType::Passthrough(len) => {
@@ -590,40 +1099,102 @@ impl Mixer {
                             (Blame: VOICE_PACKET_MAX?)",
                     );
                     let payload = rtp.payload();
-                    let opus_frame =
-                        (payload[self.crypto_mode().payload_prefix_len2()..][..len]).to_vec();
+                    let opus_frame = (payload[self.payload_prefix_len()..][..len]).to_vec();
 
                     OutputMessage::Passthrough(opus_frame)
                 },
                 MixType::MixedPcm(_) => OutputMessage::Mixed(
-                    self.sample_buffer.samples()[..self.config.mix_mode.sample_count_in_frame()]
-                        .to_vec(),
+                    self.sample_buffer.samples()[..self.mix_mode.sample_count_in_frame()].to_vec(),
                 ),
             };
 
             self.raw_msg = Some(msg);
 
-            Ok(1)
-        } else {
-            self.prep_packet(mix_len, packet)
+            return Ok(1);
+        }
+
+        self.prep_packet(mix_len, packet)
+    }
+
+    /// Engages or releases the configured `OverloadPolicy` according to how the
+    /// last frame was mixed, announcing any change to event handlers.
+    fn check_overload(&mut self) {
+        let policy = self.config.overload_policy;
+        let engage = match policy {
+            Some(p) if p.strategy == OverloadStrategy::DownmixMono =>
+                if self.overloaded {
+                    self.last_mix_time >= p.budget / 2
+                } else {
+                    self.last_mix_time > p.budget
+                },
+            Some(_) => self.held_tracks > 0,
+            None => false,
         };
 
-        #[cfg(not(test))]
-        let out = self.prep_packet(mix_len, packet);
+        if engage == self.overloaded {
+            return;
+        }
 
-        // Zero out all planes of the mix buffer if any audio was written.
-        if matches!(mix_len, MixType::MixedPcm(a) if a > 0) {
-            for plane in self.symph_mix.planes_mut().planes() {
-                plane.fill(0.0);
+        self.overloaded = engage;
+        self.set_mix_mode(
+            if engage && policy.is_some_and(|p| p.strategy == OverloadStrategy::DownmixMono) {
+                MixMode::Mono
+            } else {
+                self.config.mix_mode
+            },
+        );
+
+        if !self.prevent_events {
+            drop(self.interconnect.events.send(EventMessage::FireCoreEvent(
+                CoreContext::Overload(OverloadData {
+                    strategy: policy.map(|p| p.strategy).unwrap_or_default(),
+                    engaged: engage,
+                    mix_time: self.last_mix_time,
+                    held_tracks: self.held_tracks,
+                }),
+            )));
+        }
+    }
+
+    /// Tracks sustained silence in a newly mixed frame, replacing it with an empty
+    /// frame (and clearing the speaking flag) once silence has lasted long enough.
+    #[inline]
+    fn detect_silence(&mut self, detect: SilenceDetection, mix_len: MixType) -> Result<MixType> {
+        let silent = match mix_len {
+            MixType::MixedPcm(0) => return Ok(mix_len),
+            MixType::MixedPcm(n) =>
+                detect.is_silent(&self.sample_buffer.samples()[..n * self.mix_mode.channels()]),
+            MixType::Passthrough(_) => false,
+        };
+
+        if !silent {
+            self.quiet_frames = 0;
+            if self.quiet {
+                self.quiet = false;
+                self.send_gateway_speaking()?;
             }
+
+            return Ok(mix_len);
         }
 
-        out
+        self.quiet_frames = self.quiet_frames.saturating_add(1);
+        if self.quiet_frames < detect.hold_frames() {
+            return Ok(mix_len);
+        }
+
+        if !self.quiet {
+            self.quiet = true;
+            self.send_gateway_not_speaking();
+        }
+
+        Ok(MixType::MixedPcm(0))
     }
 
     #[inline]
     fn prep_packet(&mut self, mix_len: MixType, packet: &mut [u8]) -> Result<usize> {
         let send_buffer = self.sample_buffer.samples();
+        let protect = self.should_encrypt();
+        let encrypt = protect && !self.defer_encryption;
 
         let conn = self
             .conn_active
@@ -635,63 +1206,208 @@ impl Mixer {
                 (Blame: VOICE_PACKET_MAX?)",
         );
 
+        let ext_len = self.rtp_extensions.len();
+        rtp.set_extension(u8::from(ext_len != 0));
+
         let payload = rtp.payload_mut();
-        let crypto_mode = conn.crypto_state.kind();
-        let first_payload_byte = crypto_mode.payload_prefix_len2();
+        let first_payload_byte = conn.crypto.payload_prefix_len();
+        let opus_start = first_payload_byte + ext_len;
 
-        // If passthrough, Opus payload in place already.
+        // If passthrough, Opus payload in place already (and must be moved
+        // past any extensions).
         // Else encode into buffer with space for AEAD encryption headers.
         let payload_len = match mix_len {
-            MixType::Passthrough(opus_len) => opus_len,
+            MixType::Passthrough(opus_len) => {
+                payload.copy_within(
+                    first_payload_byte..first_payload_byte + opus_len,
+                    opus_start,
+                );
+                opus_len
+            },
             MixType::MixedPcm(_samples) => {
-                let total_payload_space = payload.len() - crypto_mode.payload_suffix_len();
+                let total_payload_space = payload.len() - conn.crypto.payload_suffix_len();
                 self.encoder.encode_float(
-                    &send_buffer[..self.config.mix_mode.sample_count_in_frame()],
-                    &mut payload[first_payload_byte..total_payload_space],
+                    &send_buffer[..self.mix_mode.sample_count_in_frame()],
+                    &mut payload[opus_start..total_payload_space],
                 )?
             },
         };
 
-        let final_payload_size = conn
-            .crypto_state
-            .write_packet_nonce(&mut rtp, first_payload_byte + payload_len);
+        payload[first_payload_byte..opus_start].copy_from_slice(&self.rtp_extensions);
+
+        let final_payload_size = match &mut conn.crypto {
+            PacketCrypto::Discord(cipher, crypto_state) => {
+                let final_payload_size =
+                    crypto_state.write_packet_nonce(&mut rtp, opus_start + payload_len);
 
+                if encrypt {
+                    cipher.encrypt_rtp_in_place(&mut rtp, final_payload_size)?;
+                }
+
+                final_payload_size
+            },
+            PacketCrypto::External(Some(protector)) if protect => {
+                let header_len = RtpPacket::minimum_packet_size();
+                let len = protector
+                    .protect(rtp.packet_mut(), header_len + opus_start + payload_len)
+                    .map_err(|e| {
+                        warn!("External gateway failed to protect voice packet: {e}");
+                        Error::Protection
+                    })?;
+
+                len - header_len
+            },
+            PacketCrypto::External(_) => opus_start + payload_len,
+        };
+
+        Ok(RtpPacket::minimum_packet_size() + final_payload_size)
+    }
+
+    #[inline]
+    fn should_encrypt(&self) -> bool {
         // Packet encryption ignored in test modes.
         #[cfg(not(test))]
         let encrypt = true;
         #[cfg(test)]
         let encrypt = self.config.override_connection.is_none();
 
-        if encrypt {
-            conn.cipher
-                .encrypt_pkt_in_place(&mut rtp, final_payload_size)?;
+        encrypt
+    }
+
+    /// Completes an exported session with the nonce and RTP counters of the next
+    /// packet, and stops sending on the session.
+    ///
+    /// Any packet sent after this point could reuse a nonce or sequence number
+    /// of the driver adopting the session.
+    pub(crate) fn export_session(
+        &mut self,
+        mut session: VoiceSession,
+        counters: RtpCounters,
+    ) -> Option<VoiceSession> {
+        let conn = self.conn_active.take()?;
+        self.auto_leave.clear();
+
+        if let PacketCrypto::Discord(_, state) = conn.crypto {
+            session.nonce = state.nonce();
         }
+        session.sequence = counters.sequence;
+        session.timestamp = counters.timestamp;
 
-        Ok(RtpPacket::minimum_packet_size() + final_payload_size)
+        Some(session)
+    }
+
+    /// Returns the cipher needed to finish the last built packet, if its
+    /// encryption was deferred to the scheduler.
+    #[inline]
+    pub(crate) fn deferred_cipher(&self) -> Option<&Cipher> {
+        self.conn_active
+            .as_ref()
+            .filter(|_| self.defer_encryption && self.should_encrypt())
+            .and_then(|conn| match &conn.crypto {
+                PacketCrypto::Discord(cipher, _) => Some(cipher),
+                PacketCrypto::External(_) => None,
+            })
     }
 
+    /// Sends a built voice packet, returning the number of datagrams sent in
+    /// its syscall.
+    ///
+    /// If `batch_keepalive` is set and a keepalive is due, it is sent alongside
+    /// the voice packet in the same syscall where supported.
     #[inline]
-    pub(crate) fn send_packet(&self, packet: &[u8]) -> Result<()> {
+    pub(crate) fn send_packet(&mut self, packet: &[u8], batch_keepalive: bool) -> Result<usize> {
         #[cfg(test)]
         let send_status = if let Some(OutputMode::Raw(tx)) = &self.config.override_connection {
             // This case has been handled before buffer clearing in `mix_and_build_packet`.
             drop(tx.send(self.raw_msg.clone().unwrap().into()));
 
-            Ok(())
+            Ok(1)
         } else {
-            self._send_packet(packet)
+            self._send_packet(packet, batch_keepalive)
         };
 
         #[cfg(not(test))]
-        let send_status = self._send_packet(packet);
+        let send_status = self._send_packet(packet, batch_keepalive);
+
+        let send_status = send_status.or_else(|e| e.disarm_would_block().map(|()| 0));
+        if let Err(Error::Io(e)) = &send_status {
+            self.record_send_failure(e);
+        }
+        let sent = send_status?;
+
+        if sent > 0 {
+            self.send_failures = 0;
+            self.send_stats.packets_sent += sent as u64;
+            self.record_transmit()?;
+        }
+
+        Ok(sent)
+    }
+
+    /// Counts a failed UDP send, asking the core task to fail over to the next of
+    /// [`Config::bind_addresses`] once too many have failed in a row, or to reconnect
+    /// once [`Config::udp_dead_threshold`] is reached.
+    fn record_send_failure(&mut self, e: &IoError) {
+        self.send_failures = self.send_failures.saturating_add(1);
+        self.send_stats.record_error(e);
+
+        if self
+            .config
+            .udp_dead_threshold
+            .is_some_and(|t| self.send_failures >= t)
+        {
+            warn!(
+                "{} consecutive UDP send failures (last: {e}): reconnecting.",
+                self.send_failures
+            );
+            drop(self.full_reconnect_gateway());
+            return;
+        }
+
+        if !self.rebinding
+            && self.config.bind_addresses.len() > 1
+            && self.send_failures >= self.config.udp_failover_threshold
+        {
+            warn!(
+                "{} consecutive UDP send failures: failing over to next bind address.",
+                self.send_failures
+            );
+            self.rebinding = true;
+            drop(self.interconnect.core.send(CoreMessage::Rebind));
+        }
+    }
+
+    /// Counts a sent voice packet, firing a [`CoreEvent::Transmit`] once the configured
+    /// amount of audio has been sent.
+    ///
+    /// [`CoreEvent::Transmit`]: crate::events::CoreEvent::Transmit
+    #[inline]
+    fn record_transmit(&mut self) -> Result<()> {
+        let Some(interval) = self.config.transmit_event_interval else {
+            return Ok(());
+        };
 
-        send_status.or_else(Error::disarm_would_block)?;
+        if self.last_frame_silent {
+            self.transmit.silence_frames += 1;
+        } else {
+            self.transmit.frames += 1;
+        }
+
+        let interval_frames = interval
+            .as_nanos()
+            .div_ceil(TIMESTEP_LENGTH.as_nanos())
+            .max(1);
+
+        if u128::from(self.transmit.total_frames()) >= interval_frames {
+            let data = std::mem::take(&mut self.transmit);
+            self.fire_event(EventMessage::FireCoreEvent(CoreContext::Transmit(data)))?;
+        }
 
         Ok(())
     }
 
     #[inline]
-    fn _send_packet(&self, packet: &[u8]) -> Result<()> {
+    fn _send_packet(&mut self, packet: &[u8], batch_keepalive: bool) -> Result<usize> {
         let conn = self
             .conn_active
             .as_ref()
@@ -701,36 +1417,58 @@ impl Mixer {
         if let Some(OutputMode::Rtp(tx)) = &self.config.override_connection {
             // Test mode: send unencrypted (compressed) packets to local receiver.
             drop(tx.send(packet.to_vec().into()));
-        } else {
-            conn.udp_tx.send(packet)?;
+            return Ok(1);
         }
 
-        #[cfg(not(test))]
+        // Normal operation: send encrypted payload to UDP Tx task.
+        let retries = self.config.udp_send_retries;
+        if batch_keepalive && conn.crypto.is_discord() && Instant::now() >= self.keepalive_deadline
         {
-            // Normal operation: send encrypted payload to UDP Tx task.
-            conn.udp_tx.send(packet)?;
-        }
+            let sent = send_with_retry(retries, &mut self.send_stats, || {
+                send_batch(&conn.udp_tx, [packet, &self.keepalive_packet[..]])
+            })?;
+            if sent == 2 {
+                self.keepalive_deadline += UDP_KEEPALIVE_GAP;
+            }
 
-        Ok(())
+            Ok(sent)
+        } else {
+            send_with_retry(retries, &mut self.send_stats, || conn.udp_tx.send(packet))?;
+
+            Ok(1)
+        }
     }
 
+    /// Sends a UDP keepalive if one is due, returning whether a packet was sent.
     #[inline]
-    pub(crate) fn check_and_send_keepalive(&mut self, now: Option<Instant>) -> Result<()> {
-        if let Some(conn) = self.conn_active.as_mut() {
+    pub(crate) fn check_and_send_keepalive(&mut self, now: Option<Instant>) -> Result<bool> {
+        // Keepalives are specific to Discord's voice servers.
+        if let Some(conn) = self.conn_active.as_mut().filter(|c| c.crypto.is_discord()) {
             let now = now.unwrap_or_else(Instant::now);
             if now >= self.keepalive_deadline {
-                conn.udp_tx.send(&self.keepalive_packet)?;
+                let retries = self.config.udp_send_retries;
+                let keepalive = &self.keepalive_packet;
+                if let Err(e) = send_with_retry(retries, &mut self.send_stats, || {
+                    conn.udp_tx.send(keepalive)
+                }) {
+                    if e.kind() != IoErrorKind::WouldBlock {
+                        self.record_send_failure(&e);
+                    }
+                    return Err(e.into());
+                }
+                self.send_stats.packets_sent += 1;
                 self.keepalive_deadline += UDP_KEEPALIVE_GAP;
+                return Ok(true);
             }
         }
 
-        Ok(())
+        Ok(false)
     }
 
     #[inline]
     pub(crate) fn send_gateway_speaking(&self) -> Result<()> {
         if let Some(ws) = &self.ws {
-            ws.send(WsMessage::Speaking(true))?;
+            ws.send(WsMessage::Speaking(true, self.config.speaking_delay))?;
         }
 
         Ok(())
@@ -743,7 +1481,7 @@ impl Mixer {
             // A full reconnect might cause an inner closed connection.
             // It's safer to leave the central task to clean this up and
             // pass the mixer a new channel.
-            drop(ws.send(WsMessage::Speaking(false)));
+            drop(ws.send(WsMessage::Speaking(false, self.config.speaking_delay)));
         }
     }
 
@@ -755,7 +1493,7 @@ impl Mixer {
                 (Blame: VOICE_PACKET_MAX?)",
         );
         let payload = rtp.payload_mut();
-        let opus_frame = &mut payload[self.crypto_mode().payload_prefix_len2()..];
+        let opus_frame = &mut payload[self.payload_prefix_len()..];
 
         // Opus frame passthrough.
         // This requires that we have only one PLAYING track, who has volume 1.0, and an
@@ -765,17 +1503,53 @@ impl Mixer {
         // quite fragile given all the ways a user can alter the PlayMode.
         let mut num_live = 0;
         let mut last_live_vol = 1.0;
+        let mut last_live_pan = 0.0f32;
         for track in &self.tracks {
             if track.playing.is_playing() {
                 num_live += 1;
-                last_live_vol = track.volume;
+                last_live_vol = track.mix_volume();
+                last_live_pan = track.pan;
             }
         }
-        let do_passthrough = num_live == 1 && (last_live_vol - 1.0).abs() < f32::EPSILON;
+        let solo = num_live == 1
+            && (last_live_vol - 1.0).abs() < f32::EPSILON
+            && last_live_pan.abs() < f32::EPSILON
+            && self.analysis.is_none();
+        // A source's own packets could exceed any bandwidth cap.
+        let capped = self.bandwidth_limit.is_some();
+        let do_passthrough = solo && !capped;
+
+        let start = Instant::now();
+        let policy = self.config.overload_policy;
+        let budget = policy
+            .filter(|p| p.strategy != OverloadStrategy::DownmixMono)
+            .map(|p| p.budget);
+
+        let mut order = std::mem::take(&mut self.mix_order);
+        order.clear();
+        order.extend(0..self.tracks.len());
+        match policy.map(|p| p.strategy) {
+            Some(OverloadStrategy::DropLowestPriority) =>
+                order.sort_by_key(|&i| std::cmp::Reverse(self.tracks[i].priority)),
+            Some(OverloadStrategy::Interleave) if !order.is_empty() => {
+                let n = order.len();
+                order.rotate_left(self.interleave_next % n);
+            },
+            _ => {},
+        }
 
         let mut len = 0;
-        for (i, track) in self.tracks.iter_mut().enumerate() {
-            let vol = track.volume;
+        let mut mixed = 0;
+        let mut held = 0;
+        let mut passthrough = None;
+        for &i in &order {
+            let track = &mut self.tracks[i];
+
+            // Panning only applies to stereo output.
+            let vol = match self.mix_mode {
+                MixMode::Mono => [track.mix_volume(); 2],
+                MixMode::Stereo => mix_logic::pan_volume(track.mix_volume(), track.pan),
+            };
 
             // This specifically tries to get tracks who are "preparing",
             // so that event handlers and the like can all be fired without
@@ -823,14 +1597,71 @@ impl Mixer {
                 continue;
             }
 
-            let (mix_type, status) = mix_logic::mix_symph_indiv(
-                &mut self.symph_mix,
-                &mut self.resample_scratch,
-                input,
-                mix_state,
-                vol,
-                do_passthrough.then_some(&mut *opus_frame),
-            );
+            // Once over budget, hold remaining tracks in place for this frame.
+            // Pre-rolled tracks can instead play out audio they have already decoded.
+            let prerolled = mix_state.preroll.is_enabled();
+            let over_budget = budget.is_some_and(|b| mixed > 0 && start.elapsed() >= b);
+            if over_budget && !prerolled {
+                if held == 0 {
+                    self.interleave_next = i;
+                }
+                held += 1;
+                continue;
+            }
+            mixed += 1;
+
+            let mut tap = self.analysis.as_mut().filter(|tap| tap.per_track());
+            if let Some(tap) = &mut tap {
+                tap.before_track(&self.symph_mix);
+            }
+
+            let mixed_frame = if prerolled {
+                preroll::mix_prerolled(
+                    &mut self.symph_mix,
+                    &mut self.resample_scratch,
+                    input,
+                    mix_state,
+                    vol,
+                    self.config.downmix,
+                    !over_budget,
+                )
+            } else {
+                Some(mix_logic::mix_symph_indiv(
+                    &mut self.symph_mix,
+                    &mut self.resample_scratch,
+                    input,
+                    mix_state,
+                    vol,
+                    self.config.downmix,
+                    (do_passthrough && fits_mix_mode(input, self.mix_mode))
+                        .then_some(&mut *opus_frame),
+                ))
+            };
+
+            if let Some(tap) = tap {
+                tap.after_track(self.track_handles[i].uuid(), &self.symph_mix);
+            }
+
+            let Some((mix_type, status)) = mixed_frame else {
+                if held == 0 {
+                    self.interleave_next = i;
+                }
+                held += 1;
+                continue;
+            };
+
+            let mix_path = match mix_type {
+                MixType::Passthrough(_) => (MixPath::Passthrough, None),
+                MixType::MixedPcm(_) => {
+                    let path = if mix_state.resampler.is_some() {
+                        MixPath::DecodeResample
+                    } else {
+                        MixPath::Decode
+                    };
+                    let reason = decode_reason(input, mix_state, solo, capped, self.mix_mode);
+                    (path, Some(reason))
+                },
+            };
 
             let return_here = if let MixType::MixedPcm(pcm_len) = mix_type {
                 len = len.max(pcm_len);
@@ -846,11 +1677,21 @@ impl Mixer {
             // FIXME: allow Ended to trigger a seek/loop/revisit in the same mix cycle?
             // Would this be possible with special-casing to mark some inputs as fast
             // to recreate? Probably not doable in the general case.
+            if matches!(status, MixStatus::Truncated) && !self.prevent_events {
+                drop(
+                    self.interconnect
+                        .events
+                        .send(EventMessage::ChangeState(i, TrackStateChange::Truncated)),
+                );
+            }
+
+            track.mix_path = Some(mix_path);
+
             match status {
                 MixStatus::Live => track.step_frame(),
                 MixStatus::Errored(e) =>
                     track.playing = PlayMode::Errored(PlayError::Decode(e.into())),
-                MixStatus::Ended if track.do_loop() => {
+                MixStatus::Ended | MixStatus::Truncated if track.do_loop() => {
                     drop(self.track_handles[i].seek(Duration::default()));
                     if !self.prevent_events {
                         // position update is sent out later, when the seek concludes.
@@ -860,17 +1701,406 @@ impl Mixer {
                         )));
                     }
                 },
-                MixStatus::Ended => {
+                MixStatus::Ended | MixStatus::Truncated => {
                     track.end();
                 },
             }
 
             // This needs to happen here due to borrow checker shenanigans.
             if return_here {
-                return mix_type;
+                passthrough = Some(mix_type);
+                break;
             }
         }
 
-        MixType::MixedPcm(len)
+        self.mix_order = order;
+        self.last_mix_time = start.elapsed();
+        self.held_tracks = held;
+
+        passthrough.unwrap_or(MixType::MixedPcm(len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        driver::{BandwidthCap, GatewayError, RtpProtector},
+        input::{
+            codecs::{CODEC_REGISTRY, PROBE},
+            RawAdapter,
+        },
+        test_utils,
+        tracks::Track,
+    };
+    use std::{
+        io::Cursor,
+        net::{Ipv4Addr, UdpSocket},
+    };
+    use tokio::runtime::Handle;
+
+    #[tokio::test]
+    async fn bitrate_automation_follows_track_position() {
+        let (mut mixer, _listeners) = Mixer::mock(Handle::current(), false);
+        let floats = test_utils::make_sine(10 * STEREO_FRAME_SIZE, true);
+        let input: Input = RawAdapter::new(Cursor::new(floats), 48_000, 2).into();
+
+        let speech = Bitrate::BitsPerSecond(24_000);
+        let music = Bitrate::BitsPerSecond(96_000);
+        let (_, ctx) = Track::from(input)
+            .bitrate_automation(vec![
+                (Duration::from_secs(60), music),
+                (Duration::from_secs(1), speech),
+            ])
+            .into_context();
+        mixer.add_track(ctx).unwrap();
+
+        mixer.apply_bitrate_automation();
+        assert_eq!(mixer.encoder.bitrate().unwrap(), mixer.bitrate);
+
+        mixer.tracks[0].position = Duration::from_secs(2);
+        mixer.apply_bitrate_automation();
+        assert_eq!(mixer.encoder.bitrate().unwrap(), speech);
+
+        // Driver-level changes wait until automation releases the encoder.
+        let mut packet = [0u8; VOICE_PACKET_MAX];
+        let driver_rate = Bitrate::BitsPerSecond(64_000);
+        mixer.handle_message(MixerMessage::SetBitrate(driver_rate), &mut packet);
+        assert_eq!(mixer.encoder.bitrate().unwrap(), speech);
+
+        mixer.tracks[0].position = Duration::from_secs(61);
+        mixer.apply_bitrate_automation();
+        assert_eq!(mixer.encoder.bitrate().unwrap(), music);
+
+        mixer.tracks[0].playing = PlayMode::Pause;
+        mixer.apply_bitrate_automation();
+        assert_eq!(mixer.encoder.bitrate().unwrap(), driver_rate);
+    }
+
+    #[tokio::test]
+    async fn bandwidth_cap_throttles_and_refuses_tracks() {
+        let (mut mixer, _listeners) = Mixer::mock(Handle::current(), false);
+        let budget = SharedBandwidth::new(64);
+        let cap = BandwidthCap::default()
+            .max_kbps(Some(48))
+            .shared(Some(budget.clone()))
+            .refuse_below_kbps(Some(24));
+        mixer.config = Arc::new((*mixer.config).clone().bandwidth_cap(Some(cap)));
+
+        // Packet headers are paid for out of the cap.
+        let overhead = mixer.packet_overhead_bps() as i32;
+        assert!(overhead > 0);
+
+        mixer.check_bandwidth();
+        assert_eq!(budget.active_calls(), 1);
+        assert_eq!(
+            mixer.encoder.bitrate().unwrap(),
+            Bitrate::BitsPerSecond(48_000 - overhead)
+        );
+
+        // Another call halves this call's share, below the per-call cap.
+        let other = budget.join();
+        mixer.check_bandwidth();
+        assert_eq!(
+            mixer.encoder.bitrate().unwrap(),
+            Bitrate::BitsPerSecond(32_000 - overhead)
+        );
+
+        // Requests under the limit are left untouched.
+        let mut packet = [0u8; VOICE_PACKET_MAX];
+        let low = Bitrate::BitsPerSecond(8_000);
+        mixer.handle_message(MixerMessage::SetBitrate(low), &mut packet);
+        mixer.check_bandwidth();
+        assert_eq!(mixer.encoder.bitrate().unwrap(), low);
+
+        let third = budget.join();
+        mixer.check_bandwidth();
+        let input: Input = RawAdapter::new(Cursor::new(Vec::<u8>::new()), 48_000, 2).into();
+        let (_, ctx) = Track::from(input).into_context();
+        mixer.add_track(ctx).unwrap();
+        assert!(matches!(
+            mixer.tracks[0].playing,
+            PlayMode::Errored(PlayError::Bandwidth)
+        ));
+
+        drop((other, third));
+        mixer.conn_active = None;
+        mixer.check_bandwidth();
+        assert_eq!(budget.active_calls(), 0);
+    }
+
+    #[cfg(feature = "rtp-control")]
+    fn rtp_state(mixer: &mut Mixer, packet: &mut [u8]) -> Option<crate::driver::RtpState> {
+        let (tx, rx) = flume::bounded(1);
+        mixer.handle_message(MixerMessage::GetRtpState(tx), packet);
+        rx.recv().unwrap()
+    }
+
+    #[cfg(feature = "rtp-control")]
+    #[tokio::test]
+    async fn rtp_override_is_applied_and_read_back() {
+        let (mut mixer, _listeners) = Mixer::mock(Handle::current(), false);
+        let mut packet = [0u8; VOICE_PACKET_MAX];
+
+        let rtp = crate::driver::RtpOverride {
+            ssrc: Some(0xDEAD_BEEF),
+            sequence: Some(1000),
+            timestamp: Some(48_000),
+        };
+        mixer.handle_message(MixerMessage::SetRtpOverride(rtp), &mut packet);
+
+        let state = rtp_state(&mut mixer, &mut packet).unwrap();
+        assert_eq!(
+            (state.ssrc, state.sequence, state.timestamp),
+            (0xDEAD_BEEF, 1000, 48_000)
+        );
+
+        // Unset fields leave the current header untouched.
+        let ssrc_only = crate::driver::RtpOverride {
+            ssrc: Some(1),
+            ..Default::default()
+        };
+        mixer.handle_message(MixerMessage::SetRtpOverride(ssrc_only), &mut packet);
+        let state = rtp_state(&mut mixer, &mut packet).unwrap();
+        assert_eq!(
+            (state.ssrc, state.sequence, state.timestamp),
+            (1, 1000, 48_000)
+        );
+
+        mixer.conn_active = None;
+        assert!(rtp_state(&mut mixer, &mut packet).is_none());
+    }
+
+    #[cfg(feature = "rtp-control")]
+    #[tokio::test]
+    async fn rtp_override_survives_reconnect() {
+        let (mut mixer, _listeners) = Mixer::mock(Handle::current(), false);
+        let mut packet = [0u8; VOICE_PACKET_MAX];
+
+        let rtp = crate::driver::RtpOverride {
+            ssrc: Some(0xDEAD_BEEF),
+            sequence: Some(1000),
+            timestamp: Some(48_000),
+        };
+        mixer.handle_message(MixerMessage::SetRtpOverride(rtp), &mut packet);
+
+        // Sending advances the counters from their overridden values.
+        {
+            let mut header = MutableRtpPacket::new(&mut packet[..]).unwrap();
+            header.set_sequence(1010_u16.into());
+            header.set_timestamp((48_000 + 10 * MONO_FRAME_SIZE as u32).into());
+        }
+
+        // A new websocket leaves the RTP state alone...
+        mixer.handle_message(MixerMessage::Ws(None), &mut packet);
+        let state = rtp_state(&mut mixer, &mut packet).unwrap();
+        assert_eq!(state.ssrc, 0xDEAD_BEEF);
+        assert_eq!(state.sequence, 1010);
+
+        // ...while a new connection, with a fresh SSRC from Discord, has the
+        // override reapplied over its randomised header.
+        let conn = mixer.conn_active.take().unwrap();
+        mixer.handle_message(MixerMessage::SetConn(conn, 42, None), &mut packet);
+        let state = rtp_state(&mut mixer, &mut packet).unwrap();
+        assert_eq!(
+            (state.ssrc, state.sequence, state.timestamp),
+            (0xDEAD_BEEF, 1000, 48_000)
+        );
+    }
+
+    #[tokio::test]
+    async fn volume_ramp_steps_once_per_frame() {
+        let (mut mixer, _listeners) = Mixer::mock(Handle::current(), false);
+        let floats = test_utils::make_sine(10 * STEREO_FRAME_SIZE, true);
+        let input: Input = RawAdapter::new(Cursor::new(floats), 48_000, 2).into();
+
+        let (handle, ctx) = Track::from(input).into_context();
+        mixer.add_track(ctx).unwrap();
+
+        handle
+            .set_volume_ramped(0.0, Duration::from_millis(100))
+            .unwrap();
+        mixer.audio_commands_events().unwrap();
+        assert!((mixer.tracks[0].volume - 1.0).abs() < f32::EPSILON);
+        assert!(mixer.tracks[0].state().volume.abs() < f32::EPSILON);
+
+        let mut last = mixer.tracks[0].volume;
+        for _ in 0..4 {
+            mixer.tracks[0].step_frame();
+            let volume = mixer.tracks[0].volume;
+            assert!(volume < last && volume > 0.0);
+            last = volume;
+        }
+
+        mixer.tracks[0].step_frame();
+        assert!(mixer.tracks[0].volume.abs() < f32::EPSILON);
+        assert!(mixer.tracks[0].volume_ramp.is_none());
+
+        // An instant change cancels any ramp in progress.
+        handle
+            .set_volume_ramped(1.0, Duration::from_secs(1))
+            .unwrap();
+        handle.set_volume(0.5).unwrap();
+        mixer.audio_commands_events().unwrap();
+        mixer.tracks[0].step_frame();
+        assert!((mixer.tracks[0].volume - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn muted_track_mixes_silence_without_pausing() {
+        let (mut mixer, _listeners) = Mixer::mock(Handle::current(), false);
+        let floats = test_utils::make_sine(10 * STEREO_FRAME_SIZE, true);
+        let input: Input = RawAdapter::new(Cursor::new(floats), 48_000, 2).into();
+
+        let (handle, ctx) = Track::from(input).volume(0.5).into_context();
+        mixer.add_track(ctx).unwrap();
+
+        handle.set_mute(true).unwrap();
+        mixer.audio_commands_events().unwrap();
+        assert!(mixer.tracks[0].playing.is_playing());
+        assert!(mixer.tracks[0].mix_volume().abs() < f32::EPSILON);
+        assert!(mixer.tracks[0].state().muted);
+
+        mixer.tracks[0].step_frame();
+        assert_eq!(mixer.tracks[0].position, TIMESTEP_LENGTH);
+
+        handle.set_mute(false).unwrap();
+        mixer.audio_commands_events().unwrap();
+        assert!((mixer.tracks[0].mix_volume() - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn sub_threshold_audio_does_not_build_up_while_quiet() {
+        let (mut mixer, _listeners) = Mixer::mock(Handle::current(), false);
+        let detect = SilenceDetection::default().hold(Duration::from_millis(40));
+        mixer.config = Arc::new((*mixer.config).clone().silence_detection(Some(detect)));
+        let (ws_tx, ws_rx) = flume::unbounded();
+        mixer.ws = Some(ws_tx);
+
+        // A DC offset at half the detection threshold.
+        let floats: Vec<u8> = std::iter::repeat(5e-5_f32.to_le_bytes())
+            .take(20 * STEREO_FRAME_SIZE)
+            .flatten()
+            .collect();
+        let input: Input = RawAdapter::new(Cursor::new(floats), 48_000, 2).into();
+        let promoted = match input {
+            Input::Live(l, _) => l.promote(&CODEC_REGISTRY, &PROBE),
+            Input::Lazy(_) => panic!("Failed to create a guaranteed source."),
+        };
+        let (_, ctx) = Track::from(Input::Live(promoted.unwrap(), None)).into_context();
+        mixer.add_track(ctx).unwrap();
+
+        let mut packet = [0u8; VOICE_PACKET_MAX];
+        for _ in 0..10 {
+            mixer.mix_and_build_packet(&mut packet).unwrap();
+        }
+
+        assert!(mixer.quiet);
+        assert!(!ws_rx
+            .try_iter()
+            .any(|msg| matches!(msg, WsMessage::Speaking(true, _))));
+        for plane in mixer.symph_mix.planes().planes() {
+            assert!(plane.iter().all(|s| s.abs() < f32::EPSILON));
+        }
+    }
+
+    #[tokio::test]
+    async fn repeated_send_failures_request_one_rebind() {
+        let (mut mixer, listeners) = Mixer::mock(Handle::current(), false);
+        let core_rx = listeners.0;
+        mixer.config = Arc::new(
+            mixer
+                .config
+                .as_ref()
+                .clone()
+                .bind_addresses(vec![
+                    Ipv4Addr::LOCALHOST.into(),
+                    Ipv4Addr::UNSPECIFIED.into(),
+                ])
+                .udp_failover_threshold(3),
+        );
+
+        for _ in 0..2 {
+            mixer.record_send_failure(&IoErrorKind::PermissionDenied.into());
+        }
+        assert!(core_rx.try_recv().is_err());
+
+        for _ in 0..5 {
+            mixer.record_send_failure(&IoErrorKind::PermissionDenied.into());
+        }
+        assert!(matches!(core_rx.try_recv(), Ok(CoreMessage::Rebind)));
+        assert!(core_rx.try_recv().is_err());
+
+        let udp_tx = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut packet = [0u8; VOICE_PACKET_MAX];
+        mixer.handle_message(MixerMessage::ReplaceUdp(udp_tx), &mut packet);
+        assert_eq!(mixer.send_failures, 0);
+
+        for _ in 0..3 {
+            mixer.record_send_failure(&IoErrorKind::PermissionDenied.into());
+        }
+        assert!(matches!(core_rx.try_recv(), Ok(CoreMessage::Rebind)));
+    }
+
+    #[tokio::test]
+    async fn dead_threshold_reconnects_and_counts_errors() {
+        let (mut mixer, listeners) = Mixer::mock(Handle::current(), false);
+        let core_rx = listeners.0;
+        mixer.config = Arc::new(mixer.config.as_ref().clone().udp_dead_threshold(Some(3)));
+
+        mixer.record_send_failure(&IoErrorKind::PermissionDenied.into());
+        mixer.record_send_failure(&IoErrorKind::InvalidInput.into());
+        assert!(core_rx.try_recv().is_err());
+
+        let mut packet = [0u8; VOICE_PACKET_MAX];
+        let (tx, rx) = flume::bounded(1);
+        mixer.handle_message(MixerMessage::GetUdpSendStats(tx), &mut packet);
+        let stats = rx.try_recv().unwrap().unwrap();
+        assert_eq!(stats.transient_errors, 1);
+        assert_eq!(stats.fatal_errors, 1);
+        assert_eq!(stats.consecutive_failures, 2);
+
+        mixer.record_send_failure(&IoErrorKind::PermissionDenied.into());
+        assert!(matches!(
+            core_rx.try_recv(),
+            Ok(CoreMessage::FullReconnect(TeardownCause::UdpFailure))
+        ));
+        assert!(mixer.conn_active.is_none());
+    }
+
+    #[tokio::test]
+    async fn external_connection_sends_plain_protected_rtp() {
+        struct Tag;
+
+        impl RtpProtector for Tag {
+            fn overhead(&self) -> usize {
+                4
+            }
+
+            fn protect(&self, packet: &mut [u8], len: usize) -> StdResult<usize, GatewayError> {
+                packet[len..][..4].copy_from_slice(b"TAG!");
+                Ok(len + 4)
+            }
+        }
+
+        let (mut mixer, _listeners) = Mixer::mock(Handle::current(), false);
+        mixer.conn_active.as_mut().unwrap().crypto = PacketCrypto::External(Some(Arc::new(Tag)));
+
+        let header_len = RtpPacket::minimum_packet_size();
+        let mut packet = [0u8; VOICE_PACKET_MAX];
+        packet[header_len..][..SILENT_FRAME.len()].copy_from_slice(&SILENT_FRAME);
+
+        let len = mixer
+            .prep_packet(MixType::Passthrough(SILENT_FRAME.len()), &mut packet)
+            .unwrap();
+
+        // No Discord nonce or encryption: only the protector's trailer is added.
+        assert_eq!(len, header_len + SILENT_FRAME.len() + 4);
+        assert_eq!(&packet[header_len..][..SILENT_FRAME.len()], &SILENT_FRAME);
+        assert_eq!(&packet[len - 4..len], b"TAG!");
+
+        // Discord's UDP keepalives are never sent to external gateways.
+        let later = Instant::now() + Duration::from_secs(60);
+        assert!(!mixer.check_and_send_keepalive(Some(later)).unwrap());
     }
 }
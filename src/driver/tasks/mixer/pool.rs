@@ -1,14 +1,16 @@
-use super::util::copy_seek_to;
+use super::util::{copy_seek_to, preroll_seek_to};
 
 use crate::{
-    driver::tasks::message::MixerInputResultMessage,
+    constants::OPUS_SEEK_PREROLL,
+    driver::{retry::Retry, tasks::message::MixerInputResultMessage},
     input::{AudioStream, AudioStreamError, Compose, Input, LiveInput, Parsed},
     Config,
 };
 use flume::Sender;
 use rusty_pool::ThreadPool;
-use std::{result::Result as StdResult, sync::Arc, time::Duration};
+use std::{result::Result as StdResult, sync::Arc, thread, time::Duration};
 use symphonia_core::{
+    codecs::CODEC_TYPE_OPUS,
     formats::{SeekMode, SeekTo},
     io::MediaSource,
 };
@@ -42,14 +44,27 @@ impl BlockyTaskPool {
         match input {
             Input::Lazy(mut lazy) => {
                 let far_pool = self.clone();
+                let mut retry = CreateRetry::new(config.input_retry);
                 if lazy.should_create_async() {
                     self.handle.spawn(async move {
-                        let out = lazy.create_async().await;
+                        let out = loop {
+                            let out = lazy.create_async().await;
+                            match out.as_ref().err().and_then(|e| retry.next_wait(e)) {
+                                Some(wait) => tokio::time::sleep(wait).await,
+                                None => break out,
+                            }
+                        };
                         far_pool.send_to_parse(out, lazy, callback, seek_time, config);
                     });
                 } else {
                     self.pool.execute(move || {
-                        let out = lazy.create();
+                        let out = loop {
+                            let out = lazy.create();
+                            match out.as_ref().err().and_then(|e| retry.next_wait(e)) {
+                                Some(wait) => thread::sleep(wait),
+                                None => break out,
+                            }
+                        };
                         far_pool.send_to_parse(out, lazy, callback, seek_time, config);
                     });
                 }
@@ -126,9 +141,29 @@ impl BlockyTaskPool {
                 pool_clone.create(callback, Input::Lazy(rec), Some(seek_time), config);
             },
             _ => {
-                let seek_result = input
-                    .format
-                    .seek(SeekMode::Accurate, copy_seek_to(&seek_time));
+                // Opus decoders need some audio ahead of the target to converge: seek
+                // early, and report the true target so that the mixer may preroll up to it.
+                let params = input.decoder.codec_params();
+                let preroll = params
+                    .time_base
+                    .filter(|_| params.codec == CODEC_TYPE_OPUS)
+                    .map(|time_base| preroll_seek_to(&seek_time, OPUS_SEEK_PREROLL, time_base));
+
+                let (seek_to, target_ts) = match preroll {
+                    Some((seek_to, target_ts)) => (seek_to, Some(target_ts)),
+                    None => (copy_seek_to(&seek_time), None),
+                };
+
+                let seek_result =
+                    input
+                        .format
+                        .seek(SeekMode::Accurate, seek_to)
+                        .map(|mut seeked| {
+                            if let Some(target_ts) = target_ts {
+                                seeked.required_ts = target_ts;
+                            }
+                            seeked
+                        });
                 input.decoder.reset();
                 drop(callback.send(MixerInputResultMessage::Seek(
                     input,
@@ -139,3 +174,96 @@ impl BlockyTaskPool {
         });
     }
 }
+
+/// Attempt tracking for recreating a lazy input according to [`Config::input_retry`].
+struct CreateRetry {
+    retry: Option<Retry>,
+    attempts: usize,
+    last_wait: Option<Duration>,
+}
+
+impl CreateRetry {
+    fn new(retry: Option<Retry>) -> Self {
+        Self {
+            retry,
+            attempts: 0,
+            last_wait: None,
+        }
+    }
+
+    /// Returns how long to wait before retrying after `err`, if another attempt is allowed.
+    fn next_wait(&mut self, err: &AudioStreamError) -> Option<Duration> {
+        let wait = match (err, self.retry) {
+            (AudioStreamError::Unsupported, _) | (_, None) => None,
+            (AudioStreamError::RetryIn(t), Some(retry)) => retry
+                .retry_in(self.last_wait, self.attempts)
+                .map(|wait| wait.max(*t)),
+            (AudioStreamError::Fail(_), Some(retry)) =>
+                retry.retry_in(self.last_wait, self.attempts),
+        }?;
+
+        tracing::debug!("Lazy input creation failed ({err}): retrying in {wait:?}.");
+        self.attempts += 1;
+        self.last_wait = Some(wait);
+
+        Some(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::test_data::FILE_WAV_TARGET,
+        driver::{retry::Strategy, Driver},
+        input::File,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct Flaky {
+        attempts: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Compose for Flaky {
+        fn create(&mut self) -> StdResult<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+            Err(AudioStreamError::Unsupported)
+        }
+
+        async fn create_async(
+            &mut self,
+        ) -> StdResult<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+            if self.attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                Err(AudioStreamError::Fail("source not yet available".into()))
+            } else {
+                File::new(FILE_WAV_TARGET).create_async().await
+            }
+        }
+
+        fn should_create_async(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn lazy_creation_is_retried() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let config = config.input_retry(Some(Retry {
+            strategy: Strategy::Every(Duration::from_millis(1)),
+            retry_limit: Some(2),
+        }));
+        let mut driver = Driver::new(config);
+
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let handle = driver.play_input(Input::Lazy(Box::new(Flaky {
+            attempts: attempts.clone(),
+        })));
+
+        t_handle
+            .ready_track(&handle, Some(Duration::from_millis(1)))
+            .await;
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,233 @@
+use super::*;
+use std::collections::VecDeque;
+
+/// Most frames decoded for one track in a single mix cycle while refilling its
+/// pre-roll, so that a large pre-roll does not stall the mixer when filled.
+const MAX_FILL_PER_FRAME: usize = 4;
+
+/// Frames of a track decoded ahead of playout at unit volume.
+///
+/// Volume and pan are applied as frames are played out, so that changes to either
+/// take effect immediately.
+#[derive(Default)]
+pub struct PreRoll {
+    capacity: usize,
+    frames: VecDeque<PreRollFrame>,
+    spare: Vec<AudioBuffer<f32>>,
+    finished: bool,
+}
+
+struct PreRollFrame {
+    audio: AudioBuffer<f32>,
+    len: usize,
+    status: MixStatus,
+}
+
+impl PreRoll {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            ..Default::default()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.capacity > 0
+    }
+
+    /// Discards all buffered audio, e.g., after a seek.
+    pub fn flush(&mut self) {
+        self.spare
+            .extend(self.frames.drain(..).map(|frame| frame.audio));
+        self.finished = false;
+    }
+
+    fn spare_buffer(&mut self, spec: SignalSpec) -> AudioBuffer<f32> {
+        let mut audio = self
+            .spare
+            .pop()
+            .filter(|buf| *buf.spec() == spec)
+            .unwrap_or_else(|| AudioBuffer::new(MONO_FRAME_SIZE as u64, spec));
+
+        audio.clear();
+        audio.render_reserved(Some(MONO_FRAME_SIZE));
+        for plane in audio.planes_mut().planes() {
+            plane.fill(0.0);
+        }
+
+        audio
+    }
+
+    /// Decodes frames from `input` until this pre-roll is full, or until
+    /// `MAX_FILL_PER_FRAME` frames have been decoded.
+    fn fill(
+        &mut self,
+        spec: SignalSpec,
+        resample_scratch: &mut AudioBuffer<f32>,
+        input: &mut Parsed,
+        local_state: &mut DecodeState,
+        downmix: DownmixMode,
+    ) {
+        // The mix buffer's layout changes along with the driver's mix mode.
+        if self.frames.front().is_some_and(|f| *f.audio.spec() != spec) {
+            self.flush();
+            self.spare.clear();
+        }
+
+        let mut decoded = 0;
+        while !self.finished && self.frames.len() <= self.capacity && decoded < MAX_FILL_PER_FRAME {
+            let mut audio = self.spare_buffer(spec);
+            let (mix_type, status) = mix_logic::mix_symph_indiv(
+                &mut audio,
+                resample_scratch,
+                input,
+                local_state,
+                [1.0; 2],
+                downmix,
+                None,
+            );
+
+            let len = match mix_type {
+                MixType::MixedPcm(len) => len,
+                MixType::Passthrough(_) =>
+                    unreachable!("Passthrough is never offered to pre-roll."),
+            };
+
+            self.finished = !matches!(status, MixStatus::Live);
+            self.frames.push_back(PreRollFrame { audio, len, status });
+            decoded += 1;
+        }
+    }
+}
+
+/// Mixes the oldest pre-rolled frame of a track into `symph_mix`, first decoding
+/// ahead if `decode` is set.
+///
+/// Returns `None` if no frame was available to play.
+pub fn mix_prerolled(
+    symph_mix: &mut AudioBuffer<f32>,
+    resample_scratch: &mut AudioBuffer<f32>,
+    input: &mut Parsed,
+    local_state: &mut DecodeState,
+    volume: [f32; 2],
+    downmix: DownmixMode,
+    decode: bool,
+) -> Option<(MixType, MixStatus)> {
+    let mut preroll = std::mem::take(&mut local_state.preroll);
+
+    if decode {
+        preroll.fill(
+            *symph_mix.spec(),
+            resample_scratch,
+            input,
+            local_state,
+            downmix,
+        );
+    }
+
+    let out = preroll.frames.pop_front().map(|frame| {
+        for (i, (out, src)) in symph_mix
+            .planes_mut()
+            .planes()
+            .iter_mut()
+            .zip(frame.audio.planes().planes())
+            .enumerate()
+        {
+            let vol = volume[i.min(1)];
+            for (out, src) in out[..frame.len].iter_mut().zip(&src[..frame.len]) {
+                *out += vol * src;
+            }
+        }
+
+        preroll.spare.push(frame.audio);
+
+        (MixType::MixedPcm(frame.len), frame.status)
+    });
+
+    local_state.preroll = preroll;
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        input::{codecs::*, LiveInput, RawAdapter},
+        test_utils,
+    };
+    use std::io::Cursor;
+
+    #[tokio::test]
+    async fn preroll_decodes_ahead_and_flushes() {
+        let floats = test_utils::make_sine(50 * STEREO_FRAME_SIZE, true);
+        let input: Input = RawAdapter::new(Cursor::new(floats), 48_000, 2).into();
+        let input = input
+            .make_playable_async(&CODEC_REGISTRY, &PROBE)
+            .await
+            .unwrap();
+        let Input::Live(LiveInput::Parsed(mut parsed), _) = input else {
+            panic!("Input should have been parsed.");
+        };
+
+        let spec = SignalSpec::new_with_layout(SAMPLE_RATE_RAW as u32, Layout::Stereo);
+        let mut symph_mix = AudioBuffer::<f32>::new(MONO_FRAME_SIZE as u64, spec);
+        symph_mix.render_reserved(Some(MONO_FRAME_SIZE));
+        let mut scratch = AudioBuffer::<f32>::new(MONO_FRAME_SIZE as u64, spec);
+        let mut state = DecodeState::with_preroll(8);
+
+        let (mix_type, status) = mix_prerolled(
+            &mut symph_mix,
+            &mut scratch,
+            &mut parsed,
+            &mut state,
+            [1.0; 2],
+            DownmixMode::default(),
+            true,
+        )
+        .unwrap();
+        assert_eq!(mix_type, MixType::MixedPcm(MONO_FRAME_SIZE));
+        assert!(matches!(status, MixStatus::Live));
+        assert_eq!(state.preroll.frames.len(), MAX_FILL_PER_FRAME - 1);
+
+        // Buffered frames still play out when the mixer cannot afford to decode.
+        for _ in 0..MAX_FILL_PER_FRAME - 1 {
+            assert!(mix_prerolled(
+                &mut symph_mix,
+                &mut scratch,
+                &mut parsed,
+                &mut state,
+                [1.0; 2],
+                DownmixMode::default(),
+                false,
+            )
+            .is_some());
+        }
+        assert!(mix_prerolled(
+            &mut symph_mix,
+            &mut scratch,
+            &mut parsed,
+            &mut state,
+            [1.0; 2],
+            DownmixMode::default(),
+            false,
+        )
+        .is_none());
+
+        for _ in 0..4 {
+            mix_prerolled(
+                &mut symph_mix,
+                &mut scratch,
+                &mut parsed,
+                &mut state,
+                [1.0; 2],
+                DownmixMode::default(),
+                true,
+            );
+        }
+        assert_eq!(state.preroll.frames.len(), 8);
+
+        state.reset();
+        assert_eq!(state.preroll.frames.len(), 0);
+    }
+}
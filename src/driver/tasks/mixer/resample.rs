@@ -0,0 +1,84 @@
+use crate::constants::*;
+use rubato::{FftFixedOut, Resampler};
+use std::{cell::RefCell, collections::HashMap};
+
+/// Maximum number of idle resamplers kept per input format, on each mixing thread.
+const MAX_IDLE_PER_FORMAT: usize = 8;
+
+thread_local! {
+    /// Idle resamplers on this thread, keyed by input sample rate and channel count.
+    static IDLE: RefCell<HashMap<(u32, usize), Vec<ResampleState>>> = RefCell::new(HashMap::new());
+}
+
+/// A resampler converting one input format to the mixer's output rate, alongside
+/// its output buffer.
+///
+/// FFT planning and buffer allocation make these costly to build, so instances are
+/// returned to a per-thread cache once a track stops using them. Short clips of
+/// the same format (e.g., soundboards) then reuse one another's resamplers.
+pub struct ResampleState {
+    rate: u32,
+    pub chan_c: usize,
+    pub resampler: FftFixedOut<f32>,
+    pub out_buf: Vec<Vec<f32>>,
+}
+
+impl ResampleState {
+    /// Takes an idle resampler for this input format from the current thread's
+    /// cache, or creates a new one.
+    pub fn take(rate: u32, chan_c: usize) -> Self {
+        IDLE.with_borrow_mut(|idle| idle.get_mut(&(rate, chan_c)).and_then(Vec::pop))
+            .unwrap_or_else(|| Self::new(rate, chan_c))
+    }
+
+    fn new(rate: u32, chan_c: usize) -> Self {
+        // TODO: integ. error handling here.
+        let resampler = FftFixedOut::new(
+            rate as usize,
+            SAMPLE_RATE_RAW,
+            RESAMPLE_OUTPUT_FRAME_SIZE,
+            4,
+            chan_c,
+        )
+        .expect("Failed to create resampler.");
+        let out_buf = resampler.output_buffer_allocate(true);
+
+        Self {
+            rate,
+            chan_c,
+            resampler,
+            out_buf,
+        }
+    }
+
+    /// Clears this resampler's state, and returns it to the current thread's cache.
+    pub fn recycle(mut self) {
+        self.resampler.reset();
+
+        IDLE.with_borrow_mut(|idle| {
+            let idle = idle.entry((self.rate, self.chan_c)).or_default();
+            if idle.len() < MAX_IDLE_PER_FORMAT {
+                idle.push(self);
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recycled_resamplers_are_reused_per_format() {
+        let state = ResampleState::take(44_100, 2);
+        let ptr = state.out_buf[0].as_ptr();
+        state.recycle();
+
+        // Other formats do not share instances.
+        let mono = ResampleState::take(44_100, 1);
+        assert_ne!(mono.out_buf[0].as_ptr(), ptr);
+
+        let state = ResampleState::take(44_100, 2);
+        assert_eq!(state.out_buf[0].as_ptr(), ptr);
+    }
+}
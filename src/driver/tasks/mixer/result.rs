@@ -1,8 +1,8 @@
 use crate::{
-    input::AudioStreamError,
+    input::{cached::CacheTruncated, AudioStreamError},
     tracks::{PlayError, SeekRequest},
 };
-use std::sync::Arc;
+use std::{error::Error as StdError, sync::Arc};
 use symphonia_core::errors::Error as SymphoniaError;
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -14,9 +14,24 @@ pub enum MixType {
 pub enum MixStatus {
     Live,
     Ended,
+    /// The input ended early, as it was read from an incomplete cache.
+    Truncated,
     Errored(SymphoniaError),
 }
 
+impl MixStatus {
+    /// Classifies the error which ended an input's stream.
+    pub fn from_end(e: &SymphoniaError) -> Self {
+        match e {
+            SymphoniaError::IoError(e)
+                if e.get_ref()
+                    .is_some_and(<dyn StdError + Send + Sync>::is::<CacheTruncated>) =>
+                Self::Truncated,
+            _ => Self::Ended,
+        }
+    }
+}
+
 impl From<SymphoniaError> for MixStatus {
     fn from(e: SymphoniaError) -> Self {
         Self::Errored(e)
@@ -1,3 +1,4 @@
+use super::{preroll::PreRoll, resample::ResampleState};
 use crate::{
     constants::OPUS_PASSTHROUGH_STRIKE_LIMIT,
     driver::tasks::message::*,
@@ -5,7 +6,6 @@ use crate::{
     tracks::{ReadyState, SeekRequest},
 };
 use flume::Receiver;
-use rubato::FftFixedOut;
 use std::time::Instant;
 
 pub enum InputState {
@@ -46,7 +46,6 @@ impl From<Input> for InputState {
 }
 
 pub struct PreparingInfo {
-    #[allow(dead_code)]
     /// Time this request was fired.
     pub time: Instant,
     /// Used to handle seek requests fired while a track was being created (or a seek was in progress).
@@ -57,15 +56,31 @@ pub struct PreparingInfo {
 
 pub struct DecodeState {
     pub inner_pos: usize,
-    pub resampler: Option<(usize, FftFixedOut<f32>, Vec<Vec<f32>>)>,
+    pub resampler: Option<ResampleState>,
     pub passthrough: Passthrough,
     pub passthrough_violations: u8,
+    pub preroll: PreRoll,
+    /// Timestamp which an accurate seek must reach before audio is played.
+    ///
+    /// Earlier packets are decoded and discarded.
+    pub skip_to: Option<u64>,
 }
 
 impl DecodeState {
+    pub fn with_preroll(frames: usize) -> Self {
+        Self {
+            preroll: PreRoll::new(frames),
+            ..Default::default()
+        }
+    }
+
     pub fn reset(&mut self) {
         self.inner_pos = 0;
-        self.resampler = None;
+        self.skip_to = None;
+        self.preroll.flush();
+        if let Some(resampler) = self.resampler.take() {
+            resampler.recycle();
+        }
     }
 
     pub fn record_and_check_passthrough_strike_final(&mut self, fatal: bool) -> bool {
@@ -85,6 +100,8 @@ impl Default for DecodeState {
             resampler: None,
             passthrough: Passthrough::Inactive,
             passthrough_violations: 0,
+            preroll: PreRoll::default(),
+            skip_to: None,
         }
     }
 }
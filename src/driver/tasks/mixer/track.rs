@@ -7,13 +7,45 @@ use super::*;
 pub struct InternalTrack {
     pub(crate) playing: PlayMode,
     pub(crate) volume: f32,
+    /// In-progress transition of `volume` towards a new target.
+    pub(crate) volume_ramp: Option<VolumeRamp>,
+    pub(crate) pan: f32,
+    pub(crate) muted: bool,
+    pub(crate) priority: i8,
+    /// Bitrate automation points, sorted by position.
+    pub(crate) bitrate_automation: Vec<(Duration, Bitrate)>,
     pub(crate) input: InputState,
     pub(crate) mix_state: DecodeState,
     pub(crate) position: Duration,
     pub(crate) play_time: Duration,
     pub(crate) commands: Receiver<TrackCommand>,
     pub(crate) loops: LoopState,
+    pub(crate) loop_count: u64,
+    pub(crate) preparation_time: Option<Duration>,
     pub(crate) callbacks: Callbacks,
+    pub(crate) play_at: Option<Instant>,
+    /// Whether this track was paused by a driver-wide pause, and should be
+    /// resumed alongside it.
+    pub(crate) held: bool,
+    /// How this track's latest frame was mixed, and why it was decoded.
+    pub(crate) mix_path: Option<(MixPath, Option<DecodeReason>)>,
+}
+
+/// A linear change in a track's volume, advanced once per mixed frame.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct VolumeRamp {
+    start: f32,
+    target: f32,
+    elapsed: Duration,
+    length: Duration,
+}
+
+impl VolumeRamp {
+    fn volume(&self) -> f32 {
+        let progress = (self.elapsed.as_secs_f32() / self.length.as_secs_f32()).min(1.0);
+
+        self.start + (self.target - self.start) * progress
+    }
 }
 
 impl<'a> InternalTrack {
@@ -25,16 +57,30 @@ impl<'a> InternalTrack {
             track,
             receiver,
         } = val;
+
+        let mut bitrate_automation = track.bitrate_automation;
+        bitrate_automation.sort_by_key(|(position, _)| *position);
+
         let out = InternalTrack {
             playing: track.playing,
             volume: track.volume,
+            volume_ramp: None,
+            pan: track.pan,
+            muted: track.muted,
+            priority: track.priority,
+            bitrate_automation,
             input: InputState::from(track.input),
-            mix_state: DecodeState::default(),
+            mix_state: DecodeState::with_preroll(track.preroll),
             position: Duration::default(),
             play_time: Duration::default(),
             commands: receiver,
             loops: track.loops,
+            loop_count: 0,
+            preparation_time: None,
             callbacks: Callbacks::default(),
+            play_at: None,
+            held: false,
+            mix_path: None,
         };
 
         let state = out.state();
@@ -47,14 +93,53 @@ impl<'a> InternalTrack {
 
         TrackState {
             playing: self.playing.clone(),
-            volume: self.volume,
+            volume: self.volume_ramp.map_or(self.volume, |ramp| ramp.target),
+            pan: self.pan,
+            muted: self.muted,
             position: self.position,
             play_time: self.play_time,
             loops: self.loops,
+            loop_count: self.loop_count,
             ready,
+            preparation_time: self.preparation_time,
+            captured_at: Some(Instant::now()),
         }
     }
 
+    /// Returns how this track was last mixed, if its input is ready and has been mixed.
+    pub(crate) fn mix_info(&self) -> Option<MixInfo> {
+        let (path, reason) = self.mix_path?;
+        let InputState::Ready(parsed, _) = &self.input else {
+            return None;
+        };
+        let params = parsed.decoder.codec_params();
+
+        Some(MixInfo {
+            path,
+            reason,
+            codec: params.codec,
+            sample_rate: params.sample_rate,
+        })
+    }
+
+    /// Returns the volume at which this track is mixed, which is zero while muted.
+    pub(crate) fn mix_volume(&self) -> f32 {
+        if self.muted {
+            0.0
+        } else {
+            self.volume
+        }
+    }
+
+    /// Returns the bitrate programmed for this track's current position, if any.
+    pub(crate) fn automated_bitrate(&self) -> Option<Bitrate> {
+        let reached = self
+            .bitrate_automation
+            .partition_point(|(position, _)| *position <= self.position);
+
+        reached.checked_sub(1).map(|i| self.bitrate_automation[i].1)
+    }
+
     pub(crate) fn view(&'a mut self) -> View<'a> {
         let ready = self.input.ready_state();
 
@@ -62,10 +147,13 @@ impl<'a> InternalTrack {
             position: &self.position,
             play_time: &self.play_time,
             volume: &mut self.volume,
+            pan: &mut self.pan,
+            muted: &mut self.muted,
             meta: self.input.metadata(),
             ready,
             playing: &mut self.playing,
             loops: &mut self.loops,
+            loop_count: &self.loop_count,
         }
     }
 
@@ -83,13 +171,18 @@ impl<'a> InternalTrack {
         while let Ok(cmd) = self.commands.try_recv() {
             match cmd {
                 TrackCommand::Play => {
+                    self.play_at = None;
+                    self.held = false;
                     self.playing.change_to(PlayMode::Play);
                     drop(ic.events.send(EventMessage::ChangeState(
                         index,
                         TrackStateChange::Mode(self.playing.clone()),
                     )));
                 },
+                TrackCommand::PlayAt(instant) => self.play_at = Some(instant),
                 TrackCommand::Pause => {
+                    self.play_at = None;
+                    self.held = false;
                     self.playing.change_to(PlayMode::Pause);
                     drop(ic.events.send(EventMessage::ChangeState(
                         index,
@@ -105,11 +198,44 @@ impl<'a> InternalTrack {
                 },
                 TrackCommand::Volume(vol) => {
                     self.volume = vol;
+                    self.volume_ramp = None;
                     drop(ic.events.send(EventMessage::ChangeState(
                         index,
                         TrackStateChange::Volume(self.volume),
                     )));
                 },
+                TrackCommand::VolumeRamp(vol, ramp) => {
+                    if ramp.is_zero() {
+                        self.volume = vol;
+                        self.volume_ramp = None;
+                    } else {
+                        self.volume_ramp = Some(VolumeRamp {
+                            start: self.volume,
+                            target: vol,
+                            elapsed: Duration::ZERO,
+                            length: ramp,
+                        });
+                    }
+                    drop(ic.events.send(EventMessage::ChangeState(
+                        index,
+                        TrackStateChange::Volume(vol),
+                    )));
+                },
+                TrackCommand::Pan(pan) => {
+                    self.pan = pan;
+                    drop(ic.events.send(EventMessage::ChangeState(
+                        index,
+                        TrackStateChange::Pan(self.pan),
+                    )));
+                },
+                TrackCommand::Mute(muted) => {
+                    self.muted = muted;
+                    drop(ic.events.send(EventMessage::ChangeState(
+                        index,
+                        TrackStateChange::Mute(self.muted),
+                    )));
+                },
+                TrackCommand::Priority(priority) => self.priority = priority,
                 TrackCommand::Seek(req) => action.seek_point = Some(req),
                 TrackCommand::AddEvent(evt) => {
                     drop(ic.events.send(EventMessage::AddTrackEvent(index, evt)));
@@ -127,6 +253,9 @@ impl<'a> InternalTrack {
                 TrackCommand::Request(tx) => {
                     drop(tx.send(self.state()));
                 },
+                TrackCommand::RequestMixInfo(tx) => {
+                    _ = tx.send(self.mix_info());
+                },
                 TrackCommand::Loop(loops) => {
                     self.loops = loops;
                     drop(ic.events.send(EventMessage::ChangeState(
@@ -141,21 +270,55 @@ impl<'a> InternalTrack {
         action
     }
 
+    /// Starts this track if it is due to begin on the next frame sent
+    /// after `now`.
+    pub(crate) fn check_play_at(&mut self, index: usize, now: Instant, ic: &Interconnect) {
+        let Some(target) = self.play_at else {
+            return;
+        };
+
+        // The next frame leaves one timestep from now: start on whichever frame
+        // lies closest to the target.
+        if now + TIMESTEP_LENGTH + TIMESTEP_LENGTH / 2 >= target {
+            self.play_at = None;
+            self.playing.change_to(PlayMode::Play);
+            drop(ic.events.send(EventMessage::ChangeState(
+                index,
+                TrackStateChange::Mode(self.playing.clone()),
+            )));
+        }
+    }
+
     pub(crate) fn do_loop(&mut self) -> bool {
-        match self.loops {
+        let should_loop = match self.loops {
             LoopState::Infinite => true,
             LoopState::Finite(0) => false,
             LoopState::Finite(ref mut n) => {
                 *n -= 1;
                 true
             },
+        };
+
+        if should_loop {
+            self.loop_count += 1;
         }
+
+        should_loop
     }
 
     /// Steps playback location forward by one frame.
     pub(crate) fn step_frame(&mut self) {
         self.position += TIMESTEP_LENGTH;
         self.play_time += TIMESTEP_LENGTH;
+
+        if let Some(ramp) = &mut self.volume_ramp {
+            ramp.elapsed += TIMESTEP_LENGTH;
+            self.volume = ramp.volume();
+
+            if ramp.elapsed >= ramp.length {
+                self.volume_ramp = None;
+            }
+        }
     }
 
     pub(crate) fn should_check_input(&self) -> bool {
@@ -215,11 +378,13 @@ impl<'a> InternalTrack {
             },
             InputState::Preparing(info) => {
                 let queued_seek = info.queued_seek.take();
+                let preparation_time = info.time.elapsed();
 
                 let orig_out = match info.callback.try_recv() {
                     Ok(MixerInputResultMessage::Built(parsed, rec)) => {
                         *input = InputState::Ready(parsed, rec);
                         mix_state.reset();
+                        self.preparation_time = Some(preparation_time);
 
                         // possible TODO: set position to the true track position here?
                         // ISSUE: need to get next_packet to see its `ts`, but inner_pos==0
@@ -228,7 +393,7 @@ impl<'a> InternalTrack {
                         if !prevent_events {
                             drop(interconnect.events.send(EventMessage::ChangeState(
                                 id,
-                                TrackStateChange::Ready(ReadyState::Playable),
+                                TrackStateChange::Prepared(preparation_time),
                             )));
                         }
 
@@ -243,14 +408,23 @@ impl<'a> InternalTrack {
                     Ok(MixerInputResultMessage::Seek(parsed, rec, seek_res)) => {
                         match seek_res {
                             Ok(pos) => {
-                                if let Some(time_base) = parsed.decoder.codec_params().time_base {
-                                    // Update track's position to match the actual timestamp the
-                                    // seek landed at.
-                                    let new_time = time_base.calc_time(pos.actual_ts);
+                                let params = parsed.decoder.codec_params();
+                                if let Some(time_base) = params.time_base {
+                                    // Opus seeks land ahead of their target, which the mixer
+                                    // decodes up to without playing.
+                                    let skip_to = (params.codec == CODEC_TYPE_OPUS
+                                        && pos.required_ts > pos.actual_ts)
+                                        .then_some(pos.required_ts);
+
+                                    // Update track's position to match the timestamp playback
+                                    // will resume from.
+                                    let new_time =
+                                        time_base.calc_time(skip_to.unwrap_or(pos.actual_ts));
                                     let time_in_float = new_time.seconds as f64 + new_time.frac;
                                     self.position =
                                         std::time::Duration::from_secs_f64(time_in_float);
 
+                                    self.preparation_time = Some(preparation_time);
                                     self.callbacks.seeked(self.position);
                                     self.callbacks.playable();
 
@@ -262,7 +436,7 @@ impl<'a> InternalTrack {
 
                                         drop(interconnect.events.send(EventMessage::ChangeState(
                                             id,
-                                            TrackStateChange::Ready(ReadyState::Playable),
+                                            TrackStateChange::Prepared(preparation_time),
                                         )));
                                     }
 
@@ -270,6 +444,7 @@ impl<'a> InternalTrack {
                                     // (Symphonia decoder state reset in the thread pool during
                                     // the operation.)
                                     mix_state.reset();
+                                    mix_state.skip_to = skip_to;
                                     *input = InputState::Ready(parsed, rec);
 
                                     if let InputState::Ready(ref mut parsed, _) = input {
@@ -327,6 +502,9 @@ impl<'a> InternalTrack {
             return;
         }
 
+        // Pre-rolled audio belongs to the old position.
+        self.mix_state.preroll.flush();
+
         // might be a little topsy turvy: rethink me.
         let SeekRequest { time, callback } = request;
 
@@ -1,4 +1,8 @@
-use symphonia_core::{formats::SeekTo, units::Time};
+use std::time::Duration;
+use symphonia_core::{
+    formats::SeekTo,
+    units::{Time, TimeBase, TimeStamp},
+};
 
 // SeekTo lacks Copy and Clone... somehow.
 pub fn copy_seek_to(pos: &SeekTo) -> SeekTo {
@@ -8,6 +12,37 @@ pub fn copy_seek_to(pos: &SeekTo) -> SeekTo {
     }
 }
 
+/// Moves a seek target `preroll` earlier, returning the new target and the
+/// timestamp of the original target in `time_base`.
+pub fn preroll_seek_to(
+    pos: &SeekTo,
+    preroll: Duration,
+    time_base: TimeBase,
+) -> (SeekTo, TimeStamp) {
+    match *pos {
+        SeekTo::Time { time, track_id } => {
+            let secs = time.seconds as f64 + time.frac;
+            let time = Time::from((secs - preroll.as_secs_f64()).max(0.0));
+
+            (
+                SeekTo::Time { time, track_id },
+                time_base.calc_timestamp(Time::from(secs)),
+            )
+        },
+        SeekTo::TimeStamp { ts, track_id } => {
+            let preroll = time_base.calc_timestamp(Time::from(preroll.as_secs_f64()));
+
+            (
+                SeekTo::TimeStamp {
+                    ts: ts.saturating_sub(preroll),
+                    track_id,
+                },
+                ts,
+            )
+        },
+    }
+}
+
 pub fn seek_to_is_zero(pos: &SeekTo) -> bool {
     match *pos {
         SeekTo::Time { time, .. } =>
@@ -18,3 +53,34 @@ pub fn seek_to_is_zero(pos: &SeekTo) -> bool {
         SeekTo::TimeStamp { ts, .. } => ts == 0,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn preroll_moves_seek_earlier_and_keeps_target() {
+        let time_base = TimeBase::new(1, 48_000);
+        let preroll = Duration::from_millis(80);
+
+        let pos = SeekTo::Time {
+            time: Time::from(1.5),
+            track_id: None,
+        };
+        let (to, target) = preroll_seek_to(&pos, preroll, time_base);
+        assert_eq!(target, 72_000);
+        let SeekTo::Time { time, .. } = to else {
+            panic!("seek target changed kind");
+        };
+        // Allow for rounding in the float-based time conversion.
+        assert!((68_159..=68_160).contains(&time_base.calc_timestamp(time)));
+
+        let pos = SeekTo::TimeStamp {
+            ts: 1_000,
+            track_id: 0,
+        };
+        let (to, target) = preroll_seek_to(&pos, preroll, time_base);
+        assert_eq!(target, 1_000);
+        assert!(matches!(to, SeekTo::TimeStamp { ts: 0, .. }));
+    }
+}
@@ -1,21 +1,37 @@
 #![allow(missing_docs)]
 
+pub(crate) mod batch;
 pub(crate) mod disposal;
 pub mod error;
 mod events;
 pub mod message;
 pub mod mixer;
+pub(crate) mod supervise;
 #[cfg(feature = "receive")]
 pub(crate) mod udp_rx;
 pub(crate) mod ws;
 
-use std::time::Duration;
+use std::{sync::Arc, time::Duration};
 
-use super::connection::{error::Error as ConnectionError, Connection};
+use super::{
+    connection::{error::Error as ConnectionError, Connection},
+    CryptoMode,
+    GatewaySession,
+    VoiceGateway,
+};
 use crate::{
     events::{
-        context_data::{DisconnectKind, DisconnectReason},
+        context_data::{
+            DisconnectKind,
+            DisconnectReason,
+            DriverTask,
+            TaskRecovery,
+            TaskRestartData,
+            TeardownCause,
+            TeardownData,
+        },
         internal_data::{InternalConnect, InternalDisconnect},
+        CallContext,
         CoreContext,
     },
     Config,
@@ -23,26 +39,42 @@ use crate::{
 };
 use flume::{Receiver, Sender};
 use message::*;
-use tokio::{spawn, time::sleep as tsleep};
-use tracing::{debug, instrument, trace};
+use tokio::{
+    spawn,
+    time::{sleep as tsleep, timeout},
+};
+use tracing::{debug, instrument, trace, Instrument};
 
 pub(crate) fn start(config: Config, rx: Receiver<CoreMessage>, tx: Sender<CoreMessage>) {
-    spawn(async move {
-        trace!("Driver started.");
-        runner(config, rx, tx).await;
-        trace!("Driver finished.");
-    });
+    let span = config.call_span();
+    let spawner = config.spawner.clone();
+    drop(
+        spawner.spawn(
+            async move {
+                trace!("Driver started.");
+                Box::pin(runner(config, rx, tx)).await;
+                trace!("Driver finished.");
+            }
+            .instrument(span),
+        ),
+    );
 }
 
 fn start_internals(core: Sender<CoreMessage>, config: &Config) -> Interconnect {
     let (evt_tx, evt_rx) = flume::unbounded();
     let (mix_tx, mix_rx) = flume::unbounded();
-
-    spawn(async move {
-        trace!("Event processor started.");
-        events::runner(evt_rx).await;
-        trace!("Event processor finished.");
-    });
+    let call = CallContext::new(config.call_identifier.as_deref());
+
+    drop(
+        config.spawner.spawn(
+            async move {
+                trace!("Event processor started.");
+                events::runner(evt_rx, call).await;
+                trace!("Event processor finished.");
+            }
+            .in_current_span(),
+        ),
+    );
 
     let ic = Interconnect {
         core,
@@ -54,17 +86,167 @@ fn start_internals(core: Sender<CoreMessage>, config: &Config) -> Interconnect {
     ic
 }
 
+fn disconnect(
+    connection: &mut Option<Connection>,
+    teardown: &mut SessionTeardown,
+    interconnect: &Interconnect,
+    reason: DisconnectReason,
+) {
+    teardown.end(interconnect, reason.into());
+
+    let last_conn = connection.take();
+    drop(interconnect.mixer.send(MixerMessage::DropConn));
+    drop(interconnect.mixer.send(MixerMessage::RebuildEncoder));
+
+    if let Some(conn) = last_conn {
+        drop(
+            interconnect
+                .events
+                .send(EventMessage::FireCoreEvent(CoreContext::DriverDisconnect(
+                    InternalDisconnect {
+                        kind: DisconnectKind::Runtime,
+                        reason: Some(reason),
+                        info: conn.info.clone(),
+                    },
+                ))),
+        );
+    }
+}
+
+/// Tracks the voice session established by the driver, so that its final
+/// teardown is reported exactly once.
+#[derive(Default)]
+struct SessionTeardown {
+    live: Option<ConnectionInfo>,
+    cause: Option<TeardownCause>,
+}
+
+impl SessionTeardown {
+    /// Records why the live session may be about to end, if it cannot be re-established.
+    ///
+    /// The earliest cause is kept until the session recovers or ends.
+    fn note(&mut self, cause: TeardownCause) {
+        if self.live.is_some() {
+            self.cause.get_or_insert(cause);
+        }
+    }
+
+    /// Ends the live session, if any.
+    fn end(&mut self, interconnect: &Interconnect, cause: TeardownCause) {
+        self.cause = None;
+
+        if let Some(info) = self.live.take() {
+            drop(interconnect.events.send(EventMessage::FireCoreEvent(
+                CoreContext::DriverTeardown(TeardownData {
+                    cause,
+                    channel_id: info.channel_id,
+                    guild_id: info.guild_id,
+                    session_id: info.session_id,
+                }),
+            )));
+        }
+    }
+
+    /// Records that the live session is about to be replaced due to a channel move,
+    /// overriding any earlier cause.
+    fn moved(&mut self) {
+        if self.live.is_some() {
+            self.cause = Some(TeardownCause::Moved);
+        }
+    }
+
+    /// Ends the live session if it is being replaced by a connection to `info`.
+    fn replace(&mut self, interconnect: &Interconnect, info: &ConnectionInfo) {
+        if self.live.as_ref().is_some_and(|live| live != info) {
+            let cause = if self.cause == Some(TeardownCause::Moved) {
+                TeardownCause::Moved
+            } else {
+                TeardownCause::Requested
+            };
+            self.end(interconnect, cause);
+        }
+    }
+
+    /// Updates the live session after the driver's connection may have changed,
+    /// ending it once replaced, or once no connection remains and no reconnection
+    /// is pending.
+    fn update(
+        &mut self,
+        interconnect: &Interconnect,
+        connection: Option<&Connection>,
+        retrying: bool,
+    ) {
+        match connection {
+            Some(conn) => {
+                self.replace(interconnect, &conn.info);
+                self.live = Some(conn.info.clone());
+                self.cause = None;
+            },
+            None if !retrying => {
+                let cause = self
+                    .cause
+                    .unwrap_or(TeardownCause::ConnectionFailed(DisconnectReason::Internal));
+                self.end(interconnect, cause);
+            },
+            None => {},
+        }
+    }
+}
+
+/// Ends the session of any active external gateway.
+///
+/// The mixer's connection must be dropped separately.
+fn leave_gateway(gateway: &mut Option<Arc<dyn VoiceGateway>>) {
+    if let Some(gateway) = gateway.take() {
+        gateway.disconnect();
+    }
+}
+
+/// Hands a session established by an external gateway to the mixer.
+fn start_gateway(
+    session: GatewaySession,
+    interconnect: &Interconnect,
+) -> Result<(), ConnectionError> {
+    // Voice receive is specific to Discord: discard anything the mixer forwards
+    // to the (absent) UDP receive task, until it drops this connection.
+    #[cfg(feature = "receive")]
+    let udp_rx = {
+        let (tx, rx) = flume::unbounded::<UdpRxMessage>();
+        spawn(async move { while rx.recv_async().await.is_ok() {} });
+        tx
+    };
+
+    let mix_conn = MixerConnection {
+        crypto: PacketCrypto::External(session.protector),
+        #[cfg(feature = "receive")]
+        udp_rx,
+        udp_tx: session.socket,
+    };
+
+    interconnect.mixer.send(MixerMessage::Ws(None))?;
+    interconnect
+        .mixer
+        .send(MixerMessage::SetConn(mix_conn, session.ssrc, None))?;
+
+    Ok(())
+}
+
 #[instrument(skip(rx, tx))]
 async fn runner(mut config: Config, rx: Receiver<CoreMessage>, tx: Sender<CoreMessage>) {
     let mut next_config: Option<Config> = None;
     let mut connection: Option<Connection> = None;
+    let mut gateway: Option<Arc<dyn VoiceGateway>> = None;
     let mut interconnect = start_internals(tx, &config);
     let mut retrying = None;
     let mut attempt_idx = 0;
+    let mut shutdown_ack = None;
+    let mut teardown = SessionTeardown::default();
 
     while let Ok(msg) = rx.recv_async().await {
         match msg {
-            CoreMessage::ConnectWithResult(info, tx) => {
+            CoreMessage::ConnectWithResult(info, crypto, tx) => {
+                leave_gateway(&mut gateway);
+
                 config = if let Some(new_config) = next_config.take() {
                     drop(
                         interconnect
@@ -81,7 +263,8 @@ async fn runner(mut config: Config, rx: Receiver<CoreMessage>, tx: Sender<CoreMe
                     // active connection.
                     // This allows the gateway component to keep sending join requests independent
                     // of driver failures.
-                    connection = ConnectionRetryData::connect(tx, info, &mut attempt_idx)
+                    teardown.replace(&interconnect, &info);
+                    connection = ConnectionRetryData::connect(tx, info, crypto, &mut attempt_idx)
                         .attempt(&mut retrying, &interconnect, &config)
                         .await;
                 } else {
@@ -100,21 +283,124 @@ async fn runner(mut config: Config, rx: Receiver<CoreMessage>, tx: Sender<CoreMe
                     }
                 }
             },
-            CoreMessage::Disconnect => {
-                let last_conn = connection.take();
-                drop(interconnect.mixer.send(MixerMessage::DropConn));
-                drop(interconnect.mixer.send(MixerMessage::RebuildEncoder));
+            CoreMessage::ResumeSession(session, tx) => {
+                leave_gateway(&mut gateway);
 
-                if let Some(conn) = last_conn {
-                    drop(interconnect.events.send(EventMessage::FireCoreEvent(
-                        CoreContext::DriverDisconnect(InternalDisconnect {
-                            kind: DisconnectKind::Runtime,
-                            reason: Some(DisconnectReason::Requested),
-                            info: conn.info.clone(),
-                        }),
-                    )));
+                config = if let Some(new_config) = next_config.take() {
+                    drop(
+                        interconnect
+                            .mixer
+                            .send(MixerMessage::SetConfig(new_config.clone())),
+                    );
+                    new_config
+                } else {
+                    config
+                };
+
+                // Any pending retries belong to the session being replaced.
+                retrying = None;
+                attempt_idx = attempt_idx.wrapping_add(1);
+
+                match Connection::resume(session, &interconnect, &config, attempt_idx).await {
+                    Ok(conn) => {
+                        drop(interconnect.events.send(EventMessage::FireCoreEvent(
+                            CoreContext::DriverConnect(InternalConnect {
+                                info: conn.info.clone(),
+                                ssrc: conn.ssrc,
+                            }),
+                        )));
+                        connection = Some(conn);
+                        drop(tx.send(Ok(())));
+                    },
+                    Err(why) => {
+                        debug!("Failed to resume exported session: {}", why);
+                        drop(tx.send(Err(why)));
+                    },
                 }
             },
+            CoreMessage::ConnectGateway(new_gateway, tx) => {
+                config = if let Some(new_config) = next_config.take() {
+                    drop(
+                        interconnect
+                            .mixer
+                            .send(MixerMessage::SetConfig(new_config.clone())),
+                    );
+                    new_config
+                } else {
+                    config
+                };
+
+                disconnect(
+                    &mut connection,
+                    &mut teardown,
+                    &interconnect,
+                    DisconnectReason::Requested,
+                );
+                leave_gateway(&mut gateway);
+                retrying = None;
+                attempt_idx = attempt_idx.wrapping_add(1);
+
+                let attempt = new_gateway.connect(&config);
+                let session = if let Some(t) = config.driver_timeout {
+                    timeout(t, attempt)
+                        .await
+                        .map_err(ConnectionError::from)
+                        .and_then(|res| res.map_err(ConnectionError::Gateway))
+                } else {
+                    attempt.await.map_err(ConnectionError::Gateway)
+                };
+
+                match session.and_then(|session| start_gateway(session, &interconnect)) {
+                    Ok(()) => {
+                        gateway = Some(new_gateway);
+                        drop(tx.send(Ok(())));
+                    },
+                    Err(why) => {
+                        debug!("Failed to connect to external gateway: {}", why);
+                        drop(tx.send(Err(why)));
+                    },
+                }
+            },
+            CoreMessage::ExportSession(tx) =>
+                if let Some(conn) = &connection {
+                    // The mixer fills in the nonce and RTP counters of its next packet.
+                    drop(
+                        interconnect
+                            .mixer
+                            .send(MixerMessage::ExportSession(Box::new(conn.session()), tx)),
+                    );
+                } else {
+                    drop(tx.send(None));
+                },
+            CoreMessage::Disconnect => {
+                disconnect(
+                    &mut connection,
+                    &mut teardown,
+                    &interconnect,
+                    DisconnectReason::Requested,
+                );
+                leave_gateway(&mut gateway);
+            },
+            CoreMessage::AutoLeave(reason) => {
+                debug!("Automatically leaving call: {:?}", reason);
+                disconnect(&mut connection, &mut teardown, &interconnect, reason);
+                leave_gateway(&mut gateway);
+            },
+            CoreMessage::SetMemberPresent(user_id, present) => {
+                drop(
+                    interconnect
+                        .mixer
+                        .send(MixerMessage::SetMemberPresent(user_id, present)),
+                );
+            },
+            CoreMessage::ChannelMoved(data) => {
+                teardown.moved();
+                drop(
+                    interconnect
+                        .events
+                        .send(EventMessage::FireCoreEvent(CoreContext::ChannelMoved(data))),
+                );
+            },
             CoreMessage::SignalWsClosure(ws_idx, ws_info, mut reason) => {
                 // if idx is not a match, quash reason
                 // (i.e., prevent users from mistakenly trying to reconnect for an *old* dead conn).
@@ -132,6 +418,8 @@ async fn runner(mut config: Config, rx: Receiver<CoreMessage>, tx: Sender<CoreMe
                 // Conn may have been unset earlier (i.e., in a deliberate disconnect).
                 // If so, do not repropagate/repeat the disconnect event.
                 if conn.is_some() {
+                    teardown.note(reason.map_or(TeardownCause::WsClosed(None), Into::into));
+
                     drop(interconnect.events.send(EventMessage::FireCoreEvent(
                         CoreContext::DriverDisconnect(InternalDisconnect {
                             kind: DisconnectKind::Runtime,
@@ -153,7 +441,7 @@ async fn runner(mut config: Config, rx: Receiver<CoreMessage>, tx: Sender<CoreMe
             CoreMessage::SetConfig(mut new_config) => {
                 next_config = Some(new_config.clone());
 
-                new_config.make_safe(&config, connection.is_some());
+                new_config.make_safe(&config, connection.is_some() || gateway.is_some());
 
                 drop(interconnect.mixer.send(MixerMessage::SetConfig(new_config)));
             },
@@ -166,11 +454,59 @@ async fn runner(mut config: Config, rx: Receiver<CoreMessage>, tx: Sender<CoreMe
             CoreMessage::Mute(m) => {
                 drop(interconnect.mixer.send(MixerMessage::SetMute(m)));
             },
+            CoreMessage::PauseAll(p) => {
+                drop(interconnect.mixer.send(MixerMessage::SetPauseAll(p)));
+            },
+            CoreMessage::SetRtpExtensions(exts) => {
+                drop(
+                    interconnect
+                        .mixer
+                        .send(MixerMessage::SetRtpExtensions(exts)),
+                );
+            },
+            #[cfg(feature = "rtp-control")]
+            CoreMessage::SetRtpOverride(rtp) => {
+                drop(interconnect.mixer.send(MixerMessage::SetRtpOverride(rtp)));
+            },
+            #[cfg(feature = "rtp-control")]
+            CoreMessage::GetRtpState(tx) => {
+                drop(interconnect.mixer.send(MixerMessage::GetRtpState(tx)));
+            },
+            CoreMessage::GetTracks(tx) => {
+                drop(interconnect.mixer.send(MixerMessage::GetTracks(tx)));
+            },
+            CoreMessage::GetUdpSendStats(tx) => {
+                drop(interconnect.mixer.send(MixerMessage::GetUdpSendStats(tx)));
+            },
+            CoreMessage::GetCryptoMode(tx) => {
+                _ = tx.send(connection.as_ref().map(|conn| conn.crypto_mode));
+            },
+            CoreMessage::SetAnalysis(tap) => {
+                drop(interconnect.mixer.send(MixerMessage::SetAnalysis(tap)));
+            },
+            #[cfg(feature = "raw-gateway")]
+            CoreMessage::SendGatewayPayload(payload) =>
+                if let Some(conn) = &connection {
+                    drop(conn.ws.send(WsMessage::SendRaw(payload)));
+                } else {
+                    debug!("Dropping raw gateway payload: driver is not connected.");
+                },
+            #[cfg(feature = "receive")]
+            CoreMessage::DumpLast(user_id, duration, tx) => {
+                drop(
+                    interconnect
+                        .mixer
+                        .send(MixerMessage::DumpLast(user_id, duration, tx)),
+                );
+            },
             CoreMessage::Reconnect => {
                 if let Some(mut conn) = connection.take() {
+                    teardown.note(TeardownCause::WsClosed(None));
+
                     // try once: if interconnect, try again.
                     // if still issue, full connect.
                     let info = conn.info.clone();
+                    let crypto = conn.crypto_preference.clone();
 
                     let full_connect = match conn.reconnect(&config).await {
                         Ok(()) => {
@@ -178,7 +514,7 @@ async fn runner(mut config: Config, rx: Receiver<CoreMessage>, tx: Sender<CoreMe
                             false
                         },
                         Err(ConnectionError::InterconnectFailure(_)) => {
-                            interconnect.restart_volatile_internals();
+                            interconnect.restart_volatile_internals(&config);
 
                             match conn.reconnect(&config).await {
                                 Ok(()) => {
@@ -192,7 +528,7 @@ async fn runner(mut config: Config, rx: Receiver<CoreMessage>, tx: Sender<CoreMe
                     };
 
                     if full_connect {
-                        connection = ConnectionRetryData::reconnect(info, &mut attempt_idx)
+                        connection = ConnectionRetryData::reconnect(info, crypto, &mut attempt_idx)
                             .attempt(&mut retrying, &interconnect, &config)
                             .await;
                     } else if let Some(ref connection) = &connection {
@@ -205,23 +541,130 @@ async fn runner(mut config: Config, rx: Receiver<CoreMessage>, tx: Sender<CoreMe
                     }
                 }
             },
-            CoreMessage::FullReconnect =>
+            CoreMessage::Rebind =>
+                if let Some(conn) = connection.as_mut() {
+                    if let Err(why) = conn.rebind(&interconnect, &config).await {
+                        // No local address can reach the voice server, so the session
+                        // cannot be kept: fall back to a full reconnect.
+                        debug!("Failed to rebind voice UDP: {}", why);
+                        drop(
+                            interconnect
+                                .core
+                                .send(CoreMessage::FullReconnect(TeardownCause::UdpFailure)),
+                        );
+                    }
+                },
+            CoreMessage::FullReconnect(cause) =>
                 if let Some(conn) = connection.take() {
+                    teardown.note(cause);
+
                     let info = conn.info.clone();
+                    let crypto = conn.crypto_preference.clone();
 
-                    connection = ConnectionRetryData::reconnect(info, &mut attempt_idx)
+                    connection = ConnectionRetryData::reconnect(info, crypto, &mut attempt_idx)
                         .attempt(&mut retrying, &interconnect, &config)
                         .await;
                 },
             CoreMessage::RebuildInterconnect => {
-                interconnect.restart_volatile_internals();
+                interconnect.restart_volatile_internals(&config);
+            },
+            CoreMessage::TaskPanicked(task_idx, task, cause) => {
+                // Tasks from an older connection attempt are expected to be gone.
+                if task_idx != attempt_idx || connection.is_none() {
+                    continue;
+                }
+
+                let recovery = match task {
+                    // The websocket task owns the gateway stream, so cannot be
+                    // restarted within the session.
+                    DriverTask::Ws => TaskRecovery::FullReconnect,
+                    #[cfg(feature = "receive")]
+                    DriverTask::UdpRx => match connection
+                        .as_mut()
+                        .map(|conn| conn.restart_udp_rx(&interconnect, &config))
+                    {
+                        Some(Ok(())) => TaskRecovery::Restarted,
+                        Some(Err(why)) => {
+                            debug!("Failed to restart UDP receive task: {}", why);
+                            TaskRecovery::FullReconnect
+                        },
+                        None => TaskRecovery::FullReconnect,
+                    },
+                };
+
+                if recovery == TaskRecovery::FullReconnect {
+                    drop(
+                        interconnect
+                            .core
+                            .send(CoreMessage::FullReconnect(TeardownCause::TaskPanic(task))),
+                    );
+                }
+
+                drop(interconnect.events.send(EventMessage::FireCoreEvent(
+                    CoreContext::DriverTaskRestarted(TaskRestartData {
+                        task,
+                        cause,
+                        recovery,
+                    }),
+                )));
+            },
+            CoreMessage::Shutdown(flush, tx) => {
+                if let Some(max_wait) = flush {
+                    let (flush_tx, flush_rx) = flume::bounded(1);
+                    if interconnect
+                        .mixer
+                        .send(MixerMessage::Flush(flush_tx))
+                        .is_ok()
+                    {
+                        _ = timeout(max_wait, flush_rx.recv_async()).await;
+                    }
+                }
+
+                shutdown_ack = Some(tx);
+                break;
             },
             CoreMessage::Poison => break,
         }
+
+        teardown.update(&interconnect, connection.as_ref(), retrying.is_some());
     }
 
+    teardown.end(&interconnect, TeardownCause::Shutdown);
+
     trace!("Main thread exited");
-    interconnect.poison_all();
+
+    // Tear down in order: the mixer (halting audio and releasing its handles to the
+    // network tasks), then UDP receive and the websocket, and finally the event
+    // thread so that any events fired along the way are still delivered.
+    let (mixer_tx, mixer_rx) = flume::bounded(1);
+    if interconnect
+        .mixer
+        .send(MixerMessage::Shutdown(mixer_tx))
+        .is_ok()
+    {
+        _ = mixer_rx.recv_async().await;
+    }
+
+    if let Some(mut conn) = connection {
+        conn.shutdown().await;
+    }
+
+    leave_gateway(&mut gateway);
+
+    let (evt_tx, evt_rx) = flume::bounded(1);
+    if interconnect
+        .events
+        .send(EventMessage::Shutdown(evt_tx))
+        .is_ok()
+    {
+        _ = evt_rx.recv_async().await;
+    }
+
+    trace!("Driver tasks shut down");
+
+    if let Some(tx) = shutdown_ack {
+        _ = tx.send(());
+    }
 }
 
 struct ConnectionRetryData {
@@ -229,6 +672,7 @@ struct ConnectionRetryData {
     attempts: usize,
     last_wait: Option<Duration>,
     info: ConnectionInfo,
+    crypto: Option<Vec<CryptoMode>>,
     idx: usize,
 }
 
@@ -236,16 +680,26 @@ impl ConnectionRetryData {
     fn connect(
         tx: Sender<Result<(), ConnectionError>>,
         info: ConnectionInfo,
+        crypto: Option<Vec<CryptoMode>>,
         idx_src: &mut usize,
     ) -> Self {
-        Self::base(ConnectionFlavour::Connect(tx), info, idx_src)
+        Self::base(ConnectionFlavour::Connect(tx), info, crypto, idx_src)
     }
 
-    fn reconnect(info: ConnectionInfo, idx_src: &mut usize) -> Self {
-        Self::base(ConnectionFlavour::Reconnect, info, idx_src)
+    fn reconnect(
+        info: ConnectionInfo,
+        crypto: Option<Vec<CryptoMode>>,
+        idx_src: &mut usize,
+    ) -> Self {
+        Self::base(ConnectionFlavour::Reconnect, info, crypto, idx_src)
     }
 
-    fn base(flavour: ConnectionFlavour, info: ConnectionInfo, idx_src: &mut usize) -> Self {
+    fn base(
+        flavour: ConnectionFlavour,
+        info: ConnectionInfo,
+        crypto: Option<Vec<CryptoMode>>,
+        idx_src: &mut usize,
+    ) -> Self {
         *idx_src = idx_src.wrapping_add(1);
 
         Self {
@@ -253,6 +707,7 @@ impl ConnectionRetryData {
             attempts: 0,
             last_wait: None,
             info,
+            crypto,
             idx: *idx_src,
         }
     }
@@ -263,7 +718,8 @@ impl ConnectionRetryData {
         interconnect: &Interconnect,
         config: &Config,
     ) -> Option<Connection> {
-        match Connection::new(self.info.clone(), interconnect, config, self.idx).await {
+        let crypto = self.crypto.clone();
+        match Connection::new(self.info.clone(), crypto, interconnect, config, self.idx).await {
             Ok(connection) => {
                 match self.flavour {
                     ConnectionFlavour::Connect(tx) => {
@@ -295,10 +751,13 @@ impl ConnectionRetryData {
                     let remote_ic = interconnect.clone();
                     let idx = self.idx;
 
-                    spawn(async move {
-                        tsleep(t).await;
-                        drop(remote_ic.core.send(CoreMessage::RetryConnect(idx)));
-                    });
+                    spawn(
+                        async move {
+                            tsleep(t).await;
+                            drop(remote_ic.core.send(CoreMessage::RetryConnect(idx)));
+                        }
+                        .in_current_span(),
+                    );
 
                     self.attempts += 1;
                     self.last_wait = Some(t);
@@ -0,0 +1,77 @@
+use super::message::CoreMessage;
+use crate::{
+    driver::{Spawner, TaskHandle},
+    events::context_data::DriverTask,
+};
+use flume::Sender;
+use futures::FutureExt;
+use std::{any::Any, future::Future, panic::AssertUnwindSafe};
+use tracing::{error, Instrument};
+
+/// Spawns a network task for connection attempt `idx`, reporting any panic to the
+/// driver core so that the task can be restarted.
+pub(crate) fn spawn_supervised<F>(
+    task: DriverTask,
+    idx: usize,
+    core: Sender<CoreMessage>,
+    spawner: &Spawner,
+    fut: F,
+) -> TaskHandle
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    spawner.spawn(
+        async move {
+            if let Err(payload) = AssertUnwindSafe(fut).catch_unwind().await {
+                let cause = panic_message(&*payload);
+                error!("Driver task {:?} panicked: {}", task, cause);
+                drop(core.send(CoreMessage::TaskPanicked(idx, task, cause)));
+            }
+        }
+        .in_current_span(),
+    )
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        (*s).to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn panics_are_reported_to_core() {
+        let (core_tx, core_rx) = flume::unbounded();
+
+        let handle = spawn_supervised(DriverTask::Ws, 3, core_tx, &Spawner::default(), async {
+            panic!("heartbeat exploded");
+        });
+        handle.join().await;
+
+        match core_rx.recv_async().await {
+            Ok(CoreMessage::TaskPanicked(3, DriverTask::Ws, cause)) =>
+                assert_eq!(cause, "heartbeat exploded"),
+            _ => panic!("Expected a TaskPanicked message."),
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn clean_exits_are_not_reported() {
+        let (core_tx, core_rx) = flume::unbounded();
+
+        spawn_supervised(DriverTask::Ws, 0, core_tx, &Spawner::default(), async {})
+            .join()
+            .await;
+
+        assert!(core_rx.is_empty());
+    }
+}
@@ -0,0 +1,112 @@
+use super::*;
+use crate::driver::SampleRate;
+use nnnoiseless::DenoiseState;
+use std::fmt::{Debug, Formatter, Result as FmtResult};
+
+const FRAME_SIZE: usize = DenoiseState::FRAME_SIZE;
+
+/// RNNoise state for one SSRC, suppressing background noise in its decoded audio.
+pub struct Denoiser {
+    channels: Vec<Box<DenoiseState<'static>>>,
+    input: [f32; FRAME_SIZE],
+    output: [f32; FRAME_SIZE],
+}
+
+impl Denoiser {
+    /// Creates a denoiser if one is enabled and usable with the decoder's output format.
+    pub fn for_config(config: &Config) -> Option<Self> {
+        if !config.denoise || config.decode_sample_rate != SampleRate::Hz48000 {
+            return None;
+        }
+
+        Some(Self {
+            channels: (0..config.decode_channels.channels())
+                .map(|_| DenoiseState::new())
+                .collect(),
+            input: [0.0; FRAME_SIZE],
+            output: [0.0; FRAME_SIZE],
+        })
+    }
+
+    /// Denoises interleaved audio in place.
+    ///
+    /// Each channel is processed separately, in 10ms frames. Decoded packets always
+    /// hold whole frames, so no audio is carried over between calls.
+    pub fn process(&mut self, audio: &mut [i16]) {
+        let n_channels = self.channels.len();
+
+        for frame in audio.chunks_exact_mut(FRAME_SIZE * n_channels) {
+            for (i, state) in self.channels.iter_mut().enumerate() {
+                for (dest, sample) in self
+                    .input
+                    .iter_mut()
+                    .zip(frame.iter().skip(i).step_by(n_channels))
+                {
+                    *dest = f32::from(*sample);
+                }
+
+                state.process_frame(&mut self.output, &self.input);
+
+                for (sample, denoised) in frame
+                    .iter_mut()
+                    .skip(i)
+                    .step_by(n_channels)
+                    .zip(&self.output)
+                {
+                    *sample = denoised.clamp(f32::from(i16::MIN), f32::from(i16::MAX)) as i16;
+                }
+            }
+        }
+    }
+}
+
+impl Debug for Denoiser {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("Denoiser")
+            .field("channels", &self.channels.len())
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::driver::{Channels, DecodeMode};
+
+    #[test]
+    fn steady_noise_is_attenuated() {
+        let config = Config::default()
+            .decode_mode(DecodeMode::Decode)
+            .decode_channels(Channels::Mono)
+            .denoise(true);
+        let mut denoiser = Denoiser::for_config(&config).unwrap();
+
+        // Deterministic white noise, run for long enough that RNNoise settles.
+        let mut seed = 0x1234_5678u32;
+        let energy = |audio: &[i16]| audio.iter().map(|s| f64::from(*s).powi(2)).sum::<f64>();
+        let mut noise = || {
+            (0..MONO_FRAME_SIZE)
+                .map(|_| {
+                    seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    ((seed >> 16) as i16) / 8
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let mut before = 0.0;
+        let mut after = 0.0;
+        for i in 0..50 {
+            let mut audio = noise();
+            let raw = energy(&audio);
+            denoiser.process(&mut audio);
+
+            if i >= 25 {
+                before += raw;
+                after += energy(&audio);
+            }
+        }
+
+        assert!(after < before / 2.0);
+        assert!(Denoiser::for_config(&config.decode_sample_rate(SampleRate::Hz16000)).is_none());
+    }
+}
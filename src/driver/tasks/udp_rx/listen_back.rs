@@ -0,0 +1,85 @@
+use super::*;
+use std::collections::VecDeque;
+
+/// Circular buffer holding the most recent decoded audio from one SSRC.
+#[derive(Debug)]
+pub struct ListenBack {
+    samples: VecDeque<i16>,
+    capacity: usize,
+}
+
+impl ListenBack {
+    pub fn new(duration: Duration, config: &Config) -> Self {
+        Self {
+            samples: VecDeque::new(),
+            capacity: sample_count(duration, config),
+        }
+    }
+
+    /// Changes the length of retained audio, discarding the oldest samples if needed.
+    pub fn set_duration(&mut self, duration: Duration, config: &Config) {
+        self.capacity = sample_count(duration, config);
+        self.trim();
+    }
+
+    /// Appends one tick of received audio, or silence if none was decoded.
+    pub fn push(&mut self, audio: Option<&[i16]>, config: &Config) {
+        match audio {
+            Some(audio) => self.samples.extend(audio),
+            None => {
+                let len = self.samples.len() + sample_count(TIMESTEP_LENGTH, config);
+                self.samples.resize(len, 0);
+            },
+        }
+
+        self.trim();
+    }
+
+    /// Copies out up to the last `duration` of retained audio.
+    pub fn last(&self, duration: Duration, config: &Config) -> Vec<i16> {
+        let skip = self
+            .samples
+            .len()
+            .saturating_sub(sample_count(duration, config));
+
+        self.samples.iter().skip(skip).copied().collect()
+    }
+
+    fn trim(&mut self) {
+        let excess = self.samples.len().saturating_sub(self.capacity);
+        self.samples.drain(..excess);
+    }
+}
+
+/// Number of interleaved samples needed to hold `duration` of decoded audio.
+fn sample_count(duration: Duration, config: &Config) -> usize {
+    let frames = duration.as_secs_f64() * f64::from(config.decode_sample_rate.hz());
+
+    frames as usize * config.decode_channels.channels()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oldest_audio_is_discarded() {
+        let config = Config::default();
+        let tick = sample_count(TIMESTEP_LENGTH, &config);
+        let mut buffer = ListenBack::new(TIMESTEP_LENGTH * 2, &config);
+
+        for i in 1..=3 {
+            buffer.push(Some(&vec![i; tick]), &config);
+        }
+
+        let kept = buffer.last(Duration::from_secs(1), &config);
+        assert_eq!(kept.len(), 2 * tick);
+        assert!(kept[..tick].iter().all(|s| *s == 2));
+        assert!(kept[tick..].iter().all(|s| *s == 3));
+
+        buffer.push(None, &config);
+        let last = buffer.last(TIMESTEP_LENGTH, &config);
+        assert_eq!(last.len(), tick);
+        assert!(last.iter().all(|s| *s == 0));
+    }
+}
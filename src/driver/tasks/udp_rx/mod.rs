@@ -1,15 +1,37 @@
 mod decode_sizes;
+#[cfg(feature = "denoise")]
+mod denoise;
+mod listen_back;
 mod playout_buffer;
+mod recording;
 mod ssrc_state;
+mod transcription;
+
+#[cfg(feature = "denoise")]
+use self::denoise::*;
+use self::{
+    decode_sizes::*,
+    listen_back::*,
+    playout_buffer::*,
+    recording::*,
+    ssrc_state::*,
+    transcription::*,
+};
 
-use self::{decode_sizes::*, playout_buffer::*, ssrc_state::*};
-
-use super::message::*;
-use crate::driver::CryptoMode;
+use super::{
+    batch::{recv_batch, MAX_RECV_BATCH},
+    message::*,
+};
+use crate::driver::{CryptoMode, SampleFormat, SampleLayout};
 use crate::{
     constants::*,
     driver::crypto::Cipher,
-    events::{context_data::VoiceTick, internal_data::*, CoreContext},
+    events::{
+        context_data::{DecodedSamples, DecryptFailData, TalkSpurtData, TeardownCause, VoiceTick},
+        internal_data::*,
+        CoreContext,
+    },
+    model::id::UserId,
     Config,
 };
 use bytes::BytesMut;
@@ -33,11 +55,16 @@ type RtpSsrc = u32;
 
 struct UdpRx {
     cipher: Cipher,
+    /// Cached receive consent decisions, alongside the SSRC's owner when decided.
+    consent: HashMap<RtpSsrc, (Option<UserId>, bool)>,
     crypto_mode: CryptoMode,
     decoder_map: HashMap<RtpSsrc, SsrcState>,
+    decrypt_failures: HashMap<RtpSsrc, usize>,
     config: Config,
+    recording: Recording,
     rx: Receiver<UdpRxMessage>,
     ssrc_signalling: Arc<SsrcTracker>,
+    transcription: Transcription,
     udp_socket: UdpSocket,
 }
 
@@ -46,19 +73,24 @@ impl UdpRx {
     async fn run(&mut self, interconnect: &mut Interconnect) {
         let mut cleanup_time = Instant::now();
         let mut playout_time = Instant::now() + TIMESTEP_LENGTH;
-        let mut byte_dest: Option<BytesMut> = None;
+        let mut byte_dests: Vec<BytesMut> = vec![];
+        let mut lens = [0; MAX_RECV_BATCH];
 
         loop {
-            if byte_dest.is_none() {
-                byte_dest = Some(BytesMut::zeroed(VOICE_PACKET_MAX));
-            }
+            let batch_size = self.config.receive_batch_size.get().min(MAX_RECV_BATCH);
+            byte_dests.resize_with(batch_size, || BytesMut::zeroed(VOICE_PACKET_MAX));
 
             select! {
-                Ok((len, _addr)) = self.udp_socket.recv_from(byte_dest.as_mut().unwrap()) => {
-                    let mut pkt = byte_dest.take().unwrap();
-                    pkt.truncate(len);
-
-                    self.process_udp_message(interconnect, pkt);
+                Ok(()) = self.udp_socket.readable() => {
+                    // Spurious wakeups and socket errors are both non-fatal here.
+                    if let Ok(n) = recv_batch(&self.udp_socket, &mut byte_dests, &mut lens) {
+                        for (dest, len) in byte_dests.iter_mut().zip(&lens[..n]) {
+                            let mut pkt = std::mem::replace(dest, BytesMut::zeroed(VOICE_PACKET_MAX));
+                            pkt.truncate(*len);
+
+                            self.process_udp_message(interconnect, pkt);
+                        }
+                    }
                 },
                 msg = self.rx.recv_async() => {
                     match msg {
@@ -69,10 +101,32 @@ impl UdpRx {
                             let old_coder = (self.config.decode_channels, self.config.decode_sample_rate);
                             let new_coder = (c.decode_channels, c.decode_sample_rate);
                             self.config = c;
+                            self.consent.clear();
 
                             if old_coder != new_coder {
                                 self.decoder_map.values_mut().for_each(|v| v.reconfigure_decoder(&self.config));
                             }
+
+                            for (ssrc, state) in &mut self.decoder_map {
+                                state.set_playout_length(self.config.playout_buffer_length_for(*ssrc).get());
+                                state.set_late_packet_grace(self.config.late_packet_grace);
+                                state.set_listen_back(&self.config);
+                                #[cfg(feature = "denoise")]
+                                state.set_denoiser(&self.config);
+                            }
+                        },
+                        Ok(UdpRxMessage::DumpLast(user_id, duration, tx)) => {
+                            let audio = self
+                                .ssrc_signalling
+                                .user_ssrc_map
+                                .get(&user_id)
+                                .and_then(|ssrc| self.decoder_map.get(&*ssrc))
+                                .and_then(|state| state.listen_back(duration, &self.config));
+
+                            drop(tx.send(audio));
+                        },
+                        Ok(UdpRxMessage::ReplaceSocket(socket)) => {
+                            self.udp_socket = socket;
                         },
                         Err(flume::RecvError::Disconnected) => break,
                     }
@@ -81,28 +135,52 @@ impl UdpRx {
                     let mut tick = VoiceTick {
                         speaking: HashMap::new(),
                         silent: HashSet::new(),
+                        corrections: HashMap::new(),
                     };
+                    let mut spurts: Vec<TalkSpurtData> = vec![];
 
                     for (ssrc, state) in &mut self.decoder_map {
+                        let corrections = state.take_corrections(&self.config);
+                        if !corrections.is_empty() {
+                            tick.corrections.insert(*ssrc, corrections);
+                        }
+
                         match state.get_voice_tick(&self.config) {
                             Ok(Some(data)) => {
+                                state.record_listen_back(data.decoded_voice.as_deref(), &self.config);
                                 tick.speaking.insert(*ssrc, data);
                             },
                             Ok(None) => {
+                                state.record_listen_back(None, &self.config);
                                 if !state.disconnected {
                                     tick.silent.insert(*ssrc);
                                 }
+                                spurts.extend(state.end_talk_spurt(*ssrc));
                             },
                             Err(e) => {
                                 warn!("Decode error for SSRC {ssrc}: {e:?}");
+                                state.record_listen_back(None, &self.config);
                                 tick.silent.insert(*ssrc);
+
+                                let data = state.record_decode_error(*ssrc, &self.config);
+                                drop(interconnect.events.send(EventMessage::FireCoreEvent(
+                                    CoreContext::DecodeError(data),
+                                )));
                             },
                         }
                     }
 
                     playout_time += TIMESTEP_LENGTH;
 
+                    self.transcription.process_tick(&tick, &self.config, &self.ssrc_signalling, interconnect);
+                    self.recording.process_tick(&tick, &self.config, &self.ssrc_signalling);
+                    convert_samples(&mut tick, &self.config);
+
                     drop(interconnect.events.send(EventMessage::FireCoreEvent(CoreContext::VoiceTick(tick))));
+
+                    for spurt in spurts {
+                        drop(interconnect.events.send(EventMessage::FireCoreEvent(CoreContext::TalkSpurtEnd(spurt))));
+                    }
                 },
                 () = tokio::time::sleep_until(cleanup_time) => {
                     // periodic cleanup.
@@ -122,6 +200,7 @@ impl UdpRx {
                         };
 
                         _ = self.ssrc_signalling.disconnected_users.remove(&id);
+                        self.ssrc_signalling.remove_video(id);
                         if let Some((_, ssrc)) = self.ssrc_signalling.user_ssrc_map.remove(&id) {
                             if let Some(state) = self.decoder_map.get_mut(&ssrc) {
                                 // don't cleanup immediately: leave for later cycle
@@ -135,6 +214,8 @@ impl UdpRx {
 
                     // now remove all dead ssrcs.
                     self.decoder_map.retain(|_, v| v.prune_time > now);
+                    self.decrypt_failures.retain(|k, _| self.decoder_map.contains_key(k));
+                    self.consent.retain(|k, _| self.decoder_map.contains_key(k));
 
                     cleanup_time = now + Duration::from_secs(5);
                 },
@@ -159,6 +240,13 @@ impl UdpRx {
                     return;
                 }
 
+                let ssrc = rtp.get_ssrc();
+                if !self.has_consent(ssrc) {
+                    // Drop any audio held from before consent was withdrawn.
+                    self.decoder_map.remove(&ssrc);
+                    return;
+                }
+
                 let packet_data = if self.config.decode_mode.should_decrypt() {
                     let out = self
                         .cipher
@@ -169,6 +257,8 @@ impl UdpRx {
                         warn!("RTP decryption failed: {:?}", e);
                     }
 
+                    self.track_decrypt_result(interconnect, rtp.get_ssrc(), out.is_ok());
+
                     out.ok()
                 } else {
                     None
@@ -183,6 +273,20 @@ impl UdpRx {
                     )
                 });
 
+                // Video and rtx streams share the socket with audio: forward them to
+                // listeners, but never decode them as a (ghost) speaker.
+                if self.ssrc_signalling.is_video(rtp.get_ssrc()) {
+                    self.decoder_map.remove(&rtp.get_ssrc());
+                    drop(interconnect.events.send(EventMessage::FireCoreEvent(
+                        CoreContext::RtpPacket(InternalRtpPacket {
+                            packet: packet.freeze(),
+                            payload_offset: rtp_body_start,
+                            payload_end_pad: rtp_body_tail,
+                        }),
+                    )));
+                    return;
+                }
+
                 let entry = self
                     .decoder_map
                     .entry(rtp.get_ssrc())
@@ -197,7 +301,7 @@ impl UdpRx {
                     decrypted,
                 };
                 let packet = store_pkt.packet.clone();
-                entry.store_packet(store_pkt, &self.config);
+                entry.store_packet(store_pkt);
 
                 drop(interconnect.events.send(EventMessage::FireCoreEvent(
                     CoreContext::RtpPacket(InternalRtpPacket {
@@ -243,6 +347,71 @@ impl UdpRx {
             },
         }
     }
+
+    /// Returns whether packets from `ssrc` may be decrypted and decoded.
+    ///
+    /// Decisions are cached, and only revisited once the owner of `ssrc` becomes known.
+    fn has_consent(&mut self, ssrc: RtpSsrc) -> bool {
+        if self.config.receive_consent.is_none() && self.config.receive_consent_overrides.is_empty()
+        {
+            return true;
+        }
+
+        let cached = self.consent.get(&ssrc).copied();
+        if let Some((Some(_), allowed)) = cached {
+            return allowed;
+        }
+
+        let user_id = self
+            .ssrc_signalling
+            .user_ssrc_map
+            .iter()
+            .find(|entry| *entry.value() == ssrc)
+            .map(|entry| *entry.key());
+
+        match (cached, user_id) {
+            (Some((None, allowed)), None) => allowed,
+            _ => {
+                let allowed = self.config.receive_consent_for(ssrc, user_id);
+                self.consent.insert(ssrc, (user_id, allowed));
+                allowed
+            },
+        }
+    }
+
+    /// Updates the consecutive decryption failure count for `ssrc`, firing
+    /// events or requesting a reconnect according to the configured policy.
+    fn track_decrypt_result(&mut self, interconnect: &Interconnect, ssrc: RtpSsrc, success: bool) {
+        if success {
+            self.decrypt_failures.remove(&ssrc);
+            return;
+        }
+
+        let policy = self.config.decrypt_failure_policy;
+        let count = self.decrypt_failures.entry(ssrc).or_default();
+        *count += 1;
+        let count = *count;
+
+        if policy.should_notify() {
+            drop(
+                interconnect
+                    .events
+                    .send(EventMessage::FireCoreEvent(CoreContext::DecryptFail(
+                        DecryptFailData { ssrc, count },
+                    ))),
+            );
+        }
+
+        if policy.reconnect_after().is_some_and(|limit| count >= limit) {
+            warn!("{count} consecutive decryption failures for SSRC {ssrc}: reconnecting.");
+            self.decrypt_failures.clear();
+            drop(
+                interconnect
+                    .core
+                    .send(CoreMessage::FullReconnect(TeardownCause::CryptoFailure)),
+            );
+        }
+    }
 }
 
 #[instrument(skip(interconnect, rx, cipher))]
@@ -259,11 +428,15 @@ pub(crate) async fn runner(
 
     let mut state = UdpRx {
         cipher,
+        consent: HashMap::new(),
         crypto_mode,
         decoder_map: HashMap::new(),
+        decrypt_failures: HashMap::new(),
         config,
+        recording: Recording::default(),
         rx,
         ssrc_signalling,
+        transcription: Transcription::default(),
         udp_socket,
     };
 
@@ -276,3 +449,218 @@ pub(crate) async fn runner(
 fn rtp_valid(packet: &RtpPacket<'_>) -> bool {
     packet.get_version() == RTP_VERSION && packet.get_payload_type() == RTP_PROFILE_TYPE
 }
+
+/// Moves decoded audio into [`DecodedSamples`] if a non-default output format is configured.
+///
+/// This runs after all internal consumers (listen-back, transcription) have seen
+/// each tick's 16-bit interleaved audio.
+fn convert_samples(tick: &mut VoiceTick, config: &Config) {
+    let format = config.decode_sample_format;
+    let layout = config.decode_sample_layout;
+    if format == SampleFormat::I16 && layout == SampleLayout::Interleaved {
+        return;
+    }
+
+    let channels = config.decode_channels.channels();
+    let convert = |audio| DecodedSamples::from_interleaved(audio, channels, format, layout);
+
+    for data in tick.speaking.values_mut() {
+        data.samples = data.decoded_voice.take().map(convert);
+    }
+
+    for correction in tick.corrections.values_mut().flatten() {
+        correction.samples = Some(convert(std::mem::take(&mut correction.decoded_voice)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        driver::{DecryptFailurePolicy, ReceiveConsent},
+        events::context_data::VoiceData,
+    };
+    use discortp::rtp::MutableRtpPacket;
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn repeated_decrypt_failures_trigger_reconnect() {
+        let (core_tx, core_rx) = flume::unbounded();
+        let (event_tx, event_rx) = flume::unbounded();
+        let (mixer_tx, _mixer_rx) = flume::unbounded();
+        let (_udp_tx, udp_rx) = flume::unbounded();
+        let interconnect = Interconnect {
+            core: core_tx,
+            events: event_tx,
+            mixer: mixer_tx,
+        };
+
+        let crypto_mode = CryptoMode::Aes256Gcm;
+        let mut state = UdpRx {
+            cipher: crypto_mode.cipher_from_key(&[0u8; 32]).unwrap(),
+            consent: HashMap::new(),
+            crypto_mode,
+            decoder_map: HashMap::new(),
+            decrypt_failures: HashMap::new(),
+            config: Config::default()
+                .decrypt_failure_policy(DecryptFailurePolicy::Reconnect(2.try_into().unwrap())),
+            recording: Recording::default(),
+            rx: udp_rx,
+            ssrc_signalling: Arc::default(),
+            transcription: Transcription::default(),
+            udp_socket: UdpSocket::bind("127.0.0.1:0").await.unwrap(),
+        };
+
+        state.track_decrypt_result(&interconnect, 1, false);
+        state.track_decrypt_result(&interconnect, 1, true);
+        state.track_decrypt_result(&interconnect, 1, false);
+        assert!(core_rx.is_empty());
+
+        state.track_decrypt_result(&interconnect, 1, false);
+        assert!(matches!(
+            core_rx.try_recv(),
+            Ok(CoreMessage::FullReconnect(TeardownCause::CryptoFailure))
+        ));
+        assert!(state.decrypt_failures.is_empty());
+
+        let counts: Vec<_> = event_rx
+            .drain()
+            .filter_map(|msg| match msg {
+                EventMessage::FireCoreEvent(CoreContext::DecryptFail(data)) => Some(data.count),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(counts, vec![1, 1, 2]);
+    }
+
+    struct DenySsrc(u32);
+
+    impl ReceiveConsent for DenySsrc {
+        fn allow(&self, ssrc: u32, _user_id: Option<UserId>) -> bool {
+            ssrc != self.0
+        }
+    }
+
+    fn rtp_from(ssrc: u32) -> BytesMut {
+        let mut packet = BytesMut::zeroed(64);
+        let mut rtp = MutableRtpPacket::new(&mut packet[..]).unwrap();
+        rtp.set_version(RTP_VERSION);
+        rtp.set_payload_type(RTP_PROFILE_TYPE);
+        rtp.set_ssrc(ssrc);
+
+        packet
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn denied_ssrcs_are_dropped_before_decryption() {
+        let (core_tx, _core_rx) = flume::unbounded();
+        let (event_tx, event_rx) = flume::unbounded();
+        let (mixer_tx, _mixer_rx) = flume::unbounded();
+        let (_udp_tx, udp_rx) = flume::unbounded();
+        let interconnect = Interconnect {
+            core: core_tx,
+            events: event_tx,
+            mixer: mixer_tx,
+        };
+
+        let crypto_mode = CryptoMode::Aes256Gcm;
+        let mut state = UdpRx {
+            cipher: crypto_mode.cipher_from_key(&[0u8; 32]).unwrap(),
+            consent: HashMap::new(),
+            crypto_mode,
+            decoder_map: HashMap::new(),
+            decrypt_failures: HashMap::new(),
+            config: Config::default().receive_consent(Some(Arc::new(DenySsrc(1)))),
+            recording: Recording::default(),
+            rx: udp_rx,
+            ssrc_signalling: Arc::default(),
+            transcription: Transcription::default(),
+            udp_socket: UdpSocket::bind("127.0.0.1:0").await.unwrap(),
+        };
+
+        state.process_udp_message(&interconnect, rtp_from(1));
+        state.process_udp_message(&interconnect, rtp_from(2));
+
+        assert!(!state.decoder_map.contains_key(&1));
+        assert!(!state.decrypt_failures.contains_key(&1));
+        assert!(state.decoder_map.contains_key(&2));
+        assert_eq!(event_rx.drain().count(), 1);
+
+        // Per-user overrides take precedence once the owner is known.
+        state.ssrc_signalling.user_ssrc_map.insert(UserId(10), 1);
+        state
+            .config
+            .receive_consent_overrides
+            .insert(UserId(10), true);
+        state.consent.clear();
+
+        state.process_udp_message(&interconnect, rtp_from(1));
+        assert!(state.decoder_map.contains_key(&1));
+    }
+
+    #[test]
+    fn repeated_decode_errors_reset_decoder() {
+        let config = Config::default().decode_error_reset(Some(2.try_into().unwrap()));
+        let packet = [0u8; 16];
+        let rtp = RtpPacket::new(&packet).unwrap();
+        let mut state = SsrcState::new(&rtp, CryptoMode::Aes256Gcm, &config);
+
+        let first = state.record_decode_error(7, &config);
+        assert_eq!(
+            (first.count, first.total, first.decoder_reset),
+            (1, 1, false)
+        );
+
+        let second = state.record_decode_error(7, &config);
+        assert_eq!(
+            (second.count, second.total, second.decoder_reset),
+            (2, 2, true)
+        );
+
+        // The consecutive count restarts after a reset, but the total does not.
+        let third = state.record_decode_error(7, &config);
+        assert_eq!(
+            (third.count, third.total, third.decoder_reset),
+            (1, 3, false)
+        );
+    }
+
+    #[test]
+    fn non_default_formats_replace_decoded_voice() {
+        let voice = |audio| VoiceData {
+            packet: None,
+            decoded_voice: Some(audio),
+            samples: None,
+        };
+        let mut tick = VoiceTick {
+            speaking: [(1, voice(vec![1, 2, 3, 4])), (2, voice(vec![]))].into(),
+            silent: HashSet::new(),
+            corrections: HashMap::new(),
+        };
+
+        let config = Config::default().decode_sample_layout(SampleLayout::Planar);
+        convert_samples(&mut tick, &config);
+
+        assert_eq!(tick.speaking[&1].decoded_voice, None);
+        assert_eq!(
+            tick.speaking[&1].samples,
+            Some(DecodedSamples::I16(vec![1, 3, 2, 4]))
+        );
+        assert_eq!(tick.speaking[&2].samples, Some(DecodedSamples::I16(vec![])));
+
+        let mut tick = VoiceTick {
+            speaking: [(1, voice(vec![i16::MIN, 16384]))].into(),
+            silent: HashSet::new(),
+            corrections: HashMap::new(),
+        };
+
+        let config = Config::default().decode_sample_format(SampleFormat::F32);
+        convert_samples(&mut tick, &config);
+
+        assert_eq!(
+            tick.speaking[&1].samples,
+            Some(DecodedSamples::F32(vec![-1.0, 0.5]))
+        );
+    }
+}
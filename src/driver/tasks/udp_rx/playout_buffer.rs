@@ -16,6 +16,9 @@ pub struct StoredPacket {
 /// a user's packet buffer up to the required length ([`Config::playout_buffer_length`])
 /// ([`Self::Fill`]) and then emit packets on each tick ([`Self::Drain`]).
 ///
+/// If the required length grows at runtime, we revert to `Fill` to build up the extra
+/// delay. If it shrinks, the oldest held packets are skipped to cut the delay at once.
+///
 /// This gets a bit harder to reason about when users stop speaking. If a speech gap
 /// lasts longer than the playout buffer, then we can simply swap from `Drain` -> `Fill`.
 /// However, a genuine gap of `n` frames must lead to us reverting to `Fill` for `n` frames.
@@ -39,23 +42,58 @@ pub enum PacketLookup {
     Filling,
 }
 
+/// A packet which arrived shortly after its slot was played out as a loss.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LatePacket {
+    pub packet: StoredPacket,
+    /// Number of slots played out since this packet's own slot, including it.
+    pub ticks_late: u16,
+}
+
+/// Arrival statistics gathered since they were last taken.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ArrivalStats {
+    /// Packets discarded for arriving after their playout slot.
+    pub late: u32,
+    /// Late packets kept within the grace window, to be decoded as corrections.
+    pub corrected: u32,
+    /// Packets which arrived after a packet with a later sequence number.
+    pub reordered: u32,
+    /// Largest sequence number distance by which any packet was reordered.
+    pub max_reorder_depth: u16,
+}
+
 #[derive(Debug)]
 pub struct PlayoutBuffer {
     buffer: VecDeque<Option<StoredPacket>>,
+    length: usize,
     playout_mode: PlayoutMode,
     next_seq: RtpSequence,
     current_timestamp: Option<RtpTimestamp>,
     consecutive_store_fails: usize,
+    /// Number of slots after playout during which a missing packet may still be accepted.
+    grace: usize,
+    /// Sequence numbers of recently missed slots, oldest first.
+    missed: VecDeque<RtpSequence>,
+    late: Vec<LatePacket>,
+    highest_seq: Option<RtpSequence>,
+    stats: ArrivalStats,
 }
 
 impl PlayoutBuffer {
-    pub fn new(capacity: usize, next_seq: RtpSequence) -> Self {
+    pub fn new(length: usize, spike_length: usize, grace: usize, next_seq: RtpSequence) -> Self {
         Self {
-            buffer: VecDeque::with_capacity(capacity),
+            buffer: VecDeque::with_capacity(length + spike_length),
+            length,
             playout_mode: PlayoutMode::Fill,
             next_seq,
             current_timestamp: None,
             consecutive_store_fails: 0,
+            grace,
+            missed: VecDeque::new(),
+            late: vec![],
+            highest_seq: None,
+            stats: ArrivalStats::default(),
         }
     }
 
@@ -63,12 +101,12 @@ impl PlayoutBuffer {
     /// its sequence number, subject to maximums.
     ///
     /// An out of bounds packet must create any remaining `None`s
-    pub fn store_packet(&mut self, packet: StoredPacket, config: &Config) {
+    pub fn store_packet(&mut self, packet: StoredPacket) {
         let rtp = RtpPacket::new(&packet.packet)
             .expect("FATAL: earlier valid packet now invalid (store)");
 
         if self.current_timestamp.is_none() {
-            self.current_timestamp = Some(reset_timeout(&rtp, config));
+            self.current_timestamp = Some(reset_timeout(&rtp, self.length));
         }
 
         // compute index by taking wrapping difference between both seq numbers.
@@ -77,16 +115,29 @@ impl PlayoutBuffer {
         let pkt_seq = rtp.get_sequence().0;
         let desired_index = (pkt_seq - self.next_seq).0 as i16;
 
+        self.track_reordering(pkt_seq);
+
         // Similar concept to fetch_packet -- if there's a critical desync, and we're unwilling
         // to slot this packet into an empty/stuck buffer then behave as though this packet is the next
         // sequence number we're releasing.
-        let err_threshold = i16::try_from(config.playout_buffer_length.get() * 5).unwrap_or(32);
+        let err_threshold = i16::try_from(self.length * 5).unwrap_or(32);
         let handling_desync = (self.buffer.is_empty()
             || self.consecutive_store_fails >= (err_threshold as usize))
             && desired_index >= err_threshold;
 
         if desired_index < 0 {
-            trace!("Missed packet arrived late, discarding from playout.");
+            if let Some(i) = self.missed.iter().position(|seq| *seq == pkt_seq) {
+                trace!("Missed packet arrived late, keeping for correction.");
+                self.missed.remove(i);
+                self.late.push(LatePacket {
+                    packet,
+                    ticks_late: desired_index.unsigned_abs(),
+                });
+                self.stats.corrected = self.stats.corrected.saturating_add(1);
+            } else {
+                trace!("Missed packet arrived late, discarding from playout.");
+                self.stats.late = self.stats.late.saturating_add(1);
+            }
         } else if !handling_desync && desired_index >= 64 {
             trace!(
                 "Packet arrived beyond playout max length({}): wanted slot {desired_index}.\
@@ -113,12 +164,42 @@ impl PlayoutBuffer {
             self.consecutive_store_fails = 0;
         }
 
-        if self.buffer.len() >= config.playout_buffer_length.get() {
+        if self.buffer.len() >= self.length {
             self.playout_mode = PlayoutMode::Drain;
         }
     }
 
-    pub fn fetch_packet(&mut self, config: &Config) -> PacketLookup {
+    /// Changes the target playout delay (in packets) of a live buffer.
+    pub fn set_length(&mut self, length: usize) {
+        let old_length = self.length;
+        self.length = length;
+
+        if length > old_length {
+            // Playout time is frozen while filling, so this builds up the extra delay.
+            if self.buffer.len() < length {
+                self.playout_mode = PlayoutMode::Fill;
+            }
+        } else if length < old_length {
+            let excess = old_length - length;
+            if let Some(ts) = self.current_timestamp.as_mut() {
+                *ts += Wrapping((excess * MONO_FRAME_SIZE) as u32);
+            }
+
+            for _ in 0..excess.min(self.buffer.len()) {
+                self.buffer.pop_front();
+                self.next_seq += 1;
+            }
+
+            if self.buffer.is_empty() {
+                self.playout_mode = PlayoutMode::Fill;
+                self.current_timestamp = None;
+            } else if self.buffer.len() >= length {
+                self.playout_mode = PlayoutMode::Drain;
+            }
+        }
+    }
+
+    pub fn fetch_packet(&mut self) -> PacketLookup {
         if self.playout_mode == PlayoutMode::Fill {
             return PacketLookup::Filling;
         }
@@ -151,9 +232,8 @@ impl PlayoutBuffer {
                 // larger than it would take to go through multiple Fill/Drain cycles, then
                 // treat its TS as the next expected value to avoid jamming the buffer and losing
                 // later audio.
-                let skip_after =
-                    i32::try_from(config.playout_buffer_length.get() * 5 * MONO_FRAME_SIZE)
-                        .unwrap_or((AUDIO_FRAME_RATE * 2 * MONO_FRAME_SIZE) as i32);
+                let skip_after = i32::try_from(self.length * 5 * MONO_FRAME_SIZE)
+                    .unwrap_or((AUDIO_FRAME_RATE * 2 * MONO_FRAME_SIZE) as i32);
 
                 if ts_diff >= 0 {
                     // At or before expected timestamp.
@@ -173,6 +253,9 @@ impl PlayoutBuffer {
                 }
             },
             Some(None) => {
+                if self.grace > 0 {
+                    self.missed.push_back(self.next_seq);
+                }
                 self.next_seq += 1;
                 PacketLookup::MissedPacket
             },
@@ -188,16 +271,146 @@ impl PlayoutBuffer {
             *ts += &(MONO_FRAME_SIZE as u32);
         }
 
+        while let Some(seq) = self.missed.front() {
+            if usize::from((self.next_seq - seq).0) > self.grace {
+                self.missed.pop_front();
+            } else {
+                break;
+            }
+        }
+
         out
     }
 
+    /// Changes the number of slots after playout during which a missing packet
+    /// may still be accepted for correction.
+    pub fn set_grace(&mut self, grace: usize) {
+        self.grace = grace;
+        if grace == 0 {
+            self.missed.clear();
+        }
+    }
+
+    /// Returns all late packets accepted for correction since the last call.
+    pub fn take_late(&mut self) -> Vec<LatePacket> {
+        std::mem::take(&mut self.late)
+    }
+
+    fn track_reordering(&mut self, pkt_seq: RtpSequence) {
+        match self.highest_seq {
+            Some(highest) if ((pkt_seq - highest).0 as i16) < 0 => {
+                self.stats.reordered = self.stats.reordered.saturating_add(1);
+                self.stats.max_reorder_depth =
+                    self.stats.max_reorder_depth.max((highest - pkt_seq).0);
+            },
+            _ => self.highest_seq = Some(pkt_seq),
+        }
+    }
+
     pub fn next_seq(&self) -> RtpSequence {
         self.next_seq
     }
+
+    /// Returns the arrival statistics gathered since the last call, resetting them.
+    pub fn take_stats(&mut self) -> ArrivalStats {
+        std::mem::take(&mut self.stats)
+    }
 }
 
 #[inline]
-fn reset_timeout(packet: &RtpPacket<'_>, config: &Config) -> RtpTimestamp {
-    let t_shift = MONO_FRAME_SIZE * config.playout_buffer_length.get();
+fn reset_timeout(packet: &RtpPacket<'_>, length: usize) -> RtpTimestamp {
+    let t_shift = MONO_FRAME_SIZE * length;
     (packet.get_timestamp() + (t_shift as u32)).0
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use discortp::rtp::MutableRtpPacket;
+
+    fn packet(seq: u16) -> StoredPacket {
+        let mut bytes = vec![0u8; 16];
+        let mut rtp = MutableRtpPacket::new(&mut bytes[..]).unwrap();
+        rtp.set_version(RTP_VERSION);
+        rtp.set_sequence(seq.into());
+        rtp.set_timestamp((u32::from(seq) * MONO_FRAME_SIZE as u32).into());
+
+        StoredPacket {
+            packet: bytes.into(),
+            decrypted: true,
+        }
+    }
+
+    fn fetched_seq(lookup: PacketLookup) -> Option<u16> {
+        match lookup {
+            PacketLookup::Packet(p) => Some(RtpPacket::new(&p.packet).unwrap().get_sequence().0 .0),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn live_length_changes_refill_and_skip() {
+        let mut buffer = PlayoutBuffer::new(2, 0, 0, Wrapping(0));
+        for seq in 0..6 {
+            buffer.store_packet(packet(seq));
+        }
+        assert_eq!(fetched_seq(buffer.fetch_packet()), Some(0));
+
+        // Growing: rebuffer until the extra delay has been built up.
+        buffer.set_length(8);
+        assert_eq!(buffer.fetch_packet(), PacketLookup::Filling);
+        for seq in 6..9 {
+            buffer.store_packet(packet(seq));
+        }
+        assert_eq!(fetched_seq(buffer.fetch_packet()), Some(1));
+
+        // Shrinking: the oldest held packets are skipped.
+        buffer.set_length(4);
+        assert_eq!(fetched_seq(buffer.fetch_packet()), Some(6));
+    }
+
+    #[test]
+    fn packets_behind_playout_are_counted_late() {
+        let mut buffer = PlayoutBuffer::new(1, 0, 0, Wrapping(0));
+        buffer.store_packet(packet(0));
+        buffer.store_packet(packet(2));
+        assert_eq!(fetched_seq(buffer.fetch_packet()), Some(0));
+        assert_eq!(buffer.fetch_packet(), PacketLookup::MissedPacket);
+
+        buffer.store_packet(packet(1));
+        buffer.store_packet(packet(0));
+
+        let stats = buffer.take_stats();
+        assert_eq!(stats.late, 2);
+        assert_eq!(stats.reordered, 2);
+        assert_eq!(stats.max_reorder_depth, 2);
+        assert_eq!(buffer.take_stats(), ArrivalStats::default());
+    }
+
+    #[test]
+    fn missed_packets_within_grace_are_corrected() {
+        let mut buffer = PlayoutBuffer::new(1, 0, 2, Wrapping(0));
+        buffer.store_packet(packet(0));
+        buffer.store_packet(packet(4));
+        assert_eq!(fetched_seq(buffer.fetch_packet()), Some(0));
+        for _ in 1..4 {
+            assert_eq!(buffer.fetch_packet(), PacketLookup::MissedPacket);
+        }
+
+        // Slot 1 has fallen outside the grace window, and slot 3 arrives twice.
+        for seq in [1, 2, 3, 3] {
+            buffer.store_packet(packet(seq));
+        }
+
+        let late: Vec<_> = buffer
+            .take_late()
+            .into_iter()
+            .map(|late| late.ticks_late)
+            .collect();
+        assert_eq!(late, vec![2, 1]);
+
+        let stats = buffer.take_stats();
+        assert_eq!(stats.corrected, 2);
+        assert_eq!(stats.late, 2);
+    }
+}
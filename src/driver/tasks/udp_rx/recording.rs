@@ -0,0 +1,215 @@
+use super::*;
+use crate::{constants::SILENT_FRAME, driver::OggOpusWriter};
+use discortp::Packet;
+use std::io::Write;
+
+type Output = OggOpusWriter<Box<dyn Write + Send>>;
+
+/// Per-SSRC recordings of received Opus packets, written to the configured [`Recorder`].
+///
+/// [`Recorder`]: crate::driver::Recorder
+#[derive(Default)]
+pub struct Recording {
+    /// Open recordings, or `None` where one could not be opened or written to.
+    streams: HashMap<RtpSsrc, Option<Output>>,
+}
+
+impl Recording {
+    /// Appends one voice tick's packets to each user's recording, filling gaps with
+    /// silence and finishing the recordings of users who have left.
+    pub fn process_tick(
+        &mut self,
+        tick: &VoiceTick,
+        config: &Config,
+        ssrc_signalling: &SsrcTracker,
+    ) {
+        let Some(recorder) = config
+            .recorder
+            .as_ref()
+            .filter(|_| config.decode_mode.should_decrypt())
+        else {
+            self.finish_all();
+            return;
+        };
+
+        for (ssrc, data) in &tick.speaking {
+            // Recordings begin with a user's first packet.
+            if data.packet.is_none() && !self.streams.contains_key(ssrc) {
+                continue;
+            }
+
+            let stream = self.streams.entry(*ssrc).or_insert_with(|| {
+                let user_id = ssrc_signalling
+                    .user_ssrc_map
+                    .iter()
+                    .find(|entry| *entry.value() == *ssrc)
+                    .map(|entry| *entry.key());
+
+                recorder
+                    .open(*ssrc, user_id)
+                    .and_then(|out| OggOpusWriter::new(out, *ssrc))
+                    .map_err(|e| warn!("Failed to open recording for SSRC {ssrc}: {e}"))
+                    .ok()
+            });
+
+            let Some(out) = stream.as_mut() else {
+                continue;
+            };
+
+            let res = match &data.packet {
+                Some(packet) => {
+                    let rtp = packet.rtp();
+                    let payload = rtp.payload();
+                    let frame = payload
+                        .get(packet.payload_offset..packet.payload_end_pad)
+                        .and_then(|body| {
+                            let start = payload_start(body, rtp.get_extension() != 0).ok()?;
+                            Some(&body[start..])
+                        });
+
+                    out.write_packet(frame.unwrap_or(&SILENT_FRAME))
+                },
+                None => out.write_packet(&SILENT_FRAME),
+            };
+
+            if let Err(e) = res {
+                warn!("Failed to record SSRC {ssrc}: {e}");
+                *stream = None;
+            }
+        }
+
+        for ssrc in &tick.silent {
+            let Some(stream) = self.streams.get_mut(ssrc) else {
+                continue;
+            };
+
+            if let Some(Err(e)) = stream.as_mut().map(|out| out.write_packet(&SILENT_FRAME)) {
+                warn!("Failed to record SSRC {ssrc}: {e}");
+                *stream = None;
+            }
+        }
+
+        let gone: Vec<_> = self
+            .streams
+            .keys()
+            .filter(|ssrc| !(tick.speaking.contains_key(ssrc) || tick.silent.contains(ssrc)))
+            .copied()
+            .collect();
+
+        for ssrc in gone {
+            if let Some(Some(out)) = self.streams.remove(&ssrc) {
+                finish(ssrc, out);
+            }
+        }
+    }
+
+    fn finish_all(&mut self) {
+        for (ssrc, out) in self.streams.drain() {
+            if let Some(out) = out {
+                finish(ssrc, out);
+            }
+        }
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        self.finish_all();
+    }
+}
+
+fn finish(ssrc: RtpSsrc, out: Output) {
+    if let Err(e) = out.finish() {
+        warn!("Failed to finish recording for SSRC {ssrc}: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        driver::Recorder,
+        events::context_data::{RtpData, VoiceData},
+        model::id::UserId,
+    };
+    use discortp::rtp::MutableRtpPacket;
+    use parking_lot::Mutex;
+    use std::io::Result as IoResult;
+
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+            self.0.lock().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> IoResult<()> {
+            Ok(())
+        }
+    }
+
+    struct Capture(Shared);
+
+    impl Recorder for Capture {
+        fn open(&self, _ssrc: u32, _user_id: Option<UserId>) -> IoResult<Box<dyn Write + Send>> {
+            Ok(Box::new(self.0.clone()))
+        }
+    }
+
+    fn tick(speaking: Option<Option<RtpData>>, silent: bool) -> VoiceTick {
+        VoiceTick {
+            speaking: speaking
+                .map(|packet| {
+                    (
+                        1,
+                        VoiceData {
+                            packet,
+                            decoded_voice: None,
+                            samples: None,
+                        },
+                    )
+                })
+                .into_iter()
+                .collect(),
+            silent: silent.then_some(1).into_iter().collect(),
+            corrections: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn packets_and_gaps_are_recorded_until_user_leaves() {
+        let out = Shared::default();
+        let config = Config::default().recorder(Some(Arc::new(Capture(out.clone()))));
+        let ssrc_signalling = SsrcTracker::default();
+        let mut recording = Recording::default();
+
+        let frame = [0xfc, 1, 2];
+        let mut packet = BytesMut::zeroed(RtpPacket::minimum_packet_size() + frame.len());
+        let mut rtp = MutableRtpPacket::new(&mut packet[..]).unwrap();
+        rtp.set_version(RTP_VERSION);
+        rtp.set_ssrc(1);
+        rtp.set_payload(&frame);
+        let data = RtpData {
+            packet: packet.freeze(),
+            payload_offset: 0,
+            payload_end_pad: frame.len(),
+        };
+
+        recording.process_tick(&tick(Some(Some(data)), false), &config, &ssrc_signalling);
+        recording.process_tick(&tick(None, true), &config, &ssrc_signalling);
+        recording.process_tick(&tick(Some(None), false), &config, &ssrc_signalling);
+
+        // The user's state is gone: their recording is finished.
+        recording.process_tick(&tick(None, false), &config, &ssrc_signalling);
+
+        let out = out.0.lock();
+        let last_page = out.windows(4).rposition(|w| w == b"OggS").unwrap();
+        let page = &out[last_page..];
+        assert_eq!(page[5], 0x04);
+        assert_eq!(u64::from_le_bytes(page[6..14].try_into().unwrap()), 3 * 960);
+        assert_eq!(page[26], 3);
+        assert!(page.ends_with(&[0xfc, 1, 2, 0xf8, 0xff, 0xfe, 0xf8, 0xff, 0xfe]));
+    }
+}
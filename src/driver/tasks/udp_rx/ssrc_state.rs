@@ -5,7 +5,7 @@ use crate::{
         Channels,
         DecodeMode,
     },
-    events::context_data::{RtpData, VoiceData},
+    events::context_data::{DecodeErrorData, RtpData, TalkSpurtData, VoiceCorrection, VoiceData},
 };
 use audiopus::{
     coder::Decoder as OpusDecoder,
@@ -21,17 +21,40 @@ pub struct SsrcState {
     crypto_mode: CryptoMode,
     decoder: OpusDecoder,
     decode_size: PacketDecodeSize,
+    /// Decoder for late packets, kept apart so as not to disturb `decoder`'s state.
+    late_decoder: Option<OpusDecoder>,
     pub(crate) prune_time: Instant,
     pub(crate) disconnected: bool,
     channels: Channels,
+    spurt: Option<TalkSpurt>,
+    listen_back: Option<ListenBack>,
+    #[cfg(feature = "denoise")]
+    denoiser: Option<Denoiser>,
+    /// Number of consecutive ticks which have failed to decode.
+    decode_failures: usize,
+    /// Total number of ticks which have failed to decode.
+    decode_errors: u64,
+}
+
+/// Running statistics for the talk spurt currently being played out.
+#[derive(Debug, Default)]
+struct TalkSpurt {
+    frames: u32,
+    lost: u32,
+    concealed: u32,
 }
 
 impl SsrcState {
     pub fn new(pkt: &RtpPacket<'_>, crypto_mode: CryptoMode, config: &Config) -> Self {
-        let playout_capacity = config.playout_buffer_length.get() + config.playout_spike_length;
+        let playout_length = config.playout_buffer_length_for(pkt.get_ssrc()).get();
 
         Self {
-            playout_buffer: PlayoutBuffer::new(playout_capacity, pkt.get_sequence().0),
+            playout_buffer: PlayoutBuffer::new(
+                playout_length,
+                config.playout_spike_length,
+                config.late_packet_grace,
+                pkt.get_sequence().0,
+            ),
             crypto_mode,
             decoder: OpusDecoder::new(
                 config.decode_sample_rate.into(),
@@ -39,9 +62,16 @@ impl SsrcState {
             )
             .expect("Failed to create new Opus decoder for source."),
             decode_size: PacketDecodeSize::TwentyMillis,
+            late_decoder: None,
             prune_time: Instant::now() + config.decode_state_timeout,
             disconnected: false,
             channels: config.decode_channels,
+            spurt: None,
+            listen_back: config.listen_back.map(|d| ListenBack::new(d, config)),
+            #[cfg(feature = "denoise")]
+            denoiser: Denoiser::for_config(config),
+            decode_failures: 0,
+            decode_errors: 0,
         }
     }
 
@@ -51,11 +81,130 @@ impl SsrcState {
             config.decode_channels.into(),
         )
         .expect("Failed to create new Opus decoder for source.");
+        self.late_decoder = None;
         self.channels = config.decode_channels;
+
+        // Retained audio no longer matches the output format.
+        self.listen_back = config.listen_back.map(|d| ListenBack::new(d, config));
+
+        #[cfg(feature = "denoise")]
+        {
+            self.denoiser = Denoiser::for_config(config);
+        }
+    }
+
+    #[cfg(feature = "denoise")]
+    pub fn set_denoiser(&mut self, config: &Config) {
+        if config.denoise != self.denoiser.is_some() {
+            self.denoiser = Denoiser::for_config(config);
+        }
+    }
+
+    pub fn set_listen_back(&mut self, config: &Config) {
+        match (config.listen_back, &mut self.listen_back) {
+            (Some(d), Some(listen_back)) => listen_back.set_duration(d, config),
+            (d, listen_back) => *listen_back = d.map(|d| ListenBack::new(d, config)),
+        }
+    }
+
+    /// Retains one tick of this SSRC's decoded audio, if enabled.
+    pub fn record_listen_back(&mut self, audio: Option<&[i16]>, config: &Config) {
+        if let Some(listen_back) = &mut self.listen_back {
+            listen_back.push(audio, config);
+        }
+    }
+
+    /// Copies out up to the last `duration` of this SSRC's retained audio.
+    pub fn listen_back(&self, duration: Duration, config: &Config) -> Option<Vec<i16>> {
+        self.listen_back
+            .as_ref()
+            .map(|listen_back| listen_back.last(duration, config))
+    }
+
+    pub fn store_packet(&mut self, packet: StoredPacket) {
+        self.playout_buffer.store_packet(packet);
+    }
+
+    pub fn set_playout_length(&mut self, length: usize) {
+        self.playout_buffer.set_length(length);
     }
 
-    pub fn store_packet(&mut self, packet: StoredPacket, config: &Config) {
-        self.playout_buffer.store_packet(packet, config);
+    pub fn set_late_packet_grace(&mut self, grace: usize) {
+        self.playout_buffer.set_grace(grace);
+    }
+
+    /// Decodes any packets which arrived within the late packet grace window since
+    /// the last tick.
+    ///
+    /// Late packets are discarded unless [`DecodeMode::Decode`] is used.
+    pub fn take_corrections(&mut self, config: &Config) -> Vec<VoiceCorrection> {
+        let late = self.playout_buffer.take_late();
+        if late.is_empty() || config.decode_mode != DecodeMode::Decode {
+            return vec![];
+        }
+
+        late.into_iter()
+            .filter(|late| late.packet.decrypted)
+            .filter_map(|late| {
+                let rtp = RtpPacket::new(&late.packet.packet).unwrap();
+                let extensions = rtp.get_extension() != 0;
+
+                let payload = rtp.payload();
+                let payload_offset = self.crypto_mode.payload_prefix_len2();
+                let payload_end_pad = payload.len() - self.crypto_mode.payload_suffix_len();
+
+                let decoder = self.late_decoder.get_or_insert_with(|| {
+                    OpusDecoder::new(
+                        config.decode_sample_rate.into(),
+                        config.decode_channels.into(),
+                    )
+                    .expect("Failed to create new Opus decoder for source.")
+                });
+
+                let audio = decode_payload(
+                    decoder,
+                    &mut self.decode_size,
+                    self.channels,
+                    &payload[payload_offset..payload_end_pad],
+                    extensions,
+                );
+
+                match audio {
+                    Ok(decoded_voice) => Some(VoiceCorrection {
+                        packet: RtpData {
+                            packet: late.packet.packet.clone(),
+                            payload_offset,
+                            payload_end_pad,
+                        },
+                        ticks_late: late.ticks_late,
+                        decoded_voice,
+                        samples: None,
+                    }),
+                    Err(e) => {
+                        warn!("Failed to decode late packet: {:?}.", e);
+                        None
+                    },
+                }
+            })
+            .collect()
+    }
+
+    /// Closes the current talk spurt once the playout buffer has drained,
+    /// returning its statistics.
+    pub fn end_talk_spurt(&mut self, ssrc: u32) -> Option<TalkSpurtData> {
+        let spurt = self.spurt.take()?;
+        let stats = self.playout_buffer.take_stats();
+
+        Some(TalkSpurtData {
+            ssrc,
+            duration: TIMESTEP_LENGTH * spurt.frames,
+            lost: spurt.lost,
+            concealed: spurt.concealed,
+            late: stats.late,
+            corrected: stats.corrected,
+            reordered: stats.reordered,
+            max_reorder_depth: stats.max_reorder_depth,
+        })
     }
 
     pub fn refresh_timer(&mut self, state_timeout: Duration) {
@@ -68,7 +217,7 @@ impl SsrcState {
         // Acquire a packet from the playout buffer:
         // Update nexts, lasts...
         // different cases: null packet who we want to decode as a miss, and packet who we must ignore temporarily.
-        let m_pkt = self.playout_buffer.fetch_packet(config);
+        let m_pkt = self.playout_buffer.fetch_packet();
         let pkt = match m_pkt {
             PacketLookup::Packet(StoredPacket { packet, decrypted }) => Some((packet, decrypted)),
             PacketLookup::MissedPacket => None,
@@ -78,9 +227,12 @@ impl SsrcState {
         let mut out = VoiceData {
             packet: None,
             decoded_voice: None,
+            samples: None,
         };
 
         let should_decode = config.decode_mode == DecodeMode::Decode;
+        let mut lost = 0;
+        let mut concealed = 0;
 
         if let Some((packet, decrypted)) = pkt {
             let rtp = RtpPacket::new(&packet).unwrap();
@@ -95,13 +247,19 @@ impl SsrcState {
             // Normal losses should be handled by the below `else` branch.
             let new_seq: u16 = rtp.get_sequence().into();
             let missed_packets = new_seq.saturating_sub(self.playout_buffer.next_seq().0);
+            let will_decode = should_decode && decrypted;
+
+            lost += u32::from(missed_packets);
+            if will_decode {
+                concealed += u32::from(missed_packets);
+            }
 
             // TODO: maybe hand over audio and extension indices alongside packet?
             let (audio, _packet_size) = self.scan_and_decode(
                 &payload[payload_offset..payload_end_pad],
                 extensions,
                 missed_packets,
-                should_decode && decrypted,
+                will_decode,
             )?;
 
             let rtp_data = RtpData {
@@ -112,20 +270,64 @@ impl SsrcState {
 
             out.packet = Some(rtp_data);
             out.decoded_voice = audio;
-        } else if should_decode {
-            let mut audio = vec![0; self.decode_size.len()];
-            let dest_samples = (&mut audio[..])
-                .try_into()
-                .expect("Decode logic will cap decode buffer size at i32::MAX.");
-            let len = self.decoder.decode(None, dest_samples, false)?;
-            audio.truncate(2 * len);
-
-            out.decoded_voice = Some(audio);
+        } else {
+            lost += 1;
+
+            if should_decode {
+                concealed += 1;
+
+                let mut audio = vec![0; self.decode_size.len()];
+                let dest_samples = (&mut audio[..])
+                    .try_into()
+                    .expect("Decode logic will cap decode buffer size at i32::MAX.");
+                let len = self.decoder.decode(None, dest_samples, false)?;
+                audio.truncate(2 * len);
+
+                out.decoded_voice = Some(audio);
+            }
+        }
+
+        #[cfg(feature = "denoise")]
+        if let (Some(denoiser), Some(audio)) = (&mut self.denoiser, &mut out.decoded_voice) {
+            denoiser.process(audio);
         }
 
+        let spurt = self.spurt.get_or_insert_with(TalkSpurt::default);
+        spurt.frames += 1;
+        spurt.lost += lost;
+        spurt.concealed += concealed;
+
+        self.decode_failures = 0;
+
         Ok(Some(out))
     }
 
+    /// Counts a tick which failed to decode, resetting the decoder once
+    /// [`Config::decode_error_reset`] consecutive failures have been seen.
+    pub fn record_decode_error(&mut self, ssrc: u32, config: &Config) -> DecodeErrorData {
+        self.decode_failures += 1;
+        self.decode_errors += 1;
+
+        let count = self.decode_failures;
+        let decoder_reset = config
+            .decode_error_reset
+            .is_some_and(|limit| count >= limit.get());
+
+        if decoder_reset {
+            warn!("{count} consecutive decode failures for SSRC {ssrc}: resetting decoder.");
+            self.reconfigure_decoder(config);
+            self.decode_size = PacketDecodeSize::TwentyMillis;
+            self.decode_failures = 0;
+        }
+
+        DecodeErrorData {
+            ssrc,
+            count,
+            total: self.decode_errors,
+            decoder_reset,
+        }
+    }
+
     fn scan_and_decode(
         &mut self,
         data: &[u8],
@@ -133,16 +335,7 @@ impl SsrcState {
         missed_packets: u16,
         decode: bool,
     ) -> Result<(Option<Vec<i16>>, usize)> {
-        let start = if extension {
-            RtpExtensionPacket::new(data)
-                .map(|pkt| pkt.packet_size())
-                .ok_or_else(|| {
-                    error!("Extension packet indicated, but insufficient space.");
-                    Error::IllegalVoicePacket
-                })
-        } else {
-            Ok(0)
-        }?;
+        let start = payload_start(data, extension)?;
 
         let pkt = if decode {
             let mut out = vec![0; self.decode_size.len()];
@@ -157,43 +350,13 @@ impl SsrcState {
                 }
             }
 
-            // In general, we should expect 20 ms frames.
-            // However, Discord occasionally like to surprise us with something bigger.
-            // This is *sender-dependent behaviour*.
-            //
-            // This should scan up to find the "correct" size that a source is using,
-            // and then remember that.
-            loop {
-                let tried_audio_len = self.decoder.decode(
-                    Some(data[start..].try_into()?),
-                    (&mut out[..]).try_into()?,
-                    false,
-                );
-                match tried_audio_len {
-                    Ok(audio_len) => {
-                        // Decoding to stereo: audio_len refers to sample count irrespective of channel count.
-                        // => multiply by number of channels.
-                        out.truncate(self.channels.channels() * audio_len);
-
-                        break;
-                    },
-                    Err(OpusError::Opus(ErrorCode::BufferTooSmall)) => {
-                        if self.decode_size.can_bump_up() {
-                            self.decode_size = self.decode_size.bump_up();
-                            out = vec![0; self.decode_size.len()];
-                        } else {
-                            error!("Received packet larger than Opus standard maximum,");
-                            return Err(Error::IllegalVoicePacket);
-                        }
-                    },
-                    Err(e) => {
-                        error!("Failed to decode received packet: {:?}.", e);
-                        return Err(e.into());
-                    },
-                }
-            }
-
-            Some(out)
+            Some(decode_payload(
+                &mut self.decoder,
+                &mut self.decode_size,
+                self.channels,
+                data,
+                extension,
+            )?)
         } else {
             None
         };
@@ -201,3 +364,65 @@ impl SsrcState {
         Ok((pkt, data.len() - start))
     }
 }
+
+/// Returns the offset of the Opus frame within a packet's payload, skipping any
+/// RTP header extensions.
+pub fn payload_start(data: &[u8], extension: bool) -> Result<usize> {
+    if extension {
+        RtpExtensionPacket::new(data)
+            .map(|pkt| pkt.packet_size())
+            .ok_or_else(|| {
+                error!("Extension packet indicated, but insufficient space.");
+                Error::IllegalVoicePacket
+            })
+    } else {
+        Ok(0)
+    }
+}
+
+/// Decodes the Opus frame held in a packet's payload.
+fn decode_payload(
+    decoder: &mut OpusDecoder,
+    decode_size: &mut PacketDecodeSize,
+    channels: Channels,
+    data: &[u8],
+    extension: bool,
+) -> Result<Vec<i16>> {
+    let start = payload_start(data, extension)?;
+    let mut out = vec![0; decode_size.len()];
+
+    // In general, we should expect 20 ms frames.
+    // However, Discord occasionally like to surprise us with something bigger.
+    // This is *sender-dependent behaviour*.
+    //
+    // This should scan up to find the "correct" size that a source is using,
+    // and then remember that.
+    loop {
+        let tried_audio_len = decoder.decode(
+            Some(data[start..].try_into()?),
+            (&mut out[..]).try_into()?,
+            false,
+        );
+        match tried_audio_len {
+            Ok(audio_len) => {
+                // Decoding to stereo: audio_len refers to sample count irrespective of channel count.
+                // => multiply by number of channels.
+                out.truncate(channels.channels() * audio_len);
+
+                return Ok(out);
+            },
+            Err(OpusError::Opus(ErrorCode::BufferTooSmall)) =>
+                if decode_size.can_bump_up() {
+                    *decode_size = decode_size.bump_up();
+                    out = vec![0; decode_size.len()];
+                } else {
+                    error!("Received packet larger than Opus standard maximum,");
+                    return Err(Error::IllegalVoicePacket);
+                },
+            Err(e) => {
+                error!("Failed to decode received packet: {:?}.", e);
+                return Err(e.into());
+            },
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use super::*;
+use crate::driver::{SpeechSegment, TranscriptSink};
+use tokio::spawn;
+
+/// Number of silent ticks after which a user's speech segment is considered complete.
+const SEGMENT_HANGOVER_TICKS: u32 = 15;
+
+/// Maximum length of a single speech segment, in samples at 16kHz (30s).
+const SEGMENT_MAX_SAMPLES: usize = 30 * SpeechSegment::SAMPLE_RATE as usize;
+
+#[derive(Default)]
+pub struct Transcription {
+    segments: HashMap<RtpSsrc, PendingSegment>,
+    next_segment_id: u64,
+}
+
+struct PendingSegment {
+    segment_id: u64,
+    audio: Vec<i16>,
+    silent_ticks: u32,
+}
+
+impl Transcription {
+    /// Accumulates decoded audio from one voice tick, dispatching any finished
+    /// segments to the configured [`Transcriber`].
+    ///
+    /// [`Transcriber`]: crate::driver::Transcriber
+    pub fn process_tick(
+        &mut self,
+        tick: &VoiceTick,
+        config: &Config,
+        ssrc_signalling: &SsrcTracker,
+        interconnect: &Interconnect,
+    ) {
+        if config.transcriber.is_none() {
+            self.segments.clear();
+            return;
+        }
+
+        self.apply_corrections(tick, config);
+
+        for (ssrc, data) in &tick.speaking {
+            let Some(audio) = &data.decoded_voice else {
+                continue;
+            };
+
+            let segment = self.segments.entry(*ssrc).or_insert_with(|| {
+                let segment_id = self.next_segment_id;
+                self.next_segment_id += 1;
+
+                PendingSegment {
+                    segment_id,
+                    audio: Vec::new(),
+                    silent_ticks: 0,
+                }
+            });
+
+            segment.silent_ticks = 0;
+            append_resampled(&mut segment.audio, audio, config);
+        }
+
+        let mut finished = vec![];
+        for (ssrc, segment) in &mut self.segments {
+            if !tick.speaking.contains_key(ssrc) {
+                segment.silent_ticks += 1;
+            }
+
+            if segment.silent_ticks >= SEGMENT_HANGOVER_TICKS
+                || segment.audio.len() >= SEGMENT_MAX_SAMPLES
+            {
+                finished.push(*ssrc);
+            }
+        }
+
+        for ssrc in finished {
+            if let Some(segment) = self.segments.remove(&ssrc) {
+                dispatch(ssrc, segment, config, ssrc_signalling, interconnect);
+            }
+        }
+    }
+
+    /// Overwrites the concealment audio of recently lost packets in pending segments
+    /// with audio decoded from their late arrivals.
+    fn apply_corrections(&mut self, tick: &VoiceTick, config: &Config) {
+        for (ssrc, corrections) in &tick.corrections {
+            let Some(segment) = self.segments.get_mut(ssrc) else {
+                continue;
+            };
+
+            for correction in corrections {
+                let mut audio = vec![];
+                append_resampled(&mut audio, &correction.decoded_voice, config);
+
+                // Every tick appends one frame of equal length to the segment.
+                let start = segment
+                    .audio
+                    .len()
+                    .checked_sub(usize::from(correction.ticks_late) * audio.len());
+                if let Some(start) = start {
+                    segment.audio[start..start + audio.len()].copy_from_slice(&audio);
+                }
+            }
+        }
+    }
+}
+
+fn dispatch(
+    ssrc: RtpSsrc,
+    segment: PendingSegment,
+    config: &Config,
+    ssrc_signalling: &SsrcTracker,
+    interconnect: &Interconnect,
+) {
+    let Some(transcriber) = config.transcriber.clone() else {
+        return;
+    };
+
+    let user_id = ssrc_signalling
+        .user_ssrc_map
+        .iter()
+        .find(|entry| *entry.value() == ssrc)
+        .map(|entry| *entry.key());
+
+    let segment = SpeechSegment {
+        ssrc,
+        user_id,
+        segment_id: segment.segment_id,
+        audio: segment.audio,
+    };
+    let sink = TranscriptSink::new(interconnect.events.clone(), &segment);
+
+    spawn(async move {
+        if let Some(text) = transcriber.transcribe(segment, sink.clone()).await {
+            sink.fire(text, true);
+        }
+    });
+}
+
+/// Downmixes and resamples decoded audio to 16kHz mono, appending it to `out`.
+fn append_resampled(out: &mut Vec<i16>, audio: &[i16], config: &Config) {
+    let channels = config.decode_channels.channels();
+    let in_rate = config.decode_sample_rate.hz();
+    let out_rate = SpeechSegment::SAMPLE_RATE;
+
+    let mono = audio.chunks_exact(channels).map(|frame| {
+        let sum: i32 = frame.iter().map(|s| i32::from(*s)).sum();
+        #[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap)]
+        let avg = (sum / channels as i32) as i16;
+        avg
+    });
+
+    if in_rate == out_rate {
+        out.extend(mono);
+        return;
+    }
+
+    // Each packet holds a whole number of 16kHz samples at every decode rate, so
+    // linear interpolation within a packet suffices.
+    let mono: Vec<i16> = mono.collect();
+    let n_out = mono.len() * out_rate as usize / in_rate as usize;
+    let step = f64::from(in_rate) / f64::from(out_rate);
+
+    out.extend((0..n_out).map(|i| {
+        #[allow(clippy::cast_precision_loss)]
+        let pos = i as f64 * step;
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let idx = pos as usize;
+        let frac = pos - idx as f64;
+
+        let a = f64::from(mono[idx]);
+        let b = f64::from(*mono.get(idx + 1).unwrap_or(&mono[idx]));
+
+        #[allow(clippy::cast_possible_truncation)]
+        let sample = (a + (b - a) * frac) as i16;
+        sample
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        driver::{Channels, DecodeMode, SampleRate, Transcriber},
+        events::context_data::VoiceData,
+    };
+    use async_trait::async_trait;
+
+    struct Echo;
+
+    #[async_trait]
+    impl Transcriber for Echo {
+        async fn transcribe(&self, segment: SpeechSegment, sink: TranscriptSink) -> Option<String> {
+            sink.interim("...");
+            Some(segment.audio.len().to_string())
+        }
+    }
+
+    fn tick(speaking: Option<(u32, Vec<i16>)>) -> VoiceTick {
+        VoiceTick {
+            speaking: speaking
+                .into_iter()
+                .map(|(ssrc, audio)| {
+                    (
+                        ssrc,
+                        VoiceData {
+                            packet: None,
+                            decoded_voice: Some(audio),
+                            samples: None,
+                        },
+                    )
+                })
+                .collect(),
+            silent: HashSet::new(),
+            corrections: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn segments_end_after_silence() {
+        let (core_tx, _core_rx) = flume::unbounded();
+        let (event_tx, event_rx) = flume::unbounded();
+        let (mixer_tx, _mixer_rx) = flume::unbounded();
+        let interconnect = Interconnect {
+            core: core_tx,
+            events: event_tx,
+            mixer: mixer_tx,
+        };
+
+        let config = Config::default()
+            .decode_mode(DecodeMode::Decode)
+            .decode_channels(Channels::Stereo)
+            .decode_sample_rate(SampleRate::Hz48000)
+            .transcriber(Some(Arc::new(Echo)));
+        let ssrcs = SsrcTracker::default();
+        let mut state = Transcription::default();
+
+        // 10 ticks of 20ms, 48kHz stereo speech.
+        for _ in 0..10 {
+            state.process_tick(
+                &tick(Some((7, vec![0; 1920]))),
+                &config,
+                &ssrcs,
+                &interconnect,
+            );
+        }
+        for _ in 0..SEGMENT_HANGOVER_TICKS {
+            state.process_tick(&tick(None), &config, &ssrcs, &interconnect);
+        }
+
+        let mut results = vec![];
+        while results.len() < 2 {
+            if let Ok(EventMessage::FireCoreEvent(CoreContext::Transcription(t))) =
+                event_rx.recv_async().await
+            {
+                results.push(t);
+            }
+        }
+
+        assert!(!results[0].is_final);
+        assert!(results[1].is_final);
+        assert_eq!(results[1].ssrc, 7);
+        assert_eq!(results[1].text, (10 * 320).to_string());
+    }
+}
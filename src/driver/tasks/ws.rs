@@ -2,13 +2,14 @@ use super::message::*;
 use crate::{
     events::CoreContext,
     model::{
+        id::UserId,
         payload::{Heartbeat, Speaking},
         CloseCode as VoiceCloseCode,
         Event as GatewayEvent,
         FromPrimitive,
         SpeakingState,
     },
-    ws::{Error as WsError, WsStream},
+    ws::{Error as WsError, WsEvent, WsStream},
     ConnectionInfo,
 };
 use flume::Receiver;
@@ -121,14 +122,14 @@ impl AuxNetwork {
                             self.heartbeat_interval = Duration::from_secs_f64(keepalive / 1000.0);
                             next_heartbeat = self.next_heartbeat();
                         },
-                        Ok(WsMessage::Speaking(is_speaking)) => {
+                        Ok(WsMessage::Speaking(is_speaking, delay)) => {
                             if self.speaking.contains(SpeakingState::MICROPHONE) != is_speaking && !self.dont_send {
                                 self.speaking.set(SpeakingState::MICROPHONE, is_speaking);
                                 info!("Changing to {:?}", self.speaking);
 
                                 let ssu_status = self.ws_client
                                     .send_json(&GatewayEvent::from(Speaking {
-                                        delay: Some(0),
+                                        delay: Some(delay),
                                         speaking: self.speaking,
                                         ssrc: self.ssrc,
                                         user_id: None,
@@ -148,6 +149,25 @@ impl AuxNetwork {
                         Ok(WsMessage::Deliver(msg)) => {
                             self.process_ws(interconnect, msg);
                         },
+                        #[cfg(feature = "raw-gateway")]
+                        Ok(WsMessage::SendRaw(payload)) => {
+                            if !self.dont_send {
+                                ws_error |= match self.ws_client.send_raw_json(&payload).await {
+                                    Err(e) => {
+                                        should_reconnect = ws_error_is_not_final(&e);
+                                        ws_reason = Some((&e).into());
+                                        true
+                                    },
+                                    _ => false,
+                                };
+                            }
+                        },
+                        Ok(WsMessage::Close) => {
+                            if !self.dont_send {
+                                drop(self.ws_client.close().await);
+                            }
+                            break;
+                        },
                         Err(flume::RecvError::Disconnected) => {
                             break;
                         },
@@ -195,9 +215,55 @@ impl AuxNetwork {
         Ok(())
     }
 
-    fn process_ws(&mut self, interconnect: &Interconnect, value: GatewayEvent) {
+    fn process_ws(&mut self, interconnect: &Interconnect, value: WsEvent) {
+        let value = match value {
+            WsEvent::Gateway(value) => value,
+            WsEvent::ClientFlags(ev) => {
+                set_member_present(interconnect, ev.user_id, true);
+                drop(
+                    interconnect
+                        .events
+                        .send(EventMessage::FireCoreEvent(CoreContext::ClientFlags(ev))),
+                );
+                return;
+            },
+            WsEvent::ClientPlatform(ev) => {
+                set_member_present(interconnect, ev.user_id, true);
+                drop(
+                    interconnect
+                        .events
+                        .send(EventMessage::FireCoreEvent(CoreContext::ClientPlatform(ev))),
+                );
+                return;
+            },
+            WsEvent::ClientVideo(ev) => {
+                set_member_present(interconnect, ev.user_id, true);
+
+                #[cfg(feature = "receive")]
+                self.ssrc_signalling.set_client_video(&ev);
+
+                drop(
+                    interconnect
+                        .events
+                        .send(EventMessage::FireCoreEvent(CoreContext::ClientVideo(ev))),
+                );
+                return;
+            },
+            #[cfg(feature = "raw-gateway")]
+            WsEvent::Raw(payload) => {
+                drop(interconnect.events.send(EventMessage::FireCoreEvent(
+                    CoreContext::RawGatewayPayload(payload.into()),
+                )));
+                return;
+            },
+        };
+
         match value {
             GatewayEvent::Speaking(ev) => {
+                if let Some(user_id) = ev.user_id {
+                    set_member_present(interconnect, user_id, true);
+                }
+
                 #[cfg(feature = "receive")]
                 if let Some(user_id) = &ev.user_id {
                     self.ssrc_signalling.user_ssrc_map.insert(*user_id, ev.ssrc);
@@ -211,6 +277,8 @@ impl AuxNetwork {
                 debug!("Received discontinued ClientConnect: {:?}", ev);
             },
             GatewayEvent::ClientDisconnect(ev) => {
+                set_member_present(interconnect, ev.user_id, false);
+
                 #[cfg(feature = "receive")]
                 {
                     self.ssrc_signalling.disconnected_users.insert(ev.user_id);
@@ -246,6 +314,18 @@ pub(crate) async fn runner(mut interconnect: Interconnect, mut aux: AuxNetwork)
     trace!("WS thread finished.");
 }
 
+/// Informs the mixer whether another user is present in the call, for
+/// [`Config::alone_timeout`].
+///
+/// [`Config::alone_timeout`]: crate::Config::alone_timeout
+fn set_member_present(interconnect: &Interconnect, user_id: UserId, present: bool) {
+    drop(
+        interconnect
+            .mixer
+            .send(MixerMessage::SetMemberPresent(user_id, present)),
+    );
+}
+
 fn ws_error_is_not_final(err: &WsError) -> bool {
     match err {
         WsError::WsClosed(Some(frame)) => match frame.code {
@@ -72,16 +72,14 @@ impl Mixer {
 
         #[cfg(feature = "receive")]
         let fake_conn = MixerConnection {
-            cipher,
-            crypto_state,
+            crypto: PacketCrypto::Discord(cipher, crypto_state),
             udp_rx: udp_receiver_tx,
             udp_tx,
         };
 
         #[cfg(not(feature = "receive"))]
         let fake_conn = MixerConnection {
-            cipher,
-            crypto_state,
+            crypto: PacketCrypto::Discord(cipher, crypto_state),
             udp_tx,
         };
 
@@ -186,7 +184,7 @@ pub struct MockScheduler {
     pub stats: Arc<StatBlock>,
     pub local: Arc<LiveStatBlock>,
     pub rx: Receiver<SchedulerMessage>,
-    pub tx: Sender<(TaskId, ParkedMixer)>,
+    pub tx: Sender<WorkerMessage>,
     pub id: TaskId,
 }
 
@@ -202,6 +200,9 @@ impl MockScheduler {
         let cfg = crate::driver::SchedulerConfig {
             strategy: mode.unwrap_or_default(),
             move_expensive_tasks: true,
+            batch_sends: true,
+            offload_encryption: false,
+            pacing: None,
         };
 
         let core = Live::new(
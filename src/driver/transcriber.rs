@@ -0,0 +1,89 @@
+use crate::{
+    driver::tasks::message::EventMessage,
+    events::{context_data::TranscriptionData, CoreContext},
+    model::id::UserId,
+};
+use async_trait::async_trait;
+use flume::Sender;
+
+/// A speech-to-text backend, fed with audio from other users in a call.
+///
+/// When set via [`Config::transcriber`], the driver splits each user's received
+/// audio into segments of continuous speech (ending once that user has been
+/// silent for a short while), converts them to 16kHz mono PCM, and passes each
+/// segment to [`Self::transcribe`] in its own task. Results are delivered to
+/// handlers registered for [`CoreEvent::Transcription`].
+///
+/// This requires [`DecodeMode::Decode`] to be set, as transcription operates on
+/// decoded audio.
+///
+/// [`Config::transcriber`]: crate::Config::transcriber
+/// [`CoreEvent::Transcription`]: crate::events::CoreEvent::Transcription
+/// [`DecodeMode::Decode`]: crate::driver::DecodeMode::Decode
+#[async_trait]
+pub trait Transcriber: Send + Sync {
+    /// Transcribes one segment of speech.
+    ///
+    /// Interim hypotheses may be reported at any time via `sink`. The returned text,
+    /// if any, is fired as the final transcription of this segment.
+    async fn transcribe(&self, segment: SpeechSegment, sink: TranscriptSink) -> Option<String>;
+}
+
+/// A single segment of speech from one user, bounded by periods of silence.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct SpeechSegment {
+    /// RTP SSRC of the speaker.
+    pub ssrc: u32,
+    /// User ID of the speaker, if this has been announced by Discord.
+    pub user_id: Option<UserId>,
+    /// Identifier of this segment, unique within a driver.
+    pub segment_id: u64,
+    /// Mono, 16-bit PCM audio sampled at [`Self::SAMPLE_RATE`].
+    pub audio: Vec<i16>,
+}
+
+impl SpeechSegment {
+    /// Sample rate of all audio passed to a [`Transcriber`], in Hz.
+    pub const SAMPLE_RATE: u32 = 16_000;
+}
+
+/// Handle used by a [`Transcriber`] to report results for a [`SpeechSegment`].
+#[derive(Clone, Debug)]
+pub struct TranscriptSink {
+    events: Sender<EventMessage>,
+    ssrc: u32,
+    user_id: Option<UserId>,
+    segment_id: u64,
+}
+
+impl TranscriptSink {
+    pub(crate) fn new(events: Sender<EventMessage>, segment: &SpeechSegment) -> Self {
+        Self {
+            events,
+            ssrc: segment.ssrc,
+            user_id: segment.user_id,
+            segment_id: segment.segment_id,
+        }
+    }
+
+    /// Fires an interim (non-final) transcription of this segment.
+    pub fn interim(&self, text: impl Into<String>) {
+        self.fire(text.into(), false);
+    }
+
+    pub(crate) fn fire(&self, text: String, is_final: bool) {
+        drop(
+            self.events
+                .send(EventMessage::FireCoreEvent(CoreContext::Transcription(
+                    TranscriptionData {
+                        ssrc: self.ssrc,
+                        user_id: self.user_id,
+                        segment_id: self.segment_id,
+                        text,
+                        is_final,
+                    },
+                ))),
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::{
+    io::{Error as IoError, ErrorKind as IoErrorKind, Result as IoResult},
+    time::Duration,
+};
+
+/// Time waited before repeating a send which failed with a transient error.
+///
+/// This gives a full conntrack table or socket buffer a moment to clear, while
+/// keeping retries well within a single tick.
+pub(crate) const SEND_RETRY_DELAY: Duration = Duration::from_micros(250);
+
+/// Counts of voice and keepalive packets sent over the current voice connection,
+/// and of any failed sends.
+///
+/// Retrieved via [`Driver::udp_send_stats`].
+///
+/// [`Driver::udp_send_stats`]: super::Driver::udp_send_stats
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct UdpSendStats {
+    /// Number of packets successfully sent.
+    pub packets_sent: u64,
+    /// Number of sends which failed with an error expected to clear by itself,
+    /// such as a firewall or conntrack refusal, or an unreachable network during
+    /// a route change.
+    pub transient_errors: u64,
+    /// Number of sends which failed with any other error.
+    pub fatal_errors: u64,
+    /// Number of sends repeated within their tick after a transient error.
+    ///
+    /// See [`Config::udp_send_retries`].
+    ///
+    /// [`Config::udp_send_retries`]: crate::Config::udp_send_retries
+    pub retries: u64,
+    /// Number of packets in a row which have failed to send, after any retries.
+    pub consecutive_failures: u32,
+}
+
+impl UdpSendStats {
+    pub(crate) fn record_error(&mut self, e: &IoError) {
+        if is_transient(e) {
+            self.transient_errors += 1;
+        } else {
+            self.fatal_errors += 1;
+        }
+    }
+}
+
+/// Returns whether a failed UDP send is likely to succeed if repeated shortly.
+pub(crate) fn is_transient(e: &IoError) -> bool {
+    // EPERM is returned when a packet is dropped by a local firewall or a full
+    // conntrack table, rather than for any fault of the socket.
+    if matches!(
+        e.kind(),
+        IoErrorKind::PermissionDenied
+            | IoErrorKind::Interrupted
+            | IoErrorKind::ConnectionRefused
+            | IoErrorKind::TimedOut
+    ) {
+        return true;
+    }
+
+    #[cfg(unix)]
+    if let Some(code) = e.raw_os_error() {
+        return matches!(
+            code,
+            libc::ENETUNREACH
+                | libc::EHOSTUNREACH
+                | libc::ENETDOWN
+                | libc::EHOSTDOWN
+                | libc::ENOBUFS
+        );
+    }
+
+    false
+}
+
+/// Runs `send`, repeating it up to `retries` times while it fails with a
+/// transient error, waiting [`SEND_RETRY_DELAY`] before each repeat.
+pub(crate) fn send_with_retry(
+    retries: u8,
+    stats: &mut UdpSendStats,
+    mut send: impl FnMut() -> IoResult<usize>,
+) -> IoResult<usize> {
+    let mut attempts = 0;
+    loop {
+        match send() {
+            Err(e) if attempts < retries && is_transient(&e) => {
+                attempts += 1;
+                stats.retries += 1;
+                std::thread::sleep(SEND_RETRY_DELAY);
+            },
+            res => return res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn transient_errors_are_retried_up_to_limit() {
+        let mut stats = UdpSendStats::default();
+        let mut calls = 0;
+        let start = Instant::now();
+        let res = send_with_retry(2, &mut stats, || {
+            calls += 1;
+            Err(IoErrorKind::PermissionDenied.into())
+        });
+        assert!(res.is_err());
+        assert_eq!(calls, 3);
+        assert_eq!(stats.retries, 2);
+        assert!(start.elapsed() >= SEND_RETRY_DELAY * 2);
+
+        let mut calls = 0;
+        let res = send_with_retry(2, &mut stats, || {
+            calls += 1;
+            if calls == 1 {
+                Err(IoErrorKind::PermissionDenied.into())
+            } else {
+                Ok(1)
+            }
+        });
+        assert_eq!(res.unwrap(), 1);
+        assert_eq!(stats.retries, 3);
+    }
+
+    #[test]
+    fn fatal_errors_are_not_retried() {
+        let mut stats = UdpSendStats::default();
+        let mut calls = 0;
+        let res = send_with_retry(2, &mut stats, || {
+            calls += 1;
+            Err(IoErrorKind::InvalidInput.into())
+        });
+        assert!(res.is_err());
+        assert_eq!(calls, 1);
+        assert_eq!(stats.retries, 0);
+
+        stats.record_error(&IoErrorKind::InvalidInput.into());
+        stats.record_error(&IoErrorKind::PermissionDenied.into());
+        assert_eq!((stats.transient_errors, stats.fatal_errors), (1, 1));
+    }
+}
@@ -0,0 +1,169 @@
+use crate::constants::TIMESTEP_LENGTH;
+use parking_lot::{Condvar, Mutex};
+use std::{sync::Arc, time::Duration};
+
+/// A manually-driven clock for deterministic mixing, intended for testing.
+///
+/// When set via [`Config::virtual_clock`], a [`Driver`] no longer mixes and sends
+/// audio every 20ms according to wall-clock time. Instead, each call to
+/// [`advance`] releases enough audio ticks to cover the given [`Duration`], and
+/// the mixing thread blocks until more time is made available. This makes
+/// event, queue, and mixer behaviour reproducible in CI regardless of machine load.
+///
+/// Every driver attached to a clock mixes exactly one frame for each tick released
+/// since the clock was created, in order. A single clock may be shared between several
+/// drivers, which will all observe the same ticks. Sub-tick durations are carried over
+/// into later calls to `advance`.
+///
+/// Ticks released while a driver is idle (i.e., has no live tracks or connection) are
+/// consumed as soon as it next becomes active. Mixing threads only observe driver removal
+/// or new commands after the next tick, so drivers using a virtual clock should be given
+/// their own [`Scheduler`] to avoid blocking unrelated calls.
+///
+/// [`Config::virtual_clock`]: crate::Config::virtual_clock
+/// [`Driver`]: crate::driver::Driver
+/// [`advance`]: VirtualClock::advance
+/// [`Scheduler`]: crate::driver::Scheduler
+#[derive(Clone, Debug, Default)]
+pub struct VirtualClock {
+    inner: Arc<InnerClock>,
+}
+
+#[derive(Debug, Default)]
+struct InnerClock {
+    state: Mutex<ClockState>,
+    cond: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct ClockState {
+    ticks: u64,
+    carry: Duration,
+}
+
+impl VirtualClock {
+    /// Creates a new clock, starting at time zero.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves time forward by `time`, allowing any attached drivers to mix
+    /// and send the corresponding number of 20ms audio frames.
+    pub fn advance(&self, time: Duration) {
+        let mut state = self.inner.state.lock();
+        let total = state.carry + time;
+        let step = TIMESTEP_LENGTH.as_nanos();
+
+        let n_ticks = u64::try_from(total.as_nanos() / step).unwrap_or(u64::MAX);
+        #[allow(clippy::cast_possible_truncation)]
+        let carry_nanos = (total.as_nanos() % step) as u64;
+        state.carry = Duration::from_nanos(carry_nanos);
+
+        Self::release(&self.inner, &mut state, n_ticks);
+    }
+
+    /// Moves time forward by exactly `n_ticks` 20ms audio frames.
+    pub fn advance_ticks(&self, n_ticks: u64) {
+        let mut state = self.inner.state.lock();
+        Self::release(&self.inner, &mut state, n_ticks);
+    }
+
+    /// Returns the amount of virtual time which has been released so far,
+    /// excluding any carried sub-tick remainder.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        let ticks = self.inner.state.lock().ticks;
+        TIMESTEP_LENGTH * u32::try_from(ticks).unwrap_or(u32::MAX)
+    }
+
+    /// Returns the number of 20ms ticks which have been released so far.
+    #[must_use]
+    pub fn ticks(&self) -> u64 {
+        self.inner.state.lock().ticks
+    }
+
+    fn release(inner: &InnerClock, state: &mut ClockState, n_ticks: u64) {
+        if n_ticks > 0 {
+            state.ticks = state.ticks.saturating_add(n_ticks);
+            inner.cond.notify_all();
+        }
+    }
+
+    /// Blocks the calling (mixing) thread until the tick after `last_seen` has been released,
+    /// returning its index.
+    pub(crate) fn wait_tick(&self, last_seen: u64) -> u64 {
+        let mut state = self.inner.state.lock();
+
+        while state.ticks <= last_seen {
+            self.inner.cond.wait(&mut state);
+        }
+
+        last_seen + 1
+    }
+
+    #[must_use]
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::test_data::FILE_WAV_TARGET,
+        driver::{Driver, OutputMode, Scheduler, SchedulerConfig, SchedulerMode},
+        input::File,
+        tracks::Track,
+        Config,
+    };
+
+    #[test]
+    fn advance_carries_partial_ticks() {
+        let clock = VirtualClock::new();
+
+        clock.advance(Duration::from_millis(30));
+        assert_eq!(clock.ticks(), 1);
+        clock.advance(Duration::from_millis(10));
+        assert_eq!(clock.ticks(), 2);
+        clock.advance(Duration::from_millis(5));
+        assert_eq!(clock.ticks(), 2);
+        assert_eq!(clock.elapsed(), Duration::from_millis(40));
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn driver_mixes_only_when_advanced() {
+        let clock = VirtualClock::new();
+        let (pkt_tx, pkt_rx) = flume::unbounded();
+
+        let config = Config::default()
+            .virtual_clock(Some(clock.clone()))
+            .scheduler(Scheduler::new(SchedulerConfig {
+                strategy: SchedulerMode::MaxPerThread(1.try_into().unwrap()),
+                move_expensive_tasks: true,
+                batch_sends: true,
+                offload_encryption: false,
+                pacing: None,
+            }))
+            .override_connection(Some(OutputMode::Raw(pkt_tx)));
+        let mut driver = Driver::new(config);
+
+        let _handle = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+
+        clock.advance(Duration::from_millis(100));
+        for _ in 0..5 {
+            pkt_rx.recv_async().await.unwrap();
+        }
+
+        // The mixer blocks before sending until the next tick is released, so
+        // exactly one packet is sent per tick.
+        assert!(pkt_rx.is_empty());
+
+        clock.advance_ticks(1);
+        pkt_rx.recv_async().await.unwrap();
+        assert!(pkt_rx.is_empty());
+        assert_eq!(clock.ticks(), 6);
+    }
+}
@@ -1,5 +1,7 @@
 //! Driver and gateway error handling.
 
+#[cfg(feature = "gateway")]
+use crate::id::ChannelId;
 #[cfg(feature = "serenity")]
 use futures::channel::mpsc::TrySendError;
 #[cfg(not(feature = "simd-json"))]
@@ -40,6 +42,27 @@ pub enum JoinError {
     ///
     /// [the `Call`'s configuration]: crate::Config
     TimedOut,
+    /// Discord placed this bot in another channel before the join completed,
+    /// typically because it was moved by an admin or another session joined elsewhere.
+    ///
+    /// *Users should `leave` the server on the gateway before
+    /// re-attempting connection.*
+    Moved {
+        /// The channel this bot was asked to join.
+        requested: ChannelId,
+        /// The channel Discord reports this bot is now in.
+        actual: ChannelId,
+    },
+    /// Discord removed this bot from voice before the join completed, e.g., if it
+    /// was disconnected by an admin.
+    Disconnected,
+    /// Another session using this bot account claimed its voice state in this guild
+    /// before the join completed.
+    ///
+    /// See [`Config::rejoin_on_conflict`] to reclaim the voice state automatically.
+    ///
+    /// [`Config::rejoin_on_conflict`]: crate::Config::rejoin_on_conflict
+    SessionConflict,
     #[cfg(feature = "driver")]
     /// The driver failed to establish a voice connection.
     ///
@@ -63,7 +86,20 @@ impl JoinError {
     /// Failure to `leave` before rejoining may cause further
     /// timeouts.
     pub fn should_leave_server(&self) -> bool {
-        matches!(self, JoinError::TimedOut)
+        matches!(self, JoinError::TimedOut | JoinError::Moved { .. })
+    }
+
+    /// Indicates whether this failure was caused by a conflicting voice state
+    /// reported by Discord while joining.
+    ///
+    /// See [`Config::rejoin_on_conflict`].
+    ///
+    /// [`Config::rejoin_on_conflict`]: crate::Config::rejoin_on_conflict
+    pub fn is_conflict(&self) -> bool {
+        matches!(
+            self,
+            JoinError::Moved { .. } | JoinError::Disconnected | JoinError::SessionConflict
+        )
     }
 
     #[cfg(feature = "driver")]
@@ -88,6 +124,11 @@ impl fmt::Display for JoinError {
             JoinError::NoSender => write!(f, "no gateway destination"),
             JoinError::NoCall => write!(f, "tried to leave a non-existent call"),
             JoinError::TimedOut => write!(f, "gateway response from Discord timed out"),
+            JoinError::Moved { requested, actual } =>
+                write!(f, "moved to channel {actual} while joining {requested}"),
+            JoinError::Disconnected => write!(f, "disconnected from voice while joining"),
+            JoinError::SessionConflict =>
+                write!(f, "voice state claimed by another session while joining"),
             #[cfg(feature = "driver")]
             JoinError::Driver(_) => write!(f, "establishing connection failed"),
             #[cfg(feature = "serenity")]
@@ -106,6 +147,9 @@ impl Error for JoinError {
             JoinError::NoSender => None,
             JoinError::NoCall => None,
             JoinError::TimedOut => None,
+            JoinError::Moved { .. } => None,
+            JoinError::Disconnected => None,
+            JoinError::SessionConflict => None,
             #[cfg(feature = "driver")]
             JoinError::Driver(e) => Some(e),
             #[cfg(feature = "serenity")]
@@ -0,0 +1,40 @@
+use crate::{
+    id::{ChannelId, GuildId},
+    ConnectionInfo,
+};
+use std::sync::Arc;
+
+/// Identifies the call whose driver fired an event.
+///
+/// This is passed to [`EventHandler::act_in_call`], allowing one handler to be
+/// registered on many drivers and still tell their events apart.
+///
+/// [`EventHandler::act_in_call`]: super::EventHandler::act_in_call
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct CallContext {
+    /// The guild of the most recent voice connection, once the driver has tried
+    /// to connect.
+    pub guild_id: Option<GuildId>,
+    /// The channel of the most recent voice connection, if known.
+    pub channel_id: Option<ChannelId>,
+    /// The driver's [`call_identifier`], if one was set.
+    ///
+    /// [`call_identifier`]: crate::Config::call_identifier
+    pub tag: Option<Arc<str>>,
+}
+
+impl CallContext {
+    pub(crate) fn new(tag: Option<&str>) -> Self {
+        Self {
+            tag: tag.map(Into::into),
+            ..Default::default()
+        }
+    }
+
+    /// Takes the guild and channel of a connection the driver has made or attempted.
+    pub(crate) fn observe(&mut self, info: &ConnectionInfo) {
+        self.guild_id = Some(info.guild_id);
+        self.channel_id = info.channel_id;
+    }
+}
@@ -0,0 +1,14 @@
+use crate::id::*;
+
+/// Details of this bot being moved between voice channels, e.g., by a server
+/// moderator.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct ChannelMoveData {
+    /// ID of the voice channel the bot was previously connected to.
+    pub from: ChannelId,
+    /// ID of the voice channel the bot has been moved into.
+    pub to: ChannelId,
+    /// ID of both channels' parent guild.
+    pub guild_id: GuildId,
+}
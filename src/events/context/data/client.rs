@@ -0,0 +1,107 @@
+use crate::model::id::UserId;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Voice client flags announced by Discord for another user in the call.
+///
+/// These are sent when a user joins the channel (or when the bot connects),
+/// and may be updated mid-session.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ClientFlags {
+    /// ID of the user these flags describe.
+    pub user_id: UserId,
+    /// Raw bitflags describing the client's capabilities and settings.
+    ///
+    /// Discord does not document the meaning of these bits, so they are
+    /// exposed unparsed.
+    pub flags: u64,
+}
+
+/// The platform that another user in the call is connected from.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ClientPlatform {
+    /// ID of the user this platform describes.
+    pub user_id: UserId,
+    /// Kind of device that the user is connected from.
+    pub platform: Platform,
+}
+
+/// SSRCs announced by Discord for another user's audio and video streams.
+///
+/// These are sent when a user joins the channel, and again whenever they
+/// start or stop sending video (i.e., turning on their camera or going live).
+/// Songbird uses these to keep video packets from being treated as speech.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[non_exhaustive]
+pub struct ClientVideo {
+    /// ID of the user these streams belong to.
+    pub user_id: UserId,
+    /// SSRC of the user's audio stream, or `0` if not yet known.
+    pub audio_ssrc: u32,
+    /// SSRC of the user's video stream, or `0` if they are not sending video.
+    pub video_ssrc: u32,
+    /// SSRC used to retransmit lost video packets, or `0` if not sending video.
+    #[serde(default)]
+    pub rtx_ssrc: u32,
+}
+
+impl ClientVideo {
+    /// Returns whether this user is currently sending video.
+    #[must_use]
+    pub fn has_video(&self) -> bool {
+        self.video_ssrc != 0
+    }
+}
+
+/// Kinds of client device reported by Discord's voice gateway.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Platform {
+    /// A desktop or web client.
+    Desktop,
+    /// A mobile (Android/iOS) client.
+    Mobile,
+    /// An Xbox console.
+    Xbox,
+    /// A PlayStation console.
+    PlayStation,
+    /// A platform code not yet known to songbird.
+    Unknown(u8),
+}
+
+impl From<u8> for Platform {
+    fn from(value: u8) -> Self {
+        match value {
+            0 => Self::Desktop,
+            1 => Self::Mobile,
+            2 => Self::Xbox,
+            3 => Self::PlayStation,
+            v => Self::Unknown(v),
+        }
+    }
+}
+
+impl From<Platform> for u8 {
+    fn from(value: Platform) -> Self {
+        match value {
+            Platform::Desktop => 0,
+            Platform::Mobile => 1,
+            Platform::Xbox => 2,
+            Platform::PlayStation => 3,
+            Platform::Unknown(v) => v,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Platform {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        u8::deserialize(deserializer).map(Self::from)
+    }
+}
+
+impl Serialize for Platform {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        u8::from(*self).serialize(serializer)
+    }
+}
@@ -0,0 +1,17 @@
+/// Details of a received voice packet from an SSRC which could not be decoded.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct DecodeErrorData {
+    /// RTP SSRC of the packet's sender.
+    pub ssrc: u32,
+    /// Number of consecutive packets from this SSRC which have failed to decode,
+    /// including this one.
+    pub count: usize,
+    /// Total number of packets from this SSRC which have failed to decode.
+    pub total: u64,
+    /// Whether this SSRC's decoder was reset as a result of this failure, according
+    /// to [`Config::decode_error_reset`].
+    ///
+    /// [`Config::decode_error_reset`]: crate::Config::decode_error_reset
+    pub decoder_reset: bool,
+}
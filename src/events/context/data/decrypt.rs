@@ -0,0 +1,10 @@
+/// Details of received packets from an SSRC which could not be decrypted.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct DecryptFailData {
+    /// RTP SSRC of the packet's sender.
+    pub ssrc: u32,
+    /// Number of consecutive packets from this SSRC which have failed decryption,
+    /// including this one.
+    pub count: usize,
+}
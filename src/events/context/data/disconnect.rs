@@ -75,6 +75,15 @@ pub enum DisconnectReason {
     ProtocolViolation,
     /// A voice connection was not established in the specified time.
     TimedOut,
+    /// The driver left after playing no tracks for its configured [`idle_timeout`].
+    ///
+    /// [`idle_timeout`]: crate::Config::idle_timeout
+    Idle,
+    /// The driver left after being alone in its channel for its configured
+    /// [`alone_timeout`].
+    ///
+    /// [`alone_timeout`]: crate::Config::alone_timeout
+    Alone,
     /// The call was manually disconnected by a user command, e.g. [`Driver::leave`].
     ///
     /// [`Driver::leave`]: crate::driver::Driver::leave
@@ -94,6 +103,7 @@ impl From<&ConnectionError> for DisconnectReason {
             | ConnectionError::CryptoModeInvalid
             | ConnectionError::CryptoModeUnavailable
             | ConnectionError::EndpointUrl
+            | ConnectionError::Gateway(_)
             | ConnectionError::IllegalDiscoveryResponse
             | ConnectionError::IllegalIp
             | ConnectionError::Json(_) => Self::ProtocolViolation,
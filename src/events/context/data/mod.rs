@@ -1,18 +1,55 @@
 //! Types containing the main body of an [`EventContext`].
 //!
 //! [`EventContext`]: super::EventContext
+mod channel_move;
+mod client;
 mod connect;
+#[cfg(feature = "receive")]
+mod decode_error;
+#[cfg(feature = "receive")]
+mod decrypt;
 mod disconnect;
+mod overload;
+#[cfg(feature = "raw-gateway")]
+mod raw_gateway;
 #[cfg(feature = "receive")]
 mod rtcp;
 #[cfg(feature = "receive")]
 mod rtp;
 #[cfg(feature = "receive")]
+mod talk_spurt;
+mod task_restart;
+mod teardown;
+mod throttle;
+#[cfg(feature = "receive")]
+mod transcription;
+mod transmit;
+#[cfg(feature = "receive")]
 mod voice;
 
 #[cfg(feature = "receive")]
 use bytes::Bytes;
 
-pub use self::{connect::*, disconnect::*};
+#[cfg(feature = "raw-gateway")]
+pub use self::raw_gateway::*;
+pub use self::{
+    channel_move::*,
+    client::*,
+    connect::*,
+    disconnect::*,
+    overload::*,
+    task_restart::*,
+    teardown::*,
+    throttle::*,
+    transmit::*,
+};
 #[cfg(feature = "receive")]
-pub use self::{rtcp::*, rtp::*, voice::*};
+pub use self::{
+    decode_error::*,
+    decrypt::*,
+    rtcp::*,
+    rtp::*,
+    talk_spurt::*,
+    transcription::*,
+    voice::*,
+};
@@ -0,0 +1,18 @@
+use crate::driver::OverloadStrategy;
+use std::time::Duration;
+
+/// A change in whether this driver's [`OverloadPolicy`] is shedding work.
+///
+/// [`OverloadPolicy`]: crate::driver::OverloadPolicy
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct OverloadData {
+    /// The strategy used to shed work.
+    pub strategy: OverloadStrategy,
+    /// Whether the policy has just engaged (`true`), or released (`false`).
+    pub engaged: bool,
+    /// Time taken to mix the frame which caused this change.
+    pub mix_time: Duration,
+    /// Number of playing tracks held in place during that frame.
+    pub held_tracks: usize,
+}
@@ -0,0 +1,23 @@
+use serde_json::Value;
+
+/// A voice gateway payload which songbird does not understand.
+///
+/// This is **experimental**: payloads which songbird later learns to parse will
+/// stop being reported here, and instead fire their own events.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct RawGatewayData {
+    /// The payload's opcode, if present.
+    pub op: Option<u64>,
+    /// The complete payload, including its opcode.
+    pub payload: Value,
+}
+
+impl From<Value> for RawGatewayData {
+    fn from(payload: Value) -> Self {
+        Self {
+            op: payload.get("op").and_then(Value::as_u64),
+            payload,
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+/// Summary of one continuous run of speech received from an SSRC.
+///
+/// This is produced once the SSRC's playout buffer has drained after the
+/// run, so that each talk spurt can be assessed for receive quality.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct TalkSpurtData {
+    /// RTP SSRC of the speaker.
+    pub ssrc: u32,
+    /// Playout time spanned by the talk spurt, including any lost frames.
+    pub duration: Duration,
+    /// Number of frames which were never received in time for playout.
+    pub lost: u32,
+    /// Number of lost frames which were replaced with concealment audio by the decoder.
+    ///
+    /// This is always `0` unless [`DecodeMode::Decode`] is used.
+    ///
+    /// [`DecodeMode::Decode`]: crate::driver::DecodeMode::Decode
+    pub concealed: u32,
+    /// Number of packets which arrived after their playout time had already
+    /// passed, and so were discarded.
+    pub late: u32,
+    /// Number of packets which arrived after their playout time, but within
+    /// [`Config::late_packet_grace`], and so were decoded as corrections.
+    ///
+    /// [`Config::late_packet_grace`]: crate::Config::late_packet_grace
+    pub corrected: u32,
+    /// Number of packets which arrived after a packet with a later sequence number.
+    pub reordered: u32,
+    /// Largest distance, in sequence numbers, by which any packet was reordered.
+    pub max_reorder_depth: u16,
+}
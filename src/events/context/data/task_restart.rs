@@ -0,0 +1,34 @@
+/// An internal driver task which died unexpectedly.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum DriverTask {
+    /// The websocket task, which handles voice gateway messages and heartbeats.
+    Ws,
+    #[cfg(feature = "receive")]
+    /// The UDP receive task, which decrypts and decodes audio from other users.
+    UdpRx,
+}
+
+/// How the driver recovered from a task's death.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum TaskRecovery {
+    /// The task was restarted within the current voice session, keeping its
+    /// configuration, encryption state, and known user SSRCs.
+    Restarted,
+    /// The task could not be restarted in place, so the driver has begun a full
+    /// reconnect to the voice channel.
+    FullReconnect,
+}
+
+/// Details of an internal driver task which panicked, and how it was recovered.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct TaskRestartData {
+    /// The task which died.
+    pub task: DriverTask,
+    /// The panic message of the task, if it could be recovered.
+    pub cause: String,
+    /// The action taken to restore the call.
+    pub recovery: TaskRecovery,
+}
@@ -0,0 +1,73 @@
+use super::{DisconnectReason, DriverTask};
+use crate::{id::*, model::CloseCode as VoiceCloseCode};
+
+/// Details of a voice session which has ended for good.
+///
+/// Unlike [`DisconnectData`], this is fired exactly once per established session,
+/// after any reconnection attempts have been exhausted.
+///
+/// [`DisconnectData`]: super::DisconnectData
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct TeardownData {
+    /// Why the session ended.
+    pub cause: TeardownCause,
+    /// ID of the voice channel which was joined, if it is known.
+    pub channel_id: Option<ChannelId>,
+    /// ID of the voice channel's parent guild.
+    pub guild_id: GuildId,
+    /// Unique string describing the ended session.
+    pub session_id: String,
+}
+
+/// Why a voice session ended for good.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum TeardownCause {
+    /// The session was left by a user command (e.g., [`Driver::leave`]), or
+    /// replaced by a connection to another channel.
+    ///
+    /// [`Driver::leave`]: crate::driver::Driver::leave
+    Requested,
+    /// The bot was moved into another voice channel, and the session was replaced
+    /// by a connection to the new channel.
+    Moved,
+    /// The driver left after playing no tracks for its configured [`idle_timeout`].
+    ///
+    /// [`idle_timeout`]: crate::Config::idle_timeout
+    Idle,
+    /// The driver left after being alone in its channel for its configured
+    /// [`alone_timeout`].
+    ///
+    /// [`alone_timeout`]: crate::Config::alone_timeout
+    Alone,
+    /// The voice websocket was closed, with Discord's close code if one was given,
+    /// and the session could not be resumed or re-established.
+    WsClosed(Option<VoiceCloseCode>),
+    /// Voice packets could no longer be sent over UDP, and the session could not
+    /// be re-established.
+    UdpFailure,
+    /// Received voice packets repeatedly failed decryption, according to
+    /// [`Config::decrypt_failure_policy`], and the session could not be re-established.
+    ///
+    /// [`Config::decrypt_failure_policy`]: crate::Config::decrypt_failure_policy
+    CryptoFailure,
+    /// An internal driver task panicked, and the session could not be re-established.
+    TaskPanic(DriverTask),
+    /// Re-establishing the session failed for another reason.
+    ConnectionFailed(DisconnectReason),
+    /// The driver was shut down or dropped.
+    Shutdown,
+}
+
+impl From<DisconnectReason> for TeardownCause {
+    fn from(reason: DisconnectReason) -> Self {
+        match reason {
+            DisconnectReason::Requested => Self::Requested,
+            DisconnectReason::Idle => Self::Idle,
+            DisconnectReason::Alone => Self::Alone,
+            DisconnectReason::WsClosed(code) => Self::WsClosed(code),
+            other => Self::ConnectionFailed(other),
+        }
+    }
+}
@@ -0,0 +1,23 @@
+use crate::driver::Bitrate;
+
+/// A change in how this driver's [`BandwidthCap`] limits its outgoing bitrate.
+///
+/// [`BandwidthCap`]: crate::driver::BandwidthCap
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct ThrottleData {
+    /// Whether the encoder's bitrate is being lowered to fit the cap (`true`), or
+    /// has been restored to the requested bitrate (`false`).
+    pub engaged: bool,
+    /// The limit currently applying to this call, in kbps.
+    ///
+    /// This may change without releasing the throttle as other calls join or
+    /// leave a [`SharedBandwidth`] budget.
+    ///
+    /// [`SharedBandwidth`]: crate::driver::SharedBandwidth
+    pub limit_kbps: Option<u32>,
+    /// The bitrate requested via [`Driver::set_bitrate`] or bitrate automation.
+    ///
+    /// [`Driver::set_bitrate`]: crate::driver::Driver::set_bitrate
+    pub requested: Bitrate,
+}
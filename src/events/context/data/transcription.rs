@@ -0,0 +1,22 @@
+use crate::model::id::UserId;
+
+/// Text recognised from another user's speech by a [`Transcriber`].
+///
+/// [`Transcriber`]: crate::driver::Transcriber
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct TranscriptionData {
+    /// RTP SSRC of the speaker.
+    pub ssrc: u32,
+    /// User ID of the speaker, if this has been announced by Discord.
+    pub user_id: Option<UserId>,
+    /// Identifier of the speech segment this text belongs to.
+    ///
+    /// Any number of interim results may be fired for a segment, followed by
+    /// at most one final result.
+    pub segment_id: u64,
+    /// Recognised text.
+    pub text: String,
+    /// Whether this is the final transcription of its segment.
+    pub is_final: bool,
+}
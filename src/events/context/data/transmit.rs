@@ -0,0 +1,23 @@
+/// Summary of the audio frames sent by this driver since the last
+/// [`CoreEvent::Transmit`] event.
+///
+/// Each frame holds 20ms of audio.
+///
+/// [`CoreEvent::Transmit`]: crate::events::CoreEvent::Transmit
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct TransmitData {
+    /// Number of frames containing (mixed or passthrough) track audio.
+    pub frames: u64,
+    /// Number of silent frames sent, i.e., when no track audio was available
+    /// or the driver was muted.
+    pub silence_frames: u64,
+}
+
+impl TransmitData {
+    /// Returns the total number of frames sent, both audio and silence.
+    #[must_use]
+    pub fn total_frames(&self) -> u64 {
+        self.frames + self.silence_frames
+    }
+}
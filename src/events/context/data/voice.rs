@@ -1,8 +1,9 @@
 use std::collections::{HashMap, HashSet};
 
 use super::*;
+use crate::driver::{SampleFormat, SampleLayout};
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
 /// Audio data from all users in a voice channel, fired every 20ms.
 ///
@@ -17,9 +18,18 @@ pub struct VoiceTick {
 
     /// Set of all SSRCs currently known in the call who aren't included in [`Self::speaking`].
     pub silent: HashSet<u32>,
+
+    /// Audio decoded from each user's packets which arrived shortly after their
+    /// playout slot, within [`Config::late_packet_grace`].
+    ///
+    /// This is always empty unless [`DecodeMode::Decode`] is used.
+    ///
+    /// [`Config::late_packet_grace`]: crate::Config::late_packet_grace
+    /// [`DecodeMode::Decode`]: crate::driver::DecodeMode::Decode
+    pub corrections: HashMap<u32, Vec<VoiceCorrection>>,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 #[non_exhaustive]
 /// Voice packet and audio data for a single user, from a single tick.
 pub struct VoiceData {
@@ -35,9 +45,96 @@ pub struct VoiceData {
     /// [`Config::decode_channels`] and [`Config::decode_sample_rate`] -- channels are interleaved
     /// (i.e., `L, R, L, R, ...`) if stereo.
     ///
-    /// This value will be `None` if Songbird is not configured to decode audio.
+    /// This value will be `None` if Songbird is not configured to decode audio, or if
+    /// audio is instead delivered via [`Self::samples`].
     ///
     /// [`Config::decode_channels`]: crate::Config::decode_channels
     /// [`Config::decode_sample_rate`]: crate::Config::decode_sample_rate
     pub decoded_voice: Option<Vec<i16>>,
+    /// PCM audio obtained from a user, in the format chosen by [`Config::decode_sample_format`]
+    /// and [`Config::decode_sample_layout`].
+    ///
+    /// This is only used when either setting differs from its default, in which case
+    /// it replaces [`Self::decoded_voice`].
+    ///
+    /// [`Config::decode_sample_format`]: crate::Config::decode_sample_format
+    /// [`Config::decode_sample_layout`]: crate::Config::decode_sample_layout
+    pub samples: Option<DecodedSamples>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+/// Audio decoded from a packet which arrived after its slot was played out as a loss.
+///
+/// Consumers which buffer recent audio may use this to replace the concealment audio
+/// emitted in that slot.
+pub struct VoiceCorrection {
+    /// RTP packet which arrived late.
+    pub packet: RtpData,
+    /// Number of ticks ago that this packet's slot was played out, where `1` is the
+    /// most recent tick.
+    pub ticks_late: u16,
+    /// PCM audio decoded from the packet, in the same format as
+    /// [`VoiceData::decoded_voice`].
+    ///
+    /// This is empty if audio is instead delivered via [`Self::samples`].
+    pub decoded_voice: Vec<i16>,
+    /// PCM audio decoded from the packet, in the same format as [`VoiceData::samples`].
+    pub samples: Option<DecodedSamples>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+/// Decoded PCM audio in a configured sample format.
+pub enum DecodedSamples {
+    /// Signed 16-bit integer samples.
+    I16(Vec<i16>),
+    /// 32-bit float samples, in the range `-1.0..=1.0`.
+    F32(Vec<f32>),
+}
+
+impl DecodedSamples {
+    /// Converts native-endian, interleaved 16-bit audio into the given format and layout.
+    pub(crate) fn from_interleaved(
+        audio: Vec<i16>,
+        channels: usize,
+        format: SampleFormat,
+        layout: SampleLayout,
+    ) -> Self {
+        let audio = match layout {
+            SampleLayout::Planar if channels > 1 => {
+                let frames = audio.len() / channels;
+                (0..channels)
+                    .flat_map(|chan| audio.iter().skip(chan).step_by(channels).take(frames))
+                    .copied()
+                    .collect()
+            },
+            _ => audio,
+        };
+
+        match format {
+            SampleFormat::I16 => Self::I16(audio),
+            SampleFormat::F32 => Self::F32(
+                audio
+                    .into_iter()
+                    .map(|s| f32::from(s) / -f32::from(i16::MIN))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns the number of samples held, across all channels.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        match self {
+            Self::I16(v) => v.len(),
+            Self::F32(v) => v.len(),
+        }
+    }
+
+    /// Returns whether no samples are held.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
 }
@@ -23,8 +23,13 @@ pub enum EventContext<'a> {
     /// Track event context, passed to events created via [`TrackHandle::add_event`],
     /// [`EventStore::add_event`], or relevant global events.
     ///
+    /// Data attached to each track via [`Track::user_data`] can be retrieved
+    /// using [`TrackHandle::data`].
+    ///
     /// [`EventStore::add_event`]: EventStore::add_event
     /// [`TrackHandle::add_event`]: TrackHandle::add_event
+    /// [`Track::user_data`]: crate::tracks::Track::user_data
+    /// [`TrackHandle::data`]: TrackHandle::data
     Track(&'a [(&'a TrackState, &'a TrackHandle)]),
 
     /// Speaking state update, typically describing how another voice
@@ -44,9 +49,35 @@ pub enum EventContext<'a> {
     /// Telemetry/statistics packet, received from another stream.
     RtcpPacket(RtcpData),
 
+    #[cfg(feature = "receive")]
+    /// Voice packet from another stream which could not be decrypted.
+    DecryptFail(DecryptFailData),
+
+    #[cfg(feature = "receive")]
+    /// Voice packet from another stream which could not be decoded.
+    DecodeError(DecodeErrorData),
+
+    #[cfg(feature = "receive")]
+    /// Receive quality statistics for a run of speech from another stream.
+    TalkSpurtEnd(TalkSpurtData),
+
+    #[cfg(feature = "receive")]
+    /// Interim or final text recognised from another user's speech.
+    Transcription(TranscriptionData),
+
     /// Fired whenever a client disconnects.
     ClientDisconnect(ClientDisconnect),
 
+    /// Client flags announced for another user in the call.
+    ClientFlags(ClientFlags),
+
+    /// The platform (e.g., desktop or mobile) another user in the call is connected from.
+    ClientPlatform(ClientPlatform),
+
+    /// Audio and video SSRCs of another user in the call, announced as they
+    /// start or stop sending video.
+    ClientVideo(ClientVideo),
+
     /// Fires when this driver successfully connects to a voice channel.
     DriverConnect(ConnectData<'a>),
 
@@ -55,6 +86,28 @@ pub enum EventContext<'a> {
 
     /// Fires when this driver fails to connect to, or drops from, a voice channel.
     DriverDisconnect(DisconnectData<'a>),
+
+    /// Fires once when a voice session established by this driver ends for good.
+    DriverTeardown(TeardownData),
+
+    /// Periodic summary of audio and silence frames sent by this driver.
+    Transmit(TransmitData),
+
+    /// The mixer's overload policy engaged or released.
+    Overload(OverloadData),
+
+    /// The driver's bandwidth cap began, changed, or stopped limiting its bitrate.
+    Throttle(ThrottleData),
+
+    /// Fires when this bot is moved into another voice channel.
+    ChannelMoved(ChannelMoveData),
+
+    /// An internal driver task panicked, and was restarted or replaced by a full reconnect.
+    DriverTaskRestarted(TaskRestartData),
+
+    #[cfg(feature = "raw-gateway")]
+    /// A voice gateway payload which songbird does not understand.
+    RawGatewayPayload(RawGatewayData),
 }
 
 #[derive(Debug)]
@@ -66,10 +119,29 @@ pub enum CoreContext {
     RtpPacket(InternalRtpPacket),
     #[cfg(feature = "receive")]
     RtcpPacket(InternalRtcpPacket),
+    #[cfg(feature = "receive")]
+    DecryptFail(DecryptFailData),
+    #[cfg(feature = "receive")]
+    DecodeError(DecodeErrorData),
+    #[cfg(feature = "receive")]
+    TalkSpurtEnd(TalkSpurtData),
+    #[cfg(feature = "receive")]
+    Transcription(TranscriptionData),
     ClientDisconnect(ClientDisconnect),
+    ClientFlags(ClientFlags),
+    ClientPlatform(ClientPlatform),
+    ClientVideo(ClientVideo),
     DriverConnect(InternalConnect),
     DriverReconnect(InternalConnect),
     DriverDisconnect(InternalDisconnect),
+    DriverTeardown(TeardownData),
+    Transmit(TransmitData),
+    Overload(OverloadData),
+    Throttle(ThrottleData),
+    ChannelMoved(ChannelMoveData),
+    DriverTaskRestarted(TaskRestartData),
+    #[cfg(feature = "raw-gateway")]
+    RawGatewayPayload(RawGatewayData),
 }
 
 impl<'a> CoreContext {
@@ -82,11 +154,30 @@ impl<'a> CoreContext {
             Self::RtpPacket(evt) => EventContext::RtpPacket(RtpData::from(evt)),
             #[cfg(feature = "receive")]
             Self::RtcpPacket(evt) => EventContext::RtcpPacket(RtcpData::from(evt)),
+            #[cfg(feature = "receive")]
+            Self::DecryptFail(evt) => EventContext::DecryptFail(*evt),
+            #[cfg(feature = "receive")]
+            Self::DecodeError(evt) => EventContext::DecodeError(*evt),
+            #[cfg(feature = "receive")]
+            Self::TalkSpurtEnd(evt) => EventContext::TalkSpurtEnd(*evt),
+            #[cfg(feature = "receive")]
+            Self::Transcription(evt) => EventContext::Transcription(evt.clone()),
             Self::ClientDisconnect(evt) => EventContext::ClientDisconnect(*evt),
+            Self::ClientFlags(evt) => EventContext::ClientFlags(*evt),
+            Self::ClientPlatform(evt) => EventContext::ClientPlatform(*evt),
+            Self::ClientVideo(evt) => EventContext::ClientVideo(*evt),
             Self::DriverConnect(evt) => EventContext::DriverConnect(ConnectData::from(evt)),
             Self::DriverReconnect(evt) => EventContext::DriverReconnect(ConnectData::from(evt)),
             Self::DriverDisconnect(evt) =>
                 EventContext::DriverDisconnect(DisconnectData::from(evt)),
+            Self::DriverTeardown(evt) => EventContext::DriverTeardown(evt.clone()),
+            Self::Transmit(evt) => EventContext::Transmit(*evt),
+            Self::Overload(evt) => EventContext::Overload(*evt),
+            Self::Throttle(evt) => EventContext::Throttle(*evt),
+            Self::ChannelMoved(evt) => EventContext::ChannelMoved(*evt),
+            Self::DriverTaskRestarted(evt) => EventContext::DriverTaskRestarted(evt.clone()),
+            #[cfg(feature = "raw-gateway")]
+            Self::RawGatewayPayload(evt) => EventContext::RawGatewayPayload(evt.clone()),
         }
     }
 }
@@ -104,10 +195,29 @@ impl EventContext<'_> {
             Self::RtpPacket(_) => Some(CoreEvent::RtpPacket),
             #[cfg(feature = "receive")]
             Self::RtcpPacket(_) => Some(CoreEvent::RtcpPacket),
+            #[cfg(feature = "receive")]
+            Self::DecryptFail(_) => Some(CoreEvent::DecryptFail),
+            #[cfg(feature = "receive")]
+            Self::DecodeError(_) => Some(CoreEvent::DecodeError),
+            #[cfg(feature = "receive")]
+            Self::TalkSpurtEnd(_) => Some(CoreEvent::TalkSpurtEnd),
+            #[cfg(feature = "receive")]
+            Self::Transcription(_) => Some(CoreEvent::Transcription),
             Self::ClientDisconnect(_) => Some(CoreEvent::ClientDisconnect),
+            Self::ClientFlags(_) => Some(CoreEvent::ClientFlags),
+            Self::ClientPlatform(_) => Some(CoreEvent::ClientPlatform),
+            Self::ClientVideo(_) => Some(CoreEvent::ClientVideo),
             Self::DriverConnect(_) => Some(CoreEvent::DriverConnect),
             Self::DriverReconnect(_) => Some(CoreEvent::DriverReconnect),
             Self::DriverDisconnect(_) => Some(CoreEvent::DriverDisconnect),
+            Self::DriverTeardown(_) => Some(CoreEvent::DriverTeardown),
+            Self::Transmit(_) => Some(CoreEvent::Transmit),
+            Self::Overload(_) => Some(CoreEvent::Overload),
+            Self::Throttle(_) => Some(CoreEvent::Throttle),
+            Self::ChannelMoved(_) => Some(CoreEvent::ChannelMoved),
+            Self::DriverTaskRestarted(_) => Some(CoreEvent::DriverTaskRestarted),
+            #[cfg(feature = "raw-gateway")]
+            Self::RawGatewayPayload(_) => Some(CoreEvent::RawGatewayPayload),
             _ => None,
         }
     }
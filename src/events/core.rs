@@ -6,7 +6,9 @@
 ///
 /// ## Events from other users
 /// Songbird can observe when a user *speaks for the first time* ([`SpeakingStateUpdate`]),
-/// when a client leaves the session ([`ClientDisconnect`]).
+/// when a client leaves the session ([`ClientDisconnect`]), and which
+/// platform ([`ClientPlatform`]) and client flags ([`ClientFlags`]) a user has,
+/// and when a user starts or stops sending video ([`ClientVideo`]).
 ///
 /// When the `"receive"` feature is enabled, songbird can also handle voice packets
 #[cfg_attr(feature = "receive", doc = "([`RtpPacket`](Self::RtpPacket)),")]
@@ -32,6 +34,9 @@
 /// [`EventData`]: super::EventData
 /// [`SpeakingStateUpdate`]: Self::SpeakingStateUpdate
 /// [`ClientDisconnect`]: Self::ClientDisconnect
+/// [`ClientPlatform`]: Self::ClientPlatform
+/// [`ClientFlags`]: Self::ClientFlags
+/// [`ClientVideo`]: Self::ClientVideo
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 #[non_exhaustive]
 pub enum CoreEvent {
@@ -42,7 +47,8 @@ pub enum CoreEvent {
     /// Fired on receipt of a speaking state update from another host.
     ///
     /// Note: this will fire when a user starts speaking for the first time,
-    /// or changes their capabilities.
+    /// or changes their capabilities. The sender's `delay` value is passed on
+    /// unchanged, and may be used as a synchronisation hint by some clients.
     SpeakingStateUpdate,
 
     #[cfg(feature = "receive")]
@@ -63,9 +69,53 @@ pub enum CoreEvent {
     /// such as latency reports.
     RtcpPacket,
 
+    #[cfg(feature = "receive")]
+    /// Fires when a voice packet from another stream fails decryption, if enabled
+    /// by [`Config::decrypt_failure_policy`].
+    ///
+    /// [`Config::decrypt_failure_policy`]: crate::Config::decrypt_failure_policy
+    DecryptFail,
+
+    #[cfg(feature = "receive")]
+    /// Fires when a voice packet from another stream fails to decode, such as when
+    /// a client sends a malformed Opus payload.
+    ///
+    /// Repeated failures may reset that stream's decoder, according to
+    /// [`Config::decode_error_reset`].
+    ///
+    /// [`Config::decode_error_reset`]: crate::Config::decode_error_reset
+    DecodeError,
+
+    #[cfg(feature = "receive")]
+    /// Fires when a run of speech from an SSRC has finished playing out, summarising
+    /// its duration and how many of its packets were lost, concealed, or late.
+    ///
+    /// This is intended for per-utterance quality monitoring, and complements
+    /// the call-wide statistics found in RTCP reports.
+    TalkSpurtEnd,
+
+    #[cfg(feature = "receive")]
+    /// Fires when a [`Transcriber`] reports interim or final text for a segment
+    /// of another user's speech.
+    ///
+    /// [`Transcriber`]: crate::driver::Transcriber
+    Transcription,
+
     /// Fires whenever a user disconnects from the same stream as the bot.
     ClientDisconnect,
 
+    /// Fires when Discord announces the client flags of a user in the same
+    /// stream as the bot, typically as they connect.
+    ClientFlags,
+
+    /// Fires when Discord announces which platform (e.g., desktop, mobile, or console)
+    /// a user in the same stream as the bot is connected from, typically as they connect.
+    ClientPlatform,
+
+    /// Fires when Discord announces the audio and video SSRCs of a user in the same
+    /// stream as the bot, typically as they connect or start/stop sending video.
+    ClientVideo,
+
     /// Fires when this driver successfully connects to a voice channel.
     DriverConnect,
 
@@ -74,4 +124,65 @@ pub enum CoreEvent {
 
     /// Fires when this driver fails to connect to, or drops from, a voice channel.
     DriverDisconnect,
+
+    /// Fires exactly once when a voice session established by this driver ends for
+    /// good, after any attempts to reconnect, with a detailed cause.
+    ///
+    /// This is intended to drive reconnection logic: unlike [`DriverDisconnect`], it
+    /// is not fired for failed reconnection attempts which are still being retried.
+    ///
+    /// [`DriverDisconnect`]: Self::DriverDisconnect
+    DriverTeardown,
+
+    /// Fires each time this driver has sent a configured amount of audio, summarising
+    /// how many frames contained track audio versus silence.
+    ///
+    /// This is disabled unless [`Config::transmit_event_interval`] is set.
+    ///
+    /// [`Config::transmit_event_interval`]: crate::Config::transmit_event_interval
+    Transmit,
+
+    /// Fires when this driver's [`OverloadPolicy`] begins or stops shedding work
+    /// to keep mixing within its time budget.
+    ///
+    /// This is disabled unless [`Config::overload_policy`] is set.
+    ///
+    /// [`OverloadPolicy`]: crate::driver::OverloadPolicy
+    /// [`Config::overload_policy`]: crate::Config::overload_policy
+    Overload,
+
+    /// Fires when this driver's [`BandwidthCap`] begins lowering its bitrate, changes
+    /// the limit it applies, or stops lowering its bitrate.
+    ///
+    /// This is disabled unless [`Config::bandwidth_cap`] is set.
+    ///
+    /// [`BandwidthCap`]: crate::driver::BandwidthCap
+    /// [`Config::bandwidth_cap`]: crate::Config::bandwidth_cap
+    Throttle,
+
+    /// Fires when this bot is moved into another voice channel, such as by a
+    /// server moderator.
+    ///
+    /// The driver reconnects to the new channel by itself, keeping all tracks
+    /// and their state. This is only detected by a [`Call`], via
+    /// [`Call::update_state`].
+    ///
+    /// [`Call`]: crate::Call
+    /// [`Call::update_state`]: crate::Call::update_state
+    ChannelMoved,
+
+    /// Fires when an internal driver task (such as the websocket or UDP receive task)
+    /// panics, and has been restarted or replaced by a full reconnect.
+    DriverTaskRestarted,
+
+    #[cfg(feature = "raw-gateway")]
+    /// Fires on receipt of a voice gateway payload which songbird does not understand,
+    /// such as a newly-added opcode.
+    ///
+    /// This is **experimental**, and is intended to be paired with
+    /// [`Driver::send_gateway_payload`] to try out new gateway features before
+    /// songbird supports them.
+    ///
+    /// [`Driver::send_gateway_payload`]: crate::driver::Driver::send_gateway_payload
+    RawGatewayPayload,
 }
@@ -47,17 +47,24 @@
 //! [track's playback time]: crate::tracks::TrackState::play_time
 //! [`CoreEvent`]: CoreEvent
 
+mod call;
 mod context;
 mod core;
 mod data;
+mod once;
+mod queued;
 mod store;
 mod track;
+pub mod typed;
 mod untimed;
 
 pub use self::{
+    call::CallContext,
     context::{context_data, EventContext},
     core::*,
     data::*,
+    once::OnceWhen,
+    queued::*,
     store::*,
     track::*,
     untimed::*,
@@ -74,6 +81,17 @@ use std::time::Duration;
 pub trait EventHandler: Send + Sync {
     /// Respond to one received event.
     async fn act(&self, ctx: &EventContext<'_>) -> Option<Event>;
+
+    /// Respond to one received event, given the call whose driver fired it.
+    ///
+    /// Handlers shared between several drivers can override this in place of
+    /// [`act`] to learn which call each event belongs to. By default, this calls
+    /// [`act`].
+    ///
+    /// [`act`]: Self::act
+    async fn act_in_call(&self, ctx: &EventContext<'_>, _call: &CallContext) -> Option<Event> {
+        self.act(ctx).await
+    }
 }
 
 /// Classes of event which may occur, triggering a handler
@@ -0,0 +1,59 @@
+use super::{CallContext, Event, EventContext, EventHandler};
+use async_trait::async_trait;
+
+/// An [`EventHandler`] which passes on the first event matching a predicate, and
+/// then removes itself.
+///
+/// The predicate is checked in the event task each time the registered [`Event`]
+/// fires, so that conditions such as "the first time this track passes 30s" can be
+/// expressed without a persistent handler which cancels itself. Events failing the
+/// predicate are ignored, and leave the handler in place (except for
+/// [`Event::Delayed`], which is always removed after firing).
+///
+/// Once the inner handler has been called, this is removed unless the inner handler
+/// returns an [`Event`] of its own.
+///
+/// See [`TrackHandle::once_when`] for a shorthand over track state.
+///
+/// [`TrackHandle::once_when`]: crate::tracks::TrackHandle::once_when
+pub struct OnceWhen<P, H> {
+    predicate: P,
+    handler: H,
+}
+
+impl<P, H> OnceWhen<P, H>
+where
+    P: Fn(&EventContext<'_>) -> bool + Send + Sync,
+    H: EventHandler,
+{
+    /// Wraps `handler`, calling it only for the first event where `predicate` holds.
+    pub fn new(predicate: P, handler: H) -> Self {
+        Self { predicate, handler }
+    }
+}
+
+#[async_trait]
+impl<P, H> EventHandler for OnceWhen<P, H>
+where
+    P: Fn(&EventContext<'_>) -> bool + Send + Sync,
+    H: EventHandler,
+{
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if !(self.predicate)(ctx) {
+            return None;
+        }
+
+        self.handler.act(ctx).await.or(Some(Event::Cancel))
+    }
+
+    async fn act_in_call(&self, ctx: &EventContext<'_>, call: &CallContext) -> Option<Event> {
+        if !(self.predicate)(ctx) {
+            return None;
+        }
+
+        self.handler
+            .act_in_call(ctx, call)
+            .await
+            .or(Some(Event::Cancel))
+    }
+}
@@ -0,0 +1,389 @@
+use super::{
+    context_data::{ConnectData, DisconnectData, DisconnectKind, DisconnectReason},
+    *,
+};
+use crate::{
+    id::{ChannelId, GuildId},
+    tracks::{TrackHandle, TrackState},
+};
+use flume::{Receiver, Sender, TrySendError};
+use once_cell::sync::OnceCell;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+
+/// Default number of events a [`QueuedHandler`] will hold before applying its
+/// [`QueuePolicy`].
+pub const DEFAULT_QUEUE_CAPACITY: usize = 32;
+
+/// How a [`QueuedHandler`] treats new events once its queue is full.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum QueuePolicy {
+    /// Discard each new event until the handler catches up.
+    #[default]
+    DropNewest,
+    /// Discard the oldest queued event, so that the handler sees the latest
+    /// state once it catches up.
+    KeepLatest,
+}
+
+/// Counts of events passed to a [`QueuedHandler`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct QueueStats {
+    /// Events handed to the wrapped handler.
+    pub handled: u64,
+    /// Events discarded because the queue was full.
+    pub dropped: u64,
+}
+
+/// Shared view of a [`QueuedHandler`]'s [`QueueStats`].
+///
+/// Clones of a monitor share the same counters.
+#[derive(Clone, Debug, Default)]
+pub struct QueueMonitor {
+    handled: Arc<AtomicU64>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl QueueMonitor {
+    /// Returns the current handled and dropped counts.
+    #[must_use]
+    pub fn stats(&self) -> QueueStats {
+        QueueStats {
+            handled: self.handled.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+
+    fn handled(&self) {
+        self.handled.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn dropped(&self) {
+        self.dropped.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Runs an [`EventHandler`] on its own task, fed by a bounded queue.
+///
+/// Handlers are otherwise awaited one after another by the driver's event task:
+/// a handler which blocks or runs slowly delays every other handler, and can
+/// eventually back up into the mixer. Wrapping a handler in a `QueuedHandler`
+/// means that the event task only ever copies the [`EventContext`] into a queue,
+/// discarding events according to the chosen [`QueuePolicy`] if the handler
+/// falls behind.
+///
+/// As the wrapped handler runs after the event has fired, its return value can
+/// only remove it: [`Event::Cancel`] takes effect the next time the event fires,
+/// and any other return value is ignored.
+pub struct QueuedHandler {
+    inner: Arc<dyn EventHandler>,
+    capacity: usize,
+    policy: QueuePolicy,
+    queue: OnceCell<(Sender<QueuedEvent>, Receiver<QueuedEvent>)>,
+    cancelled: Arc<AtomicBool>,
+    monitor: QueueMonitor,
+}
+
+impl QueuedHandler {
+    /// Wraps `handler` in a queue of [`DEFAULT_QUEUE_CAPACITY`] events,
+    /// using [`QueuePolicy::DropNewest`].
+    #[must_use]
+    pub fn new<F: EventHandler + 'static>(handler: F) -> Self {
+        Self {
+            inner: Arc::new(handler),
+            capacity: DEFAULT_QUEUE_CAPACITY,
+            policy: QueuePolicy::default(),
+            queue: OnceCell::new(),
+            cancelled: Arc::default(),
+            monitor: QueueMonitor::default(),
+        }
+    }
+
+    /// Sets the number of events which may wait for the handler.
+    ///
+    /// Values below `1` are treated as `1`.
+    #[must_use]
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Sets how new events are treated when the queue is full.
+    #[must_use]
+    pub fn policy(mut self, policy: QueuePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Returns a handle to this handler's event counts.
+    ///
+    /// This should be taken before the handler is registered.
+    #[must_use]
+    pub fn monitor(&self) -> QueueMonitor {
+        self.monitor.clone()
+    }
+
+    fn queue(&self) -> &(Sender<QueuedEvent>, Receiver<QueuedEvent>) {
+        self.queue.get_or_init(|| {
+            let (tx, rx) = flume::bounded(self.capacity);
+
+            tokio::spawn(run_queue(
+                self.inner.clone(),
+                rx.clone(),
+                self.cancelled.clone(),
+                self.monitor.clone(),
+            ));
+
+            (tx, rx)
+        })
+    }
+}
+
+#[async_trait]
+impl EventHandler for QueuedHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        self.act_in_call(ctx, &CallContext::default()).await
+    }
+
+    async fn act_in_call(&self, ctx: &EventContext<'_>, call: &CallContext) -> Option<Event> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Some(Event::Cancel);
+        }
+
+        let (tx, rx) = self.queue();
+        let mut evt = (OwnedContext::from(ctx), call.clone());
+
+        loop {
+            match tx.try_send(evt) {
+                Ok(()) | Err(TrySendError::Disconnected(_)) => break,
+                Err(TrySendError::Full(returned)) => {
+                    self.monitor.dropped();
+
+                    match self.policy {
+                        QueuePolicy::DropNewest => break,
+                        QueuePolicy::KeepLatest => {
+                            drop(rx.try_recv());
+                            evt = returned;
+                        },
+                    }
+                },
+            }
+        }
+
+        None
+    }
+}
+
+async fn run_queue(
+    handler: Arc<dyn EventHandler>,
+    rx: Receiver<QueuedEvent>,
+    cancelled: Arc<AtomicBool>,
+    monitor: QueueMonitor,
+) {
+    while let Ok((evt, call)) = rx.recv_async().await {
+        monitor.handled();
+
+        let out = match &evt {
+            OwnedContext::Track(tracks) => {
+                let tracks: Vec<_> = tracks
+                    .iter()
+                    .map(|(state, handle)| (state, handle))
+                    .collect();
+                handler
+                    .act_in_call(&EventContext::Track(&tracks), &call)
+                    .await
+            },
+            OwnedContext::DriverConnect(data) =>
+                handler
+                    .act_in_call(&EventContext::DriverConnect(data.into()), &call)
+                    .await,
+            OwnedContext::DriverReconnect(data) =>
+                handler
+                    .act_in_call(&EventContext::DriverReconnect(data.into()), &call)
+                    .await,
+            OwnedContext::DriverDisconnect(data) =>
+                handler
+                    .act_in_call(&EventContext::DriverDisconnect(data.into()), &call)
+                    .await,
+            OwnedContext::Other(ctx) => handler.act_in_call(ctx, &call).await,
+        };
+
+        if out == Some(Event::Cancel) {
+            cancelled.store(true, Ordering::Relaxed);
+            break;
+        }
+    }
+}
+
+/// An event waiting for a [`QueuedHandler`], along with the call which fired it.
+type QueuedEvent = (OwnedContext, CallContext);
+
+/// A copy of an [`EventContext`] which does not borrow from the event task.
+enum OwnedContext {
+    Track(Vec<(TrackState, TrackHandle)>),
+    DriverConnect(OwnedConnect),
+    DriverReconnect(OwnedConnect),
+    DriverDisconnect(OwnedDisconnect),
+    Other(EventContext<'static>),
+}
+
+struct OwnedConnect {
+    channel_id: Option<ChannelId>,
+    guild_id: GuildId,
+    session_id: String,
+    server: String,
+    ssrc: u32,
+}
+
+struct OwnedDisconnect {
+    kind: DisconnectKind,
+    reason: Option<DisconnectReason>,
+    channel_id: Option<ChannelId>,
+    guild_id: GuildId,
+    session_id: String,
+}
+
+impl From<&ConnectData<'_>> for OwnedConnect {
+    fn from(val: &ConnectData<'_>) -> Self {
+        Self {
+            channel_id: val.channel_id,
+            guild_id: val.guild_id,
+            session_id: val.session_id.into(),
+            server: val.server.into(),
+            ssrc: val.ssrc,
+        }
+    }
+}
+
+impl<'a> From<&'a OwnedConnect> for ConnectData<'a> {
+    fn from(val: &'a OwnedConnect) -> Self {
+        Self {
+            channel_id: val.channel_id,
+            guild_id: val.guild_id,
+            session_id: &val.session_id,
+            server: &val.server,
+            ssrc: val.ssrc,
+        }
+    }
+}
+
+impl From<&DisconnectData<'_>> for OwnedDisconnect {
+    fn from(val: &DisconnectData<'_>) -> Self {
+        Self {
+            kind: val.kind,
+            reason: val.reason,
+            channel_id: val.channel_id,
+            guild_id: val.guild_id,
+            session_id: val.session_id.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a OwnedDisconnect> for DisconnectData<'a> {
+    fn from(val: &'a OwnedDisconnect) -> Self {
+        Self {
+            kind: val.kind,
+            reason: val.reason,
+            channel_id: val.channel_id,
+            guild_id: val.guild_id,
+            session_id: &val.session_id,
+        }
+    }
+}
+
+impl From<&EventContext<'_>> for OwnedContext {
+    fn from(val: &EventContext<'_>) -> Self {
+        let other = match val {
+            EventContext::Track(tracks) =>
+                return Self::Track(
+                    tracks
+                        .iter()
+                        .map(|&(state, handle)| (state.clone(), handle.clone()))
+                        .collect(),
+                ),
+            EventContext::DriverConnect(data) => return Self::DriverConnect(data.into()),
+            EventContext::DriverReconnect(data) => return Self::DriverReconnect(data.into()),
+            EventContext::DriverDisconnect(data) => return Self::DriverDisconnect(data.into()),
+            EventContext::SpeakingStateUpdate(evt) => EventContext::SpeakingStateUpdate(*evt),
+            #[cfg(feature = "receive")]
+            EventContext::VoiceTick(evt) => EventContext::VoiceTick(evt.clone()),
+            #[cfg(feature = "receive")]
+            EventContext::RtpPacket(evt) => EventContext::RtpPacket(evt.clone()),
+            #[cfg(feature = "receive")]
+            EventContext::RtcpPacket(evt) => EventContext::RtcpPacket(evt.clone()),
+            #[cfg(feature = "receive")]
+            EventContext::DecryptFail(evt) => EventContext::DecryptFail(*evt),
+            #[cfg(feature = "receive")]
+            EventContext::DecodeError(evt) => EventContext::DecodeError(*evt),
+            #[cfg(feature = "receive")]
+            EventContext::TalkSpurtEnd(evt) => EventContext::TalkSpurtEnd(*evt),
+            #[cfg(feature = "receive")]
+            EventContext::Transcription(evt) => EventContext::Transcription(evt.clone()),
+            EventContext::ClientDisconnect(evt) => EventContext::ClientDisconnect(*evt),
+            EventContext::DriverTeardown(evt) => EventContext::DriverTeardown(evt.clone()),
+            EventContext::ClientFlags(evt) => EventContext::ClientFlags(*evt),
+            EventContext::ClientPlatform(evt) => EventContext::ClientPlatform(*evt),
+            EventContext::ClientVideo(evt) => EventContext::ClientVideo(*evt),
+            EventContext::Transmit(evt) => EventContext::Transmit(*evt),
+            EventContext::Overload(evt) => EventContext::Overload(*evt),
+            EventContext::Throttle(evt) => EventContext::Throttle(*evt),
+            EventContext::ChannelMoved(evt) => EventContext::ChannelMoved(*evt),
+            EventContext::DriverTaskRestarted(evt) =>
+                EventContext::DriverTaskRestarted(evt.clone()),
+            #[cfg(feature = "raw-gateway")]
+            EventContext::RawGatewayPayload(evt) => EventContext::RawGatewayPayload(evt.clone()),
+        };
+
+        Self::Other(other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::events::context_data::TransmitData;
+    use tokio::sync::Notify;
+
+    struct Blocked {
+        release: Arc<Notify>,
+    }
+
+    #[async_trait]
+    impl EventHandler for Blocked {
+        async fn act(&self, _ctx: &EventContext<'_>) -> Option<Event> {
+            self.release.notified().await;
+            None
+        }
+    }
+
+    #[tokio::test]
+    async fn full_queue_drops_without_blocking() {
+        let release = Arc::new(Notify::new());
+        let handler = QueuedHandler::new(Blocked {
+            release: release.clone(),
+        })
+        .capacity(2);
+        let monitor = handler.monitor();
+
+        let ctx = EventContext::Transmit(TransmitData::default());
+        for _ in 0..5 {
+            assert!(handler.act(&ctx).await.is_none());
+            tokio::task::yield_now().await;
+        }
+
+        // One event is held by the blocked handler, two are queued.
+        assert_eq!(
+            monitor.stats(),
+            QueueStats {
+                handled: 1,
+                dropped: 2,
+            }
+        );
+
+        release.notify_one();
+    }
+}
@@ -69,7 +69,12 @@ impl EventStore {
     }
 
     /// Processes all events due up to and including `now`.
-    pub(crate) async fn process_timed(&mut self, now: Duration, ctx: EventContext<'_>) {
+    pub(crate) async fn process_timed(
+        &mut self,
+        now: Duration,
+        ctx: EventContext<'_>,
+        call: &CallContext,
+    ) {
         while let Some(evt) = self.timed.peek() {
             if evt
                 .fire_time
@@ -85,7 +90,7 @@ impl EventStore {
                 .expect("Can only succeed due to peek = Some(...).");
 
             let old_evt_type = evt.event;
-            if let Some(new_evt_type) = evt.action.act(&ctx).await {
+            if let Some(new_evt_type) = evt.action.act_in_call(&ctx, call).await {
                 evt.event = new_evt_type;
                 self.add_event(evt, now);
             } else if let Event::Periodic(d, _) = old_evt_type {
@@ -111,6 +116,7 @@ impl EventStore {
         now: Duration,
         untimed_event: UntimedEvent,
         ctx: EventContext<'_>,
+        call: &CallContext,
     ) {
         // move a Vec in and out: not too expensive, but could be better.
         // Although it's obvious that moving an event out of one vec and into
@@ -126,7 +132,7 @@ impl EventStore {
             while i < events.len() {
                 let evt = &mut events[i];
                 // Only remove/readd if the event type changes (i.e., Some AND new != old)
-                if let Some(new_evt_type) = evt.action.act(&ctx).await {
+                if let Some(new_evt_type) = evt.action.act_in_call(&ctx, call).await {
                     if evt.event == new_evt_type {
                         let mut evt = events.remove(i);
 
@@ -149,6 +155,7 @@ pub(crate) struct GlobalEvents {
     pub(crate) store: EventStore,
     pub(crate) time: Duration,
     pub(crate) awaiting_tick: HashMap<TrackEvent, Vec<usize>>,
+    pub(crate) call: CallContext,
 }
 
 impl GlobalEvents {
@@ -157,7 +164,9 @@ impl GlobalEvents {
     }
 
     pub(crate) async fn fire_core_event(&mut self, evt: CoreEvent, ctx: EventContext<'_>) {
-        self.store.process_untimed(self.time, evt.into(), ctx).await;
+        self.store
+            .process_untimed(self.time, evt.into(), ctx, &self.call)
+            .await;
     }
 
     pub(crate) fn fire_track_event(&mut self, evt: TrackEvent, index: usize) {
@@ -182,7 +191,7 @@ impl GlobalEvents {
             let global_ctx: Vec<(&TrackState, &TrackHandle)> =
                 states.iter().zip(handles.iter()).collect();
             self.store
-                .process_timed(self.time, EventContext::Track(&global_ctx[..]))
+                .process_timed(self.time, EventContext::Track(&global_ctx[..]), &self.call)
                 .await;
         }
 
@@ -199,7 +208,11 @@ impl GlobalEvents {
                     .expect("Missing handle index for Tick (local timed).");
 
                 event_store
-                    .process_timed(state.play_time, EventContext::Track(&[(state, handle)]))
+                    .process_timed(
+                        state.play_time,
+                        EventContext::Track(&[(state, handle)]),
+                        &self.call,
+                    )
                     .await;
             }
         }
@@ -228,6 +241,7 @@ impl GlobalEvents {
                         state.position,
                         untimed,
                         EventContext::Track(&[(state, handle)]),
+                        &self.call,
                     )
                     .await;
             }
@@ -249,7 +263,12 @@ impl GlobalEvents {
                     .collect();
 
                 self.store
-                    .process_untimed(self.time, untimed, EventContext::Track(&global_ctx[..]))
+                    .process_untimed(
+                        self.time,
+                        untimed,
+                        EventContext::Track(&global_ctx[..]),
+                        &self.call,
+                    )
                     .await;
             }
         }
@@ -25,11 +25,30 @@ pub enum TrackEvent {
     /// The attached track has ended.
     End,
     /// The attached track has looped.
+    ///
+    /// [`TrackState::loop_count`] is incremented before this event fires, while
+    /// [`TrackState::play_time`] continues to accumulate across loops.
+    ///
+    /// [`TrackState::loop_count`]: crate::tracks::TrackState::loop_count
+    /// [`TrackState::play_time`]: crate::tracks::TrackState::play_time
     Loop,
     /// The attached track is being readied or recreated.
     Preparing,
     /// The attached track has become playable.
+    ///
+    /// The time spent preparing the track is available from
+    /// [`TrackState::preparation_time`].
+    ///
+    /// [`TrackState::preparation_time`]: crate::tracks::TrackState::preparation_time
     Playable,
     /// The attached track has encountered a runtime or initialisation error.
     Error,
+    /// The attached track reached the end of a [`Compressed`] or [`Decompressed`]
+    /// cache whose source failed partway through, so its audio was cut short.
+    ///
+    /// This fires just before the track ends or loops.
+    ///
+    /// [`Compressed`]: crate::input::cached::Compressed
+    /// [`Decompressed`]: crate::input::cached::Decompressed
+    Truncated,
 }
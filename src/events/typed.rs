@@ -0,0 +1,204 @@
+//! Typed subscriptions to individual track events.
+//!
+//! Rather than matching on an [`EventContext`] inside an [`EventHandler`], a closure
+//! may be attached to one kind of event using [`TrackHandle::on`]. The closure is
+//! called once per affected track, with a payload specific to that event:
+//!
+//! ```rust,no_run
+//! # use songbird::{events::typed::{Looped, PlaybackEnded}, tracks::TrackHandle};
+//! # fn example(handle: &TrackHandle) {
+//! handle
+//!     .on::<PlaybackEnded>(|ended| println!("{} ended", ended.handle.uuid()))
+//!     .unwrap();
+//! handle
+//!     .on::<Looped>(|looped| println!("looped {} times", looped.loop_count))
+//!     .unwrap();
+//! # }
+//! ```
+//!
+//! Each subscription is registered as an ordinary [`Event::Track`], so only the
+//! chosen event is ever dispatched to it.
+//!
+//! [`EventContext`]: super::EventContext
+//! [`EventHandler`]: super::EventHandler
+//! [`Event::Track`]: super::Event::Track
+//! [`TrackHandle::on`]: crate::tracks::TrackHandle::on
+
+use super::{Event, EventContext, EventHandler, TrackEvent};
+use crate::tracks::{PlayError, PlayMode, TrackHandle, TrackState};
+use async_trait::async_trait;
+use std::{marker::PhantomData, time::Duration};
+
+mod private {
+    pub trait Sealed {}
+}
+
+/// A kind of track event which may be subscribed to with [`TrackHandle::on`].
+///
+/// This trait is sealed, and is implemented by the marker types in this module.
+///
+/// [`TrackHandle::on`]: crate::tracks::TrackHandle::on
+pub trait TypedTrackEvent: private::Sealed + Send + Sync + 'static {
+    /// The underlying event which this subscription listens for.
+    const EVENT: TrackEvent;
+
+    /// Data passed to a subscriber each time this event fires.
+    type Data<'a>;
+
+    /// Builds this event's payload for one affected track, if applicable.
+    #[doc(hidden)]
+    fn extract<'a>(state: &'a TrackState, handle: &'a TrackHandle) -> Option<Self::Data<'a>>;
+}
+
+/// Payload for track events which only describe a change in a track's state.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct TrackChange<'a> {
+    /// Handle of the affected track.
+    pub handle: &'a TrackHandle,
+    /// State of the track when the event fired.
+    pub state: &'a TrackState,
+}
+
+/// Payload for [`Looped`] events.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct LoopData<'a> {
+    /// Handle of the affected track.
+    pub handle: &'a TrackHandle,
+    /// Number of times the track has now looped.
+    pub loop_count: u64,
+    /// Total time the track has been playing for, across all loops.
+    pub play_time: Duration,
+}
+
+/// Payload for [`Errored`] events.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct ErrorData<'a> {
+    /// Handle of the affected track.
+    pub handle: &'a TrackHandle,
+    /// The error which stopped the track.
+    pub error: &'a PlayError,
+}
+
+macro_rules! state_event {
+    ($(#[$meta:meta])* $name:ident => $event:ident) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, Debug)]
+        #[non_exhaustive]
+        pub struct $name;
+
+        impl private::Sealed for $name {}
+
+        impl TypedTrackEvent for $name {
+            const EVENT: TrackEvent = TrackEvent::$event;
+
+            type Data<'a> = TrackChange<'a>;
+
+            fn extract<'a>(state: &'a TrackState, handle: &'a TrackHandle) -> Option<Self::Data<'a>> {
+                Some(TrackChange { handle, state })
+            }
+        }
+    };
+}
+
+state_event!(
+    /// A track has resumed playing, as in [`TrackEvent::Play`].
+    Played => Play
+);
+state_event!(
+    /// A track has been paused, as in [`TrackEvent::Pause`].
+    Paused => Pause
+);
+state_event!(
+    /// A track has ended, been stopped, or failed, as in [`TrackEvent::End`].
+    PlaybackEnded => End
+);
+state_event!(
+    /// A track is being readied or recreated, as in [`TrackEvent::Preparing`].
+    Preparing => Preparing
+);
+state_event!(
+    /// A track has become playable, as in [`TrackEvent::Playable`].
+    Playable => Playable
+);
+state_event!(
+    /// A track's cached audio was cut short, as in [`TrackEvent::Truncated`].
+    Truncated => Truncated
+);
+
+/// A track has looped, as in [`TrackEvent::Loop`].
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct Looped;
+
+impl private::Sealed for Looped {}
+
+impl TypedTrackEvent for Looped {
+    const EVENT: TrackEvent = TrackEvent::Loop;
+
+    type Data<'a> = LoopData<'a>;
+
+    fn extract<'a>(state: &'a TrackState, handle: &'a TrackHandle) -> Option<Self::Data<'a>> {
+        Some(LoopData {
+            handle,
+            loop_count: state.loop_count,
+            play_time: state.play_time,
+        })
+    }
+}
+
+/// A track has encountered a runtime or initialisation error, as in [`TrackEvent::Error`].
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub struct Errored;
+
+impl private::Sealed for Errored {}
+
+impl TypedTrackEvent for Errored {
+    const EVENT: TrackEvent = TrackEvent::Error;
+
+    type Data<'a> = ErrorData<'a>;
+
+    fn extract<'a>(state: &'a TrackState, handle: &'a TrackHandle) -> Option<Self::Data<'a>> {
+        match &state.playing {
+            PlayMode::Errored(error) => Some(ErrorData { handle, error }),
+            _ => None,
+        }
+    }
+}
+
+/// Adapts a typed subscriber closure into an [`EventHandler`].
+pub(crate) struct TypedHandler<E, F> {
+    action: F,
+    event: PhantomData<fn() -> E>,
+}
+
+impl<E, F> TypedHandler<E, F> {
+    pub(crate) fn new(action: F) -> Self {
+        Self {
+            action,
+            event: PhantomData,
+        }
+    }
+}
+
+#[async_trait]
+impl<E, F> EventHandler for TypedHandler<E, F>
+where
+    E: TypedTrackEvent,
+    F: for<'a> Fn(E::Data<'a>) + Send + Sync,
+{
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::Track(tracks) = ctx {
+            for (state, handle) in *tracks {
+                if let Some(data) = E::extract(state, handle) {
+                    (self.action)(data);
+                }
+            }
+        }
+
+        None
+    }
+}
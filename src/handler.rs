@@ -1,5 +1,15 @@
 #[cfg(feature = "driver")]
-use crate::{driver::Driver, error::ConnectionResult};
+use crate::{
+    driver::Driver,
+    error::ConnectionResult,
+    events::{
+        context_data::{ChannelMoveData, DisconnectReason},
+        CoreEvent,
+        Event,
+        EventContext,
+        EventHandler,
+    },
+};
 use crate::{
     error::{JoinError, JoinResult},
     id::{ChannelId, GuildId, UserId},
@@ -8,9 +18,11 @@ use crate::{
     shards::{Shard, VoiceUpdate},
     Config,
 };
+#[cfg(feature = "driver")]
+use async_trait::async_trait;
 use flume::Sender;
 use std::fmt::Debug;
-use tracing::instrument;
+use tracing::{info, instrument, warn};
 
 #[cfg(feature = "driver")]
 use std::ops::{Deref, DerefMut};
@@ -18,14 +30,26 @@ use std::ops::{Deref, DerefMut};
 #[derive(Clone, Debug)]
 enum Return {
     // Return the connection info as it is received.
-    Info(Sender<ConnectionInfo>),
+    Info(Sender<JoinResult<ConnectionInfo>>),
 
     // Two channels: first indicates "gateway connection" was successful,
     // second indicates that the driver successfully connected.
     // The first is needed to cancel a timeout as the driver can/should
     // have separate connection timing/retry config.
     #[cfg(feature = "driver")]
-    Conn(Sender<()>, Sender<ConnectionResult<()>>),
+    Conn(Sender<JoinResult<()>>, Sender<ConnectionResult<()>>),
+}
+
+impl Return {
+    /// Fails the gateway stage of a join.
+    fn fail(&self, e: JoinError) {
+        // It's okay if the receiver hung up.
+        match self {
+            Return::Info(tx) => drop(tx.send(Err(e))),
+            #[cfg(feature = "driver")]
+            Return::Conn(first_tx, _) => drop(first_tx.send(Err(e))),
+        }
+    }
 }
 
 /// The Call handler is responsible for a single voice connection, acting
@@ -41,6 +65,8 @@ pub struct Call {
     config: Config,
 
     connection: Option<(ConnectionProgress, Return)>,
+    /// Whether the pending join has already been re-requested after a conflict.
+    rejoined: bool,
 
     #[cfg(feature = "driver")]
     /// The internal controller of the voice connection monitor thread.
@@ -117,12 +143,28 @@ impl Call {
     }
 
     fn new_raw_cfg(guild_id: GuildId, ws: Option<Shard>, user_id: UserId, config: Config) -> Self {
+        #[cfg(feature = "driver")]
+        let mut driver = Driver::new(config);
+
+        // Driver-initiated leaves must also leave the voice channel via the gateway.
+        #[cfg(feature = "driver")]
+        if let Some(ws) = &ws {
+            driver.add_global_event(
+                CoreEvent::DriverDisconnect.into(),
+                AutoLeaveHandler {
+                    guild_id,
+                    ws: ws.clone(),
+                },
+            );
+        }
+
         Call {
             #[cfg(not(feature = "driver"))]
             config,
             connection: None,
+            rejoined: false,
             #[cfg(feature = "driver")]
-            driver: Driver::new(config),
+            driver,
             guild_id,
             self_deaf: false,
             self_mute: false,
@@ -136,12 +178,12 @@ impl Call {
         match &self.connection {
             Some((ConnectionProgress::Complete(c), Return::Info(tx))) => {
                 // It's okay if the receiver hung up.
-                drop(tx.send(c.clone()));
+                drop(tx.send(Ok(c.clone())));
             },
             #[cfg(feature = "driver")]
             Some((ConnectionProgress::Complete(c), Return::Conn(first_tx, driver_tx))) => {
                 // It's okay if the receiver hung up.
-                _ = first_tx.send(());
+                _ = first_tx.send(Ok(()));
 
                 self.driver.raw_connect(c.clone(), driver_tx.clone());
             },
@@ -228,10 +270,11 @@ impl Call {
         let (gw_tx, gw_rx) = flume::unbounded();
 
         let do_conn = self
-            .should_actually_join(|_| (), &gw_tx, channel_id)
+            .should_actually_join(|_| Ok(()), &gw_tx, channel_id)
             .await?;
 
         if do_conn {
+            self.rejoined = false;
             self.connection = Some((
                 ConnectionProgress::new(self.guild_id, self.user_id, channel_id),
                 Return::Conn(gw_tx, tx),
@@ -288,13 +331,14 @@ impl Call {
 
         let do_conn = self
             .should_actually_join(
-                |call| call.connection.as_ref().unwrap().0.info().unwrap(),
+                |call| Ok(call.connection.as_ref().unwrap().0.info().unwrap()),
                 &tx,
                 channel_id,
             )
             .await?;
 
         if do_conn {
+            self.rejoined = false;
             self.connection = Some((
                 ConnectionProgress::new(self.guild_id, self.user_id, channel_id),
                 Return::Info(tx),
@@ -418,7 +462,22 @@ impl Call {
     }
 
     fn _update_state(&mut self, session_id: String, channel_id: Option<ChannelId>) {
+        if let Some(conflict) = self.join_conflict(&session_id, channel_id) {
+            if !(self.config().rejoin_on_conflict && !self.rejoined && self.rerequest_join()) {
+                warn!("Voice state conflict while joining: {conflict}.");
+                if let Some((_, ret)) = &self.connection {
+                    ret.fail(conflict);
+                }
+                self.leave_local();
+            }
+
+            return;
+        }
+
         if let Some(channel_id) = channel_id {
+            #[cfg(feature = "driver")]
+            self.check_moved(channel_id);
+
             let try_conn = if let Some((ref mut progress, _)) = self.connection.as_mut() {
                 progress.apply_state_update(session_id, channel_id)
             } else {
@@ -434,6 +493,99 @@ impl Call {
         }
     }
 
+    #[cfg(feature = "driver")]
+    /// Announces a move of this bot by someone else into `channel_id`, once connected.
+    ///
+    /// The connection is then re-established to the new channel as its voice server
+    /// details arrive, without affecting the driver's tracks.
+    fn check_moved(&mut self, channel_id: ChannelId) {
+        let Some((ConnectionProgress::Complete(info), _)) = &self.connection else {
+            return;
+        };
+
+        if let Some(from) = info.channel_id.filter(|from| *from != channel_id) {
+            info!("Moved from voice channel {from} to {channel_id}.");
+            self.driver.channel_moved(ChannelMoveData {
+                from,
+                to: channel_id,
+                guild_id: self.guild_id,
+            });
+        }
+    }
+
+    /// Checks whether a voice state update for this bot conflicts with a join in progress.
+    fn join_conflict(&self, session_id: &str, channel_id: Option<ChannelId>) -> Option<JoinError> {
+        let Some((ConnectionProgress::Incomplete(partial), _)) = &self.connection else {
+            return None;
+        };
+
+        match channel_id {
+            None => Some(JoinError::Disconnected),
+            Some(actual) if actual != partial.channel_id => Some(JoinError::Moved {
+                requested: partial.channel_id,
+                actual,
+            }),
+            Some(_)
+                if partial
+                    .session_id
+                    .as_deref()
+                    .is_some_and(|s| s != session_id) =>
+                Some(JoinError::SessionConflict),
+            Some(_) => None,
+        }
+    }
+
+    /// Restarts a join in progress, sending a fresh request for its channel over the gateway.
+    ///
+    /// Returns `false` if there is no gateway connection to send this request over.
+    fn rerequest_join(&mut self) -> bool {
+        let (Some(ws), Some((progress, _))) = (self.ws.clone(), self.connection.as_mut()) else {
+            return false;
+        };
+
+        let channel_id = progress.channel_id();
+        *progress = ConnectionProgress::new(self.guild_id, self.user_id, channel_id);
+        self.rejoined = true;
+
+        info!("Voice state conflict while joining: re-requesting channel {channel_id}.");
+
+        let (guild_id, self_deaf, self_mute) = (self.guild_id, self.self_deaf, self.self_mute);
+        tokio::spawn(async move {
+            if let Err(e) = ws
+                .update_voice_state(guild_id, Some(channel_id), self_deaf, self_mute)
+                .await
+            {
+                warn!("Failed to re-request voice channel: {e}.");
+            }
+        });
+
+        true
+    }
+
+    #[cfg(feature = "driver")]
+    /// Updates the driver's view of another user's voice state, for
+    /// [`Config::alone_timeout`].
+    ///
+    /// [`Songbird`] calls this automatically for all voice state updates it
+    /// receives.
+    ///
+    /// [`Config::alone_timeout`]: crate::Config::alone_timeout
+    /// [`Songbird`]: crate::Songbird
+    #[instrument(skip(self))]
+    pub fn update_member_state<U, C>(&mut self, user_id: U, channel_id: Option<C>)
+    where
+        U: Into<UserId> + Debug,
+        C: Into<ChannelId> + Debug,
+    {
+        let user_id = user_id.into();
+        if user_id == self.user_id {
+            return;
+        }
+
+        let present = channel_id.is_some_and(|c| Some(c.into()) == self.current_channel());
+        self.driver.set_member_present(user_id, present);
+    }
+
     /// Send an update for the current session over WS.
     ///
     /// Does nothing if initialized via [`standalone`].
@@ -455,6 +607,36 @@ impl Call {
     }
 }
 
+/// Leaves a [`Call`]'s voice channel when its driver leaves automatically.
+#[cfg(feature = "driver")]
+struct AutoLeaveHandler {
+    guild_id: GuildId,
+    ws: Shard,
+}
+
+#[cfg(feature = "driver")]
+#[async_trait]
+impl EventHandler for AutoLeaveHandler {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::DriverDisconnect(data) = ctx {
+            if matches!(
+                data.reason,
+                Some(DisconnectReason::Idle | DisconnectReason::Alone)
+            ) {
+                if let Err(e) = self
+                    .ws
+                    .update_voice_state(self.guild_id, None, false, false)
+                    .await
+                {
+                    tracing::warn!("Failed to leave voice channel automatically: {e:?}");
+                }
+            }
+        }
+
+        None
+    }
+}
+
 #[cfg(not(feature = "driver"))]
 impl Call {
     /// Access this call handler's configuration.
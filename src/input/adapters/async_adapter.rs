@@ -15,9 +15,10 @@ use std::{
         Write,
     },
     sync::{
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU64, Ordering},
         Arc,
     },
+    time::Duration,
 };
 use symphonia_core::io::MediaSource;
 use tokio::{
@@ -130,6 +131,93 @@ impl AsyncAdapterSink {
     }
 }
 
+/// How far ahead of playback an [`AsyncAdapterStream`] reads from its source.
+///
+/// Buffered bytes smooth over bitrate spikes and network jitter, and allow short
+/// forward seeks to be served without touching the underlying stream.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Prefetch {
+    /// Buffer up to this many bytes.
+    Bytes(usize),
+    /// Buffer roughly this much audio, assuming the given bitrate in bits per second.
+    Duration {
+        /// Amount of audio to buffer.
+        length: Duration,
+        /// Expected bitrate of the source, in bits per second.
+        bitrate: u32,
+    },
+}
+
+impl Prefetch {
+    /// Returns the size of this window in bytes.
+    ///
+    /// This is always at least one byte.
+    #[must_use]
+    pub fn byte_len(self) -> usize {
+        let len = match self {
+            Self::Bytes(len) => len,
+            Self::Duration { length, bitrate } =>
+                (length.as_secs_f64() * f64::from(bitrate) / 8.0).ceil() as usize,
+        };
+
+        len.max(1)
+    }
+}
+
+impl Default for Prefetch {
+    fn default() -> Self {
+        Self::Bytes(64 * 1024)
+    }
+}
+
+/// Counts of reads and seeks which were served from an [`AsyncAdapterStream`]'s
+/// prefetch buffer.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct PrefetchStats {
+    /// Reads and forward seeks served entirely from buffered data.
+    pub hits: u64,
+    /// Reads which had to wait for the source, and seeks which fell outside the buffer.
+    pub misses: u64,
+}
+
+/// A shared handle to the [`PrefetchStats`] of one input.
+///
+/// Clones of a monitor share the same counters, so a handle kept before an input
+/// is played will observe every stream created for that input, including any
+/// made when resuming after a network error.
+#[derive(Clone, Debug, Default)]
+pub struct PrefetchMonitor {
+    hits: Arc<AtomicU64>,
+    misses: Arc<AtomicU64>,
+}
+
+impl PrefetchMonitor {
+    /// Creates a new monitor with all counters at zero.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the current hit and miss counts.
+    #[must_use]
+    pub fn stats(&self) -> PrefetchStats {
+        PrefetchStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+
+    fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
 /// An adapter for converting an async media source into a synchronous one
 /// usable by symphonia.
 ///
@@ -149,6 +237,9 @@ pub struct AsyncAdapterStream {
     req_tx: Sender<AdapterRequest>,
     resp_rx: Receiver<AdapterResponse>,
     notify_tx: Arc<Notify>,
+    /// Position of the reader within the source.
+    pos: u64,
+    monitor: PrefetchMonitor,
 }
 
 impl AsyncAdapterStream {
@@ -156,6 +247,18 @@ impl AsyncAdapterStream {
     /// between the async and sync halves.
     #[must_use]
     pub fn new(stream: Box<dyn AsyncMediaSource>, buf_len: usize) -> AsyncAdapterStream {
+        Self::new_with_prefetch(stream, Prefetch::Bytes(buf_len), PrefetchMonitor::new())
+    }
+
+    /// Wrap and pull from an async file stream, reading up to `prefetch` ahead of the
+    /// sync half and recording buffer hits and misses in `monitor`.
+    #[must_use]
+    pub fn new_with_prefetch(
+        stream: Box<dyn AsyncMediaSource>,
+        prefetch: Prefetch,
+        monitor: PrefetchMonitor,
+    ) -> AsyncAdapterStream {
+        let buf_len = prefetch.byte_len();
         let (bytes_in, bytes_out) = SharedRb::<Heap<_>>::new(buf_len).split();
         let bytes_out = bytes_out.into();
         let (resp_tx, resp_rx) = flume::unbounded();
@@ -179,6 +282,8 @@ impl AsyncAdapterStream {
             req_tx,
             resp_rx,
             notify_tx,
+            pos: 0,
+            monitor,
         };
 
         tokio::spawn(async move {
@@ -188,6 +293,28 @@ impl AsyncAdapterStream {
         stream
     }
 
+    /// Returns how many reads and seeks have been served from this stream's buffer.
+    #[must_use]
+    pub fn prefetch_stats(&self) -> PrefetchStats {
+        self.monitor.stats()
+    }
+
+    /// Skips forward to `target` within already buffered bytes, if possible.
+    fn seek_buffered(&mut self, target: u64) -> Option<u64> {
+        let skip = usize::try_from(target.checked_sub(self.pos)?).ok()?;
+
+        let mut rb = self.bytes_out.lock();
+        if skip > rb.occupied_len() {
+            return None;
+        }
+
+        rb.skip(skip);
+        self.notify_tx.notify_one();
+        self.pos = target;
+
+        Some(target)
+    }
+
     fn handle_messages(&self, op: Operation) -> Option<AdapterResponse> {
         loop {
             let msg = if op.will_block() {
@@ -233,6 +360,8 @@ impl AsyncAdapterStream {
 
 impl Read for AsyncAdapterStream {
     fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        let mut stalled = false;
+
         loop {
             let block = !(self.bytes_known_present.load(Ordering::Relaxed)
                 || self.finalised.load(Ordering::Relaxed));
@@ -242,6 +371,12 @@ impl Read for AsyncAdapterStream {
             match rb.read(buf) {
                 Ok(n) => {
                     self.notify_tx.notify_one();
+                    self.pos += n as u64;
+                    if stalled {
+                        self.monitor.miss();
+                    } else {
+                        self.monitor.hit();
+                    }
                     return Ok(n);
                 },
                 Err(e) if e.kind() == IoErrorKind::WouldBlock => {
@@ -250,6 +385,7 @@ impl Read for AsyncAdapterStream {
                     if self.finalised.load(Ordering::Relaxed) {
                         return Ok(0);
                     }
+                    stalled = true;
                     self.bytes_known_present.store(false, Ordering::Relaxed);
                     self.check_dropped()?;
                 },
@@ -264,6 +400,21 @@ impl Read for AsyncAdapterStream {
 
 impl Seek for AsyncAdapterStream {
     fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+        let target = match pos {
+            SeekFrom::Start(target) => Some(target),
+            SeekFrom::Current(delta) => self.pos.checked_add_signed(delta),
+            SeekFrom::End(_) => None,
+        };
+
+        // Short forward seeks can be served from the prefetch buffer,
+        // even if the source itself cannot seek.
+        if let Some(new_pos) = target.and_then(|t| self.seek_buffered(t)) {
+            self.monitor.hit();
+            return Ok(new_pos);
+        }
+
+        self.monitor.miss();
+
         if !self.can_seek {
             return Err(IoError::new(
                 IoErrorKind::Unsupported,
@@ -290,11 +441,17 @@ impl Seek for AsyncAdapterStream {
 
         _ = self.req_tx.send(AdapterRequest::SeekCleared);
 
-        match self.handle_messages(Operation::Seek) {
+        let res = match self.handle_messages(Operation::Seek) {
             Some(AdapterResponse::SeekResult(a)) => a,
             None => self.check_dropped().map(|()| unreachable!()),
             _ => unreachable!(),
+        };
+
+        if let Ok(new_pos) = res {
+            self.pos = new_pos;
         }
+
+        res
     }
 }
 
@@ -383,3 +540,59 @@ pub trait AsyncMediaSource: AsyncRead + AsyncSeek + Send + Sync + Unpin {
         Err(AudioStreamError::Unsupported)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[async_trait]
+    impl AsyncMediaSource for Cursor<Vec<u8>> {
+        fn is_seekable(&self) -> bool {
+            false
+        }
+
+        async fn byte_len(&self) -> Option<u64> {
+            Some(self.get_ref().len() as u64)
+        }
+    }
+
+    #[test]
+    fn prefetch_duration_uses_bitrate() {
+        let prefetch = Prefetch::Duration {
+            length: Duration::from_secs(2),
+            bitrate: 128_000,
+        };
+
+        assert_eq!(prefetch.byte_len(), 32_000);
+        assert_eq!(Prefetch::Bytes(0).byte_len(), 1);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn forward_seek_within_prefetch_is_a_hit() {
+        let data: Vec<u8> = (0..4096).map(|i| i as u8).collect();
+        let monitor = PrefetchMonitor::new();
+        let mut stream = AsyncAdapterStream::new_with_prefetch(
+            Box::new(Cursor::new(data)),
+            Prefetch::Bytes(8192),
+            monitor.clone(),
+        );
+
+        tokio::task::spawn_blocking(move || {
+            let mut buf = [0u8; 16];
+            stream.read_exact(&mut buf).unwrap();
+
+            // The source cannot seek, so this must be served from the buffer.
+            assert_eq!(stream.seek(SeekFrom::Current(100)).unwrap(), 116);
+            stream.read_exact(&mut buf[..1]).unwrap();
+            assert_eq!(buf[0], 116);
+
+            assert!(stream.seek(SeekFrom::Start(0)).is_err());
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(monitor.stats(), PrefetchStats { hits: 3, misses: 1 });
+    }
+}
@@ -1,4 +1,19 @@
-use super::{compressed_cost_per_sec, default_config, CodecCacheError, ToAudioBytes};
+use super::{
+    compressed_cost_per_sec,
+    default_config,
+    make_live,
+    refetcher,
+    seek_spilled,
+    CacheHealth,
+    CodecCacheError,
+    Finaliser,
+    GrowthStrategy,
+    SharedSource,
+    SourceState,
+    Spill,
+    SpillSource,
+    ToAudioBytes,
+};
 use crate::{
     constants::*,
     input::{
@@ -18,6 +33,7 @@ use audiopus::{
     SampleRate,
 };
 use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use parking_lot::Mutex;
 use std::{
     io::{
         Cursor,
@@ -29,7 +45,11 @@ use std::{
         SeekFrom,
     },
     mem,
-    sync::atomic::{AtomicUsize, Ordering},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
 };
 use streamcatcher::{
     Config as ScConfig,
@@ -68,6 +88,48 @@ pub struct Config {
     ///
     /// Notably, this governs size hints and resize logic.
     pub streamcatcher: ScConfig,
+    /// Maximum number of bytes to hold in the cache.
+    ///
+    /// Once this is reached, the cache stops storing new data. The first handle to
+    /// read past the end of the cache then receives the rest of the source directly,
+    /// as a pass-through: it may not seek, and other handles see the source end at
+    /// the limit. This bounds the memory used when caching livestreams or other
+    /// sources of unknown length. Set [`Self::spill_dir`] to keep the remainder
+    /// seekable and shared between handles.
+    ///
+    /// Defaults to `None` (unbounded).
+    pub max_size: Option<usize>,
+    /// Directory in which to store audio beyond [`Self::max_size`].
+    ///
+    /// If set, the rest of an overflowed source is written to a temporary file in
+    /// this directory (e.g., [`std::env::temp_dir`]) rather than passed through,
+    /// so that all handles can continue to read and seek through the whole
+    /// source without holding it all in memory. The file is deleted once the
+    /// cache and all of its handles are dropped.
+    ///
+    /// Defaults to `None`.
+    pub spill_dir: Option<PathBuf>,
+    /// Whether to read the whole source into the cache on a background thread as
+    /// soon as it is created, rather than only as quickly as handles read from it.
+    ///
+    /// Defaults to `false`.
+    pub spawn_loader: bool,
+    /// Number of times to recreate the source from its original [`Compose`] if
+    /// reading from it fails partway through.
+    ///
+    /// The recreated source is read up to the point of failure and discarded, so
+    /// that caching continues seamlessly. This has no effect unless the cache is
+    /// built from an [`Input::Lazy`]. If all attempts fail, the cache is
+    /// [`CacheHealth::Truncated`].
+    ///
+    /// Async [`Compose`]s are recreated by blocking on the Tokio runtime the cache
+    /// was created in, so reads which trigger this must not be made from within an
+    /// async context.
+    ///
+    /// Defaults to `0`.
+    ///
+    /// [`Compose`]: crate::input::Compose
+    pub refetch_attempts: usize,
 }
 
 impl Default for Config {
@@ -76,6 +138,10 @@ impl Default for Config {
             codec_registry: &CODEC_REGISTRY,
             format_registry: &PROBE,
             streamcatcher: ScConfig::default(),
+            max_size: None,
+            spill_dir: None,
+            spawn_loader: false,
+            refetch_attempts: 0,
         }
     }
 }
@@ -91,6 +157,79 @@ impl Config {
             ..Default::default()
         }
     }
+
+    /// Sets this cache's registry of audio codecs.
+    #[must_use]
+    pub fn codec_registry(mut self, codec_registry: &'static CodecRegistry) -> Self {
+        self.codec_registry = codec_registry;
+        self
+    }
+
+    /// Sets this cache's registry of container formats.
+    #[must_use]
+    pub fn format_registry(mut self, format_registry: &'static Probe) -> Self {
+        self.format_registry = format_registry;
+        self
+    }
+
+    /// Sets how the cache allocates new chunks of memory as it grows.
+    #[must_use]
+    pub fn chunk_growth(mut self, chunk_size: GrowthStrategy) -> Self {
+        self.streamcatcher.chunk_size = chunk_size;
+        self
+    }
+
+    /// Sets how, and on which thread, the cache is rebuilt into a single
+    /// contiguous buffer once the source has been fully read.
+    #[must_use]
+    pub fn finaliser(mut self, finaliser: Finaliser) -> Self {
+        self.streamcatcher.spawn_finaliser = finaliser;
+        self
+    }
+
+    /// Sets the maximum number of bytes read from the source at once.
+    #[must_use]
+    pub fn read_burst_len(mut self, read_burst_len: usize) -> Self {
+        self.streamcatcher.read_burst_len = read_burst_len;
+        self
+    }
+
+    /// Sets the maximum number of bytes to hold in the cache.
+    ///
+    /// See [`Self::max_size`](#structfield.max_size) for details.
+    #[must_use]
+    pub fn max_size(mut self, max_size: Option<usize>) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets the directory used to store audio beyond the cache's maximum size.
+    ///
+    /// See [`Self::spill_dir`](#structfield.spill_dir) for details.
+    #[must_use]
+    pub fn spill_dir(mut self, spill_dir: Option<PathBuf>) -> Self {
+        self.spill_dir = spill_dir;
+        self
+    }
+
+    /// Sets whether to eagerly read the whole source on a background thread.
+    #[must_use]
+    pub fn spawn_loader(mut self, spawn_loader: bool) -> Self {
+        self.spawn_loader = spawn_loader;
+        self
+    }
+
+    /// Sets the number of times to recreate a failed source from its original
+    /// [`Compose`].
+    ///
+    /// See [`Self::refetch_attempts`](#structfield.refetch_attempts) for details.
+    ///
+    /// [`Compose`]: crate::input::Compose
+    #[must_use]
+    pub fn refetch_attempts(mut self, refetch_attempts: usize) -> Self {
+        self.refetch_attempts = refetch_attempts;
+        self
+    }
 }
 
 /// A wrapper around an existing [`Input`] which compresses
@@ -110,10 +249,15 @@ impl Config {
 /// which can be written out to disk for later use.
 ///
 /// [`Input`]: crate::input::Input
-#[derive(Clone)]
 pub struct Compressed {
     /// Inner shared bytestore.
-    pub raw: TxCatcher<ToAudioBytes, OpusCompressor>,
+    pub raw: TxCatcher<SharedSource<ToAudioBytes>, OpusCompressor>,
+    state: Arc<SourceState<ToAudioBytes>>,
+    stereo: bool,
+    bitrate: Bitrate,
+    pass_through: Option<Mutex<PassThrough>>,
+    spill: Option<Arc<Spill>>,
+    spill_pos: Option<u64>,
 }
 
 impl Compressed {
@@ -124,6 +268,13 @@ impl Compressed {
         Self::with_config(source, bitrate, None).await
     }
 
+    /// Returns the default configuration used by a cache of the given `bitrate`,
+    /// to be customised and passed to [`Self::with_config`].
+    #[must_use]
+    pub fn default_config(bitrate: Bitrate) -> Config {
+        Config::default_from_cost(compressed_cost_per_sec(bitrate))
+    }
+
     /// Wrap an existing [`Input`] with an in-memory store, compressed using Opus, with
     /// custom configuration for both Symphonia and the backing store.
     ///
@@ -133,26 +284,11 @@ impl Compressed {
         bitrate: Bitrate,
         config: Option<Config>,
     ) -> Result<Self, CodecCacheError> {
-        let input = match source {
-            Input::Lazy(mut r) => {
-                let created = if r.should_create_async() {
-                    r.create_async().await.map_err(CodecCacheError::from)
-                } else {
-                    tokio::task::spawn_blocking(move || r.create().map_err(CodecCacheError::from))
-                        .await
-                        .map_err(CodecCacheError::from)
-                        .and_then(|v| v)
-                };
-
-                created.map(LiveInput::Raw)
-            },
-            Input::Live(LiveInput::Parsed(_), _) => Err(CodecCacheError::StreamNotAtStart),
-            Input::Live(a, _rec) => Ok(a),
-        }?;
-
         let cost_per_sec = compressed_cost_per_sec(bitrate);
         let config = config.unwrap_or_else(|| Config::default_from_cost(cost_per_sec));
 
+        let (input, compose) = make_live(source, config.refetch_attempts != 0).await?;
+
         let promoted = tokio::task::spawn_blocking(move || {
             input.promote(config.codec_registry, config.format_registry)
         })
@@ -209,13 +345,35 @@ impl Compressed {
             .write_i32::<LittleEndian>(meta_len)
             .expect("Magic byte writing location guaranteed to be well-founded.");
 
-        let source = ToAudioBytes::new(parsed, Some(2));
+        // The compressor enforces any size limit, so that the cache always ends
+        // on a frame boundary.
+        let (source, state) = SharedSource::new(ToAudioBytes::new(parsed, Some(2)), None);
+        if let Some(compose) = compose {
+            state.set_refetch(
+                config.refetch_attempts,
+                refetcher(compose, &config, Some(2)),
+            );
+        }
+
+        let mut compressor = OpusCompressor::new(encoder, stereo, metabytes);
+        compressor.limit = config.max_size.map(|max| (max, state.clone()));
+
+        let raw = config.streamcatcher.build_tx(source, compressor)?;
 
-        let raw = config
-            .streamcatcher
-            .build_tx(source, OpusCompressor::new(encoder, stereo, metabytes))?;
+        if config.spawn_loader {
+            let mut loader = raw.new_handle();
+            tokio::task::spawn_blocking(move || loader.load_all());
+        }
 
-        Ok(Self { raw })
+        Ok(Self {
+            raw,
+            state,
+            stereo,
+            bitrate,
+            pass_through: None,
+            spill: config.spill_dir.map(|dir| Arc::new(Spill::new(dir))),
+            spill_pos: None,
+        })
     }
 
     /// Acquire a new handle to this object, creating a new
@@ -224,6 +382,100 @@ impl Compressed {
     pub fn new_handle(&self) -> Self {
         Self {
             raw: self.raw.new_handle(),
+            state: self.state.clone(),
+            stereo: self.stereo,
+            bitrate: self.bitrate,
+            pass_through: None,
+            spill: self.spill.clone(),
+            spill_pos: None,
+        }
+    }
+
+    /// Returns whether this cache reached its maximum size, and stopped storing
+    /// new data in memory.
+    #[must_use]
+    pub fn is_overflowed(&self) -> bool {
+        self.state.is_overflowed()
+    }
+
+    /// Returns whether this cache holds all of its source's audio, or why not.
+    #[must_use]
+    pub fn health(&self) -> CacheHealth {
+        match &self.spill {
+            Some(spill) if self.state.is_overflowed() =>
+                self.state.spilled_health(spill.len().is_some()),
+            _ => self.state.health(self.raw.is_finished()),
+        }
+    }
+
+    fn read_spilled(&mut self, pos: u64, buf: &mut [u8]) -> IoResult<usize> {
+        let Some(spill) = &self.spill else {
+            return Ok(0);
+        };
+
+        let (state, stereo, bitrate) = (&self.state, self.stereo, self.bitrate);
+        let n = spill.read_at(pos, buf, || {
+            state
+                .claim()
+                .map(|source| {
+                    PassThrough::new(source, stereo, bitrate)
+                        .map(|pass_through| Box::new(pass_through) as SpillSource)
+                })
+                .transpose()
+        })?;
+
+        self.spill_pos = Some(pos + n as u64);
+
+        Ok(n)
+    }
+}
+
+impl Clone for Compressed {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            state: self.state.clone(),
+            stereo: self.stereo,
+            bitrate: self.bitrate,
+            pass_through: None,
+            spill: self.spill.clone(),
+            spill_pos: None,
+        }
+    }
+}
+
+/// Reader claimed by the first handle to reach the end of an overflowed cache.
+struct PassThrough {
+    source: SharedSource<ToAudioBytes>,
+    compressor: OpusCompressor,
+}
+
+impl PassThrough {
+    fn new(source: SharedSource<ToAudioBytes>, stereo: bool, bitrate: Bitrate) -> IoResult<Self> {
+        let channels = if stereo {
+            Channels::Stereo
+        } else {
+            Channels::Mono
+        };
+
+        let mut encoder = OpusEncoder::new(SampleRate::Hz48000, channels, Application::Audio)
+            .map_err(|e| IoError::new(IoErrorKind::Other, e))?;
+        encoder
+            .set_bitrate(bitrate)
+            .map_err(|e| IoError::new(IoErrorKind::Other, e))?;
+
+        Ok(Self {
+            source,
+            compressor: OpusCompressor::new(encoder, stereo, vec![]),
+        })
+    }
+}
+
+impl Read for PassThrough {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        match self.compressor.transform_read(&mut self.source, buf)? {
+            TransformPosition::Read(n) => Ok(n),
+            TransformPosition::Finished => Ok(0),
         }
     }
 }
@@ -354,6 +606,8 @@ pub struct OpusCompressor {
     stereo_input: bool,
     frame_pos: usize,
     audio_bytes: AtomicUsize,
+    written: usize,
+    limit: Option<(usize, Arc<SourceState<ToAudioBytes>>)>,
 }
 
 impl OpusCompressor {
@@ -365,6 +619,8 @@ impl OpusCompressor {
             stereo_input,
             frame_pos: 0,
             audio_bytes: AtomicUsize::default(),
+            written: 0,
+            limit: None,
         }
     }
 }
@@ -377,13 +633,27 @@ where
         if let Some(prepend) = self.prepend.as_mut() {
             match prepend.read(buf)? {
                 0 => {},
-                n => return Ok(TransformPosition::Read(n)),
+                n => {
+                    self.written += n;
+                    return Ok(TransformPosition::Read(n));
+                },
             }
         }
 
         self.prepend = None;
 
         let output_start = mem::size_of::<u16>();
+        let at_frame_end =
+            self.frame_pos == self.last_frame.len() + output_start || self.last_frame.is_empty();
+
+        // Stop storing frames once full, leaving the rest of the source for a pass-through.
+        if let Some((max_size, state)) = &self.limit {
+            if at_frame_end && self.written >= *max_size {
+                state.overflow();
+                return Ok(TransformPosition::Finished);
+            }
+        }
+
         let mut eof = false;
 
         let mut raw_len = 0;
@@ -396,7 +666,7 @@ where
         };
 
         // Purge old frame and read new, if needed.
-        if self.frame_pos == self.last_frame.len() + output_start || self.last_frame.is_empty() {
+        if at_frame_end {
             self.last_frame.resize(self.last_frame.capacity(), 0);
 
             // We can't use `read_f32_into` because we can't guarantee the buffer will be filled.
@@ -480,6 +750,7 @@ where
             .map(|compressed_sz| {
                 self.audio_bytes
                     .fetch_add(raw_len * mem::size_of::<f32>(), Ordering::Release);
+                self.written += compressed_sz;
 
                 if eof {
                     TransformPosition::Finished
@@ -506,13 +777,50 @@ impl Stateful for OpusCompressor {
 
 impl Read for Compressed {
     fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
-        self.raw.read(buf)
+        let mut n = if let Some(pass_through) = &mut self.pass_through {
+            pass_through.get_mut().read(buf)?
+        } else if let Some(pos) = self.spill_pos {
+            self.read_spilled(pos, buf)?
+        } else {
+            self.raw.read(buf)?
+        };
+
+        if n == 0 && !buf.is_empty() && self.pass_through.is_none() && self.spill_pos.is_none() {
+            if self.spill.is_some() {
+                if self.state.is_overflowed() {
+                    n = self.read_spilled(0, buf)?;
+                }
+            } else if let Some(source) = self.state.claim() {
+                let mut pass_through = PassThrough::new(source, self.stereo, self.bitrate)?;
+                n = pass_through.read(buf)?;
+                self.pass_through = Some(Mutex::new(pass_through));
+            }
+        }
+
+        if n == 0 && !buf.is_empty() {
+            self.state.check_end()?;
+        }
+
+        Ok(n)
     }
 }
 
 impl Seek for Compressed {
     fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
-        self.raw.seek(pos)
+        if self.pass_through.is_some() {
+            return Err(IoErrorKind::Unsupported.into());
+        }
+
+        match &self.spill {
+            Some(spill) => seek_spilled(
+                &mut self.raw,
+                |raw| (raw.is_finished() && self.state.is_overflowed()).then(|| raw.len() as u64),
+                spill,
+                &mut self.spill_pos,
+                pos,
+            ),
+            None => self.raw.seek(pos),
+        }
     }
 }
 
@@ -522,10 +830,13 @@ impl MediaSource for Compressed {
     }
 
     fn byte_len(&self) -> Option<u64> {
-        if self.raw.is_finished() {
+        if !self.raw.is_finished() {
+            None
+        } else if !self.state.is_overflowed() {
             Some(self.raw.len() as u64)
         } else {
-            None
+            let spilled = self.spill.as_ref()?.len()?;
+            Some(self.raw.len() as u64 + spilled)
         }
     }
 }
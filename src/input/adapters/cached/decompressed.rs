@@ -1,9 +1,24 @@
-use super::{compressed::Config, CodecCacheError, ToAudioBytes};
+use super::{
+    compressed::Config,
+    make_live,
+    refetcher,
+    seek_spilled,
+    CacheHealth,
+    CodecCacheError,
+    SharedSource,
+    SourceState,
+    Spill,
+    SpillSource,
+    ToAudioBytes,
+};
 use crate::{
     constants::SAMPLE_RATE_RAW,
     input::{AudioStream, Input, LiveInput, RawAdapter},
 };
-use std::io::{Read, Result as IoResult, Seek, SeekFrom};
+use std::{
+    io::{ErrorKind as IoErrorKind, Read, Result as IoResult, Seek, SeekFrom},
+    sync::Arc,
+};
 use streamcatcher::Catcher;
 use symphonia_core::{audio::Channels, io::MediaSource};
 
@@ -26,10 +41,13 @@ use symphonia_core::{audio::Channels, io::MediaSource};
 ///
 /// [`Input`]: crate::input::Input
 /// [`Compressed`]: super::Compressed
-#[derive(Clone)]
 pub struct Decompressed {
     /// Inner shared bytestore.
-    pub raw: Catcher<RawAdapter<ToAudioBytes>>,
+    pub raw: Catcher<RawAdapter<SharedSource<ToAudioBytes>>>,
+    state: Arc<SourceState<ToAudioBytes>>,
+    pass_through: Option<SharedSource<ToAudioBytes>>,
+    spill: Option<Arc<Spill>>,
+    spill_pos: Option<u64>,
 }
 
 impl Decompressed {
@@ -40,6 +58,13 @@ impl Decompressed {
         Self::with_config(source, None).await
     }
 
+    /// Returns the default configuration used by this cache, to be customised
+    /// and passed to [`Self::with_config`].
+    #[must_use]
+    pub fn default_config() -> Config {
+        Config::default_from_cost(super::raw_cost_per_sec(true))
+    }
+
     /// Wrap an existing [`Input`] with an in-memory store, decompressed into `f32` PCM audio,
     /// with custom configuration for both Symphonia and the backing store.
     ///
@@ -48,26 +73,11 @@ impl Decompressed {
         source: Input,
         config: Option<Config>,
     ) -> Result<Self, CodecCacheError> {
-        let input = match source {
-            Input::Lazy(mut r) => {
-                let created = if r.should_create_async() {
-                    r.create_async().await.map_err(CodecCacheError::from)
-                } else {
-                    tokio::task::spawn_blocking(move || r.create().map_err(CodecCacheError::from))
-                        .await
-                        .map_err(CodecCacheError::from)
-                        .and_then(|v| v)
-                };
-
-                created.map(LiveInput::Raw)
-            },
-            Input::Live(LiveInput::Parsed(_), _) => Err(CodecCacheError::StreamNotAtStart),
-            Input::Live(a, _rec) => Ok(a),
-        }?;
-
         let cost_per_sec = super::raw_cost_per_sec(true);
         let config = config.unwrap_or_else(|| Config::default_from_cost(cost_per_sec));
 
+        let (input, compose) = make_live(source, config.refetch_attempts != 0).await?;
+
         let promoted = tokio::task::spawn_blocking(move || {
             input.promote(config.codec_registry, config.format_registry)
         })
@@ -85,15 +95,31 @@ impl Decompressed {
             .ok_or(CodecCacheError::UnknownChannelCount)?;
         let sample_rate = SAMPLE_RATE_RAW as u32;
 
-        let source = RawAdapter::new(
-            ToAudioBytes::new(parsed, Some(chan_count)),
-            sample_rate,
-            chan_count as u32,
-        );
+        let (source, state) =
+            SharedSource::new(ToAudioBytes::new(parsed, Some(chan_count)), config.max_size);
+        if let Some(compose) = compose {
+            state.set_refetch(
+                config.refetch_attempts,
+                refetcher(compose, &config, Some(chan_count)),
+            );
+        }
+
+        let source = RawAdapter::new(source, sample_rate, chan_count as u32);
 
         let raw = config.streamcatcher.build(source)?;
 
-        Ok(Self { raw })
+        if config.spawn_loader {
+            let mut loader = raw.new_handle();
+            tokio::task::spawn_blocking(move || loader.load_all());
+        }
+
+        Ok(Self {
+            raw,
+            state,
+            pass_through: None,
+            spill: config.spill_dir.map(|dir| Arc::new(Spill::new(dir))),
+            spill_pos: None,
+        })
     }
 
     /// Acquire a new handle to this object, creating a new
@@ -102,19 +128,103 @@ impl Decompressed {
     pub fn new_handle(&self) -> Self {
         Self {
             raw: self.raw.new_handle(),
+            state: self.state.clone(),
+            pass_through: None,
+            spill: self.spill.clone(),
+            spill_pos: None,
+        }
+    }
+
+    /// Returns whether this cache reached its maximum size, and stopped storing
+    /// new data in memory.
+    #[must_use]
+    pub fn is_overflowed(&self) -> bool {
+        self.state.is_overflowed()
+    }
+
+    /// Returns whether this cache holds all of its source's audio, or why not.
+    #[must_use]
+    pub fn health(&self) -> CacheHealth {
+        match &self.spill {
+            Some(spill) if self.state.is_overflowed() =>
+                self.state.spilled_health(spill.len().is_some()),
+            _ => self.state.health(self.raw.is_finished()),
+        }
+    }
+
+    fn read_spilled(&mut self, pos: u64, buf: &mut [u8]) -> IoResult<usize> {
+        let Some(spill) = &self.spill else {
+            return Ok(0);
+        };
+
+        let state = &self.state;
+        let n = spill.read_at(pos, buf, || {
+            Ok(state.claim().map(|source| Box::new(source) as SpillSource))
+        })?;
+
+        self.spill_pos = Some(pos + n as u64);
+
+        Ok(n)
+    }
+}
+
+impl Clone for Decompressed {
+    fn clone(&self) -> Self {
+        Self {
+            raw: self.raw.clone(),
+            state: self.state.clone(),
+            pass_through: None,
+            spill: self.spill.clone(),
+            spill_pos: None,
         }
     }
 }
 
 impl Read for Decompressed {
     fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
-        self.raw.read(buf)
+        let mut n = if let Some(pass_through) = &mut self.pass_through {
+            pass_through.read(buf)?
+        } else if let Some(pos) = self.spill_pos {
+            self.read_spilled(pos, buf)?
+        } else {
+            self.raw.read(buf)?
+        };
+
+        if n == 0 && !buf.is_empty() && self.pass_through.is_none() && self.spill_pos.is_none() {
+            if self.spill.is_some() {
+                if self.state.is_overflowed() {
+                    n = self.read_spilled(0, buf)?;
+                }
+            } else if let Some(mut pass_through) = self.state.claim() {
+                n = pass_through.read(buf)?;
+                self.pass_through = Some(pass_through);
+            }
+        }
+
+        if n == 0 && !buf.is_empty() {
+            self.state.check_end()?;
+        }
+
+        Ok(n)
     }
 }
 
 impl Seek for Decompressed {
     fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
-        self.raw.seek(pos)
+        if self.pass_through.is_some() {
+            return Err(IoErrorKind::Unsupported.into());
+        }
+
+        match &self.spill {
+            Some(spill) => seek_spilled(
+                &mut self.raw,
+                |raw| (raw.is_finished() && self.state.is_overflowed()).then(|| raw.len() as u64),
+                spill,
+                &mut self.spill_pos,
+                pos,
+            ),
+            None => self.raw.seek(pos),
+        }
     }
 }
 
@@ -124,10 +234,13 @@ impl MediaSource for Decompressed {
     }
 
     fn byte_len(&self) -> Option<u64> {
-        if self.raw.is_finished() {
+        if !self.raw.is_finished() {
+            None
+        } else if !self.state.is_overflowed() {
             Some(self.raw.len() as u64)
         } else {
-            None
+            let spilled = self.spill.as_ref()?.len()?;
+            Some(self.raw.len() as u64 + spilled)
         }
     }
 }
@@ -138,3 +251,203 @@ impl From<Decompressed> for Input {
         Input::Live(LiveInput::Raw(AudioStream { input, hint: None }), None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::STEREO_FRAME_SIZE,
+        input::{cached::CacheTruncated, AudioStreamError, Compose},
+        test_utils,
+    };
+    use std::{
+        io::{Cursor, Error as IoError, ErrorKind as IoErrorKind},
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    const FAIL_AT: u64 = 100_000;
+
+    /// Raw audio whose first instance fails partway through.
+    struct Flaky {
+        data: Vec<u8>,
+        creations: Arc<AtomicUsize>,
+    }
+
+    struct FlakySource {
+        data: Cursor<Vec<u8>>,
+        fail_at: Option<u64>,
+    }
+
+    impl Read for FlakySource {
+        fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+            if self.fail_at.is_some_and(|pos| self.data.position() >= pos) {
+                return Err(IoError::new(IoErrorKind::Other, "connection reset"));
+            }
+
+            self.data.read(buf)
+        }
+    }
+
+    impl Seek for FlakySource {
+        fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+            self.data.seek(pos)
+        }
+    }
+
+    impl MediaSource for FlakySource {
+        fn is_seekable(&self) -> bool {
+            false
+        }
+
+        fn byte_len(&self) -> Option<u64> {
+            None
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl Compose for Flaky {
+        fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+            let first = self.creations.fetch_add(1, Ordering::SeqCst) == 0;
+
+            Ok(AudioStream {
+                input: Box::new(FlakySource {
+                    data: Cursor::new(self.data.clone()),
+                    fail_at: first.then_some(FAIL_AT),
+                }),
+                hint: None,
+            })
+        }
+
+        async fn create_async(
+            &mut self,
+        ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+            self.create()
+        }
+
+        fn should_create_async(&self) -> bool {
+            false
+        }
+    }
+
+    fn flaky_input(creations: &Arc<AtomicUsize>) -> Input {
+        let floats = test_utils::make_sine(50 * STEREO_FRAME_SIZE, true);
+        let mut data = vec![];
+        RawAdapter::new(Cursor::new(floats), 48_000, 2)
+            .read_to_end(&mut data)
+            .unwrap();
+
+        Input::Lazy(Box::new(Flaky {
+            data,
+            creations: creations.clone(),
+        }))
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn failed_source_truncates_cache() {
+        let creations = Arc::new(AtomicUsize::new(0));
+        let mut cache = Decompressed::new(flaky_input(&creations)).await.unwrap();
+
+        let mut out = vec![];
+        let err = cache.read_to_end(&mut out).unwrap_err();
+
+        assert!(err.get_ref().unwrap().is::<CacheTruncated>());
+        assert!(matches!(cache.health(), CacheHealth::Truncated(_)));
+        assert!((out.len() as u64) <= FAIL_AT);
+        assert_eq!(creations.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn failed_source_is_refetched() {
+        let creations = Arc::new(AtomicUsize::new(0));
+        let config = Decompressed::default_config().refetch_attempts(1);
+        let mut cache = Decompressed::with_config(flaky_input(&creations), Some(config))
+            .await
+            .unwrap();
+
+        let mut out = vec![];
+        cache.read_to_end(&mut out).unwrap();
+
+        assert!(matches!(cache.health(), CacheHealth::Complete));
+        assert_eq!(creations.load(Ordering::SeqCst), 2);
+
+        // Later instances of the source do not fail.
+        let mut expected = vec![];
+        Decompressed::new(flaky_input(&Arc::new(AtomicUsize::new(1))))
+            .await
+            .unwrap()
+            .read_to_end(&mut expected)
+            .unwrap();
+
+        assert_eq!(out, expected);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn overflowed_cache_passes_through_remainder() {
+        const MAX_SIZE: usize = 4096;
+
+        let floats = test_utils::make_sine(50 * STEREO_FRAME_SIZE, true);
+        let input: Input = RawAdapter::new(Cursor::new(floats), 48_000, 2).into();
+
+        let config = Decompressed::default_config().max_size(Some(MAX_SIZE));
+        let mut cache = Decompressed::with_config(input, Some(config))
+            .await
+            .unwrap();
+        let mut late = cache.new_handle();
+
+        // The first reader to pass the limit receives the whole stream...
+        let mut all = vec![];
+        cache.read_to_end(&mut all).unwrap();
+        assert!(cache.is_overflowed());
+        assert_eq!(cache.byte_len(), None);
+        assert!(all.len() > 16 + MAX_SIZE);
+        assert!(cache.seek(SeekFrom::Start(0)).is_err());
+
+        // ...while later readers only see what was cached.
+        let mut cached = vec![];
+        late.read_to_end(&mut cached).unwrap();
+        assert_eq!(cached.len(), 16 + MAX_SIZE);
+        assert_eq!(&cached[..], &all[..cached.len()]);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn overflowed_cache_spills_to_disk() {
+        const MAX_SIZE: usize = 4096;
+
+        let floats = test_utils::make_sine(50 * STEREO_FRAME_SIZE, true);
+        let input: Input = RawAdapter::new(Cursor::new(floats), 48_000, 2).into();
+
+        let config = Decompressed::default_config()
+            .max_size(Some(MAX_SIZE))
+            .spill_dir(Some(std::env::temp_dir()));
+        let mut cache = Decompressed::with_config(input, Some(config))
+            .await
+            .unwrap();
+        let mut late = cache.new_handle();
+
+        let mut all = vec![];
+        cache.read_to_end(&mut all).unwrap();
+        assert!(cache.is_overflowed());
+        assert!(matches!(cache.health(), CacheHealth::Complete));
+        assert_eq!(cache.byte_len(), Some(all.len() as u64));
+
+        // Every handle sees the whole stream, and may seek into the spilled part.
+        let mut spilled = vec![];
+        late.read_to_end(&mut spilled).unwrap();
+        assert_eq!(spilled, all);
+
+        let pos = (16 + 2 * MAX_SIZE) as u64;
+        assert_eq!(late.seek(SeekFrom::Start(pos)).unwrap(), pos);
+        let mut tail = vec![];
+        late.read_to_end(&mut tail).unwrap();
+        assert_eq!(&tail[..], &all[pos as usize..]);
+
+        late.seek(SeekFrom::Start(0)).unwrap();
+        let mut head = [0u8; 16];
+        late.read_exact(&mut head).unwrap();
+        assert_eq!(&head[..], &all[..16]);
+    }
+}
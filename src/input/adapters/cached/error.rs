@@ -142,3 +142,22 @@ impl From<SymphError> for CodecCacheError {
         Self::Parse(val)
     }
 }
+
+/// Error returned by reads from a [`Compressed`] or [`Decompressed`] cache in place of
+/// the end of the stream, when its source failed partway through.
+///
+/// The driver reports this as a [`TrackEvent::Truncated`] when the track ends.
+///
+/// [`Compressed`]: super::Compressed
+/// [`Decompressed`]: super::Decompressed
+/// [`TrackEvent::Truncated`]: crate::events::TrackEvent::Truncated
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheTruncated;
+
+impl Display for CacheTruncated {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("cached audio ended early: its source failed partway through")
+    }
+}
+
+impl StdError for CacheTruncated {}
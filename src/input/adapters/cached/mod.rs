@@ -6,16 +6,19 @@ mod decompressed;
 mod error;
 mod hint;
 mod memory;
+mod source;
+mod spill;
 mod util;
 
-pub(crate) use self::util::*;
-pub use self::{compressed::*, decompressed::*, error::*, hint::*, memory::*};
+pub use self::{compressed::*, decompressed::*, error::*, hint::*, memory::*, source::CacheHealth};
+pub(crate) use self::{source::*, spill::*, util::*};
 
 use crate::constants::*;
 use crate::input::utils;
 use audiopus::Bitrate;
 use std::{mem, time::Duration};
-use streamcatcher::{Config as ScConfig, GrowthStrategy};
+use streamcatcher::Config as ScConfig;
+pub use streamcatcher::{Finaliser, GrowthStrategy};
 
 /// Estimates the cost, in B/s, of audio data compressed at the given bitrate.
 #[must_use]
@@ -0,0 +1,335 @@
+use super::{compressed::Config, CacheTruncated, CodecCacheError, ToAudioBytes};
+use crate::input::{Compose, Input, LiveInput};
+use parking_lot::Mutex;
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    io::{
+        self,
+        Error as IoError,
+        ErrorKind as IoErrorKind,
+        Read,
+        Result as IoResult,
+        Seek,
+        SeekFrom,
+    },
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+use symphonia_core::io::MediaSource;
+use tokio::runtime::Handle;
+
+/// The state of the data held by a [`Compressed`] or [`Decompressed`] cache.
+///
+/// [`Compressed`]: super::Compressed
+/// [`Decompressed`]: super::Decompressed
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum CacheHealth {
+    /// The source is still being read into the cache.
+    Loading,
+    /// The whole source is held in the cache.
+    Complete,
+    /// The cache reached its maximum size, and stopped storing new data.
+    Overflowed,
+    /// Reading the source failed partway through, and could not be recovered
+    /// by recreating it.
+    ///
+    /// The cache holds only the audio preceding the failure, and handles
+    /// reaching its end report [`CacheTruncated`].
+    Truncated(Arc<IoError>),
+}
+
+impl CacheHealth {
+    /// Returns whether the cache holds, or will hold, all of its source's audio.
+    #[must_use]
+    pub fn is_healthy(&self) -> bool {
+        matches!(self, Self::Loading | Self::Complete)
+    }
+}
+
+/// Input source of a cache, shared with any pass-through reader created once
+/// the cache exceeds its configured maximum size.
+///
+/// Created and managed by [`Compressed`] and [`Decompressed`].
+///
+/// [`Compressed`]: super::Compressed
+/// [`Decompressed`]: super::Decompressed
+pub struct SharedSource<T> {
+    shared: Arc<SourceState<T>>,
+    remaining: Option<usize>,
+}
+
+/// Function used to recreate a failed source from scratch.
+type MakeSource<T> = Box<dyn FnMut() -> IoResult<T> + Send>;
+
+struct Inner<T> {
+    source: T,
+    /// Bytes read from all instances of the source so far.
+    pos: u64,
+    refetch: Option<MakeSource<T>>,
+    refetch_attempts: usize,
+}
+
+impl<T: Read> Inner<T> {
+    /// Replaces the source with a new instance positioned at the point of failure,
+    /// returning the last error if this is not possible.
+    fn refetch(&mut self, mut err: IoError) -> IoResult<()> {
+        while self.refetch_attempts > 0 {
+            let Some(remake) = self.refetch.as_mut() else {
+                break;
+            };
+
+            self.refetch_attempts -= 1;
+
+            let pos = self.pos;
+            let made = remake().and_then(|mut source| {
+                let skipped = io::copy(&mut source.by_ref().take(pos), &mut io::sink())?;
+
+                if skipped == pos {
+                    Ok(source)
+                } else {
+                    Err(IoErrorKind::UnexpectedEof.into())
+                }
+            });
+
+            match made {
+                Ok(source) => {
+                    self.source = source;
+                    return Ok(());
+                },
+                Err(e) => err = e,
+            }
+        }
+
+        Err(err)
+    }
+}
+
+/// State shared between a cache's source and all of its handles.
+pub(crate) struct SourceState<T> {
+    inner: Mutex<Inner<T>>,
+    overflowed: AtomicBool,
+    claimed: AtomicBool,
+    error: Mutex<Option<Arc<IoError>>>,
+}
+
+impl<T> SourceState<T> {
+    /// Returns whether the cache stopped storing new data after reaching its
+    /// maximum size.
+    pub(crate) fn is_overflowed(&self) -> bool {
+        self.overflowed.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn overflow(&self) {
+        self.overflowed.store(true, Ordering::Release);
+    }
+
+    /// Returns the error which ended the source early, if any.
+    pub(crate) fn error(&self) -> Option<Arc<IoError>> {
+        self.error.lock().clone()
+    }
+
+    /// Returns the health of a cache built on this source, given whether the cache
+    /// has finished reading from it.
+    pub(crate) fn health(&self, finished: bool) -> CacheHealth {
+        if let Some(e) = self.error() {
+            CacheHealth::Truncated(e)
+        } else if self.is_overflowed() {
+            CacheHealth::Overflowed
+        } else if finished {
+            CacheHealth::Complete
+        } else {
+            CacheHealth::Loading
+        }
+    }
+
+    /// Returns the health of an overflowed cache whose remainder is spilled to disk,
+    /// given whether the whole source has been spilled.
+    pub(crate) fn spilled_health(&self, finished: bool) -> CacheHealth {
+        if let Some(e) = self.error() {
+            CacheHealth::Truncated(e)
+        } else if finished {
+            CacheHealth::Complete
+        } else {
+            CacheHealth::Loading
+        }
+    }
+
+    /// Converts the end of a handle's stream into a [`CacheTruncated`] error
+    /// if the source failed partway through.
+    pub(crate) fn check_end(&self) -> IoResult<()> {
+        if self.error.lock().is_some() {
+            Err(IoError::other(CacheTruncated))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Hands the remainder of the source to the caller, if the cache has
+    /// overflowed and no other handle has already claimed it.
+    pub(crate) fn claim(self: &Arc<Self>) -> Option<SharedSource<T>> {
+        (self.is_overflowed() && !self.claimed.swap(true, Ordering::AcqRel)).then(|| SharedSource {
+            shared: self.clone(),
+            remaining: None,
+        })
+    }
+
+    /// Allows the source to be recreated up to `attempts` times if reading from it fails.
+    pub(crate) fn set_refetch(&self, attempts: usize, make: MakeSource<T>) {
+        let mut inner = self.inner.lock();
+        inner.refetch = Some(make);
+        inner.refetch_attempts = attempts;
+    }
+}
+
+impl<T> Debug for SourceState<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("SourceState")
+            .field("overflowed", &self.overflowed)
+            .field("claimed", &self.claimed)
+            .field("error", &self.error)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> SharedSource<T> {
+    /// Wraps `source` for use in a cache, ending the stream seen by the cache
+    /// after `limit` bytes if set.
+    pub(crate) fn new(source: T, limit: Option<usize>) -> (Self, Arc<SourceState<T>>) {
+        let shared = Arc::new(SourceState {
+            inner: Mutex::new(Inner {
+                source,
+                pos: 0,
+                refetch: None,
+                refetch_attempts: 0,
+            }),
+            overflowed: AtomicBool::new(false),
+            claimed: AtomicBool::new(false),
+            error: Mutex::new(None),
+        });
+
+        let out = Self {
+            shared: shared.clone(),
+            remaining: limit,
+        };
+
+        (out, shared)
+    }
+}
+
+impl<T: Read> Read for SharedSource<T> {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        let buf = match self.remaining {
+            Some(0) => {
+                self.shared.overflow();
+                return Ok(0);
+            },
+            Some(n) => {
+                let len = n.min(buf.len());
+                &mut buf[..len]
+            },
+            None => buf,
+        };
+
+        let mut inner = self.shared.inner.lock();
+
+        // Failures end the stream, so that caches are finalised with all data
+        // read up to that point.
+        let n = loop {
+            match inner.source.read(buf) {
+                Ok(n) => break n,
+                Err(e) if e.kind() == IoErrorKind::Interrupted => {},
+                Err(e) => match inner.refetch(e) {
+                    Ok(()) => {},
+                    Err(e) => {
+                        *self.shared.error.lock() = Some(Arc::new(e));
+                        break 0;
+                    },
+                },
+            }
+        };
+
+        inner.pos += n as u64;
+
+        if let Some(remaining) = self.remaining.as_mut() {
+            *remaining -= n;
+        }
+
+        Ok(n)
+    }
+}
+
+impl<T> Seek for SharedSource<T> {
+    fn seek(&mut self, _pos: SeekFrom) -> IoResult<u64> {
+        Err(IoErrorKind::Unsupported.into())
+    }
+}
+
+impl<T: Read + Send> MediaSource for SharedSource<T> {
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Creates the live stream to be cached, returning the [`Compose`] which made
+/// it if the stream may need to be recreated later.
+pub(crate) async fn make_live(
+    source: Input,
+    keep_compose: bool,
+) -> Result<(LiveInput, Option<Box<dyn Compose>>), CodecCacheError> {
+    match source {
+        Input::Lazy(mut r) => {
+            let (r, created) = if r.should_create_async() {
+                let created = r.create_async().await.map_err(CodecCacheError::from);
+                (r, created)
+            } else {
+                tokio::task::spawn_blocking(move || {
+                    let created = r.create().map_err(CodecCacheError::from);
+                    (r, created)
+                })
+                .await?
+            };
+
+            Ok((LiveInput::Raw(created?), keep_compose.then_some(r)))
+        },
+        Input::Live(LiveInput::Parsed(_), _) => Err(CodecCacheError::StreamNotAtStart),
+        Input::Live(a, _rec) => Ok((a, None)),
+    }
+}
+
+/// Builds a function recreating a cache's decoded source from its original [`Compose`].
+///
+/// Async [`Compose`]s are driven using the current Tokio runtime.
+pub(crate) fn refetcher(
+    mut compose: Box<dyn Compose>,
+    config: &Config,
+    chan_limit: Option<usize>,
+) -> MakeSource<ToAudioBytes> {
+    let codec_registry = config.codec_registry;
+    let format_registry = config.format_registry;
+    let handle = Handle::current();
+
+    Box::new(move || {
+        let created = if compose.should_create_async() {
+            handle.block_on(compose.create_async())
+        } else {
+            compose.create()
+        }
+        .map_err(IoError::other)?;
+
+        let LiveInput::Parsed(parsed) = LiveInput::Raw(created)
+            .promote(codec_registry, format_registry)
+            .map_err(IoError::other)?
+        else {
+            unreachable!()
+        };
+
+        Ok(ToAudioBytes::new(parsed, chan_limit))
+    })
+}
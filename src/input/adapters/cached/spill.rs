@@ -0,0 +1,203 @@
+use parking_lot::Mutex;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{
+        Error as IoError,
+        ErrorKind as IoErrorKind,
+        Read,
+        Result as IoResult,
+        Seek,
+        SeekFrom,
+        Write,
+    },
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+/// Number of bytes moved from a cache's source to its spill file at once.
+const SPILL_CHUNK_LEN: usize = 64 * 1024;
+
+static SPILL_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Source of the bytes beyond a cache's in-memory limit.
+pub(crate) type SpillSource = Box<dyn Read + Send>;
+
+/// Disk tier of a cache, holding the part of its stream beyond `max_size`.
+///
+/// The first handle to read past the in-memory cache claims the rest of the
+/// source, which is then written to a temporary file as handles read ahead.
+/// All handles may read and seek within this file, which is deleted once the
+/// last handle is dropped.
+pub(crate) struct Spill {
+    dir: PathBuf,
+    file: Mutex<Option<SpillFile>>,
+}
+
+struct SpillFile {
+    file: File,
+    path: PathBuf,
+    source: Option<SpillSource>,
+    len: u64,
+}
+
+impl Spill {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            file: Mutex::new(None),
+        }
+    }
+
+    /// Reads spilled bytes from `pos` onwards, first taking the source via `claim`
+    /// if nothing has yet been spilled.
+    ///
+    /// `claim` returns `None` if the in-memory cache holds the whole source.
+    pub(crate) fn read_at(
+        &self,
+        pos: u64,
+        buf: &mut [u8],
+        claim: impl FnOnce() -> IoResult<Option<SpillSource>>,
+    ) -> IoResult<usize> {
+        let mut file = self.file.lock();
+
+        let file = match &mut *file {
+            Some(file) => file,
+            empty => match claim()? {
+                Some(source) => empty.insert(SpillFile::create(&self.dir, source)?),
+                None => return Ok(0),
+            },
+        };
+
+        file.fill_to(pos + buf.len() as u64)?;
+        file.read_at(pos, buf)
+    }
+
+    /// Returns the number of spilled bytes, once the whole source has been spilled.
+    pub(crate) fn len(&self) -> Option<u64> {
+        self.file
+            .lock()
+            .as_ref()
+            .and_then(|file| file.source.is_none().then_some(file.len))
+    }
+}
+
+impl SpillFile {
+    fn create(dir: &Path, source: SpillSource) -> IoResult<Self> {
+        let path = dir.join(format!(
+            "songbird-cache-{}-{}.spill",
+            std::process::id(),
+            SPILL_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+
+        Ok(Self {
+            file,
+            path,
+            source: Some(source),
+            len: 0,
+        })
+    }
+
+    /// Moves bytes from the source into the file until it holds `target` bytes,
+    /// or the source ends.
+    fn fill_to(&mut self, target: u64) -> IoResult<()> {
+        let mut chunk = vec![];
+
+        while self.len < target {
+            let Some(source) = self.source.as_mut() else {
+                break;
+            };
+
+            chunk.resize(SPILL_CHUNK_LEN, 0);
+            let n = match source.read(&mut chunk) {
+                Ok(n) => n,
+                Err(e) if e.kind() == IoErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            if n == 0 {
+                self.source = None;
+                break;
+            }
+
+            self.file.seek(SeekFrom::Start(self.len))?;
+            self.file.write_all(&chunk[..n])?;
+            self.len += n as u64;
+        }
+
+        Ok(())
+    }
+
+    fn read_at(&mut self, pos: u64, buf: &mut [u8]) -> IoResult<usize> {
+        let available = self.len.saturating_sub(pos).min(buf.len() as u64) as usize;
+        if available == 0 {
+            return Ok(0);
+        }
+
+        self.file.seek(SeekFrom::Start(pos))?;
+        self.file.read_exact(&mut buf[..available])?;
+
+        Ok(available)
+    }
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        drop(fs::remove_file(&self.path));
+    }
+}
+
+/// Seeks a cache handle backed by both an in-memory cache (`raw`) and a [`Spill`].
+///
+/// `mem_len` returns the length of the in-memory cache once it has overflowed,
+/// and `spill_pos` holds the handle's position within the spill file, if reading
+/// from it.
+pub(crate) fn seek_spilled<R: Seek>(
+    raw: &mut R,
+    mem_len: impl Fn(&R) -> Option<u64>,
+    spill: &Spill,
+    spill_pos: &mut Option<u64>,
+    pos: SeekFrom,
+) -> IoResult<u64> {
+    let invalid = || IoError::from(IoErrorKind::InvalidInput);
+
+    let target = match pos {
+        SeekFrom::Start(p) => p,
+        SeekFrom::Current(delta) => {
+            let current = match (*spill_pos, mem_len(raw)) {
+                (Some(p), Some(mem)) => mem + p,
+                _ => raw.stream_position()?,
+            };
+            current.checked_add_signed(delta).ok_or_else(invalid)?
+        },
+        SeekFrom::End(delta) => {
+            let total = mem_len(raw)
+                .zip(spill.len())
+                .map(|(mem, spilled)| mem + spilled)
+                .ok_or_else(|| IoError::from(IoErrorKind::Unsupported))?;
+            total.checked_add_signed(delta).ok_or_else(invalid)?
+        },
+    };
+
+    if let Some(mem) = mem_len(raw).filter(|mem| target >= *mem) {
+        *spill_pos = Some(target - mem);
+        return Ok(target);
+    }
+
+    *spill_pos = None;
+    let reached = raw.seek(SeekFrom::Start(target));
+
+    // Seeking past the end of a still-loading cache may be what overflows it.
+    match mem_len(raw).filter(|mem| target >= *mem) {
+        Some(mem) if reached.as_ref().map_or(true, |reached| *reached < target) => {
+            *spill_pos = Some(target - mem);
+            Ok(target)
+        },
+        _ => reached,
+    }
+}
@@ -3,13 +3,14 @@ use crate::{constants::*, driver::tasks::mixer::mix_logic, input::Parsed};
 use byteorder::{LittleEndian, WriteBytesExt};
 use rubato::{FftFixedOut, Resampler};
 use std::{
-    io::{ErrorKind as IoErrorKind, Read, Result as IoResult, Seek, Write},
+    io::{Error as IoError, ErrorKind as IoErrorKind, Read, Result as IoResult, Seek, Write},
     mem,
     ops::Range,
 };
 use symphonia_core::{
     audio::{AudioBuffer, AudioBufferRef, Layout, Signal, SignalSpec},
     conv::IntoSample,
+    errors::Error as SymphError,
     io::MediaSource,
     sample::Sample,
 };
@@ -28,6 +29,8 @@ pub struct ToAudioBytes {
     inner_pos: Range<usize>,
     resample: Option<ResampleState>,
     done: bool,
+    /// Failure which ended the stream early, reported once all prior audio is read.
+    error: Option<SymphError>,
 
     interrupted_samples: Vec<f32>,
     interrupted_byte_pos: Range<usize>,
@@ -105,6 +108,7 @@ impl ToAudioBytes {
             inner_pos: 0..0,
             resample,
             done: false,
+            error: None,
 
             interrupted_samples: Vec::with_capacity(chan_count),
             interrupted_byte_pos: 0..0,
@@ -168,22 +172,33 @@ impl Read for ToAudioBytes {
             // Now work with new packets.
             let source_packet = if !self.inner_pos.is_empty() {
                 Some(self.parsed.decoder.last_decoded())
-            } else if let Ok(pkt) = self.parsed.format.next_packet() {
-                if pkt.track_id() != self.parsed.track_id {
-                    continue;
-                }
-
-                self.parsed
-                    .decoder
-                    .decode(&pkt)
-                    .map(|pkt| {
-                        self.inner_pos = 0..pkt.frames();
-                        pkt
-                    })
-                    .ok()
             } else {
-                // EOF.
-                None
+                match self.parsed.format.next_packet() {
+                    Ok(pkt) => {
+                        if pkt.track_id() != self.parsed.track_id {
+                            continue;
+                        }
+
+                        match self.parsed.decoder.decode(&pkt) {
+                            Ok(pkt) => {
+                                self.inner_pos = 0..pkt.frames();
+                                Some(pkt)
+                            },
+                            // Skip over corrupt packets.
+                            Err(SymphError::DecodeError(_)) => continue,
+                            Err(e) => {
+                                self.error = Some(e);
+                                None
+                            },
+                        }
+                    },
+                    // EOF.
+                    Err(SymphError::IoError(e)) if e.kind() == IoErrorKind::UnexpectedEof => None,
+                    Err(e) => {
+                        self.error = Some(e);
+                        None
+                    },
+                }
             };
 
             if source_packet.is_none() {
@@ -313,7 +328,16 @@ impl Read for ToAudioBytes {
                 buf = &mut buf[bytes_advanced..];
             }
         }
-        Ok(orig_sz - buf.len())
+
+        let written = orig_sz - buf.len();
+
+        if written == 0 && orig_sz != 0 {
+            if let Some(e) = self.error.take() {
+                return Err(IoError::new(IoErrorKind::Other, e));
+            }
+        }
+
+        Ok(written)
     }
 }
 
@@ -2,12 +2,140 @@ use crate::input::{AudioStream, Input, LiveInput};
 use std::{
     io::{Read, Result as IoResult},
     mem,
-    process::Child,
+    process::{Child, Command},
+    time::Duration,
 };
 use symphonia_core::io::{MediaSource, ReadOnlySource};
-use tokio::runtime::Handle;
+use tokio::{process::Command as AsyncCommand, runtime::Handle};
 use tracing::debug;
 
+/// Resource limits applied to a child process, such as `yt-dlp` or `ffmpeg`, when
+/// it is spawned.
+///
+/// These prevent a malicious or malformed media URL from consuming the whole host.
+/// Limits are enforced by the operating system, and are inherited by any processes
+/// which the child itself spawns, except for `timeout`.
+///
+/// All limits are currently only supported on Unix-like platforms, and are ignored
+/// elsewhere.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct ChildLimits {
+    /// Scheduling priority of the child, from `-20` (highest) to `19` (lowest).
+    ///
+    /// Raising priority above the parent's typically requires elevated permissions.
+    /// Defaults to `None`, inheriting the parent's priority.
+    pub nice: Option<i32>,
+    /// Maximum size of the child's virtual address space, in bytes.
+    ///
+    /// Allocations beyond this limit fail. Defaults to `None` (unlimited).
+    pub max_memory: Option<u64>,
+    /// Wall-clock time after which the child is killed, rounded up to whole seconds.
+    ///
+    /// Defaults to `None` (unlimited).
+    pub timeout: Option<Duration>,
+}
+
+impl ChildLimits {
+    /// Creates a new set of limits, with no limits set.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the scheduling priority of the child.
+    #[must_use]
+    pub fn nice(mut self, nice: i32) -> Self {
+        self.nice = Some(nice);
+        self
+    }
+
+    /// Sets the maximum size of the child's address space, in bytes.
+    #[must_use]
+    pub fn max_memory(mut self, max_memory: u64) -> Self {
+        self.max_memory = Some(max_memory);
+        self
+    }
+
+    /// Sets the time after which the child is killed.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Configures `cmd` so that any process it spawns is bound by these limits.
+    pub fn apply(&self, cmd: &mut Command) {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            let limits = *self;
+            // SAFETY: the hook makes only async-signal-safe calls, and does not allocate.
+            unsafe {
+                cmd.pre_exec(move || limits.limit_current_process());
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = (self, cmd);
+    }
+
+    /// Configures the async `cmd` so that any process it spawns is bound by these limits.
+    pub fn apply_async(&self, cmd: &mut AsyncCommand) {
+        #[cfg(unix)]
+        {
+            let limits = *self;
+            // SAFETY: as in `apply`.
+            unsafe {
+                cmd.pre_exec(move || limits.limit_current_process());
+            }
+        }
+
+        #[cfg(not(unix))]
+        let _ = (self, cmd);
+    }
+
+    /// Applies these limits to the calling process, between `fork` and `exec`.
+    #[cfg(unix)]
+    fn limit_current_process(self) -> IoResult<()> {
+        use std::io::Error as IoError;
+
+        if let Some(nice) = self.nice {
+            // SAFETY: setpriority is async-signal-safe.
+            if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, nice) } != 0 {
+                return Err(IoError::last_os_error());
+            }
+        }
+
+        if let Some(bytes) = self.max_memory {
+            let limit = libc::rlimit {
+                rlim_cur: bytes as libc::rlim_t,
+                rlim_max: bytes as libc::rlim_t,
+            };
+
+            // SAFETY: setrlimit is async-signal-safe, and `limit` outlives the call.
+            if unsafe { libc::setrlimit(libc::RLIMIT_AS, &limit) } != 0 {
+                return Err(IoError::last_os_error());
+            }
+        }
+
+        if let Some(timeout) = self.timeout {
+            // Pending alarms survive `exec`, and SIGALRM terminates the process
+            // unless it installs its own handler.
+            let secs = timeout.as_secs() + u64::from(timeout.subsec_nanos() > 0);
+            let secs = secs.clamp(1, libc::c_uint::MAX.into()) as libc::c_uint;
+
+            // SAFETY: alarm is async-signal-safe.
+            unsafe {
+                libc::alarm(secs);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Handle for a child process which ensures that any subprocesses are properly closed
 /// on drop.
 ///
@@ -89,3 +217,31 @@ fn cleanup_child_processes(mut children: Vec<Child>) {
         debug!("Error awaiting child process: {:?}", e);
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    #[test]
+    fn nice_level_is_applied() {
+        let mut cmd = Command::new("nice");
+        ChildLimits::new().nice(10).apply(&mut cmd);
+
+        let out = cmd.output().unwrap();
+        assert_eq!(String::from_utf8_lossy(&out.stdout).trim(), "10");
+    }
+
+    #[test]
+    #[ntest::timeout(10_000)]
+    fn timeout_kills_child() {
+        let mut cmd = Command::new("sleep");
+        cmd.arg("30");
+        ChildLimits::new()
+            .timeout(Duration::from_millis(500))
+            .apply(&mut cmd);
+
+        let status = cmd.status().unwrap();
+        assert_eq!(status.signal(), Some(libc::SIGALRM));
+    }
+}
@@ -2,5 +2,6 @@ mod async_adapter;
 pub mod cached;
 mod child;
 mod raw_adapter;
+pub mod tone;
 
 pub use self::{async_adapter::*, child::*, raw_adapter::*};
@@ -0,0 +1,367 @@
+//! Synthesised tones, DTMF sequences, and silence.
+//!
+//! [`Tone`]s are generated on the fly as mono `f32` PCM at 48kHz, and need no
+//! files or external processes. They are fully seekable.
+//!
+//! ```rust
+//! use songbird::input::{tone::Tone, Input};
+//! use std::time::Duration;
+//!
+//! let beep = Tone::sine(880.0, Duration::from_millis(150))
+//!     .then(Tone::silence(Duration::from_millis(100)))
+//!     .then(Tone::sine(880.0, Duration::from_millis(150)));
+//! let input: Input = beep.into();
+//!
+//! let dial = Tone::dtmf("555-0123", Duration::from_millis(100), Duration::from_millis(50))
+//!     .expect("only valid DTMF symbols were given");
+//! ```
+use super::RawAdapter;
+use crate::{constants::SAMPLE_RATE_RAW, input::Input};
+use std::{
+    f32::consts::TAU,
+    io::{ErrorKind as IoErrorKind, Read, Result as IoResult, Seek, SeekFrom},
+    mem,
+    time::Duration,
+};
+use symphonia::core::io::MediaSource;
+
+const SAMPLE_LEN: u64 = mem::size_of::<f32>() as u64;
+
+/// Shape of a synthesised tone.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Waveform {
+    /// A pure sine wave.
+    #[default]
+    Sine,
+    /// A square wave, alternating between full positive and negative amplitude.
+    Square,
+}
+
+impl Waveform {
+    fn sample(self, phase: f32) -> f32 {
+        match self {
+            Self::Sine => (TAU * phase).sin(),
+            Self::Square =>
+                if phase.fract() < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                },
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Segment {
+    waveform: Waveform,
+    /// Frequencies mixed together in this segment, in Hz. Empty for silence.
+    frequencies: Vec<f32>,
+    volume: f32,
+    frames: u64,
+}
+
+impl Segment {
+    fn new(waveform: Waveform, frequencies: Vec<f32>, duration: Duration) -> Self {
+        Self {
+            waveform,
+            frequencies,
+            volume: 1.0,
+            frames: (duration.as_secs_f64() * SAMPLE_RATE_RAW as f64).round() as u64,
+        }
+    }
+
+    fn sample(&self, frame: u64) -> f32 {
+        if self.frequencies.is_empty() {
+            return 0.0;
+        }
+
+        let t = frame as f64 / SAMPLE_RATE_RAW as f64;
+        let sum: f32 = self
+            .frequencies
+            .iter()
+            .map(|&f| self.waveform.sample((t * f64::from(f)).fract() as f32))
+            .sum();
+
+        self.volume * sum / self.frequencies.len() as f32
+    }
+}
+
+/// A sequence of synthesised tones and silences.
+///
+/// Sequences are built from the constructors below, joined using [`Self::then`],
+/// and converted into an [`Input`] for playback.
+#[derive(Clone, Debug, Default)]
+pub struct Tone {
+    segments: Vec<Segment>,
+}
+
+impl Tone {
+    /// Creates a sine tone of the given frequency (in Hz) and duration.
+    #[must_use]
+    pub fn sine(frequency: f32, duration: Duration) -> Self {
+        Self::wave(Waveform::Sine, frequency, duration)
+    }
+
+    /// Creates a square tone of the given frequency (in Hz) and duration.
+    #[must_use]
+    pub fn square(frequency: f32, duration: Duration) -> Self {
+        Self::wave(Waveform::Square, frequency, duration)
+    }
+
+    /// Creates a tone of the given shape, frequency (in Hz) and duration.
+    #[must_use]
+    pub fn wave(waveform: Waveform, frequency: f32, duration: Duration) -> Self {
+        Self::from_segment(Segment::new(waveform, vec![frequency], duration))
+    }
+
+    /// Creates silence of the given duration.
+    #[must_use]
+    pub fn silence(duration: Duration) -> Self {
+        Self::from_segment(Segment::new(Waveform::Sine, vec![], duration))
+    }
+
+    /// Creates a sequence of DTMF (touch-tone) signals, each lasting `tone_length`
+    /// and separated by `gap` of silence.
+    ///
+    /// Valid symbols are `0`-`9`, `*`, `#`, and `A`-`D` (case-insensitive). A `,`
+    /// inserts an additional pause of `tone_length`, while spaces and `-` are ignored.
+    ///
+    /// Returns `None` if `sequence` contains any other characters.
+    #[must_use]
+    pub fn dtmf(sequence: &str, tone_length: Duration, gap: Duration) -> Option<Self> {
+        const ROWS: [f32; 4] = [697.0, 770.0, 852.0, 941.0];
+        const COLS: [f32; 4] = [1209.0, 1336.0, 1477.0, 1633.0];
+
+        let mut out = Self::default();
+
+        for c in sequence.chars() {
+            let (row, col) = match c.to_ascii_uppercase() {
+                '1' => (0, 0),
+                '2' => (0, 1),
+                '3' => (0, 2),
+                'A' => (0, 3),
+                '4' => (1, 0),
+                '5' => (1, 1),
+                '6' => (1, 2),
+                'B' => (1, 3),
+                '7' => (2, 0),
+                '8' => (2, 1),
+                '9' => (2, 2),
+                'C' => (2, 3),
+                '*' => (3, 0),
+                '0' => (3, 1),
+                '#' => (3, 2),
+                'D' => (3, 3),
+                ',' => {
+                    out = out.then(Self::silence(tone_length));
+                    continue;
+                },
+                ' ' | '-' => continue,
+                _ => return None,
+            };
+
+            if !out.segments.is_empty() {
+                out = out.then(Self::silence(gap));
+            }
+
+            out = out.then(Self::from_segment(Segment::new(
+                Waveform::Sine,
+                vec![ROWS[row], COLS[col]],
+                tone_length,
+            )));
+        }
+
+        Some(out)
+    }
+
+    /// Appends `next` to the end of this sequence.
+    #[must_use]
+    pub fn then(mut self, next: Self) -> Self {
+        self.segments.extend(next.segments);
+        self
+    }
+
+    /// Scales the amplitude of every tone in this sequence.
+    ///
+    /// Tones are generated at full scale (`1.0`) by default.
+    #[must_use]
+    pub fn volume(mut self, volume: f32) -> Self {
+        for segment in &mut self.segments {
+            segment.volume *= volume;
+        }
+        self
+    }
+
+    /// Returns the total length of this sequence.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        let frames: u64 = self.segments.iter().map(|s| s.frames).sum();
+        Duration::from_nanos(frames * 1_000_000_000 / SAMPLE_RATE_RAW as u64)
+    }
+
+    fn from_segment(segment: Segment) -> Self {
+        Self {
+            segments: vec![segment],
+        }
+    }
+}
+
+impl From<Tone> for Input {
+    fn from(val: Tone) -> Self {
+        RawAdapter::new(ToneSource::new(val), SAMPLE_RATE_RAW as u32, 1).into()
+    }
+}
+
+/// Byte stream of `f32` samples generated from a [`Tone`].
+struct ToneSource {
+    segments: Vec<Segment>,
+    /// First frame of each segment.
+    starts: Vec<u64>,
+    len: u64,
+    pos: u64,
+}
+
+impl ToneSource {
+    fn new(tone: Tone) -> Self {
+        let mut starts = Vec::with_capacity(tone.segments.len());
+        let mut frames = 0;
+
+        for segment in &tone.segments {
+            starts.push(frames);
+            frames += segment.frames;
+        }
+
+        Self {
+            segments: tone.segments,
+            starts,
+            len: frames * SAMPLE_LEN,
+            pos: 0,
+        }
+    }
+
+    fn sample(&self, frame: u64) -> f32 {
+        let idx = self.starts.partition_point(|&start| start <= frame);
+
+        idx.checked_sub(1)
+            .map_or(0.0, |i| self.segments[i].sample(frame - self.starts[i]))
+    }
+}
+
+impl Read for ToneSource {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        let mut written = 0;
+
+        while written < buf.len() && self.pos < self.len {
+            let offset = (self.pos % SAMPLE_LEN) as usize;
+            let bytes = self.sample(self.pos / SAMPLE_LEN).to_le_bytes();
+            let n = (bytes.len() - offset).min(buf.len() - written);
+
+            buf[written..][..n].copy_from_slice(&bytes[offset..][..n]);
+            written += n;
+            self.pos += n as u64;
+        }
+
+        Ok(written)
+    }
+}
+
+impl Seek for ToneSource {
+    fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+        let target = match pos {
+            SeekFrom::Start(p) => Some(p),
+            SeekFrom::End(p) => self.len.checked_add_signed(p),
+            SeekFrom::Current(p) => self.pos.checked_add_signed(p),
+        };
+
+        self.pos = target.ok_or(IoErrorKind::InvalidInput)?;
+
+        Ok(self.pos)
+    }
+}
+
+impl MediaSource for ToneSource {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{driver::Driver, tracks::Track, Config};
+
+    fn samples(tone: Tone) -> Vec<f32> {
+        let mut src = ToneSource::new(tone);
+        let mut bytes = vec![];
+        src.read_to_end(&mut bytes).unwrap();
+
+        bytes
+            .chunks_exact(SAMPLE_LEN as usize)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn tone_lengths_are_exact() {
+        let tone = Tone::sine(440.0, Duration::from_millis(100))
+            .then(Tone::silence(Duration::from_millis(50)));
+
+        assert_eq!(tone.duration(), Duration::from_millis(150));
+        assert_eq!(samples(tone).len(), 7_200);
+    }
+
+    #[test]
+    fn silence_and_square_values() {
+        let out = samples(
+            Tone::silence(Duration::from_millis(10))
+                .then(Tone::square(100.0, Duration::from_millis(10)).volume(0.5)),
+        );
+
+        assert!(out[..480].iter().all(|&s| s == 0.0));
+        assert!(out[480..].iter().all(|&s| s == 0.5 || s == -0.5));
+        assert!(out[480..].iter().any(|&s| s < 0.0));
+    }
+
+    #[test]
+    fn dtmf_parses_symbols() {
+        let tone_len = Duration::from_millis(40);
+        let gap = Duration::from_millis(20);
+
+        let seq = Tone::dtmf("1a#-*", tone_len, gap).unwrap();
+        assert_eq!(seq.duration(), 4 * tone_len + 3 * gap);
+
+        assert!(Tone::dtmf("12x", tone_len, gap).is_none());
+    }
+
+    #[test]
+    fn unaligned_reads_and_seeks_match() {
+        let tone = Tone::sine(440.0, Duration::from_millis(20));
+        let expected = samples(tone.clone());
+
+        let mut src = ToneSource::new(tone);
+        src.seek(SeekFrom::Start(SAMPLE_LEN * 10 + 2)).unwrap();
+
+        let mut bytes = [0u8; 6];
+        src.read_exact(&mut bytes).unwrap();
+
+        assert_eq!(bytes[..2], expected[10].to_le_bytes()[2..]);
+        assert_eq!(bytes[2..], expected[11].to_le_bytes());
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn tone_track_plays() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+
+        let handle = driver.play(Track::from(Tone::sine(440.0, Duration::from_millis(100))));
+        let state = t_handle.ready_track(&handle, None).await;
+
+        assert!(state.playing.is_playing());
+    }
+}
@@ -126,6 +126,53 @@ impl Error for MetadataError {
     }
 }
 
+/// Errors encountered when choosing which audio track of an [`Input`] to play.
+///
+/// [`Input`]: super::Input
+#[non_exhaustive]
+#[derive(Debug)]
+pub enum SelectTrackError {
+    /// The input has not been made live and parsed, so its tracks are unknown.
+    Metadata(MetadataError),
+    /// The chosen track does not exist, or no decoder could be built for it.
+    Decode(SymphError),
+}
+
+impl Display for SelectTrackError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("failed to select track: ")?;
+        match self {
+            Self::Metadata(m) => f.write_fmt(format_args!("{m}")),
+            Self::Decode(d) => {
+                f.write_str("building decoder [")?;
+                f.write_fmt(format_args!("{}", &d))?;
+                f.write_str("]")
+            },
+        }
+    }
+}
+
+impl Error for SelectTrackError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Metadata(m) => Some(m),
+            Self::Decode(d) => Some(d),
+        }
+    }
+}
+
+impl From<MetadataError> for SelectTrackError {
+    fn from(val: MetadataError) -> Self {
+        Self::Metadata(val)
+    }
+}
+
+impl From<SymphError> for SelectTrackError {
+    fn from(val: SymphError) -> Self {
+        Self::Decode(val)
+    }
+}
+
 /// Errors encountered when trying to access out-of-band [`AuxMetadata`] for an [`Input`]
 /// or [`Compose`].
 ///
@@ -1,4 +1,4 @@
-use super::{AudioStream, Metadata, MetadataError, Parsed};
+use super::{AudioStream, AudioTrackInfo, Metadata, MetadataError, Parsed, SelectTrackError};
 
 use symphonia_core::{
     codecs::{CodecRegistry, DecoderOptions},
@@ -30,7 +30,8 @@ impl LiveInput {
     ///
     /// Where applicable, this will convert [`Raw`] -> [`Wrapped`] -> [`Parsed`], and will
     /// play the default track (or the first encountered track if this is not available) if a
-    /// container holds multiple audio streams.
+    /// container holds multiple audio streams. Another stream may then be chosen via
+    /// [`Self::select_track`].
     ///
     /// *This is a blocking operation. Symphonia uses standard library I/O (e.g., [`Read`], [`Seek`]).
     /// If you wish to use this from an async task, you must do so within `spawn_blocking`.*
@@ -143,15 +144,57 @@ impl LiveInput {
             Err(MetadataError::NotParsed)
         }
     }
+
+    /// Lists the audio tracks which may be chosen for playback via [`Self::select_track`].
+    ///
+    /// Only exists when this input is [`LiveInput::Parsed`].
+    pub fn audio_tracks(&self) -> Result<Vec<AudioTrackInfo>, MetadataError> {
+        self.parsed()
+            .map(Parsed::audio_tracks)
+            .ok_or(MetadataError::NotParsed)
+    }
+
+    /// Chooses which audio track to decode, by its ID.
+    ///
+    /// See [`Parsed::select_track`].
+    pub fn select_track(
+        &mut self,
+        track_id: u32,
+        codecs: &CodecRegistry,
+    ) -> Result<(), SelectTrackError> {
+        self.parsed_mut()
+            .ok_or(MetadataError::NotParsed)?
+            .select_track(track_id, codecs)
+            .map_err(Into::into)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         constants::test_data::FILE_VID_TARGET,
-        input::{codecs::*, File, Input},
+        input::{codecs::*, File, Input, SelectTrackError},
     };
 
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn audio_tracks_can_be_listed_and_selected() {
+        let mut input = Input::from(File::new(FILE_VID_TARGET))
+            .make_playable_async(&CODEC_REGISTRY, &PROBE)
+            .await
+            .unwrap();
+
+        let tracks = input.audio_tracks().unwrap();
+        let chosen = input.parsed().unwrap().track_id;
+        assert!(tracks.iter().any(|t| t.id == chosen));
+
+        input.select_track(chosen, &CODEC_REGISTRY).unwrap();
+        assert!(matches!(
+            input.select_track(u32::MAX, &CODEC_REGISTRY),
+            Err(SelectTrackError::Decode(_))
+        ));
+    }
+
     #[tokio::test]
     #[ntest::timeout(10_000)]
     async fn promote_finds_valid_audio() {
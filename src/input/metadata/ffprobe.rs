@@ -1,4 +1,4 @@
-use super::AuxMetadata;
+use super::{AudioTrackInfo, AuxMetadata};
 use serde::{Deserialize, Serialize};
 use serde_aux::prelude::*;
 use std::{collections::HashMap, time::Duration};
@@ -165,6 +165,18 @@ impl Output {
                 continue;
             }
 
+            out.audio_tracks.push(AudioTrackInfo {
+                id: u32::try_from(stream.index).unwrap_or(u32::MAX),
+                language: stream
+                    .tags
+                    .as_ref()
+                    .and_then(|tags| tags.get("language"))
+                    .cloned(),
+                channels: stream.channels.and_then(|c| u8::try_from(c).ok()),
+                sample_rate: stream.sample_rate,
+                default: stream.disposition.as_ref().is_some_and(|d| d.default),
+            });
+
             if let Some(tags) = stream.tags {
                 apply_tags(tags, &mut out);
             }
@@ -44,6 +44,36 @@ pub struct AuxMetadata {
     pub title: Option<String>,
     /// The thumbnail url of this stream.
     pub thumbnail: Option<String>,
+    /// The audio tracks held in this stream, in container order.
+    ///
+    /// When retrieved via `ffprobe`, each track's `id` is its stream index as
+    /// reported by `ffprobe`, which need not match the IDs used to select tracks
+    /// via [`Parsed::select_track`].
+    ///
+    /// [`Parsed::select_track`]: crate::input::Parsed::select_track
+    pub audio_tracks: Vec<AudioTrackInfo>,
+}
+
+/// Details of one audio track inside a container holding several (e.g., an MKV
+/// file with multiple languages).
+///
+/// Available tracks can be listed before playback via [`Input::audio_tracks`],
+/// or in advance via [`AuxMetadata::audio_tracks`].
+///
+/// [`Input::audio_tracks`]: crate::input::Input::audio_tracks
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct AudioTrackInfo {
+    /// The ID of this track within its container.
+    pub id: u32,
+    /// The language of this track, typically as an ISO 639 code.
+    pub language: Option<String>,
+    /// The number of audio channels in this track.
+    pub channels: Option<u8>,
+    /// The sample rate of this track.
+    pub sample_rate: Option<u32>,
+    /// Whether the container marks this as its default track.
+    pub default: bool,
 }
 
 impl AuxMetadata {
@@ -70,6 +100,7 @@ impl AuxMetadata {
             source_url: self.source_url.take(),
             title: self.title.take(),
             thumbnail: self.thumbnail.take(),
+            audio_tracks: std::mem::take(&mut self.audio_tracks),
         }
     }
 }
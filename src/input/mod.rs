@@ -62,6 +62,7 @@ pub mod input_tests;
 mod live_input;
 mod metadata;
 mod parsed;
+mod probe_cache;
 mod sources;
 pub mod utils;
 
@@ -73,6 +74,7 @@ pub use self::{
     live_input::*,
     metadata::*,
     parsed::*,
+    probe_cache::*,
     sources::*,
 };
 
@@ -223,6 +225,35 @@ impl Input {
         }
     }
 
+    /// Lists the audio tracks in this stream which may be chosen for playback.
+    ///
+    /// Containers holding several audio tracks (e.g., MKV files with multiple languages)
+    /// otherwise play their default track. Only exists when this input is both
+    /// [`Self::Live`] and has been fully parsed.
+    pub fn audio_tracks(&self) -> Result<Vec<AudioTrackInfo>, MetadataError> {
+        if let Self::Live(live, _) = self {
+            live.audio_tracks()
+        } else {
+            Err(MetadataError::NotLive)
+        }
+    }
+
+    /// Chooses which audio track to decode, by its ID, before this input is played.
+    ///
+    /// Track IDs can be found via [`Self::audio_tracks`]. Only exists when this input
+    /// is both [`Self::Live`] and has been fully parsed.
+    pub fn select_track(
+        &mut self,
+        track_id: u32,
+        codecs: &CodecRegistry,
+    ) -> Result<(), SelectTrackError> {
+        if let Self::Live(live, _) = self {
+            live.select_track(track_id, codecs)
+        } else {
+            Err(MetadataError::NotLive.into())
+        }
+    }
+
     /// Initialises (but does not parse) an [`Input::Lazy`] into an [`Input::Live`],
     /// placing blocking I/O on the current thread.
     ///
@@ -1,4 +1,10 @@
-use symphonia_core::{codecs::Decoder, formats::FormatReader, probe::ProbedMetadata};
+use super::AudioTrackInfo;
+use symphonia_core::{
+    codecs::{CodecRegistry, Decoder, DecoderOptions, CODEC_TYPE_NULL},
+    errors::Error as SymphError,
+    formats::FormatReader,
+    probe::ProbedMetadata,
+};
 
 /// An audio file which has had its headers parsed and decoder state built.
 pub struct Parsed {
@@ -29,3 +35,50 @@ pub struct Parsed {
     /// it must seek backwards.
     pub supports_backseek: bool,
 }
+
+impl Parsed {
+    /// Lists the audio tracks in this container which may be chosen for playback
+    /// via [`Self::select_track`].
+    #[must_use]
+    pub fn audio_tracks(&self) -> Vec<AudioTrackInfo> {
+        let default_id = self.format.default_track().map(|track| track.id);
+
+        self.format
+            .tracks()
+            .iter()
+            .filter(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .map(|track| AudioTrackInfo {
+                id: track.id,
+                language: track.language.clone(),
+                channels: track
+                    .codec_params
+                    .channels
+                    .and_then(|c| u8::try_from(c.count()).ok()),
+                sample_rate: track.codec_params.sample_rate,
+                default: default_id == Some(track.id),
+            })
+            .collect()
+    }
+
+    /// Chooses the track with the given ID for playback, replacing the current decoder.
+    ///
+    /// This should be called before the input is played: packets of the old track
+    /// which have already been read are not replayed from the new one.
+    pub fn select_track(
+        &mut self,
+        track_id: u32,
+        codecs: &CodecRegistry,
+    ) -> Result<(), SymphError> {
+        let track = self
+            .format
+            .tracks()
+            .iter()
+            .find(|track| track.id == track_id)
+            .ok_or(SymphError::DecodeError("track not found"))?;
+
+        self.decoder = codecs.make(&track.codec_params, &DecoderOptions::default())?;
+        self.track_id = track_id;
+
+        Ok(())
+    }
+}
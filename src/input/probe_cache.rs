@@ -0,0 +1,160 @@
+use super::{metadata::ytdl::Output, AuxMetadata};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Information learned while probing a lazily-created input, which later
+/// inputs from the same source can reuse.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct ProbeResult {
+    /// Auxiliary metadata fetched for the source.
+    pub metadata: Option<AuxMetadata>,
+    /// Length of the source in bytes, if known.
+    pub content_length: Option<u64>,
+    /// MIME type reported for the source, if any.
+    pub mime_type: Option<String>,
+    /// Resolved streams returned by youtube-dl.
+    pub(crate) ytdl: Option<Vec<Output>>,
+}
+
+/// A bounded cache of [`ProbeResult`]s, each expiring after a fixed time-to-live.
+///
+/// Sources such as [`YoutubeDl`] and [`HttpRequest`] can be given a shared cache,
+/// keyed by URL or search query, to skip repeated metadata lookups and network
+/// round trips when the same source is played again (e.g., soundboard clips or
+/// radio streams). Caches are opt-in, and cheap to clone: all clones share the
+/// same entries.
+///
+/// The TTL should be shorter than the lifetime of any resolved stream URLs:
+/// YouTube's, for instance, expire after several hours.
+///
+/// [`YoutubeDl`]: super::YoutubeDl
+/// [`HttpRequest`]: super::HttpRequest
+#[derive(Clone, Debug)]
+pub struct ProbeCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, (Instant, ProbeResult)>>>,
+}
+
+impl ProbeCache {
+    /// Creates a cache holding up to `capacity` results, each kept for up to `ttl`.
+    #[must_use]
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: Arc::default(),
+        }
+    }
+
+    /// Returns the unexpired result stored for `key`, if any.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<ProbeResult> {
+        let mut entries = self.entries.lock().unwrap();
+
+        match entries.get(key) {
+            Some((inserted, result)) if inserted.elapsed() < self.ttl => Some(result.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            },
+            None => None,
+        }
+    }
+
+    /// Stores a result for `key`, evicting expired entries and then the oldest
+    /// entry if the cache is full.
+    pub fn insert(&self, key: impl Into<String>, result: ProbeResult) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let key = key.into();
+        let mut entries = self.entries.lock().unwrap();
+
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            entries.retain(|_, (inserted, _)| inserted.elapsed() < self.ttl);
+        }
+
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (inserted, _))| *inserted)
+                .map(|(k, _)| k.clone());
+
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+
+        entries.insert(key, (Instant::now(), result));
+    }
+
+    /// Removes any result stored for `key`, e.g., if it is known to be stale.
+    #[must_use]
+    pub fn remove(&self, key: &str) -> Option<ProbeResult> {
+        self.entries
+            .lock()
+            .unwrap()
+            .remove(key)
+            .map(|(_, result)| result)
+    }
+
+    /// Removes all stored results.
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    /// Returns the number of stored results, including any which have expired
+    /// but not yet been evicted.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    /// Returns whether no results are stored.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sized(len: u64) -> ProbeResult {
+        ProbeResult {
+            content_length: Some(len),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn full_cache_evicts_oldest() {
+        let cache = ProbeCache::new(2, Duration::from_secs(60));
+        cache.insert("a", sized(1));
+        cache.insert("b", sized(2));
+        cache.insert("a", sized(3));
+        assert_eq!(cache.len(), 2);
+
+        cache.insert("c", sized(4));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.get("b").unwrap().content_length, Some(2));
+        assert_eq!(cache.get("c").unwrap().content_length, Some(4));
+    }
+
+    #[test]
+    fn expired_results_are_not_returned() {
+        let cache = ProbeCache::new(2, Duration::ZERO);
+        cache.insert("a", sized(1));
+
+        assert!(cache.get("a").is_none());
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,323 @@
+use crate::input::{AudioStream, AudioStreamError, Compose, Input};
+use std::{
+    error::Error,
+    ffi::OsStr,
+    fs::File as StdFile,
+    io::{
+        Cursor,
+        Error as IoError,
+        ErrorKind as IoErrorKind,
+        Read,
+        Result as IoResult,
+        Seek,
+        SeekFrom,
+    },
+    path::{Path, PathBuf},
+};
+use symphonia_core::{io::MediaSource, probe::Hint};
+use zip::{CompressionMethod, ZipArchive};
+
+/// Archive formats understood by [`ArchiveFile`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum ArchiveKind {
+    /// A zip archive.
+    ///
+    /// Stored (uncompressed) members are read in place. Compressed members are
+    /// first decompressed into memory.
+    Zip,
+    /// An uncompressed tar archive, whose members are always read in place.
+    Tar,
+}
+
+/// A lazily instantiated audio file, stored as a member of a local zip or tar archive.
+///
+/// The member is read directly from the archive without extracting it to disk,
+/// and its name is used as a format hint. Unless set with [`Self::kind`], the archive
+/// format is detected from the archive's contents.
+#[derive(Clone, Debug)]
+pub struct ArchiveFile<P: AsRef<Path>> {
+    path: P,
+    member: String,
+    kind: Option<ArchiveKind>,
+}
+
+impl<P: AsRef<Path>> ArchiveFile<P> {
+    /// Creates a lazy archive member object, which will open `member` within the
+    /// archive at the target path.
+    ///
+    /// This is infallible as the path and member are only checked during creation.
+    pub fn new(path: P, member: impl Into<String>) -> Self {
+        Self {
+            path,
+            member: member.into(),
+            kind: None,
+        }
+    }
+
+    /// Sets the archive format, rather than detecting it on creation.
+    #[must_use]
+    pub fn kind(mut self, kind: ArchiveKind) -> Self {
+        self.kind = Some(kind);
+        self
+    }
+}
+
+impl<P: AsRef<Path> + Send + Sync + 'static> From<ArchiveFile<P>> for Input {
+    fn from(val: ArchiveFile<P>) -> Self {
+        Input::Lazy(Box::new(val))
+    }
+}
+
+#[async_trait::async_trait]
+impl<P: AsRef<Path> + Send + Sync> Compose for ArchiveFile<P> {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let err: Box<dyn Error + Send + Sync> =
+            "Files should be created asynchronously.".to_string().into();
+        Err(AudioStreamError::Fail(err))
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let path: PathBuf = self.path.as_ref().into();
+        let member = self.member.clone();
+        let kind = self.kind;
+
+        // Archive indices are read (and members possibly decompressed) with blocking I/O.
+        let input = tokio::task::spawn_blocking(move || open_member(&path, &member, kind))
+            .await
+            .map_err(|e| AudioStreamError::Fail(Box::new(e)))?
+            .map_err(|io| AudioStreamError::Fail(Box::new(io)))?;
+
+        let mut hint = Hint::default();
+        if let Some(ext) = Path::new(&self.member).extension().and_then(OsStr::to_str) {
+            hint.with_extension(ext);
+        }
+
+        Ok(AudioStream {
+            input,
+            hint: Some(hint),
+        })
+    }
+
+    fn should_create_async(&self) -> bool {
+        true
+    }
+}
+
+fn open_member(
+    path: &Path,
+    member: &str,
+    kind: Option<ArchiveKind>,
+) -> IoResult<Box<dyn MediaSource>> {
+    let mut file = StdFile::open(path)?;
+
+    let kind = match kind {
+        Some(kind) => kind,
+        None => detect_kind(&mut file)?,
+    };
+
+    match kind {
+        ArchiveKind::Zip => open_zip_member(file, member),
+        ArchiveKind::Tar => open_tar_member(file, member),
+    }
+}
+
+fn detect_kind(file: &mut StdFile) -> IoResult<ArchiveKind> {
+    let mut magic = [0u8; 4];
+    let is_zip = file.read_exact(&mut magic).is_ok() && magic == *b"PK\x03\x04";
+    file.rewind()?;
+
+    Ok(if is_zip {
+        ArchiveKind::Zip
+    } else {
+        ArchiveKind::Tar
+    })
+}
+
+fn open_zip_member(file: StdFile, member: &str) -> IoResult<Box<dyn MediaSource>> {
+    let mut archive = ZipArchive::new(file).map_err(IoError::other)?;
+    let mut entry = archive.by_name(member).map_err(|e| match e {
+        zip::result::ZipError::FileNotFound => missing_member(member),
+        e => IoError::other(e),
+    })?;
+
+    if entry.compression() == CompressionMethod::Stored {
+        let start = entry.data_start();
+        let len = entry.size();
+        drop(entry);
+
+        MemberSource::new(archive.into_inner(), start, len)
+            .map(|v| Box::new(v) as Box<dyn MediaSource>)
+    } else {
+        let mut data = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut data)?;
+
+        Ok(Box::new(Cursor::new(data)))
+    }
+}
+
+fn open_tar_member(file: StdFile, member: &str) -> IoResult<Box<dyn MediaSource>> {
+    let mut archive = tar::Archive::new(file);
+    let mut found = None;
+
+    for entry in archive.entries_with_seek()? {
+        let entry = entry?;
+        if entry.header().entry_type().is_file() && entry.path()? == Path::new(member) {
+            found = Some((entry.raw_file_position(), entry.size()));
+            break;
+        }
+    }
+
+    let (start, len) = found.ok_or_else(|| missing_member(member))?;
+
+    MemberSource::new(archive.into_inner(), start, len).map(|v| Box::new(v) as Box<dyn MediaSource>)
+}
+
+fn missing_member(member: &str) -> IoError {
+    IoError::new(
+        IoErrorKind::NotFound,
+        format!("no file named {member:?} in archive"),
+    )
+}
+
+/// A seekable [`MediaSource`] over one member of an archive, stored in place.
+struct MemberSource {
+    file: StdFile,
+    start: u64,
+    len: u64,
+    pos: u64,
+}
+
+impl MemberSource {
+    fn new(mut file: StdFile, start: u64, len: u64) -> IoResult<Self> {
+        file.seek(SeekFrom::Start(start))?;
+
+        Ok(Self {
+            file,
+            start,
+            len,
+            pos: 0,
+        })
+    }
+}
+
+impl Read for MemberSource {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        let remaining = self.len.saturating_sub(self.pos);
+        let max = buf
+            .len()
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+
+        let n = self.file.read(&mut buf[..max])?;
+        self.pos += n as u64;
+
+        Ok(n)
+    }
+}
+
+impl Seek for MemberSource {
+    fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+        let target = match pos {
+            SeekFrom::Start(n) => Some(n),
+            SeekFrom::End(n) => self.len.checked_add_signed(n),
+            SeekFrom::Current(n) => self.pos.checked_add_signed(n),
+        }
+        .ok_or_else(|| {
+            IoError::new(
+                IoErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        self.file.seek(SeekFrom::Start(self.start + target))?;
+        self.pos = target;
+
+        Ok(target)
+    }
+}
+
+impl MediaSource for MemberSource {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::test_data::{FILE_WAV_TARGET, FILE_WEBM_TARGET},
+        input::input_tests::*,
+    };
+    use std::io::Write;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    fn make_archives(name: &str) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir();
+        let zip_path = dir.join(format!("songbird-{name}-{}.zip", std::process::id()));
+        let tar_path = dir.join(format!("songbird-{name}-{}.tar", std::process::id()));
+
+        let mut zip = ZipWriter::new(StdFile::create(&zip_path).unwrap());
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        zip.start_file("sounds/clip.wav", stored).unwrap();
+        zip.write_all(&std::fs::read(FILE_WAV_TARGET).unwrap())
+            .unwrap();
+        zip.start_file("sounds/clip.webm", SimpleFileOptions::default())
+            .unwrap();
+        zip.write_all(&std::fs::read(FILE_WEBM_TARGET).unwrap())
+            .unwrap();
+        zip.finish().unwrap();
+
+        let mut tar = tar::Builder::new(StdFile::create(&tar_path).unwrap());
+        tar.append_path_with_name(FILE_WAV_TARGET, "sounds/clip.wav")
+            .unwrap();
+        tar.append_path_with_name(FILE_WEBM_TARGET, "sounds/clip.webm")
+            .unwrap();
+        tar.finish().unwrap();
+
+        (zip_path, tar_path)
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn archive_members_play() {
+        let (zip_path, tar_path) = make_archives("play");
+
+        track_plays_mixed(|| ArchiveFile::new(zip_path.clone(), "sounds/clip.wav")).await;
+        track_plays_mixed(|| ArchiveFile::new(zip_path.clone(), "sounds/clip.webm")).await;
+        track_plays_mixed(|| ArchiveFile::new(tar_path.clone(), "sounds/clip.wav")).await;
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn archive_member_seeks_correct() {
+        let (_, tar_path) = make_archives("seek");
+
+        forward_seek_correct(|| ArchiveFile::new(tar_path.clone(), "sounds/clip.webm")).await;
+    }
+
+    #[test]
+    fn archive_member_reports_member_length() {
+        let (zip_path, tar_path) = make_archives("len");
+        let expected = std::fs::metadata(FILE_WAV_TARGET).unwrap().len();
+
+        for path in [zip_path, tar_path] {
+            let src = open_member(&path, "sounds/clip.wav", None).unwrap();
+            assert!(src.is_seekable());
+            assert_eq!(src.byte_len(), Some(expected));
+        }
+
+        assert!(open_member(
+            Path::new(FILE_WAV_TARGET),
+            "missing.wav",
+            Some(ArchiveKind::Tar)
+        )
+        .is_err());
+    }
+}
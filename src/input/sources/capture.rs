@@ -0,0 +1,280 @@
+use crate::input::{AudioStream, AudioStreamError, Compose, Input, RawAdapter};
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    Device,
+    FromSample,
+    Sample,
+    SampleFormat,
+    SizedSample,
+    StreamConfig,
+};
+use flume::{Receiver, Sender, TryRecvError, TrySendError};
+use std::{
+    error::Error,
+    io::{ErrorKind as IoErrorKind, Read, Result as IoResult, Seek, SeekFrom},
+    mem,
+    thread,
+};
+use symphonia_core::io::MediaSource;
+
+/// Number of device callbacks which may be buffered before newly captured
+/// audio is dropped, bounding the latency added by a slow consumer.
+const CAPTURE_BACKLOG: usize = 32;
+
+/// A local audio device to capture from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum CaptureDevice {
+    /// The host's default input device (usually a microphone).
+    DefaultInput,
+    /// Loopback capture of the host's default output device, i.e., "what you hear".
+    ///
+    /// This is currently only supported by the WASAPI host on Windows.
+    DefaultOutput,
+    /// The input device with the given name, as reported by the host.
+    Named(String),
+}
+
+/// A lazily instantiated capture stream from a local audio device.
+///
+/// Audio is captured at the device's default sample rate and channel count,
+/// and is resampled by the driver as needed. The device is opened when the
+/// track is made playable, and released when the track ends.
+///
+/// Requires the `"capture"` feature.
+#[derive(Clone, Debug)]
+pub struct Capture {
+    device: CaptureDevice,
+}
+
+impl Capture {
+    /// Creates a lazy capture from the given device.
+    ///
+    /// This is infallible as the device is only opened during creation.
+    #[must_use]
+    pub fn new(device: CaptureDevice) -> Self {
+        Self { device }
+    }
+
+    fn open(&self) -> Result<(Device, cpal::SupportedStreamConfig), Box<dyn Error + Send + Sync>> {
+        let host = cpal::default_host();
+
+        let device = match &self.device {
+            CaptureDevice::DefaultInput => host.default_input_device(),
+            CaptureDevice::DefaultOutput => host.default_output_device(),
+            CaptureDevice::Named(name) => host
+                .input_devices()?
+                .find(|d| d.name().is_ok_and(|n| &n == name)),
+        }
+        .ok_or("requested capture device was not found")?;
+
+        let config = match self.device {
+            CaptureDevice::DefaultOutput => device.default_output_config()?,
+            _ => device.default_input_config()?,
+        };
+
+        Ok((device, config))
+    }
+}
+
+impl From<Capture> for Input {
+    fn from(val: Capture) -> Self {
+        Input::Lazy(Box::new(val))
+    }
+}
+
+#[async_trait::async_trait]
+impl Compose for Capture {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let (device, config) = self.open().map_err(AudioStreamError::Fail)?;
+        let format = config.sample_format();
+        let config = config.config();
+        let (sample_rate, channels) = (config.sample_rate.0, config.channels);
+
+        let (audio_tx, audio_rx) = flume::bounded(CAPTURE_BACKLOG);
+        let (ready_tx, ready_rx) = flume::bounded(1);
+        let (stop_tx, stop_rx) = flume::bounded::<()>(1);
+
+        // Streams cannot be moved between threads on all hosts, so each capture
+        // owns a thread which holds its stream until the reader is dropped.
+        thread::spawn(move || {
+            let stream = match format {
+                SampleFormat::I16 => build_stream::<i16>(&device, &config, audio_tx),
+                SampleFormat::U16 => build_stream::<u16>(&device, &config, audio_tx),
+                SampleFormat::F32 => build_stream::<f32>(&device, &config, audio_tx),
+                f => Err(format!("unsupported capture sample format: {f}").into()),
+            }
+            .and_then(|s| s.play().map(|()| s).map_err(Into::into));
+
+            match stream {
+                Ok(stream) => {
+                    drop(ready_tx.send(Ok(())));
+                    _ = stop_rx.recv();
+                    drop(stream);
+                },
+                Err(e) => drop(ready_tx.send(Err(e))),
+            }
+        });
+
+        ready_rx
+            .recv()
+            .map_err(|_| "capture thread exited before opening stream".into())
+            .and_then(|r| r)
+            .map_err(AudioStreamError::Fail)?;
+
+        let reader = CaptureReader::new(audio_rx, channels.into(), stop_tx);
+
+        Ok(AudioStream {
+            input: Box::new(RawAdapter::new(reader, sample_rate, channels.into())),
+            hint: None,
+        })
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        Err(AudioStreamError::Unsupported)
+    }
+
+    fn should_create_async(&self) -> bool {
+        false
+    }
+}
+
+fn build_stream<T>(
+    device: &Device,
+    config: &StreamConfig,
+    tx: Sender<Vec<f32>>,
+) -> Result<cpal::Stream, Box<dyn Error + Send + Sync>>
+where
+    T: SizedSample,
+    f32: FromSample<T>,
+{
+    device
+        .build_input_stream(
+            config,
+            move |data: &[T], _| {
+                let samples = data.iter().map(|s| f32::from_sample(*s)).collect();
+                if let Err(TrySendError::Full(_)) = tx.try_send(samples) {
+                    tracing::trace!("Capture backlog full: dropping audio.");
+                }
+            },
+            |e| tracing::warn!("Capture stream error: {e}"),
+            None,
+        )
+        .map_err(Into::into)
+}
+
+/// Reads captured audio as a stream of little-endian `f32` bytes.
+///
+/// Reads never block the mixer: if the device has not yet delivered any audio,
+/// silence is read in its place.
+struct CaptureReader {
+    rx: Receiver<Vec<f32>>,
+    frame_len: usize,
+    buf: Vec<u8>,
+    pos: usize,
+    _stop: Sender<()>,
+}
+
+impl CaptureReader {
+    fn new(rx: Receiver<Vec<f32>>, channels: usize, stop: Sender<()>) -> Self {
+        Self {
+            rx,
+            frame_len: mem::size_of::<f32>() * channels.max(1),
+            buf: vec![],
+            pos: 0,
+            _stop: stop,
+        }
+    }
+}
+
+impl Read for CaptureReader {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        if self.pos >= self.buf.len() {
+            let samples = match self.rx.try_recv() {
+                Ok(samples) => samples,
+                Err(TryRecvError::Empty) => {
+                    // Fill the gap with whole frames of silence, so that a stalled
+                    // device cannot hold up every call on this mixer thread.
+                    let n = match buf.len() - buf.len() % self.frame_len {
+                        0 => buf.len(),
+                        n => n,
+                    };
+                    buf[..n].fill(0);
+                    return Ok(n);
+                },
+                // The stream only ends if the capture thread has exited.
+                Err(TryRecvError::Disconnected) => return Ok(0),
+            };
+
+            self.buf.clear();
+            self.buf
+                .extend(samples.iter().flat_map(|s| s.to_le_bytes()));
+            self.pos = 0;
+        }
+
+        let n = buf.len().min(self.buf.len() - self.pos);
+        buf[..n].copy_from_slice(&self.buf[self.pos..][..n]);
+        self.pos += n;
+
+        Ok(n)
+    }
+}
+
+impl Seek for CaptureReader {
+    fn seek(&mut self, _pos: SeekFrom) -> IoResult<u64> {
+        Err(IoErrorKind::Unsupported.into())
+    }
+}
+
+impl MediaSource for CaptureReader {
+    fn is_seekable(&self) -> bool {
+        false
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_samples(reader: &mut CaptureReader, n: usize) -> Vec<f32> {
+        let mut bytes = vec![0u8; n * mem::size_of::<f32>()];
+        let mut filled = 0;
+        while filled < bytes.len() {
+            let read = reader.read(&mut bytes[filled..]).unwrap();
+            assert_ne!(read, 0);
+            filled += read;
+        }
+
+        bytes
+            .chunks_exact(mem::size_of::<f32>())
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn stalled_device_reads_as_silence() {
+        let (audio_tx, audio_rx) = flume::bounded(CAPTURE_BACKLOG);
+        let (stop_tx, _stop_rx) = flume::bounded(1);
+        let mut reader = CaptureReader::new(audio_rx, 2, stop_tx);
+
+        // Nothing captured yet: silence is returned at once.
+        assert_eq!(read_samples(&mut reader, 4), vec![0.0; 4]);
+
+        audio_tx.send(vec![0.5, -0.5, 0.25, -0.25]).unwrap();
+        assert_eq!(read_samples(&mut reader, 4), vec![0.5, -0.5, 0.25, -0.25]);
+
+        // A gap part-way through a read is padded with silence.
+        audio_tx.send(vec![1.0, -1.0]).unwrap();
+        assert_eq!(read_samples(&mut reader, 4), vec![1.0, -1.0, 0.0, 0.0]);
+
+        // The stream ends once the capture thread hangs up.
+        drop(audio_tx);
+        assert_eq!(reader.read(&mut [0u8; 8]).unwrap(), 0);
+    }
+}
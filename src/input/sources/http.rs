@@ -3,19 +3,36 @@ use crate::input::{
     AsyncMediaSource,
     AudioStream,
     AudioStreamError,
+    AuxMetadata,
     Compose,
     Input,
+    Prefetch,
+    PrefetchMonitor,
+    ProbeCache,
+    ProbeResult,
 };
 use async_trait::async_trait;
+use derivative::Derivative;
 use futures::TryStreamExt;
 use pin_project::pin_project;
 use reqwest::{
-    header::{HeaderMap, ACCEPT_RANGES, CONTENT_LENGTH, CONTENT_TYPE, RANGE, RETRY_AFTER},
+    header::{
+        HeaderMap,
+        ACCEPT_RANGES,
+        CONTENT_LENGTH,
+        CONTENT_RANGE,
+        CONTENT_TYPE,
+        RANGE,
+        RETRY_AFTER,
+    },
     Client,
+    Response,
+    StatusCode,
 };
 use std::{
     io::{Error as IoError, ErrorKind as IoErrorKind, Result as IoResult, SeekFrom},
     pin::Pin,
+    sync::Arc,
     task::{Context, Poll},
     time::Duration,
 };
@@ -23,8 +40,28 @@ use symphonia_core::{io::MediaSource, probe::Hint};
 use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
 use tokio_util::io::StreamReader;
 
+/// Number of bytes fetched from a file by [`HttpRequest::aux_metadata`] to find
+/// its first audio frame.
+const METADATA_PROBE_LEN: u64 = 16 * 1024;
+
+/// Renews the URL or headers of an [`HttpRequest`] once they have expired.
+///
+/// Some CDNs (e.g., YouTube's) hand out signed URLs which stop working after a
+/// while. Streams must make new requests when resuming after a dropped connection
+/// or seeking, and these fail once the URL has expired.
+#[async_trait]
+pub trait UrlRefresher: Send + Sync {
+    /// Called when a request fails with `403 Forbidden` or `410 Gone`.
+    ///
+    /// Implementations should update `url` and/or `headers` in place, returning
+    /// `true` if the request should be retried. Retried requests keep their byte
+    /// offset, so the replacement URL must serve the same file.
+    async fn refresh(&self, url: &mut String, headers: &mut HeaderMap, status: StatusCode) -> bool;
+}
+
 /// A lazily instantiated HTTP request.
-#[derive(Clone, Debug)]
+#[derive(Clone, Derivative)]
+#[derivative(Debug)]
 pub struct HttpRequest {
     /// A reqwest client instance used to send the HTTP GET request.
     pub client: Client,
@@ -38,6 +75,24 @@ pub struct HttpRequest {
     /// `range: bytes=0-1023` instead of the simpler `range: bytes=0-` (such as
     /// Youtube).
     pub content_length: Option<u64>,
+    /// How far ahead of playback to download.
+    ///
+    /// Defaults to 64KiB.
+    pub prefetch: Prefetch,
+    /// Counts reads and seeks served from the prefetch buffer, shared by every
+    /// stream created from this request.
+    pub monitor: PrefetchMonitor,
+    /// Cache of content lengths and MIME types, keyed by [`Self::request`].
+    ///
+    /// Cached values are used when a response omits either header, such as
+    /// chunked responses to repeated requests for the same file.
+    pub probe_cache: Option<ProbeCache>,
+    /// Hook used to renew [`Self::request`] and [`Self::headers`] if the server
+    /// reports that they have expired.
+    ///
+    /// Each request is retried at most once after a successful refresh.
+    #[derivative(Debug = "ignore")]
+    pub refresher: Option<Arc<dyn UrlRefresher>>,
 }
 
 impl HttpRequest {
@@ -55,38 +110,119 @@ impl HttpRequest {
             request,
             headers,
             content_length: None,
+            prefetch: Prefetch::default(),
+            monitor: PrefetchMonitor::new(),
+            probe_cache: None,
+            refresher: None,
         }
     }
 
-    async fn create_stream(
+    #[must_use]
+    /// Set how far ahead of playback to download.
+    pub fn prefetch(mut self, prefetch: Prefetch) -> Self {
+        self.prefetch = prefetch;
+        self
+    }
+
+    #[must_use]
+    /// Share a cache of probe results between requests for the same URL.
+    pub fn probe_cache(mut self, cache: ProbeCache) -> Self {
+        self.probe_cache = Some(cache);
+        self
+    }
+
+    #[must_use]
+    /// Set a hook to renew this request's URL or headers once they expire.
+    pub fn refresher(mut self, refresher: impl UrlRefresher + 'static) -> Self {
+        self.refresher = Some(Arc::new(refresher));
+        self
+    }
+
+    #[must_use]
+    /// Returns a handle to this request's prefetch hit and miss counts.
+    ///
+    /// This should be taken before the request is converted into an [`Input`].
+    pub fn prefetch_monitor(&self) -> PrefetchMonitor {
+        self.monitor.clone()
+    }
+
+    /// Sends a GET request for `range` of the resource, refreshing the URL once via
+    /// [`Self::refresher`] if it has expired.
+    async fn send(&mut self, range: Option<String>) -> Result<Response, AudioStreamError> {
+        let mut refreshed = false;
+
+        loop {
+            let mut req = self.client.get(&self.request).headers(self.headers.clone());
+            if let Some(range) = &range {
+                req = req.header(RANGE, range);
+            }
+
+            let resp = req
+                .send()
+                .await
+                .map_err(|e| AudioStreamError::Fail(Box::new(e)))?;
+            let status = resp.status();
+
+            if matches!(status, StatusCode::FORBIDDEN | StatusCode::GONE) && !refreshed {
+                if let Some(refresher) = self.refresher.clone() {
+                    refreshed = true;
+                    if refresher
+                        .refresh(&mut self.request, &mut self.headers, status)
+                        .await
+                    {
+                        continue;
+                    }
+                }
+            }
+
+            if !status.is_success() {
+                let msg: Box<dyn std::error::Error + Send + Sync + 'static> =
+                    format!("failed with http status code: {status}").into();
+                return Err(AudioStreamError::Fail(msg));
+            }
+
+            return Ok(resp);
+        }
+    }
+
+    /// Fetches up to `len` bytes starting at `offset`, along with the response.
+    async fn fetch_range(
         &mut self,
-        offset: Option<u64>,
-    ) -> Result<(HttpStream, Option<Hint>), AudioStreamError> {
-        let mut resp = self.client.get(&self.request).headers(self.headers.clone());
-
-        match (offset, self.content_length) {
-            (Some(offset), None) => {
-                resp = resp.header(RANGE, format!("bytes={offset}-"));
-            },
-            (offset, Some(max)) => {
-                resp = resp.header(
-                    RANGE,
-                    format!("bytes={}-{}", offset.unwrap_or(0), max.saturating_sub(1)),
-                );
-            },
-            _ => {},
+        offset: u64,
+        len: u64,
+    ) -> Result<(Response, Vec<u8>), AudioStreamError> {
+        let mut resp = self
+            .send(Some(format!("bytes={offset}-{}", offset + len - 1)))
+            .await?;
+
+        // Servers ignoring `Range` send the whole file, so stop reading early.
+        let mut body = Vec::new();
+        while (body.len() as u64) < len {
+            match resp.chunk().await {
+                Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                _ => break,
+            }
         }
+        body.truncate(len as usize);
 
-        let resp = resp
-            .send()
-            .await
-            .map_err(|e| AudioStreamError::Fail(Box::new(e)))?;
+        Ok((resp, body))
+    }
 
-        if !resp.status().is_success() {
-            let msg: Box<dyn std::error::Error + Send + Sync + 'static> =
-                format!("failed with http status code: {}", resp.status()).into();
-            return Err(AudioStreamError::Fail(msg));
-        }
+    async fn create_stream(
+        &mut self,
+        offset: Option<u64>,
+    ) -> Result<(HttpStream, Option<Hint>), AudioStreamError> {
+        let range = match (offset, self.content_length) {
+            (Some(offset), None) => Some(format!("bytes={offset}-")),
+            (offset, Some(max)) => Some(format!(
+                "bytes={}-{}",
+                offset.unwrap_or(0),
+                max.saturating_sub(1)
+            )),
+            _ => None,
+        };
+
+        let resp = self.send(range).await?;
 
         if let Some(t) = resp.headers().get(RETRY_AFTER) {
             t.to_str()
@@ -106,20 +242,34 @@ impl HttpRequest {
         } else {
             let headers = resp.headers();
 
-            let hint = headers
+            let mut mime_type = headers
                 .get(CONTENT_TYPE)
                 .and_then(|val| val.to_str().ok())
-                .map(|val| {
-                    let mut out = Hint::default();
-                    out.mime_type(val);
-                    out
-                });
+                .map(ToOwned::to_owned);
 
-            let len = headers
+            let mut len = headers
                 .get(CONTENT_LENGTH)
                 .and_then(|val| val.to_str().ok())
                 .and_then(|val| val.parse().ok());
 
+            // Only a response from the start of the resource describes all of it.
+            if let (Some(cache), None) = (&self.probe_cache, offset) {
+                let cached = cache.get(&self.request).unwrap_or_default();
+                mime_type = mime_type.or(cached.mime_type);
+                len = len.or(cached.content_length);
+
+                cache.insert(
+                    self.request.as_str(),
+                    ProbeResult {
+                        mime_type: mime_type.clone(),
+                        content_length: len,
+                        ..cached
+                    },
+                );
+            }
+
+            let hint = mime_type.as_deref().map(hint_for_mime);
+
             let resume = headers
                 .get(ACCEPT_RANGES)
                 .and_then(|a| a.to_str().ok())
@@ -210,7 +360,11 @@ impl Compose for HttpRequest {
         &mut self,
     ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
         self.create_stream(None).await.map(|(input, hint)| {
-            let stream = AsyncAdapterStream::new(Box::new(input), 64 * 1024);
+            let stream = AsyncAdapterStream::new_with_prefetch(
+                Box::new(input),
+                self.prefetch,
+                self.monitor.clone(),
+            );
 
             AudioStream {
                 input: Box::new(stream) as Box<dyn MediaSource>,
@@ -222,6 +376,65 @@ impl Compose for HttpRequest {
     fn should_create_async(&self) -> bool {
         true
     }
+
+    /// Reads metadata from the response headers and the start of the file.
+    ///
+    /// The duration of MP3 files is taken from their Xing/Info header if present,
+    /// and is otherwise estimated from the bitrate of the first frame, assuming
+    /// a constant bitrate. Radio streams report their `icy-name` as the title.
+    async fn aux_metadata(&mut self) -> Result<AuxMetadata, AudioStreamError> {
+        let (resp, mut head) = self.fetch_range(0, METADATA_PROBE_LEN).await?;
+        let headers = resp.headers();
+
+        let header_str = |name: &str| headers.get(name).and_then(|val| val.to_str().ok());
+
+        // A partial response's `Content-Length` only covers the requested range.
+        let len = if resp.status() == StatusCode::PARTIAL_CONTENT {
+            header_str(CONTENT_RANGE.as_str())
+                .and_then(|val| val.rsplit_once('/'))
+                .and_then(|(_, total)| total.parse().ok())
+        } else {
+            header_str(CONTENT_LENGTH.as_str()).and_then(|val| val.parse().ok())
+        };
+        let is_mpeg = header_str(CONTENT_TYPE.as_str())
+            .map_or(true, |mime| mime_essence(mime) == "audio/mpeg");
+        let icy_bitrate = header_str("icy-br")
+            .and_then(|val| val.split(',').next())
+            .and_then(|val| val.trim().parse::<u64>().ok());
+
+        let mut out = AuxMetadata {
+            source_url: Some(self.request.clone()),
+            title: header_str("icy-name").map(ToOwned::to_owned),
+            ..Default::default()
+        };
+
+        let mut audio_start = 0;
+        if is_mpeg {
+            // Embedded cover art can push the first frame past the initial read.
+            audio_start = id3v2_len(&head).unwrap_or(0);
+            if audio_start as usize >= head.len() {
+                head = self.fetch_range(audio_start, 4096).await?.1;
+            } else {
+                head.drain(..audio_start as usize);
+            }
+
+            if let Some(frame) = MpegFrame::parse(&head) {
+                out.sample_rate = Some(frame.sample_rate);
+                out.channels = Some(frame.channels);
+                out.duration = frame.xing_duration(&head).or_else(|| {
+                    len.map(|len: u64| frame.cbr_duration(len.saturating_sub(audio_start)))
+                });
+            }
+        }
+
+        if let (None, Some(len), Some(kbps)) = (out.duration, len, icy_bitrate) {
+            out.duration = Some(Duration::from_secs_f64(
+                len.saturating_sub(audio_start) as f64 * 8.0 / (kbps * 1000) as f64,
+            ));
+        }
+
+        Ok(out)
+    }
 }
 
 impl From<HttpRequest> for Input {
@@ -230,6 +443,150 @@ impl From<HttpRequest> for Input {
     }
 }
 
+/// Builds a probe hint from a `Content-Type` header, adding the usual file extension
+/// for common audio formats.
+fn hint_for_mime(mime: &str) -> Hint {
+    let mut hint = Hint::new();
+    hint.mime_type(mime);
+
+    let ext = match mime_essence(mime).as_str() {
+        "audio/mpeg" | "audio/mp3" => Some("mp3"),
+        "audio/aac" | "audio/aacp" => Some("aac"),
+        "audio/mp4" | "audio/x-m4a" | "video/mp4" => Some("m4a"),
+        "audio/ogg" | "application/ogg" | "audio/opus" => Some("ogg"),
+        "audio/webm" | "video/webm" => Some("webm"),
+        "audio/x-matroska" | "video/x-matroska" => Some("mkv"),
+        "audio/flac" | "audio/x-flac" => Some("flac"),
+        "audio/wav" | "audio/wave" | "audio/x-wav" | "audio/vnd.wave" => Some("wav"),
+        _ => None,
+    };
+
+    if let Some(ext) = ext {
+        hint.with_extension(ext);
+    }
+
+    hint
+}
+
+/// Strips parameters such as `charset` from a MIME type.
+fn mime_essence(mime: &str) -> String {
+    mime.split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase()
+}
+
+/// Returns the length of an ID3v2 tag at the start of `head`, including its header.
+fn id3v2_len(head: &[u8]) -> Option<u64> {
+    if head.len() < 10 || &head[..3] != b"ID3" {
+        return None;
+    }
+
+    // Tag sizes are stored as four 7-bit bytes, and exclude the 10-byte header
+    // and any 10-byte footer.
+    let size = head[6..10]
+        .iter()
+        .fold(0u64, |acc, &b| (acc << 7) | u64::from(b & 0x7f));
+    let footer = if head[5] & 0x10 == 0 { 0 } else { 10 };
+
+    Some(10 + size + footer)
+}
+
+/// Properties of an MPEG-1/2/2.5 Layer III frame header.
+#[derive(Debug, PartialEq)]
+struct MpegFrame {
+    mpeg1: bool,
+    bitrate: u32,
+    sample_rate: u32,
+    channels: u8,
+}
+
+impl MpegFrame {
+    /// Parses the frame header at the start of `head`.
+    fn parse(head: &[u8]) -> Option<Self> {
+        const MPEG1_KBPS: [u32; 15] = [
+            0, 32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+        ];
+        const MPEG2_KBPS: [u32; 15] =
+            [0, 8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+        const MPEG1_RATES: [u32; 3] = [44_100, 48_000, 32_000];
+
+        let header = head.get(..4)?;
+        if header[0] != 0xff || header[1] & 0xe0 != 0xe0 {
+            return None;
+        }
+
+        let version = (header[1] >> 3) & 0b11;
+        let layer = (header[1] >> 1) & 0b11;
+        let bitrate_idx = usize::from(header[2] >> 4);
+        let rate_idx = usize::from((header[2] >> 2) & 0b11);
+
+        // Only Layer III, with a known bitrate and sample rate.
+        if version == 0b01
+            || layer != 0b01
+            || bitrate_idx == 0
+            || bitrate_idx == 15
+            || rate_idx == 3
+        {
+            return None;
+        }
+
+        let mpeg1 = version == 0b11;
+        let kbps = if mpeg1 { MPEG1_KBPS } else { MPEG2_KBPS }[bitrate_idx];
+        let rate_div = match version {
+            0b11 => 1,
+            0b10 => 2,
+            _ => 4,
+        };
+
+        Some(Self {
+            mpeg1,
+            bitrate: kbps * 1000,
+            sample_rate: MPEG1_RATES[rate_idx] / rate_div,
+            channels: if header[3] >> 6 == 0b11 { 1 } else { 2 },
+        })
+    }
+
+    fn samples_per_frame(&self) -> u32 {
+        if self.mpeg1 {
+            1152
+        } else {
+            576
+        }
+    }
+
+    /// Reads the exact duration from a Xing or Info header in this (first) frame.
+    fn xing_duration(&self, head: &[u8]) -> Option<Duration> {
+        let side_info = match (self.mpeg1, self.channels) {
+            (true, 1) | (false, 2) => 17,
+            (true, _) => 32,
+            (false, _) => 9,
+        };
+        let xing = head.get(4 + side_info..4 + side_info + 12)?;
+
+        if &xing[..4] != b"Xing" && &xing[..4] != b"Info" {
+            return None;
+        }
+
+        let flags = u32::from_be_bytes(xing[4..8].try_into().ok()?);
+        if flags & 1 == 0 {
+            return None;
+        }
+
+        let frames = u32::from_be_bytes(xing[8..12].try_into().ok()?);
+
+        Some(Duration::from_secs_f64(
+            f64::from(frames) * f64::from(self.samples_per_frame()) / f64::from(self.sample_rate),
+        ))
+    }
+
+    /// Estimates the duration of `len` bytes of audio at this frame's bitrate.
+    fn cbr_duration(&self, len: u64) -> Duration {
+        Duration::from_secs_f64(len as f64 * 8.0 / f64::from(self.bitrate))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -237,6 +594,10 @@ mod tests {
         constants::test_data::{HTTP_OPUS_TARGET, HTTP_TARGET, HTTP_WEBM_TARGET},
         input::input_tests::*,
     };
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
 
     #[tokio::test]
     #[ntest::timeout(10_000)]
@@ -293,4 +654,77 @@ mod tests {
     async fn http_webm_backward_seek_correct() {
         backward_seek_correct(|| HttpRequest::new(Client::new(), HTTP_WEBM_TARGET.into())).await;
     }
+
+    struct Renew;
+
+    #[async_trait]
+    impl UrlRefresher for Renew {
+        async fn refresh(
+            &self,
+            url: &mut String,
+            _headers: &mut HeaderMap,
+            status: StatusCode,
+        ) -> bool {
+            assert_eq!(status, StatusCode::GONE);
+            *url = url.replace("/expired", "/fresh");
+            true
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn expired_url_is_refreshed() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buf = vec![0u8; 1024];
+                let n = stream.read(&mut buf).await.unwrap();
+
+                let resp: &[u8] = if buf[..n].starts_with(b"GET /fresh") {
+                    b"HTTP/1.1 206 Partial Content\r\nContent-Length: 4\r\nConnection: close\r\n\r\nsong"
+                } else {
+                    b"HTTP/1.1 410 Gone\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                };
+                stream.write_all(resp).await.unwrap();
+            }
+        });
+
+        let mut req =
+            HttpRequest::new(Client::new(), format!("http://{addr}/expired")).refresher(Renew);
+        let (_, body) = req.fetch_range(0, 4).await.unwrap();
+
+        assert_eq!(body, b"song");
+        assert_eq!(req.request, format!("http://{addr}/fresh"));
+    }
+
+    #[test]
+    fn mp3_duration_from_frame_header() {
+        // MPEG-1 Layer III, 128kbps, 44.1kHz, joint stereo.
+        let mut head = vec![0u8; 64];
+        head[..4].copy_from_slice(&[0xff, 0xfb, 0x90, 0x40]);
+
+        let frame = MpegFrame::parse(&head).unwrap();
+        assert_eq!(frame.sample_rate, 44_100);
+        assert_eq!(frame.channels, 2);
+        assert_eq!(frame.cbr_duration(1_600_000), Duration::from_secs(100));
+        assert!(frame.xing_duration(&head).is_none());
+
+        head[36..40].copy_from_slice(b"Info");
+        head[40..44].copy_from_slice(&1u32.to_be_bytes());
+        head[44..48].copy_from_slice(&11_025u32.to_be_bytes());
+        assert_eq!(
+            frame.xing_duration(&head),
+            Some(Duration::from_secs_f64(288.0))
+        );
+    }
+
+    #[test]
+    fn id3v2_tag_skipped() {
+        let head = [b'I', b'D', b'3', 4, 0, 0, 0, 0, 0x01, 0x7f];
+        assert_eq!(id3v2_len(&head), Some(10 + 255));
+        assert!(id3v2_len(&[0xff, 0xfb, 0x90, 0x40]).is_none());
+    }
 }
@@ -0,0 +1,168 @@
+use crate::input::{AudioStream, AudioStreamError, Compose, Input};
+use memmap2::Mmap;
+use std::{
+    error::Error,
+    ffi::OsStr,
+    fs::File as StdFile,
+    io::{Cursor, Read, Result as IoResult, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+use symphonia_core::{io::MediaSource, probe::Hint};
+
+/// A lazily instantiated local file, read through a memory mapping.
+///
+/// Large files (e.g., multi-hundred-MB audiobooks) are otherwise copied from the
+/// OS page cache into symphonia's own read buffers. Mapping the file lets reads
+/// be served directly from the page cache instead.
+///
+/// If the target cannot be mapped (it is empty, is not a regular file, or the
+/// platform refuses the mapping), this falls back to regular file reads, behaving
+/// identically to [`File`].
+///
+/// [`File`]: crate::input::File
+#[derive(Clone, Debug)]
+pub struct MmapFile<P: AsRef<Path>> {
+    path: P,
+}
+
+impl<P: AsRef<Path>> MmapFile<P> {
+    /// Creates a lazy memory-mapped file object, which will open the target path.
+    ///
+    /// This is infallible as the path is only checked during creation.
+    pub fn new(path: P) -> Self {
+        Self { path }
+    }
+}
+
+impl<P: AsRef<Path> + Send + Sync + 'static> From<MmapFile<P>> for Input {
+    fn from(val: MmapFile<P>) -> Self {
+        Input::Lazy(Box::new(val))
+    }
+}
+
+#[async_trait::async_trait]
+impl<P: AsRef<Path> + Send + Sync> Compose for MmapFile<P> {
+    fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let err: Box<dyn Error + Send + Sync> =
+            "Files should be created asynchronously.".to_string().into();
+        Err(AudioStreamError::Fail(err))
+    }
+
+    async fn create_async(
+        &mut self,
+    ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+        let path: PathBuf = self.path.as_ref().into();
+
+        // Mapping a file may page in its first chunk: keep this off the async executor.
+        let input = tokio::task::spawn_blocking(move || open_mapped(&path))
+            .await
+            .map_err(|e| AudioStreamError::Fail(Box::new(e)))?
+            .map_err(|io| AudioStreamError::Fail(Box::new(io)))?;
+
+        let mut hint = Hint::default();
+        if let Some(ext) = self.path.as_ref().extension().and_then(OsStr::to_str) {
+            hint.with_extension(ext);
+        }
+
+        Ok(AudioStream {
+            input,
+            hint: Some(hint),
+        })
+    }
+
+    fn should_create_async(&self) -> bool {
+        true
+    }
+}
+
+fn open_mapped(path: &Path) -> IoResult<Box<dyn MediaSource>> {
+    let file = StdFile::open(path)?;
+    let meta = file.metadata()?;
+
+    // Empty files cannot be mapped on all platforms, and pipes/devices may
+    // change length or refuse mapping entirely.
+    if !meta.is_file() || meta.len() == 0 {
+        return Ok(Box::new(file));
+    }
+
+    // SAFETY: the mapping is read-only. Truncation of the underlying file by
+    // another process while mapped is outside of our control, in the same way
+    // as for any other mmap consumer.
+    match unsafe { Mmap::map(&file) } {
+        Ok(map) => {
+            #[cfg(unix)]
+            let _ = map.advise(memmap2::Advice::Sequential);
+
+            Ok(Box::new(MmapSource {
+                inner: Cursor::new(map),
+            }))
+        },
+        Err(e) => {
+            tracing::debug!("Failed to mmap {:?}, falling back to reads: {e:?}", path);
+            Ok(Box::new(file))
+        },
+    }
+}
+
+/// A read-only [`MediaSource`] over a memory-mapped file.
+struct MmapSource {
+    inner: Cursor<Mmap>,
+}
+
+impl Read for MmapSource {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for MmapSource {
+    fn seek(&mut self, pos: SeekFrom) -> IoResult<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+impl MediaSource for MmapSource {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.inner.get_ref().len() as u64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::test_data::{FILE_WAV_TARGET, FILE_WEBM_TARGET},
+        input::input_tests::*,
+    };
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn mmap_track_plays() {
+        track_plays_mixed(|| MmapFile::new(FILE_WAV_TARGET)).await;
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn mmap_forward_seek_correct() {
+        forward_seek_correct(|| MmapFile::new(FILE_WEBM_TARGET)).await;
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn mmap_backward_seek_correct() {
+        backward_seek_correct(|| MmapFile::new(FILE_WEBM_TARGET)).await;
+    }
+
+    #[test]
+    fn mmap_source_reports_file_length() {
+        let src = open_mapped(Path::new(FILE_WAV_TARGET)).unwrap();
+        let expected = std::fs::metadata(FILE_WAV_TARGET).unwrap().len();
+
+        assert!(src.is_seekable());
+        assert_eq!(src.byte_len(), Some(expected));
+    }
+}
@@ -1,8 +1,24 @@
+#[cfg(feature = "archive")]
+mod archive;
+#[cfg(feature = "capture")]
+mod capture;
 mod file;
 mod hls;
 mod http;
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg(feature = "object-store")]
+mod object_store;
 mod ytdl;
 
+#[cfg(feature = "archive")]
+pub use self::archive::*;
+#[cfg(feature = "capture")]
+pub use self::capture::*;
+#[cfg(feature = "mmap")]
+pub use self::mmap::*;
+#[cfg(feature = "object-store")]
+pub use self::object_store::*;
 pub use self::{file::*, hls::*, http::*, ytdl::*};
 
 use std::{
@@ -3,9 +3,14 @@ use crate::input::{
     AudioStream,
     AudioStreamError,
     AuxMetadata,
+    ChildLimits,
     Compose,
     HttpRequest,
     Input,
+    Prefetch,
+    PrefetchMonitor,
+    ProbeCache,
+    ProbeResult,
 };
 use async_trait::async_trait;
 use reqwest::{
@@ -40,6 +45,8 @@ pub struct YoutubeDl {
     metadata: Option<AuxMetadata>,
     query: QueryType,
     user_args: Vec<String>,
+    limits: ChildLimits,
+    probe_cache: Option<ProbeCache>,
 }
 
 impl YoutubeDl {
@@ -63,6 +70,8 @@ impl YoutubeDl {
             metadata: None,
             query: QueryType::Url(url),
             user_args: Vec::new(),
+            limits: ChildLimits::default(),
+            probe_cache: None,
         }
     }
 
@@ -83,6 +92,8 @@ impl YoutubeDl {
             metadata: None,
             query: QueryType::Search(query),
             user_args: Vec::new(),
+            limits: ChildLimits::default(),
+            probe_cache: None,
         }
     }
 
@@ -93,6 +104,22 @@ impl YoutubeDl {
         self
     }
 
+    /// Sets resource limits for each "yt-dlp" process.
+    #[must_use]
+    pub fn limits(mut self, limits: ChildLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Shares a cache of resolved streams and metadata, keyed by URL or search query.
+    ///
+    /// While a cached result is live, no "yt-dlp" process is run for the same query.
+    #[must_use]
+    pub fn probe_cache(mut self, cache: ProbeCache) -> Self {
+        self.probe_cache = Some(cache);
+        self
+    }
+
     /// Runs a search for the given query, returning a list of up to `n_results`
     /// possible matches which are `AuxMetadata` objects containing a valid URL.
     ///
@@ -124,6 +151,17 @@ impl YoutubeDl {
                 &new_query
             },
         };
+
+        let cached = self
+            .probe_cache
+            .as_ref()
+            .and_then(|cache| cache.get(query_str))
+            .and_then(|result| result.metadata.zip(result.ytdl));
+        if let Some((meta, out)) = cached {
+            self.metadata = Some(meta);
+            return Ok(out);
+        }
+
         let ytdl_args = [
             "-j",
             query_str,
@@ -132,7 +170,10 @@ impl YoutubeDl {
             "--no-playlist",
         ];
 
-        let mut output = Command::new(self.program)
+        let mut cmd = Command::new(self.program);
+        self.limits.apply_async(&mut cmd);
+
+        let mut output = cmd
             .args(self.user_args.clone())
             .args(ytdl_args)
             .output()
@@ -171,6 +212,18 @@ impl YoutubeDl {
             })?
             .as_aux_metadata();
 
+        if let Some(cache) = &self.probe_cache {
+            cache.insert(
+                query_str.as_str(),
+                ProbeResult {
+                    metadata: Some(meta.clone()),
+                    content_length: out[0].filesize,
+                    mime_type: None,
+                    ytdl: Some(out.clone()),
+                },
+            );
+        }
+
         self.metadata = Some(meta);
 
         Ok(out)
@@ -220,6 +273,10 @@ impl Compose for YoutubeDl {
                     request: result.url,
                     headers,
                     content_length: result.filesize,
+                    prefetch: Prefetch::default(),
+                    monitor: PrefetchMonitor::new(),
+                    probe_cache: None,
+                    refresher: None,
                 };
                 req.create_async().await
             },
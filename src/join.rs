@@ -33,7 +33,7 @@ use tokio::time::{self, Timeout};
 #[pin_project]
 pub struct Join {
     #[pin]
-    gw: JoinClass<()>,
+    gw: JoinClass<JoinResult<()>>,
     #[pin]
     driver: JoinClass<ConnectionResult<()>>,
     state: JoinState,
@@ -43,7 +43,7 @@ pub struct Join {
 impl Join {
     pub(crate) fn new(
         driver: RecvFut<'static, ConnectionResult<()>>,
-        gw_recv: RecvFut<'static, ()>,
+        gw_recv: RecvFut<'static, JoinResult<()>>,
         timeout: Option<Duration>,
     ) -> Self {
         Self {
@@ -62,7 +62,7 @@ impl Future for Join {
         let this = self.project();
 
         if *this.state == JoinState::BeforeGw {
-            let poll = this.gw.poll(cx);
+            let poll = this.gw.poll(cx).map(|res| res.and_then(convert::identity));
             match poll {
                 Poll::Ready(a) if a.is_ok() => {
                     *this.state = JoinState::AfterGw;
@@ -117,11 +117,14 @@ enum JoinState {
 #[pin_project]
 pub struct JoinGateway {
     #[pin]
-    inner: JoinClass<ConnectionInfo>,
+    inner: JoinClass<JoinResult<ConnectionInfo>>,
 }
 
 impl JoinGateway {
-    pub(crate) fn new(recv: RecvFut<'static, ConnectionInfo>, timeout: Option<Duration>) -> Self {
+    pub(crate) fn new(
+        recv: RecvFut<'static, JoinResult<ConnectionInfo>>,
+        timeout: Option<Duration>,
+    ) -> Self {
         Self {
             inner: JoinClass::new(recv, timeout),
         }
@@ -132,7 +135,10 @@ impl Future for JoinGateway {
     type Output = JoinResult<ConnectionInfo>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.project().inner.poll(cx)
+        self.project()
+            .inner
+            .poll(cx)
+            .map(|res| res.and_then(convert::identity))
     }
 }
 
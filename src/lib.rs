@@ -15,8 +15,16 @@
 //!  * A standalone driver for voice calls, via the `"driver"` feature. If you can create
 //!     a `ConnectionInfo` using any other gateway, or language for your bot, then you
 //!     can run the songbird voice driver.
+//!  * A minimal built-in gateway client for voice-only bots, via the `"standalone-gateway"`
+//!     feature.
 //!  * Voice receive and RT(C)P packet handling via the `"receive"` feature.
+//!  * Noise suppression of decoded received audio via the `"denoise"` feature.
 //!  * SIMD-accelerated JSON decoding via the `"simd-json"` feature.
+//!  * Streaming audio from S3-compatible object stores via the `"object-store"` feature.
+//!  * Memory-mapped local file inputs via the `"mmap"` feature.
+//!  * Inputs read from members of local zip and tar archives via the `"archive"` feature.
+//!  * Experimental control of drivers from other processes over Unix sockets via the `"ipc"` feature.
+//!  * A local mock voice server for end-to-end connection tests via the `"mock-server"` feature.
 //!  * And, by default, a fully featured voice system featuring events, queues,
 //!     seeking on compatible streams, shared multithreaded audio stream caches,
 //!     and direct Opus data passthrough from DCA files.
@@ -97,10 +105,13 @@ pub mod input;
 pub mod join;
 #[cfg(feature = "gateway")]
 mod manager;
+pub mod opus_util;
 #[cfg(feature = "serenity")]
 pub mod serenity;
 #[cfg(feature = "gateway")]
 pub mod shards;
+#[cfg(feature = "standalone-gateway")]
+pub mod standalone;
 #[cfg(any(test, feature = "internals"))]
 pub mod test_utils;
 #[cfg(feature = "driver")]
@@ -1,5 +1,9 @@
 #[cfg(feature = "serenity")]
 use crate::shards::SerenitySharder;
+#[cfg(feature = "standalone-gateway")]
+use crate::standalone::{StandaloneError, StandaloneGateway};
+#[cfg(feature = "driver")]
+use crate::tracks::{Track, TrackHandle};
 use crate::{
     error::{JoinError, JoinResult},
     id::{ChannelId, GuildId, UserId},
@@ -13,6 +17,8 @@ use async_trait::async_trait;
 use dashmap::DashMap;
 #[cfg(feature = "serenity")]
 use futures::channel::mpsc::UnboundedSender as Sender;
+#[cfg(feature = "driver")]
+use futures::future::join_all;
 use once_cell::sync::OnceCell;
 use parking_lot::RwLock as PRwLock;
 #[cfg(feature = "serenity")]
@@ -24,12 +30,19 @@ use serenity::{
     },
 };
 use std::sync::Arc;
+#[cfg(feature = "driver")]
+use std::time::{Duration, Instant};
 use tokio::sync::Mutex;
 #[cfg(feature = "serenity")]
 use tracing::debug;
 #[cfg(feature = "twilight")]
 use twilight_model::gateway::event::Event as TwilightEvent;
 
+/// Time between all tracks becoming playable and their scheduled start in
+/// [`Songbird::synchronized_play`], allowing each mixer to receive its command.
+#[cfg(feature = "driver")]
+const SYNCHRONIZED_START_LEAD: Duration = Duration::from_millis(100);
+
 #[derive(Clone, Copy, Debug)]
 struct ClientData {
     shard_count: u64,
@@ -120,6 +133,36 @@ impl Songbird {
         }
     }
 
+    #[cfg(feature = "standalone-gateway")]
+    /// Create a new Songbird instance which connects to Discord by itself, using
+    /// a minimal [`StandaloneGateway`].
+    ///
+    /// This requires only a bot token, and handles all voice state and voice server
+    /// updates without any user involvement. The gateway connection is closed once
+    /// this instance is dropped.
+    ///
+    /// The token should not include a `Bot ` prefix.
+    pub async fn standalone_gateway(
+        token: impl Into<String>,
+        config: Config,
+    ) -> Result<Arc<Self>, StandaloneError> {
+        let gateway = StandaloneGateway::connect(token).await?;
+
+        let songbird = Arc::new(Self {
+            client_data: OnceCell::with_value(ClientData {
+                shard_count: 1,
+                user_id: gateway.user_id(),
+            }),
+            calls: DashMap::new(),
+            sharder: Sharder::Generic(Arc::new(gateway.clone())),
+            config: config.initialise_disposer().into(),
+        });
+
+        gateway.attach(&songbird);
+
+        Ok(songbird)
+    }
+
     /// Set the bot's user, and the number of shards in use.
     ///
     /// If this struct is already initialised (e.g., from [`::twilight`]),
@@ -174,12 +217,17 @@ impl Songbird {
                         .get_shard(shard)
                         .expect("Failed to get shard handle: shard_count incorrect?");
 
-                    let call = Call::from_config(
-                        guild_id,
-                        shard_handle,
-                        info.user_id,
-                        self.config.read().clone(),
-                    );
+                    let config = self.config.read().clone();
+
+                    #[cfg(feature = "driver")]
+                    let config = if config.call_identifier.is_some() {
+                        config
+                    } else {
+                        config
+                            .call_identifier(Some(format!("guild {} shard {}", guild_id.0, shard)))
+                    };
+
+                    let call = Call::from_config(guild_id, shard_handle, info.user_id, config);
 
                     Arc::new(Mutex::new(call))
                 })
@@ -350,6 +398,50 @@ impl Songbird {
         self.calls.remove(&guild_id);
         Ok(())
     }
+
+    #[cfg(feature = "driver")]
+    /// Plays a track in each of the given guilds, starting all of them on the
+    /// same wall-clock frame boundary.
+    ///
+    /// `make_track` is called once per guild with an existing [`Call`]; guilds
+    /// without a [`Call`] are skipped. Each track is added paused and made
+    /// playable, after which all are scheduled via [`TrackHandle::play_at`] to
+    /// begin shortly afterwards. This compensates for each driver's tick phase, so
+    /// playback across all calls begins within half a frame of one another.
+    ///
+    /// Returns the handle of every track which was added, alongside its guild.
+    ///
+    /// [`Call`]: Call
+    /// [`TrackHandle::play_at`]: crate::tracks::TrackHandle::play_at
+    pub async fn synchronized_play<G, F>(
+        &self,
+        guilds: impl IntoIterator<Item = G>,
+        mut make_track: F,
+    ) -> Vec<(GuildId, TrackHandle)>
+    where
+        G: Into<GuildId>,
+        F: FnMut(GuildId) -> Track,
+    {
+        let mut handles = vec![];
+        for guild_id in guilds {
+            let guild_id = guild_id.into();
+            if let Some(call) = self.get(guild_id) {
+                let track = make_track(guild_id).pause();
+                let handle = call.lock().await.play(track);
+                handles.push((guild_id, handle));
+            }
+        }
+
+        // Failures here are reported to each track's own event handlers.
+        join_all(handles.iter().map(|(_, h)| h.make_playable_async())).await;
+
+        let start = Instant::now() + SYNCHRONIZED_START_LEAD;
+        for (_, handle) in &handles {
+            _ = handle.play_at(start);
+        }
+
+        handles
+    }
 }
 
 impl<'a> IntoIterator for &'a Songbird {
@@ -393,19 +485,20 @@ impl Songbird {
                 }
             },
             TwilightEvent::VoiceStateUpdate(v) => {
-                if self
-                    .client_data
-                    .get()
-                    .map_or(true, |data| v.0.user_id.into_nonzero() != data.user_id.0)
-                {
+                let Some(data) = self.client_data.get() else {
                     return;
-                }
+                };
 
                 let call = v.0.guild_id.map(GuildId::from).and_then(|id| self.get(id));
 
                 if let Some(call) = call {
                     let mut handler = call.lock().await;
-                    handler.update_state(v.0.session_id.clone(), v.0.channel_id);
+                    if v.0.user_id.into_nonzero() == data.user_id.0 {
+                        handler.update_state(v.0.session_id.clone(), v.0.channel_id);
+                    } else {
+                        #[cfg(feature = "driver")]
+                        handler.update_member_state(v.0.user_id, v.0.channel_id);
+                    }
                 }
             },
             _ => {},
@@ -453,15 +546,18 @@ impl VoiceGatewayManager for Songbird {
     }
 
     async fn state_update(&self, guild_id: SerenityGuild, voice_state: &VoiceState) {
-        if self.client_data.get().map_or(true, |data| {
-            voice_state.user_id.get() != data.user_id.0.get()
-        }) {
+        let Some(data) = self.client_data.get() else {
             return;
-        }
+        };
 
         if let Some(call) = self.get(guild_id) {
             let mut handler = call.lock().await;
-            handler.update_state(voice_state.session_id.clone(), voice_state.channel_id);
+            if voice_state.user_id.get() == data.user_id.0.get() {
+                handler.update_state(voice_state.session_id.clone(), voice_state.channel_id);
+            } else {
+                #[cfg(feature = "driver")]
+                handler.update_member_state(voice_state.user_id, voice_state.channel_id);
+            }
         }
     }
 }
@@ -0,0 +1,303 @@
+//! Inspection of Opus packet headers, as defined in [RFC 6716, section 3].
+//!
+//! Every Opus packet begins with a table-of-contents (TOC) byte describing the
+//! codec mode, audio bandwidth, frame duration, and channel count used, followed
+//! by information on how many frames the packet holds. These helpers read this
+//! information without decoding any audio, e.g., to check that packets are suited
+//! to passthrough before sending them, or to analyse received audio.
+//!
+//! [RFC 6716, section 3]: https://www.rfc-editor.org/rfc/rfc6716#section-3
+
+use std::{
+    error::Error as StdError,
+    fmt::{Display, Formatter, Result as FmtResult},
+    time::Duration,
+};
+
+/// Number of samples per channel in one 2.5ms unit of audio at 48kHz.
+const SAMPLES_PER_UNIT: usize = 120;
+
+/// The longest duration of audio an Opus packet may hold, in 2.5ms units.
+const MAX_PACKET_UNITS: usize = 48;
+
+/// The coding mode used by an Opus packet.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Mode {
+    /// Linear-prediction coding (SILK), used for speech at lower bandwidths.
+    Silk,
+    /// SILK combined with CELT for higher frequencies.
+    Hybrid,
+    /// MDCT-based coding (CELT), used for music and low-latency audio.
+    Celt,
+}
+
+/// The audio bandwidth coded by an Opus packet.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[non_exhaustive]
+pub enum Bandwidth {
+    /// 4kHz bandwidth, sampled at 8kHz.
+    Narrowband,
+    /// 6kHz bandwidth, sampled at 12kHz.
+    Mediumband,
+    /// 8kHz bandwidth, sampled at 16kHz.
+    Wideband,
+    /// 12kHz bandwidth, sampled at 24kHz.
+    SuperWideband,
+    /// 20kHz bandwidth, sampled at 48kHz.
+    Fullband,
+}
+
+/// How the frames of an Opus packet are sized.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum Framing {
+    /// The packet holds a single frame.
+    Single,
+    /// The packet holds several frames of equal length (constant bitrate).
+    Cbr,
+    /// The packet holds several frames of differing lengths (variable bitrate).
+    Vbr,
+}
+
+/// The table-of-contents byte beginning every Opus packet.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Toc(pub u8);
+
+impl Toc {
+    /// Returns the packet's configuration number, from `0` to `31`.
+    #[must_use]
+    pub fn config(self) -> u8 {
+        self.0 >> 3
+    }
+
+    /// Returns the coding mode used by each frame.
+    #[must_use]
+    pub fn mode(self) -> Mode {
+        match self.config() {
+            0..=11 => Mode::Silk,
+            12..=15 => Mode::Hybrid,
+            _ => Mode::Celt,
+        }
+    }
+
+    /// Returns the audio bandwidth coded by each frame.
+    #[must_use]
+    pub fn bandwidth(self) -> Bandwidth {
+        match self.config() {
+            0..=3 | 16..=19 => Bandwidth::Narrowband,
+            4..=7 => Bandwidth::Mediumband,
+            8..=11 | 20..=23 => Bandwidth::Wideband,
+            12..=13 | 24..=27 => Bandwidth::SuperWideband,
+            _ => Bandwidth::Fullband,
+        }
+    }
+
+    /// Returns whether each frame is coded in stereo.
+    #[must_use]
+    pub fn is_stereo(self) -> bool {
+        self.0 & 0b100 != 0
+    }
+
+    /// Returns the number of samples per channel in each frame, at 48kHz.
+    #[must_use]
+    pub fn frame_samples(self) -> usize {
+        self.frame_units() * SAMPLES_PER_UNIT
+    }
+
+    /// Returns the duration of each frame.
+    #[must_use]
+    pub fn frame_duration(self) -> Duration {
+        Duration::from_micros(self.frame_units() as u64 * 2500)
+    }
+
+    /// Returns the frame count code, from `0` to `3`.
+    #[must_use]
+    pub fn frame_code(self) -> u8 {
+        self.0 & 0b11
+    }
+
+    /// Frame duration, in 2.5ms units.
+    fn frame_units(self) -> usize {
+        let config = self.config();
+        let size_idx = config % 4;
+
+        match config {
+            // SILK: 10, 20, 40, 60ms.
+            0..=11 => [4, 8, 16, 24][usize::from(size_idx)],
+            // Hybrid: 10, 20ms.
+            12..=15 => [4, 8][usize::from(size_idx % 2)],
+            // CELT: 2.5, 5, 10, 20ms.
+            _ => 1 << size_idx,
+        }
+    }
+}
+
+impl From<u8> for Toc {
+    fn from(val: u8) -> Self {
+        Self(val)
+    }
+}
+
+/// Header information read from a complete Opus packet.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct PacketInfo {
+    /// The packet's table-of-contents byte.
+    pub toc: Toc,
+    /// The number of frames held in the packet.
+    pub frame_count: usize,
+    /// How the packet's frames are sized.
+    pub framing: Framing,
+}
+
+impl PacketInfo {
+    /// Reads the header of the Opus packet `packet`.
+    ///
+    /// This checks the frame count and the packet's length, but does not validate
+    /// the size of each frame.
+    pub fn parse(packet: &[u8]) -> Result<Self, PacketError> {
+        let (&toc, body) = packet.split_first().ok_or(PacketError::Empty)?;
+        let toc = Toc(toc);
+
+        let (frame_count, framing) = match toc.frame_code() {
+            0 => (1, Framing::Single),
+            1 if body.len() % 2 != 0 => return Err(PacketError::UnevenFrames),
+            1 => (2, Framing::Cbr),
+            2 => (2, Framing::Vbr),
+            _ => {
+                let &count = body.first().ok_or(PacketError::MissingFrameCount)?;
+                let framing = if count & 0x80 != 0 {
+                    Framing::Vbr
+                } else {
+                    Framing::Cbr
+                };
+
+                (usize::from(count & 0x3f), framing)
+            },
+        };
+
+        if frame_count == 0 || frame_count * toc.frame_units() > MAX_PACKET_UNITS {
+            return Err(PacketError::InvalidFrameCount(frame_count));
+        }
+
+        Ok(Self {
+            toc,
+            frame_count,
+            framing,
+        })
+    }
+
+    /// Returns the number of samples per channel held in the packet, at 48kHz.
+    #[must_use]
+    pub fn samples(&self) -> usize {
+        self.frame_count * self.toc.frame_samples()
+    }
+
+    /// Returns the duration of audio held in the packet.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        self.toc.frame_duration() * self.frame_count as u32
+    }
+}
+
+/// Errors encountered while reading an Opus packet's header.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum PacketError {
+    /// The packet held no bytes.
+    Empty,
+    /// A packet with an arbitrary number of frames was missing its frame count byte.
+    MissingFrameCount,
+    /// The packet held no frames, or more than 120ms of audio.
+    InvalidFrameCount(usize),
+    /// A packet of two equal-length frames held an odd number of bytes.
+    UnevenFrames,
+}
+
+impl Display for PacketError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Self::Empty => f.write_str("packet was empty"),
+            Self::MissingFrameCount => f.write_str("packet was missing its frame count"),
+            Self::InvalidFrameCount(n) => f.write_fmt(format_args!(
+                "packet held an invalid number of frames ({n})"
+            )),
+            Self::UnevenFrames => f.write_str("packet's equal-length frames had uneven sizes"),
+        }
+    }
+}
+
+impl StdError for PacketError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toc_fields_are_read() {
+        // Config 31 (CELT FB 20ms), stereo, one frame: Discord's usual packet.
+        let toc = Toc(0b1111_1100);
+        assert_eq!(toc.mode(), Mode::Celt);
+        assert_eq!(toc.bandwidth(), Bandwidth::Fullband);
+        assert!(toc.is_stereo());
+        assert_eq!(toc.frame_samples(), 960);
+        assert_eq!(toc.frame_duration(), Duration::from_millis(20));
+
+        // Config 3 (SILK NB 60ms), mono.
+        let toc = Toc(0b0001_1000);
+        assert_eq!(toc.mode(), Mode::Silk);
+        assert_eq!(toc.bandwidth(), Bandwidth::Narrowband);
+        assert!(!toc.is_stereo());
+        assert_eq!(toc.frame_duration(), Duration::from_millis(60));
+
+        // Config 13 (Hybrid SWB 20ms).
+        let toc = Toc(13 << 3);
+        assert_eq!(toc.mode(), Mode::Hybrid);
+        assert_eq!(toc.bandwidth(), Bandwidth::SuperWideband);
+        assert_eq!(toc.frame_samples(), 960);
+
+        // Config 16 (CELT NB 2.5ms).
+        assert_eq!(Toc(16 << 3).frame_duration(), Duration::from_micros(2500));
+    }
+
+    #[test]
+    fn frame_counts_and_framing_are_read() {
+        let info = PacketInfo::parse(&[0xfc, 0xff, 0xfe]).unwrap();
+        assert_eq!((info.frame_count, info.framing), (1, Framing::Single));
+        assert_eq!(info.samples(), 960);
+
+        let info = PacketInfo::parse(&[0xfd, 1, 2]).unwrap();
+        assert_eq!((info.frame_count, info.framing), (2, Framing::Cbr));
+        assert_eq!(info.duration(), Duration::from_millis(40));
+
+        let info = PacketInfo::parse(&[0xfe, 1, 2, 3]).unwrap();
+        assert_eq!((info.frame_count, info.framing), (2, Framing::Vbr));
+
+        let info = PacketInfo::parse(&[0xff, 0x83, 1, 2, 3]).unwrap();
+        assert_eq!((info.frame_count, info.framing), (3, Framing::Vbr));
+        assert_eq!(info.samples(), 2880);
+    }
+
+    #[test]
+    fn malformed_packets_are_rejected() {
+        assert_eq!(PacketInfo::parse(&[]), Err(PacketError::Empty));
+        assert_eq!(
+            PacketInfo::parse(&[0xfd, 1]),
+            Err(PacketError::UnevenFrames)
+        );
+        assert_eq!(
+            PacketInfo::parse(&[0xff]),
+            Err(PacketError::MissingFrameCount)
+        );
+        assert_eq!(
+            PacketInfo::parse(&[0xff, 0x00]),
+            Err(PacketError::InvalidFrameCount(0))
+        );
+        // 7 frames of 20ms exceeds the 120ms limit.
+        assert_eq!(
+            PacketInfo::parse(&[0xff, 0x07]),
+            Err(PacketError::InvalidFrameCount(7))
+        );
+    }
+}
@@ -0,0 +1,639 @@
+//! A minimal Discord gateway client for voice-only applications.
+//!
+//! Tools which only need voice (e.g., recorders or TTS daemons) can use this to perform
+//! the voice state handshake for a bot token directly, rather than depending upon a full
+//! Discord library such as serenity or twilight.
+//!
+//! This client only requests the `GUILD_VOICE_STATES` intent, and only understands the
+//! events needed to drive [`Call`]s. It maintains a single gateway connection (one shard),
+//! resuming or re-identifying as needed.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use songbird::{Config, Songbird};
+//! use std::num::NonZeroU64;
+//!
+//! # async {
+//! let token = std::env::var("DISCORD_TOKEN").unwrap();
+//! let manager = Songbird::standalone_gateway(token, Config::default())
+//!     .await
+//!     .expect("Failed to connect to Discord.");
+//!
+//! let guild_id = NonZeroU64::new(1).unwrap();
+//! let channel_id = NonZeroU64::new(2).unwrap();
+//! let call = manager.join(guild_id, channel_id).await;
+//! # };
+//! ```
+//!
+//! [`Call`]: crate::Call
+
+use crate::{
+    error::{JoinError, JoinResult},
+    id::{ChannelId, GuildId, UserId},
+    shards::{GenericSharder, VoiceUpdate},
+    Songbird,
+};
+use async_trait::async_trait;
+use flume::{Receiver, Sender};
+use futures::{SinkExt, StreamExt};
+use once_cell::sync::OnceCell;
+use serde_json::{json, Error as JsonError, Value};
+use std::{
+    error::Error,
+    fmt,
+    num::NonZeroU64,
+    sync::{Arc, Weak},
+    time::Duration,
+};
+use tokio::{
+    net::TcpStream,
+    time::{interval_at, timeout, Instant, MissedTickBehavior},
+};
+use tokio_tungstenite::{
+    tungstenite::{
+        error::Error as TungsteniteError,
+        protocol::{frame::coding::CloseCode, CloseFrame},
+        Message,
+    },
+    MaybeTlsStream,
+    WebSocketStream,
+};
+use tracing::{debug, error, info, instrument, warn};
+
+type WsStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+const GATEWAY_URL: &str = "wss://gateway.discord.gg";
+const GATEWAY_QUERY: &str = "/?v=10&encoding=json";
+
+/// Only voice state updates (and voice server updates, which need no intent) are required.
+const INTENT_GUILD_VOICE_STATES: u64 = 1 << 7;
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+mod opcode {
+    pub const DISPATCH: u8 = 0;
+    pub const HEARTBEAT: u8 = 1;
+    pub const IDENTIFY: u8 = 2;
+    pub const VOICE_STATE_UPDATE: u8 = 4;
+    pub const RESUME: u8 = 6;
+    pub const RECONNECT: u8 = 7;
+    pub const INVALID_SESSION: u8 = 9;
+    pub const HELLO: u8 = 10;
+    pub const HEARTBEAT_ACK: u8 = 11;
+}
+
+/// Error returned when the standalone gateway fails to connect to Discord.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum StandaloneError {
+    /// The websocket connection failed.
+    Ws(TungsteniteError),
+    /// A gateway payload could not be (de)serialised.
+    Json(JsonError),
+    /// Discord closed the connection, e.g., due to an invalid token.
+    Closed(Option<CloseFrame<'static>>),
+    /// Discord rejected this session.
+    InvalidSession,
+    /// Discord sent an unexpected payload during the handshake.
+    UnexpectedPayload,
+    /// Discord did not complete the handshake in time.
+    TimedOut,
+}
+
+impl StandaloneError {
+    /// Indicates whether reconnecting with the same token can never succeed,
+    /// e.g., due to an invalid token or disallowed intents.
+    #[must_use]
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            Self::Closed(Some(frame)) => {
+                let code = u16::from(frame.code);
+                code == 4004 || (4010..=4014).contains(&code)
+            },
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for StandaloneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "standalone gateway failed: ")?;
+        match self {
+            Self::Ws(e) => e.fmt(f),
+            Self::Json(e) => e.fmt(f),
+            Self::Closed(Some(frame)) => write!(f, "connection closed ({frame})"),
+            Self::Closed(None) => write!(f, "connection closed"),
+            Self::InvalidSession => write!(f, "session was invalidated"),
+            Self::UnexpectedPayload => write!(f, "unexpected payload during handshake"),
+            Self::TimedOut => write!(f, "handshake timed out"),
+        }
+    }
+}
+
+impl Error for StandaloneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Ws(e) => Some(e),
+            Self::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<TungsteniteError> for StandaloneError {
+    fn from(e: TungsteniteError) -> Self {
+        Self::Ws(e)
+    }
+}
+
+impl From<JsonError> for StandaloneError {
+    fn from(e: JsonError) -> Self {
+        Self::Json(e)
+    }
+}
+
+/// Handle to a minimal, single-shard Discord gateway connection.
+///
+/// This is cheap to clone. The background connection task exits once every
+/// handle (including those held by a [`Songbird`] instance) has been dropped.
+///
+/// Most users will want to use [`Songbird::standalone_gateway`] rather than
+/// creating this directly.
+#[derive(Clone, Debug)]
+pub struct StandaloneGateway {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    user_id: UserId,
+    commands: Sender<String>,
+    manager: Arc<OnceCell<Weak<Songbird>>>,
+}
+
+impl StandaloneGateway {
+    /// Connects to Discord's gateway using a bot token, returning once the
+    /// session is ready.
+    ///
+    /// The token should not include a `Bot ` prefix.
+    #[instrument(skip(token))]
+    pub async fn connect(token: impl Into<String>) -> Result<Self, StandaloneError> {
+        let mut session = Session::new(token.into());
+        let (ws, heartbeat) = session.open().await?;
+        let user_id = session
+            .user_id
+            .expect("A freshly identified session must know its user ID.");
+
+        info!("Standalone gateway ready as user {}.", user_id);
+
+        let (commands, rx) = flume::unbounded();
+        let manager = Arc::new(OnceCell::new());
+
+        tokio::spawn(runner(session, ws, heartbeat, rx, manager.clone()));
+
+        Ok(Self {
+            inner: Arc::new(Inner {
+                user_id,
+                commands,
+                manager,
+            }),
+        })
+    }
+
+    /// Returns the ID of the bot user this gateway is connected as.
+    #[must_use]
+    pub fn user_id(&self) -> UserId {
+        self.inner.user_id
+    }
+
+    /// Forwards voice events received from Discord to a [`Songbird`] instance.
+    ///
+    /// Only the first manager attached to a gateway will receive events.
+    pub(crate) fn attach(&self, manager: &Arc<Songbird>) {
+        if self.inner.manager.set(Arc::downgrade(manager)).is_err() {
+            warn!("Standalone gateway was already attached to a Songbird instance.");
+        }
+    }
+}
+
+impl GenericSharder for StandaloneGateway {
+    fn get_shard(&self, _shard_id: u64) -> Option<Arc<dyn VoiceUpdate + Send + Sync>> {
+        Some(Arc::new(self.clone()))
+    }
+}
+
+#[async_trait]
+impl VoiceUpdate for StandaloneGateway {
+    async fn update_voice_state(
+        &self,
+        guild_id: GuildId,
+        channel_id: Option<ChannelId>,
+        self_deaf: bool,
+        self_mute: bool,
+    ) -> JoinResult<()> {
+        let payload = json!({
+            "op": opcode::VOICE_STATE_UPDATE,
+            "d": {
+                "channel_id": channel_id.map(|c| c.0.to_string()),
+                "guild_id": guild_id.0.to_string(),
+                "self_deaf": self_deaf,
+                "self_mute": self_mute,
+            }
+        });
+
+        self.inner
+            .commands
+            .send(payload.to_string())
+            .map_err(|_| JoinError::NoSender)
+    }
+}
+
+/// Identify and resume state of a gateway session.
+struct Session {
+    token: String,
+    user_id: Option<UserId>,
+    session_id: Option<String>,
+    resume_url: Option<String>,
+    seq: Option<u64>,
+}
+
+impl Session {
+    fn new(token: String) -> Self {
+        Self {
+            token,
+            user_id: None,
+            session_id: None,
+            resume_url: None,
+            seq: None,
+        }
+    }
+
+    fn can_resume(&self) -> bool {
+        self.session_id.is_some() && self.seq.is_some()
+    }
+
+    fn invalidate(&mut self) {
+        self.session_id = None;
+        self.resume_url = None;
+        self.seq = None;
+    }
+
+    /// Connects to the gateway and resumes the current session, or identifies a new one.
+    ///
+    /// Returns the websocket and its heartbeat interval.
+    async fn open(&mut self) -> Result<(WsStream, Duration), StandaloneError> {
+        let base = match &self.resume_url {
+            Some(url) if self.can_resume() => url.as_str(),
+            _ => GATEWAY_URL,
+        };
+        let url = format!("{}{GATEWAY_QUERY}", base.trim_end_matches('/'));
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await?;
+
+        let hello = timeout(HANDSHAKE_TIMEOUT, recv(&mut ws))
+            .await
+            .map_err(|_| StandaloneError::TimedOut)??;
+        let heartbeat = match hello {
+            Some(v) if op(&v) == Some(opcode::HELLO) => v["d"]["heartbeat_interval"]
+                .as_u64()
+                .map(Duration::from_millis)
+                .ok_or(StandaloneError::UnexpectedPayload)?,
+            _ => return Err(StandaloneError::UnexpectedPayload),
+        };
+
+        if self.can_resume() {
+            debug!("Resuming standalone gateway session.");
+            send(
+                &mut ws,
+                &json!({
+                    "op": opcode::RESUME,
+                    "d": {
+                        "token": self.token,
+                        "session_id": self.session_id,
+                        "seq": self.seq,
+                    }
+                }),
+            )
+            .await?;
+
+            // Replayed events and `RESUMED` are handled by the main loop.
+            return Ok((ws, heartbeat));
+        }
+
+        debug!("Identifying new standalone gateway session.");
+        send(
+            &mut ws,
+            &json!({
+                "op": opcode::IDENTIFY,
+                "d": {
+                    "token": self.token,
+                    "intents": INTENT_GUILD_VOICE_STATES,
+                    "properties": {
+                        "os": std::env::consts::OS,
+                        "browser": "songbird",
+                        "device": "songbird",
+                    },
+                }
+            }),
+        )
+        .await?;
+
+        timeout(HANDSHAKE_TIMEOUT, self.await_ready(&mut ws))
+            .await
+            .map_err(|_| StandaloneError::TimedOut)??;
+
+        Ok((ws, heartbeat))
+    }
+
+    async fn await_ready(&mut self, ws: &mut WsStream) -> Result<(), StandaloneError> {
+        loop {
+            let Some(value) = recv(ws).await? else {
+                continue;
+            };
+
+            self.track_seq(&value);
+
+            match op(&value) {
+                Some(opcode::DISPATCH) if value["t"] == "READY" => {
+                    let d = &value["d"];
+                    self.user_id = parse_id(&d["user"]["id"]).map(UserId);
+                    self.session_id = d["session_id"].as_str().map(str::to_owned);
+                    self.resume_url = d["resume_gateway_url"].as_str().map(str::to_owned);
+
+                    return if self.user_id.is_some() && self.session_id.is_some() {
+                        Ok(())
+                    } else {
+                        Err(StandaloneError::UnexpectedPayload)
+                    };
+                },
+                Some(opcode::INVALID_SESSION) => return Err(StandaloneError::InvalidSession),
+                _ => {},
+            }
+        }
+    }
+
+    fn track_seq(&mut self, value: &Value) {
+        if let Some(seq) = value["s"].as_u64() {
+            self.seq = Some(seq);
+        }
+    }
+}
+
+/// Why a live gateway connection ended.
+enum Exit {
+    /// All gateway handles were dropped.
+    Shutdown,
+    /// The connection should be re-established.
+    Reconnect,
+    /// The connection was closed in a way which cannot be recovered from.
+    Fatal,
+}
+
+async fn runner(
+    mut session: Session,
+    mut ws: WsStream,
+    mut heartbeat: Duration,
+    commands: Receiver<String>,
+    manager: Arc<OnceCell<Weak<Songbird>>>,
+) {
+    let user_id = session
+        .user_id
+        .expect("A freshly identified session must know its user ID.");
+
+    loop {
+        match run_connection(
+            &mut session,
+            &mut ws,
+            heartbeat,
+            &commands,
+            &manager,
+            user_id,
+        )
+        .await
+        {
+            Exit::Shutdown => {
+                _ = ws.close(None).await;
+                break;
+            },
+            Exit::Fatal => break,
+            Exit::Reconnect => {
+                // Close with a non-1000 code, so that the session remains resumable.
+                _ = ws
+                    .close(Some(CloseFrame {
+                        code: CloseCode::Library(4000),
+                        reason: "reconnecting".into(),
+                    }))
+                    .await;
+            },
+        }
+
+        loop {
+            if commands.is_disconnected() {
+                debug!("Standalone gateway dropped during reconnect.");
+                return;
+            }
+
+            match session.open().await {
+                Ok((new_ws, new_heartbeat)) => {
+                    info!("Standalone gateway reconnected.");
+                    ws = new_ws;
+                    heartbeat = new_heartbeat;
+                    break;
+                },
+                Err(e) if e.is_fatal() => {
+                    error!("Standalone gateway cannot reconnect: {}", e);
+                    return;
+                },
+                Err(e) => {
+                    warn!("Standalone gateway failed to reconnect: {}", e);
+                    if matches!(e, StandaloneError::InvalidSession) {
+                        session.invalidate();
+                    }
+                    tokio::time::sleep(RECONNECT_DELAY).await;
+                },
+            }
+        }
+    }
+
+    debug!("Standalone gateway exited.");
+}
+
+async fn run_connection(
+    session: &mut Session,
+    ws: &mut WsStream,
+    heartbeat: Duration,
+    commands: &Receiver<String>,
+    manager: &OnceCell<Weak<Songbird>>,
+    user_id: UserId,
+) -> Exit {
+    let mut ticker = interval_at(Instant::now() + heartbeat, heartbeat);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut acked = true;
+
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if !acked {
+                    warn!("Standalone gateway missed a heartbeat ACK, reconnecting.");
+                    return Exit::Reconnect;
+                }
+
+                acked = false;
+                if send_heartbeat(ws, session.seq).await.is_err() {
+                    return Exit::Reconnect;
+                }
+            },
+            cmd = commands.recv_async() => {
+                let Ok(cmd) = cmd else {
+                    return Exit::Shutdown;
+                };
+
+                if let Err(e) = ws.send(Message::Text(cmd)).await {
+                    warn!("Standalone gateway failed to send command: {}", e);
+                    return Exit::Reconnect;
+                }
+            },
+            msg = recv(ws) => {
+                let value = match msg {
+                    Ok(Some(value)) => value,
+                    Ok(None) => continue,
+                    Err(e) if e.is_fatal() => {
+                        error!("Standalone gateway closed by Discord: {}", e);
+                        return Exit::Fatal;
+                    },
+                    Err(e) => {
+                        debug!("Standalone gateway connection lost: {}", e);
+                        return Exit::Reconnect;
+                    },
+                };
+
+                session.track_seq(&value);
+
+                match op(&value) {
+                    Some(opcode::DISPATCH) => {
+                        if let Some(kind) = value["t"].as_str() {
+                            dispatch(manager, user_id, kind, &value["d"]).await;
+                        }
+                    },
+                    Some(opcode::HEARTBEAT) => {
+                        if send_heartbeat(ws, session.seq).await.is_err() {
+                            return Exit::Reconnect;
+                        }
+                    },
+                    Some(opcode::HEARTBEAT_ACK) => acked = true,
+                    Some(opcode::RECONNECT) => {
+                        debug!("Standalone gateway asked to reconnect.");
+                        return Exit::Reconnect;
+                    },
+                    Some(opcode::INVALID_SESSION) => {
+                        if value["d"] != true {
+                            session.invalidate();
+                        }
+                        return Exit::Reconnect;
+                    },
+                    _ => {},
+                }
+            },
+        }
+    }
+}
+
+/// Forwards voice state and voice server updates to the attached manager's [`Call`]s.
+///
+/// [`Call`]: crate::Call
+async fn dispatch(manager: &OnceCell<Weak<Songbird>>, own_id: UserId, kind: &str, d: &Value) {
+    let Some(manager) = manager.get().and_then(Weak::upgrade) else {
+        return;
+    };
+
+    let Some(call) = parse_id(&d["guild_id"]).and_then(|id| manager.get(GuildId(id))) else {
+        return;
+    };
+
+    match kind {
+        "VOICE_SERVER_UPDATE" => {
+            if let (Some(endpoint), Some(token)) = (d["endpoint"].as_str(), d["token"].as_str()) {
+                let mut handler = call.lock().await;
+                handler.update_server(endpoint.to_owned(), token.to_owned());
+            }
+        },
+        "VOICE_STATE_UPDATE" => {
+            let Some(user_id) = parse_id(&d["user_id"]).map(UserId) else {
+                return;
+            };
+            let channel_id = parse_id(&d["channel_id"]).map(ChannelId);
+
+            let mut handler = call.lock().await;
+            if user_id == own_id {
+                if let Some(session_id) = d["session_id"].as_str() {
+                    handler.update_state(session_id.to_owned(), channel_id);
+                }
+            } else {
+                #[cfg(feature = "driver")]
+                handler.update_member_state(user_id, channel_id);
+            }
+        },
+        _ => {},
+    }
+}
+
+fn op(value: &Value) -> Option<u8> {
+    value["op"].as_u64().and_then(|op| u8::try_from(op).ok())
+}
+
+/// Discord sends all snowflakes as strings.
+fn parse_id(value: &Value) -> Option<NonZeroU64> {
+    value.as_str().and_then(|s| s.parse().ok())
+}
+
+async fn recv(ws: &mut WsStream) -> Result<Option<Value>, StandaloneError> {
+    match ws.next().await {
+        Some(Ok(Message::Text(text))) => Ok(Some(serde_json::from_str(&text)?)),
+        Some(Ok(Message::Close(frame))) => Err(StandaloneError::Closed(frame)),
+        // Binary payloads are only sent when compression is requested.
+        Some(Ok(_)) => Ok(None),
+        Some(Err(e)) => Err(e.into()),
+        None => Err(StandaloneError::Closed(None)),
+    }
+}
+
+async fn send(ws: &mut WsStream, value: &Value) -> Result<(), StandaloneError> {
+    Ok(ws.send(Message::Text(value.to_string())).await?)
+}
+
+async fn send_heartbeat(ws: &mut WsStream, seq: Option<u64>) -> Result<(), StandaloneError> {
+    send(ws, &json!({ "op": opcode::HEARTBEAT, "d": seq })).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fatal_close_codes_are_detected() {
+        let closed = |code: u16| {
+            StandaloneError::Closed(Some(CloseFrame {
+                code: CloseCode::from(code),
+                reason: "".into(),
+            }))
+        };
+
+        assert!(closed(4004).is_fatal());
+        assert!(closed(4014).is_fatal());
+        assert!(!closed(4000).is_fatal());
+        assert!(!StandaloneError::TimedOut.is_fatal());
+    }
+
+    #[test]
+    fn snowflakes_parse_from_strings() {
+        let d = json!({ "guild_id": "41771983423143937", "channel_id": null });
+
+        assert_eq!(
+            parse_id(&d["guild_id"]).map(NonZeroU64::get),
+            Some(41_771_983_423_143_937)
+        );
+        assert_eq!(parse_id(&d["channel_id"]), None);
+    }
+}
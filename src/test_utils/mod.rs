@@ -1,5 +1,11 @@
 #![allow(missing_docs)]
 
+#[cfg(all(feature = "driver", any(test, feature = "mock-server")))]
+mod voice_server;
+
+#[cfg(all(feature = "driver", any(test, feature = "mock-server")))]
+pub use voice_server::*;
+
 use byteorder::{LittleEndian, WriteBytesExt};
 use std::mem;
 
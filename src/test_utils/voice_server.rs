@@ -0,0 +1,665 @@
+//! A local mock of Discord's voice servers, for end-to-end connection tests.
+//!
+//! [`MockVoiceServer`] runs a plaintext voice websocket implementing the
+//! Identify/Resume handshake and encryption negotiation, alongside a UDP socket
+//! which answers IP discovery and decrypts every received RTP packet with the
+//! negotiated session key. This lets downstream projects run a real [`Driver`]
+//! against a loopback server in CI, without any access to Discord.
+//!
+//! [`Driver`]: crate::Driver
+use crate::{
+    driver::{Cipher, CryptoMode},
+    id::{GuildId, UserId},
+    model::{
+        payload::{
+            HeartbeatAck,
+            Hello,
+            Identify,
+            Ready,
+            Resume,
+            SelectProtocol,
+            SessionDescription,
+            Speaking,
+        },
+        Event as GatewayEvent,
+    },
+    ws::{convert_ws_message, WsEvent},
+    ConnectionInfo,
+};
+use discortp::{
+    discord::{IpDiscoveryPacket, IpDiscoveryType, KeepalivePacket, MutableIpDiscoveryPacket},
+    rtp::{MutableRtpPacket, RtpPacket},
+    Packet,
+};
+use flume::{Receiver, Sender};
+use futures::{SinkExt, StreamExt};
+use parking_lot::Mutex;
+use rand::Rng;
+use std::{
+    io::Result as IoResult,
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    num::NonZeroU64,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+use tokio::{
+    net::{TcpListener, TcpStream, UdpSocket},
+    sync::broadcast,
+    task::JoinHandle,
+};
+use tokio_tungstenite::{
+    tungstenite::{
+        protocol::{frame::coding::CloseCode, CloseFrame},
+        Message,
+    },
+    WebSocketStream,
+};
+use tracing::debug;
+
+/// Configuration for a [`MockVoiceServer`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct MockServerConfig {
+    /// SSRC assigned to every connecting client.
+    ///
+    /// Defaults to `1`.
+    pub ssrc: u32,
+    /// Encryption modes offered to clients in the `Ready` payload.
+    ///
+    /// Defaults to [`CryptoMode::Aes256Gcm`] and [`CryptoMode::XChaCha20Poly1305`].
+    pub modes: Vec<CryptoMode>,
+    /// Heartbeat interval sent to clients, in milliseconds.
+    ///
+    /// Defaults to `41250.0`, matching Discord.
+    pub heartbeat_interval: f64,
+    /// Whether to send each correctly encrypted RTP packet back to its sender.
+    ///
+    /// Defaults to `false`.
+    pub echo: bool,
+}
+
+impl Default for MockServerConfig {
+    fn default() -> Self {
+        Self {
+            ssrc: 1,
+            modes: vec![CryptoMode::Aes256Gcm, CryptoMode::XChaCha20Poly1305],
+            heartbeat_interval: 41250.0,
+            echo: false,
+        }
+    }
+}
+
+impl MockServerConfig {
+    /// Sets this server's assigned SSRC.
+    #[must_use]
+    pub fn ssrc(mut self, ssrc: u32) -> Self {
+        self.ssrc = ssrc;
+        self
+    }
+
+    /// Sets the encryption modes offered by this server.
+    #[must_use]
+    pub fn modes(mut self, modes: Vec<CryptoMode>) -> Self {
+        self.modes = modes;
+        self
+    }
+
+    /// Sets the heartbeat interval sent to clients, in milliseconds.
+    #[must_use]
+    pub fn heartbeat_interval(mut self, heartbeat_interval: f64) -> Self {
+        self.heartbeat_interval = heartbeat_interval;
+        self
+    }
+
+    /// Sets whether valid RTP packets are echoed back to their sender.
+    #[must_use]
+    pub fn echo(mut self, echo: bool) -> Self {
+        self.echo = echo;
+        self
+    }
+}
+
+/// An RTP packet received and successfully decrypted by a [`MockVoiceServer`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub struct MockVoicePacket {
+    /// SSRC of the sender.
+    pub ssrc: u32,
+    /// RTP sequence number.
+    pub sequence: u16,
+    /// RTP timestamp.
+    pub timestamp: u32,
+    /// Nonce counter of the packet, for encryption modes which increment one
+    /// with each packet (e.g., [`CryptoMode::Aes256Gcm`]).
+    pub nonce: Option<u32>,
+    /// Decrypted packet body, excluding any nonce and authentication tag.
+    pub payload: Vec<u8>,
+}
+
+/// Activity observed by a [`MockVoiceServer`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum MockEvent {
+    /// A client opened a new session with this `Identify` payload.
+    Identified(Identify),
+    /// A client resumed a session with this `Resume` payload.
+    Resumed(Resume),
+    /// A client chose an encryption mode, and has been sent its session key.
+    ProtocolSelected(CryptoMode),
+    /// A client sent a speaking state update.
+    Speaking(Speaking),
+    /// A client sent a heartbeat with the given nonce.
+    Heartbeat(u64),
+    /// A client sent a UDP keepalive for the given SSRC.
+    Keepalive(u32),
+    /// A client sent a correctly encrypted RTP packet.
+    Packet(MockVoicePacket),
+    /// A client sent a UDP packet which could not be parsed or decrypted.
+    InvalidPacket(Vec<u8>),
+    /// A client's websocket connection closed.
+    Disconnected,
+}
+
+#[derive(Default)]
+struct Shared {
+    cipher: Mutex<Option<Cipher>>,
+    valid_packets: AtomicU64,
+    invalid_packets: AtomicU64,
+}
+
+/// A local mock of a Discord voice server.
+///
+/// Point a [`Driver`] at [`Self::connection_info`] to run through the full voice
+/// handshake: websocket `Identify`, IP discovery, encryption negotiation, and
+/// encrypted RTP transmission. All activity is reported over [`Self::events`].
+///
+/// The server runs until dropped.
+///
+/// [`Driver`]: crate::Driver
+pub struct MockVoiceServer {
+    ws_addr: SocketAddr,
+    udp_addr: SocketAddr,
+    events: Receiver<MockEvent>,
+    shared: Arc<Shared>,
+    close_tx: broadcast::Sender<u16>,
+    tasks: [JoinHandle<()>; 2],
+}
+
+impl MockVoiceServer {
+    /// Starts a mock voice server on the loopback interface with default settings.
+    pub async fn start() -> IoResult<Self> {
+        Self::with_config(MockServerConfig::default()).await
+    }
+
+    /// Starts a mock voice server on the loopback interface.
+    pub async fn with_config(config: MockServerConfig) -> IoResult<Self> {
+        let localhost = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+        let listener = TcpListener::bind((localhost, 0)).await?;
+        let udp = UdpSocket::bind((localhost, 0)).await?;
+        let ws_addr = listener.local_addr()?;
+        let udp_addr = udp.local_addr()?;
+
+        let (tx, events) = flume::unbounded();
+        let (close_tx, _) = broadcast::channel(4);
+        let shared = Arc::new(Shared::default());
+
+        let ws_task = tokio::spawn(ws_listener(
+            listener,
+            config.clone(),
+            udp_addr,
+            shared.clone(),
+            tx.clone(),
+            close_tx.clone(),
+        ));
+        let udp_task = tokio::spawn(udp_runner(udp, config, shared.clone(), tx));
+
+        Ok(Self {
+            ws_addr,
+            udp_addr,
+            events,
+            shared,
+            close_tx,
+            tasks: [ws_task, udp_task],
+        })
+    }
+
+    /// The voice websocket endpoint of this server, suitable for use in
+    /// [`ConnectionInfo::endpoint`].
+    #[must_use]
+    pub fn endpoint(&self) -> String {
+        format!("ws://{}", self.ws_addr)
+    }
+
+    /// The address of this server's UDP socket.
+    #[must_use]
+    pub fn udp_addr(&self) -> SocketAddr {
+        self.udp_addr
+    }
+
+    /// Builds connection details for a [`Driver`] targeting this server.
+    ///
+    /// [`Driver`]: crate::Driver
+    #[must_use]
+    pub fn connection_info(&self, guild_id: NonZeroU64, user_id: NonZeroU64) -> ConnectionInfo {
+        ConnectionInfo {
+            channel_id: None,
+            endpoint: self.endpoint(),
+            guild_id: GuildId(guild_id),
+            session_id: "mock-session".into(),
+            token: "mock-token".into(),
+            user_id: UserId(user_id),
+        }
+    }
+
+    /// A stream of all activity seen by this server.
+    #[must_use]
+    pub fn events(&self) -> &Receiver<MockEvent> {
+        &self.events
+    }
+
+    /// Number of RTP packets which were successfully decrypted.
+    #[must_use]
+    pub fn valid_packets(&self) -> u64 {
+        self.shared.valid_packets.load(Ordering::Relaxed)
+    }
+
+    /// Number of UDP packets which could not be parsed or decrypted.
+    #[must_use]
+    pub fn invalid_packets(&self) -> u64 {
+        self.shared.invalid_packets.load(Ordering::Relaxed)
+    }
+
+    /// Closes all open websocket connections with the given close code.
+    ///
+    /// Codes such as `4015` (voice server crash) should prompt clients to
+    /// resume their session, while others (e.g., `4014`) are final.
+    pub fn close_connections(&self, code: u16) {
+        drop(self.close_tx.send(code));
+    }
+}
+
+impl Drop for MockVoiceServer {
+    fn drop(&mut self) {
+        for task in &self.tasks {
+            task.abort();
+        }
+    }
+}
+
+async fn ws_listener(
+    listener: TcpListener,
+    config: MockServerConfig,
+    udp_addr: SocketAddr,
+    shared: Arc<Shared>,
+    tx: Sender<MockEvent>,
+    close_tx: broadcast::Sender<u16>,
+) {
+    while let Ok((stream, _)) = listener.accept().await {
+        let config = config.clone();
+        let shared = shared.clone();
+        let tx = tx.clone();
+        let close_rx = close_tx.subscribe();
+
+        tokio::spawn(async move {
+            match tokio_tungstenite::accept_async(stream).await {
+                Ok(ws) => ws_session(ws, &config, udp_addr, &shared, &tx, close_rx).await,
+                Err(e) => debug!("Mock voice server failed WS handshake: {e:?}"),
+            }
+
+            drop(tx.send(MockEvent::Disconnected));
+        });
+    }
+}
+
+async fn ws_session(
+    mut ws: WebSocketStream<TcpStream>,
+    config: &MockServerConfig,
+    udp_addr: SocketAddr,
+    shared: &Shared,
+    tx: &Sender<MockEvent>,
+    mut close_rx: broadcast::Receiver<u16>,
+) {
+    loop {
+        let msg = tokio::select! {
+            msg = ws.next() => msg,
+            Ok(code) = close_rx.recv() => {
+                drop(ws.close(Some(CloseFrame {
+                    code: CloseCode::from(code),
+                    reason: "".into(),
+                })).await);
+                return;
+            },
+        };
+
+        let evt = match msg {
+            Some(Ok(m)) => match convert_ws_message(Some(m)) {
+                Ok(Some(WsEvent::Gateway(evt))) => evt,
+                Ok(_) => continue,
+                Err(_) => return,
+            },
+            Some(Err(_)) | None => return,
+        };
+
+        let reply: Vec<GatewayEvent> = match evt {
+            GatewayEvent::Identify(identify) => {
+                drop(tx.send(MockEvent::Identified(identify)));
+
+                let modes = config
+                    .modes
+                    .iter()
+                    .map(|m| m.to_request_str().into())
+                    .collect();
+
+                vec![
+                    Hello {
+                        heartbeat_interval: config.heartbeat_interval,
+                    }
+                    .into(),
+                    Ready {
+                        ip: udp_addr.ip(),
+                        modes,
+                        port: udp_addr.port(),
+                        ssrc: config.ssrc,
+                    }
+                    .into(),
+                ]
+            },
+            GatewayEvent::Resume(resume) => {
+                drop(tx.send(MockEvent::Resumed(resume)));
+
+                vec![
+                    Hello {
+                        heartbeat_interval: config.heartbeat_interval,
+                    }
+                    .into(),
+                    GatewayEvent::Resumed,
+                ]
+            },
+            GatewayEvent::SelectProtocol(SelectProtocol { data, .. }) => {
+                let Ok(mode) = data.mode.parse::<CryptoMode>() else {
+                    return;
+                };
+
+                let mut secret_key = vec![0u8; 32];
+                rand::thread_rng().fill(&mut secret_key[..]);
+
+                let Ok(cipher) = mode.cipher_from_key(&secret_key) else {
+                    return;
+                };
+                *shared.cipher.lock() = Some(cipher);
+
+                drop(tx.send(MockEvent::ProtocolSelected(mode)));
+
+                vec![SessionDescription {
+                    mode: data.mode,
+                    secret_key,
+                }
+                .into()]
+            },
+            GatewayEvent::Heartbeat(hb) => {
+                drop(tx.send(MockEvent::Heartbeat(hb.nonce)));
+
+                vec![HeartbeatAck { nonce: hb.nonce }.into()]
+            },
+            GatewayEvent::Speaking(speaking) => {
+                drop(tx.send(MockEvent::Speaking(speaking)));
+                vec![]
+            },
+            _ => vec![],
+        };
+
+        for evt in reply {
+            let Ok(text) = crate::json::to_string(&evt) else {
+                continue;
+            };
+
+            if ws.send(Message::Text(text)).await.is_err() {
+                return;
+            }
+        }
+    }
+}
+
+async fn udp_runner(
+    udp: UdpSocket,
+    config: MockServerConfig,
+    shared: Arc<Shared>,
+    tx: Sender<MockEvent>,
+) {
+    let mut buf = [0u8; 1500];
+
+    while let Ok((len, addr)) = udp.recv_from(&mut buf).await {
+        let pkt = &mut buf[..len];
+
+        if let Some(disc) = IpDiscoveryPacket::new(pkt) {
+            if len == IpDiscoveryPacket::const_packet_size()
+                && disc.get_pkt_type() == IpDiscoveryType::Request
+            {
+                let resp = ip_discovery_response(disc.get_ssrc(), addr);
+                drop(udp.send_to(&resp, addr).await);
+                continue;
+            }
+        }
+
+        if len == KeepalivePacket::minimum_packet_size() {
+            if let Some(ka) = KeepalivePacket::new(pkt) {
+                drop(tx.send(MockEvent::Keepalive(ka.get_ssrc())));
+                drop(udp.send_to(pkt, addr).await);
+                continue;
+            }
+        }
+
+        // Decryption happens in place, so keep the original ciphertext around to echo.
+        let original = config.echo.then(|| pkt.to_vec());
+        let cipher = shared.cipher.lock().clone();
+
+        match cipher.and_then(|c| decrypt_voice(&c, pkt)) {
+            Some(voice) => {
+                shared.valid_packets.fetch_add(1, Ordering::Relaxed);
+                drop(tx.send(MockEvent::Packet(voice)));
+
+                if let Some(original) = original {
+                    drop(udp.send_to(&original, addr).await);
+                }
+            },
+            None => {
+                shared.invalid_packets.fetch_add(1, Ordering::Relaxed);
+                drop(tx.send(MockEvent::InvalidPacket(pkt.to_vec())));
+            },
+        }
+    }
+}
+
+fn ip_discovery_response(
+    ssrc: u32,
+    addr: SocketAddr,
+) -> [u8; IpDiscoveryPacket::const_packet_size()] {
+    let mut bytes = [0u8; IpDiscoveryPacket::const_packet_size()];
+    let mut view = MutableIpDiscoveryPacket::new(&mut bytes[..])
+        .expect("IP discovery buffer is sized to fit its packet.");
+
+    let mut address = [0u8; 64];
+    let ip = addr.ip().to_string();
+    address[..ip.len()].copy_from_slice(ip.as_bytes());
+
+    view.set_pkt_type(IpDiscoveryType::Response);
+    view.set_length(70);
+    view.set_ssrc(ssrc);
+    view.set_address(&address);
+    view.set_port(addr.port());
+
+    bytes
+}
+
+fn decrypt_voice(cipher: &Cipher, pkt: &mut [u8]) -> Option<MockVoicePacket> {
+    let mode = cipher.mode();
+
+    // Counter nonces are written big-endian at the very end of each packet.
+    let nonce = match mode.nonce_size() {
+        4 => pkt
+            .get(pkt.len().checked_sub(4)?..)
+            .and_then(|b| b.try_into().ok())
+            .map(u32::from_be_bytes),
+        _ => None,
+    };
+
+    let mut rtp = MutableRtpPacket::new(pkt)?;
+
+    if rtp.get_version() != 2 {
+        return None;
+    }
+
+    cipher.decrypt_rtp_in_place(&mut rtp).ok()?;
+
+    let rtp = RtpPacket::new(rtp.packet())?;
+    let payload = rtp.payload();
+    let end = payload.len().checked_sub(mode.payload_suffix_len())?;
+    let body = payload.get(mode.payload_prefix_len2()..end)?;
+
+    Some(MockVoicePacket {
+        ssrc: rtp.get_ssrc(),
+        sequence: rtp.get_sequence().into(),
+        timestamp: rtp.get_timestamp().into(),
+        nonce,
+        payload: body.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::test_data::FILE_WAV_TARGET, input::File, Config, Driver};
+
+    async fn next_packet(server: &MockVoiceServer) -> MockVoicePacket {
+        loop {
+            match server.events().recv_async().await.unwrap() {
+                MockEvent::Packet(pkt) => return pkt,
+                MockEvent::InvalidPacket(pkt) => panic!("Undecryptable packet: {pkt:?}"),
+                _ => {},
+            }
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn driver_connects_and_sends_encrypted_audio() {
+        let server = MockVoiceServer::with_config(MockServerConfig::default().ssrc(1234))
+            .await
+            .unwrap();
+        let id = NonZeroU64::new(1).unwrap();
+
+        let mut driver = Driver::new(Config::default());
+        driver
+            .connect(server.connection_info(id, id))
+            .await
+            .unwrap();
+        let _ = driver.play_input(File::new(FILE_WAV_TARGET).into());
+
+        let first = next_packet(&server).await;
+        let second = next_packet(&server).await;
+
+        assert_eq!(first.ssrc, 1234);
+        assert_eq!(second.sequence, first.sequence.wrapping_add(1));
+        assert_eq!(server.invalid_packets(), 0);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn driver_negotiates_offered_mode() {
+        let server = MockVoiceServer::with_config(
+            MockServerConfig::default().modes(vec![CryptoMode::XChaCha20Poly1305]),
+        )
+        .await
+        .unwrap();
+        let id = NonZeroU64::new(1).unwrap();
+
+        let mut driver = Driver::new(Config::default());
+        driver
+            .connect(server.connection_info(id, id))
+            .await
+            .unwrap();
+
+        let mode = server.events().try_iter().find_map(|evt| match evt {
+            MockEvent::ProtocolSelected(mode) => Some(mode),
+            _ => None,
+        });
+
+        assert_eq!(mode, Some(CryptoMode::XChaCha20Poly1305));
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn driver_crypto_preference_overrides_config() {
+        let server = MockVoiceServer::start().await.unwrap();
+        let id = NonZeroU64::new(1).unwrap();
+
+        let mut driver = Driver::new(Config::default());
+        driver.set_crypto_preference(Some(vec![CryptoMode::XChaCha20Poly1305]));
+        driver
+            .connect(server.connection_info(id, id))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            driver.crypto_mode().await,
+            Some(CryptoMode::XChaCha20Poly1305)
+        );
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn resumed_session_continues_nonce_and_rtp_counters() {
+        let server = MockVoiceServer::with_config(
+            MockServerConfig::default()
+                .ssrc(1234)
+                .modes(vec![CryptoMode::Aes256Gcm]),
+        )
+        .await
+        .unwrap();
+        let id = NonZeroU64::new(1).unwrap();
+
+        let mut old = Driver::new(Config::default());
+        old.connect(server.connection_info(id, id)).await.unwrap();
+        let _ = old.play_input(File::new(FILE_WAV_TARGET).into());
+        let before = next_packet(&server).await;
+
+        let session = old.export_session().await.unwrap();
+        assert_eq!(session.ssrc, 1234);
+        assert!(session.nonce.is_some());
+
+        let mut new = Driver::new(Config::default());
+        new.resume_session(session.clone()).await.unwrap();
+        let _ = new.play_input(File::new(FILE_WAV_TARGET).into());
+
+        // Packets sent by the old driver before the export all precede the
+        // exported counters.
+        let mut last = before;
+        let resumed = loop {
+            let pkt = next_packet(&server).await;
+            if pkt.sequence == session.sequence {
+                break pkt;
+            }
+
+            assert_eq!(pkt.sequence, last.sequence.wrapping_add(1));
+            assert_eq!(pkt.nonce, last.nonce.map(|n| n.wrapping_add(1)));
+            last = pkt;
+        };
+
+        assert_eq!(resumed.ssrc, 1234);
+        assert_eq!(resumed.nonce, session.nonce);
+        assert_eq!(
+            resumed.nonce,
+            last.nonce.map(|n| n.wrapping_add(1)),
+            "resumed driver must not reuse any nonce sent before the export"
+        );
+        assert!(resumed.timestamp.wrapping_sub(session.timestamp) < u32::MAX / 2);
+
+        let next = next_packet(&server).await;
+        assert_eq!(next.sequence, session.sequence.wrapping_add(1));
+        assert_eq!(next.nonce, session.nonce.map(|n| n.wrapping_add(1)));
+        assert_eq!(server.invalid_packets(), 0);
+    }
+}
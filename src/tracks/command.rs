@@ -1,7 +1,10 @@
 use super::*;
 use crate::events::EventData;
 use flume::Sender;
-use std::fmt::{Debug, Formatter, Result as FmtResult};
+use std::{
+    fmt::{Debug, Formatter, Result as FmtResult},
+    time::Instant,
+};
 
 /// A request from external code using a [`TrackHandle`] to modify
 /// or act upon an [`Track`] object.
@@ -12,12 +15,23 @@ use std::fmt::{Debug, Formatter, Result as FmtResult};
 pub enum TrackCommand {
     /// Set the track's play_mode to play/resume.
     Play,
+    /// Set the track's play_mode to play/resume on the frame sent closest to
+    /// the given instant.
+    PlayAt(Instant),
     /// Set the track's play_mode to pause.
     Pause,
     /// Stop the target track. This cannot be undone.
     Stop,
     /// Set the track's volume.
     Volume(f32),
+    /// Smoothly change the track's volume over the given duration.
+    VolumeRamp(f32, Duration),
+    /// Set the track's stereo pan position.
+    Pan(f32),
+    /// Mute or unmute the track, without pausing it.
+    Mute(bool),
+    /// Set the track's priority under mixer overload.
+    Priority(i8),
     /// Seek to the given duration.
     ///
     /// On unsupported input types, this can be fatal.
@@ -28,6 +42,8 @@ pub enum TrackCommand {
     Do(Box<dyn FnOnce(View<'_>) -> Option<Action> + Send + Sync + 'static>),
     /// Request a copy of this track's state.
     Request(Sender<TrackState>),
+    /// Request diagnostics on how this track is being mixed.
+    RequestMixInfo(Sender<Option<MixInfo>>),
     /// Change the loop count/strategy of this track.
     Loop(LoopState),
     /// Prompts a track's input to become live and usable, if it is not already.
@@ -41,13 +57,19 @@ impl Debug for TrackCommand {
             "TrackCommand::{}",
             match self {
                 Self::Play => "Play".to_string(),
+                Self::PlayAt(t) => format!("PlayAt({t:?})"),
                 Self::Pause => "Pause".to_string(),
                 Self::Stop => "Stop".to_string(),
                 Self::Volume(vol) => format!("Volume({vol})"),
+                Self::VolumeRamp(vol, ramp) => format!("VolumeRamp({vol}, {ramp:?})"),
+                Self::Pan(pan) => format!("Pan({pan})"),
+                Self::Mute(muted) => format!("Mute({muted})"),
+                Self::Priority(priority) => format!("Priority({priority})"),
                 Self::Seek(s) => format!("Seek({:?})", s.time),
                 Self::AddEvent(evt) => format!("AddEvent({evt:?})"),
                 Self::Do(_f) => "Do([function])".to_string(),
                 Self::Request(tx) => format!("Request({tx:?})"),
+                Self::RequestMixInfo(tx) => format!("RequestMixInfo({tx:?})"),
                 Self::Loop(loops) => format!("Loop({loops:?})"),
                 Self::MakePlayable(_) => "MakePlayable".to_string(),
             }
@@ -0,0 +1,63 @@
+use super::*;
+use crate::events::{Event, EventContext, EventHandler};
+use async_trait::async_trait;
+use flume::Sender;
+
+/// Why a track stopped playing, as reported by [`TrackHandle::wait_for_end`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum TrackEndReason {
+    /// The track reached the end of its input (and any loops).
+    Ended,
+    /// The track was stopped via [`TrackHandle::stop`] or its queue.
+    Stopped,
+    /// The track encountered a fatal error.
+    Errored(PlayError),
+    /// The track was removed from its driver without finishing, e.g. by a call to
+    /// [`Driver::play_only`] or [`Driver::stop`], or the driver shutting down.
+    ///
+    /// [`Driver::play_only`]: crate::driver::Driver::play_only
+    /// [`Driver::stop`]: crate::driver::Driver::stop
+    Replaced,
+}
+
+/// The final outcome of a track, returned by [`TrackHandle::wait_for_end`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct TrackEnd {
+    /// ID of the track which ended.
+    pub id: TrackId,
+    /// Why the track stopped.
+    pub reason: TrackEndReason,
+    /// The last known state of the track.
+    ///
+    /// This is `None` if the track was [replaced](TrackEndReason::Replaced).
+    pub state: Option<TrackState>,
+}
+
+/// Internal event hook used to complete [`TrackHandle::wait_for_end`].
+pub(crate) struct EndWaiter {
+    pub(crate) tx: Sender<TrackEnd>,
+}
+
+#[async_trait]
+impl EventHandler for EndWaiter {
+    async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+        if let EventContext::Track(&[(state, handle)]) = ctx {
+            let reason = match &state.playing {
+                PlayMode::End => TrackEndReason::Ended,
+                PlayMode::Stop => TrackEndReason::Stopped,
+                PlayMode::Errored(e) => TrackEndReason::Errored(e.clone()),
+                PlayMode::Play | PlayMode::Pause => return None,
+            };
+
+            drop(self.tx.try_send(TrackEnd {
+                id: handle.id(),
+                reason,
+                state: Some(state.clone()),
+            }));
+        }
+
+        None
+    }
+}
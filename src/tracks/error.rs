@@ -1,4 +1,5 @@
-use crate::input::AudioStreamError;
+use super::TrackHandle;
+use crate::input::{AudioStreamError, MakePlayableError};
 use flume::RecvError;
 use std::{
     error::Error,
@@ -77,6 +78,11 @@ pub enum PlayError {
     Decode(Arc<SymphoniaError>),
     /// Failed to seek to the requested location.
     Seek(Arc<SymphoniaError>),
+    /// The track was refused as the driver's [`BandwidthCap`] left too little bitrate
+    /// to play it.
+    ///
+    /// [`BandwidthCap`]: crate::driver::BandwidthCap
+    Bandwidth,
 }
 
 impl Display for PlayError {
@@ -103,8 +109,78 @@ impl Display for PlayError {
                 f.write_fmt(format_args!("{}", &s))?;
                 f.write_str("]")
             },
+            Self::Bandwidth => f.write_str("bandwidth cap too low to accept track"),
         }
     }
 }
 
 impl Error for PlayError {}
+
+/// Errors returned when adding a track to a [`TrackQueue`] via [`TrackQueue::try_add`]
+/// or [`TrackQueue::add_keyed`].
+///
+/// [`TrackQueue`]: super::TrackQueue
+/// [`TrackQueue::try_add`]: super::TrackQueue::try_add
+/// [`TrackQueue::add_keyed`]: super::TrackQueue::add_keyed
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EnqueueError {
+    /// The queue's [`EnqueueFilter`] refused this track.
+    ///
+    /// [`EnqueueFilter`]: super::EnqueueFilter
+    Rejected,
+    /// The track was identical to the last track in the queue, and refused under
+    /// [`DuplicatePolicy::Reject`].
+    ///
+    /// This contains a handle to the existing track.
+    ///
+    /// [`DuplicatePolicy::Reject`]: super::DuplicatePolicy::Reject
+    Duplicate(TrackHandle),
+    /// The track's input could not be created or parsed to sample its audio.
+    Input(MakePlayableError),
+    /// The track's audio could not be decoded.
+    Decode(SymphoniaError),
+    /// The track's input was consumed while being sampled, and can neither seek back
+    /// to its start nor be recreated.
+    Unrewindable,
+    /// The thread sampling this track's audio panicked.
+    Panicked,
+}
+
+impl Display for EnqueueError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.write_str("failed to enqueue track: ")?;
+        match self {
+            Self::Rejected => f.write_str("refused by queue filter"),
+            Self::Duplicate(_) => f.write_str("duplicate of last queued track"),
+            Self::Input(i) => {
+                f.write_str("readying input [")?;
+                f.write_fmt(format_args!("{}", &i))?;
+                f.write_str("]")
+            },
+            Self::Decode(d) => {
+                f.write_str("decoding packets [")?;
+                f.write_fmt(format_args!("{}", &d))?;
+                f.write_str("]")
+            },
+            Self::Unrewindable => f.write_str("input could not be rewound after sampling"),
+            Self::Panicked => f.write_str("sampling thread panicked"),
+        }
+    }
+}
+
+impl Error for EnqueueError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Input(i) => Some(i),
+            Self::Decode(d) => Some(d),
+            Self::Rejected | Self::Duplicate(_) | Self::Unrewindable | Self::Panicked => None,
+        }
+    }
+}
+
+impl From<MakePlayableError> for EnqueueError {
+    fn from(val: MakePlayableError) -> Self {
+        Self::Input(val)
+    }
+}
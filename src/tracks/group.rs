@@ -0,0 +1,120 @@
+use super::*;
+use std::mem;
+
+/// A set of [`TrackHandle`]s whose tracks are stopped together.
+///
+/// All tracks in a group are stopped when [`Self::stop`] is called or when the
+/// group is dropped, including while unwinding from a panic. This ties the
+/// lifetime of temporary audio (e.g., sound effects for a minigame) to the
+/// task or object which owns the group, preventing it from outliving its use.
+///
+/// Handles can be detached from a group without stopping their tracks using
+/// [`Self::release`].
+#[derive(Debug, Default)]
+pub struct TrackGroup {
+    handles: Vec<TrackHandle>,
+}
+
+impl TrackGroup {
+    /// Creates a new, empty track group.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a track to this group, returning a reference to its handle.
+    pub fn add(&mut self, handle: TrackHandle) -> &TrackHandle {
+        self.handles.push(handle);
+        self.handles.last().expect("Handle was pushed above.")
+    }
+
+    /// Returns the handles of all tracks in this group.
+    #[must_use]
+    pub fn handles(&self) -> &[TrackHandle] {
+        &self.handles
+    }
+
+    /// Returns the number of tracks in this group.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Returns whether this group contains no tracks.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+
+    /// Stops every track in this group, removing them from the group.
+    ///
+    /// Tracks which have already ended are ignored.
+    pub fn stop(&mut self) {
+        for handle in self.handles.drain(..) {
+            // Errors here only indicate that a track has already finished.
+            _ = handle.stop();
+        }
+    }
+
+    /// Detaches every track from this group without stopping them, returning
+    /// their handles.
+    #[must_use]
+    pub fn release(&mut self) -> Vec<TrackHandle> {
+        mem::take(&mut self.handles)
+    }
+}
+
+impl Extend<TrackHandle> for TrackGroup {
+    fn extend<T: IntoIterator<Item = TrackHandle>>(&mut self, iter: T) {
+        self.handles.extend(iter);
+    }
+}
+
+impl FromIterator<TrackHandle> for TrackGroup {
+    fn from_iter<T: IntoIterator<Item = TrackHandle>>(iter: T) -> Self {
+        Self {
+            handles: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Drop for TrackGroup {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{constants::test_data::FILE_WAV_TARGET, driver::Driver, input::File, Config};
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn dropping_group_stops_tracks() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+
+        let kept = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+        let group: TrackGroup = (0..2)
+            .map(|_| driver.play(Track::from(File::new(FILE_WAV_TARGET))))
+            .collect();
+        t_handle.spawn_ticker();
+
+        let waits: Vec<_> = group
+            .handles()
+            .iter()
+            .cloned()
+            .map(|h| tokio::spawn(async move { h.wait_for_end().await }))
+            .collect();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        drop(group);
+
+        for wait in waits {
+            let end = wait.await.unwrap().unwrap();
+            assert!(matches!(end.reason, TrackEndReason::Stopped));
+        }
+        assert!(kept.get_info().await.is_ok());
+    }
+}
@@ -1,7 +1,15 @@
 use super::*;
-use crate::events::{Event, EventData, EventHandler};
+use crate::events::{
+    typed::{TypedHandler, TypedTrackEvent},
+    Event,
+    EventContext,
+    EventData,
+    EventHandler,
+    OnceWhen,
+    TrackEvent,
+};
 use flume::{Receiver, Sender};
-use std::{fmt, sync::Arc};
+use std::{any::Any, fmt, sync::Arc, time::Instant};
 use tokio::sync::RwLock;
 use typemap_rev::TypeMap;
 
@@ -20,16 +28,20 @@ pub struct TrackHandle {
 
 struct InnerHandle {
     command_channel: Sender<TrackCommand>,
+    id: TrackId,
     uuid: Uuid,
     typemap: RwLock<TypeMap>,
+    user_data: Arc<dyn Any + Send + Sync + 'static>,
 }
 
 impl fmt::Debug for InnerHandle {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("InnerHandle")
             .field("command_channel", &self.command_channel)
+            .field("id", &self.id)
             .field("uuid", &self.uuid)
             .field("typemap", &"<LOCK>")
+            .field("user_data", &"<DATA>")
             .finish()
     }
 }
@@ -39,11 +51,18 @@ impl TrackHandle {
     ///
     /// [`Input`]: crate::input::Input
     #[must_use]
-    pub(crate) fn new(command_channel: Sender<TrackCommand>, uuid: Uuid) -> Self {
+    pub(crate) fn new(
+        command_channel: Sender<TrackCommand>,
+        id: TrackId,
+        uuid: Uuid,
+        user_data: Arc<dyn Any + Send + Sync + 'static>,
+    ) -> Self {
         let inner = Arc::new(InnerHandle {
             command_channel,
+            id,
             uuid,
             typemap: RwLock::new(TypeMap::new()),
+            user_data,
         });
 
         Self { inner }
@@ -54,6 +73,17 @@ impl TrackHandle {
         self.send(TrackCommand::Play)
     }
 
+    /// Plays or resumes an audio track on the frame sent closest to `instant`.
+    ///
+    /// Tracks on different drivers given the same instant will begin within
+    /// half a frame (10ms) of one another, regardless of each driver's tick phase.
+    /// The track should be made playable beforehand (e.g., via
+    /// [`Self::make_playable_async`]), and any later call to [`Self::play`] or
+    /// [`Self::pause`] cancels the pending start.
+    pub fn play_at(&self, instant: Instant) -> TrackResult<()> {
+        self.send(TrackCommand::PlayAt(instant))
+    }
+
     /// Pauses an audio track.
     pub fn pause(&self) -> TrackResult<()> {
         self.send(TrackCommand::Pause)
@@ -74,6 +104,49 @@ impl TrackHandle {
         self.send(TrackCommand::Volume(volume))
     }
 
+    /// Sets the volume of an audio track, moving gradually from its current volume
+    /// to the target over `ramp`.
+    ///
+    /// The mixer steps the volume once per 20ms frame while the track is playing,
+    /// avoiding the audible click of an instant change on sustained audio. A zero
+    /// `ramp` is equivalent to [`set_volume`], which also cancels any ramp in progress.
+    ///
+    /// [`set_volume`]: Self::set_volume
+    pub fn set_volume_ramped(&self, volume: f32, ramp: Duration) -> TrackResult<()> {
+        self.send(TrackCommand::VolumeRamp(volume, ramp))
+    }
+
+    /// Sets the stereo pan position of an audio track, from `-1.0` (fully left)
+    /// to `1.0` (fully right).
+    ///
+    /// Values outside this range are clamped. See [`Track::pan`] for how panning
+    /// is applied.
+    ///
+    /// [`Track::pan`]: super::Track::pan
+    pub fn set_pan(&self, pan: f32) -> TrackResult<()> {
+        self.send(TrackCommand::Pan(pan.clamp(-1.0, 1.0)))
+    }
+
+    /// Mutes or unmutes an audio track.
+    ///
+    /// A muted track continues to decode and advance its position in real time,
+    /// but contributes only silence to the mix. This keeps the track aligned with
+    /// real time, unlike [`pause`], which shifts its timeline.
+    ///
+    /// [`pause`]: Self::pause
+    pub fn set_mute(&self, muted: bool) -> TrackResult<()> {
+        self.send(TrackCommand::Mute(muted))
+    }
+
+    /// Sets this track's importance when the driver is overloaded.
+    ///
+    /// See [`Track::priority`].
+    ///
+    /// [`Track::priority`]: super::Track::priority
+    pub fn set_priority(&self, priority: i8) -> TrackResult<()> {
+        self.send(TrackCommand::Priority(priority))
+    }
+
     #[must_use]
     /// Ready a track for playing if it is lazily initialised.
     ///
@@ -137,6 +210,50 @@ impl TrackHandle {
         }
     }
 
+    /// Attach an event handler which is called only the first time `event` fires
+    /// while `predicate` holds for this track's state, and is then removed.
+    ///
+    /// For instance, pairing a short [`Event::Periodic`] with a check on
+    /// [`TrackState::position`] fires once the track passes a given point. The predicate
+    /// is evaluated in the event task. See [`OnceWhen`] for predicates over any
+    /// [`EventContext`].
+    ///
+    pub fn once_when<P, F>(&self, event: Event, predicate: P, action: F) -> TrackResult<()>
+    where
+        P: Fn(&TrackState) -> bool + Send + Sync + 'static,
+        F: EventHandler + 'static,
+    {
+        let predicate = move |ctx: &EventContext<'_>| match ctx {
+            EventContext::Track(tracks) => tracks.iter().any(|(state, _)| predicate(state)),
+            _ => false,
+        };
+
+        self.add_event(event, OnceWhen::new(predicate, action))
+    }
+
+    /// Attach a closure to one kind of track event, receiving that event's typed payload.
+    ///
+    /// The closure is called once for each time the event fires, in place of matching on
+    /// an [`EventContext`]. See [`events::typed`] for the available events.
+    ///
+    /// ```rust,no_run
+    /// # use songbird::{events::typed::PlaybackEnded, tracks::TrackHandle};
+    /// # fn example(handle: &TrackHandle) {
+    /// handle
+    ///     .on::<PlaybackEnded>(|ended| println!("ended in {:?}", ended.state.playing))
+    ///     .unwrap();
+    /// # }
+    /// ```
+    ///
+    /// [`EventContext`]: crate::events::EventContext
+    /// [`events::typed`]: crate::events::typed
+    pub fn on<E: TypedTrackEvent>(
+        &self,
+        action: impl for<'a> Fn(E::Data<'a>) + Send + Sync + 'static,
+    ) -> TrackResult<()> {
+        self.add_event(Event::Track(E::EVENT), TypedHandler::<E, _>::new(action))
+    }
+
     /// Perform an arbitrary synchronous action on a raw [`Track`] object.
     ///
     /// This will give access to a [`View`] of the current track state and [`Metadata`],
@@ -162,6 +279,38 @@ impl TrackHandle {
         rx.recv_async().await.map_err(|_| ControlError::Finished)
     }
 
+    /// Request diagnostics on how the mixer is processing this track's audio.
+    ///
+    /// This reports whether the track's latest frame was passed through as Opus,
+    /// decoded at its native rate, or decoded and resampled, and why passthrough
+    /// was not possible. Returns `None` if the track has not yet been mixed.
+    pub async fn mix_info(&self) -> TrackResult<Option<MixInfo>> {
+        let (tx, rx) = flume::bounded(1);
+        self.send(TrackCommand::RequestMixInfo(tx))?;
+
+        rx.recv_async().await.map_err(|_| ControlError::Finished)
+    }
+
+    /// Waits until this track has finished playing, returning its final state and
+    /// the reason it stopped.
+    ///
+    /// Returns [`ControlError::Finished`] if the track has already been removed
+    /// from the driver when called.
+    pub async fn wait_for_end(&self) -> TrackResult<TrackEnd> {
+        let (tx, rx) = flume::bounded(1);
+
+        self.add_event(Event::Track(TrackEvent::End), EndWaiter { tx: tx.clone() })?;
+        self.add_event(Event::Track(TrackEvent::Error), EndWaiter { tx })?;
+
+        // Both hooks are dropped alongside the track's event state, so a hangup
+        // without a result means that the track was removed before it could end.
+        Ok(rx.recv_async().await.unwrap_or(TrackEnd {
+            id: self.id(),
+            reason: TrackEndReason::Replaced,
+            state: None,
+        }))
+    }
+
     /// Set an audio track to loop indefinitely.
     ///
     /// This requires either a [`Compose`] to be present or for the
@@ -191,6 +340,12 @@ impl TrackHandle {
         self.send(TrackCommand::Loop(LoopState::Finite(count)))
     }
 
+    /// Returns this handle's (and track's) creation-ordered identifier.
+    #[must_use]
+    pub fn id(&self) -> TrackId {
+        self.inner.id
+    }
+
     /// Returns this handle's (and track's) unique identifier.
     #[must_use]
     pub fn uuid(&self) -> Uuid {
@@ -209,6 +364,21 @@ impl TrackHandle {
         &self.inner.typemap
     }
 
+    /// Returns the user data attached to this track when it was created,
+    /// if it is of type `Data`.
+    ///
+    /// See [`Track::user_data`] for how to attach this data.
+    #[must_use]
+    pub fn data<Data: Send + Sync + 'static>(&self) -> Option<Arc<Data>> {
+        Arc::clone(&self.inner.user_data).downcast().ok()
+    }
+
+    /// Returns the untyped user data attached to this track when it was created.
+    #[must_use]
+    pub fn user_data(&self) -> &Arc<dyn Any + Send + Sync + 'static> {
+        &self.inner.user_data
+    }
+
     #[inline]
     /// Send a raw command to the [`Track`] object.
     ///
@@ -279,6 +449,109 @@ mod tests {
         assert!(callback.result_async().await.is_ok());
     }
 
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn play_at_starts_on_target_instant() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+
+        let file = File::new(FILE_WAV_TARGET);
+        let handle = driver.play(Track::from(file).pause());
+        t_handle.spawn_ticker();
+        assert!(handle.make_playable_async().await.is_ok());
+
+        assert!(handle
+            .play_at(Instant::now() + Duration::from_millis(300))
+            .is_ok());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(handle.get_info().await.unwrap().playing, PlayMode::Pause);
+
+        tokio::time::sleep(Duration::from_millis(400)).await;
+        assert_eq!(handle.get_info().await.unwrap().playing, PlayMode::Play);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn wait_for_end_reports_stop_and_replace() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+
+        let stopped = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+        let replaced = driver.play(Track::from(File::new(FILE_WAV_TARGET)).pause());
+        t_handle.spawn_ticker();
+
+        let stop_wait = tokio::spawn({
+            let stopped = stopped.clone();
+            async move { stopped.wait_for_end().await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(stopped.stop().is_ok());
+        let end = stop_wait.await.unwrap().unwrap();
+        assert!(matches!(end.reason, TrackEndReason::Stopped));
+        assert_eq!(end.state.map(|s| s.playing), Some(PlayMode::Stop));
+
+        let replace_wait = tokio::spawn({
+            let replaced = replaced.clone();
+            async move { replaced.wait_for_end().await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        driver.stop();
+        let end = replace_wait.await.unwrap().unwrap();
+        assert!(matches!(end.reason, TrackEndReason::Replaced));
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn mix_info_reports_resampled_pcm() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+
+        let handle = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+        t_handle.spawn_ticker();
+        assert!(handle.make_playable_async().await.is_ok());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let info = handle.mix_info().await.unwrap().unwrap();
+        assert_eq!(info.path, MixPath::DecodeResample);
+        assert_eq!(info.reason, Some(DecodeReason::Codec));
+        assert_eq!(info.sample_rate, Some(44_100));
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn typed_subscriptions_receive_only_their_event() {
+        use crate::events::typed::{Paused, Playable, PlaybackEnded};
+
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+
+        let handle = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+
+        let (tx, rx) = flume::unbounded();
+        let playable_tx = tx.clone();
+        handle
+            .on::<Playable>(move |c| {
+                let _ = playable_tx.send(("playable", c.handle.uuid()));
+            })
+            .unwrap();
+        let paused_tx = tx.clone();
+        handle
+            .on::<Paused>(move |c| {
+                let _ = paused_tx.send(("paused", c.handle.uuid()));
+            })
+            .unwrap();
+        handle
+            .on::<PlaybackEnded>(move |c| {
+                let _ = tx.send(("ended", c.handle.uuid()));
+            })
+            .unwrap();
+        t_handle.spawn_ticker();
+
+        assert_eq!(rx.recv_async().await.unwrap(), ("playable", handle.uuid()));
+        assert!(handle.stop().is_ok());
+        assert_eq!(rx.recv_async().await.unwrap(), ("ended", handle.uuid()));
+    }
+
     #[tokio::test]
     #[ntest::timeout(10_000)]
     async fn seek_callback_fires() {
@@ -298,4 +571,80 @@ mod tests {
         let delta = Duration::from_millis(100);
         assert!(answer > target - delta && answer < target + delta);
     }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn user_data_visible_to_track_events() {
+        struct Owner(&'static str);
+
+        struct Check {
+            tx: Sender<Option<&'static str>>,
+        }
+
+        #[async_trait::async_trait]
+        impl EventHandler for Check {
+            async fn act(&self, ctx: &crate::EventContext<'_>) -> Option<Event> {
+                if let crate::EventContext::Track(&[(_, handle)]) = ctx {
+                    _ = self.tx.send(handle.data::<Owner>().map(|o| o.0));
+                }
+
+                Some(Event::Cancel)
+            }
+        }
+
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+
+        let track = Track::from(File::new(FILE_WAV_TARGET)).user_data(Arc::new(Owner("alice")));
+        let handle = driver.play(track);
+        assert_eq!(handle.data::<Owner>().map(|o| o.0), Some("alice"));
+        assert!(handle.data::<u32>().is_none());
+
+        let (tx, rx) = flume::bounded(1);
+        handle
+            .add_event(Event::Track(TrackEvent::Playable), Check { tx })
+            .unwrap();
+        t_handle.spawn_ticker();
+
+        assert_eq!(rx.recv_async().await.unwrap(), Some("alice"));
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn once_when_fires_once_predicate_holds() {
+        struct Check {
+            tx: Sender<Duration>,
+        }
+
+        #[async_trait::async_trait]
+        impl EventHandler for Check {
+            async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
+                if let EventContext::Track(&[(state, _)]) = ctx {
+                    _ = self.tx.send(state.position);
+                }
+
+                None
+            }
+        }
+
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+
+        let handle = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+
+        let (tx, rx) = flume::unbounded();
+        let target = Duration::from_millis(200);
+        handle
+            .once_when(
+                Event::Periodic(Duration::from_millis(20), None),
+                move |state| state.position >= target,
+                Check { tx },
+            )
+            .unwrap();
+        t_handle.spawn_ticker();
+
+        assert!(rx.recv_async().await.unwrap() >= target);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(rx.is_empty());
+    }
 }
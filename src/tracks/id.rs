@@ -0,0 +1,58 @@
+use std::{
+    fmt::{Display, Formatter, Result as FmtResult},
+    num::NonZeroU64,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+static NEXT_TRACK_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Compact, process-unique identifier for a [`Track`].
+///
+/// IDs are assigned in creation order when a [`Track`] is built, so they are known
+/// before the track is handed to a driver and can be used to refer to a track in logs
+/// or UIs without holding onto its [`TrackHandle`]. Unlike a track's [`Uuid`], these
+/// cannot be overridden by the user.
+///
+/// [`Track`]: super::Track
+/// [`TrackHandle`]: super::TrackHandle
+/// [`Uuid`]: uuid::Uuid
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct TrackId(NonZeroU64);
+
+impl TrackId {
+    pub(crate) fn next() -> Self {
+        let id = NEXT_TRACK_ID.fetch_add(1, Ordering::Relaxed);
+        Self(NonZeroU64::new(id).expect("Track ID counter should never wrap to zero."))
+    }
+
+    /// Returns the raw value of this ID.
+    #[must_use]
+    pub fn get(self) -> u64 {
+        self.0.get()
+    }
+}
+
+impl Display for TrackId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl From<TrackId> for u64 {
+    fn from(id: TrackId) -> Self {
+        id.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ids_are_creation_ordered() {
+        let a = TrackId::next();
+        let b = TrackId::next();
+
+        assert!(a < b);
+    }
+}
@@ -73,18 +73,19 @@ mod tests {
         // 2) Track ends.
         // 3) Playtime >> Position
         assert_eq!(
-            l_rx.recv_async().await.map(|v| v.loops),
-            Ok(LoopState::Finite(1))
+            l_rx.recv_async().await.map(|v| (v.loops, v.loop_count)),
+            Ok((LoopState::Finite(1), 1))
         );
         assert_eq!(
-            l_rx.recv_async().await.map(|v| v.loops),
-            Ok(LoopState::Finite(0))
+            l_rx.recv_async().await.map(|v| (v.loops, v.loop_count)),
+            Ok((LoopState::Finite(0), 2))
         );
         let ended = e_rx.recv_async().await;
 
         assert!(ended.is_ok());
 
         let ended = ended.unwrap();
+        assert_eq!(ended.loop_count, 2);
         assert!(ended.play_time > 2 * ended.position);
     }
 
@@ -123,6 +124,7 @@ mod tests {
         let final_state = final_state.unwrap();
 
         assert_eq!(final_state.playing, PlayMode::Play);
+        assert_eq!(final_state.loop_count, 3);
         assert!(final_state.play_time > 2 * final_state.position);
     }
 }
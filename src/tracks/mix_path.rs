@@ -0,0 +1,60 @@
+use crate::input::core::codecs::CodecType;
+
+/// How the mixer processed a track's most recent frame of audio.
+///
+/// Retrieved via [`TrackHandle::mix_info`].
+///
+/// [`TrackHandle::mix_info`]: super::TrackHandle::mix_info
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum MixPath {
+    /// Opus packets were sent to Discord as-is, without decoding or re-encoding.
+    ///
+    /// This is the cheapest path, and requires an Opus source playing alone at
+    /// full volume.
+    Passthrough,
+    /// Audio was decoded and mixed at its native sample rate, then re-encoded.
+    Decode,
+    /// Audio was decoded, resampled to 48kHz and mixed, then re-encoded.
+    DecodeResample,
+}
+
+/// Why a track's audio could not take the [`MixPath::Passthrough`] path.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum DecodeReason {
+    /// The source is not encoded using Opus.
+    Codec,
+    /// The source's Opus packets do not match Discord's frame size, and so
+    /// cannot be sent directly.
+    FrameSize,
+    /// The track was created with pre-rolling enabled, which always decodes
+    /// audio in advance.
+    Preroll,
+    /// Other audio must be mixed in, or the track is playing with a modified
+    /// volume or pan, or the driver has an analysis tap.
+    Mixing,
+    /// The driver's [`BandwidthCap`] requires all audio to be re-encoded.
+    ///
+    /// [`BandwidthCap`]: crate::driver::BandwidthCap
+    Bandwidth,
+    /// The source has more channels than the driver's [`MixMode`] allows.
+    ///
+    /// [`MixMode`]: crate::driver::MixMode
+    Channels,
+}
+
+/// Diagnostics on how the mixer handles a track's audio, used to understand
+/// the CPU cost of playing a source.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct MixInfo {
+    /// How the track's latest frame was mixed.
+    pub path: MixPath,
+    /// Why the track's latest frame was decoded, if not passed through.
+    pub reason: Option<DecodeReason>,
+    /// The codec of the track's source.
+    pub codec: CodecType,
+    /// The native sample rate of the track's source, if known.
+    pub sample_rate: Option<u32>,
+}
@@ -18,9 +18,13 @@
 
 mod action;
 mod command;
+mod end;
 mod error;
+mod group;
 mod handle;
+mod id;
 mod looping;
+mod mix_path;
 mod mode;
 mod queue;
 mod ready;
@@ -29,9 +33,13 @@ mod view;
 
 pub use self::{
     action::*,
+    end::{TrackEnd, TrackEndReason},
     error::*,
+    group::*,
     handle::*,
+    id::TrackId,
     looping::*,
+    mix_path::*,
     mode::*,
     queue::*,
     ready::*,
@@ -39,9 +47,15 @@ pub use self::{
     view::*,
 };
 pub(crate) use command::*;
+pub(crate) use end::EndWaiter;
 
-use crate::{constants::*, driver::tasks::message::*, events::EventStore, input::Input};
-use std::time::Duration;
+use crate::{
+    constants::*,
+    driver::{tasks::message::*, Bitrate},
+    events::EventStore,
+    input::Input,
+};
+use std::{any::Any, sync::Arc, time::Duration};
 use uuid::Uuid;
 
 /// Initial state for audio playback.
@@ -82,6 +96,58 @@ pub struct Track {
     /// Defaults to `1.0`.
     pub volume: f32,
 
+    /// The stereo pan position for playback, from `-1.0` (fully left) to
+    /// `1.0` (fully right).
+    ///
+    /// Panning uses a constant-power law: a centred track is unchanged, while a
+    /// track panned fully to one side is silent in the other channel and 3dB
+    /// louder in its own. This has no effect when the driver mixes in mono.
+    ///
+    /// Defaults to `0.0`.
+    pub pan: f32,
+
+    /// Whether this track is muted.
+    ///
+    /// A muted track keeps decoding and advancing its position as if it were
+    /// playing, but adds only silence to the mix. Unlike pausing, this keeps the
+    /// track aligned with real time.
+    ///
+    /// Defaults to `false`.
+    pub muted: bool,
+
+    /// This track's importance when the driver is overloaded.
+    ///
+    /// Under [`OverloadStrategy::DropLowestPriority`], tracks with lower priority are
+    /// the first to be held in place when the mixer runs out of time.
+    ///
+    /// Defaults to `0`.
+    ///
+    /// [`OverloadStrategy::DropLowestPriority`]: crate::driver::OverloadStrategy::DropLowestPriority
+    pub priority: i8,
+
+    /// Encoder bitrates to apply as playback reaches each given position in this track.
+    ///
+    /// Each `(position, bitrate)` point holds until the next point is reached. Before the
+    /// first point, the driver's own bitrate is used. When several playing tracks have
+    /// automation, the one added to the driver first takes effect.
+    ///
+    /// This allows, e.g., lowering the bitrate during long speech segments and raising
+    /// it again for music, without affecting other tracks' settings.
+    ///
+    /// Defaults to an empty list, which never changes the bitrate.
+    pub bitrate_automation: Vec<(Duration, Bitrate)>,
+
+    /// Number of 20ms frames to decode ahead of playout.
+    ///
+    /// A pre-roll lets playback ride out brief stalls in decoding: a track with buffered
+    /// frames keeps playing when its source is slow or when the mixer is overloaded. Larger
+    /// values suit flaky sources, while latency-sensitive audio such as text-to-speech
+    /// should use none. Buffered audio is discarded on seek, and pre-rolled tracks are
+    /// never sent via Opus passthrough.
+    ///
+    /// Defaults to `0`.
+    pub preroll: usize,
+
     /// The live or lazily-initialised audio stream to be played.
     pub input: Input,
 
@@ -104,6 +170,16 @@ pub struct Track {
     ///
     /// Defaults to a random 128-bit number.
     pub uuid: Uuid,
+
+    /// Any data to be associated with the track.
+    ///
+    /// This is shared by every [`TrackHandle`] to this track, and so can be
+    /// retrieved from the handles given to track events.
+    ///
+    /// Defaults to `()`.
+    pub user_data: Arc<dyn Any + Send + Sync + 'static>,
+
+    id: TrackId,
 }
 
 impl Track {
@@ -121,10 +197,17 @@ impl Track {
         Self {
             playing: PlayMode::default(),
             volume: 1.0,
+            pan: 0.0,
+            muted: false,
+            priority: 0,
+            bitrate_automation: Vec::new(),
+            preroll: 0,
             input,
             events: EventStore::new_local(),
             loops: LoopState::Finite(0),
             uuid,
+            user_data: Arc::new(()),
+            id: TrackId::next(),
         }
     }
 
@@ -162,6 +245,60 @@ impl Track {
         self
     }
 
+    #[must_use]
+    /// Sets [`pan`] in a manner that allows method chaining.
+    ///
+    /// Values outside `-1.0..=1.0` are clamped.
+    ///
+    /// [`pan`]: Track::pan
+    pub fn pan(mut self, pan: f32) -> Self {
+        self.pan = pan.clamp(-1.0, 1.0);
+
+        self
+    }
+
+    #[must_use]
+    /// Sets [`muted`] in a manner that allows method chaining.
+    ///
+    /// [`muted`]: Track::muted
+    pub fn mute(mut self, muted: bool) -> Self {
+        self.muted = muted;
+
+        self
+    }
+
+    #[must_use]
+    /// Sets [`priority`] in a manner that allows method chaining.
+    ///
+    /// [`priority`]: Track::priority
+    pub fn priority(mut self, priority: i8) -> Self {
+        self.priority = priority;
+
+        self
+    }
+
+    #[must_use]
+    /// Sets [`bitrate_automation`] in a manner that allows method chaining.
+    ///
+    /// Points may be given in any order.
+    ///
+    /// [`bitrate_automation`]: Track::bitrate_automation
+    pub fn bitrate_automation(mut self, points: Vec<(Duration, Bitrate)>) -> Self {
+        self.bitrate_automation = points;
+
+        self
+    }
+
+    #[must_use]
+    /// Sets [`preroll`] in a manner that allows method chaining.
+    ///
+    /// [`preroll`]: Track::preroll
+    pub fn preroll(mut self, frames: usize) -> Self {
+        self.preroll = frames;
+
+        self
+    }
+
     #[must_use]
     /// Set an audio track to loop a set number of times.
     pub fn loops(mut self, loops: LoopState) -> Self {
@@ -178,9 +315,25 @@ impl Track {
         self
     }
 
+    #[must_use]
+    /// Sets [`user_data`] in a manner that allows method chaining.
+    ///
+    /// [`user_data`]: Track::user_data
+    pub fn user_data(mut self, user_data: Arc<dyn Any + Send + Sync + 'static>) -> Self {
+        self.user_data = user_data;
+
+        self
+    }
+
+    /// Returns this track's [`TrackId`], which is shared by all of its handles.
+    #[must_use]
+    pub fn id(&self) -> TrackId {
+        self.id
+    }
+
     pub(crate) fn into_context(self) -> (TrackHandle, TrackContext) {
         let (tx, receiver) = flume::unbounded();
-        let handle = TrackHandle::new(tx, self.uuid);
+        let handle = TrackHandle::new(tx, self.id, self.uuid, self.user_data.clone());
 
         let context = TrackContext {
             handle: handle.clone(),
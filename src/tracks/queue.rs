@@ -1,12 +1,42 @@
 use crate::{
-    driver::Driver,
+    driver::{tasks::message::CoreMessage, Driver},
     events::{Event, EventContext, EventData, EventHandler, TrackEvent},
-    input::Input,
-    tracks::{Track, TrackHandle, TrackResult},
+    input::{
+        codecs::{CODEC_REGISTRY, PROBE},
+        Compose,
+        Input,
+        LiveInput,
+        Parsed,
+    },
+    tracks::{
+        EnqueueError,
+        LoopState,
+        PlayError,
+        PlayMode,
+        Track,
+        TrackHandle,
+        TrackId,
+        TrackResult,
+    },
 };
 use async_trait::async_trait;
+use flume::Sender;
+use futures::future::join_all;
 use parking_lot::Mutex;
-use std::{collections::VecDeque, ops::Deref, sync::Arc, time::Duration};
+use std::{
+    any::Any,
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    fmt::{Debug, Formatter, Result as FmtResult},
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::Arc,
+    time::Duration,
+};
+use symphonia_core::{
+    audio::SampleBuffer,
+    errors::Error as SymphoniaError,
+    formats::{SeekMode, SeekTo},
+};
 use tracing::{info, warn};
 
 /// A simple queue for several audio sources, designed to
@@ -64,13 +94,24 @@ pub struct TrackQueue {
 ///
 /// Instances *should not* be moved from one queue to another.
 #[derive(Debug)]
-pub struct Queued(TrackHandle);
+pub struct Queued {
+    handle: TrackHandle,
+    requester: Option<u64>,
+    duration: Option<Duration>,
+    key: Option<u64>,
+    votes: usize,
+    source: Option<QueueSource>,
+    metadata: Option<QueueMetadata>,
+}
+
+/// User-defined data attached to a queue entry.
+type QueueMetadata = Arc<dyn Any + Send + Sync>;
 
 impl Deref for Queued {
     type Target = TrackHandle;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.handle
     }
 }
 
@@ -78,11 +119,264 @@ impl Queued {
     /// Clones the inner handle
     #[must_use]
     pub fn handle(&self) -> TrackHandle {
-        self.0.clone()
+        self.handle.clone()
+    }
+
+    /// Returns the requester key this track was enqueued with, if any.
+    ///
+    /// See [`TrackQueue::add_with_requester`].
+    #[must_use]
+    pub fn requester(&self) -> Option<u64> {
+        self.requester
+    }
+
+    /// Returns the length of a single play-through of this track, if known.
+    ///
+    /// This is known when the track was added via [`TrackQueue::add`] (or similar)
+    /// and its [`AuxMetadata`] reported a duration.
+    ///
+    /// [`AuxMetadata`]: crate::input::AuxMetadata
+    #[must_use]
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Returns how many times this track has been requested.
+    ///
+    /// This starts at 1, and increases each time a duplicate is coalesced into
+    /// this entry under [`DuplicatePolicy::Coalesce`].
+    #[must_use]
+    pub fn votes(&self) -> usize {
+        self.votes
+    }
+
+    /// Returns whether this track can be repeated by the queue's [`QueueLoop`] mode.
+    ///
+    /// See [`TrackQueue::add_repeatable`].
+    #[must_use]
+    pub fn is_repeatable(&self) -> bool {
+        self.source.is_some()
+    }
+
+    /// Returns the metadata attached to this entry, if it has any of type `T`.
+    ///
+    /// See [`TrackQueue::set_metadata`].
+    #[must_use]
+    pub fn metadata<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        downcast_metadata(self.metadata.as_ref())
+    }
+
+    /// Attaches metadata to this entry, replacing any existing metadata.
+    ///
+    /// See [`TrackQueue::set_metadata`].
+    pub fn set_metadata<T: Send + Sync + 'static>(&mut self, metadata: T) {
+        self.metadata = Some(Arc::new(metadata));
+    }
+}
+
+fn downcast_metadata<T: Send + Sync + 'static>(metadata: Option<&QueueMetadata>) -> Option<Arc<T>> {
+    metadata.cloned()?.downcast().ok()
+}
+
+/// Queue metadata for a track which is about to be added.
+#[derive(Default)]
+struct Pending {
+    requester: Option<u64>,
+    duration: Option<Duration>,
+    key: Option<u64>,
+    source: Option<QueueSource>,
+}
+
+/// Function used to configure each track created from a [`QueueSource`].
+type TrackBuilder = Arc<dyn Fn(Track) -> Track + Send + Sync>;
+
+/// A stored lazy source, used to recreate a queued track each time it repeats.
+#[derive(Clone)]
+struct QueueSource {
+    input: Arc<dyn Fn() -> Input + Send + Sync>,
+    build: Option<TrackBuilder>,
+}
+
+impl QueueSource {
+    fn new<C: Compose + Clone + 'static>(source: C, build: Option<TrackBuilder>) -> Self {
+        let source = Mutex::new(source);
+        Self {
+            input: Arc::new(move || Input::Lazy(Box::new(source.lock().clone()))),
+            build,
+        }
+    }
+
+    fn track(&self) -> Track {
+        let track = Track::from((self.input)());
+
+        match &self.build {
+            Some(build) => build(track),
+            None => track,
+        }
+    }
+}
+
+impl Debug for QueueSource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("QueueSource").finish_non_exhaustive()
+    }
+}
+
+/// Strategies for ordering new entries in a [`TrackQueue`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum QueueOrder {
+    /// Tracks are played in the order they were added.
+    ///
+    /// The default choice.
+    #[default]
+    Fifo,
+    /// Upcoming tracks are interleaved between requesters, so that each requester
+    /// has at most one track played before any other requester's next track.
+    ///
+    /// Tracks within each requester's share are played in the order they were added.
+    /// All tracks added without a requester key share a single turn.
+    RoundRobin,
+}
+
+/// How a [`TrackQueue`] handles a keyed track which is identical to the track
+/// before it.
+///
+/// Tracks are compared using the key given to [`TrackQueue::add_keyed`] (e.g., a URL
+/// or content hash), against the last track in the queue. Tracks added without a key
+/// are never considered duplicates.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum DuplicatePolicy {
+    /// Duplicates are queued as normal.
+    ///
+    /// The default choice.
+    #[default]
+    Allow,
+    /// Duplicates are refused with [`EnqueueError::Duplicate`].
+    Reject,
+    /// Duplicates are not queued, and instead add a vote to the existing entry
+    /// (see [`Queued::votes`]).
+    ///
+    /// The handle of the existing entry is returned in place of a new track.
+    Coalesce,
+}
+
+/// How a [`TrackQueue`] repeats tracks once they leave the head of the queue.
+///
+/// Only tracks added via [`TrackQueue::add_repeatable`] can be repeated, as each
+/// repeat is recreated from its stored source. Other tracks leave the queue as normal.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum QueueLoop {
+    /// Tracks leave the queue once they end.
+    ///
+    /// The default choice.
+    #[default]
+    Off,
+    /// The current track is replayed each time it ends.
+    ///
+    /// Skipping the track moves on to the next entry as normal.
+    Track,
+    /// Each track is added back to the end of the queue once it ends or is skipped.
+    Queue,
+}
+
+/// Details of a queued track which failed to play.
+///
+/// This is passed to a queue's [`QueueErrorHandler`].
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct QueueError {
+    /// Position of the failed track in the queue at the time of failure.
+    ///
+    /// The failed track has already been removed from the queue.
+    pub index: usize,
+    /// Handle to the failed track.
+    pub handle: TrackHandle,
+    /// The requester key this track was enqueued with, if any.
+    pub requester: Option<u64>,
+    /// The reason the track could not be played.
+    pub error: PlayError,
+    metadata: Option<QueueMetadata>,
+}
+
+impl QueueError {
+    /// Returns the metadata attached to the failed entry, if it has any of type `T`.
+    ///
+    /// Metadata is carried over to any replacement returned by the [`QueueErrorHandler`].
+    #[must_use]
+    pub fn metadata<T: Send + Sync + 'static>(&self) -> Option<Arc<T>> {
+        downcast_metadata(self.metadata.as_ref())
+    }
+}
+
+/// Observes tracks in a [`TrackQueue`] which fail to play, optionally supplying a
+/// replacement source.
+///
+/// This is useful for lazy sources such as search-on-demand [`Compose`] inputs, where
+/// an alternative source may be resolved if the original cannot be created.
+///
+/// [`Compose`]: crate::input::Compose
+#[async_trait]
+pub trait QueueErrorHandler: Send + Sync {
+    /// Called after a queued track fails, either during playback or while being preloaded.
+    ///
+    /// Returning `Some` inserts the given input into the queue in place of the failed
+    /// track. Otherwise, the queue skips to its next track as normal.
+    async fn on_error(&self, error: &QueueError) -> Option<Input>;
+}
+
+/// The opening audio of a track, decoded while it is added to a [`TrackQueue`].
+///
+/// This is passed to a queue's [`EnqueueFilter`].
+#[derive(Clone, Debug, Default, PartialEq)]
+#[non_exhaustive]
+pub struct AudioSample {
+    /// Interleaved samples, in the track's native sample rate and channel layout.
+    ///
+    /// This may be shorter than requested if the track itself is shorter.
+    pub samples: Vec<f32>,
+    /// Number of channels interleaved in `samples`.
+    pub channels: usize,
+    /// Sample rate of `samples`, in Hz.
+    pub sample_rate: u32,
+}
+
+impl AudioSample {
+    /// Returns the length of audio held in this sample.
+    #[must_use]
+    pub fn duration(&self) -> Duration {
+        if self.channels == 0 || self.sample_rate == 0 {
+            return Duration::ZERO;
+        }
+
+        let frames = self.samples.len() / self.channels;
+        Duration::from_secs_f64(frames as f64 / f64::from(self.sample_rate))
+    }
+}
+
+/// Inspects the opening audio of tracks added via [`TrackQueue::try_add`], deciding
+/// whether they may join the queue.
+///
+/// This allows bots to detect and refuse duplicate or reposted tracks, e.g., by
+/// fingerprinting each sample.
+#[async_trait]
+pub trait EnqueueFilter: Send + Sync {
+    /// The length of audio to decode from the start of each track.
+    ///
+    /// Defaults to 10 seconds.
+    fn sample_length(&self) -> Duration {
+        Duration::from_secs(10)
     }
+
+    /// Called with the opening audio of a new track.
+    ///
+    /// Returning `false` refuses the track, which is then dropped without being played.
+    async fn accept(&self, sample: &AudioSample) -> bool;
 }
 
-#[derive(Debug, Default)]
+#[derive(Default)]
 /// Inner portion of a [`TrackQueue`].
 ///
 /// This abstracts away thread-safety from the user,
@@ -91,6 +385,29 @@ impl Queued {
 /// [`TrackQueue`]: TrackQueue
 struct TrackQueueCore {
     tracks: VecDeque<Queued>,
+    order: QueueOrder,
+    duplicate_policy: DuplicatePolicy,
+    loop_mode: QueueLoop,
+    error_handler: Option<Arc<dyn QueueErrorHandler>>,
+    enqueue_filter: Option<Arc<dyn EnqueueFilter>>,
+    // Set by `pause_queue`, preventing any track from starting until `resume_queue`.
+    paused: bool,
+    // Updated on each insertion, used to play fallback tracks.
+    driver: Option<Sender<CoreMessage>>,
+}
+
+impl Debug for TrackQueueCore {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        f.debug_struct("TrackQueueCore")
+            .field("tracks", &self.tracks)
+            .field("order", &self.order)
+            .field("duplicate_policy", &self.duplicate_policy)
+            .field("loop_mode", &self.loop_mode)
+            .field("error_handler", &self.error_handler.is_some())
+            .field("enqueue_filter", &self.enqueue_filter.is_some())
+            .field("paused", &self.paused)
+            .finish_non_exhaustive()
+    }
 }
 
 struct QueueHandler {
@@ -100,37 +417,65 @@ struct QueueHandler {
 #[async_trait]
 impl EventHandler for QueueHandler {
     async fn act(&self, ctx: &EventContext<'_>) -> Option<Event> {
-        let mut inner = self.remote_lock.lock();
+        let (index, failure, handler) = {
+            let mut inner = self.remote_lock.lock();
+
+            // Due to possibility that users might remove, reorder,
+            // or dequeue+stop tracks, we need to verify that the FIRST
+            // track is the one who has ended, unless it failed during preload.
+            let (index, error, ended) = match ctx {
+                EventContext::Track(ts) => {
+                    // This slice should have exactly one entry.
+                    let (state, handle) = ts.first()?;
+                    let error = match &state.playing {
+                        PlayMode::Errored(e) => Some(e.clone()),
+                        _ => None,
+                    };
+
+                    let index = inner.tracks.iter().position(|q| q.id() == handle.id())?;
+                    if index != 0 && error.is_none() {
+                        return None;
+                    }
+
+                    (index, error, state.playing == PlayMode::End)
+                },
+                _ => return None,
+            };
+
+            let old = inner.tracks.remove(index)?;
+
+            info!("Queued track ended: {:?}.", ctx);
+
+            if error.is_none() {
+                inner.repeat(&old, ended, &self.remote_lock);
+            }
 
-        // Due to possibility that users might remove, reorder,
-        // or dequeue+stop tracks, we need to verify that the FIRST
-        // track is the one who has ended.
-        match ctx {
-            EventContext::Track(ts) => {
-                // This slice should have exactly one entry.
-                // If the ended track has same id as the queue head, then
-                // we can progress the queue.
-                if inner.tracks.front()?.uuid() != ts.first()?.1.uuid() {
-                    return None;
-                }
-            },
-            _ => return None,
-        }
+            info!("{} tracks remain.", inner.tracks.len());
 
-        let _old = inner.tracks.pop_front();
+            let failure = error.map(|error| QueueError {
+                index,
+                handle: old.handle,
+                requester: old.requester,
+                error,
+                metadata: old.metadata,
+            });
 
-        info!("Queued track ended: {:?}.", ctx);
-        info!("{} tracks remain.", inner.tracks.len());
+            (index, failure, inner.error_handler.clone())
+        };
 
-        // Keep going until we find one track which works, or we run out.
-        while let Some(new) = inner.tracks.front() {
-            if new.play().is_err() {
-                // Discard files which cannot be used for whatever reason.
-                warn!("Track in Queue couldn't be played...");
-                inner.tracks.pop_front();
-            } else {
-                break;
-            }
+        let fallback = match (&failure, handler) {
+            (Some(failure), Some(handler)) => handler.on_error(failure).await,
+            _ => None,
+        };
+
+        let mut inner = self.remote_lock.lock();
+
+        if let (Some(input), Some(failure)) = (fallback, failure) {
+            inner.insert_fallback(index, input, failure, &self.remote_lock);
+        }
+
+        if index == 0 {
+            inner.play_head();
         }
 
         None
@@ -149,7 +494,7 @@ impl EventHandler for SongPreloader {
         if let Some(track) = inner.tracks.get(1) {
             // This is the sync-version so that we can fire and ignore
             // the request ASAP.
-            drop(track.0.make_playable());
+            drop(track.handle.make_playable());
         }
 
         None
@@ -161,9 +506,7 @@ impl TrackQueue {
     #[must_use]
     pub fn new() -> Self {
         Self {
-            inner: Arc::new(Mutex::new(TrackQueueCore {
-                tracks: VecDeque::new(),
-            })),
+            inner: Arc::new(Mutex::new(TrackQueueCore::default())),
         }
     }
 
@@ -177,21 +520,184 @@ impl TrackQueue {
         self.add(input.into(), driver).await
     }
 
+    /// Adds an audio source to the queue after configuring its [`Track`] with `build`,
+    /// to be played in the channel managed by `driver`.
+    ///
+    /// `build` may set the track's volume or loops, or attach event handlers, all of
+    /// which are in place before the track is handed to the driver. The queue's own
+    /// handlers are registered afterwards. Otherwise, this behaves identically to
+    /// [`Self::add`].
+    pub async fn add_with<F>(&self, input: Input, build: F, driver: &mut Driver) -> TrackHandle
+    where
+        F: FnOnce(Track) -> Track,
+    {
+        self.add(build(Track::from(input)), driver).await
+    }
+
     /// Adds a [`Track`] object to the queue, to be played in the channel managed by `driver`.
     ///
     /// This allows additional configuration or event handlers to be added
-    /// before enqueueing the audio track. [`Track`]s will be paused pre-emptively.
+    /// before enqueueing the audio track. [`Track`]s will be paused pre-emptively,
+    /// and the queue's own event handlers are registered after any already attached.
     ///
     /// This method will preload the next track 5 seconds before the current track ends, if
     /// the [`AuxMetadata`] can be successfully queried for a [`Duration`].
     ///
     /// [`AuxMetadata`]: crate::input::AuxMetadata
     pub async fn add(&self, mut track: Track, driver: &mut Driver) -> TrackHandle {
-        let preload_time = Self::get_preload_time(&mut track).await;
-        self.add_with_preload(track, driver, preload_time)
+        let duration = Self::get_duration(&mut track).await;
+        self.add_inner(track, driver, Self::preload_time(duration), None, duration)
+    }
+
+    /// Adds a [`Track`] object to the queue on behalf of a requester, to be played in
+    /// the channel managed by `driver`.
+    ///
+    /// `requester` is an opaque key (e.g., the ID of the user who asked for this track)
+    /// which is used to share playback fairly when the queue uses [`QueueOrder::RoundRobin`].
+    /// Otherwise, this behaves identically to [`Self::add`].
+    pub async fn add_with_requester(
+        &self,
+        mut track: Track,
+        requester: u64,
+        driver: &mut Driver,
+    ) -> TrackHandle {
+        let duration = Self::get_duration(&mut track).await;
+        self.add_inner(
+            track,
+            driver,
+            Self::preload_time(duration),
+            Some(requester),
+            duration,
+        )
+    }
+
+    /// Adds a lazily created source to the queue, to be played in the channel managed
+    /// by `driver`.
+    ///
+    /// The queue keeps a copy of `source`, so that the track can be recreated whenever
+    /// it is repeated under [`QueueLoop::Track`] or [`QueueLoop::Queue`]. Each repeat is
+    /// a new track, with its own [`TrackHandle`] and default settings.
+    ///
+    /// Otherwise, this behaves identically to [`Self::add_source`].
+    pub async fn add_repeatable<C>(&self, source: C, driver: &mut Driver) -> TrackHandle
+    where
+        C: Compose + Clone + 'static,
+    {
+        self.add_repeatable_inner(QueueSource::new(source, None), driver)
+            .await
+    }
+
+    /// Adds a lazily created source to the queue, configuring its [`Track`] with `build`,
+    /// to be played in the channel managed by `driver`.
+    ///
+    /// `build` is called again whenever the track is repeated, so that each repeat keeps
+    /// the same settings and event handlers. Otherwise, this behaves identically to
+    /// [`Self::add_repeatable`].
+    pub async fn add_repeatable_with<C, F>(
+        &self,
+        source: C,
+        build: F,
+        driver: &mut Driver,
+    ) -> TrackHandle
+    where
+        C: Compose + Clone + 'static,
+        F: Fn(Track) -> Track + Send + Sync + 'static,
+    {
+        self.add_repeatable_inner(QueueSource::new(source, Some(Arc::new(build))), driver)
+            .await
+    }
+
+    async fn add_repeatable_inner(&self, source: QueueSource, driver: &mut Driver) -> TrackHandle {
+        let mut track = source.track();
+        let duration = Self::get_duration(&mut track).await;
+
+        self.insert(
+            track,
+            driver,
+            Self::preload_time(duration),
+            Pending {
+                duration,
+                source: Some(source),
+                ..Default::default()
+            },
+        )
+        .expect("Tracks without a key are never duplicates.")
+    }
+
+    /// Adds an audio source to the queue under a deduplication key, to be played in the
+    /// channel managed by `driver`.
+    ///
+    /// See [`Self::add_keyed`] for how `key` is used.
+    pub async fn add_source_keyed(
+        &self,
+        input: Input,
+        key: impl Hash,
+        driver: &mut Driver,
+    ) -> Result<TrackHandle, EnqueueError> {
+        self.add_keyed(input.into(), key, driver).await
+    }
+
+    /// Adds a [`Track`] object to the queue under a deduplication key, to be played
+    /// in the channel managed by `driver`.
+    ///
+    /// `key` identifies the track's content (e.g., its URL or a hash of it). If it matches
+    /// the key of the last track in the queue, this queue's [`DuplicatePolicy`] decides
+    /// whether the new track is queued, refused, or counted as a vote for the existing
+    /// entry. This check is made while the queue is locked, so concurrent requests for
+    /// the same track cannot slip past one another.
+    ///
+    /// Otherwise, this behaves identically to [`Self::add`].
+    pub async fn add_keyed(
+        &self,
+        mut track: Track,
+        key: impl Hash,
+        driver: &mut Driver,
+    ) -> Result<TrackHandle, EnqueueError> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+
+        let duration = Self::get_duration(&mut track).await;
+        self.insert(
+            track,
+            driver,
+            Self::preload_time(duration),
+            Pending {
+                duration,
+                key: Some(hasher.finish()),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Adds a [`Track`] object to the queue if it is accepted by this queue's
+    /// [`EnqueueFilter`], to be played in the channel managed by `driver`.
+    ///
+    /// If a filter is set, the track's input is created, parsed, and its opening audio
+    /// is decoded and passed to the filter. The input is then rewound to its start, or
+    /// recreated if it cannot seek backwards. Otherwise, this behaves identically to
+    /// [`Self::add`].
+    ///
+    /// Filters are not consulted by any other method used to add tracks.
+    pub async fn try_add(
+        &self,
+        mut track: Track,
+        driver: &mut Driver,
+    ) -> Result<TrackHandle, EnqueueError> {
+        let filter = self.inner.lock().enqueue_filter.clone();
+
+        if let Some(filter) = filter {
+            let (input, sample) = sample_input(track.input, filter.sample_length()).await?;
+            track.input = input;
+
+            if !filter.accept(&sample).await {
+                return Err(EnqueueError::Rejected);
+            }
+        }
+
+        Ok(self.add(track, driver).await)
     }
 
-    pub(crate) async fn get_preload_time(track: &mut Track) -> Option<Duration> {
+    pub(crate) async fn get_duration(track: &mut Track) -> Option<Duration> {
         let meta = match track.input {
             Input::Lazy(ref mut rec) | Input::Live(_, Some(ref mut rec)) =>
                 rec.aux_metadata().await.ok(),
@@ -199,7 +705,10 @@ impl TrackQueue {
         };
 
         meta.and_then(|meta| meta.duration)
-            .map(|d| d.saturating_sub(Duration::from_secs(5)))
+    }
+
+    pub(crate) fn preload_time(duration: Option<Duration>) -> Option<Duration> {
+        duration.map(|d| d.saturating_sub(Duration::from_secs(5)))
     }
 
     /// Add an existing [`Track`] to the queue, using a known time to preload the next track.
@@ -215,43 +724,146 @@ impl TrackQueue {
     #[inline]
     pub fn add_with_preload(
         &self,
-        mut track: Track,
+        track: Track,
+        driver: &mut Driver,
+        preload_time: Option<Duration>,
+    ) -> TrackHandle {
+        self.add_inner(track, driver, preload_time, None, None)
+    }
+
+    pub(crate) fn add_inner(
+        &self,
+        track: Track,
         driver: &mut Driver,
         preload_time: Option<Duration>,
+        requester: Option<u64>,
+        duration: Option<Duration>,
     ) -> TrackHandle {
+        self.insert(
+            track,
+            driver,
+            preload_time,
+            Pending {
+                requester,
+                duration,
+                ..Default::default()
+            },
+        )
+        .expect("Tracks without a key are never duplicates.")
+    }
+
+    fn insert(
+        &self,
+        mut track: Track,
+        driver: &mut Driver,
+        preload_time: Option<Duration>,
+        pending: Pending,
+    ) -> Result<TrackHandle, EnqueueError> {
         // Attempts to start loading the next track before this one ends.
         // Idea is to provide as close to gapless playback as possible,
         // while minimising memory use.
-        info!("Track added to queue.");
-
-        let remote_lock = self.inner.clone();
-        track.events.add_event(
-            EventData::new(Event::Track(TrackEvent::End), QueueHandler { remote_lock }),
-            Duration::ZERO,
-        );
-
-        if let Some(time) = preload_time {
-            let remote_lock = self.inner.clone();
-            track.events.add_event(
-                EventData::new(Event::Delayed(time), SongPreloader { remote_lock }),
-                Duration::ZERO,
-            );
-        }
+        attach_queue_events(&mut track, &self.inner, preload_time);
 
         let (should_play, handle) = {
             let mut inner = self.inner.lock();
 
-            let handle = driver.play(track.pause());
-            inner.tracks.push_back(Queued(handle.clone()));
+            let policy = inner.duplicate_policy;
+            if let Some(existing) = inner.duplicate_of(pending.key) {
+                match policy {
+                    DuplicatePolicy::Allow => {},
+                    DuplicatePolicy::Reject => {
+                        info!("Duplicate track refused by queue.");
+                        return Err(EnqueueError::Duplicate(existing.handle()));
+                    },
+                    DuplicatePolicy::Coalesce => {
+                        info!("Duplicate track coalesced into queue entry.");
+                        existing.votes += 1;
+                        return Ok(existing.handle());
+                    },
+                }
+            }
 
-            (inner.tracks.len() == 1, handle)
+            info!("Track added to queue.");
+            inner.driver = Some(driver.core_sender());
+
+            let handle = driver.play(track.pause());
+            inner.tracks.push_back(Queued {
+                handle: handle.clone(),
+                requester: pending.requester,
+                duration: pending.duration,
+                key: pending.key,
+                votes: 1,
+                source: pending.source,
+                metadata: None,
+            });
+            inner.reorder();
+
+            (inner.tracks.len() == 1 && !inner.paused, handle)
         };
 
         if should_play {
             drop(handle.play());
         }
 
-        handle
+        Ok(handle)
+    }
+
+    /// Returns the strategy used to order newly added tracks.
+    #[must_use]
+    pub fn order(&self) -> QueueOrder {
+        self.inner.lock().order
+    }
+
+    /// Changes the strategy used to order newly added tracks.
+    ///
+    /// Switching to [`QueueOrder::RoundRobin`] immediately rearranges all upcoming
+    /// tracks. The currently playing track is never moved.
+    pub fn set_order(&self, order: QueueOrder) {
+        let mut inner = self.inner.lock();
+        inner.order = order;
+        inner.reorder();
+    }
+
+    /// Returns how keyed tracks identical to the last queued track are handled.
+    #[must_use]
+    pub fn duplicate_policy(&self) -> DuplicatePolicy {
+        self.inner.lock().duplicate_policy
+    }
+
+    /// Changes how keyed tracks identical to the last queued track are handled.
+    ///
+    /// See [`Self::add_keyed`].
+    pub fn set_duplicate_policy(&self, policy: DuplicatePolicy) {
+        self.inner.lock().duplicate_policy = policy;
+    }
+
+    /// Returns how tracks are repeated once they leave the head of the queue.
+    #[must_use]
+    pub fn loop_mode(&self) -> QueueLoop {
+        self.inner.lock().loop_mode
+    }
+
+    /// Changes how tracks are repeated once they leave the head of the queue.
+    ///
+    /// This takes effect when the current track next ends or is skipped.
+    pub fn set_loop_mode(&self, mode: QueueLoop) {
+        self.inner.lock().loop_mode = mode;
+    }
+
+    /// Sets a handler which is informed of queued tracks which fail to play, and
+    /// which may supply replacements for them.
+    ///
+    /// Any previous handler is replaced, and `None` removes the current handler.
+    pub fn set_error_handler(&self, handler: Option<Arc<dyn QueueErrorHandler>>) {
+        self.inner.lock().error_handler = handler;
+    }
+
+    /// Sets a filter which inspects the opening audio of tracks added via
+    /// [`Self::try_add`], and may refuse them.
+    ///
+    /// Any previous filter is replaced, and `None` removes the current filter.
+    pub fn set_enqueue_filter(&self, filter: Option<Arc<dyn EnqueueFilter>>) {
+        self.inner.lock().enqueue_filter = filter;
     }
 
     /// Returns a handle to the currently playing track.
@@ -272,56 +884,195 @@ impl TrackQueue {
         self.modify_queue(|vq| vq.remove(index))
     }
 
-    /// Returns the number of tracks currently in the queue.
+    /// Returns the current position of the track with the given ID, if it is
+    /// in this queue.
     #[must_use]
-    pub fn len(&self) -> usize {
+    pub fn position(&self, id: TrackId) -> Option<usize> {
         let inner = self.inner.lock();
 
-        inner.tracks.len()
+        inner.tracks.iter().position(|q| q.id() == id)
     }
 
-    /// Returns whether there are no tracks currently in the queue.
+    /// Attempts to remove the track with the given ID from the queue.
+    ///
+    /// See [`dequeue`] for how the returned entry may be used.
+    ///
+    /// [`dequeue`]: TrackQueue::dequeue
     #[must_use]
-    pub fn is_empty(&self) -> bool {
-        let inner = self.inner.lock();
-
-        inner.tracks.is_empty()
+    pub fn dequeue_id(&self, id: TrackId) -> Option<Queued> {
+        self.modify_queue(|vq| {
+            let index = vq.iter().position(|q| q.id() == id)?;
+            vq.remove(index)
+        })
     }
 
-    /// Allows modification of the inner queue (i.e., deletion, reordering).
+    /// Attaches metadata (e.g., a requester's name, or the source URL) to the queue
+    /// entry for the track with the given ID, replacing any existing metadata.
     ///
-    /// Users must be careful to `stop` removed tracks, so as to prevent
-    /// resource leaks.
-    pub fn modify_queue<F, O>(&self, func: F) -> O
-    where
-        F: FnOnce(&mut VecDeque<Queued>) -> O,
-    {
+    /// Unlike [`Track::user_data`], metadata belongs to the queue entry: it is kept
+    /// when a track is repeated by the queue's [`QueueLoop`] mode, or replaced by a
+    /// [`QueueErrorHandler`], and is removed alongside the entry.
+    ///
+    /// Returns `false` if the track is not in this queue.
+    pub fn set_metadata<T: Send + Sync + 'static>(&self, id: TrackId, metadata: T) -> bool {
         let mut inner = self.inner.lock();
-        func(&mut inner.tracks)
+
+        inner
+            .tracks
+            .iter_mut()
+            .find(|q| q.id() == id)
+            .map(|q| q.set_metadata(metadata))
+            .is_some()
     }
 
-    /// Pause the track at the head of the queue.
-    pub fn pause(&self) -> TrackResult<()> {
+    /// Returns the metadata attached to the queue entry for the track with the given
+    /// ID, if it has any of type `T`.
+    #[must_use]
+    pub fn metadata<T: Send + Sync + 'static>(&self, id: TrackId) -> Option<Arc<T>> {
         let inner = self.inner.lock();
 
-        if let Some(handle) = inner.tracks.front() {
-            handle.pause()
-        } else {
-            Ok(())
-        }
+        inner.tracks.iter().find(|q| q.id() == id)?.metadata()
     }
 
-    /// Resume the track at the head of the queue.
-    pub fn resume(&self) -> TrackResult<()> {
-        let inner = self.inner.lock();
-
-        if let Some(handle) = inner.tracks.front() {
+    /// Estimates how long until the track at `index` begins to play.
+    ///
+    /// This is the remaining play time of the current track, plus the [`duration`] of
+    /// every track between it and `index`, including any loops. Queued tracks play
+    /// back-to-back, so no overlap is accounted for. The estimate assumes
+    /// that playback continues uninterrupted, i.e., that no tracks are paused,
+    /// skipped, seeked, or reordered.
+    ///
+    /// Returns `None` if `index` is out of range, the current track has ended, or
+    /// any required duration is unknown (or infinite).
+    ///
+    /// [`duration`]: Queued::duration
+    pub async fn eta(&self, index: usize) -> Option<Duration> {
+        let entries = {
+            let inner = self.inner.lock();
+            if index >= inner.tracks.len() {
+                return None;
+            }
+
+            inner
+                .tracks
+                .range(..index)
+                .map(|q| Some((q.handle(), q.duration?)))
+                .collect::<Option<Vec<_>>>()?
+        };
+
+        let states = join_all(entries.iter().map(|(handle, _)| handle.get_info())).await;
+
+        let mut eta = Duration::ZERO;
+        for (i, ((_, duration), state)) in entries.iter().zip(states).enumerate() {
+            let state = state.ok()?;
+            let loops = match state.loops {
+                LoopState::Finite(n) => u32::try_from(n).ok()?,
+                LoopState::Infinite => return None,
+            };
+
+            // Only the current track has made progress through its input.
+            let first_pass = if i == 0 {
+                duration.saturating_sub(state.position)
+            } else {
+                *duration
+            };
+
+            eta = eta
+                .checked_add(first_pass)?
+                .checked_add(duration.checked_mul(loops)?)?;
+        }
+
+        Some(eta)
+    }
+
+    /// Returns the number of tracks currently in the queue.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        let inner = self.inner.lock();
+
+        inner.tracks.len()
+    }
+
+    /// Returns whether there are no tracks currently in the queue.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        let inner = self.inner.lock();
+
+        inner.tracks.is_empty()
+    }
+
+    /// Allows modification of the inner queue (i.e., deletion, reordering).
+    ///
+    /// Users must be careful to `stop` removed tracks, so as to prevent
+    /// resource leaks.
+    pub fn modify_queue<F, O>(&self, func: F) -> O
+    where
+        F: FnOnce(&mut VecDeque<Queued>) -> O,
+    {
+        let mut inner = self.inner.lock();
+        func(&mut inner.tracks)
+    }
+
+    /// Pause the track at the head of the queue.
+    pub fn pause(&self) -> TrackResult<()> {
+        let inner = self.inner.lock();
+
+        if let Some(handle) = inner.tracks.front() {
+            handle.pause()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Resume the track at the head of the queue.
+    pub fn resume(&self) -> TrackResult<()> {
+        let inner = self.inner.lock();
+
+        if let Some(handle) = inner.tracks.front() {
             handle.play()
         } else {
             Ok(())
         }
     }
 
+    /// Pause the track at the head of the queue, and hold the queue in place until
+    /// [`resume_queue`] is called.
+    ///
+    /// Unlike [`pause`], no other track will start while the queue is held: if the
+    /// current track ends or is skipped, or a track is added to an empty queue, the
+    /// next track stays paused.
+    ///
+    /// [`resume_queue`]: TrackQueue::resume_queue
+    /// [`pause`]: TrackQueue::pause
+    pub fn pause_queue(&self) -> TrackResult<()> {
+        let mut inner = self.inner.lock();
+        inner.paused = true;
+
+        if let Some(handle) = inner.tracks.front() {
+            handle.pause()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Release a queue held by [`pause_queue`], playing the track at its head.
+    ///
+    /// [`pause_queue`]: TrackQueue::pause_queue
+    pub fn resume_queue(&self) {
+        let mut inner = self.inner.lock();
+        inner.paused = false;
+
+        inner.play_head();
+    }
+
+    /// Returns whether the queue is held by [`pause_queue`].
+    ///
+    /// [`pause_queue`]: TrackQueue::pause_queue
+    #[must_use]
+    pub fn is_queue_paused(&self) -> bool {
+        self.inner.lock().paused
+    }
+
     /// Stop the currently playing track, and clears the queue.
     pub fn stop(&self) {
         let mut inner = self.inner.lock();
@@ -353,9 +1104,161 @@ impl TrackQueue {
 
         inner.tracks.iter().map(Queued::handle).collect()
     }
+
+    /// Returns a list of currently queued tracks, alongside any metadata of type `T`
+    /// attached to each entry.
+    ///
+    /// See [`current_queue`] and [`set_metadata`].
+    ///
+    /// [`current_queue`]: TrackQueue::current_queue
+    /// [`set_metadata`]: TrackQueue::set_metadata
+    #[must_use]
+    pub fn current_queue_with_metadata<T: Send + Sync + 'static>(
+        &self,
+    ) -> Vec<(TrackHandle, Option<Arc<T>>)> {
+        let inner = self.inner.lock();
+
+        inner
+            .tracks
+            .iter()
+            .map(|q| (q.handle(), q.metadata()))
+            .collect()
+    }
 }
 
 impl TrackQueueCore {
+    /// Rearranges upcoming tracks according to the queue's [`QueueOrder`].
+    ///
+    /// In round-robin mode, each track's *round* is the number of tracks from the
+    /// same requester ahead of it (including the current track). Upcoming tracks are
+    /// then stably sorted by round.
+    fn reorder(&mut self) {
+        if self.order != QueueOrder::RoundRobin || self.tracks.len() < 3 {
+            return;
+        }
+
+        let mut seen: HashMap<Option<u64>, usize> = HashMap::new();
+        let mut rounds = Vec::with_capacity(self.tracks.len());
+        for track in &self.tracks {
+            let count = seen.entry(track.requester).or_default();
+            rounds.push(*count);
+            *count += 1;
+        }
+
+        let mut upcoming: Vec<_> = self.tracks.drain(1..).zip(rounds.drain(1..)).collect();
+        upcoming.sort_by_key(|(_, round)| *round);
+        self.tracks
+            .extend(upcoming.into_iter().map(|(track, _)| track));
+    }
+
+    /// Returns the last track in the queue if it was added under `key`.
+    fn duplicate_of(&mut self, key: Option<u64>) -> Option<&mut Queued> {
+        let key = key?;
+        self.tracks.back_mut().filter(|q| q.key == Some(key))
+    }
+
+    /// Plays the track at the head of the queue, discarding any tracks which cannot
+    /// be played.
+    ///
+    /// Does nothing while the queue is paused.
+    fn play_head(&mut self) {
+        if self.paused {
+            return;
+        }
+
+        // Keep going until we find one track which works, or we run out.
+        while let Some(new) = self.tracks.front() {
+            if new.play().is_err() {
+                // Discard files which cannot be used for whatever reason.
+                warn!("Track in Queue couldn't be played...");
+                self.tracks.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Adds a replacement for a failed track at (or as close as possible to) its
+    /// original position.
+    fn insert_fallback(
+        &mut self,
+        index: usize,
+        input: Input,
+        failure: QueueError,
+        remote_lock: &Arc<Mutex<TrackQueueCore>>,
+    ) {
+        let Some(driver) = &self.driver else {
+            return;
+        };
+
+        let mut track = Track::from(input).pause();
+        attach_queue_events(&mut track, remote_lock, None);
+
+        let (handle, ctx) = track.into_context();
+        if driver.send(CoreMessage::AddTrack(ctx)).is_err() {
+            warn!("Driver stopped before queue fallback could be added.");
+            return;
+        }
+
+        info!("Replacing failed queue track at index {index}.");
+        self.tracks.insert(
+            index.min(self.tracks.len()),
+            Queued {
+                handle,
+                requester: failure.requester,
+                duration: None,
+                key: None,
+                votes: 1,
+                source: None,
+                metadata: failure.metadata,
+            },
+        );
+    }
+
+    /// Recreates a track which has just left the head of the queue, according to the
+    /// queue's [`QueueLoop`] mode.
+    ///
+    /// `ended` is `true` if the track played to completion, rather than being stopped.
+    fn repeat(&mut self, old: &Queued, ended: bool, remote_lock: &Arc<Mutex<TrackQueueCore>>) {
+        let index = match self.loop_mode {
+            QueueLoop::Track if ended => 0,
+            QueueLoop::Queue => self.tracks.len(),
+            _ => return,
+        };
+
+        let (Some(source), Some(driver)) = (&old.source, &self.driver) else {
+            return;
+        };
+
+        let mut track = source.track().pause();
+        attach_queue_events(
+            &mut track,
+            remote_lock,
+            TrackQueue::preload_time(old.duration),
+        );
+
+        let (handle, ctx) = track.into_context();
+        if driver.send(CoreMessage::AddTrack(ctx)).is_err() {
+            warn!("Driver stopped before queue track could be repeated.");
+            return;
+        }
+
+        info!("Repeating queue track at index {index}.");
+        self.tracks.insert(
+            index,
+            Queued {
+                handle,
+                requester: old.requester,
+                duration: old.duration,
+                key: old.key,
+                votes: 1,
+                source: Some(source.clone()),
+                metadata: old.metadata.clone(),
+            },
+        );
+        self.reorder();
+    }
+
     /// Skip to the next track in the queue, if it exists.
     fn stop_current(&self) -> TrackResult<()> {
         if let Some(handle) = self.tracks.front() {
@@ -366,16 +1269,430 @@ impl TrackQueueCore {
     }
 }
 
+/// Registers the event handlers which advance a queue and preload its next track.
+fn attach_queue_events(
+    track: &mut Track,
+    remote_lock: &Arc<Mutex<TrackQueueCore>>,
+    preload_time: Option<Duration>,
+) {
+    track.events.add_event(
+        EventData::new(
+            Event::Track(TrackEvent::End),
+            QueueHandler {
+                remote_lock: remote_lock.clone(),
+            },
+        ),
+        Duration::ZERO,
+    );
+
+    if let Some(time) = preload_time {
+        track.events.add_event(
+            EventData::new(
+                Event::Delayed(time),
+                SongPreloader {
+                    remote_lock: remote_lock.clone(),
+                },
+            ),
+            Duration::ZERO,
+        );
+    }
+}
+
+/// Parses `input` and decodes up to `length` of its opening audio, returning the input
+/// rewound to its start alongside the decoded sample.
+async fn sample_input(
+    input: Input,
+    length: Duration,
+) -> Result<(Input, AudioSample), EnqueueError> {
+    let input = input.make_playable_async(&CODEC_REGISTRY, &PROBE).await?;
+
+    tokio::task::spawn_blocking(move || {
+        let Input::Live(LiveInput::Parsed(mut parsed), lazy) = input else {
+            unreachable!("make_playable_async must return a parsed input.")
+        };
+
+        let sample = decode_sample(&mut parsed, length).map_err(EnqueueError::Decode)?;
+
+        let input = if rewind(&mut parsed) {
+            Input::Live(LiveInput::Parsed(parsed), lazy)
+        } else if let Some(lazy) = lazy {
+            Input::Lazy(lazy)
+        } else {
+            return Err(EnqueueError::Unrewindable);
+        };
+
+        Ok((input, sample))
+    })
+    .await
+    .map_err(|_| EnqueueError::Panicked)?
+}
+
+fn decode_sample(parsed: &mut Parsed, length: Duration) -> Result<AudioSample, SymphoniaError> {
+    let mut out = AudioSample::default();
+    let mut target_len = usize::MAX;
+
+    while out.samples.len() < target_len {
+        let packet = match parsed.format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof =>
+                break,
+            Err(e) => return Err(e),
+        };
+
+        if packet.track_id() != parsed.track_id {
+            continue;
+        }
+
+        let decoded = match parsed.decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(e),
+        };
+
+        let spec = *decoded.spec();
+        let mut buf = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        buf.copy_interleaved_ref(decoded);
+
+        if out.samples.is_empty() {
+            out.channels = spec.channels.count();
+            out.sample_rate = spec.rate;
+            target_len = (length.as_secs_f64() * f64::from(spec.rate)) as usize * out.channels;
+        }
+
+        out.samples.extend_from_slice(buf.samples());
+    }
+
+    out.samples.truncate(target_len);
+
+    Ok(out)
+}
+
+fn rewind(parsed: &mut Parsed) -> bool {
+    if !parsed.supports_backseek {
+        return false;
+    }
+
+    let rewound = parsed
+        .format
+        .seek(
+            SeekMode::Accurate,
+            SeekTo::TimeStamp {
+                ts: 0,
+                track_id: parsed.track_id,
+            },
+        )
+        .is_ok();
+    parsed.decoder.reset();
+
+    rewound
+}
+
 #[cfg(all(test, feature = "builtin-queue"))]
 mod tests {
+    use super::*;
     use crate::{
-        driver::Driver,
-        input::{File, HttpRequest},
-        tracks::PlayMode,
+        input::{AudioStream, AudioStreamError, AuxMetadata, Compose, File, HttpRequest},
         Config,
     };
     use reqwest::Client;
     use std::time::Duration;
+    use symphonia_core::io::MediaSource;
+
+    #[derive(Default)]
+    struct Dedup {
+        seen: Mutex<Vec<Vec<f32>>>,
+    }
+
+    #[async_trait]
+    impl EnqueueFilter for Dedup {
+        fn sample_length(&self) -> Duration {
+            Duration::from_secs(1)
+        }
+
+        async fn accept(&self, sample: &AudioSample) -> bool {
+            let mut seen = self.seen.lock();
+            if seen.contains(&sample.samples) {
+                false
+            } else {
+                seen.push(sample.samples.clone());
+                true
+            }
+        }
+    }
+
+    struct Unavailable;
+
+    #[async_trait]
+    impl Compose for Unavailable {
+        fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+            Err(AudioStreamError::Fail("no search results".into()))
+        }
+
+        async fn create_async(
+            &mut self,
+        ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+            Err(AudioStreamError::Unsupported)
+        }
+
+        fn should_create_async(&self) -> bool {
+            false
+        }
+    }
+
+    /// A local file which reports a fixed duration in its metadata.
+    struct Timed(File<&'static str>, Duration);
+
+    #[async_trait]
+    impl Compose for Timed {
+        fn create(&mut self) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+            self.0.create()
+        }
+
+        async fn create_async(
+            &mut self,
+        ) -> Result<AudioStream<Box<dyn MediaSource>>, AudioStreamError> {
+            self.0.create_async().await
+        }
+
+        fn should_create_async(&self) -> bool {
+            self.0.should_create_async()
+        }
+
+        async fn aux_metadata(&mut self) -> Result<AuxMetadata, AudioStreamError> {
+            Ok(AuxMetadata {
+                duration: Some(self.1),
+                ..Default::default()
+            })
+        }
+    }
+
+    struct Fallback {
+        tx: flume::Sender<usize>,
+    }
+
+    #[async_trait]
+    impl QueueErrorHandler for Fallback {
+        async fn on_error(&self, error: &QueueError) -> Option<Input> {
+            _ = self.tx.send(error.index);
+            Some(File::new("resources/ting.wav").into())
+        }
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn failed_track_is_replaced_by_fallback() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+        let (tx, rx) = flume::unbounded();
+        driver
+            .queue()
+            .set_error_handler(Some(Arc::new(Fallback { tx })));
+
+        let failed = driver
+            .enqueue_input(Input::Lazy(Box::new(Unavailable)))
+            .await;
+
+        let index = loop {
+            t_handle.skip(1).await;
+            if let Ok(index) = rx.try_recv() {
+                break index;
+            }
+        };
+        assert_eq!(index, 0);
+
+        // The replacement is inserted once the handler returns.
+        let replacement = loop {
+            if let Some(handle) = driver.queue().current() {
+                break handle;
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        };
+        assert_ne!(replacement.uuid(), failed.uuid());
+        assert_eq!(driver.queue().len(), 1);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn consecutive_duplicates_follow_policy() {
+        let (_t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+        let queue = TrackQueue::new();
+        let file = File::new("resources/ting.wav");
+
+        let first = queue
+            .add_source_keyed(file.clone().into(), "ting", &mut driver)
+            .await
+            .unwrap();
+        let second = queue
+            .add_source_keyed(file.clone().into(), "ting", &mut driver)
+            .await
+            .unwrap();
+        assert_ne!(first.uuid(), second.uuid());
+
+        queue.set_duplicate_policy(DuplicatePolicy::Reject);
+        let rejected = queue
+            .add_source_keyed(file.clone().into(), "ting", &mut driver)
+            .await;
+        assert!(
+            matches!(rejected, Err(EnqueueError::Duplicate(ref h)) if h.uuid() == second.uuid())
+        );
+
+        queue.set_duplicate_policy(DuplicatePolicy::Coalesce);
+        for _ in 0..2 {
+            let coalesced = queue
+                .add_source_keyed(file.clone().into(), "ting", &mut driver)
+                .await
+                .unwrap();
+            assert_eq!(coalesced.uuid(), second.uuid());
+        }
+        assert_eq!(queue.modify_queue(|q| q.back().map(Queued::votes)), Some(3));
+
+        // Only *consecutive* duplicates are caught.
+        queue.add_source(file.clone().into(), &mut driver).await;
+        queue
+            .add_source_keyed(file.into(), "ting", &mut driver)
+            .await
+            .unwrap();
+        assert_eq!(queue.len(), 4);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn round_robin_interleaves_requesters() {
+        let (_t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+        let queue = TrackQueue::new();
+        queue.set_order(QueueOrder::RoundRobin);
+
+        let file = File::new("resources/ting.wav");
+        let mut handles = vec![];
+        for requester in [1, 1, 1, 2, 2, 3] {
+            let track = Track::from(file.clone());
+            handles.push(
+                queue
+                    .add_with_requester(track, requester, &mut driver)
+                    .await,
+            );
+        }
+
+        // Head stays in place, and later requesters get a turn before requester 1's backlog.
+        let order: Vec<_> = queue
+            .current_queue()
+            .iter()
+            .map(TrackHandle::uuid)
+            .collect();
+        let expected: Vec<_> = [0, 3, 5, 1, 4, 2]
+            .into_iter()
+            .map(|i| handles[i].uuid())
+            .collect();
+        assert_eq!(order, expected);
+
+        let requesters =
+            queue.modify_queue(|q| q.iter().map(Queued::requester).collect::<Vec<_>>());
+        assert_eq!(requesters, [1, 2, 3, 1, 2, 1].map(Some).to_vec(),);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn eta_sums_remaining_durations_and_loops() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+        let queue = TrackQueue::new();
+
+        let timed = || {
+            Input::Lazy(Box::new(Timed(
+                File::new("resources/ting.wav"),
+                Duration::from_secs(10),
+            )))
+        };
+        queue.add(timed().into(), &mut driver).await;
+        queue
+            .add(
+                Track::from(timed()).loops(LoopState::Finite(1)),
+                &mut driver,
+            )
+            .await;
+        queue.add(timed().into(), &mut driver).await;
+        queue.add_with_preload(File::new("resources/ting.wav").into(), &mut driver, None);
+        queue.add(timed().into(), &mut driver).await;
+        // Tick in real time, so that the current track cannot end mid-test.
+        let ticker = t_handle.clone();
+        tokio::spawn(async move {
+            loop {
+                ticker.skip(1).await;
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+        });
+
+        assert_eq!(queue.eta(0).await, Some(Duration::ZERO));
+
+        let eta = queue.eta(2).await.unwrap();
+        assert!(eta <= Duration::from_secs(30) && eta > Duration::from_secs(29));
+
+        // Unknown durations block any later estimates.
+        assert!(queue.eta(3).await.is_some());
+        assert_eq!(queue.eta(4).await, None);
+        assert_eq!(queue.eta(5).await, None);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn entries_are_addressable_by_id() {
+        let (_t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+        let queue = TrackQueue::new();
+
+        let file = File::new("resources/ting.wav");
+        let tracks: Vec<_> = (0..3).map(|_| Track::from(file.clone())).collect();
+        let ids: Vec<_> = tracks.iter().map(Track::id).collect();
+        assert!(ids.windows(2).all(|w| w[0] < w[1]));
+
+        for track in tracks {
+            queue.add(track, &mut driver).await;
+        }
+
+        assert_eq!(queue.position(ids[1]), Some(1));
+        let removed = queue.dequeue_id(ids[1]).unwrap();
+        assert_eq!(removed.id(), ids[1]);
+        assert_eq!(queue.position(ids[1]), None);
+        assert_eq!(queue.position(ids[2]), Some(1));
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn metadata_follows_queue_entries() {
+        #[derive(Debug, PartialEq)]
+        struct Request {
+            user: u64,
+        }
+
+        let (_t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+        let queue = TrackQueue::new();
+
+        let file = File::new("resources/ting.wav");
+        let tracks: Vec<_> = (0..3).map(|_| Track::from(file.clone())).collect();
+        let ids: Vec<_> = tracks.iter().map(Track::id).collect();
+
+        for track in tracks {
+            queue.add(track, &mut driver).await;
+        }
+
+        assert!(queue.set_metadata(ids[1], Request { user: 1 }));
+        assert!(queue.set_metadata(ids[2], Request { user: 2 }));
+        assert!(queue.metadata::<u64>(ids[1]).is_none());
+
+        let removed = queue.dequeue_id(ids[1]).unwrap();
+        assert_eq!(removed.metadata::<Request>().unwrap().user, 1);
+        assert!(!queue.set_metadata(ids[1], Request { user: 3 }));
+
+        let users: Vec<_> = queue
+            .current_queue_with_metadata::<Request>()
+            .into_iter()
+            .map(|(_, meta)| meta.map(|m| m.user))
+            .collect();
+        assert_eq!(users, vec![None, Some(2)]);
+    }
 
     #[tokio::test]
     #[ntest::timeout(20_000)]
@@ -415,6 +1732,47 @@ mod tests {
         assert_eq!(h2a.await.unwrap().playing, PlayMode::Play);
     }
 
+    #[tokio::test]
+    #[ntest::timeout(15_000)]
+    async fn paused_queue_does_not_advance() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+
+        let file1 = File::new("resources/ting.wav");
+        let file2 = file1.clone();
+
+        let h1 = driver.enqueue_input(file1.into()).await;
+        let h2 = driver.enqueue_input(file2.into()).await;
+
+        t_handle
+            .ready_track(&h1, Some(Duration::from_millis(1)))
+            .await;
+
+        assert!(driver.queue().pause_queue().is_ok());
+        assert!(driver.queue().is_queue_paused());
+        assert!(driver.queue().skip().is_ok());
+
+        t_handle
+            .ready_track(&h2, Some(Duration::from_millis(1)))
+            .await;
+        t_handle.skip(1).await;
+
+        let h1a = h1.get_info();
+        let h2a = h2.get_info();
+        t_handle.tick(2);
+
+        // Track 1 is done, but track 2 is held back.
+        assert!(h1a.await.is_err());
+        assert_eq!(h2a.await.unwrap().playing, PlayMode::Pause);
+
+        driver.queue().resume_queue();
+        assert!(!driver.queue().is_queue_paused());
+
+        let h2a = h2.get_info();
+        t_handle.tick(2);
+        assert_eq!(h2a.await.unwrap().playing, PlayMode::Play);
+    }
+
     #[tokio::test]
     #[ntest::timeout(15_000)]
     async fn next_track_plays_on_skip() {
@@ -496,4 +1854,99 @@ mod tests {
         assert!(h1a.await.is_err());
         assert_eq!(h2a.await.unwrap().playing, PlayMode::Play);
     }
+
+    #[tokio::test]
+    #[ntest::timeout(15_000)]
+    async fn loop_modes_repeat_stored_sources() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+        let queue = TrackQueue::new();
+
+        let file = File::new("resources/ting.wav");
+        let a = queue.add_repeatable(file.clone(), &mut driver).await;
+        let b = queue.add_repeatable(file, &mut driver).await;
+        queue.set_loop_mode(QueueLoop::Queue);
+
+        t_handle
+            .ready_track(&a, Some(Duration::from_millis(1)))
+            .await;
+        assert!(queue.skip().is_ok());
+
+        // Skipped tracks return to the back of the queue.
+        while queue.current().map(|h| h.uuid()) != Some(b.uuid()) {
+            t_handle.skip(1).await;
+        }
+        let repeat = queue
+            .modify_queue(|q| q.back().map(Queued::handle))
+            .unwrap();
+        assert_eq!(queue.len(), 2);
+        assert_ne!(repeat.uuid(), a.uuid());
+
+        // Skipping leaves a looping track behind.
+        queue.set_loop_mode(QueueLoop::Track);
+        t_handle
+            .ready_track(&b, Some(Duration::from_millis(1)))
+            .await;
+        assert!(queue.skip().is_ok());
+
+        while queue.current().map(|h| h.uuid()) != Some(repeat.uuid()) {
+            t_handle.skip(1).await;
+        }
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(15_000)]
+    async fn repeats_are_rebuilt_with_track_config() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+        let queue = TrackQueue::new();
+
+        let a = queue
+            .add_repeatable_with(
+                File::new("resources/ting.wav"),
+                |track| track.volume(0.5),
+                &mut driver,
+            )
+            .await;
+        queue.set_loop_mode(QueueLoop::Queue);
+
+        t_handle
+            .ready_track(&a, Some(Duration::from_millis(1)))
+            .await;
+        assert!(queue.skip().is_ok());
+
+        while queue.current().map(|h| h.uuid()) == Some(a.uuid()) {
+            t_handle.skip(1).await;
+        }
+        let repeat = queue.current().unwrap();
+
+        let info = repeat.get_info();
+        t_handle.tick(2);
+        assert!((info.await.unwrap().volume - 0.5).abs() < f32::EPSILON);
+    }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn enqueue_filter_refuses_duplicates() {
+        let (_t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+        let queue = TrackQueue::new();
+        queue.set_enqueue_filter(Some(Arc::new(Dedup::default())));
+
+        let first = queue
+            .try_add(File::new("resources/ting.wav").into(), &mut driver)
+            .await;
+        let repost = queue
+            .try_add(File::new("resources/ting.wav").into(), &mut driver)
+            .await;
+        let other = queue
+            .try_add(File::new("resources/loop.wav").into(), &mut driver)
+            .await;
+
+        assert!(first.is_ok());
+        assert!(matches!(repost, Err(EnqueueError::Rejected)));
+        assert!(other.is_ok());
+        assert_eq!(queue.len(), 2);
+    }
 }
@@ -1,4 +1,5 @@
 use super::*;
+use std::time::Instant;
 
 /// State of an [`Track`] object, designed to be passed to event handlers
 /// and retrieved remotely via [`TrackHandle::get_info`].
@@ -13,6 +14,12 @@ pub struct TrackState {
     /// Current volume of this track.
     pub volume: f32,
 
+    /// Current stereo pan position of this track.
+    pub pan: f32,
+
+    /// Whether this track is muted, while still advancing its position.
+    pub muted: bool,
+
     /// Current playback position in the source.
     ///
     /// This is altered by loops and seeks, and represents this track's
@@ -20,27 +27,105 @@ pub struct TrackState {
     pub position: Duration,
 
     /// Total playback time, increasing monotonically.
+    ///
+    /// Unlike [`position`], this is cumulative across every loop iteration
+    /// and is unaffected by seeks.
+    ///
+    /// [`position`]: Self::position
     pub play_time: Duration,
 
     /// Remaining loops on this track.
     pub loops: LoopState,
 
+    /// Number of times this track has restarted from the beginning of its
+    /// input due to looping.
+    ///
+    /// This is incremented before each [`TrackEvent::Loop`] is fired, and is
+    /// unaffected by user changes to [`loops`].
+    ///
+    /// [`TrackEvent::Loop`]: crate::events::TrackEvent::Loop
+    /// [`loops`]: Self::loops
+    pub loop_count: u64,
+
     /// Whether this track has been made live, is being processed, or is
     /// currently uninitialised.
     pub ready: ReadyState,
+
+    /// Time taken by this track's most recent preparation, from entering
+    /// [`ReadyState::Preparing`] until it became [`ReadyState::Playable`].
+    ///
+    /// This covers creating and probing lazy inputs as well as seeks, and is
+    /// updated before each [`TrackEvent::Playable`] is fired.
+    ///
+    /// [`TrackEvent::Playable`]: crate::events::TrackEvent::Playable
+    pub preparation_time: Option<Duration>,
+
+    /// The moment at which [`position`] and [`play_time`] were last known to be accurate.
+    ///
+    /// States are updated once per tick of the mixer, and may spend some time in
+    /// transit before they are read: this allows [`position_now`] to account for
+    /// any time elapsed since.
+    ///
+    /// [`position`]: Self::position
+    /// [`play_time`]: Self::play_time
+    /// [`position_now`]: Self::position_now
+    pub captured_at: Option<Instant>,
 }
 
 impl TrackState {
     pub(crate) fn step_frame(&mut self) {
         self.position += TIMESTEP_LENGTH;
         self.play_time += TIMESTEP_LENGTH;
+        self.recapture();
+    }
+
+    pub(crate) fn recapture(&mut self) {
+        self.captured_at = Some(Instant::now());
+    }
+
+    /// Returns the time elapsed since this state was captured, if the track
+    /// has been advancing since.
+    fn elapsed(&self) -> Duration {
+        match self.captured_at {
+            Some(at) if self.playing.is_playing() && self.ready == ReadyState::Playable =>
+                at.elapsed(),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Estimates the current playback position in the source, interpolating
+    /// from [`position`] if the track is playing.
+    ///
+    /// This is intended for smooth progress displays. As the estimate cannot see
+    /// pauses, seeks, or loops which have occurred since capture, fresh states
+    /// should be fetched periodically via [`TrackHandle::get_info`].
+    ///
+    /// [`position`]: Self::position
+    /// [`TrackHandle::get_info`]: TrackHandle::get_info
+    #[must_use]
+    pub fn position_now(&self) -> Duration {
+        self.position + self.elapsed()
+    }
+
+    /// Estimates the current total playback time, interpolating from
+    /// [`play_time`] if the track is playing.
+    ///
+    /// [`play_time`]: Self::play_time
+    #[must_use]
+    pub fn play_time_now(&self) -> Duration {
+        self.play_time + self.elapsed()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{constants::test_data::YTDL_TARGET, driver::Driver, input::YoutubeDl, Config};
+    use crate::{
+        constants::test_data::{FILE_WAV_TARGET, YTDL_TARGET},
+        driver::Driver,
+        input::{File, YoutubeDl},
+        Config,
+    };
     use reqwest::Client;
 
     #[tokio::test]
@@ -61,4 +146,34 @@ mod tests {
         assert_eq!(state.position, Duration::from_millis(20));
         assert_eq!(state.play_time, Duration::from_millis(20));
     }
+
+    #[tokio::test]
+    #[ntest::timeout(10_000)]
+    async fn preparation_time_reported_when_playable() {
+        let (t_handle, config) = Config::test_cfg(true);
+        let mut driver = Driver::new(config.clone());
+
+        let handle = driver.play(Track::from(File::new(FILE_WAV_TARGET)));
+
+        let state = t_handle.ready_track(&handle, None).await;
+
+        assert_eq!(state.ready, ReadyState::Playable);
+        assert!(state.preparation_time.is_some());
+    }
+
+    #[test]
+    fn position_interpolated_only_while_playing() {
+        let mut state = TrackState {
+            position: Duration::from_secs(1),
+            ready: ReadyState::Playable,
+            captured_at: Instant::now().checked_sub(Duration::from_millis(500)),
+            ..Default::default()
+        };
+
+        assert!(state.position_now() >= Duration::from_millis(1500));
+        assert!(state.play_time_now() >= Duration::from_millis(500));
+
+        state.playing = PlayMode::Pause;
+        assert_eq!(state.position_now(), state.position);
+    }
 }
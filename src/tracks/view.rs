@@ -15,6 +15,12 @@ pub struct View<'a> {
     /// The current mixing volume of this track.
     pub volume: &'a mut f32,
 
+    /// The current stereo pan position of this track.
+    pub pan: &'a mut f32,
+
+    /// Whether this track is muted, while still advancing its position.
+    pub muted: &'a mut bool,
+
     /// In-stream metadata for this track, if it is fully readied.
     pub meta: Option<Metadata<'a>>,
 
@@ -27,4 +33,7 @@ pub struct View<'a> {
 
     /// The number of remaning loops on this track.
     pub loops: &'a mut LoopState,
+
+    /// The number of times this track has already looped.
+    pub loop_count: &'a u64,
 }
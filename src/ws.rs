@@ -1,6 +1,11 @@
-use crate::{error::JsonError, model::Event};
+use crate::{
+    error::JsonError,
+    events::context_data::{ClientFlags, ClientPlatform, ClientVideo},
+    model::Event,
+};
 
 use futures::{SinkExt, StreamExt, TryStreamExt};
+use serde::Deserialize;
 use tokio::{
     net::TcpStream,
     time::{timeout, Duration},
@@ -19,6 +24,60 @@ use url::Url;
 
 pub struct WsStream(WebSocketStream<MaybeTlsStream<TcpStream>>);
 
+/// Opcode of Discord's voice client connect message, describing a user's audio
+/// and video SSRCs.
+const CLIENT_VIDEO_OPCODE: u8 = 12;
+/// Opcode of Discord's voice client flags message.
+const CLIENT_FLAGS_OPCODE: u8 = 18;
+/// Opcode of Discord's voice client platform message.
+const CLIENT_PLATFORM_OPCODE: u8 = 20;
+
+/// A message received over the voice gateway.
+///
+/// This extends the events understood by `serenity-voice-model` with
+/// any additional payloads songbird parses itself.
+#[derive(Clone, Debug)]
+pub enum WsEvent {
+    Gateway(Event),
+    ClientFlags(ClientFlags),
+    ClientPlatform(ClientPlatform),
+    ClientVideo(ClientVideo),
+    /// A well-formed payload which songbird does not otherwise understand.
+    #[cfg(feature = "raw-gateway")]
+    Raw(serde_json::Value),
+}
+
+impl From<Event> for WsEvent {
+    fn from(value: Event) -> Self {
+        Self::Gateway(value)
+    }
+}
+
+#[derive(Deserialize)]
+struct ExtFrame {
+    op: u8,
+    d: ExtBody,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ExtBody {
+    Video(ClientVideo),
+    Flags(ClientFlags),
+    Platform(ClientPlatform),
+}
+
+impl ExtFrame {
+    fn into_event(self) -> Option<WsEvent> {
+        match (self.op, self.d) {
+            (CLIENT_FLAGS_OPCODE, ExtBody::Flags(f)) => Some(WsEvent::ClientFlags(f)),
+            (CLIENT_PLATFORM_OPCODE, ExtBody::Platform(p)) => Some(WsEvent::ClientPlatform(p)),
+            (CLIENT_VIDEO_OPCODE, ExtBody::Video(v)) => Some(WsEvent::ClientVideo(v)),
+            _ => None,
+        }
+    }
+}
+
 impl WsStream {
     #[instrument]
     pub(crate) async fn connect(url: Url) -> Result<Self> {
@@ -36,7 +95,7 @@ impl WsStream {
         Ok(Self(stream))
     }
 
-    pub(crate) async fn recv_json(&mut self) -> Result<Option<Event>> {
+    pub(crate) async fn recv_json(&mut self) -> Result<Option<WsEvent>> {
         const TIMEOUT: Duration = Duration::from_millis(500);
 
         let ws_message = match timeout(TIMEOUT, self.0.next()).await {
@@ -48,10 +107,14 @@ impl WsStream {
         convert_ws_message(ws_message)
     }
 
-    pub(crate) async fn recv_json_no_timeout(&mut self) -> Result<Option<Event>> {
+    pub(crate) async fn recv_json_no_timeout(&mut self) -> Result<Option<WsEvent>> {
         convert_ws_message(self.0.try_next().await?)
     }
 
+    pub(crate) async fn close(&mut self) -> Result<()> {
+        Ok(self.0.close(None).await?)
+    }
+
     pub(crate) async fn send_json(&mut self, value: &Event) -> Result<()> {
         Ok(crate::json::to_string(value)
             .map(Message::Text)
@@ -59,6 +122,11 @@ impl WsStream {
             .map(|m| self.0.send(m))?
             .await?)
     }
+
+    #[cfg(feature = "raw-gateway")]
+    pub(crate) async fn send_raw_json(&mut self, value: &serde_json::Value) -> Result<()> {
+        Ok(self.0.send(Message::Text(value.to_string())).await?)
+    }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -90,20 +158,9 @@ impl From<TungsteniteError> for Error {
 
 #[inline]
 #[allow(unused_unsafe)]
-pub(crate) fn convert_ws_message(message: Option<Message>) -> Result<Option<Event>> {
+pub(crate) fn convert_ws_message(message: Option<Message>) -> Result<Option<WsEvent>> {
     Ok(match message {
-        // SAFETY:
-        // simd-json::serde::from_str may leave an &mut str in a non-UTF state on failure.
-        // The below is safe as we have taken ownership of the inner `String`, and if
-        // failure occurs we forcibly re-validate its contents before logging.
-        Some(Message::Text(mut payload)) =>
-            (unsafe { crate::json::from_str(payload.as_mut_str()) })
-                .map_err(|e| {
-                    let safe_payload = String::from_utf8_lossy(payload.as_bytes());
-                    debug!("Unexpected JSON: {e}. Payload: {safe_payload}");
-                    e
-                })
-                .ok(),
+        Some(Message::Text(payload)) => convert_text_message(payload),
         Some(Message::Binary(bytes)) => {
             return Err(Error::UnexpectedBinaryMessage(bytes));
         },
@@ -114,3 +171,118 @@ pub(crate) fn convert_ws_message(message: Option<Message>) -> Result<Option<Even
         _ => None,
     })
 }
+
+#[inline]
+#[allow(unused_unsafe)]
+fn convert_text_message(mut payload: String) -> Option<WsEvent> {
+    // Voice gateway messages are infrequent, so keeping a second copy for
+    // payloads unknown to the model crate is cheap.
+    let mut ext_payload = payload.clone();
+    #[cfg(feature = "raw-gateway")]
+    let raw_payload = payload.clone();
+
+    // SAFETY:
+    // simd-json::serde::from_str may leave an &mut str in a non-UTF state on failure.
+    // The below is safe as we have taken ownership of both `String`s, and if
+    // failure occurs we forcibly re-validate their contents before logging.
+    match unsafe { crate::json::from_str::<Event>(payload.as_mut_str()) } {
+        // The model crate's `ClientConnect` omits rtx SSRCs, so reparse it ourselves.
+        Ok(evt @ Event::ClientConnect(_)) =>
+            convert_ext_message(&mut ext_payload).or_else(|| Some(evt.into())),
+        Ok(evt) => Some(evt.into()),
+        Err(e) => convert_ext_message(&mut ext_payload).or_else(|| {
+            #[cfg(feature = "raw-gateway")]
+            if let Ok(value) = serde_json::from_str(&raw_payload) {
+                return Some(WsEvent::Raw(value));
+            }
+
+            let safe_payload = String::from_utf8_lossy(payload.as_bytes());
+            debug!("Unexpected JSON: {e}. Payload: {safe_payload}");
+            None
+        }),
+    }
+}
+
+#[inline]
+#[allow(unused_unsafe)]
+fn convert_ext_message(payload: &mut String) -> Option<WsEvent> {
+    // SAFETY: as above, `payload` is never read as a `str` after a failed parse.
+    (unsafe { crate::json::from_str::<ExtFrame>(payload.as_mut_str()) })
+        .ok()
+        .and_then(ExtFrame::into_event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{events::context_data::Platform, model::id::UserId};
+
+    #[test]
+    fn parses_client_flags_and_platform() {
+        let flags = r#"{"op":18,"d":{"user_id":"1234","flags":3}}"#;
+        let platform = r#"{"op":20,"d":{"user_id":"1234","platform":1}}"#;
+
+        assert!(matches!(
+            convert_text_message(flags.into()),
+            Some(WsEvent::ClientFlags(ClientFlags {
+                user_id: UserId(1234),
+                flags: 3
+            }))
+        ));
+        assert!(matches!(
+            convert_text_message(platform.into()),
+            Some(WsEvent::ClientPlatform(ClientPlatform {
+                user_id: UserId(1234),
+                platform: Platform::Mobile
+            }))
+        ));
+    }
+
+    #[test]
+    fn mismatched_opcode_is_ignored() {
+        let bad = r#"{"op":20,"d":{"user_id":"1234","flags":3}}"#;
+
+        let evt = convert_text_message(bad.into());
+
+        #[cfg(not(feature = "raw-gateway"))]
+        assert!(evt.is_none());
+        #[cfg(feature = "raw-gateway")]
+        assert!(matches!(evt, Some(WsEvent::Raw(_))));
+    }
+
+    #[cfg(feature = "raw-gateway")]
+    #[test]
+    fn unknown_opcode_is_kept_raw() {
+        let unknown = r#"{"op":31,"d":{"sound_id":"42"}}"#;
+
+        let Some(WsEvent::Raw(value)) = convert_text_message(unknown.into()) else {
+            panic!("expected a raw payload");
+        };
+        assert_eq!(value["op"], 31);
+        assert_eq!(value["d"]["sound_id"], "42");
+    }
+
+    #[test]
+    fn parses_client_video_ssrcs() {
+        let video = r#"{"op":12,"d":{"user_id":"1234","audio_ssrc":1,"video_ssrc":2,"rtx_ssrc":3,"streams":[]}}"#;
+        let audio_only = r#"{"op":12,"d":{"user_id":"1234","audio_ssrc":1,"video_ssrc":0}}"#;
+
+        assert!(matches!(
+            convert_text_message(video.into()),
+            Some(WsEvent::ClientVideo(ClientVideo {
+                user_id: UserId(1234),
+                audio_ssrc: 1,
+                video_ssrc: 2,
+                rtx_ssrc: 3,
+            }))
+        ));
+        assert!(matches!(
+            convert_text_message(audio_only.into()),
+            Some(WsEvent::ClientVideo(ClientVideo {
+                video_ssrc: 0,
+                rtx_ssrc: 0,
+                ..
+            }))
+        ));
+    }
+}